@@ -1,6 +1,9 @@
 use cairo_vm::{
-    types::{layout_name::LayoutName, program::Program},
-    vm::runners::cairo_runner::CairoRunner,
+    types::{layout_name::LayoutName, program::Program, relocatable::MaybeRelocatable},
+    vm::{
+        runners::cairo_runner::CairoRunner,
+        vm_memory::{memory_backend::SparseMemoryBackend, memory_segments::MemorySegmentManager},
+    },
 };
 use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
 
@@ -30,6 +33,7 @@ fn build_many_runners(c: &mut Criterion) {
                 CairoRunner::new(
                     black_box(&program),
                     black_box(LayoutName::starknet_with_keccak),
+                    black_box(None),
                     black_box(false),
                     black_box(false),
                 )
@@ -45,12 +49,61 @@ fn load_program_data(c: &mut Criterion) {
     let program = Program::from_bytes(program.as_slice(), Some("main")).unwrap();
     c.bench_function("initialize", |b| {
         b.iter_batched(
-            || CairoRunner::new(&program, LayoutName::starknet_with_keccak, false, false).unwrap(),
+            || {
+                CairoRunner::new(
+                    &program,
+                    LayoutName::starknet_with_keccak,
+                    None,
+                    false,
+                    false,
+                )
+                .unwrap()
+            },
             |mut runner| _ = black_box(runner.initialize(false).unwrap()),
             BatchSize::SmallInput,
         )
     });
 }
 
-criterion_group!(runner, build_many_runners, load_program_data, parse_program);
+type NewSegments = fn() -> MemorySegmentManager;
+
+// Compares the default memory backend, whose inserts don't go through the `MemoryBackend` trait, with the sparse one
+fn load_memory_data(c: &mut Criterion) {
+    let data: Vec<MaybeRelocatable> = (0..100_000_usize).map(MaybeRelocatable::from).collect();
+    let mut group = c.benchmark_group("load memory data");
+    let backends: [(&str, NewSegments); 2] = [
+        ("vec backend", MemorySegmentManager::new),
+        ("sparse backend", || {
+            MemorySegmentManager::with_memory_backends(
+                Box::new(SparseMemoryBackend::new()),
+                Box::new(SparseMemoryBackend::new()),
+            )
+        }),
+    ];
+    for (name, new_segments) in backends {
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || {
+                    let mut segments = new_segments();
+                    let ptr = segments.add();
+                    (segments, ptr)
+                },
+                |(mut segments, ptr)| {
+                    _ = black_box(segments.load_data(ptr, black_box(&data)).unwrap());
+                    segments
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    runner,
+    build_many_runners,
+    load_program_data,
+    parse_program,
+    load_memory_data
+);
 criterion_main!(runner);
@@ -2,8 +2,8 @@ use core::hint::black_box;
 use iai_callgrind::main;
 
 use cairo_vm::{
-    types::{layout_name::LayoutName, program::Program},
-    vm::runners::cairo_runner::CairoRunner,
+    types::{layout_name::LayoutName, program::Program, relocatable::MaybeRelocatable},
+    vm::{runners::cairo_runner::CairoRunner, vm_memory::memory_segments::MemorySegmentManager},
 };
 
 use mimalloc::MiMalloc;
@@ -34,6 +34,7 @@ fn build_runner() {
     let runner = CairoRunner::new(
         black_box(&program),
         LayoutName::starknet_with_keccak,
+        None,
         false,
         false,
     )
@@ -47,7 +48,14 @@ fn build_runner_helper() -> CairoRunner {
     //Picked the biggest one at the time of writing
     let program = include_bytes!("../cairo_programs/benchmarks/keccak_integration_benchmark.json");
     let program = Program::from_bytes(program.as_slice(), Some("main")).unwrap();
-    CairoRunner::new(&program, LayoutName::starknet_with_keccak, false, false).unwrap()
+    CairoRunner::new(
+        &program,
+        LayoutName::starknet_with_keccak,
+        None,
+        false,
+        false,
+    )
+    .unwrap()
 }
 
 #[inline(never)]
@@ -56,7 +64,22 @@ fn load_program_data() {
     _ = black_box(runner.initialize(false).unwrap());
 }
 
+#[inline(never)]
+fn load_memory_data() {
+    let data = load_memory_data_helper();
+    let mut segments = MemorySegmentManager::new();
+    let ptr = segments.add();
+    _ = black_box(segments.load_data(ptr, black_box(&data)).unwrap());
+    core::mem::drop(black_box(segments));
+}
+
+#[export_name = "helper::load_memory_data"]
+#[inline(never)]
+fn load_memory_data_helper() -> Vec<MaybeRelocatable> {
+    (0..100_000_usize).map(MaybeRelocatable::from).collect()
+}
+
 main!(
     callgrind_args = "toggle-collect=helper::*,core::mem::drop";
-    functions = parse_program, build_runner, load_program_data
+    functions = parse_program, build_runner, load_program_data, load_memory_data
 );
@@ -3,22 +3,29 @@
 use bincode::enc::write::Writer;
 use cairo_vm::air_public_input::PublicInputError;
 use cairo_vm::cairo_run::{self, EncodeTraceError};
+use cairo_vm::fact_topology::{FactInfo, FactTopologyError};
 use cairo_vm::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor;
+use cairo_vm::hint_processor::hint_whitelist::HintWhitelist;
+use cairo_vm::program_analysis::ProgramAnalysis;
+use cairo_vm::program_hash::{compute_program_hash, ProgramHashError, ProgramHashFunction};
 #[cfg(feature = "with_tracer")]
 use cairo_vm::serde::deserialize_program::DebugInfo;
+use cairo_vm::types::builtin_name::BuiltinName;
+use cairo_vm::types::layout::CairoLayoutParams;
 use cairo_vm::types::layout_name::LayoutName;
+use cairo_vm::types::program::Program;
+use cairo_vm::vm::decoding::disassembler::Disassembly;
 use cairo_vm::vm::errors::cairo_run_errors::CairoRunError;
 use cairo_vm::vm::errors::trace_errors::TraceError;
 use cairo_vm::vm::errors::vm_errors::VirtualMachineError;
-use cairo_vm::vm::runners::cairo_pie::CairoPie;
-#[cfg(feature = "with_tracer")]
-use cairo_vm::vm::runners::cairo_runner::CairoRunner;
-use cairo_vm::vm::runners::cairo_runner::RunResources;
+use cairo_vm::vm::runners::cairo_pie::{BuiltinAdditionalData, CairoPie, SegmentInfo};
+use cairo_vm::vm::runners::cairo_runner::{CairoRunner, RunResources};
+use cairo_vm::vm::runners::profiler::Profile;
 #[cfg(feature = "with_tracer")]
 use cairo_vm_tracer::error::trace_data_errors::TraceDataError;
 #[cfg(feature = "with_tracer")]
 use cairo_vm_tracer::tracer::run_tracer;
-use clap::{Parser, ValueHint};
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand, ValueEnum, ValueHint};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
@@ -31,10 +38,19 @@ use mimalloc::MiMalloc;
 static ALLOC: MiMalloc = MiMalloc;
 
 #[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
+#[clap(
+    author,
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
-    #[clap(value_parser, value_hint=ValueHint::FilePath)]
-    filename: PathBuf,
+    #[clap(subcommand)]
+    command: Option<Command>,
+    #[clap(value_parser, value_hint=ValueHint::FilePath, required = true)]
+    filename: Option<PathBuf>,
     #[clap(long = "trace_file", value_parser)]
     trace_file: Option<PathBuf>,
     #[structopt(long = "print_output")]
@@ -45,6 +61,8 @@ struct Args {
     memory_file: Option<PathBuf>,
     #[clap(long = "layout", default_value = "plain", value_enum)]
     layout: LayoutName,
+    #[clap(long = "cairo_layout_params_file", required_if_eq("layout", "dynamic"))]
+    cairo_layout_params_file: Option<PathBuf>,
     #[structopt(long = "proof_mode")]
     proof_mode: bool,
     #[structopt(long = "secure_run")]
@@ -75,6 +93,96 @@ struct Args {
         conflicts_with_all = ["proof_mode", "air_private_input", "air_public_input"]
     )]
     run_from_cairo_pie: bool,
+    #[structopt(long = "print_fact")]
+    print_fact: bool,
+    #[clap(
+        long = "program_hash_function",
+        default_value = "pedersen",
+        value_enum,
+        requires = "print_fact"
+    )]
+    program_hash_function: ProgramHashFunction,
+    #[clap(long = "profile_output", value_parser, value_hint=ValueHint::FilePath)]
+    profile_output: Option<PathBuf>,
+    #[clap(long = "profile_format", default_value = "pprof", value_enum)]
+    profile_format: ProfileFormat,
+    #[clap(
+        long = "stream_trace_and_memory",
+        // The relocated trace & memory are not kept in memory when streaming them
+        conflicts_with_all = ["air_public_input", "air_private_input", "run_from_cairo_pie", "profile_output"]
+    )]
+    stream_trace_and_memory: bool,
+    /// Reject the program if any of its hints, or the references they use, are not allowed by
+    /// this whitelist (in the format of cairo-lang's hint whitelists)
+    #[clap(long = "hint_whitelist", value_parser, value_hint=ValueHint::FilePath)]
+    hint_whitelist: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print the hash of a compiled program, or of the program of a Cairo PIE
+    HashProgram(HashProgramArgs),
+    /// Print the contents of a Cairo PIE, or compare two of them
+    Pie(PieArgs),
+    /// Print the instructions of a compiled program, or of the program of a Cairo PIE, as Cairo
+    /// assembly
+    Disasm(DisasmArgs),
+    /// Analyze the control flow of a compiled program, or of the program of a Cairo PIE, and
+    /// report unreachable code, invalid jumps, misplaced hints, out-of-date references and
+    /// functions with an unknown ap change
+    Analyze(AnalyzeArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct HashProgramArgs {
+    #[clap(value_parser, value_hint=ValueHint::FilePath)]
+    filename: PathBuf,
+    #[clap(long = "hash_function", default_value = "pedersen", value_enum)]
+    hash_function: ProgramHashFunction,
+    #[clap(long = "entrypoint", default_value = "main")]
+    entrypoint: String,
+}
+
+#[derive(clap::Args, Debug)]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct PieArgs {
+    #[clap(subcommand)]
+    command: Option<PieCommand>,
+    #[clap(value_parser, value_hint=ValueHint::FilePath, required = true)]
+    filename: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum PieCommand {
+    /// List the program fields, segments, memory cells, execution resources and additional
+    /// data entries that differ between two Cairo PIEs
+    Diff {
+        #[clap(value_parser, value_hint=ValueHint::FilePath)]
+        left: PathBuf,
+        #[clap(value_parser, value_hint=ValueHint::FilePath)]
+        right: PathBuf,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct DisasmArgs {
+    #[clap(value_parser, value_hint=ValueHint::FilePath)]
+    filename: PathBuf,
+}
+
+#[derive(clap::Args, Debug)]
+struct AnalyzeArgs {
+    #[clap(value_parser, value_hint=ValueHint::FilePath)]
+    filename: PathBuf,
+    /// Print the control flow graph, functions and issues as JSON
+    #[clap(long = "json")]
+    json: bool,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ProfileFormat {
+    Pprof,
+    Folded,
 }
 
 #[derive(Debug, Error)]
@@ -94,6 +202,16 @@ enum Error {
     #[error(transparent)]
     PublicInput(#[from] PublicInputError),
     #[error(transparent)]
+    ProgramHash(#[from] ProgramHashError),
+    #[error(transparent)]
+    FactTopology(#[from] FactTopologyError),
+    #[error("The Cairo PIEs differ in {0} places")]
+    CairoPieDiff(usize),
+    #[error("The program analysis found {0} issues")]
+    ProgramAnalysis(usize),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
     #[cfg(feature = "with_tracer")]
     TraceDataError(#[from] TraceDataError),
 }
@@ -126,6 +244,13 @@ impl FileWriter {
         }
     }
 
+    fn create(path: &Path, capacity: usize) -> io::Result<Self> {
+        Ok(Self::new(io::BufWriter::with_capacity(
+            capacity,
+            std::fs::File::create(path)?,
+        )))
+    }
+
     fn flush(&mut self) -> io::Result<()> {
         self.buf_writer.flush()
     }
@@ -160,7 +285,31 @@ fn start_tracer(cairo_runner: &CairoRunner) -> Result<(), TraceDataError> {
 fn run(args: impl Iterator<Item = String>) -> Result<(), Error> {
     let args = Args::try_parse_from(args)?;
 
-    let trace_enabled = args.trace_file.is_some() || args.air_public_input.is_some();
+    let filename = match args.command {
+        Some(ref command) => return run_command(command, &mut io::stdout()),
+        None => args.filename.clone().ok_or_else(missing_filename)?,
+    };
+
+    let trace_enabled = args.trace_file.is_some()
+        || args.air_public_input.is_some()
+        || args.profile_output.is_some();
+
+    let cairo_layout_params = match args.cairo_layout_params_file {
+        Some(ref file) => Some(CairoLayoutParams::from_file(file)?),
+        None => None,
+    };
+
+    let hint_whitelist = match args.hint_whitelist {
+        Some(ref file) => Some(HintWhitelist::from_file(file)?),
+        None => None,
+    };
+    // The hint processor rejects the hints that are not whitelisted when compiling them
+    let with_hint_whitelist = move |mut hint_processor: BuiltinHintProcessor| {
+        if let Some(hint_whitelist) = hint_whitelist {
+            hint_processor.set_hint_whitelist(hint_whitelist);
+        }
+        hint_processor
+    };
 
     let cairo_run_config = cairo_run::CairoRunConfig {
         entrypoint: &args.entrypoint,
@@ -170,20 +319,55 @@ fn run(args: impl Iterator<Item = String>) -> Result<(), Error> {
         proof_mode: args.proof_mode,
         secure_run: args.secure_run,
         allow_missing_builtins: args.allow_missing_builtins,
+        dynamic_layout_params: cairo_layout_params,
         ..Default::default()
     };
 
+    // When streaming, the trace & memory files are written during the run instead of once it is over
+    let (mut trace_writer, mut memory_writer) = if args.stream_trace_and_memory {
+        (
+            args.trace_file
+                .as_deref()
+                .map(|path| FileWriter::create(path, 3 * 1024 * 1024))
+                .transpose()?,
+            args.memory_file
+                .as_deref()
+                .map(|path| FileWriter::create(path, 5 * 1024 * 1024))
+                .transpose()?,
+        )
+    } else {
+        (None, None)
+    };
+
     let mut cairo_runner = match {
-        if args.run_from_cairo_pie {
-            let pie = CairoPie::read_zip_file(&args.filename)?;
-            let mut hint_processor = BuiltinHintProcessor::new(
+        if args.stream_trace_and_memory {
+            let program_content = std::fs::read(&filename).map_err(Error::IO)?;
+            let mut hint_processor = with_hint_whitelist(BuiltinHintProcessor::new_empty());
+            Program::from_bytes(&program_content, Some(&args.entrypoint))
+                .map_err(CairoRunError::Program)
+                .and_then(|program| {
+                    cairo_run::cairo_run_program_with_writers(
+                        &program,
+                        &cairo_run_config,
+                        &mut hint_processor,
+                        trace_writer
+                            .as_mut()
+                            .map(|writer| writer as &mut dyn Writer),
+                        memory_writer
+                            .as_mut()
+                            .map(|writer| writer as &mut dyn Writer),
+                    )
+                })
+        } else if args.run_from_cairo_pie {
+            let pie = CairoPie::read_zip_file(&filename)?;
+            let mut hint_processor = with_hint_whitelist(BuiltinHintProcessor::new(
                 Default::default(),
                 RunResources::new(pie.execution_resources.n_steps),
-            );
+            ));
             cairo_run::cairo_run_pie(&pie, &cairo_run_config, &mut hint_processor)
         } else {
-            let program_content = std::fs::read(args.filename).map_err(Error::IO)?;
-            let mut hint_processor = BuiltinHintProcessor::new_empty();
+            let program_content = std::fs::read(&filename).map_err(Error::IO)?;
+            let mut hint_processor = with_hint_whitelist(BuiltinHintProcessor::new_empty());
             cairo_run::cairo_run(&program_content, &cairo_run_config, &mut hint_processor)
         }
     } {
@@ -193,6 +377,12 @@ fn run(args: impl Iterator<Item = String>) -> Result<(), Error> {
             return Err(Error::Runner(error));
         }
     };
+    if let Some(ref mut trace_writer) = trace_writer {
+        trace_writer.flush()?;
+    }
+    if let Some(ref mut memory_writer) = memory_writer {
+        memory_writer.flush()?;
+    }
 
     if args.print_output {
         let mut output_buffer = "Program Output:\n".to_string();
@@ -200,25 +390,23 @@ fn run(args: impl Iterator<Item = String>) -> Result<(), Error> {
         print!("{output_buffer}");
     }
 
-    if let Some(ref trace_path) = args.trace_file {
+    if args.print_fact {
+        print_fact_info(&cairo_runner, args.program_hash_function)?;
+    }
+
+    if let (Some(trace_path), false) = (&args.trace_file, args.stream_trace_and_memory) {
         let relocated_trace = cairo_runner
             .relocated_trace
             .as_ref()
             .ok_or(Error::Trace(TraceError::TraceNotRelocated))?;
 
-        let trace_file = std::fs::File::create(trace_path)?;
-        let mut trace_writer =
-            FileWriter::new(io::BufWriter::with_capacity(3 * 1024 * 1024, trace_file));
-
+        let mut trace_writer = FileWriter::create(trace_path, 3 * 1024 * 1024)?;
         cairo_run::write_encoded_trace(relocated_trace, &mut trace_writer)?;
         trace_writer.flush()?;
     }
 
-    if let Some(ref memory_path) = args.memory_file {
-        let memory_file = std::fs::File::create(memory_path)?;
-        let mut memory_writer =
-            FileWriter::new(io::BufWriter::with_capacity(5 * 1024 * 1024, memory_file));
-
+    if let (Some(memory_path), false) = (&args.memory_file, args.stream_trace_and_memory) {
+        let mut memory_writer = FileWriter::create(memory_path, 5 * 1024 * 1024)?;
         cairo_run::write_encoded_memory(&cairo_runner.relocated_memory, &mut memory_writer)?;
         memory_writer.flush()?;
     }
@@ -228,6 +416,14 @@ fn run(args: impl Iterator<Item = String>) -> Result<(), Error> {
         std::fs::write(file_path, json)?;
     }
 
+    if let Some(ref profile_path) = args.profile_output {
+        let profile = Profile::from_runner(&cairo_runner)?;
+        match args.profile_format {
+            ProfileFormat::Pprof => std::fs::write(profile_path, profile.to_pprof())?,
+            ProfileFormat::Folded => std::fs::write(profile_path, profile.to_folded())?,
+        }
+    }
+
     #[cfg(feature = "with_tracer")]
     if args.tracer {
         start_tracer(&cairo_runner)?;
@@ -269,6 +465,216 @@ fn run(args: impl Iterator<Item = String>) -> Result<(), Error> {
     Ok(())
 }
 
+// Prints the fact topology & fact of a finished run
+fn print_fact_info(
+    cairo_runner: &CairoRunner,
+    program_hash_function: ProgramHashFunction,
+) -> Result<(), Error> {
+    let fact_info = FactInfo::from_runner(cairo_runner, program_hash_function)?;
+    let fact: String = fact_info.fact.iter().fold(String::new(), |string, byte| {
+        string + &format!("{byte:02x}")
+    });
+    println!("Fact topology:");
+    println!(
+        "  tree_structure: {:?}",
+        fact_info.fact_topology.tree_structure
+    );
+    println!("  page_sizes: {:?}", fact_info.fact_topology.page_sizes);
+    println!("Fact: 0x{fact}");
+    Ok(())
+}
+
+// Zip files (and therefore Cairo PIEs) start with a local file header
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+// clap only accepts a missing filename along with a subcommand, so this error is only returned if
+// the arguments are misconfigured
+fn missing_filename() -> clap::Error {
+    Args::command().error(
+        ErrorKind::MissingRequiredArgument,
+        "the filename is required when no subcommand is given",
+    )
+}
+
+// Runs a subcommand, writing what it prints to `out`
+fn run_command(command: &Command, out: &mut dyn Write) -> Result<(), Error> {
+    match command {
+        Command::HashProgram(hash_program_args) => hash_program(hash_program_args, out),
+        Command::Pie(pie_args) => pie(pie_args, out),
+        Command::Disasm(disasm_args) => disasm(disasm_args, out),
+        Command::Analyze(analyze_args) => analyze(analyze_args, out),
+    }
+}
+
+// Prints the hash of the program, read from a compiled program or a Cairo PIE
+fn hash_program(args: &HashProgramArgs, out: &mut dyn Write) -> Result<(), Error> {
+    let file_content = std::fs::read(&args.filename)?;
+    let stripped_program = if file_content.starts_with(ZIP_MAGIC) {
+        CairoPie::read_zip_file(&args.filename)?.metadata.program
+    } else {
+        Program::from_bytes(&file_content, Some(&args.entrypoint))
+            .and_then(|program| program.get_stripped_program())
+            .map_err(CairoRunError::Program)?
+    };
+    let program_hash = compute_program_hash(&stripped_program, 0, args.hash_function)?;
+    writeln!(out, "{program_hash:#x}")?;
+    Ok(())
+}
+
+// Reads a compiled program, or the program of a Cairo PIE
+fn read_program(filename: &Path) -> Result<Program, Error> {
+    let file_content = std::fs::read(filename)?;
+    if file_content.starts_with(ZIP_MAGIC) {
+        Ok(Program::from_stripped_program(
+            &CairoPie::read_zip_file(filename)?.metadata.program,
+        ))
+    } else {
+        Ok(Program::from_bytes(&file_content, None).map_err(CairoRunError::Program)?)
+    }
+}
+
+// Prints the disassembly of a compiled program or of the program of a Cairo PIE
+fn disasm(args: &DisasmArgs, out: &mut dyn Write) -> Result<(), Error> {
+    let program = read_program(&args.filename)?;
+    write!(out, "{}", Disassembly::from_program(&program))?;
+    Ok(())
+}
+
+// Prints the analysis of a compiled program or of the program of a Cairo PIE, failing if it
+// reports any issues
+fn analyze(args: &AnalyzeArgs, out: &mut dyn Write) -> Result<(), Error> {
+    let analysis = ProgramAnalysis::from_program(&read_program(&args.filename)?);
+    if args.json {
+        writeln!(out, "{}", analysis.serialize_json()?)?;
+    } else {
+        write!(out, "{analysis}")?;
+    }
+    if analysis.issues.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::ProgramAnalysis(analysis.issues.len()))
+    }
+}
+
+// Prints the contents of a Cairo PIE, or the differences between two of them
+fn pie(args: &PieArgs, out: &mut dyn Write) -> Result<(), Error> {
+    match args.command {
+        Some(PieCommand::Diff {
+            ref left,
+            ref right,
+        }) => {
+            let diffs = CairoPie::read_zip_file(left)?.diff(&CairoPie::read_zip_file(right)?);
+            for diff in diffs.iter() {
+                writeln!(out, "{diff}")?;
+            }
+            if diffs.is_empty() {
+                Ok(())
+            } else {
+                Err(Error::CairoPieDiff(diffs.len()))
+            }
+        }
+        None => {
+            let filename = args.filename.as_ref().ok_or_else(missing_filename)?;
+            print_cairo_pie(&CairoPie::read_zip_file(filename)?, out)?;
+            Ok(())
+        }
+    }
+}
+
+fn print_cairo_pie(pie: &CairoPie, out: &mut dyn Write) -> io::Result<()> {
+    let metadata = &pie.metadata;
+    // Builtins are listed in the order of the program
+    let builtins: Vec<&BuiltinName> = metadata.program.builtins.iter().collect();
+
+    writeln!(out, "Program:")?;
+    writeln!(out, "  main: {}", metadata.program.main)?;
+    writeln!(
+        out,
+        "  builtins: [{}]",
+        builtins
+            .iter()
+            .map(|name| name.to_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    )?;
+    writeln!(out, "  data: {} words", metadata.program.data.len())?;
+
+    writeln!(out, "Segments:")?;
+    let mut print_segment =
+        |name: &str, segment: &SegmentInfo| writeln!(out, "  {name}: {segment}");
+    print_segment("program", &metadata.program_segment)?;
+    print_segment("execution", &metadata.execution_segment)?;
+    print_segment("ret_fp", &metadata.ret_fp_segment)?;
+    print_segment("ret_pc", &metadata.ret_pc_segment)?;
+    for name in builtins.iter() {
+        if let Some(segment) = metadata.builtin_segments.get(name) {
+            print_segment(name.to_str(), segment)?;
+        }
+    }
+    for (i, segment) in metadata.extra_segments.iter().enumerate() {
+        print_segment(&format!("extra[{i}]"), segment)?;
+    }
+
+    let resources = &pie.execution_resources;
+    writeln!(out, "Execution resources:")?;
+    writeln!(out, "  n_steps: {}", resources.n_steps)?;
+    writeln!(out, "  n_memory_holes: {}", resources.n_memory_holes)?;
+    writeln!(out, "  builtin_instance_counter:")?;
+    for name in builtins.iter() {
+        if let Some(counter) = resources.builtin_instance_counter.get(name) {
+            writeln!(out, "    {}: {counter}", name.to_str())?;
+        }
+    }
+
+    writeln!(out, "Additional data:")?;
+    for name in builtins.iter() {
+        match pie.additional_data.0.get(name) {
+            Some(BuiltinAdditionalData::Output(data)) => {
+                writeln!(out, "  {}:", name.to_str())?;
+                let mut pages: Vec<_> = data.pages.iter().collect();
+                pages.sort_by_key(|(page_id, _)| **page_id);
+                for (page_id, page) in pages {
+                    writeln!(
+                        out,
+                        "    page {page_id}: start {}, size {}",
+                        page.start, page.size
+                    )?;
+                }
+                let mut attributes: Vec<_> = data.attributes.iter().collect();
+                attributes.sort();
+                for (attribute, values) in attributes {
+                    writeln!(out, "    {attribute}: {values:?}")?;
+                }
+            }
+            Some(BuiltinAdditionalData::Hash(addresses)) => writeln!(
+                out,
+                "  {}: {} verified addresses",
+                name.to_str(),
+                addresses.len()
+            )?,
+            Some(BuiltinAdditionalData::Signature(signatures)) => {
+                writeln!(out, "  {}: {} signatures", name.to_str(), signatures.len())?
+            }
+            Some(BuiltinAdditionalData::None) | None => {}
+        }
+    }
+
+    if let Some(output_segment) = metadata.builtin_segments.get(&BuiltinName::output) {
+        writeln!(out, "Output:")?;
+        let mut output: Vec<_> = pie
+            .memory
+            .0
+            .iter()
+            .filter(|((segment_index, _), _)| *segment_index as isize == output_segment.index)
+            .collect();
+        output.sort_by_key(|(address, _)| *address);
+        for ((_, offset), value) in output {
+            writeln!(out, "  [{offset}] {value}")?;
+        }
+    }
+    Ok(())
+}
+
 fn main() -> Result<(), Error> {
     #[cfg(test)]
     return Ok(());
@@ -388,6 +794,107 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_run_dynamic_layout_without_params_file() {
+        let args = [
+            "cairo-vm-cli",
+            "../cairo_programs/proof_programs/fibonacci.json",
+            "--layout",
+            "dynamic",
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run(args), Err(Error::Cli(_)));
+    }
+
+    #[rstest]
+    #[case(["cairo-vm-cli", "../cairo_programs/proof_programs/fibonacci.json", "--layout", "dynamic", "--cairo_layout_params_file", "../vm/src/tests/cairo_layout_params_file.json"].as_slice())]
+    #[case(["cairo-vm-cli", "../cairo_programs/proof_programs/fibonacci.json", "--layout", "dynamic", "--cairo_layout_params_file", "../vm/src/tests/cairo_layout_params_file.json", "--proof_mode", "--trace_file", "/dev/null", "--air_public_input", "/dev/null"].as_slice())]
+    fn test_run_dynamic_layout(#[case] args: &[&str]) {
+        let args = args.iter().cloned().map(String::from);
+        assert_matches!(run(args), Ok(_));
+    }
+
+    #[rstest]
+    #[case(["cairo-vm-cli", "../cairo_programs/fibonacci.json", "--profile_output", "/dev/null"].as_slice())]
+    #[case(["cairo-vm-cli", "../cairo_programs/fibonacci.json", "--profile_output", "/dev/null", "--profile_format", "folded"].as_slice())]
+    #[case(["cairo-vm-cli", "../cairo_programs/fibonacci.json", "--profile_output", "/dev/null", "--trace_file", "/dev/null"].as_slice())]
+    fn test_run_with_profile_output(#[case] args: &[&str]) {
+        let args = args.iter().cloned().map(String::from);
+        assert_matches!(run(args), Ok(_));
+    }
+
+    #[rstest]
+    #[case(["cairo-vm-cli", "../cairo_programs/fibonacci.json", "--stream_trace_and_memory", "--trace_file", "/dev/null", "--memory_file", "/dev/null"].as_slice())]
+    #[case(["cairo-vm-cli", "../cairo_programs/proof_programs/fibonacci.json", "--stream_trace_and_memory", "--proof_mode", "--trace_file", "/dev/null", "--memory_file", "/dev/null", "--print_output"].as_slice())]
+    #[case(["cairo-vm-cli", "../cairo_programs/fibonacci.json", "--stream_trace_and_memory", "--memory_file", "/dev/null", "--cairo_pie_output", "/dev/null"].as_slice())]
+    fn test_run_stream_trace_and_memory(#[case] args: &[&str]) {
+        let args = args.iter().cloned().map(String::from);
+        assert_matches!(run(args), Ok(_));
+    }
+
+    #[rstest]
+    #[case(["cairo-vm-cli", "../cairo_programs/fibonacci.json", "--stream_trace_and_memory", "--proof_mode", "--air_public_input", "/dev/null"].as_slice())]
+    #[case(["cairo-vm-cli", "../cairo_programs/fibonacci.json", "--stream_trace_and_memory", "--profile_output", "/dev/null"].as_slice())]
+    fn test_run_stream_trace_and_memory_conflicts(#[case] args: &[&str]) {
+        let args = args.iter().cloned().map(String::from);
+        assert_matches!(run(args), Err(Error::Cli(_)));
+    }
+
+    #[test]
+    fn test_run_missing_params_file() {
+        let args = [
+            "cairo-vm-cli",
+            "../cairo_programs/proof_programs/fibonacci.json",
+            "--layout",
+            "dynamic",
+            "--cairo_layout_params_file",
+            "../missing/params.json",
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run(args), Err(Error::IO(_)));
+    }
+
+    #[test]
+    fn test_run_hint_whitelist() {
+        let whitelist_file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            whitelist_file.path(),
+            r#"{"allowed_reference_expressions_for_hint": [{"allowed_expressions": [], "hint_lines": ["memory[ap] = segments.add()"]}]}"#,
+        )
+        .unwrap();
+        // valid_program_a.json also has an "import math" hint
+        let args = [
+            "cairo-vm-cli",
+            "../cairo_programs/manually_compiled/valid_program_a.json",
+            "--hint_whitelist",
+            whitelist_file.path().to_str().unwrap(),
+        ]
+        .into_iter()
+        .map(String::from);
+        match run(args) {
+            Err(Error::Runner(CairoRunError::VmException(exception))) => assert_matches!(
+                exception.inner_exc,
+                VirtualMachineError::HintNotWhitelisted(code) if code.as_ref() == "import math"
+            ),
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_run_missing_hint_whitelist() {
+        let args = [
+            "cairo-vm-cli",
+            "../cairo_programs/fibonacci.json",
+            "--hint_whitelist",
+            "../missing/whitelist.json",
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run(args), Err(Error::IO(_)));
+    }
+
     #[test]
     fn test_run_missing_program() {
         let args = ["cairo-vm-cli", "../missing/program.json"]
@@ -407,6 +914,332 @@ mod tests {
         assert_matches!(run(args), Err(Error::Runner(_)));
     }
 
+    #[rstest]
+    #[case(["cairo-vm-cli", "../cairo_programs/bitwise_output.json", "--layout", "all_cairo", "--print_fact"].as_slice())]
+    #[case(["cairo-vm-cli", "../cairo_programs/bitwise_output.json", "--layout", "all_cairo", "--print_fact", "--program_hash_function", "poseidon"].as_slice())]
+    #[case(["cairo-vm-cli", "../cairo_programs/bitwise_output.json", "--layout", "all_cairo", "--print_fact", "--stream_trace_and_memory"].as_slice())]
+    fn test_run_print_fact(#[case] args: &[&str]) {
+        let args = args.iter().cloned().map(String::from);
+        assert_matches!(run(args), Ok(()));
+    }
+
+    #[test]
+    fn test_run_print_fact_without_output_builtin() {
+        let args = [
+            "cairo-vm-cli",
+            "../cairo_programs/fibonacci.json",
+            "--print_fact",
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(
+            run(args),
+            Err(Error::FactTopology(FactTopologyError::NoOutputBuiltin))
+        );
+    }
+
+    #[test]
+    fn test_run_program_hash_function_without_print_fact() {
+        let args = [
+            "cairo-vm-cli",
+            "../cairo_programs/fibonacci.json",
+            "--program_hash_function",
+            "poseidon",
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run(args), Err(Error::Cli(_)));
+    }
+
+    // Runs a subcommand, returning its result along with what it printed
+    fn run_command_output(args: &[&str]) -> (Result<(), Error>, String) {
+        let args = Args::try_parse_from(args).unwrap();
+        let mut output = Vec::new();
+        let result = run_command(args.command.as_ref().unwrap(), &mut output);
+        (result, String::from_utf8(output).unwrap())
+    }
+
+    #[rstest]
+    #[case(["cairo-vm-cli", "hash-program", "../cairo_programs/fibonacci.json"].as_slice())]
+    #[case(["cairo-vm-cli", "hash-program", "../cairo_programs/fibonacci.json", "--hash_function", "poseidon"].as_slice())]
+    fn test_hash_program(#[case] args: &[&str]) {
+        let (result, output) = run_command_output(args);
+        assert_matches!(result, Ok(()));
+        let hash = output.strip_suffix('\n').unwrap();
+        assert!(hash.starts_with("0x") && hash[2..].chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn test_hash_program_output() {
+        let (result, output) = run_command_output(&[
+            "cairo-vm-cli",
+            "hash-program",
+            "../cairo_programs/manually_compiled/valid_program_a.json",
+        ]);
+        assert_matches!(result, Ok(()));
+        assert_eq!(
+            output,
+            "0x9675db5eb4b1cd5a9f171c49913773309b721ab4e188cad88eef66905e622c\n"
+        );
+    }
+
+    #[test]
+    fn test_hash_program_from_cairo_pie() {
+        let pie_path = std::env::temp_dir().join("cairo_vm_cli_hash_program_pie.zip");
+        let args = [
+            "cairo-vm-cli",
+            "../cairo_programs/fibonacci.json",
+            "--cairo_pie_output",
+            pie_path.to_str().unwrap(),
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run(args), Ok(()));
+
+        // The program of the PIE has the same hash
+        let (result, pie_output) =
+            run_command_output(&["cairo-vm-cli", "hash-program", pie_path.to_str().unwrap()]);
+        assert_matches!(result, Ok(()));
+        let (_, program_output) = run_command_output(&[
+            "cairo-vm-cli",
+            "hash-program",
+            "../cairo_programs/fibonacci.json",
+        ]);
+        assert_eq!(pie_output, program_output);
+        std::fs::remove_file(pie_path).unwrap();
+    }
+
+    #[rstest]
+    #[case(["cairo-vm-cli", "hash-program"].as_slice())]
+    #[case(["cairo-vm-cli", "hash-program", "../cairo_programs/fibonacci.json", "--hash_function", "sha256"].as_slice())]
+    #[case(["cairo-vm-cli", "--print_output", "hash-program", "../cairo_programs/fibonacci.json"].as_slice())]
+    fn test_hash_program_invalid_args(#[case] args: &[&str]) {
+        let args = args.iter().cloned().map(String::from);
+        assert_matches!(run(args), Err(Error::Cli(_)));
+    }
+
+    #[test]
+    fn test_hash_program_no_main() {
+        let args = [
+            "cairo-vm-cli",
+            "hash-program",
+            "../cairo_programs/manually_compiled/no_main_program.json",
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run(args), Err(Error::Runner(_)));
+    }
+
+    fn write_output_pie(file_name: &str, output: &[usize]) -> PathBuf {
+        use cairo_vm::types::relocatable::MaybeRelocatable;
+        use cairo_vm::vm::runners::cairo_pie::{
+            CairoPieAdditionalData, CairoPieMemory, CairoPieMetadata, CairoPieVersion,
+            OutputBuiltinAdditionalData, StrippedProgram,
+        };
+        use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
+        use std::collections::HashMap;
+
+        let mut memory = vec![
+            ((0, 0), MaybeRelocatable::from(5)),
+            ((1, 0), MaybeRelocatable::from((2, 0))),
+        ];
+        memory.extend(
+            output
+                .iter()
+                .enumerate()
+                .map(|(offset, value)| ((2, offset), MaybeRelocatable::from(*value))),
+        );
+        let pie = CairoPie {
+            metadata: CairoPieMetadata {
+                program: StrippedProgram {
+                    data: vec![MaybeRelocatable::from(5)],
+                    builtins: vec![BuiltinName::output],
+                    main: 0,
+                    prime: (),
+                },
+                program_segment: SegmentInfo::from((0, 1)),
+                execution_segment: SegmentInfo::from((1, 1)),
+                ret_fp_segment: SegmentInfo::from((3, 0)),
+                ret_pc_segment: SegmentInfo::from((4, 0)),
+                builtin_segments: HashMap::from([(
+                    BuiltinName::output,
+                    SegmentInfo::from((2, output.len())),
+                )]),
+                extra_segments: vec![],
+            },
+            memory: CairoPieMemory(memory),
+            execution_resources: ExecutionResources {
+                n_steps: 1,
+                n_memory_holes: 0,
+                builtin_instance_counter: HashMap::from([(BuiltinName::output, output.len())]),
+            },
+            additional_data: CairoPieAdditionalData(HashMap::from([(
+                BuiltinName::output,
+                BuiltinAdditionalData::Output(OutputBuiltinAdditionalData {
+                    pages: HashMap::new(),
+                    attributes: HashMap::new(),
+                }),
+            )])),
+            version: CairoPieVersion { cairo_pie: () },
+        };
+        let pie_path = std::env::temp_dir().join(file_name);
+        pie.write_zip_file(&pie_path).unwrap();
+        pie_path
+    }
+
+    #[test]
+    fn test_pie() {
+        let pie_path = write_output_pie("cairo_vm_cli_pie.zip", &[7, 8]);
+        let (result, output) =
+            run_command_output(&["cairo-vm-cli", "pie", pie_path.to_str().unwrap()]);
+        assert_matches!(result, Ok(()));
+        assert_eq!(output, "Program:\n  main: 0\n  builtins: [output]\n  data: 1 words\nSegments:\n  program: index 0, size 1\n  execution: index 1, size 1\n  ret_fp: index 3, size 0\n  ret_pc: index 4, size 0\n  output: index 2, size 2\nExecution resources:\n  n_steps: 1\n  n_memory_holes: 0\n  builtin_instance_counter:\n    output: 2\nAdditional data:\n  output:\nOutput:\n  [0] 7\n  [1] 8\n");
+        std::fs::remove_file(pie_path).unwrap();
+    }
+
+    #[test]
+    fn test_pie_diff() {
+        let pie_path_a = write_output_pie("cairo_vm_cli_pie_diff_a.zip", &[7, 8]);
+        let pie_path_b = write_output_pie("cairo_vm_cli_pie_diff_b.zip", &[7, 9]);
+        let pie_diff = |left: &PathBuf, right: &PathBuf| {
+            run_command_output(&[
+                "cairo-vm-cli",
+                "pie",
+                "diff",
+                left.to_str().unwrap(),
+                right.to_str().unwrap(),
+            ])
+        };
+        let (result, output) = pie_diff(&pie_path_a, &pie_path_a);
+        assert_matches!(result, Ok(()));
+        assert_eq!(output, "");
+        let (result, output) = pie_diff(&pie_path_a, &pie_path_b);
+        assert_matches!(result, Err(Error::CairoPieDiff(1)));
+        assert_eq!(output, "memory[2:1]: 8 != 9\n");
+        std::fs::remove_file(pie_path_a).unwrap();
+        std::fs::remove_file(pie_path_b).unwrap();
+    }
+
+    #[rstest]
+    #[case(["cairo-vm-cli", "pie"].as_slice())]
+    #[case(["cairo-vm-cli", "pie", "diff", "a.zip"].as_slice())]
+    #[case(["cairo-vm-cli", "pie", "a.zip", "b.zip"].as_slice())]
+    fn test_pie_invalid_args(#[case] args: &[&str]) {
+        let args = args.iter().cloned().map(String::from);
+        assert_matches!(run(args), Err(Error::Cli(_)));
+    }
+
+    #[test]
+    fn test_pie_missing_file() {
+        let args = ["cairo-vm-cli", "pie", "../missing/pie.zip"]
+            .into_iter()
+            .map(String::from);
+        assert_matches!(run(args), Err(Error::IO(_)));
+    }
+
+    #[test]
+    fn test_disasm() {
+        let (result, output) = run_command_output(&[
+            "cairo-vm-cli",
+            "disasm",
+            "../cairo_programs/manually_compiled/valid_program_a.json",
+        ]);
+        assert_matches!(result, Ok(()));
+        assert_eq!(
+            output,
+            "__main__.main:\n    %{\n        memory[ap] = segments.add()\n    %}\n     0: [ap] = 1000, ap++\n     2: [ap] = 2000, ap++\n    %{\n        import math\n    %}\n     4: [ap] = [ap + -2] + [ap + -1], ap++\n     5: ret\n"
+        );
+    }
+
+    #[rstest]
+    #[case("../cairo_programs/fibonacci.json", "__main__.fib:\n")]
+    #[case(
+        "../cairo_programs/manually_compiled/no_main_program.json",
+        "     0: ret\n"
+    )]
+    fn test_disasm_contains(#[case] program: &str, #[case] expected_line: &str) {
+        let (result, output) = run_command_output(&["cairo-vm-cli", "disasm", program]);
+        assert_matches!(result, Ok(()));
+        assert!(output.contains(expected_line));
+    }
+
+    #[test]
+    fn test_disasm_cairo_pie() {
+        let pie_path = write_output_pie("cairo_vm_cli_disasm_pie.zip", &[7]);
+        let (result, output) =
+            run_command_output(&["cairo-vm-cli", "disasm", pie_path.to_str().unwrap()]);
+        assert_matches!(result, Ok(()));
+        assert_eq!(output, "main:\n     0: nop\n");
+        std::fs::remove_file(pie_path).unwrap();
+    }
+
+    #[test]
+    fn test_disasm_invalid_program() {
+        let args = [
+            "cairo-vm-cli",
+            "disasm",
+            "../cairo_programs/manually_compiled/invalid_even_length_hex.json",
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run(args), Err(Error::Runner(_)));
+    }
+
+    #[test]
+    fn test_analyze() {
+        let (result, output) = run_command_output(&[
+            "cairo-vm-cli",
+            "analyze",
+            "../cairo_programs/manually_compiled/valid_program_a.json",
+        ]);
+        assert_matches!(result, Ok(()));
+        assert_eq!(
+            output,
+            "Functions:\n  __main__.main (pc 0): ap change 3\nNo issues found\n"
+        );
+    }
+
+    #[test]
+    fn test_analyze_json() {
+        let (result, output) = run_command_output(&[
+            "cairo-vm-cli",
+            "analyze",
+            "../cairo_programs/manually_compiled/valid_program_a.json",
+            "--json",
+        ]);
+        assert_matches!(result, Ok(()));
+        let analysis: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(analysis["cfg"]["nodes"].as_array().unwrap().len(), 4);
+        assert_eq!(
+            analysis["functions"],
+            serde_json::json!([{"name": "__main__.main", "pc": 0, "ap_change": 3}])
+        );
+        assert_eq!(analysis["issues"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_analyze_cairo_pie_with_issues() {
+        // The program of the PIE runs past its end, so the ap change of main isn't known
+        let pie_path = write_output_pie("cairo_vm_cli_analyze_pie.zip", &[7]);
+        let (result, output) =
+            run_command_output(&["cairo-vm-cli", "analyze", pie_path.to_str().unwrap()]);
+        assert_matches!(result, Err(Error::ProgramAnalysis(1)));
+        assert_eq!(output, "Functions:\n  main (pc 0): unknown ap change\nIssues:\n  pc 1: the ap change of main is not statically known\n");
+        std::fs::remove_file(pie_path).unwrap();
+    }
+
+    #[test]
+    fn test_analyze_invalid_program() {
+        let args = [
+            "cairo-vm-cli",
+            "analyze",
+            "../cairo_programs/manually_compiled/invalid_even_length_hex.json",
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run(args), Err(Error::Runner(_)));
+    }
+
     //Since the functionality here is trivial, I just call the function
     //to fool Codecov.
     #[test]
@@ -0,0 +1,41 @@
+use cairo_vm::{
+    types::errors::program_errors::ProgramError,
+    vm::errors::{runner_errors::RunnerError, vm_errors::VirtualMachineError},
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to interact with the file system: {0}")]
+    IO(#[from] std::io::Error),
+    #[error("Invalid arguments for the {command} request: {error}")]
+    InvalidArguments {
+        command: String,
+        error: serde_json::Error,
+    },
+    #[error("Unsupported request: {0}")]
+    UnsupportedRequest(String),
+    #[error("No program has been launched")]
+    NotLaunched,
+    #[error("A program has already been launched")]
+    AlreadyLaunched,
+    #[error("The program is not running")]
+    NotRunning,
+    #[error("Unknown source")]
+    UnknownSource,
+    #[error(transparent)]
+    Program(#[from] ProgramError),
+    #[error(transparent)]
+    Runner(#[from] RunnerError),
+    #[error(transparent)]
+    VirtualMachine(#[from] VirtualMachineError),
+    #[cfg(feature = "cairo-1")]
+    #[error(transparent)]
+    Cairo1Run(#[from] cairo1_run::error::Error),
+    #[cfg(feature = "cairo-1")]
+    #[error("Failed to compile the Cairo 1 program: {0}")]
+    Cairo1Compilation(String),
+    #[cfg(feature = "cairo-1")]
+    #[error("Invalid program argument: {0}, expected a felt or a list of felts")]
+    InvalidProgramArgument(String),
+}
@@ -0,0 +1,349 @@
+#![deny(warnings)]
+#![forbid(unsafe_code)]
+//! Debug Adapter Protocol server for programs run by the cairo-vm.
+//! The client communicates with the adapter through its stdin & stdout
+
+use std::io;
+
+use server::Server;
+
+mod error;
+mod protocol;
+mod server;
+mod session;
+mod source_map;
+
+fn main() -> io::Result<()> {
+    Server::new(io::stdin().lock(), io::stdout()).run()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use std::io::Cursor;
+
+    /* Program used (data hand-assembled):
+    func foo(x) {
+        tempvar y = x + 1;
+        ret;
+    }
+
+    func main() {
+        tempvar a = 3;
+        foo(a);
+        ret;
+    }
+    */
+    pub(crate) const PROGRAM_JSON: &str = r#"
+    {
+        "prime": "0x800000000000011000000000000000000000000000000000000000000000001",
+        "attributes": [],
+        "builtins": [],
+        "data": [
+            "0x480680017fff8000",
+            "0x3",
+            "0x1104800180018000",
+            "0x3",
+            "0x208b7fff7fff7ffe",
+            "0x482680017ffd8000",
+            "0x1",
+            "0x208b7fff7fff7ffe"
+        ],
+        "debug_info": {
+            "instruction_locations": {
+                "0": {
+                    "accessible_scopes": ["__main__", "__main__.main"],
+                    "flow_tracking_data": {"ap_tracking": {"group": 0, "offset": 0}, "reference_ids": {}},
+                    "hints": [],
+                    "inst": {"end_col": 19, "end_line": 7, "input_file": {"filename": "src/call.cairo"}, "start_col": 5, "start_line": 7}
+                },
+                "2": {
+                    "accessible_scopes": ["__main__", "__main__.main"],
+                    "flow_tracking_data": {"ap_tracking": {"group": 0, "offset": 1}, "reference_ids": {}},
+                    "hints": [],
+                    "inst": {"end_col": 11, "end_line": 8, "input_file": {"filename": "src/call.cairo"}, "start_col": 5, "start_line": 8}
+                },
+                "4": {
+                    "accessible_scopes": ["__main__", "__main__.main"],
+                    "flow_tracking_data": {"ap_tracking": {"group": 1, "offset": 0}, "reference_ids": {}},
+                    "hints": [],
+                    "inst": {"end_col": 8, "end_line": 9, "input_file": {"filename": "src/call.cairo"}, "start_col": 5, "start_line": 9}
+                },
+                "5": {
+                    "accessible_scopes": ["__main__", "__main__.foo"],
+                    "flow_tracking_data": {"ap_tracking": {"group": 0, "offset": 0}, "reference_ids": {}},
+                    "hints": [],
+                    "inst": {"end_col": 23, "end_line": 2, "input_file": {"filename": "src/call.cairo"}, "start_col": 5, "start_line": 2}
+                },
+                "7": {
+                    "accessible_scopes": ["__main__", "__main__.foo"],
+                    "flow_tracking_data": {"ap_tracking": {"group": 0, "offset": 1}, "reference_ids": {}},
+                    "hints": [],
+                    "inst": {"end_col": 8, "end_line": 3, "input_file": {"filename": "src/call.cairo"}, "start_col": 5, "start_line": 3}
+                }
+            }
+        },
+        "hints": {},
+        "identifiers": {
+            "__main__.foo": {"decorators": [], "pc": 5, "type": "function"},
+            "__main__.main": {"decorators": [], "pc": 0, "type": "function"}
+        },
+        "main_scope": "__main__",
+        "reference_manager": {"references": []}
+    }"#;
+
+    #[derive(Debug, Deserialize)]
+    struct Message {
+        #[serde(rename = "type")]
+        type_: String,
+        command: Option<String>,
+        event: Option<String>,
+        success: Option<bool>,
+        message: Option<String>,
+        body: Option<Value>,
+    }
+
+    // Runs a debug session with the given (command, arguments) requests, returning the messages sent by the adapter
+    fn debug_session(requests: &[(&str, Value)]) -> Vec<Message> {
+        let dir = std::env::temp_dir().join(format!("cairo-vm-dap-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let program_path = dir.join("call.json");
+        std::fs::write(&program_path, PROGRAM_JSON).unwrap();
+
+        let mut input = String::new();
+        for (seq, (command, arguments)) in requests.iter().enumerate() {
+            let mut arguments = arguments.clone();
+            if *command == "launch" && arguments.get("program").is_none() {
+                arguments["program"] = json!(program_path);
+            }
+            let content = json!({
+                "seq": seq + 1,
+                "type": "request",
+                "command": command,
+                "arguments": arguments,
+            })
+            .to_string();
+            input.push_str(&format!(
+                "Content-Length: {}\r\n\r\n{content}",
+                content.len()
+            ));
+        }
+        let mut server = Server::new(Cursor::new(input), Vec::new());
+        server.run().unwrap();
+
+        let mut output = Cursor::new(server.output().clone());
+        let mut messages = Vec::new();
+        // The adapter's messages share the requests' framing
+        while let Some(message) = read_raw_message(&mut output) {
+            messages.push(message);
+        }
+        messages
+    }
+
+    fn read_raw_message(output: &mut Cursor<Vec<u8>>) -> Option<Message> {
+        let mut header = String::new();
+        io::BufRead::read_line(output, &mut header).ok()?;
+        let length: usize = header
+            .trim()
+            .strip_prefix("Content-Length: ")?
+            .parse()
+            .ok()?;
+        io::BufRead::read_line(output, &mut String::new()).ok()?;
+        let mut content = vec![0; length];
+        io::Read::read_exact(output, &mut content).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    fn launch_arguments(stop_on_entry: bool) -> Value {
+        json!({ "stopOnEntry": stop_on_entry, "cwd": "/home/user/project" })
+    }
+
+    fn breakpoints(lines: &[u32]) -> Value {
+        json!({
+            "source": { "path": "/home/user/project/src/call.cairo" },
+            "breakpoints": lines.iter().map(|line| json!({ "line": line })).collect::<Vec<_>>(),
+        })
+    }
+
+    fn events(messages: &[Message]) -> Vec<(String, Option<String>)> {
+        messages
+            .iter()
+            .filter(|message| message.type_ == "event")
+            .map(|message| {
+                (
+                    message.event.clone().unwrap(),
+                    message
+                        .body
+                        .as_ref()
+                        .and_then(|body| body["reason"].as_str())
+                        .map(String::from),
+                )
+            })
+            .collect()
+    }
+
+    fn response<'a>(messages: &'a [Message], command: &str, nth: usize) -> &'a Message {
+        messages
+            .iter()
+            .filter(|message| message.command.as_deref() == Some(command))
+            .nth(nth)
+            .unwrap()
+    }
+
+    fn event(name: &str, reason: Option<&str>) -> (String, Option<String>) {
+        (name.to_string(), reason.map(String::from))
+    }
+
+    #[test]
+    fn step_through_program() {
+        let messages = debug_session(&[
+            ("initialize", json!({ "adapterID": "cairo" })),
+            ("launch", launch_arguments(true)),
+            ("setBreakpoints", breakpoints(&[3, 5])),
+            ("configurationDone", Value::Null),
+            ("stackTrace", json!({ "threadId": 1 })),
+            ("next", json!({ "threadId": 1 })),
+            // Stepping over the call stops at the breakpoint within foo
+            ("next", json!({ "threadId": 1 })),
+            ("stackTrace", json!({ "threadId": 1 })),
+            ("scopes", json!({ "frameId": 1 })),
+            ("variables", json!({ "variablesReference": 1 })),
+            ("variables", json!({ "variablesReference": 2 })),
+            ("variables", json!({ "variablesReference": 4 })),
+            ("stepOut", json!({ "threadId": 1 })),
+            ("continue", json!({ "threadId": 1 })),
+            ("continue", json!({ "threadId": 1 })),
+            ("disconnect", Value::Null),
+        ]);
+        assert!(messages
+            .iter()
+            .filter(|message| message.type_ == "response")
+            .all(|message| message.success == Some(true)
+                || message.command.as_deref() == Some("continue")));
+        assert_eq!(
+            events(&messages),
+            vec![
+                event("initialized", None),
+                event("stopped", Some("entry")),
+                event("stopped", Some("step")),
+                event("stopped", Some("breakpoint")),
+                event("stopped", Some("step")),
+                event("exited", None),
+                event("terminated", None),
+            ]
+        );
+        assert_eq!(
+            response(&messages, "setBreakpoints", 0).body,
+            Some(json!({ "breakpoints": [
+                { "verified": true, "line": 3, "instructionReference": "7" },
+                { "verified": false, "line": 5, "message": "No instruction is located at this line" },
+            ]}))
+        );
+        let source = json!({ "name": "call.cairo", "path": "/home/user/project/src/call.cairo" });
+        assert_eq!(
+            response(&messages, "stackTrace", 0).body,
+            Some(json!({ "totalFrames": 1, "stackFrames": [
+                { "id": 0, "name": "__main__.main", "line": 7, "column": 5, "instructionPointerReference": "0:0", "source": source },
+            ]}))
+        );
+        assert_eq!(
+            response(&messages, "stackTrace", 1).body,
+            Some(json!({ "totalFrames": 2, "stackFrames": [
+                { "id": 0, "name": "__main__.foo", "line": 3, "column": 5, "instructionPointerReference": "0:7", "source": source },
+                { "id": 1, "name": "__main__.main", "line": 9, "column": 5, "instructionPointerReference": "0:4", "source": source },
+            ]}))
+        );
+        assert_eq!(
+            response(&messages, "scopes", 0).body,
+            Some(json!({ "scopes": [
+                { "name": "Registers", "variablesReference": 3, "expensive": false },
+                { "name": "Frame", "variablesReference": 4, "expensive": false },
+            ]}))
+        );
+        let variables = |nth: usize| {
+            response(&messages, "variables", nth).body.as_ref().unwrap()["variables"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|variable| {
+                    format!(
+                        "{} = {}",
+                        variable["name"].as_str().unwrap(),
+                        variable["value"].as_str().unwrap()
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(variables(0), ["pc = 0:7", "ap = 1:6", "fp = 1:5"]);
+        assert_eq!(variables(1), ["[fp-2] = 1:2", "[fp-1] = 0:4", "[fp+0] = 4"]);
+        assert_eq!(variables(2), ["[fp-2] = 2:0", "[fp-1] = 3:0", "[fp+0] = 3"]);
+        let continue_response = response(&messages, "continue", 1);
+        assert_eq!(continue_response.success, Some(false));
+        assert_eq!(
+            continue_response.message.as_deref(),
+            Some("The program is not running")
+        );
+    }
+
+    #[test]
+    fn step_in_and_breakpoint_at_entry() {
+        let messages = debug_session(&[
+            ("initialize", json!({ "adapterID": "cairo" })),
+            ("launch", launch_arguments(false)),
+            ("setBreakpoints", breakpoints(&[7])),
+            ("configurationDone", Value::Null),
+            ("stepIn", json!({ "threadId": 1 })),
+            ("stepIn", json!({ "threadId": 1 })),
+            ("stackTrace", json!({ "threadId": 1 })),
+            ("setBreakpoints", breakpoints(&[])),
+            ("continue", json!({ "threadId": 1 })),
+            ("stackTrace", json!({ "threadId": 1 })),
+        ]);
+        assert_eq!(
+            events(&messages),
+            vec![
+                event("initialized", None),
+                event("stopped", Some("breakpoint")),
+                event("stopped", Some("step")),
+                event("stopped", Some("step")),
+                event("exited", None),
+                event("terminated", None),
+            ]
+        );
+        let frames = &response(&messages, "stackTrace", 0).body.as_ref().unwrap()["stackFrames"];
+        assert_eq!(frames[0]["name"], "__main__.foo");
+        assert_eq!(frames[0]["line"], 2);
+        assert_eq!(
+            response(&messages, "stackTrace", 1).body,
+            Some(json!({ "totalFrames": 0, "stackFrames": [] }))
+        );
+    }
+
+    #[test]
+    fn invalid_requests() {
+        let messages = debug_session(&[
+            ("continue", json!({ "threadId": 1 })),
+            ("launch", json!({ "program": "missing.json" })),
+            ("launch", json!({ "stopOnEntry": "yes" })),
+            ("launch", launch_arguments(true)),
+            ("launch", launch_arguments(true)),
+            ("evaluate", json!({ "expression": "ap" })),
+            ("source", json!({ "sourceReference": 1 })),
+        ]);
+        let errors: Vec<_> = messages
+            .iter()
+            .filter(|message| message.success == Some(false))
+            .map(|message| message.message.as_deref().unwrap())
+            .collect();
+        assert_eq!(errors.len(), 6);
+        assert_eq!(errors[0], "No program has been launched");
+        assert!(errors[1].starts_with("Failed to interact with the file system"));
+        assert!(errors[2].starts_with("Invalid arguments for the launch request"));
+        assert_eq!(errors[3], "A program has already been launched");
+        assert_eq!(errors[4], "Unsupported request: evaluate");
+        // Cairo 0 sources are read by the client from disk
+        assert_eq!(errors[5], "Unknown source");
+    }
+}
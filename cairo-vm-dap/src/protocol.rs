@@ -0,0 +1,147 @@
+//! Message framing & envelopes of the Debug Adapter Protocol.
+//! See https://microsoft.github.io/debug-adapter-protocol/specification for the full specification
+
+use std::io::{self, BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const CONTENT_LENGTH_HEADER: &str = "Content-Length:";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Request {
+    pub seq: u64,
+    pub command: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum OutgoingMessage {
+    Response {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        command: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<Value>,
+    },
+    Event {
+        seq: u64,
+        event: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<Value>,
+    },
+}
+
+/// Reads the next message sent by the client.
+/// Returns None once the client closed the stream
+pub fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<Request>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        // Headers are terminated by an empty line
+        if header.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some(length) = header.strip_prefix(CONTENT_LENGTH_HEADER) {
+            content_length = Some(length.trim().parse::<usize>().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid content length: {length}"),
+                )
+            })?);
+        }
+    }
+    let mut content = vec![0; content_length.unwrap_or_default()];
+    input.read_exact(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn write_message<W: Write>(output: &mut W, message: &OutgoingMessage) -> io::Result<()> {
+    let content = serde_json::to_string(message)?;
+    write!(
+        output,
+        "{CONTENT_LENGTH_HEADER} {}\r\n\r\n{content}",
+        content.len()
+    )?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    #[test]
+    fn read_messages() {
+        let first = r#"{"seq":1,"type":"request","command":"initialize","arguments":{"adapterID":"cairo"}}"#;
+        let second = r#"{"seq":2,"type":"request","command":"threads"}"#;
+        let mut input = Cursor::new(format!(
+            "Content-Length: {}\r\n\r\n{first}Content-Length: {}\r\n\r\n{second}",
+            first.len(),
+            second.len()
+        ));
+        assert_eq!(
+            read_message(&mut input).unwrap(),
+            Some(Request {
+                seq: 1,
+                command: String::from("initialize"),
+                arguments: json!({"adapterID": "cairo"}),
+            })
+        );
+        assert_eq!(
+            read_message(&mut input).unwrap(),
+            Some(Request {
+                seq: 2,
+                command: String::from("threads"),
+                arguments: Value::Null,
+            })
+        );
+        assert_eq!(read_message(&mut input).unwrap(), None);
+    }
+
+    #[test]
+    fn read_invalid_content_length() {
+        let mut input = Cursor::new("Content-Length: ten\r\n\r\n{}");
+        assert_eq!(
+            read_message(&mut input).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn write_response() {
+        let mut output = Vec::new();
+        write_message(
+            &mut output,
+            &OutgoingMessage::Response {
+                seq: 3,
+                request_seq: 1,
+                success: true,
+                command: String::from("threads"),
+                message: None,
+                body: Some(json!({"threads": []})),
+            },
+        )
+        .unwrap();
+        let content = r#"{"type":"response","seq":3,"request_seq":1,"success":true,"command":"threads","body":{"threads":[]}}"#;
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!("Content-Length: {}\r\n\r\n{content}", content.len())
+        );
+    }
+}
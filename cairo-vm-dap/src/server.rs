@@ -0,0 +1,362 @@
+use std::io::{self, BufRead, Write};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::Error;
+use crate::protocol::{read_message, write_message, OutgoingMessage, Request};
+use crate::session::{DebugSession, LaunchArguments, ResumeMode, StackFrame, StopReason, Variable};
+
+// Cairo programs are single threaded
+const THREAD_ID: u64 = 1;
+
+// Each stack frame exposes two scopes, whose variables references are derived from the frame id
+const SCOPES_PER_FRAME: usize = 2;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SourceArguments {
+    path: Option<String>,
+    source_reference: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct SourceBreakpoint {
+    line: u32,
+}
+
+#[derive(Deserialize)]
+struct SetBreakpointsArguments {
+    source: SourceArguments,
+    #[serde(default)]
+    breakpoints: Vec<SourceBreakpoint>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScopesArguments {
+    frame_id: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VariablesArguments {
+    variables_reference: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SourceRequestArguments {
+    source: Option<SourceArguments>,
+    source_reference: Option<usize>,
+}
+
+// What to do once the response to a request has been sent
+enum Action {
+    None,
+    // Notify the client that it can send its configuration
+    Initialize,
+    // Start the execution once the client is configured
+    Start,
+    Resume(ResumeMode),
+    Terminate,
+    Disconnect,
+}
+
+/// Debug adapter serving a single debug session over the given streams
+pub struct Server<R, W> {
+    input: R,
+    output: W,
+    seq: u64,
+    session: Option<DebugSession>,
+    stop_on_entry: bool,
+}
+
+impl<R: BufRead, W: Write> Server<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Server {
+            input,
+            output,
+            seq: 0,
+            session: None,
+            stop_on_entry: false,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+
+    /// Handles the client's requests until it disconnects or closes the input stream
+    pub fn run(&mut self) -> io::Result<()> {
+        while let Some(request) = read_message(&mut self.input)? {
+            let action = match self.handle_request(&request) {
+                Ok((body, action)) => {
+                    self.respond(&request, Ok(body))?;
+                    action
+                }
+                Err(error) => {
+                    self.respond(&request, Err(error))?;
+                    Action::None
+                }
+            };
+            match action {
+                Action::None => {}
+                Action::Initialize => self.send_event("initialized", None)?,
+                Action::Start => self.start()?,
+                Action::Resume(mode) => self.resume(mode)?,
+                Action::Terminate => self.send_event("terminated", None)?,
+                Action::Disconnect => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&mut self, request: &Request) -> Result<(Option<Value>, Action), Error> {
+        match request.command.as_str() {
+            "initialize" => Ok((
+                Some(json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsTerminateRequest": true,
+                })),
+                Action::None,
+            )),
+            "launch" => {
+                if self.session.is_some() {
+                    return Err(Error::AlreadyLaunched);
+                }
+                let args: LaunchArguments = parse_arguments(request)?;
+                self.session = Some(DebugSession::launch(&args)?);
+                self.stop_on_entry = args.stop_on_entry;
+                Ok((None, Action::Initialize))
+            }
+            "setBreakpoints" => {
+                let args: SetBreakpointsArguments = parse_arguments(request)?;
+                let session = self.session.as_mut().ok_or(Error::NotLaunched)?;
+                let source = session
+                    .source_map()
+                    .find_source(args.source.path.as_deref(), args.source.source_reference);
+                let lines: Vec<_> = args.breakpoints.iter().map(|b| b.line).collect();
+                let pcs = match source {
+                    Some(source) => session.set_breakpoints(source, &lines),
+                    None => vec![None; lines.len()],
+                };
+                let breakpoints: Vec<_> = lines
+                    .iter()
+                    .zip(pcs)
+                    .map(|(line, pc)| match pc {
+                        Some(pc) => json!({
+                            "verified": true,
+                            "line": line,
+                            "instructionReference": pc.to_string(),
+                        }),
+                        None => json!({
+                            "verified": false,
+                            "line": line,
+                            "message": "No instruction is located at this line",
+                        }),
+                    })
+                    .collect();
+                Ok((Some(json!({ "breakpoints": breakpoints })), Action::None))
+            }
+            "configurationDone" => {
+                self.session.as_ref().ok_or(Error::NotLaunched)?;
+                Ok((None, Action::Start))
+            }
+            "threads" => Ok((
+                Some(json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })),
+                Action::None,
+            )),
+            "stackTrace" => {
+                let session = self.session.as_ref().ok_or(Error::NotLaunched)?;
+                let frames: Vec<_> = session
+                    .stack_frames()
+                    .iter()
+                    .enumerate()
+                    .map(|(id, frame)| self.stack_frame_json(id, frame))
+                    .collect();
+                Ok((
+                    Some(json!({ "totalFrames": frames.len(), "stackFrames": frames })),
+                    Action::None,
+                ))
+            }
+            "scopes" => {
+                let args: ScopesArguments = parse_arguments(request)?;
+                let reference = args.frame_id * SCOPES_PER_FRAME;
+                Ok((
+                    Some(json!({ "scopes": [
+                        { "name": "Registers", "variablesReference": reference + 1, "expensive": false },
+                        { "name": "Frame", "variablesReference": reference + 2, "expensive": false },
+                    ]})),
+                    Action::None,
+                ))
+            }
+            "variables" => {
+                let args: VariablesArguments = parse_arguments(request)?;
+                let session = self.session.as_ref().ok_or(Error::NotLaunched)?;
+                let scope = args.variables_reference.saturating_sub(1);
+                let frame = scope / SCOPES_PER_FRAME;
+                let variables = match scope % SCOPES_PER_FRAME {
+                    0 => session.registers(frame),
+                    _ => session.frame_cells(frame),
+                };
+                let variables: Vec<_> = variables
+                    .into_iter()
+                    .map(|Variable { name, value }| {
+                        json!({ "name": name, "value": value, "variablesReference": 0 })
+                    })
+                    .collect();
+                Ok((Some(json!({ "variables": variables })), Action::None))
+            }
+            "continue" => Ok((
+                Some(json!({ "allThreadsContinued": true })),
+                self.resume_action(ResumeMode::Continue)?,
+            )),
+            "next" => Ok((None, self.resume_action(ResumeMode::Next)?)),
+            "stepIn" => Ok((None, self.resume_action(ResumeMode::StepIn)?)),
+            "stepOut" => Ok((None, self.resume_action(ResumeMode::StepOut)?)),
+            // The program only runs while a request is being handled, so it is always paused by now
+            "pause" => Ok((None, Action::None)),
+            "source" => {
+                let args: SourceRequestArguments = parse_arguments(request)?;
+                let session = self.session.as_ref().ok_or(Error::NotLaunched)?;
+                let (path, source_reference) = match args.source {
+                    Some(source) => (source.path, source.source_reference),
+                    None => (None, args.source_reference),
+                };
+                let source_map = session.source_map();
+                let content = source_map
+                    .find_source(path.as_deref(), source_reference)
+                    .and_then(|source| source_map.sources()[source].content.clone())
+                    .ok_or(Error::UnknownSource)?;
+                Ok((Some(json!({ "content": content })), Action::None))
+            }
+            "terminate" => Ok((None, Action::Terminate)),
+            "disconnect" => Ok((None, Action::Disconnect)),
+            command => Err(Error::UnsupportedRequest(command.to_string())),
+        }
+    }
+
+    fn resume_action(&self, mode: ResumeMode) -> Result<Action, Error> {
+        let session = self.session.as_ref().ok_or(Error::NotLaunched)?;
+        if !session.is_running() {
+            return Err(Error::NotRunning);
+        }
+        Ok(Action::Resume(mode))
+    }
+
+    fn start(&mut self) -> io::Result<()> {
+        let Some(session) = self.session.as_ref() else {
+            return Ok(());
+        };
+        if self.stop_on_entry {
+            self.send_stopped("entry", None)
+        } else if session.at_breakpoint() {
+            self.send_stopped("breakpoint", None)
+        } else {
+            self.resume(ResumeMode::Continue)
+        }
+    }
+
+    fn resume(&mut self, mode: ResumeMode) -> io::Result<()> {
+        let Some(session) = self.session.as_mut() else {
+            return Ok(());
+        };
+        match session.resume(mode) {
+            StopReason::Step => self.send_stopped("step", None),
+            StopReason::Breakpoint => self.send_stopped("breakpoint", None),
+            StopReason::Exception(error) => {
+                self.send_event(
+                    "output",
+                    Some(json!({ "category": "stderr", "output": format!("{error}\n") })),
+                )?;
+                self.send_stopped("exception", Some(error))
+            }
+            StopReason::Finished => {
+                self.send_event("exited", Some(json!({ "exitCode": 0 })))?;
+                self.send_event("terminated", None)
+            }
+        }
+    }
+
+    fn stack_frame_json(&self, id: usize, frame: &StackFrame) -> Value {
+        let mut frame_json = json!({
+            "id": id,
+            "name": frame.function.as_deref().unwrap_or("<unknown>"),
+            "line": 0,
+            "column": 0,
+            "instructionPointerReference": frame.pc.to_string(),
+        });
+        let source_map = self.session.as_ref().map(|session| session.source_map());
+        if let (Some(location), Some(source_map)) = (frame.location, source_map) {
+            let source = &source_map.sources()[location.source];
+            frame_json["line"] = json!(location.line);
+            frame_json["column"] = json!(location.column);
+            frame_json["source"] = match source.path {
+                Some(ref path) => json!({ "name": source.name, "path": path }),
+                None => json!({ "name": source.name, "sourceReference": location.source + 1 }),
+            };
+        }
+        frame_json
+    }
+
+    fn respond(
+        &mut self,
+        request: &Request,
+        result: Result<Option<Value>, Error>,
+    ) -> io::Result<()> {
+        self.seq += 1;
+        let (success, message, body) = match result {
+            Ok(body) => (true, None, body),
+            Err(error) => (
+                false,
+                Some(error.to_string()),
+                Some(json!({ "error": { "id": 1, "format": error.to_string() } })),
+            ),
+        };
+        write_message(
+            &mut self.output,
+            &OutgoingMessage::Response {
+                seq: self.seq,
+                request_seq: request.seq,
+                success,
+                command: request.command.clone(),
+                message,
+                body,
+            },
+        )
+    }
+
+    fn send_stopped(&mut self, reason: &str, text: Option<String>) -> io::Result<()> {
+        let mut body = json!({
+            "reason": reason,
+            "threadId": THREAD_ID,
+            "allThreadsStopped": true,
+        });
+        if let Some(text) = text {
+            body["text"] = json!(text);
+        }
+        self.send_event("stopped", Some(body))
+    }
+
+    fn send_event(&mut self, event: &str, body: Option<Value>) -> io::Result<()> {
+        self.seq += 1;
+        write_message(
+            &mut self.output,
+            &OutgoingMessage::Event {
+                seq: self.seq,
+                event: event.to_string(),
+                body,
+            },
+        )
+    }
+}
+
+fn parse_arguments<T: DeserializeOwned>(request: &Request) -> Result<T, Error> {
+    serde_json::from_value(request.arguments.clone()).map_err(|error| Error::InvalidArguments {
+        command: request.command.clone(),
+        error,
+    })
+}
@@ -0,0 +1,442 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use cairo_vm::{
+    hint_processor::{
+        builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+        hint_processor_definition::HintProcessor,
+    },
+    types::{
+        layout::CairoLayoutParams, layout_name::LayoutName, program::Program,
+        relocatable::MaybeRelocatable, relocatable::Relocatable,
+    },
+    vm::runners::cairo_runner::CairoRunner,
+};
+use serde::Deserialize;
+#[cfg(feature = "cairo-1")]
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::source_map::{SourceLocation, SourceMap};
+
+// Limits used to keep the variables & stack trace views readable on corrupted frames
+const MAX_STACK_FRAMES: usize = 1000;
+const MAX_FRAME_CELLS: usize = 100;
+
+/// Arguments of the `launch` request
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchArguments {
+    /// Either a compiled Cairo 0 program, a Sierra program or a Cairo 1 file
+    pub program: PathBuf,
+    #[serde(default = "default_entrypoint")]
+    pub entrypoint: String,
+    #[serde(default = "default_layout")]
+    pub layout: LayoutName,
+    pub cairo_layout_params_file: Option<PathBuf>,
+    #[serde(default)]
+    pub allow_missing_builtins: bool,
+    #[serde(default)]
+    pub stop_on_entry: bool,
+    /// Arguments of a Cairo 1 program's main function, arrays are given as nested lists
+    #[cfg(feature = "cairo-1")]
+    #[serde(default)]
+    pub args: Vec<Value>,
+    /// Directory from which the relative source paths of a Cairo 0 program are resolved, defaults to the current one
+    pub cwd: Option<PathBuf>,
+}
+
+fn default_entrypoint() -> String {
+    String::from("main")
+}
+
+fn default_layout() -> LayoutName {
+    LayoutName::plain
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumeMode {
+    Continue,
+    /// Stops at the next source location, entering function calls
+    StepIn,
+    /// Stops at the next source location of the current function, or once it returns
+    Next,
+    /// Stops once the current function returns
+    StepOut,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    Step,
+    Breakpoint,
+    Finished,
+    Exception(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ExecutionState {
+    Running,
+    Finished,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackFrame {
+    pub pc: Relocatable,
+    pub fp: Relocatable,
+    pub function: Option<String>,
+    pub location: Option<SourceLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variable {
+    pub name: String,
+    pub value: String,
+}
+
+/// A program loaded into a runner, executed one step at a time
+pub struct DebugSession {
+    runner: CairoRunner,
+    hint_processor: Box<dyn HintProcessor>,
+    // Compiled once at launch, as the program is stepped through one instruction at a time
+    hint_data: Vec<Box<dyn Any>>,
+    end: Relocatable,
+    source_map: SourceMap,
+    // Breakpoint pcs, by source
+    breakpoints: HashMap<usize, Vec<usize>>,
+    state: ExecutionState,
+}
+
+impl DebugSession {
+    pub fn launch(args: &LaunchArguments) -> Result<DebugSession, Error> {
+        let cairo_layout_params = match args.cairo_layout_params_file {
+            Some(ref file) => Some(CairoLayoutParams::from_file(file)?),
+            None => None,
+        };
+        #[cfg(feature = "cairo-1")]
+        if args.program.extension().is_some_and(|ext| ext == "cairo") {
+            let sierra_program = cairo1::compile(&args.program)?;
+            return cairo1::launch(args, &sierra_program, cairo_layout_params);
+        }
+        let program_bytes = std::fs::read(&args.program)?;
+        let program = match Program::from_bytes(&program_bytes, Some(&args.entrypoint)) {
+            Ok(program) => program,
+            #[cfg(feature = "cairo-1")]
+            Err(error) => match serde_json::from_slice(&program_bytes) {
+                Ok(sierra_program) => {
+                    return cairo1::launch(args, &sierra_program, cairo_layout_params)
+                }
+                Err(_) => return Err(error.into()),
+            },
+            #[cfg(not(feature = "cairo-1"))]
+            Err(error) => return Err(error.into()),
+        };
+        let base_dir = match args.cwd {
+            Some(ref cwd) => cwd.clone(),
+            None => std::env::current_dir()?,
+        };
+        let mut runner =
+            CairoRunner::new(&program, args.layout, cairo_layout_params, false, false)?;
+        let end = runner.initialize(args.allow_missing_builtins)?;
+        DebugSession::new(
+            runner,
+            Box::new(BuiltinHintProcessor::new_empty()),
+            end,
+            SourceMap::from_program(&program, &base_dir),
+        )
+    }
+
+    pub fn new(
+        runner: CairoRunner,
+        mut hint_processor: Box<dyn HintProcessor>,
+        end: Relocatable,
+        source_map: SourceMap,
+    ) -> Result<Self, Error> {
+        let hint_data = runner.compile_hints(hint_processor.as_mut())?;
+        Ok(DebugSession {
+            runner,
+            hint_processor,
+            hint_data,
+            end,
+            source_map,
+            breakpoints: HashMap::new(),
+            state: ExecutionState::Running,
+        })
+    }
+
+    pub fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state == ExecutionState::Running
+    }
+
+    /// Replaces the breakpoints of a source.
+    /// Returns the pc of each breakpoint, or None if no instruction is located at its line
+    pub fn set_breakpoints(&mut self, source: usize, lines: &[u32]) -> Vec<Option<usize>> {
+        let pcs: Vec<_> = lines
+            .iter()
+            .map(|line| self.source_map.pc_for_line(source, *line))
+            .collect();
+        self.breakpoints
+            .insert(source, pcs.iter().flatten().copied().collect());
+        pcs
+    }
+
+    fn program_segment_index(&self) -> isize {
+        self.runner
+            .program_base
+            .map(|base| base.segment_index)
+            .unwrap_or_default()
+    }
+
+    // Returns the offset of the pc if it points to the program segment
+    fn program_offset(&self, pc: Relocatable) -> Option<usize> {
+        (pc.segment_index == self.program_segment_index()).then_some(pc.offset)
+    }
+
+    fn location_at(&self, pc: Relocatable) -> Option<SourceLocation> {
+        self.source_map.location_at(self.program_offset(pc)?)
+    }
+
+    /// Returns true if the execution is paused at a breakpoint
+    pub fn at_breakpoint(&self) -> bool {
+        self.is_breakpoint(self.runner.vm.get_pc())
+    }
+
+    fn is_breakpoint(&self, pc: Relocatable) -> bool {
+        self.program_offset(pc).is_some_and(|pc| {
+            self.breakpoints
+                .values()
+                .any(|breakpoints| breakpoints.contains(&pc))
+        })
+    }
+
+    // Returns the (pc, fp) the execution returns to once the frame at `fp` returns
+    fn return_point(&self, fp: Relocatable) -> Option<(Relocatable, Relocatable)> {
+        let vm = &self.runner.vm;
+        match (vm.get_maybe(&(fp - 1).ok()?), vm.get_maybe(&(fp - 2).ok()?)) {
+            (
+                Some(MaybeRelocatable::RelocatableValue(return_pc)),
+                Some(MaybeRelocatable::RelocatableValue(return_fp)),
+            ) => Some((return_pc, return_fp)),
+            _ => None,
+        }
+    }
+
+    /// Executes the program until it reaches the stopping point of the given mode, a breakpoint, or its end
+    pub fn resume(&mut self, mode: ResumeMode) -> StopReason {
+        let start_location = self.location_at(self.runner.vm.get_pc());
+        let start_fp = self.runner.vm.get_fp();
+        let return_point = self.return_point(start_fp);
+        loop {
+            match &self.state {
+                ExecutionState::Running => {}
+                ExecutionState::Finished => return StopReason::Finished,
+                ExecutionState::Failed(error) => return StopReason::Exception(error.clone()),
+            }
+            if self.runner.vm.get_pc() == self.end {
+                self.state = ExecutionState::Finished;
+                continue;
+            }
+            if let Err(error) = self
+                .runner
+                .step_with_hint_data(self.hint_processor.as_mut(), &self.hint_data)
+            {
+                self.state = ExecutionState::Failed(error.to_string());
+                continue;
+            }
+            let pc = self.runner.vm.get_pc();
+            let fp = self.runner.vm.get_fp();
+            if pc == self.end {
+                continue;
+            }
+            if self.is_breakpoint(pc) {
+                return StopReason::Breakpoint;
+            }
+            let returned = return_point == Some((pc, fp));
+            let location = self.location_at(pc);
+            let new_line = location.is_some() && location != start_location;
+            let stop = match mode {
+                ResumeMode::Continue => false,
+                ResumeMode::StepIn => new_line || returned,
+                ResumeMode::Next => (new_line && fp == start_fp) || returned,
+                ResumeMode::StepOut => returned,
+            };
+            if stop {
+                return StopReason::Step;
+            }
+        }
+    }
+
+    /// Returns the frames of the call stack, starting from the innermost one.
+    /// The stack is empty once the program finished
+    pub fn stack_frames(&self) -> Vec<StackFrame> {
+        let mut frames = Vec::new();
+        let (mut pc, mut fp) = (self.runner.vm.get_pc(), self.runner.vm.get_fp());
+        while let Some(offset) = self.program_offset(pc) {
+            frames.push(StackFrame {
+                pc,
+                fp,
+                function: self.source_map.function_at(offset).map(String::from),
+                location: self.source_map.location_at(offset),
+            });
+            if frames.len() == MAX_STACK_FRAMES {
+                break;
+            }
+            let Some((return_pc, return_fp)) = self.return_point(fp) else {
+                break;
+            };
+            (pc, fp) = (return_pc, return_fp);
+        }
+        frames
+    }
+
+    /// Returns the registers of the given frame, the ap is only known for the innermost one
+    pub fn registers(&self, frame: usize) -> Vec<Variable> {
+        let Some(stack_frame) = self.stack_frames().into_iter().nth(frame) else {
+            return Vec::new();
+        };
+        let mut registers = vec![Variable {
+            name: String::from("pc"),
+            value: stack_frame.pc.to_string(),
+        }];
+        if frame == 0 {
+            registers.push(Variable {
+                name: String::from("ap"),
+                value: self.runner.vm.get_ap().to_string(),
+            });
+        }
+        registers.push(Variable {
+            name: String::from("fp"),
+            value: stack_frame.fp.to_string(),
+        });
+        registers
+    }
+
+    /// Returns the memory cells of the given frame, from [fp - 2] up to the ap for the innermost frame,
+    /// or up to the start of the frame it called for the outer ones
+    pub fn frame_cells(&self, frame: usize) -> Vec<Variable> {
+        let stack_frames = self.stack_frames();
+        let Some(stack_frame) = stack_frames.get(frame) else {
+            return Vec::new();
+        };
+        let frame_end = match frame.checked_sub(1) {
+            None => Some(self.runner.vm.get_ap()),
+            Some(callee) => (stack_frames[callee].fp - 2).ok(),
+        };
+        let (Ok(start), Some(end)) = (stack_frame.fp - 2, frame_end) else {
+            return Vec::new();
+        };
+        if start.segment_index != end.segment_index {
+            return Vec::new();
+        }
+        (start.offset..end.offset.max(start.offset))
+            .take(MAX_FRAME_CELLS)
+            .map(|offset| {
+                let address = Relocatable::from((start.segment_index, offset));
+                let fp_offset = offset as isize - stack_frame.fp.offset as isize;
+                Variable {
+                    name: format!("[fp{fp_offset:+}]"),
+                    value: self
+                        .runner
+                        .vm
+                        .get_maybe(&address)
+                        .map(|value| value.to_string())
+                        .unwrap_or_else(|| String::from("<unknown>")),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "cairo-1")]
+mod cairo1 {
+    use super::*;
+    use cairo1_run::{prepare_cairo_run, Cairo1RunConfig, FuncArg, PreparedCairoRun};
+    use cairo_lang_compiler::{
+        compile_prepared_db, db::RootDatabase, project::setup_project, CompilerConfig,
+    };
+    use cairo_lang_sierra::program::Program as SierraProgram;
+    use cairo_vm::Felt252;
+    use std::path::Path;
+
+    pub(super) fn compile(path: &Path) -> Result<SierraProgram, Error> {
+        let compiler_config = CompilerConfig {
+            replace_ids: true,
+            ..CompilerConfig::default()
+        };
+        let mut db = RootDatabase::builder()
+            .detect_corelib()
+            .skip_auto_withdraw_gas()
+            .build()
+            .map_err(|e| Error::Cairo1Compilation(e.to_string()))?;
+        let main_crate_ids =
+            setup_project(&mut db, path).map_err(|e| Error::Cairo1Compilation(e.to_string()))?;
+        compile_prepared_db(&mut db, main_crate_ids, compiler_config)
+            .map_err(|e| Error::Cairo1Compilation(e.to_string()))
+    }
+
+    pub(super) fn launch(
+        args: &LaunchArguments,
+        sierra_program: &SierraProgram,
+        cairo_layout_params: Option<CairoLayoutParams>,
+    ) -> Result<DebugSession, Error> {
+        let func_args = args
+            .args
+            .iter()
+            .map(parse_func_arg)
+            .collect::<Result<Vec<_>, _>>()?;
+        let cairo_run_config = Cairo1RunConfig {
+            args: &func_args,
+            layout: args.layout,
+            dynamic_layout_params: cairo_layout_params,
+            ..Default::default()
+        };
+        let PreparedCairoRun {
+            runner,
+            hint_processor,
+            end,
+            debug_info,
+        } = prepare_cairo_run(sierra_program, &cairo_run_config)?;
+        let name = args
+            .program
+            .file_stem()
+            .map(|stem| format!("{}.sierra", stem.to_string_lossy()))
+            .unwrap_or_else(|| String::from("program.sierra"));
+        DebugSession::new(
+            runner,
+            Box::new(hint_processor),
+            end,
+            SourceMap::from_sierra(sierra_program, &debug_info, &name),
+        )
+    }
+
+    // Felts can be given either as numbers or as decimal strings, and arrays as lists of felts
+    fn parse_func_arg(arg: &Value) -> Result<FuncArg, Error> {
+        match arg {
+            Value::Array(elements) => elements
+                .iter()
+                .map(|element| parse_felt(element).ok_or_else(|| invalid_arg(arg)))
+                .collect::<Result<Vec<_>, _>>()
+                .map(FuncArg::Array),
+            _ => parse_felt(arg)
+                .map(FuncArg::Single)
+                .ok_or_else(|| invalid_arg(arg)),
+        }
+    }
+
+    fn parse_felt(value: &Value) -> Option<Felt252> {
+        match value {
+            Value::Number(number) => Felt252::from_dec_str(&number.to_string()).ok(),
+            Value::String(string) => Felt252::from_dec_str(string).ok(),
+            _ => None,
+        }
+    }
+
+    fn invalid_arg(arg: &Value) -> Error {
+        Error::InvalidProgramArgument(arg.to_string())
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+#[cfg(feature = "cairo-1")]
+use cairo1_run::Cairo1DebugInfo;
+#[cfg(feature = "cairo-1")]
+use cairo_lang_sierra::program::Program as SierraProgram;
+use cairo_vm::types::program::Program;
+
+/// A source file as presented to the client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Source {
+    pub name: String,
+    /// Path of the file on disk, None for sources generated by the adapter
+    pub path: Option<String>,
+    /// Content of generated sources, which the client fetches through the `source` request
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// Index of the source within the source map
+    pub source: usize,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// Maps the pcs of the program segment to source locations & function names
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    sources: Vec<Source>,
+    locations: HashMap<usize, SourceLocation>,
+    // (pc, name) of every function, sorted by pc
+    functions: Vec<(usize, String)>,
+}
+
+impl SourceMap {
+    /// Builds the source map of a Cairo 0 program out of its instruction locations.
+    /// Relative source paths are resolved from `base_dir`
+    pub fn from_program(program: &Program, base_dir: &Path) -> Self {
+        let mut source_map = SourceMap::default();
+        let mut source_indexes = HashMap::new();
+        for (pc, location) in program.iter_instruction_locations() {
+            let filename = &location.inst.input_file.filename;
+            let source = *source_indexes.entry(filename.clone()).or_insert_with(|| {
+                let path = base_dir.join(filename);
+                source_map.sources.push(Source {
+                    name: path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_else(|| filename.clone()),
+                    path: Some(path.to_string_lossy().to_string()),
+                    content: None,
+                });
+                source_map.sources.len() - 1
+            });
+            source_map.locations.insert(
+                pc,
+                SourceLocation {
+                    source,
+                    line: location.inst.start_line,
+                    column: location.inst.start_col,
+                },
+            );
+        }
+        source_map.functions = program
+            .iter_identifiers()
+            .filter(|(_, identifier)| identifier.type_.as_deref() == Some("function"))
+            .filter_map(|(name, identifier)| Some((identifier.pc?, name.to_string())))
+            .collect();
+        source_map.functions.sort();
+        source_map
+    }
+
+    /// Builds the source map of a Cairo 1 program, whose only source is the listing of its Sierra statements.
+    /// Each line of the listing holds the statement with the same index (starting from line 1)
+    #[cfg(feature = "cairo-1")]
+    pub fn from_sierra(
+        sierra_program: &SierraProgram,
+        debug_info: &Cairo1DebugInfo,
+        name: &str,
+    ) -> Self {
+        use std::fmt::Write;
+
+        let mut listing = String::new();
+        for statement in &sierra_program.statements {
+            // Writing to a String can't fail
+            let _ = writeln!(listing, "{statement};");
+        }
+        let mut source_map = SourceMap {
+            sources: vec![Source {
+                name: name.to_string(),
+                path: None,
+                content: Some(listing),
+            }],
+            ..Default::default()
+        };
+        for pc in debug_info.code_offset..debug_info.code_offset + debug_info.code_size {
+            if let Some(statement_idx) = debug_info.statement_at(pc) {
+                source_map.locations.insert(
+                    pc,
+                    SourceLocation {
+                        source: 0,
+                        line: statement_idx as u32 + 1,
+                        column: 1,
+                    },
+                );
+            }
+        }
+        source_map.functions = sierra_program
+            .funcs
+            .iter()
+            .filter_map(|function| {
+                Some((
+                    debug_info.pc_of_statement(function.entry_point.0)?,
+                    function.id.to_string(),
+                ))
+            })
+            .collect();
+        source_map.functions.sort();
+        source_map
+    }
+
+    pub fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    pub fn location_at(&self, pc: usize) -> Option<SourceLocation> {
+        self.locations.get(&pc).copied()
+    }
+
+    /// Returns the full name of the function containing the given pc
+    pub fn function_at(&self, pc: usize) -> Option<&str> {
+        self.functions
+            .iter()
+            .rev()
+            .find(|(function_pc, _)| *function_pc <= pc)
+            .map(|(_, name)| name.as_str())
+    }
+
+    /// Finds the source referred to by the client, either by path or by source reference.
+    /// Paths match if either of them is a suffix of the other, as Cairo 0 programs store the paths used at compile time
+    pub fn find_source(
+        &self,
+        path: Option<&str>,
+        source_reference: Option<usize>,
+    ) -> Option<usize> {
+        if let Some(source_reference) = source_reference.filter(|reference| *reference > 0) {
+            return Some(source_reference - 1).filter(|source| *source < self.sources.len());
+        }
+        let path = Path::new(path?);
+        self.sources.iter().position(|source| {
+            source.path.as_deref().is_some_and(|source_path| {
+                let source_path = Path::new(source_path);
+                source_path.ends_with(path) || path.ends_with(source_path)
+            })
+        })
+    }
+
+    /// Returns the first pc of the instructions located at the given line
+    pub fn pc_for_line(&self, source: usize, line: u32) -> Option<usize> {
+        self.locations
+            .iter()
+            .filter(|(_, location)| location.source == source && location.line == line)
+            .map(|(pc, _)| *pc)
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::PROGRAM_JSON;
+    use std::path::PathBuf;
+
+    fn source_map() -> SourceMap {
+        let program = Program::from_bytes(PROGRAM_JSON.as_bytes(), Some("main")).unwrap();
+        SourceMap::from_program(&program, Path::new("/home/user/project"))
+    }
+
+    #[test]
+    fn cairo_0_sources() {
+        assert_eq!(
+            source_map().sources(),
+            &[Source {
+                name: String::from("call.cairo"),
+                path: Some(String::from("/home/user/project/src/call.cairo")),
+                content: None
+            }]
+        );
+    }
+
+    #[test]
+    fn cairo_0_locations_and_functions() {
+        let source_map = source_map();
+        assert_eq!(
+            source_map.location_at(5),
+            Some(SourceLocation {
+                source: 0,
+                line: 2,
+                column: 5
+            })
+        );
+        assert_eq!(source_map.location_at(1), None);
+        assert_eq!(source_map.function_at(4), Some("__main__.main"));
+        assert_eq!(source_map.function_at(7), Some("__main__.foo"));
+        assert_eq!(source_map.pc_for_line(0, 8), Some(2));
+        assert_eq!(source_map.pc_for_line(0, 5), None);
+    }
+
+    #[test]
+    fn find_source_by_path() {
+        let source_map = source_map();
+        assert_eq!(
+            source_map.find_source(Some("/home/user/project/src/call.cairo"), None),
+            Some(0)
+        );
+        assert_eq!(
+            source_map.find_source(Some("src/call.cairo"), None),
+            Some(0)
+        );
+        assert_eq!(
+            source_map.find_source(Some("/home/user/other/call.cairo"), None),
+            None
+        );
+        assert_eq!(source_map.find_source(None, Some(1)), Some(0));
+        assert_eq!(source_map.find_source(None, Some(2)), None);
+        assert_eq!(source_map.find_source(None, None), None);
+    }
+
+    #[test]
+    fn relative_base_dir() {
+        let program = Program::from_bytes(PROGRAM_JSON.as_bytes(), Some("main")).unwrap();
+        let source_map = SourceMap::from_program(&program, &PathBuf::new());
+        assert_eq!(
+            source_map.sources()[0].path.as_deref(),
+            Some("src/call.cairo")
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use std::str::FromStr;
+
+use cairo_vm::types::relocatable::Relocatable;
+
+pub const HELP: &str = "Commands:
+  break [<pc> | <file>:<line>]    Adds a breakpoint, lists the breakpoints if no location is given (alias: b)
+  delete <pc> | <file>:<line>     Removes a breakpoint (alias: d)
+  step [<n>]                      Executes the next n instructions, defaults to 1 (alias: s)
+  continue                        Resumes the execution until the next breakpoint (alias: c)
+  registers                       Prints the pc, ap & fp registers (alias: r)
+  print ap | fp | pc | ids.<name> Prints a register or the value of a reference (alias: p)
+  memory <address> [<n>]          Prints n memory cells starting from the address, defaults to 1 (alias: x)
+                                  The address can be either <segment>:<offset> or ap/fp with an optional +/- offset
+  where                           Prints the source location of the current instruction (alias: w)
+  help                            Prints this message (alias: h)
+  quit                            Stops the execution (alias: q)";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BreakpointLocation {
+    Pc(usize),
+    Source { file: String, line: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    Ap,
+    Fp,
+    Pc,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Address {
+    Absolute(Relocatable),
+    // Register plus a signed offset, only ap & fp are allowed
+    Register(Register, isize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrintTarget {
+    Register(Register),
+    Ids(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Break(Option<BreakpointLocation>),
+    Delete(BreakpointLocation),
+    Step(usize),
+    Continue,
+    Registers,
+    Print(PrintTarget),
+    Memory(Address, usize),
+    Where,
+    Help,
+    Quit,
+}
+
+impl FromStr for BreakpointLocation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(pc) = s.parse() {
+            return Ok(BreakpointLocation::Pc(pc));
+        }
+        match s.rsplit_once(':') {
+            Some((file, line)) if !file.is_empty() => Ok(BreakpointLocation::Source {
+                file: file.to_string(),
+                line: line
+                    .parse()
+                    .map_err(|_| format!("Invalid line number: {line}"))?,
+            }),
+            _ => Err(format!(
+                "Invalid breakpoint location: {s}, expected <pc> or <file>:<line>"
+            )),
+        }
+    }
+}
+
+impl FromStr for Register {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ap" => Ok(Register::Ap),
+            "fp" => Ok(Register::Fp),
+            "pc" => Ok(Register::Pc),
+            _ => Err(format!("Unknown register: {s}")),
+        }
+    }
+}
+
+impl FromStr for Address {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid address: {s}");
+        if let Some((segment_index, offset)) = s.split_once(':') {
+            return Ok(Address::Absolute(Relocatable::from((
+                segment_index.parse::<isize>().map_err(|_| invalid())?,
+                offset.parse::<usize>().map_err(|_| invalid())?,
+            ))));
+        }
+        let (register, offset) = match s.find(['+', '-']) {
+            Some(idx) => (
+                &s[..idx],
+                s[idx..]
+                    .trim_start_matches('+')
+                    .parse::<isize>()
+                    .map_err(|_| invalid())?,
+            ),
+            None => (s, 0),
+        };
+        match register.trim().parse().map_err(|_| invalid())? {
+            Register::Pc => Err(format!("{s}: Only ap & fp based addresses are supported")),
+            register => Ok(Address::Register(register, offset)),
+        }
+    }
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let command = words.next().unwrap_or_default();
+        let args: Vec<&str> = words.collect();
+        let parse_count = |arg: Option<&&str>| -> Result<usize, String> {
+            arg.map_or(Ok(1), |n| {
+                n.parse().map_err(|_| format!("Invalid count: {n}"))
+            })
+        };
+        let command = match (command, args.as_slice()) {
+            ("break" | "b", []) => Command::Break(None),
+            ("break" | "b", [location]) => Command::Break(Some(location.parse()?)),
+            ("delete" | "d", [location]) => Command::Delete(location.parse()?),
+            ("step" | "s", [] | [_]) => Command::Step(parse_count(args.first())?),
+            ("continue" | "c", []) => Command::Continue,
+            ("registers" | "r", []) => Command::Registers,
+            ("print" | "p", [target]) => match target.strip_prefix("ids.") {
+                Some(name) if !name.is_empty() => {
+                    Command::Print(PrintTarget::Ids(name.to_string()))
+                }
+                _ => Command::Print(PrintTarget::Register(target.parse()?)),
+            },
+            ("memory" | "x", [address] | [address, _]) => {
+                Command::Memory(address.parse()?, parse_count(args.get(1))?)
+            }
+            ("where" | "w", []) => Command::Where,
+            ("help" | "h", []) => Command::Help,
+            ("quit" | "q", []) => Command::Quit,
+            ("", _) => return Err(String::from("Empty command")),
+            (
+                "break" | "b" | "delete" | "d" | "step" | "s" | "continue" | "c" | "registers"
+                | "r" | "print" | "p" | "memory" | "x" | "where" | "w" | "help" | "h" | "quit"
+                | "q",
+                _,
+            ) => return Err(format!("Wrong number of arguments for {command}")),
+            _ => {
+                return Err(format!(
+                    "Unknown command: {command}, type help to list the commands"
+                ))
+            }
+        };
+        Ok(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("break", Command::Break(None))]
+    #[case("b 12", Command::Break(Some(BreakpointLocation::Pc(12))))]
+    #[case(
+        "break src/main.cairo:4",
+        Command::Break(Some(BreakpointLocation::Source { file: String::from("src/main.cairo"), line: 4 }))
+    )]
+    #[case("d 3", Command::Delete(BreakpointLocation::Pc(3)))]
+    #[case("step", Command::Step(1))]
+    #[case("s 10", Command::Step(10))]
+    #[case("  c  ", Command::Continue)]
+    #[case("registers", Command::Registers)]
+    #[case("p ap", Command::Print(PrintTarget::Register(Register::Ap)))]
+    #[case("print ids.x", Command::Print(PrintTarget::Ids(String::from("x"))))]
+    #[case("x 1:4", Command::Memory(Address::Absolute(Relocatable::from((1, 4))), 1))]
+    #[case("memory fp-3 3", Command::Memory(Address::Register(Register::Fp, -3), 3))]
+    #[case("x ap+2", Command::Memory(Address::Register(Register::Ap, 2), 1))]
+    #[case("x ap", Command::Memory(Address::Register(Register::Ap, 0), 1))]
+    #[case("where", Command::Where)]
+    #[case("h", Command::Help)]
+    #[case("quit", Command::Quit)]
+    fn parse_command(#[case] input: &str, #[case] expected: Command) {
+        assert_eq!(input.parse::<Command>(), Ok(expected));
+    }
+
+    #[rstest]
+    #[case("", "Empty command")]
+    #[case("jump 4", "Unknown command: jump, type help to list the commands")]
+    #[case("c 4", "Wrong number of arguments for c")]
+    #[case(
+        "b main.cairo",
+        "Invalid breakpoint location: main.cairo, expected <pc> or <file>:<line>"
+    )]
+    #[case("b main.cairo:x", "Invalid line number: x")]
+    #[case("s two", "Invalid count: two")]
+    #[case("p sp", "Unknown register: sp")]
+    #[case("x pc+1", "pc+1: Only ap & fp based addresses are supported")]
+    #[case("x 1:-1", "Invalid address: 1:-1")]
+    fn parse_invalid_command(#[case] input: &str, #[case] expected_error: &str) {
+        assert_eq!(input.parse::<Command>(), Err(expected_error.to_string()));
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+
+use cairo_vm::{
+    hint_processor::hint_processor_utils::get_maybe_relocatable_from_reference,
+    types::relocatable::Relocatable,
+    vm::{errors::vm_errors::VirtualMachineError, vm_core::VirtualMachine},
+};
+
+use crate::command::{Address, BreakpointLocation, Command, PrintTarget, Register, HELP};
+use crate::program_info::ProgramInfo;
+
+const PROMPT: &str = "(cairo-vm-debug) ";
+
+// Breakpoints and source locations refer to the program segment
+const PROGRAM_SEGMENT_INDEX: isize = 0;
+
+enum Mode {
+    // Pause after executing this many instructions
+    Step(usize),
+    // Pause at the next breakpoint
+    Continue,
+}
+
+/// Interactive debugger driven by the VM's `pre_step_instruction` hook.
+/// Whenever the execution pauses, commands are read from `input` and their results written to `output`.
+pub struct Debugger<R, W> {
+    info: ProgramInfo,
+    breakpoints: BTreeSet<usize>,
+    mode: Mode,
+    quit: bool,
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Debugger<R, W> {
+    /// Creates a debugger which pauses before executing the first instruction
+    pub fn new(info: ProgramInfo, input: R, output: W) -> Self {
+        Debugger {
+            info,
+            breakpoints: BTreeSet::new(),
+            mode: Mode::Step(1),
+            quit: false,
+            input,
+            output,
+        }
+    }
+
+    /// Returns true if the user stopped the execution via the quit command
+    pub fn has_quit(&self) -> bool {
+        self.quit
+    }
+
+    #[cfg(test)]
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+
+    pub fn add_breakpoint(&mut self, location: &BreakpointLocation) -> Result<usize, String> {
+        let pc = self.resolve_location(location)?;
+        self.breakpoints.insert(pc);
+        Ok(pc)
+    }
+
+    fn resolve_location(&self, location: &BreakpointLocation) -> Result<usize, String> {
+        match location {
+            BreakpointLocation::Pc(pc) => Ok(*pc),
+            BreakpointLocation::Source { file, line } => self
+                .info
+                .pc_for_location(file, *line)
+                .ok_or_else(|| format!("No instruction found at {file}:{line}")),
+        }
+    }
+
+    /// To be called before each instruction is executed, pauses the execution if needed
+    pub fn pre_step(&mut self, vm: &VirtualMachine) -> Result<(), VirtualMachineError> {
+        let pc = vm.get_pc();
+        let at_breakpoint =
+            pc.segment_index == PROGRAM_SEGMENT_INDEX && self.breakpoints.contains(&pc.offset);
+        let pause = match &mut self.mode {
+            Mode::Step(remaining) => {
+                *remaining = remaining.saturating_sub(1);
+                *remaining == 0 || at_breakpoint
+            }
+            Mode::Continue => at_breakpoint,
+        };
+        if !pause {
+            return Ok(());
+        }
+        self.pause(vm, at_breakpoint)
+            .map_err(|e| VirtualMachineError::Other(e.into()))?;
+        if self.quit {
+            return Err(VirtualMachineError::Other(anyhow::anyhow!(
+                "Execution stopped by the debugger"
+            )));
+        }
+        Ok(())
+    }
+
+    fn pause(&mut self, vm: &VirtualMachine, at_breakpoint: bool) -> io::Result<()> {
+        if at_breakpoint {
+            writeln!(self.output, "Breakpoint hit at pc {}", vm.get_pc())?;
+        }
+        self.print_location(vm.get_pc())?;
+        self.repl(vm, true)
+    }
+
+    /// Lets the user inspect the VM's state once the program is no longer running
+    pub fn post_mortem(&mut self, vm: &VirtualMachine, message: &str) -> io::Result<()> {
+        writeln!(self.output, "{message}")?;
+        self.repl(vm, false)
+    }
+
+    fn repl(&mut self, vm: &VirtualMachine, running: bool) -> io::Result<()> {
+        loop {
+            write!(self.output, "{PROMPT}")?;
+            self.output.flush()?;
+            let mut line = String::new();
+            // Treat the end of the input as a quit command
+            if self.input.read_line(&mut line)? == 0 {
+                self.quit = true;
+                return Ok(());
+            }
+            match line.parse::<Command>() {
+                Err(error) => writeln!(self.output, "{error}")?,
+                Ok(Command::Step(n)) if running => {
+                    self.mode = Mode::Step(n.max(1));
+                    return Ok(());
+                }
+                Ok(Command::Continue) if running => {
+                    self.mode = Mode::Continue;
+                    return Ok(());
+                }
+                Ok(Command::Step(_) | Command::Continue) => {
+                    writeln!(self.output, "The program is not running")?
+                }
+                Ok(Command::Quit) => {
+                    self.quit = true;
+                    return Ok(());
+                }
+                Ok(command) => self.execute(vm, command)?,
+            }
+        }
+    }
+
+    fn execute(&mut self, vm: &VirtualMachine, command: Command) -> io::Result<()> {
+        match command {
+            Command::Break(None) => {
+                if self.breakpoints.is_empty() {
+                    writeln!(self.output, "No breakpoints set")?;
+                }
+                for pc in self.breakpoints.iter() {
+                    write!(self.output, "pc {pc}")?;
+                    match self.info.location_at(*pc) {
+                        Some(location) => writeln!(
+                            self.output,
+                            " at {}:{}",
+                            location.input_file.filename, location.start_line
+                        )?,
+                        None => writeln!(self.output)?,
+                    }
+                }
+            }
+            Command::Break(Some(location)) => match self.add_breakpoint(&location) {
+                Ok(pc) => writeln!(self.output, "Breakpoint set at pc {pc}")?,
+                Err(error) => writeln!(self.output, "{error}")?,
+            },
+            Command::Delete(location) => match self.resolve_location(&location) {
+                Ok(pc) if self.breakpoints.remove(&pc) => {
+                    writeln!(self.output, "Breakpoint removed from pc {pc}")?
+                }
+                Ok(pc) => writeln!(self.output, "No breakpoint set at pc {pc}")?,
+                Err(error) => writeln!(self.output, "{error}")?,
+            },
+            Command::Registers => {
+                writeln!(self.output, "pc = {}", vm.get_pc())?;
+                writeln!(self.output, "ap = {}", vm.get_ap())?;
+                writeln!(self.output, "fp = {}", vm.get_fp())?;
+            }
+            Command::Print(PrintTarget::Register(register)) => {
+                let value = match register {
+                    Register::Ap => vm.get_ap(),
+                    Register::Fp => vm.get_fp(),
+                    Register::Pc => vm.get_pc(),
+                };
+                writeln!(self.output, "{value}")?;
+            }
+            Command::Print(PrintTarget::Ids(name)) => self.print_reference(vm, &name)?,
+            Command::Memory(address, count) => match resolve_address(vm, &address) {
+                Some(address) => {
+                    for offset in 0..count {
+                        let Ok(addr) = address + offset else { break };
+                        match vm.get_maybe(&addr) {
+                            Some(value) => writeln!(self.output, "{addr}: {value}")?,
+                            None => writeln!(self.output, "{addr}: <unknown>")?,
+                        }
+                    }
+                }
+                None => writeln!(self.output, "Invalid address")?,
+            },
+            Command::Where => self.print_location(vm.get_pc())?,
+            Command::Help => writeln!(self.output, "{HELP}")?,
+            // Handled by the repl
+            Command::Step(_) | Command::Continue | Command::Quit => {}
+        }
+        Ok(())
+    }
+
+    fn print_reference(&mut self, vm: &VirtualMachine, name: &str) -> io::Result<()> {
+        let pc = vm.get_pc();
+        if pc.segment_index != PROGRAM_SEGMENT_INDEX {
+            return writeln!(self.output, "The current pc is not in the program segment");
+        }
+        let resolved = match self.info.resolve_reference(name, pc.offset) {
+            Ok(resolved) => resolved,
+            Err(error) => return writeln!(self.output, "{error}"),
+        };
+        match get_maybe_relocatable_from_reference(vm, &resolved.reference, &resolved.ap_tracking) {
+            Some(value) => write!(self.output, "ids.{name} = {value}")?,
+            None => write!(self.output, "ids.{name} = <unknown>")?,
+        }
+        match resolved.reference.cairo_type {
+            Some(cairo_type) => writeln!(self.output, " ({cairo_type})"),
+            None => writeln!(self.output),
+        }
+    }
+
+    fn print_location(&mut self, pc: Relocatable) -> io::Result<()> {
+        if pc.segment_index != PROGRAM_SEGMENT_INDEX {
+            return writeln!(self.output, "pc = {pc}");
+        }
+        let function = self.info.function_at(pc.offset).unwrap_or("<unknown>");
+        let Some(location) = self.info.location_at(pc.offset) else {
+            return writeln!(self.output, "pc = {pc} in {function}");
+        };
+        writeln!(
+            self.output,
+            "pc = {pc} in {function} at {}:{}:{}",
+            location.input_file.filename, location.start_line, location.start_col
+        )?;
+        // The source file may not be available if the program was compiled elsewhere
+        if let Some(source_line) = location.input_file.get_content().ok().and_then(|content| {
+            content
+                .lines()
+                .nth((location.start_line as usize).saturating_sub(1))
+                .map(String::from)
+        }) {
+            writeln!(self.output, "{:>5} | {source_line}", location.start_line)?;
+        }
+        Ok(())
+    }
+}
+
+fn resolve_address(vm: &VirtualMachine, address: &Address) -> Option<Relocatable> {
+    match address {
+        Address::Absolute(address) => Some(*address),
+        Address::Register(register, offset) => {
+            let base = match register {
+                Register::Ap => vm.get_ap(),
+                Register::Fp => vm.get_fp(),
+                Register::Pc => vm.get_pc(),
+            };
+            if *offset >= 0 {
+                (base + offset.unsigned_abs()).ok()
+            } else {
+                (base - offset.unsigned_abs()).ok()
+            }
+        }
+    }
+}
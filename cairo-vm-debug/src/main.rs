@@ -0,0 +1,275 @@
+#![deny(warnings)]
+#![forbid(unsafe_code)]
+use std::any::Any;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use cairo_vm::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor;
+use cairo_vm::hint_processor::hint_processor_definition::HintProcessor;
+use cairo_vm::types::errors::program_errors::ProgramError;
+use cairo_vm::types::exec_scope::ExecutionScopes;
+use cairo_vm::types::layout::CairoLayoutParams;
+use cairo_vm::types::layout_name::LayoutName;
+use cairo_vm::types::program::Program;
+use cairo_vm::vm::errors::cairo_run_errors::CairoRunError;
+use cairo_vm::vm::errors::runner_errors::RunnerError;
+use cairo_vm::vm::errors::vm_errors::VirtualMachineError;
+use cairo_vm::vm::errors::vm_exception::VmException;
+use cairo_vm::vm::hooks::Hooks;
+use cairo_vm::vm::runners::cairo_runner::CairoRunner;
+use cairo_vm::vm::vm_core::{VirtualMachine, VirtualMachineBuilder};
+use cairo_vm::Felt252;
+use clap::{Parser, ValueHint};
+use thiserror::Error;
+
+use command::BreakpointLocation;
+use debugger::Debugger;
+use program_info::ProgramInfo;
+
+mod command;
+mod debugger;
+mod program_info;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    #[clap(value_parser, value_hint=ValueHint::FilePath)]
+    filename: PathBuf,
+    #[structopt(long = "entrypoint", default_value = "main")]
+    entrypoint: String,
+    #[clap(long = "layout", default_value = "plain", value_enum)]
+    layout: LayoutName,
+    #[clap(long = "cairo_layout_params_file", required_if_eq("layout", "dynamic"))]
+    cairo_layout_params_file: Option<PathBuf>,
+    #[structopt(long = "allow_missing_builtins")]
+    allow_missing_builtins: Option<bool>,
+    /// Breakpoints to set before starting the execution, either as <pc> or <file>:<line>
+    #[clap(long = "break")]
+    breakpoints: Vec<BreakpointLocation>,
+}
+
+#[derive(Debug, Error)]
+enum Error {
+    #[error("Invalid arguments")]
+    Cli(#[from] clap::Error),
+    #[error("Failed to interact with the file system")]
+    IO(#[from] std::io::Error),
+    #[error(transparent)]
+    Program(#[from] ProgramError),
+    #[error("Failed to read the program's debug information: {0}")]
+    ProgramInfo(#[from] serde_json::Error),
+    #[error(transparent)]
+    Runner(#[from] RunnerError),
+    #[error("The cairo program execution failed")]
+    Execution(#[from] CairoRunError),
+    #[error("{0}")]
+    Breakpoint(String),
+}
+
+type SharedDebugger<R, W> = Arc<Mutex<Debugger<R, W>>>;
+
+fn debug_program<R, W>(
+    program_bytes: &[u8],
+    args: &Args,
+    cairo_layout_params: Option<CairoLayoutParams>,
+    input: R,
+    output: W,
+) -> Result<SharedDebugger<R, W>, Error>
+where
+    R: BufRead + Send + 'static,
+    W: Write + Send + 'static,
+{
+    let program = Program::from_bytes(program_bytes, Some(&args.entrypoint))?;
+    let mut debugger = Debugger::new(ProgramInfo::from_bytes(program_bytes)?, input, output);
+    for location in args.breakpoints.iter() {
+        debugger
+            .add_breakpoint(location)
+            .map_err(Error::Breakpoint)?;
+    }
+    let debugger = Arc::new(Mutex::new(debugger));
+
+    let hook_debugger = debugger.clone();
+    let pre_step_hook = Arc::new(
+        move |vm: &mut VirtualMachine,
+              _hint_processor: &mut dyn HintProcessor,
+              _exec_scopes: &mut ExecutionScopes,
+              _hint_data: &[Box<dyn Any>],
+              _constants: &HashMap<String, Felt252>|
+              -> Result<(), VirtualMachineError> {
+            hook_debugger
+                .lock()
+                .map_err(|_| VirtualMachineError::Unexpected)?
+                .pre_step(vm)
+        },
+    );
+
+    let mut cairo_runner =
+        CairoRunner::new(&program, args.layout, cairo_layout_params, false, false)?;
+    cairo_runner.vm = VirtualMachineBuilder::default()
+        .hooks(Hooks::new(None, Some(pre_step_hook), None))
+        .build();
+    let allow_missing_builtins = args.allow_missing_builtins.unwrap_or_default();
+    let end = cairo_runner.initialize(allow_missing_builtins)?;
+
+    let mut hint_processor = BuiltinHintProcessor::new_empty();
+    let result = cairo_runner.run_until_pc(end, &mut hint_processor);
+    let mut locked_debugger = debugger.lock().expect("The debugger's lock was poisoned");
+    if locked_debugger.has_quit() {
+        drop(locked_debugger);
+        return Ok(debugger);
+    }
+    match result {
+        Ok(()) => locked_debugger.post_mortem(&cairo_runner.vm, "Program finished")?,
+        Err(error) => {
+            let error = VmException::from_vm_error(&cairo_runner, error);
+            locked_debugger.post_mortem(&cairo_runner.vm, &format!("Program failed: {error}"))?;
+            return Err(CairoRunError::from(error).into());
+        }
+    }
+    drop(locked_debugger);
+    Ok(debugger)
+}
+
+fn run(args: impl Iterator<Item = String>) -> Result<(), Error> {
+    let args = Args::try_parse_from(args)?;
+    let program_bytes = std::fs::read(&args.filename)?;
+    let cairo_layout_params = match args.cairo_layout_params_file {
+        Some(ref file) => Some(CairoLayoutParams::from_file(file)?),
+        None => None,
+    };
+    debug_program(
+        &program_bytes,
+        &args,
+        cairo_layout_params,
+        io::BufReader::new(io::stdin()),
+        io::stdout(),
+    )?;
+    Ok(())
+}
+
+fn main() -> Result<(), Error> {
+    match run(std::env::args()) {
+        Err(Error::Cli(err)) => err.exit(),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program_info::tests::PROGRAM_JSON;
+    use assert_matches::assert_matches;
+    use std::io::Cursor;
+
+    fn debug_session(args: &[&str], commands: &str) -> Result<String, Error> {
+        let args =
+            Args::try_parse_from(["cairo-vm-debug", "program.json"].iter().chain(args.iter()))?;
+        let debugger = debug_program(
+            PROGRAM_JSON.as_bytes(),
+            &args,
+            None,
+            Cursor::new(commands.to_string()),
+            Vec::new(),
+        )?;
+        let output = debugger.lock().unwrap().output().clone();
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn step_through_program() {
+        let output = debug_session(&[], "r\ns\nx fp-2 3\ns 5\nregisters\nq\n").unwrap();
+        assert_eq!(
+            output,
+            "pc = 0:0 in __main__.main at src/tempvars.cairo:2:5
+(cairo-vm-debug) pc = 0:0
+ap = 1:2
+fp = 1:2
+(cairo-vm-debug) pc = 0:2 in __main__.main at src/tempvars.cairo:3:5
+(cairo-vm-debug) 1:0: 2:0
+1:1: 3:0
+1:2: 5
+(cairo-vm-debug) Program finished
+(cairo-vm-debug) pc = 3:0
+ap = 1:4
+fp = 1:0
+(cairo-vm-debug) "
+        );
+    }
+
+    #[test]
+    fn continue_to_breakpoint_and_print_references() {
+        let output = debug_session(
+            &["--break", "tempvars.cairo:4"],
+            "c\np ids.a\np ids.b\np ids.c\np pc\nc\nc\nquit\n",
+        )
+        .unwrap();
+        assert_eq!(
+            output,
+            "pc = 0:0 in __main__.main at src/tempvars.cairo:2:5
+(cairo-vm-debug) Breakpoint hit at pc 0:4
+pc = 0:4 in __main__.main at src/tempvars.cairo:4:5
+(cairo-vm-debug) ids.a = 5 (felt)
+(cairo-vm-debug) ids.b = 7 (felt)
+(cairo-vm-debug) Unknown reference ids.c at the current pc
+(cairo-vm-debug) 0:4
+(cairo-vm-debug) Program finished
+(cairo-vm-debug) The program is not running
+(cairo-vm-debug) "
+        );
+    }
+
+    #[test]
+    fn manage_breakpoints() {
+        let output = debug_session(
+            &["--break", "2"],
+            "b\nb 4\nd 2\nd 3\nb src/other.cairo:2\nb\nquit\n",
+        )
+        .unwrap();
+        assert_eq!(
+            output,
+            "pc = 0:0 in __main__.main at src/tempvars.cairo:2:5
+(cairo-vm-debug) pc 2 at src/tempvars.cairo:3
+(cairo-vm-debug) Breakpoint set at pc 4
+(cairo-vm-debug) Breakpoint removed from pc 2
+(cairo-vm-debug) No breakpoint set at pc 3
+(cairo-vm-debug) No instruction found at src/other.cairo:2
+(cairo-vm-debug) pc 4 at src/tempvars.cairo:4
+(cairo-vm-debug) "
+        );
+    }
+
+    #[test]
+    fn quit_on_end_of_input() {
+        let output = debug_session(&[], "jump\n").unwrap();
+        assert_eq!(
+            output,
+            "pc = 0:0 in __main__.main at src/tempvars.cairo:2:5
+(cairo-vm-debug) Unknown command: jump, type help to list the commands
+(cairo-vm-debug) "
+        );
+    }
+
+    #[test]
+    fn invalid_breakpoint_argument() {
+        assert_matches!(
+            debug_session(&["--break", "main.cairo:1"], ""),
+            Err(Error::Breakpoint(error)) if error == "No instruction found at main.cairo:1"
+        );
+        assert_matches!(
+            debug_session(&["--break", "main.cairo"], ""),
+            Err(Error::Cli(_))
+        );
+    }
+
+    #[test]
+    fn missing_program_file() {
+        assert_matches!(
+            run(["cairo-vm-debug", "missing.json"]
+                .into_iter()
+                .map(String::from)),
+            Err(Error::IO(_))
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, HashMap};
+
+use cairo_vm::{
+    hint_processor::hint_processor_definition::HintReference,
+    serde::deserialize_program::{
+        ApTracking, DebugInfo, HintParams, Location, Reference, ReferenceManager,
+    },
+};
+use serde::Deserialize;
+
+/// Parts of the compiled program's json used for debugging which are not kept by `Program`
+#[derive(Deserialize)]
+struct ProgramDebugJson {
+    identifiers: HashMap<String, IdentifierJson>,
+    #[serde(default)]
+    hints: BTreeMap<usize, Vec<HintParams>>,
+    reference_manager: ReferenceManager,
+    debug_info: Option<DebugInfo>,
+}
+
+#[derive(Deserialize)]
+struct IdentifierJson {
+    #[serde(rename = "type")]
+    type_: Option<String>,
+    pc: Option<usize>,
+    // Only present on identifiers of type "reference"
+    #[serde(default)]
+    references: Vec<Reference>,
+}
+
+/// A reference resolved to the `ids` variable visible at a given pc
+#[derive(Debug, PartialEq, Eq)]
+pub struct ResolvedReference {
+    pub reference: HintReference,
+    // The ap tracking at the pc the reference is resolved for
+    pub ap_tracking: ApTracking,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ResolveError {
+    NoFunction(usize),
+    UnknownReference(String),
+    ApBasedReference(String),
+}
+
+impl std::fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResolveError::NoFunction(pc) => write!(f, "No function contains pc {pc}"),
+            ResolveError::UnknownReference(name) => {
+                write!(f, "Unknown reference ids.{name} at the current pc")
+            }
+            ResolveError::ApBasedReference(name) => write!(
+                f,
+                "ids.{name} is ap-based and the ap tracking is only known where it is defined or at hint locations"
+            ),
+        }
+    }
+}
+
+pub struct ProgramInfo {
+    // (pc, full name) of every function, sorted by pc
+    functions: Vec<(usize, String)>,
+    // Every definition of each reference, sorted by pc
+    references: HashMap<String, Vec<Reference>>,
+    hints: BTreeMap<usize, Vec<HintParams>>,
+    reference_manager: ReferenceManager,
+    instruction_locations: HashMap<usize, Location>,
+}
+
+impl ProgramInfo {
+    pub fn from_bytes(bytes: &[u8]) -> Result<ProgramInfo, serde_json::Error> {
+        let program_json: ProgramDebugJson = serde_json::from_slice(bytes)?;
+        let mut functions = Vec::new();
+        let mut references = HashMap::new();
+        for (name, identifier) in program_json.identifiers {
+            match (identifier.type_.as_deref(), identifier.pc) {
+                (Some("function"), Some(pc)) => functions.push((pc, name)),
+                (Some("reference"), _) => {
+                    let mut refs = identifier.references;
+                    refs.sort_by_key(|r| r.pc);
+                    references.insert(name, refs);
+                }
+                _ => {}
+            }
+        }
+        functions.sort();
+        let instruction_locations = program_json
+            .debug_info
+            .map(|debug_info| {
+                debug_info
+                    .get_instruction_locations()
+                    .into_iter()
+                    .map(|(pc, location)| (pc, location.inst))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(ProgramInfo {
+            functions,
+            references,
+            hints: program_json.hints,
+            reference_manager: program_json.reference_manager,
+            instruction_locations,
+        })
+    }
+
+    /// Returns the full name of the function containing the given pc
+    pub fn function_at(&self, pc: usize) -> Option<&str> {
+        self.functions
+            .iter()
+            .rev()
+            .find(|(function_pc, _)| *function_pc <= pc)
+            .map(|(_, name)| name.as_str())
+    }
+
+    pub fn location_at(&self, pc: usize) -> Option<&Location> {
+        self.instruction_locations.get(&pc)
+    }
+
+    /// Returns the first pc of the instructions located at the given source line.
+    /// The file matches if it is either the full path of the input file or a suffix of it
+    pub fn pc_for_location(&self, file: &str, line: u32) -> Option<usize> {
+        self.instruction_locations
+            .iter()
+            .filter(|(_, location)| {
+                let filename = &location.input_file.filename;
+                location.start_line == line
+                    && (filename == file || filename.ends_with(&format!("/{file}")))
+            })
+            .map(|(pc, _)| *pc)
+            .min()
+    }
+
+    /// Finds the reference `ids.<name>` visible at the given pc.
+    /// At hint locations the hint's flow tracking data is used, otherwise the latest definition of
+    /// the reference within the function containing the pc is used.
+    pub fn resolve_reference(
+        &self,
+        name: &str,
+        pc: usize,
+    ) -> Result<ResolvedReference, ResolveError> {
+        let suffix = format!(".{name}");
+        if let Some(hints) = self.hints.get(&pc) {
+            for hint in hints {
+                let flow_tracking_data = &hint.flow_tracking_data;
+                let reference = flow_tracking_data
+                    .reference_ids
+                    .iter()
+                    .find(|(full_name, _)| full_name.ends_with(&suffix))
+                    .and_then(|(_, id)| self.reference_manager.references.get(*id));
+                if let Some(reference) = reference {
+                    return Ok(ResolvedReference {
+                        reference: reference.clone().into(),
+                        ap_tracking: flow_tracking_data.ap_tracking.clone(),
+                    });
+                }
+            }
+        }
+
+        let function = self.function_at(pc).ok_or(ResolveError::NoFunction(pc))?;
+        let reference = self
+            .references
+            .get(&format!("{function}{suffix}"))
+            .and_then(|refs| refs.iter().rev().find(|r| r.pc.unwrap_or_default() <= pc))
+            .ok_or_else(|| ResolveError::UnknownReference(name.to_string()))?;
+        let reference_pc = reference.pc.unwrap_or_default();
+        let hint_reference = HintReference::from(reference.clone());
+        // Ap-based references can only be corrected if we know the ap tracking at the current pc
+        if hint_reference.ap_tracking_data.is_some() && reference_pc != pc {
+            return Err(ResolveError::ApBasedReference(name.to_string()));
+        }
+        Ok(ResolvedReference {
+            reference: hint_reference,
+            ap_tracking: reference.ap_tracking_data.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    /* Program used (data hand-assembled):
+    func main() {
+        tempvar a = 5;
+        tempvar b = 7;
+        ret;
+    }
+    */
+    pub(crate) const PROGRAM_JSON: &str = r#"
+    {
+        "prime": "0x800000000000011000000000000000000000000000000000000000000000001",
+        "attributes": [],
+        "builtins": [],
+        "data": [
+            "0x480680017fff8000",
+            "0x5",
+            "0x480680017fff8000",
+            "0x7",
+            "0x208b7fff7fff7ffe"
+        ],
+        "debug_info": {
+            "instruction_locations": {
+                "0": {
+                    "accessible_scopes": ["__main__", "__main__.main"],
+                    "flow_tracking_data": {"ap_tracking": {"group": 0, "offset": 0}, "reference_ids": {}},
+                    "hints": [],
+                    "inst": {"end_col": 24, "end_line": 2, "input_file": {"filename": "src/tempvars.cairo"}, "start_col": 5, "start_line": 2}
+                },
+                "2": {
+                    "accessible_scopes": ["__main__", "__main__.main"],
+                    "flow_tracking_data": {"ap_tracking": {"group": 0, "offset": 1}, "reference_ids": {}},
+                    "hints": [],
+                    "inst": {"end_col": 24, "end_line": 3, "input_file": {"filename": "src/tempvars.cairo"}, "start_col": 5, "start_line": 3}
+                },
+                "4": {
+                    "accessible_scopes": ["__main__", "__main__.main"],
+                    "flow_tracking_data": {"ap_tracking": {"group": 0, "offset": 2}, "reference_ids": {}},
+                    "hints": [],
+                    "inst": {"end_col": 8, "end_line": 4, "input_file": {"filename": "src/tempvars.cairo"}, "start_col": 5, "start_line": 4}
+                }
+            }
+        },
+        "hints": {
+            "4": [
+                {
+                    "accessible_scopes": ["__main__", "__main__.main"],
+                    "code": "memory[ap] = segments.add()",
+                    "flow_tracking_data": {
+                        "ap_tracking": {"group": 0, "offset": 2},
+                        "reference_ids": {"__main__.main.a": 0, "__main__.main.b": 1}
+                    }
+                }
+            ]
+        },
+        "identifiers": {
+            "__main__.main": {"decorators": [], "pc": 0, "type": "function"},
+            "__main__.main.a": {
+                "cairo_type": "felt",
+                "full_name": "__main__.main.a",
+                "references": [
+                    {"ap_tracking_data": {"group": 0, "offset": 1}, "pc": 2, "value": "[cast(fp, felt)]"}
+                ],
+                "type": "reference"
+            },
+            "__main__.main.b": {
+                "cairo_type": "felt",
+                "full_name": "__main__.main.b",
+                "references": [
+                    {"ap_tracking_data": {"group": 0, "offset": 2}, "pc": 4, "value": "[cast(ap + (-1), felt)]"}
+                ],
+                "type": "reference"
+            }
+        },
+        "main_scope": "__main__",
+        "reference_manager": {
+            "references": [
+                {"ap_tracking_data": {"group": 0, "offset": 1}, "pc": 2, "value": "[cast(fp, felt)]"},
+                {"ap_tracking_data": {"group": 0, "offset": 2}, "pc": 4, "value": "[cast(ap + (-1), felt)]"}
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn function_at_pc() {
+        let info = ProgramInfo::from_bytes(PROGRAM_JSON.as_bytes()).unwrap();
+        assert_eq!(info.function_at(0), Some("__main__.main"));
+        assert_eq!(info.function_at(4), Some("__main__.main"));
+    }
+
+    #[test]
+    fn pc_for_source_location() {
+        let info = ProgramInfo::from_bytes(PROGRAM_JSON.as_bytes()).unwrap();
+        assert_eq!(info.pc_for_location("src/tempvars.cairo", 3), Some(2));
+        assert_eq!(info.pc_for_location("tempvars.cairo", 4), Some(4));
+        assert_eq!(info.pc_for_location("vars.cairo", 4), None);
+        assert_eq!(info.pc_for_location("tempvars.cairo", 7), None);
+    }
+
+    #[test]
+    fn resolve_fp_based_reference() {
+        let info = ProgramInfo::from_bytes(PROGRAM_JSON.as_bytes()).unwrap();
+        let resolved = info.resolve_reference("a", 3).unwrap();
+        assert_eq!(resolved.reference.ap_tracking_data, None);
+        assert_matches!(
+            info.resolve_reference("a", 0),
+            Err(ResolveError::UnknownReference(name)) if name == "a"
+        );
+    }
+
+    #[test]
+    fn resolve_ap_based_reference() {
+        let info = ProgramInfo::from_bytes(PROGRAM_JSON.as_bytes()).unwrap();
+        // pc 4 has a hint, so its flow tracking data is used
+        let resolved = info.resolve_reference("b", 4).unwrap();
+        assert_eq!(
+            resolved.ap_tracking,
+            ApTracking {
+                group: 0,
+                offset: 2
+            }
+        );
+        assert_eq!(
+            resolved.reference.ap_tracking_data,
+            Some(ApTracking {
+                group: 0,
+                offset: 2
+            })
+        );
+        assert_matches!(
+            info.resolve_reference("b", 5),
+            Err(ResolveError::ApBasedReference(name)) if name == "b"
+        );
+    }
+
+    #[test]
+    fn resolve_unknown_function() {
+        let info = ProgramInfo::from_bytes(
+            PROGRAM_JSON
+                .replace(
+                    r#""pc": 0, "type": "function""#,
+                    r#""pc": 1, "type": "function""#,
+                )
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(
+            info.resolve_reference("a", 0),
+            Err(ResolveError::NoFunction(0))
+        );
+    }
+}
@@ -13,7 +13,7 @@ use cairo_lang_sierra::{
         bitwise::BitwiseType,
         core::{CoreLibfunc, CoreType},
         ec::EcOpType,
-        gas::GasBuiltinType,
+        gas::{CostTokenType, GasBuiltinType},
         pedersen::PedersenType,
         poseidon::PoseidonType,
         range_check::RangeCheckType,
@@ -28,17 +28,22 @@ use cairo_lang_sierra::{
 use cairo_lang_sierra_gas::objects::CostInfoProvider;
 use cairo_lang_sierra_to_casm::{
     compiler::{CairoProgram, SierraToCasmConfig},
-    metadata::calc_metadata_ap_change_only,
+    metadata::{calc_metadata, calc_metadata_ap_change_only, Metadata},
 };
 use cairo_lang_sierra_type_size::get_type_size_map;
 use cairo_lang_utils::{casts::IntoOrPanic, unordered_hash_map::UnorderedHashMap};
 use cairo_vm::{
-    hint_processor::cairo_1_hint_processor::hint_processor::Cairo1HintProcessor,
+    hint_processor::cairo_1_hint_processor::{
+        hint_processor::Cairo1HintProcessor, syscall_handler::token_gas_cost,
+    },
     math_utils::signed_felt,
     serde::deserialize_program::{ApTracking, FlowTrackingData, HintParams, ReferenceManager},
     types::{
-        builtin_name::BuiltinName, layout_name::LayoutName, program::Program,
-        relocatable::MaybeRelocatable,
+        builtin_name::BuiltinName,
+        layout::CairoLayoutParams,
+        layout_name::LayoutName,
+        program::Program,
+        relocatable::{MaybeRelocatable, Relocatable},
     },
     vm::{
         errors::{runner_errors::RunnerError, vm_errors::VirtualMachineError},
@@ -52,11 +57,12 @@ use num_traits::{cast::ToPrimitive, Zero};
 use std::{collections::HashMap, iter::Peekable};
 
 /// Representation of a cairo argument
-/// Can consist of a single Felt or an array of Felts
+/// Can consist of a single Felt, an array of Felts, or an array made up of other arguments (such as an array of arrays)
 #[derive(Debug, Clone)]
 pub enum FuncArg {
     Array(Vec<Felt252>),
     Single(Felt252),
+    Composed(Vec<FuncArg>),
 }
 
 impl From<Felt252> for FuncArg {
@@ -84,6 +90,8 @@ pub struct Cairo1RunConfig<'a> {
     pub relocate_mem: bool,
     /// Cairo layout chosen for the run
     pub layout: LayoutName,
+    /// Params of the dynamic layout, required when `layout` is `LayoutName::dynamic`
+    pub dynamic_layout_params: Option<CairoLayoutParams>,
     /// Run in proof_mode
     pub proof_mode: bool,
     /// Should be true if either air_public_input or cairo_pie_output are needed
@@ -91,6 +99,10 @@ pub struct Cairo1RunConfig<'a> {
     pub finalize_builtins: bool,
     /// Appends return values to the output segment. This is performed by default when running in proof_mode
     pub append_return_values: bool,
+    /// Gas available to the run. When set, gas costs are computed from the Sierra gas metadata & checked on compilation,
+    /// and the gas required to call `main` is withdrawn from it before the run.
+    /// When None, the program runs with a fixed amount of gas and no gas accounting
+    pub available_gas: Option<usize>,
 }
 
 impl Default for Cairo1RunConfig<'_> {
@@ -101,56 +113,381 @@ impl Default for Cairo1RunConfig<'_> {
             trace_enabled: false,
             relocate_mem: false,
             layout: LayoutName::plain,
+            dynamic_layout_params: None,
             proof_mode: false,
             finalize_builtins: false,
             append_return_values: false,
+            available_gas: None,
         }
     }
 }
 
+/// Gas accounting of a run with `Cairo1RunConfig::available_gas` set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasReport {
+    /// Gas available to the run
+    pub available_gas: usize,
+    /// Gas consumed by the run, including the gas required to call `main`
+    pub gas_consumed: usize,
+    /// Gas required to call each function of the program, as computed by the Sierra gas metadata
+    pub function_costs: Vec<(String, usize)>,
+}
+
+/// A Cairo 1 program loaded into a runner, which can be executed step by step
+pub struct PreparedCairoRun {
+    pub runner: CairoRunner,
+    pub hint_processor: Cairo1HintProcessor,
+    /// The pc at which the execution ends
+    pub end: Relocatable,
+    pub debug_info: Cairo1DebugInfo,
+}
+
+/// Maps the pcs of a loaded Cairo 1 program back to its Sierra statements
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cairo1DebugInfo {
+    /// Offset of the compiled Sierra program within the program segment, which starts after the entry code
+    pub code_offset: usize,
+    /// Size of the compiled Sierra program, the libfunc footer is placed right after it
+    pub code_size: usize,
+    /// Offset of the first instruction of each Sierra statement, relative to `code_offset`
+    pub statement_offsets: Vec<usize>,
+}
+
+impl Cairo1DebugInfo {
+    /// Returns the index of the Sierra statement the instruction at `pc` was compiled from.
+    /// Returns None for the instructions of the entry code & the libfunc footer
+    pub fn statement_at(&self, pc: usize) -> Option<usize> {
+        let offset = pc.checked_sub(self.code_offset)?;
+        if offset >= self.code_size {
+            return None;
+        }
+        // Statements which don't generate any instructions share their offset with the next one
+        self.statement_offsets
+            .partition_point(|statement_offset| *statement_offset <= offset)
+            .checked_sub(1)
+    }
+
+    /// Returns the pc of the first instruction of the given Sierra statement
+    pub fn pc_of_statement(&self, statement_idx: usize) -> Option<usize> {
+        self.statement_offsets
+            .get(statement_idx)
+            .map(|offset| self.code_offset + offset)
+    }
+
+    /// Returns the (pc, name) of each function of the program, sorted by pc.
+    /// The entry code & the libfunc footer are listed as functions of their own
+    pub fn function_pcs(&self, sierra_program: &SierraProgram) -> Vec<(usize, String)> {
+        let mut functions: Vec<_> = sierra_program
+            .funcs
+            .iter()
+            .filter_map(|function| {
+                Some((
+                    self.pc_of_statement(function.entry_point.0)?,
+                    function.id.to_string(),
+                ))
+            })
+            .collect();
+        functions.push((0, String::from("<entry code>")));
+        functions.push((
+            self.code_offset + self.code_size,
+            String::from("<libfunc footer>"),
+        ));
+        functions.sort();
+        functions
+    }
+}
+
+// A Sierra program compiled to casm, from which any of its functions can be loaded into a runner
+pub(crate) struct CompiledSierraProgram {
+    casm_program: CairoProgram,
+    sierra_program_registry: ProgramRegistry<CoreType, CoreLibfunc>,
+    type_sizes: UnorderedHashMap<ConcreteTypeId, i16>,
+    // Gas required to call each function, empty when compiled without gas accounting
+    function_costs: Vec<(String, usize)>,
+}
+
+// The parts of a loaded program needed to execute it and to fetch its return values
+pub(crate) struct LoadedProgram<'a> {
+    pub(crate) runner: CairoRunner,
+    pub(crate) hint_processor: Cairo1HintProcessor,
+    pub(crate) end: Relocatable,
+    pub(crate) main_func: &'a Function,
+    pub(crate) sierra_program_registry: &'a ProgramRegistry<CoreType, CoreLibfunc>,
+    pub(crate) type_sizes: &'a UnorderedHashMap<ConcreteTypeId, i16>,
+    pub(crate) builtins: Vec<BuiltinName>,
+    pub(crate) debug_info: Cairo1DebugInfo,
+    pub(crate) gas_accounting: Option<GasAccounting>,
+}
+
+// What's needed to build the `GasReport` of a run
+pub(crate) struct GasAccounting {
+    available_gas: usize,
+    // Gas given to `main`, once the gas required to call it was withdrawn
+    initial_gas: usize,
+    // The pc right after the call to `main`, where its return values are at the top of the stack
+    return_pc: Relocatable,
+    // Offset from ap of the final value of `main`'s gas builtin at `return_pc`, None if it doesn't use gas
+    remaining_gas_offset: Option<i16>,
+    function_costs: Vec<(String, usize)>,
+}
+
 // Runs a Cairo 1 program
 // Returns the runner after execution + the return values + the serialized return values (if serialize_output is enabled)
 pub fn cairo_run_program(
     sierra_program: &SierraProgram,
     cairo_run_config: Cairo1RunConfig,
 ) -> Result<(CairoRunner, Vec<MaybeRelocatable>, Option<String>), Error> {
-    let metadata = calc_metadata_ap_change_only(sierra_program)
-        .map_err(|_| VirtualMachineError::Unexpected)?;
+    let (runner, return_values, serialized_output, _) =
+        cairo_run_program_with_gas_report(sierra_program, cairo_run_config)?;
+    Ok((runner, return_values, serialized_output))
+}
+
+/// Runs a Cairo 1 program like `cairo_run_program`, also returning its gas accounting if `available_gas` is set.
+/// Running out of gas results in `Error::OutOfGas`
+#[allow(clippy::type_complexity)]
+pub fn cairo_run_program_with_gas_report(
+    sierra_program: &SierraProgram,
+    cairo_run_config: Cairo1RunConfig,
+) -> Result<
+    (
+        CairoRunner,
+        Vec<MaybeRelocatable>,
+        Option<String>,
+        Option<GasReport>,
+    ),
+    Error,
+> {
+    let compiled_program =
+        compile_sierra_program(sierra_program, cairo_run_config.available_gas.is_some())?;
+    let LoadedProgram {
+        mut runner,
+        mut hint_processor,
+        end,
+        main_func,
+        sierra_program_registry,
+        type_sizes,
+        builtins,
+        gas_accounting,
+        ..
+    } = load_program(
+        &compiled_program,
+        find_function(sierra_program, "::main")?,
+        &cairo_run_config,
+    )?;
+
+    // Fetch return type data
+
+    let return_type_id = main_func.signature.ret_types.last();
+    let return_type_size = return_type_id
+        .and_then(|id| type_sizes.get(id).cloned())
+        .unwrap_or_default();
+    let builtin_count: i16 = builtins.len().into_or_panic();
+
+    let gas_report = run_loaded_program(
+        &mut runner,
+        &mut hint_processor,
+        end,
+        gas_accounting,
+        cairo_run_config.proof_mode,
+    )?;
+
+    let skip_output = cairo_run_config.proof_mode || cairo_run_config.append_return_values;
+
+    let result_inner_type_size =
+        result_inner_type_size(return_type_id, sierra_program_registry, type_sizes);
+    // Fetch return values
+    let return_values = fetch_return_values(
+        return_type_size,
+        result_inner_type_size,
+        &runner.vm,
+        builtin_count,
+        skip_output,
+    )?;
+
+    let serialized_output = if cairo_run_config.serialize_output {
+        Some(serialize_output(
+            &return_values,
+            &mut runner.vm,
+            return_type_id,
+            sierra_program_registry,
+            type_sizes,
+        ))
+    } else {
+        None
+    };
+
+    // Set stop pointers for builtins so we can obtain the air public input
+    if cairo_run_config.finalize_builtins {
+        if skip_output {
+            // Set stop pointer for each builtin
+            runner.vm.builtins_final_stack_from_stack_pointer_dict(
+                &builtins
+                    .iter()
+                    .enumerate()
+                    .map(|(i, builtin)| {
+                        (
+                            *builtin,
+                            (runner.vm.get_ap() - (builtins.len() - 1 - i)).unwrap(),
+                        )
+                    })
+                    .collect(),
+                false,
+            )?;
+        } else {
+            finalize_builtins(
+                &main_func.signature.ret_types,
+                type_sizes,
+                &mut runner.vm,
+                builtin_count,
+            )?;
+        }
+
+        // Build execution public memory
+        if cairo_run_config.proof_mode {
+            runner.finalize_segments()?;
+        }
+    }
+
+    runner.relocate(true)?;
+
+    Ok((runner, return_values, serialized_output, gas_report))
+}
+
+// Runs a loaded program until its end, returning its gas report if gas is accounted for
+pub(crate) fn run_loaded_program(
+    runner: &mut CairoRunner,
+    hint_processor: &mut Cairo1HintProcessor,
+    end: Relocatable,
+    gas_accounting: Option<GasAccounting>,
+    proof_mode: bool,
+) -> Result<Option<GasReport>, Error> {
+    // Stop once `main` returns to fetch its remaining gas, as the stack may grow before the end of the entry code
+    let gas_report = match gas_accounting {
+        Some(gas_accounting) => {
+            runner.run_until_pc(gas_accounting.return_pc, hint_processor)?;
+            let remaining_gas = match gas_accounting.remaining_gas_offset {
+                Some(offset) => runner
+                    .vm
+                    .get_integer(
+                        (runner.vm.get_ap() - offset as usize)
+                            .map_err(VirtualMachineError::Math)?,
+                    )?
+                    .to_usize()
+                    .ok_or(Error::FailedToExtractReturnValues)?,
+                None => gas_accounting.initial_gas,
+            };
+            Some(GasReport {
+                available_gas: gas_accounting.available_gas,
+                gas_consumed: gas_accounting.available_gas.saturating_sub(remaining_gas),
+                function_costs: gas_accounting.function_costs,
+            })
+        }
+        None => None,
+    };
+
+    // Run it until the end / infinite loop in proof_mode
+    runner.run_until_pc(end, hint_processor)?;
+    if proof_mode {
+        runner.run_for_steps(1, hint_processor)?;
+    }
+
+    runner.end_run(false, false, hint_processor)?;
+
+    Ok(gas_report)
+}
+
+/// Loads a Cairo 1 program into a runner without executing it, so that it can be driven step by step via
+/// `CairoRunner::run_for_steps` using the returned hint processor until the `end` pc is reached
+pub fn prepare_cairo_run(
+    sierra_program: &SierraProgram,
+    cairo_run_config: &Cairo1RunConfig,
+) -> Result<PreparedCairoRun, Error> {
+    let compiled_program =
+        compile_sierra_program(sierra_program, cairo_run_config.available_gas.is_some())?;
+    let loaded_program = load_program(
+        &compiled_program,
+        find_function(sierra_program, "::main")?,
+        cairo_run_config,
+    )?;
+    Ok(PreparedCairoRun {
+        runner: loaded_program.runner,
+        hint_processor: loaded_program.hint_processor,
+        end: loaded_program.end,
+        debug_info: loaded_program.debug_info,
+    })
+}
+
+// Compiles the Sierra program to casm, computing its gas costs if `gas_accounting` is set
+pub(crate) fn compile_sierra_program(
+    sierra_program: &SierraProgram,
+    gas_accounting: bool,
+) -> Result<CompiledSierraProgram, Error> {
+    let metadata = if gas_accounting {
+        calc_metadata(sierra_program, Default::default())?
+    } else {
+        calc_metadata_ap_change_only(sierra_program).map_err(|_| VirtualMachineError::Unexpected)?
+    };
     let sierra_program_registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(sierra_program)?;
     let type_sizes =
         get_type_size_map(sierra_program, &sierra_program_registry).unwrap_or_default();
     let config = SierraToCasmConfig {
-        gas_usage_check: false,
+        gas_usage_check: gas_accounting,
         max_bytecode_size: usize::MAX,
     };
     let casm_program =
         cairo_lang_sierra_to_casm::compiler::compile(sierra_program, &metadata, config)?;
+    Ok(CompiledSierraProgram {
+        casm_program,
+        sierra_program_registry,
+        type_sizes,
+        function_costs: function_gas_costs(sierra_program, &metadata),
+    })
+}
 
-    let main_func = find_function(sierra_program, "::main")?;
+// Loads a function of a compiled program into a runner along with its arguments.
+// The program must have been compiled with gas accounting if `available_gas` is set
+pub(crate) fn load_program<'a>(
+    compiled_program: &'a CompiledSierraProgram,
+    main_func: &'a Function,
+    cairo_run_config: &Cairo1RunConfig,
+) -> Result<LoadedProgram<'a>, Error> {
+    let CompiledSierraProgram {
+        casm_program,
+        sierra_program_registry,
+        type_sizes,
+        function_costs,
+    } = compiled_program;
 
-    let initial_gas = 9999999999999_usize;
+    let initial_gas = match cairo_run_config.available_gas {
+        Some(available_gas) => {
+            let required = function_costs
+                .iter()
+                .find(|(name, _)| *name == main_func.id.to_string())
+                .map(|(_, cost)| *cost)
+                .unwrap_or_default();
+            available_gas
+                .checked_sub(required)
+                .ok_or(Error::NotEnoughGasToCall {
+                    required,
+                    available: available_gas,
+                })?
+        }
+        None => 9999999999999_usize,
+    };
 
     // Modified entry code to be compatible with custom cairo1 Proof Mode.
     // This adds code that's needed for dictionaries, adjusts ap for builtin pointers, adds initial gas for the gas builtin if needed, and sets up other necessary code for cairo1
-    let (entry_code, builtins) = create_entry_code(
-        &sierra_program_registry,
-        &casm_program,
-        &type_sizes,
+    let (entry_code, builtins, return_offset) = create_entry_code(
+        sierra_program_registry,
+        casm_program,
+        type_sizes,
         main_func,
         initial_gas,
-        &cairo_run_config,
+        cairo_run_config,
     )?;
 
-    // Fetch return type data
-
-    let return_type_id = main_func.signature.ret_types.last();
-    let return_type_size = return_type_id
-        .and_then(|id| type_sizes.get(id).cloned())
-        .unwrap_or_default();
-
     // This footer is used by lib funcs
     let libfunc_footer = create_code_footer();
-    let builtin_count: i16 = builtins.len().into_or_panic();
 
     // This is the program we are actually running/proving
     // With (embedded proof mode), cairo1 header and the libfunc footer
@@ -162,13 +499,22 @@ pub fn cairo_run_program(
 
     let (processor_hints, program_hints) = build_hints_vec(instructions.clone());
 
-    let mut hint_processor = Cairo1HintProcessor::new(&processor_hints, RunResources::default());
+    let hint_processor = Cairo1HintProcessor::new(&processor_hints, RunResources::default());
 
-    let data: Vec<MaybeRelocatable> = instructions
+    let mut data: Vec<MaybeRelocatable> = instructions
         .flat_map(|inst| inst.assemble().encode())
         .map(|x| Felt252::from(&x))
         .map(MaybeRelocatable::from)
         .collect();
+    if cairo_run_config.available_gas.is_some() {
+        // Libfuncs withdrawing the gas used by builtins read their costs through a pointer placed right after the
+        // footer's `ret`. The program is always loaded into the first segment
+        data.push(Relocatable::from((0, data.len() + 1)).into());
+        data.extend(
+            CostTokenType::iter_precost()
+                .map(|token_type| MaybeRelocatable::from(token_gas_cost(*token_type))),
+        );
+    }
 
     let program = if cairo_run_config.proof_mode {
         Program::new_for_proof(
@@ -210,80 +556,91 @@ pub fn cairo_run_program(
     let mut runner = CairoRunner::new_v2(
         &program,
         cairo_run_config.layout,
+        cairo_run_config.dynamic_layout_params.clone(),
         runner_mode,
         cairo_run_config.trace_enabled,
     )?;
     let end = runner.initialize(cairo_run_config.proof_mode)?;
-    load_arguments(&mut runner, &cairo_run_config, main_func)?;
-
-    // Run it until the end / infinite loop in proof_mode
-    runner.run_until_pc(end, &mut hint_processor)?;
-    if cairo_run_config.proof_mode {
-        runner.run_for_steps(1, &mut hint_processor)?;
-    }
-
-    runner.end_run(false, false, &mut hint_processor)?;
+    load_arguments(&mut runner, cairo_run_config, main_func)?;
 
-    let skip_output = cairo_run_config.proof_mode || cairo_run_config.append_return_values;
-
-    let result_inner_type_size =
-        result_inner_type_size(return_type_id, &sierra_program_registry, &type_sizes);
-    // Fetch return values
-    let return_values = fetch_return_values(
-        return_type_size,
-        result_inner_type_size,
-        &runner.vm,
-        builtin_count,
-        skip_output,
-    )?;
+    let gas_accounting = match cairo_run_config.available_gas {
+        Some(available_gas) => Some(GasAccounting {
+            available_gas,
+            initial_gas,
+            return_pc: (runner.program_base.ok_or(RunnerError::NoProgBase)? + return_offset)
+                .map_err(VirtualMachineError::Math)?,
+            remaining_gas_offset: remaining_gas_offset(
+                main_func,
+                sierra_program_registry,
+                type_sizes,
+            ),
+            function_costs: function_costs.clone(),
+        }),
+        None => None,
+    };
 
-    let serialized_output = if cairo_run_config.serialize_output {
-        Some(serialize_output(
-            &return_values,
-            &mut runner.vm,
-            return_type_id,
-            &sierra_program_registry,
-            &type_sizes,
-        ))
-    } else {
-        None
+    let debug_info = Cairo1DebugInfo {
+        code_offset: entry_code.current_code_offset,
+        code_size: casm_program
+            .instructions
+            .iter()
+            .map(|inst| inst.body.op_size())
+            .sum(),
+        statement_offsets: casm_program
+            .debug_info
+            .sierra_statement_info
+            .iter()
+            .map(|statement_info| statement_info.code_offset)
+            .collect(),
     };
 
-    // Set stop pointers for builtins so we can obtain the air public input
-    if cairo_run_config.finalize_builtins {
-        if skip_output {
-            // Set stop pointer for each builtin
-            runner.vm.builtins_final_stack_from_stack_pointer_dict(
-                &builtins
-                    .iter()
-                    .enumerate()
-                    .map(|(i, builtin)| {
-                        (
-                            *builtin,
-                            (runner.vm.get_ap() - (builtins.len() - 1 - i)).unwrap(),
-                        )
-                    })
-                    .collect(),
-                false,
-            )?;
-        } else {
-            finalize_builtins(
-                &main_func.signature.ret_types,
-                &type_sizes,
-                &mut runner.vm,
-                builtin_count,
-            )?;
-        }
+    Ok(LoadedProgram {
+        runner,
+        hint_processor,
+        end,
+        main_func,
+        sierra_program_registry,
+        type_sizes,
+        builtins,
+        debug_info,
+        gas_accounting,
+    })
+}
 
-        // Build execution public memory
-        if cairo_run_config.proof_mode {
-            runner.finalize_segments()?;
+// Returns the gas required to call each function of the program, as computed by the Sierra gas metadata
+fn function_gas_costs(sierra_program: &SierraProgram, metadata: &Metadata) -> Vec<(String, usize)> {
+    sierra_program
+        .funcs
+        .iter()
+        .filter_map(|function| {
+            let costs = metadata.gas_info.function_costs.get(&function.id)?;
+            let cost = costs
+                .iter()
+                .map(|(token_type, count)| {
+                    (*count).into_or_panic::<usize>() * token_gas_cost(*token_type)
+                })
+                .sum();
+            Some((function.id.to_string(), cost))
+        })
+        .collect()
+}
+
+// Returns the offset from ap of the final value of the function's gas builtin, right after it returns
+fn remaining_gas_offset(
+    func: &Function,
+    sierra_program_registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    type_sizes: &UnorderedHashMap<ConcreteTypeId, i16>,
+) -> Option<i16> {
+    let mut offset = 0;
+    for ty in func.signature.ret_types.iter().rev() {
+        offset += type_sizes.get(ty).copied().unwrap_or_default();
+        if get_info(sierra_program_registry, ty)
+            .is_some_and(|info| info.long_id.generic_id == GasBuiltinType::ID)
+        {
+            return Some(offset);
         }
     }
-
-    runner.relocate(true)?;
-
-    Ok((runner, return_values, serialized_output))
+    None
 }
 
 #[allow(clippy::type_complexity)]
@@ -316,7 +673,7 @@ fn build_hints_vec<'b>(
 }
 
 /// Finds first function ending with `name_suffix`.
-fn find_function<'a>(
+pub(crate) fn find_function<'a>(
     sierra_program: &'a SierraProgram,
     name_suffix: &'a str,
 ) -> Result<&'a Function, RunnerError> {
@@ -432,39 +789,39 @@ fn load_arguments(
         }
     }
     for arg in cairo_run_config.args {
-        match arg {
-            FuncArg::Array(args) => {
-                let array_start = runner.vm.add_memory_segment();
-                let array_end = runner.vm.load_data(
-                    array_start,
-                    &args.iter().map(|f| f.into()).collect::<Vec<_>>(),
-                )?;
-                runner.vm.insert_value(
-                    (runner.vm.get_ap() + ap_offset).map_err(VirtualMachineError::Math)?,
-                    array_start,
-                )?;
-                ap_offset += 1;
-                runner.vm.insert_value(
-                    (runner.vm.get_ap() + ap_offset).map_err(VirtualMachineError::Math)?,
-                    array_end,
-                )?;
-                ap_offset += 1;
-            }
-            FuncArg::Single(arg) => {
-                runner.vm.insert_value(
-                    (runner.vm.get_ap() + ap_offset).map_err(VirtualMachineError::Math)?,
-                    arg,
-                )?;
-                ap_offset += 1;
-            }
+        for value in load_arg(runner, arg)? {
+            runner.vm.insert_value(
+                (runner.vm.get_ap() + ap_offset).map_err(VirtualMachineError::Math)?,
+                value,
+            )?;
+            ap_offset += 1;
         }
     }
 
     Ok(())
 }
 
+// Loads the arrays of an argument into new segments, returning the values that represent it in the stack
+fn load_arg(runner: &mut CairoRunner, arg: &FuncArg) -> Result<Vec<MaybeRelocatable>, Error> {
+    let array_values = match arg {
+        FuncArg::Single(arg) => return Ok(vec![arg.into()]),
+        FuncArg::Array(args) => args.iter().map(MaybeRelocatable::from).collect(),
+        FuncArg::Composed(args) => {
+            let mut array_values = Vec::new();
+            for arg in args {
+                array_values.extend(load_arg(runner, arg)?);
+            }
+            array_values
+        }
+    };
+    let array_start = runner.vm.add_memory_segment();
+    let array_end = runner.vm.load_data(array_start, &array_values)?;
+    Ok(vec![array_start.into(), array_end.into()])
+}
+
 /// Returns the instructions to add to the beginning of the code to successfully call the main
-/// function, as well as the builtins required to execute the program.
+/// function, as well as the builtins required to execute the program and the offset of the
+/// instruction following the call.
 fn create_entry_code(
     sierra_program_registry: &ProgramRegistry<CoreType, CoreLibfunc>,
     casm_program: &CairoProgram,
@@ -472,7 +829,7 @@ fn create_entry_code(
     func: &Function,
     initial_gas: usize,
     config: &Cairo1RunConfig,
-) -> Result<(CasmContext, Vec<BuiltinName>), Error> {
+) -> Result<(CasmContext, Vec<BuiltinName>, usize), Error> {
     let copy_to_output_builtin = config.proof_mode || config.append_return_values;
     let signature = &func.signature;
     // The builtins in the formatting expected by the runner.
@@ -554,7 +911,7 @@ fn create_entry_code(
         .iter()
         .map(|arg| match arg {
             FuncArg::Single(_) => 1,
-            FuncArg::Array(_) => 2,
+            FuncArg::Array(_) | FuncArg::Composed(_) => 2,
         })
         .sum::<i16>();
     if expected_arguments_size != actual_args_size {
@@ -677,6 +1034,7 @@ fn create_entry_code(
     let instruction_sizes = instructions.iter().map(|inst| inst.body.op_size());
     let prev_call_size: usize = instruction_sizes.clone().take(*call_inst).sum();
     let post_call_size: usize = instruction_sizes.skip(*call_inst).sum();
+    let return_offset = prev_call_size + instructions[*call_inst].body.op_size();
     let InstructionBody::Call(inst) = &mut instructions[*call_inst].body else {
         panic!("Expected call instruction");
     };
@@ -691,10 +1049,11 @@ fn create_entry_code(
             current_hints: vec![],
         },
         builtins,
+        return_offset,
     ))
 }
 
-fn get_info<'a>(
+pub(crate) fn get_info<'a>(
     sierra_program_registry: &'a ProgramRegistry<CoreType, CoreLibfunc>,
     ty: &'a cairo_lang_sierra::ids::ConcreteTypeId,
 ) -> Option<&'a cairo_lang_sierra::extensions::types::TypeInfo> {
@@ -740,7 +1099,7 @@ fn get_function_builtins(
 
 // Returns the size of the T type in PanicResult::Ok(T) if applicable
 // Returns None if the return_type_id is not a PanicResult
-fn result_inner_type_size(
+pub(crate) fn result_inner_type_size(
     return_type_id: Option<&ConcreteTypeId>,
     sierra_program_registry: &ProgramRegistry<CoreType, CoreLibfunc>,
     type_sizes: &UnorderedHashMap<ConcreteTypeId, i16>,
@@ -767,7 +1126,7 @@ fn result_inner_type_size(
     }
 }
 
-fn fetch_return_values(
+pub(crate) fn fetch_return_values(
     return_type_size: i16,
     result_inner_type_size: Option<i16>,
     vm: &VirtualMachine,
@@ -805,7 +1164,7 @@ fn fetch_return_values(
                 panic_data_start,
                 (panic_data_end - panic_data_start).map_err(VirtualMachineError::Math)?,
             )?;
-            return Err(Error::RunPanic(
+            return Err(Error::from_panic_data(
                 panic_data.iter().map(|c| *c.as_ref()).collect(),
             ));
         } else {
@@ -1243,6 +1602,7 @@ mod tests {
     use std::path::Path;
 
     use super::*;
+    use assert_matches::assert_matches;
     use cairo_lang_compiler::{
         compile_prepared_db, db::RootDatabase, project::setup_project, CompilerConfig,
     };
@@ -1250,15 +1610,20 @@ mod tests {
     use rstest::rstest;
 
     fn compile_to_sierra(filename: &str) -> SierraProgram {
+        compile_to_sierra_withdrawing_gas(filename, false)
+    }
+
+    fn compile_to_sierra_withdrawing_gas(filename: &str, withdraw_gas: bool) -> SierraProgram {
         let compiler_config = CompilerConfig {
             replace_ids: true,
             ..CompilerConfig::default()
         };
-        let mut db = RootDatabase::builder()
-            .detect_corelib()
-            .skip_auto_withdraw_gas()
-            .build()
-            .unwrap();
+        let mut db_builder = RootDatabase::builder();
+        db_builder.detect_corelib();
+        if !withdraw_gas {
+            db_builder.skip_auto_withdraw_gas();
+        }
+        let mut db = db_builder.build().unwrap();
         let main_crate_ids = setup_project(&mut db, Path::new(filename)).unwrap();
         compile_prepared_db(&mut db, main_crate_ids, compiler_config).unwrap()
     }
@@ -1371,4 +1736,127 @@ mod tests {
                 .unwrap();
         assert_eq!(hash_a, hash_b)
     }
+
+    #[test]
+    fn run_prepared_program_step_by_step() {
+        let sierra_program =
+            compile_to_sierra("../cairo_programs/cairo-1-programs/fibonacci.cairo");
+        let cairo_run_config = Cairo1RunConfig {
+            layout: LayoutName::all_cairo,
+            ..Default::default()
+        };
+        let PreparedCairoRun {
+            mut runner,
+            mut hint_processor,
+            end,
+            debug_info,
+        } = prepare_cairo_run(&sierra_program, &cairo_run_config).unwrap();
+        while runner.vm.get_pc() != end {
+            let pc = runner.vm.get_pc().offset;
+            if let Some(statement_idx) = debug_info.statement_at(pc) {
+                assert!(debug_info.pc_of_statement(statement_idx).unwrap() <= pc);
+            }
+            runner.run_for_steps(1, &mut hint_processor).unwrap();
+        }
+        let (expected_runner, _, _) = cairo_run_program(&sierra_program, cairo_run_config).unwrap();
+        assert_eq!(runner.vm.get_ap(), expected_runner.vm.get_ap());
+        assert_eq!(
+            runner.get_execution_resources().unwrap().n_steps,
+            expected_runner.get_execution_resources().unwrap().n_steps
+        );
+    }
+
+    #[test]
+    fn sierra_statement_of_pc() {
+        let debug_info = Cairo1DebugInfo {
+            code_offset: 10,
+            code_size: 8,
+            statement_offsets: vec![0, 2, 2, 5],
+        };
+        // Entry code
+        assert_eq!(debug_info.statement_at(9), None);
+        assert_eq!(debug_info.statement_at(10), Some(0));
+        assert_eq!(debug_info.statement_at(11), Some(0));
+        // Statement 1 doesn't generate any instructions
+        assert_eq!(debug_info.statement_at(12), Some(2));
+        assert_eq!(debug_info.statement_at(17), Some(3));
+        // Libfunc footer
+        assert_eq!(debug_info.statement_at(18), None);
+        assert_eq!(debug_info.pc_of_statement(3), Some(15));
+        assert_eq!(debug_info.pc_of_statement(4), None);
+    }
+
+    #[test]
+    fn function_pcs_of_program() {
+        let sierra_program =
+            compile_to_sierra("../cairo_programs/cairo-1-programs/fibonacci.cairo");
+        let debug_info = prepare_cairo_run(&sierra_program, &Cairo1RunConfig::default())
+            .unwrap()
+            .debug_info;
+        let functions = debug_info.function_pcs(&sierra_program);
+        assert_eq!(functions.len(), sierra_program.funcs.len() + 2);
+        assert_eq!(functions[0], (0, String::from("<entry code>")));
+        assert_eq!(
+            functions[1],
+            (
+                debug_info.code_offset,
+                sierra_program.funcs[0].id.to_string()
+            )
+        );
+        assert_eq!(
+            functions.last(),
+            Some(&(
+                debug_info.code_offset + debug_info.code_size,
+                String::from("<libfunc footer>")
+            ))
+        );
+    }
+
+    #[test]
+    fn run_with_gas_accounting() {
+        let sierra_program = compile_to_sierra_withdrawing_gas(
+            "../cairo_programs/cairo-1-programs/recursion.cairo",
+            true,
+        );
+        let run_with_gas = |available_gas| {
+            cairo_run_program_with_gas_report(
+                &sierra_program,
+                Cairo1RunConfig {
+                    layout: LayoutName::all_cairo,
+                    available_gas: Some(available_gas),
+                    ..Default::default()
+                },
+            )
+        };
+        // Runs without gas accounting don't report any gas
+        let (_, expected_return_values, _, gas_report) = cairo_run_program_with_gas_report(
+            &compile_to_sierra("../cairo_programs/cairo-1-programs/recursion.cairo"),
+            Cairo1RunConfig {
+                layout: LayoutName::all_cairo,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(gas_report, None);
+
+        let (_, return_values, _, gas_report) = run_with_gas(100_000_000).unwrap();
+        assert_eq!(return_values, expected_return_values);
+        let gas_report = gas_report.unwrap();
+        assert_eq!(gas_report.available_gas, 100_000_000);
+        let (_, main_cost) = gas_report
+            .function_costs
+            .iter()
+            .find(|(function, _)| function.ends_with("::main"))
+            .unwrap();
+        assert!(*main_cost > 0);
+        assert!(gas_report.gas_consumed > *main_cost);
+        assert!(gas_report.gas_consumed < 100_000_000);
+
+        // Each iteration of the loop withdraws gas
+        assert_matches!(run_with_gas(1000).err(), Some(Error::OutOfGas));
+        assert_matches!(
+            run_with_gas(main_cost - 1).err(),
+            Some(Error::NotEnoughGasToCall { required, available }) if required == *main_cost && available == main_cost - 1
+        );
+    }
 }
@@ -0,0 +1,363 @@
+use crate::{
+    error::Error,
+    json_output::UserTypeNames,
+    test_runner::{CompiledTests, TestCase, TestExpectation},
+};
+use cairo_lang_compiler::{
+    compile_prepared_db, db::RootDatabase, diagnostics::DiagnosticsReporter,
+    project::setup_project, CompilerConfig,
+};
+use cairo_lang_defs::{db::DefsGroup, ids::TopLevelLanguageElementId};
+use cairo_lang_filesystem::{
+    cfg::{Cfg, CfgSet},
+    db::{CrateConfiguration, FilesGroup, FilesGroupEx},
+    ids::{CrateId, CrateLongId, Directory},
+};
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_sierra::program::Program as SierraProgram;
+use cairo_lang_starknet_classes::compiler_version::current_compiler_version_id;
+use cairo_lang_test_plugin::{
+    compile_test_prepared_db,
+    test_config::{PanicExpectation, TestExpectation as CairoTestExpectation},
+    test_plugin_suite, TestsCompilationConfig,
+};
+use cairo_vm::Felt252;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+// Cached compilations are invalidated along with this crate, or when the compiler is upgraded, as
+// the cairo-lang crates are released together under the version given by `current_compiler_version_id`.
+// They are pinned to a single revision of the HerodotusDev fork, so a version always names the same compiler
+const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Compiles a Cairo 1 program to Sierra. `path` can be a single `.cairo` file, a directory holding
+/// a `cairo_project.toml`, or a Scarb package (a directory holding a `Scarb.toml`, with its sources in `src`).
+/// Gas is only withdrawn by the compiled program if `withdraw_gas` is set.
+/// If a `cache_dir` is given, the program is only compiled if its sources changed since it was cached there
+pub fn compile_cairo_program(
+    path: &Path,
+    withdraw_gas: bool,
+    cache_dir: Option<&Path>,
+) -> Result<SierraProgram, Error> {
+    compile_cairo_program_with_type_names(path, withdraw_gas, cache_dir)
+        .map(|(sierra_program, _)| sierra_program)
+}
+
+/// Compiles a Cairo 1 program to Sierra as `compile_cairo_program` does, also returning the names
+/// of the struct members & enum variants declared by the program and its dependencies, which
+/// `serialize_output_json` uses to name them
+pub fn compile_cairo_program_with_type_names(
+    path: &Path,
+    withdraw_gas: bool,
+    cache_dir: Option<&Path>,
+) -> Result<(SierraProgram, UserTypeNames), Error> {
+    let kind = if withdraw_gas {
+        "program_withdrawing_gas"
+    } else {
+        "program"
+    };
+    cached(path, kind, cache_dir, || {
+        let mut db_builder = RootDatabase::builder();
+        db_builder.detect_corelib();
+        if !withdraw_gas {
+            db_builder.skip_auto_withdraw_gas();
+        }
+        let mut db = db_builder.build().map_err(compilation_error)?;
+        let main_crate_ids = setup_crates(&mut db, path)?;
+        let compiler_config = CompilerConfig {
+            replace_ids: true,
+            ..CompilerConfig::default()
+        };
+        let sierra_program = compile_prepared_db(&mut db, main_crate_ids, compiler_config)
+            .map_err(compilation_error)?;
+        Ok((sierra_program, user_type_names(&db)))
+    })
+}
+
+// Collects the names of the members of every struct & of the variants of every enum in the db's
+// crates, by the path of their type
+fn user_type_names(db: &RootDatabase) -> UserTypeNames {
+    let mut names = UserTypeNames::default();
+    for crate_id in db.crates() {
+        for module_id in db.crate_modules(crate_id).iter() {
+            for struct_id in db.module_structs_ids(*module_id).unwrap_or_default().iter() {
+                if let Ok(members) = db.struct_members(*struct_id) {
+                    names.members.insert(
+                        struct_id.full_path(db),
+                        members.keys().map(ToString::to_string).collect(),
+                    );
+                }
+            }
+            for enum_id in db.module_enums_ids(*module_id).unwrap_or_default().iter() {
+                if let Ok(variants) = db.enum_variants(*enum_id) {
+                    names.variants.insert(
+                        enum_id.full_path(db),
+                        variants.keys().map(ToString::to_string).collect(),
+                    );
+                }
+            }
+        }
+    }
+    names
+}
+
+/// Compiles the `#[test]` functions of a Cairo 1 project, which can be given as in `compile_cairo_program`,
+/// caching them the same way
+pub fn compile_cairo_tests(path: &Path, cache_dir: Option<&Path>) -> Result<CompiledTests, Error> {
+    cached(path, "tests", cache_dir, || {
+        let mut db = RootDatabase::builder()
+            .detect_corelib()
+            .with_cfg(CfgSet::from_iter([Cfg::name("test")]))
+            .with_plugin_suite(test_plugin_suite())
+            .build()
+            .map_err(compilation_error)?;
+        let main_crate_ids = setup_crates(&mut db, path)?;
+        DiagnosticsReporter::stderr()
+            .with_crates(&main_crate_ids)
+            .ensure(&db)
+            .map_err(compilation_error)?;
+        let test_compilation = compile_test_prepared_db(
+            &db,
+            TestsCompilationConfig {
+                starknet: false,
+                add_statements_functions: false,
+            },
+            main_crate_ids.clone(),
+            main_crate_ids,
+        )
+        .map_err(compilation_error)?;
+        let tests = test_compilation
+            .named_tests
+            .into_iter()
+            .map(|(name, test_config)| {
+                let expectation = match test_config.expectation {
+                    CairoTestExpectation::Success => TestExpectation::Success,
+                    CairoTestExpectation::Panics(PanicExpectation::Any) => {
+                        TestExpectation::Panics(None)
+                    }
+                    CairoTestExpectation::Panics(PanicExpectation::Exact(panic_data)) => {
+                        TestExpectation::Panics(Some(
+                            panic_data
+                                .iter()
+                                .map(|felt| Felt252::from_str(&felt.to_string()))
+                                .collect::<Result<_, _>>()
+                                .map_err(compilation_error)?,
+                        ))
+                    }
+                };
+                Ok(TestCase {
+                    name,
+                    available_gas: test_config.available_gas,
+                    expectation,
+                    ignored: test_config.ignored,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(CompiledTests {
+            sierra_program: test_compilation.sierra_program,
+            tests,
+        })
+    })
+}
+
+#[derive(Deserialize)]
+struct ScarbManifest {
+    package: ScarbPackage,
+}
+
+#[derive(Deserialize)]
+struct ScarbPackage {
+    name: String,
+}
+
+// Sets up the crates of a project in the db, returning the ids of its main crates
+fn setup_crates(db: &mut RootDatabase, path: &Path) -> Result<Vec<CrateId>, Error> {
+    let scarb_manifest = path.join("Scarb.toml");
+    if !scarb_manifest.is_file() {
+        return setup_project(db, path).map_err(compilation_error);
+    }
+    let manifest: ScarbManifest =
+        toml::from_str(&fs::read_to_string(&scarb_manifest)?).map_err(compilation_error)?;
+    let crate_id = db.intern_crate(CrateLongId::Real(manifest.package.name.into()));
+    let root = Directory::Real(path.join("src"));
+    db.set_crate_config(crate_id, Some(CrateConfiguration::default_for_root(root)));
+    Ok(vec![crate_id])
+}
+
+// Loads what was compiled from the sources of a project from the cache, or compiles & caches it
+fn cached<T: Serialize + DeserializeOwned>(
+    path: &Path,
+    kind: &str,
+    cache_dir: Option<&Path>,
+    compile: impl FnOnce() -> Result<T, Error>,
+) -> Result<T, Error> {
+    let Some(cache_dir) = cache_dir else {
+        return compile();
+    };
+    let cache_file = cache_dir.join(format!("{}.json", sources_hash(path, kind)?));
+    // A cache file which can't be read is compiled again
+    if let Some(compiled) = fs::read(&cache_file)
+        .ok()
+        .and_then(|cached| serde_json::from_slice(&cached).ok())
+    {
+        return Ok(compiled);
+    }
+    let compiled = compile()?;
+    fs::create_dir_all(cache_dir)?;
+    let serialized = serde_json::to_vec(&compiled).map_err(compilation_error)?;
+    fs::write(&cache_file, serialized)?;
+    Ok(compiled)
+}
+
+// Hashes the sources of a project, along with the kind of compilation they're used for
+fn sources_hash(path: &Path, kind: &str) -> Result<String, Error> {
+    let mut hasher = Sha256::new();
+    hasher.update(current_compiler_version_id().to_string());
+    hasher.update([0]);
+    hasher.update(CRATE_VERSION);
+    hasher.update([0]);
+    hasher.update(kind);
+    for source in project_sources(path)? {
+        hasher.update([0]);
+        hasher.update(source.canonicalize()?.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(fs::read(&source)?);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// Lists the files a project's compilation can depend on, sorted so they're always hashed in the same order
+fn project_sources(path: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut sources = Vec::new();
+    if path.is_dir() {
+        collect_sources(path, &mut sources)?;
+    } else {
+        sources.push(path.to_path_buf());
+        // A file's submodules are found in the directory named after it, or next to it for a `lib.cairo`
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let modules_dir = match path.file_stem() {
+            Some(stem) if stem != "lib" => dir.join(stem),
+            _ => dir.to_path_buf(),
+        };
+        if modules_dir.is_dir() {
+            collect_sources(&modules_dir, &mut sources)?;
+        }
+    }
+    sources.sort();
+    sources.dedup();
+    Ok(sources)
+}
+
+// Collects the `.cairo` & `.toml` files of a directory, skipping build outputs & hidden directories
+fn collect_sources(dir: &Path, sources: &mut Vec<PathBuf>) -> Result<(), Error> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden_or_target = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| name.starts_with('.') || name == "target");
+        if path.is_dir() {
+            if !hidden_or_target {
+                collect_sources(&path, sources)?;
+            }
+        } else if path.extension().map_or(false, |extension| {
+            extension == "cairo" || extension == "toml"
+        }) {
+            sources.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn compilation_error(error: impl ToString) -> Error {
+    Error::SierraCompilation(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    const SCARB_PACKAGE: &str = "../cairo_programs/cairo-1-programs/scarb_package";
+
+    #[test]
+    fn compile_scarb_package_tests() {
+        let compiled_tests = compile_cairo_tests(Path::new(SCARB_PACKAGE), None).unwrap();
+        let test = |name: &str| {
+            compiled_tests
+                .tests
+                .iter()
+                .find(|test| test.name.ends_with(&format!("::{name}")))
+                .unwrap()
+        };
+        assert!(compiled_tests
+            .tests
+            .iter()
+            .all(|test| test.name.starts_with("scarb_package::")));
+        assert_eq!(test("test_sum_to").expectation, TestExpectation::Success);
+        assert_eq!(
+            test("test_division_by_zero").expectation,
+            TestExpectation::Panics(Some(vec![Felt252::from_bytes_be_slice(
+                b"division by zero"
+            )]))
+        );
+        assert!(test("test_ignored").ignored);
+        assert_eq!(test("test_with_available_gas").available_gas, Some(1000000));
+    }
+
+    #[test]
+    fn cache_compiled_program() {
+        // Unique to this run, so that concurrent runs don't share their cache
+        let cache_dir = std::env::temp_dir().join(format!(
+            "cairo1-run-compile-test-cache-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let path = Path::new(SCARB_PACKAGE);
+        let program = compile_cairo_program(path, false, Some(&cache_dir)).unwrap();
+        let cache_file = cache_dir.join(format!("{}.json", sources_hash(path, "program").unwrap()));
+        assert!(cache_file.is_file());
+        // The cached program is loaded instead of compiling it again
+        assert_eq!(
+            compile_cairo_program(path, false, Some(&cache_dir)).unwrap(),
+            program
+        );
+        // Programs withdrawing gas are cached separately
+        assert_ne!(
+            sources_hash(path, "program").unwrap(),
+            sources_hash(path, "program_withdrawing_gas").unwrap()
+        );
+        fs::write(&cache_file, "invalid").unwrap();
+        assert_eq!(
+            compile_cairo_program(path, false, Some(&cache_dir)).unwrap(),
+            program
+        );
+        fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn sources_of_single_file() {
+        let sources = project_sources(Path::new(
+            "../cairo_programs/cairo-1-programs/with_tests/failing_tests.cairo",
+        ))
+        .unwrap();
+        assert_eq!(
+            sources,
+            [PathBuf::from(
+                "../cairo_programs/cairo-1-programs/with_tests/failing_tests.cairo"
+            )]
+        );
+        assert_matches!(
+            compile_cairo_program(Path::new("missing.cairo"), false, None),
+            Err(Error::SierraCompilation(_))
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use crate::error::Error;
+use cairo_lang_starknet_classes::{
+    casm_contract_class::{CasmContractClass, CasmContractEntryPoint},
+    contract_class::ContractClass,
+};
+use cairo_vm::{
+    hint_processor::cairo_1_hint_processor::syscall_handler::{
+        run_contract_entrypoint, selector_from_name, CallInfo, ExecutionInfo,
+        InMemorySyscallHandler, StarknetState,
+    },
+    vm::runners::cairo_runner::RunResources,
+    Felt252,
+};
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+// The contract class is declared & deployed under these in the state its entry points are run on
+const CLASS_HASH: Felt252 = Felt252::ONE;
+const CONTRACT_ADDRESS: Felt252 = Felt252::ONE;
+
+/// Loads a contract class from its JSON, either a `CasmContractClass`, or a Sierra `ContractClass`
+/// which is compiled into one
+pub fn load_contract_class(contract_class_json: &[u8]) -> Result<CasmContractClass, Error> {
+    if let Ok(casm_contract_class) = serde_json::from_slice(contract_class_json) {
+        return Ok(casm_contract_class);
+    }
+    let contract_class: ContractClass =
+        serde_json::from_slice(contract_class_json).map_err(Error::ContractClassJson)?;
+    CasmContractClass::from_contract_class(contract_class, false, usize::MAX)
+        .map_err(|error| Error::ContractClassCompilation(error.to_string()))
+}
+
+/// Finds an external entry point of a contract class by its selector, given as a decimal or
+/// `0x`-prefixed hex number, or by its name
+pub fn find_entry_point<'a>(
+    contract_class: &'a CasmContractClass,
+    entry_point: &str,
+) -> Result<&'a CasmContractEntryPoint, Error> {
+    // Entry point names are identifiers, which can't start with a digit
+    let selector = if entry_point.starts_with(|c: char| c.is_ascii_digit()) {
+        Felt252::from_str(entry_point)
+            .map_err(|_| Error::ArgumentsParse(format!("Invalid selector: {entry_point}")))?
+    } else {
+        selector_from_name(entry_point)
+    };
+    contract_class
+        .entry_points_by_type
+        .external
+        .iter()
+        .find(|entry_point| Felt252::from(&entry_point.selector) == selector)
+        .ok_or(Error::EntryPointNotFound(selector))
+}
+
+/// Runs an entry point of a contract class with the given calldata & gas, using the `Cairo1HintProcessor`.
+/// The contract is deployed on an empty in-memory Starknet state, which handles its syscalls,
+/// so it can use its storage, emit events or call itself
+pub fn run_contract_entry_point(
+    contract_class: &CasmContractClass,
+    entry_point: &CasmContractEntryPoint,
+    calldata: &[Felt252],
+    initial_gas: u64,
+) -> Result<CallInfo, Error> {
+    let mut state = StarknetState::default();
+    state.declare_class(CLASS_HASH, contract_class.clone());
+    state.deploy_contract(CONTRACT_ADDRESS, CLASS_HASH);
+    let execution_info = ExecutionInfo {
+        contract_address: CONTRACT_ADDRESS,
+        entry_point_selector: Felt252::from(&entry_point.selector),
+        ..Default::default()
+    };
+    Ok(run_contract_entrypoint(
+        contract_class,
+        entry_point,
+        calldata,
+        initial_gas,
+        &mut RunResources::default(),
+        Box::new(InMemorySyscallHandler::new(
+            Arc::new(Mutex::new(state)),
+            execution_info,
+        )),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    const SYSCALLS_CONTRACT: &str = "../cairo_programs/cairo-2-contracts/syscalls";
+
+    #[test]
+    fn load_sierra_and_casm_contract_classes() {
+        let casm_contract_class =
+            load_contract_class(&std::fs::read(format!("{SYSCALLS_CONTRACT}.casm")).unwrap())
+                .unwrap();
+        let compiled_contract_class =
+            load_contract_class(&std::fs::read(format!("{SYSCALLS_CONTRACT}.sierra")).unwrap())
+                .unwrap();
+        let selectors = |contract_class: &CasmContractClass| {
+            contract_class
+                .entry_points_by_type
+                .external
+                .iter()
+                .map(|entry_point| entry_point.selector.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            selectors(&casm_contract_class),
+            selectors(&compiled_contract_class)
+        );
+        assert_matches!(load_contract_class(b"{}"), Err(Error::ContractClassJson(_)));
+    }
+
+    #[test]
+    fn find_entry_point_by_name_or_selector() {
+        let contract_class =
+            load_contract_class(&std::fs::read(format!("{SYSCALLS_CONTRACT}.casm")).unwrap())
+                .unwrap();
+        let selector = selector_from_name("get_value");
+        let entry_point = find_entry_point(&contract_class, "get_value").unwrap();
+        assert_eq!(Felt252::from(&entry_point.selector), selector);
+        assert_eq!(
+            find_entry_point(&contract_class, &selector.to_hex_string())
+                .unwrap()
+                .offset,
+            entry_point.offset
+        );
+        assert_eq!(
+            find_entry_point(&contract_class, &selector.to_string())
+                .unwrap()
+                .offset,
+            entry_point.offset
+        );
+        assert_matches!(
+            find_entry_point(&contract_class, "missing"),
+            Err(Error::EntryPointNotFound(selector)) if selector == selector_from_name("missing")
+        );
+        assert_matches!(
+            find_entry_point(&contract_class, "0xz"),
+            Err(Error::ArgumentsParse(_))
+        );
+    }
+
+    #[test]
+    fn run_entry_points_on_in_memory_state() {
+        let contract_class =
+            load_contract_class(&std::fs::read(format!("{SYSCALLS_CONTRACT}.casm")).unwrap())
+                .unwrap();
+        let run = |name: &str, calldata: &[Felt252]| {
+            let entry_point = find_entry_point(&contract_class, name).unwrap();
+            run_contract_entry_point(&contract_class, entry_point, calldata, 100_000_000).unwrap()
+        };
+
+        let call_info = run("get_value", &[5.into()]);
+        assert!(!call_info.failed);
+        assert_eq!(call_info.retdata, [Felt252::ZERO]);
+        assert!(call_info.remaining_gas < 100_000_000);
+        assert!(call_info.execution_resources.n_steps > 0);
+        // Calls to itself are run on the same state
+        assert_eq!(
+            run("get_value_from", &[CONTRACT_ADDRESS, 5.into()]).retdata,
+            [Felt252::ZERO]
+        );
+
+        let call_info = run("set_value_and_fail", &[6.into(), 8.into()]);
+        assert!(call_info.failed);
+        assert_eq!(
+            call_info.retdata,
+            [Felt252::from_bytes_be_slice(b"failure")]
+        );
+    }
+}
@@ -5,8 +5,8 @@ use cairo_vm::{
     cairo_run::EncodeTraceError,
     types::errors::program_errors::ProgramError,
     vm::errors::{
-        memory_errors::MemoryError, runner_errors::RunnerError, trace_errors::TraceError,
-        vm_errors::VirtualMachineError,
+        cairo_run_errors::CairoRunError, memory_errors::MemoryError, runner_errors::RunnerError,
+        trace_errors::TraceError, vm_errors::VirtualMachineError,
     },
     Felt252,
 };
@@ -42,10 +42,16 @@ pub enum Error {
     Memory(#[from] MemoryError),
     #[error("Program panicked with {0:?}")]
     RunPanic(Vec<Felt252>),
+    #[error("Program ran out of gas")]
+    OutOfGas,
+    #[error("Not enough gas to call main: {required} required, {available} available")]
+    NotEnoughGasToCall { required: usize, available: usize },
     #[error("Function signature has no return types")]
     NoRetTypesInSignature,
     #[error("No size for concrete type id: {0}")]
     NoTypeSizeForId(ConcreteTypeId),
+    #[error("Failed to compute the sizes of the types of the sierra program")]
+    NoTypeSizes,
     #[error("Concrete type id has no debug name: {0}")]
     TypeIdNoDebugName(ConcreteTypeId),
     #[error("No info in sierra program registry for concrete type id: {0}")]
@@ -59,4 +65,46 @@ pub enum Error {
         param_index: usize,
         arg_index: usize,
     },
+    #[error("Failed to parse arguments: {0}")]
+    ArgumentsParse(String),
+    #[error("Failed to parse JSON arguments")]
+    ArgumentsJson(#[from] serde_json::Error),
+    #[error("Function expects {expected} arguments and received {actual} instead.")]
+    ArgumentsCountMismatch { expected: usize, actual: usize },
+    #[error("Function param {param_index} of type {param_type} can't be given the value {value}.")]
+    InvalidArgument {
+        param_index: usize,
+        param_type: ConcreteTypeId,
+        value: serde_json::Value,
+    },
+    #[error("Function params of type {0} can't be given as JSON arguments")]
+    UnsupportedArgumentType(ConcreteTypeId),
+    #[error("Return value of type {0} is malformed")]
+    InvalidReturnValue(ConcreteTypeId),
+    #[error("Return values of type {0} can't be serialized into JSON")]
+    UnsupportedReturnType(ConcreteTypeId),
+    #[error("Failed to parse contract class")]
+    ContractClassJson(serde_json::Error),
+    #[error("Failed to compile contract class:\n {0}")]
+    ContractClassCompilation(String),
+    #[error("Contract class has no external entry point with selector {0:#x}")]
+    EntryPointNotFound(Felt252),
+    #[error(transparent)]
+    CairoRun(#[from] CairoRunError),
+    #[error("{0} tests failed")]
+    TestsFailed(usize),
+}
+
+// The panic data of a run which ran out of gas
+pub(crate) const OUT_OF_GAS_PANIC: &[u8] = b"Out of gas";
+
+impl Error {
+    /// Returns the error of a run which panicked with the given data, telling apart running out of gas
+    pub fn from_panic_data(panic_data: Vec<Felt252>) -> Self {
+        if panic_data == [Felt252::from_bytes_be_slice(OUT_OF_GAS_PANIC)] {
+            Error::OutOfGas
+        } else {
+            Error::RunPanic(panic_data)
+        }
+    }
 }
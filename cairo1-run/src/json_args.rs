@@ -0,0 +1,437 @@
+use crate::{
+    cairo_run::{find_function, get_info},
+    error::Error,
+    json_output::UserTypeNames,
+    FuncArg,
+};
+use cairo_lang_sierra::{
+    extensions::{
+        bitwise::BitwiseType,
+        core::{CoreLibfunc, CoreType, CoreTypeConcrete},
+        ec::EcOpType,
+        gas::GasBuiltinType,
+        pedersen::PedersenType,
+        poseidon::PoseidonType,
+        range_check::RangeCheckType,
+        segment_arena::SegmentArenaType,
+        starknet::syscalls::SystemType,
+        types::TypeInfo,
+        NamedType,
+    },
+    ids::ConcreteTypeId,
+    program::{Function, GenericArg, Program as SierraProgram},
+    program_registry::ProgramRegistry,
+};
+use cairo_lang_sierra_type_size::get_type_size_map;
+use cairo_lang_utils::unordered_hash_map::UnorderedHashMap;
+use cairo_vm::{utils::CAIRO_PRIME, Felt252};
+use num_bigint::BigInt;
+use num_traits::{One, Signed, Zero};
+use serde_json::Value;
+
+/// Parses the arguments of the `main` function of a Sierra program from a JSON array, which holds
+/// a value for each of its parameters, builtins & implicit parameters (such as `System`) excluded.
+/// Each value is checked against the Sierra type of its parameter, and serialized into the felts
+/// (and segments, for arrays) of that type:
+/// * `felt252`, integers, `bytes31` & `u256`: numbers, or strings holding decimal or `0x`-prefixed hex numbers
+/// * `bool`: `true` or `false`
+/// * `ByteArray`: strings
+/// * `Array<T>` & `Span<T>`: arrays of `T` values
+/// * Structs: objects holding the value of each member by its name, such as `{"x": 1, "y": 2}`,
+///   when their member names are in `type_names`, or arrays holding the value of each member
+/// * Tuples: arrays holding the value of each member, `null` for `()`
+/// * `Option<T>`: `null` or a `T` value
+/// * Enums: objects such as `{"variant": "Circle", "value": 5}`, holding the name of the variant
+///   and its value, which can be left out for variants of type `()`. The variant can also be given
+///   by its index, and is only named when its name is in `type_names`, save for those of `Option`
+///   & `Result`
+///
+/// Return values serialized by `serialize_output_json` with the same `type_names` can be given back as arguments
+pub fn parse_json_args(
+    sierra_program: &SierraProgram,
+    type_names: &UserTypeNames,
+    json_args: &str,
+) -> Result<Vec<FuncArg>, Error> {
+    let main_func = find_function(sierra_program, "::main")?;
+    let sierra_program_registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(sierra_program)?;
+    let type_sizes =
+        get_type_size_map(sierra_program, &sierra_program_registry).ok_or(Error::NoTypeSizes)?;
+    let values: Vec<Value> = serde_json::from_str(json_args)?;
+    func_args_from_json(
+        &sierra_program_registry,
+        &type_sizes,
+        type_names,
+        main_func,
+        &values,
+    )
+}
+
+// Serializes the JSON values given to the explicit parameters of a function
+pub(crate) fn func_args_from_json(
+    sierra_program_registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    type_sizes: &UnorderedHashMap<ConcreteTypeId, i16>,
+    type_names: &UserTypeNames,
+    func: &Function,
+    values: &[Value],
+) -> Result<Vec<FuncArg>, Error> {
+    let mut param_types = Vec::new();
+    for ty in &func.signature.param_types {
+        let info = get_info(sierra_program_registry, ty)
+            .ok_or_else(|| Error::NoInfoForType(ty.clone()))?;
+        if !is_implicit_param(info) {
+            param_types.push(ty);
+        }
+    }
+    if param_types.len() != values.len() {
+        return Err(Error::ArgumentsCountMismatch {
+            expected: param_types.len(),
+            actual: values.len(),
+        });
+    }
+    let mut args = Vec::new();
+    for (param_index, (param_type, value)) in param_types.into_iter().zip(values).enumerate() {
+        let serializer = ArgSerializer {
+            sierra_program_registry,
+            type_sizes,
+            type_names,
+            param_index,
+            param_type,
+        };
+        serializer.serialize(param_type, value, &mut args)?;
+    }
+    Ok(args)
+}
+
+// Builtins & implicit parameters are provided by the entry code
+pub(crate) fn is_implicit_param(info: &TypeInfo) -> bool {
+    [
+        BitwiseType::ID,
+        EcOpType::ID,
+        GasBuiltinType::ID,
+        PedersenType::ID,
+        PoseidonType::ID,
+        RangeCheckType::ID,
+        SegmentArenaType::ID,
+        SystemType::ID,
+    ]
+    .contains(&info.long_id.generic_id)
+}
+
+// Serializes the value of a function param, which is used to report errors
+struct ArgSerializer<'a> {
+    sierra_program_registry: &'a ProgramRegistry<CoreType, CoreLibfunc>,
+    type_sizes: &'a UnorderedHashMap<ConcreteTypeId, i16>,
+    type_names: &'a UserTypeNames,
+    param_index: usize,
+    param_type: &'a ConcreteTypeId,
+}
+
+impl ArgSerializer<'_> {
+    fn invalid_argument(&self, value: &Value) -> Error {
+        Error::InvalidArgument {
+            param_index: self.param_index,
+            param_type: self.param_type.clone(),
+            value: value.clone(),
+        }
+    }
+
+    // Appends the serialization of a value of type `ty` to `args`
+    fn serialize(
+        &self,
+        ty: &ConcreteTypeId,
+        value: &Value,
+        args: &mut Vec<FuncArg>,
+    ) -> Result<(), Error> {
+        let concrete_type = self
+            .sierra_program_registry
+            .get_type(ty)
+            .map_err(|_| Error::NoInfoForType(ty.clone()))?;
+        let felt = match concrete_type {
+            CoreTypeConcrete::Felt252(_) => {
+                self.integer(value, BigInt::one() - prime(), prime())?
+            }
+            CoreTypeConcrete::Uint8(_) => self.unsigned(value, 8)?,
+            CoreTypeConcrete::Uint16(_) => self.unsigned(value, 16)?,
+            CoreTypeConcrete::Uint32(_) => self.unsigned(value, 32)?,
+            CoreTypeConcrete::Uint64(_) => self.unsigned(value, 64)?,
+            CoreTypeConcrete::Uint128(_) => self.unsigned(value, 128)?,
+            CoreTypeConcrete::Bytes31(_) => self.unsigned(value, 248)?,
+            CoreTypeConcrete::Sint8(_) => self.signed(value, 8)?,
+            CoreTypeConcrete::Sint16(_) => self.signed(value, 16)?,
+            CoreTypeConcrete::Sint32(_) => self.signed(value, 32)?,
+            CoreTypeConcrete::Sint64(_) => self.signed(value, 64)?,
+            CoreTypeConcrete::Sint128(_) => self.signed(value, 128)?,
+            CoreTypeConcrete::Snapshot(info) => return self.serialize(&info.ty, value, args),
+            CoreTypeConcrete::NonZero(info) => {
+                let mut inner_args = Vec::new();
+                self.serialize(&info.ty, value, &mut inner_args)?;
+                if inner_args
+                    .iter()
+                    .all(|arg| matches!(arg, FuncArg::Single(felt) if felt.is_zero()))
+                {
+                    return Err(self.invalid_argument(value));
+                }
+                args.extend(inner_args);
+                return Ok(());
+            }
+            CoreTypeConcrete::Array(info) => {
+                let elements = value
+                    .as_array()
+                    .ok_or_else(|| self.invalid_argument(value))?;
+                let mut element_args = Vec::new();
+                for element in elements {
+                    self.serialize(&info.ty, element, &mut element_args)?;
+                }
+                // Arrays of arrays need a segment for each inner array
+                let felts: Option<Vec<Felt252>> = element_args
+                    .iter()
+                    .map(|arg| match arg {
+                        FuncArg::Single(felt) => Some(*felt),
+                        _ => None,
+                    })
+                    .collect();
+                args.push(match felts {
+                    Some(felts) => FuncArg::Array(felts),
+                    None => FuncArg::Composed(element_args),
+                });
+                return Ok(());
+            }
+            CoreTypeConcrete::Struct(info) => {
+                return self.serialize_struct(
+                    user_type_name(&info.info),
+                    &info.members,
+                    value,
+                    args,
+                )
+            }
+            CoreTypeConcrete::Enum(info) => {
+                return self.serialize_enum(user_type_name(&info.info), &info.variants, value, args)
+            }
+            _ => return Err(Error::UnsupportedArgumentType(ty.clone())),
+        };
+        args.push(FuncArg::Single(felt));
+        Ok(())
+    }
+
+    fn serialize_struct(
+        &self,
+        name: &str,
+        members: &[ConcreteTypeId],
+        value: &Value,
+        args: &mut Vec<FuncArg>,
+    ) -> Result<(), Error> {
+        match value {
+            Value::String(string) if name == "core::byte_array::ByteArray" => {
+                let (data, pending_word, pending_word_len) = byte_array_words(string.as_bytes());
+                args.extend([
+                    FuncArg::Array(data),
+                    FuncArg::Single(pending_word),
+                    FuncArg::Single(pending_word_len.into()),
+                ]);
+            }
+            Value::Number(_) | Value::String(_) if name == "core::integer::u256" => {
+                let two_to_128 = BigInt::one() << 128;
+                let integer = self.parse_integer(value, BigInt::zero(), BigInt::one() << 256)?;
+                args.extend([
+                    FuncArg::Single((&integer % &two_to_128).into()),
+                    FuncArg::Single((integer / two_to_128).into()),
+                ]);
+            }
+            // A span holds a snapshot of the array it is given as
+            Value::Array(_) if name.starts_with("core::array::Span::") && members.len() == 1 => {
+                self.serialize(&members[0], value, args)?
+            }
+            Value::Array(member_values) if member_values.len() == members.len() => {
+                for (member, member_value) in members.iter().zip(member_values) {
+                    self.serialize(member, member_value, args)?
+                }
+            }
+            Value::Object(member_values) if member_values.len() == members.len() => {
+                let member_names = self
+                    .type_names
+                    .member_names(name, members.len())
+                    .ok_or_else(|| self.invalid_argument(value))?;
+                for (member, member_name) in members.iter().zip(member_names) {
+                    let member_value = member_values
+                        .get(member_name)
+                        .ok_or_else(|| self.invalid_argument(value))?;
+                    self.serialize(member, member_value, args)?
+                }
+            }
+            Value::Null if members.is_empty() => {}
+            _ => return Err(self.invalid_argument(value)),
+        }
+        Ok(())
+    }
+
+    fn serialize_enum(
+        &self,
+        name: &str,
+        variants: &[ConcreteTypeId],
+        value: &Value,
+        args: &mut Vec<FuncArg>,
+    ) -> Result<(), Error> {
+        let variant_names = self.type_names.variant_names(name, variants.len());
+        let named_variant_idx = value
+            .get("variant")
+            .and_then(Value::as_str)
+            .and_then(|variant_name| variant_names.iter().position(|name| *name == variant_name));
+        let is_option = name.starts_with("core::option::Option::");
+        let (variant_idx, variant_value) = match (value, named_variant_idx) {
+            // Variants can be given by their name, as output by `serialize_output_json`, or by their index
+            (_, Some(variant_idx)) => (variant_idx, value.get("value")),
+            (Value::Bool(boolean), _) if name == "core::bool" => (*boolean as usize, None),
+            // Option::Some is the first variant
+            (Value::Null, _) if is_option => (1, None),
+            (_, _) if is_option => (0, Some(value)),
+            (Value::Object(object), _) => {
+                let variant_idx = object
+                    .get("variant")
+                    .and_then(Value::as_u64)
+                    .and_then(|idx| usize::try_from(idx).ok())
+                    .ok_or_else(|| self.invalid_argument(value))?;
+                (variant_idx, object.get("value"))
+            }
+            _ => return Err(self.invalid_argument(value)),
+        };
+        let variant_type_id = variants
+            .get(variant_idx)
+            .ok_or_else(|| self.invalid_argument(value))?;
+        let mut variant_args = Vec::new();
+        match variant_value {
+            Some(variant_value) => {
+                self.serialize(variant_type_id, variant_value, &mut variant_args)?
+            }
+            // Unit variants can be given without a value
+            None => self
+                .serialize(
+                    variant_type_id,
+                    &Value::Array(Vec::new()),
+                    &mut variant_args,
+                )
+                .map_err(|_| self.invalid_argument(value))?,
+        }
+        // Sierra variant indexes are converted into casm ones, which are the (odd) offsets of the
+        // variants in the jump table of a match, see `serialize_output_inner`
+        let num_variants = variants.len();
+        let casm_variant_idx = if num_variants > 2 {
+            2 * (num_variants - variant_idx) - 1
+        } else {
+            variant_idx
+        };
+        args.push(FuncArg::Single(casm_variant_idx.into()));
+        // Space is always allocated for the largest enum member, padding with zeros in front for the smaller variants
+        let mut max_variant_size = 0;
+        for variant in variants {
+            let variant_size = self
+                .type_sizes
+                .get(variant)
+                .ok_or_else(|| Error::NoTypeSizeForId(variant.clone()))?;
+            max_variant_size = std::cmp::max(max_variant_size, *variant_size)
+        }
+        let variant_size = self
+            .type_sizes
+            .get(variant_type_id)
+            .ok_or_else(|| Error::NoTypeSizeForId(variant_type_id.clone()))?;
+        for _ in 0..max_variant_size - variant_size {
+            args.push(FuncArg::Single(Felt252::ZERO));
+        }
+        args.extend(variant_args);
+        Ok(())
+    }
+
+    fn unsigned(&self, value: &Value, bits: usize) -> Result<Felt252, Error> {
+        self.integer(value, BigInt::zero(), BigInt::one() << bits)
+    }
+
+    fn signed(&self, value: &Value, bits: usize) -> Result<Felt252, Error> {
+        let bound = BigInt::one() << (bits - 1);
+        self.integer(value, -&bound, bound)
+    }
+
+    // Parses an integer in the range [min, max) into a felt
+    fn integer(&self, value: &Value, min: BigInt, max: BigInt) -> Result<Felt252, Error> {
+        Ok(Felt252::from(&self.parse_integer(value, min, max)?))
+    }
+
+    fn parse_integer(&self, value: &Value, min: BigInt, max: BigInt) -> Result<BigInt, Error> {
+        parse_integer(value)
+            .filter(|integer| min <= *integer && *integer < max)
+            .ok_or_else(|| self.invalid_argument(value))
+    }
+}
+
+fn prime() -> BigInt {
+    BigInt::from(CAIRO_PRIME.clone())
+}
+
+// Integers can be given as numbers, or as strings holding decimal or `0x`-prefixed hex numbers
+fn parse_integer(value: &Value) -> Option<BigInt> {
+    let string = match value {
+        Value::Number(number) => number.to_string(),
+        Value::String(string) => string.clone(),
+        _ => return None,
+    };
+    let (negative, digits) = match string.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, string.as_str()),
+    };
+    let integer = match digits.strip_prefix("0x") {
+        Some(hex_digits) => BigInt::parse_bytes(hex_digits.as_bytes(), 16)?,
+        None => BigInt::parse_bytes(digits.as_bytes(), 10)?,
+    };
+    // Reject strings such as "--1"
+    if integer.is_negative() {
+        return None;
+    }
+    Some(if negative { -integer } else { integer })
+}
+
+// Returns the name of a user defined type, such as `core::bool`
+pub(crate) fn user_type_name(info: &TypeInfo) -> &str {
+    match info.long_id.generic_args.first() {
+        Some(GenericArg::UserType(user_type)) => user_type.debug_name.as_deref().unwrap_or(""),
+        _ => "",
+    }
+}
+
+// Splits a byte array into the words of a `ByteArray`: its full 31-byte words, the pending word & its length
+fn byte_array_words(bytes: &[u8]) -> (Vec<Felt252>, Felt252, usize) {
+    let mut chunks = bytes.chunks_exact(31);
+    let data = chunks.by_ref().map(Felt252::from_bytes_be_slice).collect();
+    let pending_word = chunks.remainder();
+    (
+        data,
+        Felt252::from_bytes_be_slice(pending_word),
+        pending_word.len(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_json_integers() {
+        assert_eq!(parse_integer(&json!(17)), Some(BigInt::from(17)));
+        assert_eq!(parse_integer(&json!(-17)), Some(BigInt::from(-17)));
+        assert_eq!(parse_integer(&json!("0x11")), Some(BigInt::from(17)));
+        assert_eq!(parse_integer(&json!("-0x11")), Some(BigInt::from(-17)));
+        assert_eq!(parse_integer(&json!("--17")), None);
+        assert_eq!(parse_integer(&json!("1.5")), None);
+        assert_eq!(parse_integer(&json!(true)), None);
+    }
+
+    #[test]
+    fn split_byte_array_words() {
+        assert_eq!(
+            byte_array_words(b"hello"),
+            (Vec::new(), Felt252::from(0x68656c6c6f_u64), 5)
+        );
+        let (data, pending_word, pending_word_len) = byte_array_words(&[b'a'; 33]);
+        assert_eq!(data, vec![Felt252::from_bytes_be_slice(&[b'a'; 31])]);
+        assert_eq!(pending_word, Felt252::from(0x6161_u64));
+        assert_eq!(pending_word_len, 2);
+    }
+}
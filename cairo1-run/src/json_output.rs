@@ -0,0 +1,424 @@
+use crate::{
+    cairo_run::{find_function, get_info},
+    error::Error,
+    json_args::{is_implicit_param, user_type_name},
+};
+use cairo_lang_sierra::{
+    extensions::core::{CoreLibfunc, CoreType, CoreTypeConcrete},
+    ids::ConcreteTypeId,
+    program::Program as SierraProgram,
+    program_registry::ProgramRegistry,
+};
+use cairo_lang_sierra_type_size::get_type_size_map;
+use cairo_lang_utils::unordered_hash_map::UnorderedHashMap;
+use cairo_vm::{
+    math_utils::signed_felt,
+    types::relocatable::{MaybeRelocatable, Relocatable},
+    vm::vm_core::VirtualMachine,
+    Felt252,
+};
+use itertools::Itertools;
+use num_traits::{ToPrimitive, Zero};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use std::{collections::HashMap, fmt::Display, slice::Iter};
+
+/// Names of the members of the structs & of the variants of the enums declared by a Cairo program,
+/// by the path of their type (such as `my_crate::Point`), as returned by
+/// `compile_cairo_program_with_type_names`. Sierra programs only hold the names of their types
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserTypeNames {
+    pub members: HashMap<String, Vec<String>>,
+    pub variants: HashMap<String, Vec<String>>,
+}
+
+impl UserTypeNames {
+    // Looks up the names of a type's members or variants, by the name of the type, which is
+    // followed by its generic arguments for generic types
+    fn get<'a>(
+        names: &'a HashMap<String, Vec<String>>,
+        type_name: &str,
+        len: usize,
+    ) -> Option<&'a [String]> {
+        let path = type_name
+            .split_once("::<")
+            .map_or(type_name, |(path, _)| path);
+        names
+            .get(path)
+            .map(Vec::as_slice)
+            .filter(|names| names.len() == len)
+    }
+
+    // Names of the members of a struct, in order, if they are known
+    pub(crate) fn member_names(&self, type_name: &str, num_members: usize) -> Option<&[String]> {
+        Self::get(&self.members, type_name, num_members)
+    }
+
+    // Names of the variants of an enum, in order, those of `Option` & `Result` being known without
+    // `type_names`. Empty if they aren't known
+    pub(crate) fn variant_names(&self, type_name: &str, num_variants: usize) -> Vec<&str> {
+        if let Some(names) = Self::get(&self.variants, type_name, num_variants) {
+            return names.iter().map(String::as_str).collect();
+        }
+        if type_name.starts_with("core::option::Option::") {
+            vec!["Some", "None"]
+        } else if type_name.starts_with("core::result::Result::") {
+            vec!["Ok", "Err"]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Decodes the return value of the `main` function of a Sierra program into JSON, following its
+/// Sierra type. `return_values` & `vm` are the return values & the runner's VM returned by `cairo_run_program`.
+/// Struct members & enum variants are named after `type_names`, and values are decoded as:
+/// * `felt252`, integers, `bytes31` & `u256`: numbers
+/// * `bool`: `true` or `false`
+/// * `ByteArray`: strings
+/// * `Array<T>` & `Span<T>`: arrays of `T` values
+/// * Structs: objects holding the value of each member by its name, such as `{"x": 1, "y": 2}`
+/// * Tuples & structs whose member names aren't in `type_names`: arrays holding the value of each
+///   member, as taken by `parse_json_args`, `null` for `()`
+/// * `Nullable<T>`: `null` or a `T` value
+/// * `Felt252Dict<T>` & `SquashedFelt252Dict<T>`: objects holding the `T` value of each key
+/// * Enums: objects such as `{"variant": "Some", "value": 5}`, holding the name of the variant and
+///   its value, which is left out for variants of type `()`. Variants whose name isn't in
+///   `type_names` are named by their index, save for those of `Option` & `Result`
+///
+/// The names of struct members & enum variants aren't held by Sierra programs, nor by their debug
+/// info, so the output of a program loaded from a Sierra file, with an empty `type_names`, holds
+/// arrays for its structs and indexes for its variants
+pub fn serialize_output_json(
+    sierra_program: &SierraProgram,
+    type_names: &UserTypeNames,
+    vm: &VirtualMachine,
+    return_values: &[MaybeRelocatable],
+) -> Result<Value, Error> {
+    let main_func = find_function(sierra_program, "::main")?;
+    let sierra_program_registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(sierra_program)?;
+    let type_sizes =
+        get_type_size_map(sierra_program, &sierra_program_registry).ok_or(Error::NoTypeSizes)?;
+    let return_type_id = match main_func.signature.ret_types.last() {
+        Some(return_type_id) => return_type_id,
+        None => return Ok(Value::Null),
+    };
+    let return_type_info = get_info(&sierra_program_registry, return_type_id)
+        .ok_or_else(|| Error::NoInfoForType(return_type_id.clone()))?;
+    // Functions without a return value only return their builtins
+    if is_implicit_param(return_type_info) {
+        return Ok(Value::Null);
+    }
+    let decoder = OutputDecoder {
+        vm,
+        sierra_program_registry: &sierra_program_registry,
+        type_sizes: &type_sizes,
+        type_names,
+    };
+    decoder.decode(return_type_id, &mut return_values.iter())
+}
+
+// Decodes return values, fetching the values they point to from the VM's memory
+struct OutputDecoder<'a> {
+    vm: &'a VirtualMachine,
+    sierra_program_registry: &'a ProgramRegistry<CoreType, CoreLibfunc>,
+    type_sizes: &'a UnorderedHashMap<ConcreteTypeId, i16>,
+    type_names: &'a UserTypeNames,
+}
+
+impl OutputDecoder<'_> {
+    // Decodes a value of type `ty`, consuming its felts from `values`
+    fn decode(
+        &self,
+        ty: &ConcreteTypeId,
+        values: &mut Iter<'_, MaybeRelocatable>,
+    ) -> Result<Value, Error> {
+        let concrete_type = self
+            .sierra_program_registry
+            .get_type(ty)
+            .map_err(|_| Error::NoInfoForType(ty.clone()))?;
+        let invalid = || Error::InvalidReturnValue(ty.clone());
+        Ok(match concrete_type {
+            CoreTypeConcrete::Felt252(_)
+            | CoreTypeConcrete::Bytes31(_)
+            | CoreTypeConcrete::Uint8(_)
+            | CoreTypeConcrete::Uint16(_)
+            | CoreTypeConcrete::Uint32(_)
+            | CoreTypeConcrete::Uint64(_)
+            | CoreTypeConcrete::Uint128(_) => json_number(next_int(values).ok_or_else(invalid)?),
+            CoreTypeConcrete::Sint8(_)
+            | CoreTypeConcrete::Sint16(_)
+            | CoreTypeConcrete::Sint32(_)
+            | CoreTypeConcrete::Sint64(_)
+            | CoreTypeConcrete::Sint128(_) => {
+                json_number(signed_felt(next_int(values).ok_or_else(invalid)?))
+            }
+            CoreTypeConcrete::NonZero(info) => self.decode(&info.ty, values)?,
+            CoreTypeConcrete::Snapshot(info) => self.decode(&info.ty, values)?,
+            CoreTypeConcrete::Box(info) => {
+                let ptr = next_relocatable(values).ok_or_else(invalid)?;
+                self.decode_pointed(&info.ty, ptr)?
+            }
+            CoreTypeConcrete::Nullable(info) => match values.next() {
+                Some(MaybeRelocatable::RelocatableValue(ptr)) => {
+                    self.decode_pointed(&info.ty, *ptr)?
+                }
+                Some(MaybeRelocatable::Int(felt)) if felt.is_zero() => Value::Null,
+                _ => return Err(invalid()),
+            },
+            CoreTypeConcrete::Array(info) => {
+                let array_start = next_relocatable(values).ok_or_else(invalid)?;
+                let array_end = next_relocatable(values).ok_or_else(invalid)?;
+                let array_size = (array_end - array_start).map_err(|_| invalid())?;
+                let array_data = self.vm.get_continuous_range(array_start, array_size)?;
+                let mut array_data_iter = array_data.iter();
+                let mut elements = Vec::new();
+                while !array_data_iter.as_slice().is_empty() {
+                    elements.push(self.decode(&info.ty, &mut array_data_iter)?);
+                }
+                Value::Array(elements)
+            }
+            CoreTypeConcrete::Struct(info) => {
+                self.decode_struct(ty, user_type_name(&info.info), &info.members, values)?
+            }
+            CoreTypeConcrete::Enum(info) => {
+                self.decode_enum(ty, user_type_name(&info.info), &info.variants, values)?
+            }
+            CoreTypeConcrete::Felt252Dict(info) => {
+                // The dictionary's segment ends at the dictionary's pointer
+                let dict_ptr = next_relocatable(values).ok_or_else(invalid)?;
+                if self.vm.get_segment_size(dict_ptr.segment_index as usize)
+                    != Some(dict_ptr.offset)
+                {
+                    return Err(invalid());
+                }
+                self.decode_dict(
+                    ty,
+                    &info.ty,
+                    (dict_ptr.segment_index, 0).into(),
+                    dict_ptr.offset,
+                )?
+            }
+            CoreTypeConcrete::SquashedFelt252Dict(info) => {
+                let dict_start = next_relocatable(values).ok_or_else(invalid)?;
+                let dict_end = next_relocatable(values).ok_or_else(invalid)?;
+                let dict_size = (dict_end - dict_start).map_err(|_| invalid())?;
+                self.decode_dict(ty, &info.ty, dict_start, dict_size)?
+            }
+            _ => return Err(Error::UnsupportedReturnType(ty.clone())),
+        })
+    }
+
+    // Decodes a value of type `ty` stored at `ptr`
+    fn decode_pointed(&self, ty: &ConcreteTypeId, ptr: Relocatable) -> Result<Value, Error> {
+        let data = self.vm.get_continuous_range(ptr, self.type_size(ty)?)?;
+        self.decode(ty, &mut data.iter())
+    }
+
+    fn decode_struct(
+        &self,
+        ty: &ConcreteTypeId,
+        name: &str,
+        members: &[ConcreteTypeId],
+        values: &mut Iter<'_, MaybeRelocatable>,
+    ) -> Result<Value, Error> {
+        let invalid = || Error::InvalidReturnValue(ty.clone());
+        match name {
+            "core::byte_array::ByteArray" => {
+                // Made up of an array of full 31-byte words, the pending word & its length
+                let data_start = next_relocatable(values).ok_or_else(invalid)?;
+                let data_end = next_relocatable(values).ok_or_else(invalid)?;
+                let data_size = (data_end - data_start).map_err(|_| invalid())?;
+                let words: Vec<Felt252> = self
+                    .vm
+                    .get_integer_range(data_start, data_size)?
+                    .iter()
+                    .map(|word| *word.as_ref())
+                    .collect();
+                let pending_word = next_int(values).ok_or_else(invalid)?;
+                let pending_word_len = next_int(values)
+                    .and_then(|len| len.to_usize())
+                    .filter(|len| *len < 31)
+                    .ok_or_else(invalid)?;
+                let bytes = byte_array_bytes(&words, &pending_word, pending_word_len);
+                Ok(Value::String(String::from_utf8_lossy(&bytes).into_owned()))
+            }
+            "core::integer::u256" => {
+                let low = next_int(values).ok_or_else(invalid)?;
+                let high = next_int(values).ok_or_else(invalid)?;
+                Ok(json_number(
+                    low.to_biguint() + (high.to_biguint() << 128_usize),
+                ))
+            }
+            _ if name.starts_with("core::array::Span::") && members.len() == 1 => {
+                self.decode(&members[0], values)
+            }
+            _ if members.is_empty() => Ok(Value::Null),
+            _ => {
+                let mut member_values = Vec::new();
+                for member in members {
+                    member_values.push(self.decode(member, values)?);
+                }
+                match self.type_names.member_names(name, members.len()) {
+                    Some(member_names) => Ok(Value::Object(
+                        member_names.iter().cloned().zip(member_values).collect(),
+                    )),
+                    None => Ok(Value::Array(member_values)),
+                }
+            }
+        }
+    }
+
+    fn decode_enum(
+        &self,
+        ty: &ConcreteTypeId,
+        name: &str,
+        variants: &[ConcreteTypeId],
+        values: &mut Iter<'_, MaybeRelocatable>,
+    ) -> Result<Value, Error> {
+        let invalid = || Error::InvalidReturnValue(ty.clone());
+        // Panics are handled when fetching the return values, which only hold the non-panic variant
+        if name.starts_with("core::panics::PanicResult") {
+            let ok_type_id = variants.first().ok_or_else(invalid)?;
+            // The non-panic variant wraps the return value in a tuple of its own
+            if let Ok(CoreTypeConcrete::Struct(info)) =
+                self.sierra_program_registry.get_type(ok_type_id)
+            {
+                if let [return_type_id] = info.members.as_slice() {
+                    return self.decode(return_type_id, values);
+                }
+            }
+            return self.decode(ok_type_id, values);
+        }
+        let casm_variant_idx = next_int(values)
+            .and_then(|tag| tag.to_usize())
+            .ok_or_else(invalid)?;
+        // Convert casm variant idx to sierra variant idx, see `serialize_output_inner`
+        let num_variants = variants.len();
+        let variant_idx = if num_variants > 2 {
+            (num_variants - 1).checked_sub(casm_variant_idx >> 1)
+        } else {
+            Some(casm_variant_idx)
+        }
+        .filter(|idx| *idx < num_variants)
+        .ok_or_else(invalid)?;
+        let variant_type_id = &variants[variant_idx];
+        // Space is always allocated for the largest enum member, padding with zeros in front for the smaller variants
+        let mut max_variant_size = 0;
+        for variant in variants {
+            max_variant_size = std::cmp::max(max_variant_size, self.type_size(variant)?);
+        }
+        let variant_size = self.type_size(variant_type_id)?;
+        for _ in variant_size..max_variant_size {
+            if values.next() != Some(&MaybeRelocatable::from(0)) {
+                return Err(invalid());
+            }
+        }
+        let value = self.decode(variant_type_id, values)?;
+
+        if name == "core::bool" {
+            return Ok(Value::Bool(variant_idx == 1));
+        }
+        let variant_name = self
+            .type_names
+            .variant_names(name, num_variants)
+            .get(variant_idx)
+            .map_or_else(|| Value::from(variant_idx), |name| Value::from(*name));
+        let mut object = Map::new();
+        object.insert(String::from("variant"), variant_name);
+        // Variants of type `()` are left without a value
+        if variant_size != 0 {
+            object.insert(String::from("value"), value);
+        }
+        Ok(Value::Object(object))
+    }
+
+    // Decodes the `size` cells of dictionary memory starting at `start` into an object holding the
+    // value of each key
+    fn decode_dict(
+        &self,
+        ty: &ConcreteTypeId,
+        value_type_id: &ConcreteTypeId,
+        start: Relocatable,
+        size: usize,
+    ) -> Result<Value, Error> {
+        if size % 3 != 0 {
+            return Err(Error::InvalidReturnValue(ty.clone()));
+        }
+        let dict_mem = self.vm.get_continuous_range(start, size)?;
+        let mut dict = Map::new();
+        // The dictionary's memory is made up of (key, prev_value, next_value) tuples
+        // Keys accessed more than once keep their last value
+        for (key, _, value) in dict_mem.iter().tuples() {
+            let key = key
+                .get_int()
+                .ok_or_else(|| Error::InvalidReturnValue(ty.clone()))?;
+            let value = self.decode(value_type_id, &mut std::slice::from_ref(value).iter())?;
+            dict.insert(key.to_string(), value);
+        }
+        Ok(Value::Object(dict))
+    }
+
+    fn type_size(&self, ty: &ConcreteTypeId) -> Result<usize, Error> {
+        self.type_sizes
+            .get(ty)
+            .and_then(|size| usize::try_from(*size).ok())
+            .ok_or_else(|| Error::NoTypeSizeForId(ty.clone()))
+    }
+}
+
+fn next_int(values: &mut Iter<'_, MaybeRelocatable>) -> Option<Felt252> {
+    values.next()?.get_int()
+}
+
+fn next_relocatable(values: &mut Iter<'_, MaybeRelocatable>) -> Option<Relocatable> {
+    values.next()?.get_relocatable()
+}
+
+// Integers of any size are kept as they are, as serde_json's `arbitrary_precision` is enabled
+fn json_number(value: impl Display) -> Value {
+    value
+        .to_string()
+        .parse::<Number>()
+        .map_or(Value::Null, Value::Number)
+}
+
+// Joins the words of a `ByteArray`: its full 31-byte words & the last `pending_word_len` bytes of the pending word
+fn byte_array_bytes(words: &[Felt252], pending_word: &Felt252, pending_word_len: usize) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for word in words {
+        bytes.extend_from_slice(&word.to_bytes_be()[1..]);
+    }
+    bytes.extend_from_slice(&pending_word.to_bytes_be()[32 - pending_word_len..]);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn join_byte_array_words() {
+        assert_eq!(
+            byte_array_bytes(&[], &Felt252::from(0x68656c6c6f_u64), 5),
+            b"hello"
+        );
+        assert_eq!(
+            byte_array_bytes(
+                &[Felt252::from_bytes_be_slice(&[b'a'; 31])],
+                &Felt252::from(0x6161_u64),
+                2
+            ),
+            [b'a'; 33]
+        );
+        assert_eq!(byte_array_bytes(&[], &Felt252::ZERO, 0), b"");
+    }
+
+    #[test]
+    fn json_numbers_keep_their_precision() {
+        let value = json_number(Felt252::MAX);
+        assert_eq!(value.to_string(), Felt252::MAX.to_string());
+        assert_eq!(json_number(-10), json!(-10));
+    }
+}
@@ -1,7 +1,22 @@
 pub mod cairo_run;
+pub mod compile;
+pub mod contract;
 pub mod error;
+pub mod json_args;
+pub mod json_output;
+pub mod test_runner;
 // Re-export main struct and functions from crate for convenience
-pub use crate::cairo_run::{cairo_run_program, Cairo1RunConfig, FuncArg};
+pub use crate::cairo_run::{
+    cairo_run_program, cairo_run_program_with_gas_report, prepare_cairo_run, Cairo1DebugInfo,
+    Cairo1RunConfig, FuncArg, GasReport, PreparedCairoRun,
+};
+pub use crate::compile::{
+    compile_cairo_program, compile_cairo_program_with_type_names, compile_cairo_tests,
+};
+pub use crate::contract::{find_entry_point, load_contract_class, run_contract_entry_point};
+pub use crate::json_args::parse_json_args;
+pub use crate::json_output::{serialize_output_json, UserTypeNames};
+pub use crate::test_runner::{CompiledTests, TestCase, TestResult, TestRunner, TestStatus};
 // Re-export cairo_vm structs returned by this crate for ease of use
 pub use cairo_vm::{
     types::relocatable::{MaybeRelocatable, Relocatable},
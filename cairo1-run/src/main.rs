@@ -1,18 +1,23 @@
 use bincode::enc::write::Writer;
 use cairo1_run::error::Error;
-use cairo1_run::{cairo_run_program, Cairo1RunConfig, FuncArg};
-use cairo_lang_compiler::{
-    compile_prepared_db, db::RootDatabase, project::setup_project, CompilerConfig,
+use cairo1_run::{
+    cairo_run_program_with_gas_report, compile_cairo_program_with_type_names, compile_cairo_tests,
+    find_entry_point, load_contract_class, parse_json_args, prepare_cairo_run,
+    run_contract_entry_point, serialize_output_json, Cairo1RunConfig, FuncArg, TestRunner,
+    TestStatus, UserTypeNames,
 };
 use cairo_vm::{
-    air_public_input::PublicInputError, types::layout_name::LayoutName,
-    vm::errors::trace_errors::TraceError, Felt252,
+    air_public_input::PublicInputError,
+    types::{layout::CairoLayoutParams, layout_name::LayoutName},
+    vm::{errors::trace_errors::TraceError, runners::profiler::Profile},
+    Felt252,
 };
-use clap::{Parser, ValueHint};
+use clap::{Parser, ValueEnum, ValueHint};
 use itertools::Itertools;
 use std::{
     io::{self, Write},
     path::PathBuf,
+    str::FromStr,
 };
 
 #[derive(Parser, Debug)]
@@ -26,6 +31,8 @@ struct Args {
     memory_file: Option<PathBuf>,
     #[clap(long = "layout", default_value = "plain", value_enum)]
     layout: LayoutName,
+    #[clap(long = "cairo_layout_params_file", required_if_eq("layout", "dynamic"))]
+    cairo_layout_params_file: Option<PathBuf>,
     #[clap(long = "proof_mode", value_parser)]
     proof_mode: bool,
     #[clap(long = "air_public_input", requires = "proof_mode")]
@@ -44,13 +51,20 @@ struct Args {
     cairo_pie_output: Option<PathBuf>,
     // Arguments should be spaced, with array elements placed between brackets
     // For example " --args '1 2 [1 2 3]'" will yield 3 arguments, with the last one being an array of 3 elements
-    #[clap(long = "args", default_value = "", value_parser=process_args, conflicts_with = "args_file")]
+    #[clap(long = "args", default_value = "", value_parser=process_args, conflicts_with_all = ["args_file", "args_json"])]
     args: FuncArgs,
     // Same rules from `args` apply here
-    #[clap(long = "args_file", value_parser, value_hint=ValueHint::FilePath, conflicts_with = "args")]
+    #[clap(long = "args_file", value_parser, value_hint=ValueHint::FilePath, conflicts_with_all = ["args", "args_json"])]
     args_file: Option<PathBuf>,
+    // A JSON array with a value for each of the main function's params, which are checked against their types
+    // For example " --args_json '[1, [2, 3], null]'" will yield a felt, an array and an Option::None
+    #[clap(long = "args_json", conflicts_with_all = ["args", "args_file"])]
+    args_json: Option<String>,
     #[clap(long = "print_output", value_parser)]
     print_output: bool,
+    // Prints the return value as JSON, decoded following its Sierra type
+    #[clap(long = "output_json", conflicts_with = "print_output")]
+    output_json: bool,
     #[clap(
         long = "append_return_values",
         // We need to add these air_private_input & air_public_input or else
@@ -58,6 +72,47 @@ struct Args {
         conflicts_with_all = ["proof_mode", "air_private_input", "air_public_input"]
     )]
     append_return_values: bool,
+    #[clap(long = "profile_output", value_parser, value_hint=ValueHint::FilePath)]
+    profile_output: Option<PathBuf>,
+    #[clap(long = "profile_format", default_value = "pprof", value_enum)]
+    profile_format: ProfileFormat,
+    // Runs an external entry point of a contract class, given by its name or selector, instead of a main function
+    // The file is then expected to hold either a Sierra contract class or a compiled casm contract class
+    #[clap(
+        long = "contract_entrypoint",
+        conflicts_with_all = [
+            "args", "args_file", "args_json", "proof_mode", "trace_file", "memory_file",
+            "cairo_pie_output", "append_return_values", "profile_output"
+        ]
+    )]
+    contract_entrypoint: Option<String>,
+    // Whitespace-separated felts given as calldata to the contract entry point
+    #[clap(long = "calldata", value_parser=process_calldata, requires = "contract_entrypoint")]
+    calldata: Option<Calldata>,
+    // Gas available to the run, enabling gas accounting: running out of it makes the run fail, and the gas consumed
+    // is printed along with the gas required to call each function when using `print_output`
+    // In `test` mode, it's the gas available to tests without an `#[available_gas]` attribute
+    #[clap(long = "available_gas", value_parser)]
+    available_gas: Option<usize>,
+    // Runs the `#[test]` functions of the Cairo file or Scarb package, printing whether each of them passed
+    #[clap(
+        long = "test",
+        conflicts_with_all = [
+            "contract_entrypoint", "args", "args_file", "args_json", "proof_mode", "trace_file",
+            "memory_file", "cairo_pie_output", "append_return_values", "profile_output",
+            "print_output", "output_json"
+        ]
+    )]
+    test: bool,
+    // Directory where the Sierra compiled from Cairo files is cached, by the hash of their sources
+    #[clap(long = "sierra_cache_dir", value_hint=ValueHint::DirPath)]
+    sierra_cache_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ProfileFormat {
+    Pprof,
+    Folded,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -67,36 +122,33 @@ fn process_args(value: &str) -> Result<FuncArgs, String> {
     if value.is_empty() {
         return Ok(FuncArgs::default());
     }
+    let parse_felt =
+        |value: &str| Felt252::from_dec_str(value).map_err(|_| format!("Invalid felt: {value}"));
     let mut args = Vec::new();
     let mut input = value.split(' ');
     while let Some(value) = input.next() {
         // First argument in an array
-        if value.starts_with('[') {
-            if value.ends_with(']') {
-                if value.len() == 2 {
+        if let Some(value) = value.strip_prefix('[') {
+            if let Some(value) = value.strip_suffix(']') {
+                if value.is_empty() {
                     args.push(FuncArg::Array(Vec::new()));
                 } else {
-                    args.push(FuncArg::Array(vec![Felt252::from_dec_str(
-                        value.strip_prefix('[').unwrap().strip_suffix(']').unwrap(),
-                    )
-                    .unwrap()]));
+                    args.push(FuncArg::Array(vec![parse_felt(value)?]));
                 }
             } else {
-                let mut array_arg =
-                    vec![Felt252::from_dec_str(value.strip_prefix('[').unwrap()).unwrap()];
+                let mut array_arg = vec![parse_felt(value)?];
                 // Process following args in array
                 let mut array_end = false;
                 while !array_end {
-                    if let Some(value) = input.next() {
-                        // Last arg in array
-                        if value.ends_with(']') {
-                            array_arg.push(
-                                Felt252::from_dec_str(value.strip_suffix(']').unwrap()).unwrap(),
-                            );
-                            array_end = true;
-                        } else {
-                            array_arg.push(Felt252::from_dec_str(value).unwrap())
-                        }
+                    let value = input
+                        .next()
+                        .ok_or_else(|| String::from("Unterminated array"))?;
+                    // Last arg in array
+                    if let Some(value) = value.strip_suffix(']') {
+                        array_arg.push(parse_felt(value)?);
+                        array_end = true;
+                    } else {
+                        array_arg.push(parse_felt(value)?)
                     }
                 }
                 // Finalize array
@@ -104,12 +156,23 @@ fn process_args(value: &str) -> Result<FuncArgs, String> {
             }
         } else {
             // Single argument
-            args.push(FuncArg::Single(Felt252::from_dec_str(value).unwrap()))
+            args.push(FuncArg::Single(parse_felt(value)?))
         }
     }
     Ok(FuncArgs(args))
 }
 
+#[derive(Debug, Clone, Default)]
+struct Calldata(Vec<Felt252>);
+
+fn process_calldata(value: &str) -> Result<Calldata, String> {
+    value
+        .split_whitespace()
+        .map(|felt| Felt252::from_str(felt).map_err(|_| format!("Invalid felt: {felt}")))
+        .collect::<Result<_, _>>()
+        .map(Calldata)
+}
+
 pub struct FileWriter {
     buf_writer: io::BufWriter<std::fs::File>,
     bytes_written: usize,
@@ -143,10 +206,128 @@ impl FileWriter {
     }
 }
 
+// Gas given to contract entry points when no `available_gas` is given, the same given to main functions
+const CONTRACT_INITIAL_GAS: u64 = 9999999999999;
+
+// Runs the `#[test]` functions of a Cairo project, printing the result of each of them
+fn run_tests(args: &Args) -> Result<Option<String>, Error> {
+    let compiled_tests = compile_cairo_tests(&args.filename, Some(&sierra_cache_dir(args)))?;
+    let test_runner = TestRunner::new(&compiled_tests.sierra_program, args.available_gas)?;
+    println!("running {} tests", compiled_tests.tests.len());
+    let (mut passed, mut failed, mut ignored) = (0, 0, 0);
+    for test in &compiled_tests.tests {
+        let result = test_runner.run_test(test)?;
+        println!("{result}");
+        match result.status {
+            TestStatus::Passed => passed += 1,
+            TestStatus::Ignored => ignored += 1,
+            TestStatus::Failed(_) | TestStatus::Panicked(_) => failed += 1,
+        }
+    }
+    let summary = if failed == 0 { "ok" } else { "FAILED" };
+    println!("test result: {summary}. {passed} passed; {failed} failed; {ignored} ignored");
+    if failed > 0 {
+        return Err(Error::TestsFailed(failed));
+    }
+    Ok(None)
+}
+
+fn sierra_cache_dir(args: &Args) -> PathBuf {
+    args.sierra_cache_dir
+        .clone()
+        .unwrap_or_else(|| std::env::temp_dir().join("cairo1-run"))
+}
+
+// Runs an external entry point of a contract class, returning its retdata, gas consumed & execution resources
+fn run_contract(args: &Args, entry_point: &str) -> Result<Option<String>, Error> {
+    let contract_class = load_contract_class(&std::fs::read(&args.filename)?)?;
+    let entry_point = find_entry_point(&contract_class, entry_point)?;
+    let calldata = args
+        .calldata
+        .as_ref()
+        .map(|calldata| calldata.0.as_slice())
+        .unwrap_or_default();
+    let initial_gas = args
+        .available_gas
+        .map_or(CONTRACT_INITIAL_GAS, |available_gas| available_gas as u64);
+    let call_info = run_contract_entry_point(&contract_class, entry_point, calldata, initial_gas)?;
+    if call_info.failed {
+        return Err(Error::from_panic_data(call_info.retdata));
+    }
+    let gas_consumed = initial_gas.saturating_sub(call_info.remaining_gas);
+    let execution_resources = call_info.execution_resources.filter_unused_builtins();
+
+    if args.output_json {
+        return Ok(Some(
+            serde_json::json!({
+                "retdata": call_info.retdata,
+                "gas_consumed": gas_consumed,
+                "execution_resources": execution_resources,
+            })
+            .to_string(),
+        ));
+    }
+    if !args.print_output {
+        return Ok(None);
+    }
+    let mut output = format!("[{}]", call_info.retdata.iter().join(" "));
+    output.push_str(&format!("\nGas consumed: {gas_consumed}"));
+    output.push_str(&format!("\nSteps: {}", execution_resources.n_steps));
+    output.push_str(&format!(
+        "\nMemory holes: {}",
+        execution_resources.n_memory_holes
+    ));
+    for (builtin_name, instances) in execution_resources
+        .builtin_instance_counter
+        .iter()
+        .sorted_by_key(|(builtin_name, _)| builtin_name.to_str())
+    {
+        output.push_str(&format!(
+            "\n{} instances: {instances}",
+            builtin_name.to_str()
+        ));
+    }
+    Ok(Some(output))
+}
+
 fn run(args: impl Iterator<Item = String>) -> Result<Option<String>, Error> {
     let mut args = Args::try_parse_from(args)?;
-    if let Some(filename) = args.args_file {
-        args.args = process_args(&std::fs::read_to_string(filename)?).unwrap();
+    if let Some(entry_point) = &args.contract_entrypoint {
+        return run_contract(&args, entry_point);
+    }
+    if args.test {
+        return run_tests(&args);
+    }
+    if let Some(filename) = &args.args_file {
+        args.args =
+            process_args(&std::fs::read_to_string(filename)?).map_err(Error::ArgumentsParse)?;
+    }
+
+    let cairo_layout_params = match &args.cairo_layout_params_file {
+        Some(file) => Some(CairoLayoutParams::from_file(file)?),
+        None => None,
+    };
+
+    // Try to parse the file as a sierra program, directories being Cairo projects or Scarb packages
+    let sierra_program = match args.filename.is_dir() {
+        true => None,
+        false => serde_json::from_slice(&std::fs::read(&args.filename)?).ok(),
+    };
+    // The names of struct members & enum variants are only known when compiling the program
+    let from_sierra_file = sierra_program.is_some();
+    let (sierra_program, type_names) = match sierra_program {
+        Some(program) => (program, UserTypeNames::default()),
+        // If it fails, try to compile it as a cairo program, gas is only withdrawn when accounting for it
+        None => compile_cairo_program_with_type_names(
+            &args.filename,
+            args.available_gas.is_some(),
+            Some(&sierra_cache_dir(&args)),
+        )?,
+    };
+
+    // JSON arguments are checked against the types of the main function's params
+    if let Some(json_args) = args.args_json {
+        args.args = FuncArgs(parse_json_args(&sierra_program, &type_names, &json_args)?);
     }
 
     let cairo_run_config = Cairo1RunConfig {
@@ -154,33 +335,55 @@ fn run(args: impl Iterator<Item = String>) -> Result<Option<String>, Error> {
         serialize_output: args.print_output,
         relocate_mem: args.memory_file.is_some() || args.air_public_input.is_some(),
         layout: args.layout,
-        trace_enabled: args.trace_file.is_some() || args.air_public_input.is_some(),
+        dynamic_layout_params: cairo_layout_params,
+        trace_enabled: args.trace_file.is_some()
+            || args.air_public_input.is_some()
+            || args.profile_output.is_some(),
         args: &args.args.0,
         finalize_builtins: args.air_public_input.is_some() || args.cairo_pie_output.is_some(),
         append_return_values: args.append_return_values,
+        available_gas: args.available_gas,
+    };
+
+    // Fetch the pcs of the Sierra functions before the config is consumed by the run
+    let function_pcs = match args.profile_output {
+        Some(_) => prepare_cairo_run(&sierra_program, &cairo_run_config)?
+            .debug_info
+            .function_pcs(&sierra_program),
+        None => Vec::new(),
     };
 
-    // Try to parse the file as a sierra program
-    let file = std::fs::read(&args.filename)?;
-    let sierra_program = match serde_json::from_slice(&file) {
-        Ok(program) => program,
-        Err(_) => {
-            // If it fails, try to compile it as a cairo program
-            let compiler_config = CompilerConfig {
-                replace_ids: true,
-                ..CompilerConfig::default()
-            };
-            let mut db = RootDatabase::builder()
-                .detect_corelib()
-                .skip_auto_withdraw_gas()
-                .build()
-                .unwrap();
-            let main_crate_ids = setup_project(&mut db, &args.filename).unwrap();
-            compile_prepared_db(&mut db, main_crate_ids, compiler_config).unwrap()
+    let (runner, return_values, serialized_output, gas_report) =
+        cairo_run_program_with_gas_report(&sierra_program, cairo_run_config)?;
+
+    let serialized_output = if args.output_json {
+        if from_sierra_file {
+            eprintln!("Warning: Sierra files don't hold the names of struct members & enum variants, structs are output as arrays of their members and enum variants by their index, save for those of `Option` & `Result`");
+        }
+        Some(
+            serialize_output_json(&sierra_program, &type_names, &runner.vm, &return_values)?
+                .to_string(),
+        )
+    } else {
+        match (serialized_output, gas_report) {
+            (Some(mut output), Some(gas_report)) => {
+                output.push_str(&format!("\nGas consumed: {}", gas_report.gas_consumed));
+                for (function, cost) in gas_report.function_costs {
+                    output.push_str(&format!("\n{function} gas cost: {cost}"));
+                }
+                Some(output)
+            }
+            (serialized_output, _) => serialized_output,
         }
     };
 
-    let (runner, _, serialized_output) = cairo_run_program(&sierra_program, cairo_run_config)?;
+    if let Some(ref profile_path) = args.profile_output {
+        let profile = Profile::from_runner_with_functions(&runner, &function_pcs)?;
+        match args.profile_format {
+            ProfileFormat::Pprof => std::fs::write(profile_path, profile.to_pprof())?,
+            ProfileFormat::Folded => std::fs::write(profile_path, profile.to_folded())?,
+        }
+    }
 
     if let Some(file_path) = args.air_public_input {
         let json = runner.get_air_public_input()?.serialize_json()?;
@@ -268,6 +471,10 @@ fn main() -> Result<(), Error> {
             }
             Ok(())
         }
+        Err(Error::OutOfGas) => {
+            println!("Run ran out of gas");
+            Ok(())
+        }
         Err(err) => Err(err),
     }
 }
@@ -276,8 +483,12 @@ fn main() -> Result<(), Error> {
 mod tests {
     use super::*;
     use assert_matches::assert_matches;
+    use cairo1_run::compile_cairo_program;
+    use cairo_vm::hint_processor::cairo_1_hint_processor::syscall_handler::selector_from_name;
     use rstest::rstest;
 
+    const SCARB_PACKAGE: &str = "../cairo_programs/cairo-1-programs/scarb_package";
+
     #[rstest]
     #[case(
         "ecdsa_recover.cairo",
@@ -372,6 +583,22 @@ mod tests {
         assert_matches!(run(args), Ok(Some(res)) if res == expected_output, "Program {} failed with flags {}", program, extra_flags.concat());
     }
 
+    #[rstest]
+    #[case("pprof")]
+    #[case("folded")]
+    fn test_run_with_profile_output(#[case] format: &str) {
+        let args = [
+            "cairo1-run",
+            "../cairo_programs/cairo-1-programs/fibonacci.cairo",
+            "--profile_output",
+            "/dev/null",
+            "--profile_format",
+            format,
+        ];
+        let args = args.iter().cloned().map(String::from);
+        assert_matches!(run(args), Ok(_));
+    }
+
     #[rstest]
     #[case(["cairo1-run", "../cairo_programs/cairo-1-programs/with_input/branching.cairo", "--layout", "all_cairo", "--cairo_pie_output", "/dev/null"].as_slice())]
     #[case(["cairo1-run", "../cairo_programs/cairo-1-programs/with_input/branching.cairo", "--layout", "all_cairo", "--proof_mode"].as_slice())]
@@ -387,4 +614,485 @@ mod tests {
         let args = args.iter().cloned().map(String::from);
         assert_matches!(run(args), Err(Error::ArgumentsSizeMismatch { expected, actual }) if expected == 1 && actual == 3);
     }
+
+    #[rstest]
+    #[case(
+        "with_input/json_args.cairo",
+        "1048",
+        r#"[[3, 4], {"variant": 1, "value": [5, 6]}, "0x100000000000000000000000000000002", "hello", [[1, 2], [3]], 7, true, -10]"#
+    )]
+    #[case(
+        "with_input/json_args.cairo",
+        "15",
+        r#"[[1, 1], {"variant": 0, "value": 2}, 1, "ab", [[]], 3, false, 5]"#
+    )]
+    #[case(
+        "with_input/json_args.cairo",
+        "0",
+        r#"[[0, 0], {"variant": 2}, 0, "", [], null, false, 0]"#
+    )]
+    #[case(
+        "with_input/json_args.cairo",
+        "1048",
+        r#"[{"x": 3, "y": 4}, {"variant": "Rect", "value": {"x": 5, "y": 6}}, "0x100000000000000000000000000000002", "hello", [[1, 2], [3]], {"variant": "Some", "value": 7}, true, -10]"#
+    )]
+    #[case(
+        "with_input/json_args.cairo",
+        "0",
+        r#"[{"y": 0, "x": 0}, {"variant": "Empty"}, 0, "", [], {"variant": "None"}, false, 0]"#
+    )]
+    #[case(
+        "with_input/array_input_sum.cairo",
+        "12",
+        "[2, [1, 2, 3, 4], 0, [9, 8]]"
+    )]
+    #[case("with_input/tensor.cairo", "1", "[[[2, 2], [1, 2, 3, 4]]]")]
+    fn test_run_with_args_json(
+        #[case] program: &str,
+        #[case] expected_output: &str,
+        #[case] json_args: &str,
+    ) {
+        let filename = format!("../cairo_programs/cairo-1-programs/{}", program);
+        let args = [
+            "cairo1-run",
+            &filename,
+            "--layout",
+            "all_cairo",
+            "--print_output",
+            "--args_json",
+            json_args,
+        ];
+        let args = args.iter().cloned().map(String::from);
+        assert_matches!(run(args), Ok(Some(res)) if res == expected_output, "Program {} failed with args {}", program, json_args);
+    }
+
+    #[rstest]
+    #[case(
+        r#"{"variant": "Circle", "value": 7}"#,
+        r#"{"variant": "Circle", "value": 7}"#
+    )]
+    #[case(
+        r#"{"variant": "Rect", "value": [2, 3]}"#,
+        r#"{"variant": "Rect", "value": [2, 3]}"#
+    )]
+    #[case(
+        r#"{"variant": "Triangle", "value": [4, 5, 6]}"#,
+        r#"{"variant": "Triangle", "value": [4, 5, 6]}"#
+    )]
+    #[case(r#"{"variant": "Empty"}"#, r#"{"variant": "Empty"}"#)]
+    #[case(
+        r#"{"variant": 0, "value": 7}"#,
+        r#"{"variant": "Circle", "value": 7}"#
+    )]
+    #[case(r#"{"variant": 3}"#, r#"{"variant": "Empty"}"#)]
+    fn test_run_enum_args_json_round_trip(#[case] shape: &str, #[case] expected_shape: &str) {
+        let json_args = format!("[{shape}]");
+        let args = [
+            "cairo1-run",
+            "../cairo_programs/cairo-1-programs/with_input/enum_round_trip.cairo",
+            "--layout",
+            "all_cairo",
+            "--output_json",
+            "--args_json",
+            &json_args,
+        ];
+        let args = args.iter().cloned().map(String::from);
+        let expected_output: serde_json::Value = serde_json::from_str(expected_shape).unwrap();
+        assert_matches!(
+            run(args),
+            Ok(Some(res)) if serde_json::from_str::<serde_json::Value>(&res).unwrap() == expected_output,
+            "Variant {} failed", shape
+        );
+    }
+
+    #[rstest]
+    #[case(r#"[{"x": 3}, {"variant": "Empty"}, 0, "", [], null, false, 0]"#)]
+    #[case(r#"[{"x": 3, "z": 4}, {"variant": "Empty"}, 0, "", [], null, false, 0]"#)]
+    #[case(r#"[[3, 4], {"variant": "Square"}, 0, "", [], null, false, 0]"#)]
+    #[case(r#"[[3, 4], {"variant": 3}, 0, "", [], null, false, 0]"#)]
+    fn test_run_json_args_invalid_names(#[case] json_args: &str) {
+        let args = [
+            "cairo1-run",
+            "../cairo_programs/cairo-1-programs/with_input/json_args.cairo",
+            "--args_json",
+            json_args,
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run(args), Err(Error::InvalidArgument { .. }));
+    }
+
+    #[rstest]
+    #[case(r#"["a"]"#)]
+    #[case("[4294967296]")]
+    #[case("[-1]")]
+    #[case("[[1]]")]
+    fn test_run_branching_invalid_args_json(#[case] json_args: &str) {
+        let args = [
+            "cairo1-run",
+            "../cairo_programs/cairo-1-programs/with_input/branching.cairo",
+            "--args_json",
+            json_args,
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run(args), Err(Error::InvalidArgument { param_index, .. }) if param_index == 0);
+    }
+
+    #[test]
+    fn test_run_args_json_errors() {
+        let run_with_args_json = |json_args: &str| {
+            run([
+                "cairo1-run",
+                "../cairo_programs/cairo-1-programs/with_input/branching.cairo",
+                "--args_json",
+                json_args,
+            ]
+            .into_iter()
+            .map(String::from))
+        };
+        assert_matches!(
+            run_with_args_json("[1, 2]"),
+            Err(Error::ArgumentsCountMismatch {
+                expected: 1,
+                actual: 2
+            })
+        );
+        assert_matches!(run_with_args_json("{"), Err(Error::ArgumentsJson(_)));
+    }
+
+    #[rstest]
+    #[case("null_ret.cairo", "null")]
+    #[case("simple.cairo", "true")]
+    #[case("bytes31_ret.cairo", "123")]
+    #[case("simple_struct.cairo", r#"{"a": 100}"#)]
+    #[case(
+        "enum_match.cairo",
+        "[10, 3618502788666131213697322783095070105623107215331596699973092056135872020471]"
+    )]
+    #[case("array_integer_tuple.cairo", "[[1], 1]")]
+    #[case("felt_span.cairo", "[8, 9, 10, 11]")]
+    #[case("struct_span_return.cairo", "[[4, 3], [2, 1]]")]
+    #[case(
+        "tensor_new.cairo",
+        r#"{"shape": [1, 2], "data": [{"mag": 1, "sign": false}, {"mag": 1, "sign": true}]}"#
+    )]
+    #[case("felt_dict.cairo", r#"{"66675": [8, 9, 10, 11], "66676": [1, 2, 3]}"#)]
+    #[case(
+        "felt_dict_squash.cairo",
+        r#"{"66675": [4, 5, 6], "66676": [1, 2, 3]}"#
+    )]
+    #[case(
+        "dict_with_struct.cairo",
+        r#"{
+            "0": {"mag": 1, "sign": true},
+            "1": {"mag": 1, "sign": false},
+            "2": {"mag": 1, "sign": true}
+        }"#
+    )]
+    #[case(
+        "nullable_box_vec.cairo",
+        r#"{"items": {"0": 10, "1": 20, "2": 30}, "len": 3}"#
+    )]
+    #[case("nullable_dict.cairo", "null")]
+    #[case(
+        "json_output.cairo",
+        r#"[
+            "Hello, Cairo! This string is longer than 31 bytes",
+            340282366920938463463374607431768211458,
+            {"variant": "Some", "value": 5},
+            {"variant": "None"},
+            {"variant": "Err", "value": 1869574259},
+            {"variant": "Rect", "value": [2, 3]},
+            {"variant": "Empty"},
+            {"x": 1, "y": -2},
+            -10,
+            true
+        ]"#
+    )]
+    fn test_run_with_output_json(
+        #[case] program: &str,
+        #[case] expected_output: &str,
+        #[values(&[], &["--proof_mode"])] extra_flags: &[&str],
+    ) {
+        let filename = format!("../cairo_programs/cairo-1-programs/{}", program);
+        let mut args = vec![
+            "cairo1-run",
+            &filename,
+            "--layout",
+            "all_cairo",
+            "--output_json",
+        ];
+        args.extend_from_slice(extra_flags);
+        let args = args.iter().cloned().map(String::from);
+        let expected_output: serde_json::Value = serde_json::from_str(expected_output).unwrap();
+        assert_matches!(
+            run(args),
+            Ok(Some(res)) if serde_json::from_str::<serde_json::Value>(&res).unwrap() == expected_output,
+            "Program {} failed with flags {}", program, extra_flags.concat()
+        );
+    }
+
+    #[test]
+    fn test_run_sierra_file_with_output_json() {
+        let sierra_program = compile_cairo_program(
+            std::path::Path::new("../cairo_programs/cairo-1-programs/json_output.cairo"),
+            false,
+            None,
+        )
+        .unwrap();
+        // Sierra files are read as JSON
+        let sierra_file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            sierra_file.path(),
+            serde_json::to_vec(&sierra_program).unwrap(),
+        )
+        .unwrap();
+        let args = [
+            "cairo1-run",
+            sierra_file.path().to_str().unwrap(),
+            "--layout",
+            "all_cairo",
+            "--output_json",
+        ]
+        .into_iter()
+        .map(String::from);
+        // Structs are output as arrays & variants by their index, as their names aren't known
+        let expected_output: serde_json::Value = serde_json::from_str(
+            r#"[
+                "Hello, Cairo! This string is longer than 31 bytes",
+                340282366920938463463374607431768211458,
+                {"variant": "Some", "value": 5},
+                {"variant": "None"},
+                {"variant": "Err", "value": 1869574259},
+                {"variant": 1, "value": [2, 3]},
+                {"variant": 2},
+                [1, -2],
+                -10,
+                true
+            ]"#,
+        )
+        .unwrap();
+        assert_matches!(
+            run(args),
+            Ok(Some(res)) if serde_json::from_str::<serde_json::Value>(&res).unwrap() == expected_output
+        );
+    }
+
+    #[test]
+    fn test_run_output_json_conflicts_with_print_output() {
+        let args = [
+            "cairo1-run",
+            "../cairo_programs/cairo-1-programs/simple.cairo",
+            "--print_output",
+            "--output_json",
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run(args), Err(Error::Cli(_)));
+    }
+
+    fn run_contract(contract_class: &str, flags: &[&str]) -> Result<Option<String>, Error> {
+        let filename = format!("../cairo_programs/cairo-2-contracts/{}", contract_class);
+        let mut args = vec!["cairo1-run", &filename];
+        args.extend_from_slice(flags);
+        run(args.into_iter().map(String::from))
+    }
+
+    #[rstest]
+    #[case("syscalls.casm")]
+    #[case("syscalls.sierra")]
+    fn test_run_contract_entrypoint(#[case] contract_class: &str) {
+        assert_matches!(
+            run_contract(contract_class, &["--contract_entrypoint", "get_value", "--calldata", "5", "--print_output"]),
+            Ok(Some(res)) if res.starts_with("[0]\nGas consumed: ")
+        );
+        let selector = selector_from_name("get_block_number").to_hex_string();
+        assert_matches!(
+            run_contract(contract_class, &["--contract_entrypoint", &selector, "--print_output"]),
+            Ok(Some(res)) if res.starts_with("[0]\n")
+        );
+        assert_matches!(
+            run_contract(
+                contract_class,
+                &["--contract_entrypoint", "get_value", "--calldata", "5"]
+            ),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test_run_contract_entrypoint_output_json() {
+        let res = run_contract(
+            "syscalls.casm",
+            &[
+                "--contract_entrypoint",
+                "keccak_u256",
+                "--calldata",
+                "1 0",
+                "--output_json",
+            ],
+        )
+        .unwrap()
+        .unwrap();
+        let output: serde_json::Value = serde_json::from_str(&res).unwrap();
+        let retdata: Vec<Felt252> = output["retdata"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|felt| Felt252::from_str(felt.as_str().unwrap()).unwrap())
+            .collect();
+        assert_eq!(
+            retdata,
+            [
+                Felt252::from(0x587f7cc3722e9654ea3963d5fe8c0748_u128),
+                Felt252::from(0xa5963aa610cb75ba273817bce5f8c48f_u128)
+            ]
+        );
+        assert!(output["gas_consumed"].as_u64().unwrap() > 0);
+        assert!(output["execution_resources"]["n_steps"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_run_contract_entrypoint_errors() {
+        assert_matches!(
+            run_contract("syscalls.casm", &["--contract_entrypoint", "set_value_and_fail", "--calldata", "6 8"]),
+            Err(Error::RunPanic(panic_data)) if panic_data == [Felt252::from_bytes_be_slice(b"failure")]
+        );
+        assert_matches!(
+            run_contract("syscalls.casm", &["--contract_entrypoint", "missing"]),
+            Err(Error::EntryPointNotFound(_))
+        );
+        assert_matches!(
+            run_contract(
+                "syscalls.casm",
+                &["--contract_entrypoint", "get_value", "--calldata", "a"]
+            ),
+            Err(Error::Cli(_))
+        );
+        assert_matches!(
+            run_contract(
+                "syscalls.casm",
+                &["--contract_entrypoint", "get_value", "--args", "5"]
+            ),
+            Err(Error::Cli(_))
+        );
+        assert_matches!(
+            run_contract("syscalls.casm", &["--calldata", "5"]),
+            Err(Error::Cli(_))
+        );
+        assert_matches!(
+            run_contract(
+                "syscalls.casm",
+                &[
+                    "--contract_entrypoint",
+                    "get_value",
+                    "--calldata",
+                    "5",
+                    "--available_gas",
+                    "0"
+                ]
+            ),
+            Err(Error::OutOfGas)
+        );
+    }
+
+    #[test]
+    fn test_run_scarb_package() {
+        let args = ["cairo1-run", SCARB_PACKAGE, "--print_output"]
+            .into_iter()
+            .map(String::from);
+        assert_matches!(run(args), Ok(Some(res)) if res == "5050");
+    }
+
+    #[test]
+    fn test_run_tests() {
+        let run_tests = |path: &str, extra_args: &[&str]| {
+            run(["cairo1-run", path, "--test"]
+                .iter()
+                .chain(extra_args)
+                .map(|arg| arg.to_string()))
+        };
+        assert_matches!(run_tests(SCARB_PACKAGE, &[]), Ok(None));
+        assert_matches!(
+            run_tests(
+                "../cairo_programs/cairo-1-programs/with_tests/failing_tests.cairo",
+                &[]
+            ),
+            Err(Error::TestsFailed(4))
+        );
+        assert_matches!(
+            run_tests(SCARB_PACKAGE, &["--print_output"]),
+            Err(Error::Cli(_))
+        );
+    }
+
+    #[test]
+    fn test_run_with_available_gas() {
+        let run_with_available_gas = |available_gas: &str| {
+            run([
+                "cairo1-run",
+                "../cairo_programs/cairo-1-programs/recursion.cairo",
+                "--layout",
+                "all_cairo",
+                "--print_output",
+                "--available_gas",
+                available_gas,
+            ]
+            .into_iter()
+            .map(String::from))
+        };
+        let res = run_with_available_gas("100000000").unwrap().unwrap();
+        let mut lines = res.lines();
+        assert_eq!(
+            lines.next(),
+            Some("1154076154663935037074198317650845438095734251249125412074882362667803016453")
+        );
+        let gas_consumed: usize = lines
+            .next()
+            .and_then(|line| line.strip_prefix("Gas consumed: "))
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(gas_consumed > 0 && gas_consumed < 100000000);
+        assert!(lines.any(|line| line.contains("::main gas cost: ")));
+        assert_matches!(run_with_available_gas("1000"), Err(Error::OutOfGas));
+    }
+
+    #[rstest]
+    #[case("1 [2")]
+    #[case("[1 a]")]
+    #[case("0x1")]
+    fn test_run_invalid_args(#[case] program_args: &str) {
+        let args = [
+            "cairo1-run",
+            "../cairo_programs/cairo-1-programs/with_input/branching.cairo",
+            "--args",
+            program_args,
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run(args), Err(Error::Cli(_)));
+    }
+
+    #[rstest]
+    #[case(["cairo1-run", "../cairo_programs/cairo-1-programs/fibonacci.cairo", "--layout", "dynamic", "--cairo_layout_params_file", "../vm/src/tests/cairo_layout_params_file.json", "--print_output"].as_slice())]
+    #[case(["cairo1-run", "../cairo_programs/cairo-1-programs/fibonacci.cairo", "--layout", "dynamic", "--cairo_layout_params_file", "../vm/src/tests/cairo_layout_params_file.json", "--print_output", "--proof_mode", "--air_public_input", "/dev/null"].as_slice())]
+    fn test_run_dynamic_layout(#[case] args: &[&str]) {
+        let args = args.iter().cloned().map(String::from);
+        assert_matches!(run(args), Ok(Some(res)) if res == "89");
+    }
+
+    #[test]
+    fn test_run_dynamic_layout_without_params_file() {
+        let args = [
+            "cairo1-run",
+            "../cairo_programs/cairo-1-programs/fibonacci.cairo",
+            "--layout",
+            "dynamic",
+        ]
+        .into_iter()
+        .map(String::from);
+        assert_matches!(run(args), Err(Error::Cli(_)));
+    }
 }
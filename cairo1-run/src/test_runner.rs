@@ -0,0 +1,334 @@
+use crate::{
+    cairo_run::{
+        compile_sierra_program, fetch_return_values, find_function, load_program,
+        result_inner_type_size, run_loaded_program, Cairo1RunConfig, CompiledSierraProgram,
+        LoadedProgram,
+    },
+    error::{Error, OUT_OF_GAS_PANIC},
+};
+use cairo_lang_sierra::program::Program as SierraProgram;
+use cairo_lang_utils::casts::IntoOrPanic;
+use cairo_vm::{
+    types::layout_name::LayoutName, vm::runners::cairo_runner::ExecutionResources, Felt252,
+};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Gas available to tests without an `#[available_gas]` attribute, unless the `TestRunner` is given another amount
+pub const DEFAULT_TEST_AVAILABLE_GAS: usize = 9999999999999;
+
+/// The `#[test]` functions of a Cairo project, along with the Sierra program they were compiled into
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompiledTests {
+    pub sierra_program: SierraProgram,
+    pub tests: Vec<TestCase>,
+}
+
+/// A `#[test]` function, along with the attributes it was declared with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestCase {
+    /// Full path of the test function
+    pub name: String,
+    /// Gas available to the test, set by its `#[available_gas]` attribute
+    pub available_gas: Option<usize>,
+    pub expectation: TestExpectation,
+    /// Set by the `#[ignore]` attribute
+    pub ignored: bool,
+}
+
+/// The expected outcome of a test, set by its `#[should_panic]` attribute
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TestExpectation {
+    Success,
+    /// The test is expected to panic, with the given panic data if any
+    Panics(Option<Vec<Felt252>>),
+}
+
+/// The outcome of a test
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestStatus {
+    /// The test returned, or panicked as expected
+    Passed,
+    /// The test was expected to panic, but returned or panicked with other data
+    Failed(Option<Vec<Felt252>>),
+    /// The test panicked while it was expected to return, which includes running out of gas
+    Panicked(Vec<Felt252>),
+    Ignored,
+}
+
+/// The result of a test, along with the resources it used
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    pub name: String,
+    pub status: TestStatus,
+    /// Gas consumed by the test, None if it wasn't run
+    pub gas_consumed: Option<usize>,
+    /// Resources used by the test, None if it wasn't run
+    pub execution_resources: Option<ExecutionResources>,
+}
+
+/// Runs the tests of a Sierra program, which is compiled to casm once for all of them
+pub struct TestRunner<'a> {
+    sierra_program: &'a SierraProgram,
+    compiled_program: CompiledSierraProgram,
+    available_gas: Option<usize>,
+}
+
+impl<'a> TestRunner<'a> {
+    /// Tests without an `#[available_gas]` attribute are given `available_gas`, or `DEFAULT_TEST_AVAILABLE_GAS` if None
+    pub fn new(
+        sierra_program: &'a SierraProgram,
+        available_gas: Option<usize>,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            sierra_program,
+            compiled_program: compile_sierra_program(sierra_program, true)?,
+            available_gas,
+        })
+    }
+
+    /// Runs a test using the `all_cairo` layout, checking whether it panicked against its expectation
+    pub fn run_test(&self, test: &TestCase) -> Result<TestResult, Error> {
+        let mut result = TestResult {
+            name: test.name.clone(),
+            status: TestStatus::Ignored,
+            gas_consumed: None,
+            execution_resources: None,
+        };
+        if test.ignored {
+            return Ok(result);
+        }
+        let func = find_function(self.sierra_program, &test.name)?;
+        let cairo_run_config = Cairo1RunConfig {
+            layout: LayoutName::all_cairo,
+            available_gas: Some(
+                test.available_gas
+                    .or(self.available_gas)
+                    .unwrap_or(DEFAULT_TEST_AVAILABLE_GAS),
+            ),
+            ..Default::default()
+        };
+        let LoadedProgram {
+            mut runner,
+            mut hint_processor,
+            end,
+            sierra_program_registry,
+            type_sizes,
+            builtins,
+            gas_accounting,
+            ..
+        } = match load_program(&self.compiled_program, func, &cairo_run_config) {
+            Ok(loaded_program) => loaded_program,
+            // Not having enough gas to call the test is reported as running out of gas
+            Err(Error::NotEnoughGasToCall { .. }) => {
+                result.status = test_status(&test.expectation, Some(out_of_gas_panic_data()));
+                return Ok(result);
+            }
+            Err(error) => return Err(error),
+        };
+        let gas_report =
+            run_loaded_program(&mut runner, &mut hint_processor, end, gas_accounting, false)?;
+
+        let return_type_id = func.signature.ret_types.last();
+        let return_type_size = return_type_id
+            .and_then(|id| type_sizes.get(id).cloned())
+            .unwrap_or_default();
+        let panic_data = match fetch_return_values(
+            return_type_size,
+            result_inner_type_size(return_type_id, sierra_program_registry, type_sizes),
+            &runner.vm,
+            builtins.len().into_or_panic(),
+            false,
+        ) {
+            Ok(_) => None,
+            Err(Error::RunPanic(panic_data)) => Some(panic_data),
+            Err(Error::OutOfGas) => Some(out_of_gas_panic_data()),
+            Err(error) => return Err(error),
+        };
+        result.status = test_status(&test.expectation, panic_data);
+        result.gas_consumed = gas_report.map(|gas_report| gas_report.gas_consumed);
+        result.execution_resources =
+            Some(runner.get_execution_resources()?.filter_unused_builtins());
+        Ok(result)
+    }
+}
+
+fn out_of_gas_panic_data() -> Vec<Felt252> {
+    vec![Felt252::from_bytes_be_slice(OUT_OF_GAS_PANIC)]
+}
+
+// Checks whether a test panicked, and with which data, against its expectation
+fn test_status(expectation: &TestExpectation, panic_data: Option<Vec<Felt252>>) -> TestStatus {
+    match (expectation, panic_data) {
+        (TestExpectation::Success, None) => TestStatus::Passed,
+        (TestExpectation::Success, Some(panic_data)) => TestStatus::Panicked(panic_data),
+        (TestExpectation::Panics(None), Some(_)) => TestStatus::Passed,
+        (TestExpectation::Panics(Some(expected)), Some(panic_data)) if *expected == panic_data => {
+            TestStatus::Passed
+        }
+        (TestExpectation::Panics(_), panic_data) => TestStatus::Failed(panic_data),
+    }
+}
+
+// Formats panic data, showing the felts which hold a short string along with it
+fn format_panic_data(panic_data: &[Felt252]) -> String {
+    let felts = panic_data.iter().map(|felt| {
+        let bytes = felt.to_bytes_be();
+        let short_string = bytes
+            .iter()
+            .skip_while(|byte| **byte == 0)
+            .copied()
+            .collect_vec();
+        match String::from_utf8(short_string) {
+            Ok(string)
+                if !string.is_empty()
+                    && string.chars().all(|c| c.is_ascii_graphic() || c == ' ') =>
+            {
+                format!("{felt} ('{string}')")
+            }
+            _ => felt.to_string(),
+        }
+    });
+    format!("[{}]", felts.format(", "))
+}
+
+impl fmt::Display for TestResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "test {} ... ", self.name)?;
+        match &self.status {
+            TestStatus::Passed => write!(f, "ok")?,
+            TestStatus::Failed(None) => write!(f, "fail: expected a panic")?,
+            TestStatus::Failed(Some(panic_data)) => write!(
+                f,
+                "fail: panicked with unexpected data {}",
+                format_panic_data(panic_data)
+            )?,
+            TestStatus::Panicked(panic_data) => {
+                write!(f, "panic: {}", format_panic_data(panic_data))?
+            }
+            TestStatus::Ignored => write!(f, "ignored")?,
+        }
+        if let Some(execution_resources) = &self.execution_resources {
+            write!(f, " (")?;
+            if let Some(gas_consumed) = self.gas_consumed {
+                write!(f, "gas consumed: {gas_consumed}, ")?;
+            }
+            write!(
+                f,
+                "steps: {}, memory holes: {}",
+                execution_resources.n_steps, execution_resources.n_memory_holes
+            )?;
+            for (builtin_name, instances) in execution_resources
+                .builtin_instance_counter
+                .iter()
+                .sorted_by_key(|(builtin_name, _)| builtin_name.to_str())
+            {
+                write!(f, ", {} instances: {instances}", builtin_name.to_str())?;
+            }
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::compile_cairo_tests;
+    use assert_matches::assert_matches;
+    use std::path::Path;
+
+    #[test]
+    fn run_tests_of_single_file() {
+        let compiled_tests = compile_cairo_tests(
+            Path::new("../cairo_programs/cairo-1-programs/with_tests/failing_tests.cairo"),
+            None,
+        )
+        .unwrap();
+        let test_runner = TestRunner::new(&compiled_tests.sierra_program, None).unwrap();
+        let results: Vec<_> = compiled_tests
+            .tests
+            .iter()
+            .map(|test| test_runner.run_test(test).unwrap())
+            .collect();
+        let status_of = |name: &str| {
+            &results
+                .iter()
+                .find(|result| result.name.ends_with(&format!("::{name}")))
+                .unwrap()
+                .status
+        };
+        let wrong_sum = vec![Felt252::from_bytes_be_slice(b"wrong sum")];
+
+        assert_eq!(status_of("test_passing"), &TestStatus::Passed);
+        assert_eq!(
+            status_of("test_panicking"),
+            &TestStatus::Panicked(wrong_sum.clone())
+        );
+        assert_eq!(status_of("test_expected_panic"), &TestStatus::Passed);
+        assert_eq!(status_of("test_not_panicking"), &TestStatus::Failed(None));
+        assert_eq!(
+            status_of("test_wrong_panic"),
+            &TestStatus::Failed(Some(wrong_sum))
+        );
+        assert_eq!(
+            status_of("test_out_of_gas"),
+            &TestStatus::Panicked(out_of_gas_panic_data())
+        );
+        assert_eq!(status_of("test_ignored"), &TestStatus::Ignored);
+
+        let passing = results
+            .iter()
+            .find(|result| result.name.ends_with("::test_passing"))
+            .unwrap();
+        assert!(passing.gas_consumed.unwrap() > 0);
+        assert!(passing.execution_resources.as_ref().unwrap().n_steps > 0);
+        assert!(passing
+            .to_string()
+            .contains("::test_passing ... ok (gas consumed: "));
+        assert_matches!(
+            results
+                .iter()
+                .find(|result| result.status == TestStatus::Ignored),
+            Some(TestResult {
+                gas_consumed: None,
+                execution_resources: None,
+                ..
+            })
+        );
+    }
+
+    #[test]
+    fn format_test_results() {
+        let result = |status| TestResult {
+            name: String::from("pkg::test"),
+            status,
+            gas_consumed: None,
+            execution_resources: None,
+        };
+        assert_eq!(
+            result(TestStatus::Passed).to_string(),
+            "test pkg::test ... ok"
+        );
+        assert_eq!(
+            result(TestStatus::Panicked(vec![
+                Felt252::from_bytes_be_slice(b"wrong sum"),
+                Felt252::from(3)
+            ]))
+            .to_string(),
+            format!(
+                "test pkg::test ... panic: [{} ('wrong sum'), 3]",
+                Felt252::from_bytes_be_slice(b"wrong sum")
+            )
+        );
+        assert_eq!(
+            result(TestStatus::Failed(None)).to_string(),
+            "test pkg::test ... fail: expected a panic"
+        );
+        assert_eq!(
+            result(TestStatus::Ignored).to_string(),
+            "test pkg::test ... ignored"
+        );
+    }
+}
@@ -0,0 +1,29 @@
+#[derive(Drop)]
+enum Shape {
+    Circle: u32,
+    Rect: (u32, u32),
+    Empty,
+}
+
+#[derive(Drop)]
+struct Point {
+    x: u8,
+    y: i16,
+}
+
+fn main() -> (
+    ByteArray, u256, Option<u8>, Option<u8>, Result<u16, felt252>, Shape, Shape, Point, i16, bool
+) {
+    (
+        "Hello, Cairo! This string is longer than 31 bytes",
+        0x100000000000000000000000000000002_u256,
+        Option::Some(5),
+        Option::None,
+        Result::Err('oops'),
+        Shape::Rect((2, 3)),
+        Shape::Empty,
+        Point { x: 1, y: -2 },
+        -10,
+        true,
+    )
+}
@@ -0,0 +1,33 @@
+mod math;
+
+fn main() -> felt252 {
+    math::sum_to(100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::math::{sum_to, checked_div};
+
+    #[test]
+    fn test_sum_to() {
+        assert(sum_to(10) == 55, 'wrong sum');
+    }
+
+    #[test]
+    #[should_panic(expected: ('division by zero',))]
+    fn test_division_by_zero() {
+        let _ = checked_div(1, 0);
+    }
+
+    #[test]
+    #[ignore]
+    fn test_ignored() {
+        assert(false, 'ignored');
+    }
+
+    #[test]
+    #[available_gas(1000000)]
+    fn test_with_available_gas() {
+        assert(checked_div(10, 2) == 5, 'wrong quotient');
+    }
+}
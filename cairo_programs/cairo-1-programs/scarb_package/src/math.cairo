@@ -0,0 +1,11 @@
+fn sum_to(n: felt252) -> felt252 {
+    if n == 0 {
+        return 0;
+    }
+    n + sum_to(n - 1)
+}
+
+fn checked_div(a: u32, b: u32) -> u32 {
+    assert(b != 0, 'division by zero');
+    a / b
+}
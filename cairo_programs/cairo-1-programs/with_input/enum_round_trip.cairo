@@ -0,0 +1,16 @@
+#[derive(Drop)]
+enum Shape {
+    Circle: u32,
+    Rect: (u32, u32),
+    Triangle: (u32, u32, u32),
+    Empty,
+}
+
+fn main(shape: Shape) -> Shape {
+    match shape {
+        Shape::Circle(radius) => Shape::Circle(radius),
+        Shape::Rect(sides) => Shape::Rect(sides),
+        Shape::Triangle(sides) => Shape::Triangle(sides),
+        Shape::Empty => Shape::Empty,
+    }
+}
@@ -0,0 +1,57 @@
+#[derive(Drop)]
+struct Point {
+    x: u32,
+    y: u32
+}
+
+#[derive(Drop)]
+enum Shape {
+    Circle: u32,
+    Rect: Point,
+    Empty,
+}
+
+fn main(
+    point: Point,
+    shape: Shape,
+    amount: u256,
+    name: ByteArray,
+    matrix: Array<Array<felt252>>,
+    maybe: Option<u8>,
+    flag: bool,
+    delta: i16
+) -> felt252 {
+    let mut total: felt252 = point.x.into();
+    total += point.y.into();
+    match shape {
+        Shape::Circle(radius) => { total += radius.into(); },
+        Shape::Rect(corner) => { total += (corner.x * corner.y).into(); },
+        Shape::Empty => {},
+    }
+    total += amount.low.into();
+    total += amount.high.into();
+    total += name.len().into();
+    let mut matrix = matrix;
+    loop {
+        match matrix.pop_front() {
+            Option::Some(row) => {
+                let mut row = row;
+                loop {
+                    match row.pop_front() {
+                        Option::Some(value) => { total += value; },
+                        Option::None => { break; },
+                    }
+                };
+            },
+            Option::None => { break; },
+        }
+    };
+    match maybe {
+        Option::Some(value) => { total += value.into(); },
+        Option::None => {},
+    }
+    if flag {
+        total += 1000;
+    }
+    total + delta.into()
+}
@@ -0,0 +1,51 @@
+fn sum_to(n: felt252) -> felt252 {
+    if n == 0 {
+        return 0;
+    }
+    n + sum_to(n - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sum_to;
+
+    #[test]
+    fn test_passing() {
+        assert(sum_to(3) == 6, 'wrong sum');
+    }
+
+    #[test]
+    fn test_panicking() {
+        assert(sum_to(3) == 7, 'wrong sum');
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_expected_panic() {
+        assert(sum_to(3) == 7, 'wrong sum');
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_not_panicking() {
+        assert(sum_to(3) == 6, 'wrong sum');
+    }
+
+    #[test]
+    #[should_panic(expected: ('other error',))]
+    fn test_wrong_panic() {
+        assert(sum_to(3) == 7, 'wrong sum');
+    }
+
+    #[test]
+    #[available_gas(100000)]
+    fn test_out_of_gas() {
+        assert(sum_to(100000) == 5000050000, 'wrong sum');
+    }
+
+    #[test]
+    #[ignore]
+    fn test_ignored() {
+        assert(false, 'ignored');
+    }
+}
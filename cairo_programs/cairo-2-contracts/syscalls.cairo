@@ -0,0 +1,83 @@
+#[starknet::interface]
+trait ISyscalls<TContractState> {
+    fn set_value(ref self: TContractState, key: felt252, value: felt252);
+    fn set_value_and_fail(ref self: TContractState, key: felt252, value: felt252);
+    fn get_value(self: @TContractState, key: felt252) -> felt252;
+    fn get_value_from(
+        self: @TContractState, contract_address: starknet::ContractAddress, key: felt252
+    ) -> felt252;
+    fn get_block_number(self: @TContractState) -> u64;
+    fn keccak_u256(self: @TContractState, value: u256) -> u256;
+    fn call_self(self: @TContractState, class_hash: starknet::ClassHash, depth: felt252) -> felt252;
+}
+
+#[starknet::contract]
+mod Syscalls {
+    use starknet::{ClassHash, ContractAddress, SyscallResultTrait};
+    use starknet::syscalls::{call_contract_syscall, library_call_syscall};
+
+    #[storage]
+    struct Storage {
+        values: LegacyMap<felt252, felt252>,
+    }
+
+    #[event]
+    #[derive(Drop, starknet::Event)]
+    enum Event {
+        ValueSet: ValueSet,
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct ValueSet {
+        #[key]
+        key: felt252,
+        value: felt252,
+    }
+
+    #[abi(embed_v0)]
+    impl Syscalls of super::ISyscalls<ContractState> {
+        fn set_value(ref self: ContractState, key: felt252, value: felt252) {
+            self.values.write(key, value);
+            self.emit(ValueSet { key, value });
+        }
+
+        fn set_value_and_fail(ref self: ContractState, key: felt252, value: felt252) {
+            self.values.write(key, value);
+            panic_with_felt252('failure');
+        }
+
+        fn get_value(self: @ContractState, key: felt252) -> felt252 {
+            self.values.read(key)
+        }
+
+        fn get_value_from(
+            self: @ContractState, contract_address: ContractAddress, key: felt252
+        ) -> felt252 {
+            let retdata = call_contract_syscall(
+                contract_address, selector!("get_value"), array![key].span()
+            )
+                .unwrap_syscall();
+            *retdata.at(0)
+        }
+
+        fn get_block_number(self: @ContractState) -> u64 {
+            starknet::get_block_info().unbox().block_number
+        }
+
+        fn keccak_u256(self: @ContractState, value: u256) -> u256 {
+            core::keccak::keccak_u256s_le_inputs(array![value].span())
+        }
+
+        // Nests `depth` library calls to itself, returning the amount of calls made
+        fn call_self(self: @ContractState, class_hash: ClassHash, depth: felt252) -> felt252 {
+            if depth == 0 {
+                return 0;
+            }
+            let retdata = library_call_syscall(
+                class_hash, selector!("call_self"), array![class_hash.into(), depth - 1].span()
+            )
+                .unwrap_syscall();
+            *retdata.at(0) + 1
+        }
+    }
+}
@@ -7,6 +7,7 @@ use crate::{
         collections::HashMap,
         prelude::{String, Vec},
     },
+    types::layout::CairoLayoutParams,
     vm::{
         errors::{trace_errors::TraceError, vm_errors::VirtualMachineError},
         trace::trace_entry::RelocatedTraceEntry,
@@ -97,14 +98,14 @@ pub struct PublicInput<'a> {
     pub n_steps: usize,
     pub memory_segments: HashMap<&'a str, MemorySegmentAddresses>,
     pub public_memory: Vec<PublicMemoryEntry>,
-    #[serde(skip_deserializing)] // This is set to None by default so we can skip it
-    dynamic_params: (),
+    pub dynamic_params: Option<CairoLayoutParams>,
 }
 
 impl<'a> PublicInput<'a> {
     pub fn new(
         memory: &[Option<Felt252>],
         layout: &'a str,
+        dynamic_params: Option<CairoLayoutParams>,
         public_memory_addresses: &[(usize, usize)],
         memory_segment_addresses: HashMap<&'static str, (usize, usize)>,
         trace: &[RelocatedTraceEntry],
@@ -133,7 +134,7 @@ impl<'a> PublicInput<'a> {
 
         Ok(PublicInput {
             layout,
-            dynamic_params: (),
+            dynamic_params,
             rc_min,
             rc_max,
             n_steps: trace.len(),
@@ -1,12 +1,23 @@
 use crate::{
     hint_processor::hint_processor_definition::HintProcessor,
-    types::{builtin_name::BuiltinName, layout_name::LayoutName, program::Program},
+    stdlib::vec::Vec,
+    types::{
+        builtin_name::BuiltinName,
+        compiled_program::CompiledProgram,
+        layout::CairoLayoutParams,
+        layout_name::LayoutName,
+        program::Program,
+        relocatable::{relocate_address, relocate_value},
+    },
     vm::{
         errors::{
-            cairo_run_errors::CairoRunError, runner_errors::RunnerError, vm_exception::VmException,
+            cairo_run_errors::CairoRunError, memory_errors::MemoryError,
+            runner_errors::RunnerError, trace_errors::TraceError, vm_exception::VmException,
         },
         runners::{cairo_pie::CairoPie, cairo_runner::CairoRunner},
         security::verify_secure_runner,
+        trace::trace_entry::{relocate_trace_register, RelocatedTraceEntry, TraceEntry},
+        vm_memory::memory_segments::MemorySegmentManager,
     },
 };
 
@@ -29,6 +40,8 @@ pub struct CairoRunConfig<'a> {
     pub secure_run: Option<bool>,
     pub disable_trace_padding: bool,
     pub allow_missing_builtins: Option<bool>,
+    /// Params of the dynamic layout, required when `layout` is `LayoutName::dynamic`
+    pub dynamic_layout_params: Option<CairoLayoutParams>,
 }
 
 impl<'a> Default for CairoRunConfig<'a> {
@@ -42,6 +55,7 @@ impl<'a> Default for CairoRunConfig<'a> {
             secure_run: None,
             disable_trace_padding: false,
             allow_missing_builtins: None,
+            dynamic_layout_params: None,
         }
     }
 }
@@ -50,6 +64,59 @@ pub fn cairo_run_program(
     program: &Program,
     cairo_run_config: &CairoRunConfig,
     hint_processor: &mut dyn HintProcessor,
+) -> Result<CairoRunner, CairoRunError> {
+    run_program(program, None, cairo_run_config, hint_processor)
+}
+
+/// Runs a compiled program like `cairo_run_program`, reusing the data of its hints instead of compiling them.
+/// Its hints must be run by a hint processor executing `HintProcessorData`, such as the `BuiltinHintProcessor`
+pub fn cairo_run_compiled_program(
+    compiled_program: &CompiledProgram,
+    cairo_run_config: &CairoRunConfig,
+    hint_processor: &mut dyn HintProcessor,
+) -> Result<CairoRunner, CairoRunError> {
+    run_program(
+        compiled_program.program(),
+        Some(compiled_program),
+        cairo_run_config,
+        hint_processor,
+    )
+}
+
+fn run_program(
+    program: &Program,
+    compiled_program: Option<&CompiledProgram>,
+    cairo_run_config: &CairoRunConfig,
+    hint_processor: &mut dyn HintProcessor,
+) -> Result<CairoRunner, CairoRunError> {
+    let mut cairo_runner = execute_program(
+        program,
+        compiled_program,
+        cairo_run_config,
+        hint_processor,
+        cairo_run_config.trace_enabled,
+        usize::MAX,
+        |_, _| Ok(()),
+    )?;
+    cairo_runner.relocate(cairo_run_config.relocate_mem)?;
+
+    Ok(cairo_runner)
+}
+
+// Runs a program until the end of its execution, checking it as configured, which is shared by all the ways of running
+// a program. Whenever the trace reaches `trace_chunk_size` entries they are handed over to `trace_chunk_handler`, and
+// the runner is returned without relocating its trace & memory
+fn execute_program(
+    program: &Program,
+    compiled_program: Option<&CompiledProgram>,
+    cairo_run_config: &CairoRunConfig,
+    hint_processor: &mut dyn HintProcessor,
+    trace_enabled: bool,
+    trace_chunk_size: usize,
+    mut trace_chunk_handler: impl FnMut(
+        &[TraceEntry],
+        &MemorySegmentManager,
+    ) -> Result<(), CairoRunError>,
 ) -> Result<CairoRunner, CairoRunError> {
     let secure_run = cairo_run_config
         .secure_run
@@ -62,24 +129,40 @@ pub fn cairo_run_program(
     let mut cairo_runner = CairoRunner::new(
         program,
         cairo_run_config.layout,
+        cairo_run_config.dynamic_layout_params.clone(),
         cairo_run_config.proof_mode,
-        cairo_run_config.trace_enabled,
+        trace_enabled,
     )?;
+    if let Some(compiled_program) = compiled_program {
+        cairo_runner.set_compiled_hint_data(compiled_program)?;
+    }
 
     let end = cairo_runner.initialize(allow_missing_builtins)?;
     // check step calculation
 
     cairo_runner
-        .run_until_pc(end, hint_processor)
-        .map_err(|err| VmException::from_vm_error(&cairo_runner, err))?;
+        .run_until_pc_with_trace_chunks(
+            end,
+            hint_processor,
+            trace_chunk_size,
+            &mut trace_chunk_handler,
+        )
+        .map_err(|err| match err {
+            CairoRunError::VirtualMachine(err) => {
+                VmException::from_vm_error(&cairo_runner, err).into()
+            }
+            err => err,
+        })?;
 
     if cairo_run_config.proof_mode {
         cairo_runner.run_for_steps(1, hint_processor)?;
     }
-    cairo_runner.end_run(
+    cairo_runner.end_run_with_trace_chunks(
         cairo_run_config.disable_trace_padding,
         false,
         hint_processor,
+        trace_chunk_size,
+        trace_chunk_handler,
     )?;
 
     cairo_runner.vm.verify_auto_deductions()?;
@@ -90,11 +173,126 @@ pub fn cairo_run_program(
     if secure_run {
         verify_secure_runner(&cairo_runner, true, None)?;
     }
-    cairo_runner.relocate(cairo_run_config.relocate_mem)?;
 
     Ok(cairo_runner)
 }
 
+/// Amount of trace entries kept in memory by `cairo_run_program_with_writers` before writing them
+pub const TRACE_CHUNK_SIZE: usize = 1 << 20;
+
+/// Runs a program like `cairo_run_program`, writing its relocated trace & memory into the given writers without keeping
+/// the whole trace & relocated memory in memory. The output is the same as the one of `write_encoded_trace` & `write_encoded_memory`.
+/// The trace is written in chunks of `TRACE_CHUNK_SIZE` entries during the execution, while the memory is relocated as it is
+/// written once the run is over.
+/// The `trace_enabled` & `relocate_mem` fields of the config are ignored, and the returned runner holds neither the trace
+/// nor the relocated trace & memory
+pub fn cairo_run_program_with_writers(
+    program: &Program,
+    cairo_run_config: &CairoRunConfig,
+    hint_processor: &mut dyn HintProcessor,
+    trace_writer: Option<&mut dyn Writer>,
+    memory_writer: Option<&mut dyn Writer>,
+) -> Result<CairoRunner, CairoRunError> {
+    run_program_with_writers(
+        program,
+        cairo_run_config,
+        hint_processor,
+        trace_writer,
+        memory_writer,
+        TRACE_CHUNK_SIZE,
+    )
+}
+
+fn run_program_with_writers(
+    program: &Program,
+    cairo_run_config: &CairoRunConfig,
+    hint_processor: &mut dyn HintProcessor,
+    mut trace_writer: Option<&mut dyn Writer>,
+    memory_writer: Option<&mut dyn Writer>,
+    trace_chunk_size: usize,
+) -> Result<CairoRunner, CairoRunError> {
+    // Only the program segment's relocation is final before the end of the run, the execution segment will be
+    // placed right after it as long as the program segment doesn't grow past the program loaded into it. This is
+    // checked before writing each chunk, and before writing the last entries once the final relocation table is
+    // known. As segments can't shrink, the chunks written before are then relocated with the final relocation table
+    let program_segment_len = program.data_len();
+    let trace_enabled = trace_writer.is_some();
+    let partial_relocation_table = vec![1, 1 + program_segment_len];
+    let mut written_entries = 0;
+    let mut write_trace = |trace: &[TraceEntry]| -> Result<(), CairoRunError> {
+        if let Some(dest) = trace_writer.as_deref_mut() {
+            for entry in trace {
+                let relocated_entry = RelocatedTraceEntry {
+                    pc: relocate_trace_register(entry.pc, &partial_relocation_table)?,
+                    ap: entry.ap + partial_relocation_table[1],
+                    fp: entry.fp + partial_relocation_table[1],
+                };
+                write_encoded_trace_entry(written_entries, &relocated_entry, dest)?;
+                written_entries += 1;
+            }
+        }
+        Ok(())
+    };
+
+    let mut cairo_runner = execute_program(
+        program,
+        None,
+        cairo_run_config,
+        hint_processor,
+        trace_enabled,
+        trace_chunk_size,
+        |trace, segments| {
+            if segments.memory.segment_len(0).unwrap_or_default() != program_segment_len {
+                return Err(TraceError::ProgramSegmentSizeChanged.into());
+            }
+            write_trace(trace)
+        },
+    )?;
+
+    cairo_runner.vm.segments.compute_effective_sizes();
+    let relocation_table = cairo_runner.vm.segments.relocate_segments()?;
+    if relocation_table.get(..2) != Some(partial_relocation_table.as_slice()) {
+        return Err(TraceError::ProgramSegmentSizeChanged.into());
+    }
+    // Write the entries left since the last chunk
+    if let Some(trace) = cairo_runner.vm.trace.take() {
+        write_trace(&trace)?;
+    }
+    if let Some(dest) = memory_writer {
+        write_relocated_memory(&cairo_runner, &relocation_table, dest)?;
+    }
+    cairo_runner.vm.relocation_table = Some(relocation_table);
+
+    Ok(cairo_runner)
+}
+
+// Relocates the memory of the runner as it is written, producing the same output as `write_encoded_memory`
+fn write_relocated_memory(
+    cairo_runner: &CairoRunner,
+    relocation_table: &Vec<usize>,
+    dest: &mut dyn Writer,
+) -> Result<(), CairoRunError> {
+    let mut next_address = 0;
+    for (index, offset, cell) in cairo_runner.vm.segments.memory.data.iter_values() {
+        let Some(value) = cell.get_value() else {
+            continue;
+        };
+        let address = relocate_address((index as isize, offset).into(), relocation_table)?;
+        // The addresses have to be written in ascending order, which only fails to hold if a segment
+        // exceeds its finalized size
+        if address < next_address {
+            return Err(MemoryError::Relocation.into());
+        }
+        next_address = address + 1;
+        let value = relocate_value(value, relocation_table)?;
+        dest.write(&(address as u64).to_le_bytes())
+            .map_err(|e| EncodeTraceError(address, e))?;
+        dest.write(&value.to_bytes_le())
+            .map_err(|e| EncodeTraceError(address, e))?;
+    }
+    Ok(())
+}
+
 pub fn cairo_run(
     program_content: &[u8],
     cairo_run_config: &CairoRunConfig,
@@ -133,6 +331,7 @@ pub fn cairo_run_pie(
     let mut cairo_runner = CairoRunner::new(
         &program,
         cairo_run_config.layout,
+        cairo_run_config.dynamic_layout_params.clone(),
         false,
         cairo_run_config.trace_enabled,
     )?;
@@ -204,6 +403,7 @@ pub fn cairo_run_fuzzed_program(
     let mut cairo_runner = CairoRunner::new(
         &program,
         cairo_run_config.layout,
+        cairo_run_config.dynamic_layout_params.clone(),
         cairo_run_config.proof_mode,
         cairo_run_config.trace_enabled,
     )?;
@@ -241,21 +441,30 @@ pub struct EncodeTraceError(usize, bincode::error::EncodeError);
 /// Bincode encodes to little endian by default and each trace entry is composed of
 /// 3 usize values that are padded to always reach 64 bit size.
 pub fn write_encoded_trace(
-    relocated_trace: &[crate::vm::trace::trace_entry::RelocatedTraceEntry],
+    relocated_trace: &[RelocatedTraceEntry],
     dest: &mut impl Writer,
 ) -> Result<(), EncodeTraceError> {
     for (i, entry) in relocated_trace.iter().enumerate() {
-        dest.write(&((entry.ap as u64).to_le_bytes()))
-            .map_err(|e| EncodeTraceError(i, e))?;
-        dest.write(&((entry.fp as u64).to_le_bytes()))
-            .map_err(|e| EncodeTraceError(i, e))?;
-        dest.write(&((entry.pc as u64).to_le_bytes()))
-            .map_err(|e| EncodeTraceError(i, e))?;
+        write_encoded_trace_entry(i, entry, dest)?;
     }
 
     Ok(())
 }
 
+fn write_encoded_trace_entry<W: Writer + ?Sized>(
+    i: usize,
+    entry: &RelocatedTraceEntry,
+    dest: &mut W,
+) -> Result<(), EncodeTraceError> {
+    dest.write(&((entry.ap as u64).to_le_bytes()))
+        .map_err(|e| EncodeTraceError(i, e))?;
+    dest.write(&((entry.fp as u64).to_le_bytes()))
+        .map_err(|e| EncodeTraceError(i, e))?;
+    dest.write(&((entry.pc as u64).to_le_bytes()))
+        .map_err(|e| EncodeTraceError(i, e))?;
+    Ok(())
+}
+
 /// Writes a binary representation of the relocated memory.
 ///
 /// The memory pairs (address, value) are encoded and concatenated:
@@ -293,6 +502,7 @@ mod tests {
         },
         utils::test_utils::*,
     };
+    use assert_matches::assert_matches;
     use bincode::enc::write::SliceWriter;
 
     use rstest::rstest;
@@ -475,6 +685,148 @@ mod tests {
         assert!(cairo_run_pie(&cairo_pie, &cairo_run_config, &mut hint_processor).is_ok());
     }
 
+    #[derive(Default)]
+    struct VecWriter(Vec<u8>);
+
+    impl Writer for VecWriter {
+        fn write(&mut self, bytes: &[u8]) -> Result<(), bincode::error::EncodeError> {
+            self.0.extend_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    // Runs the program both with & without writers, checking that they produce the same trace & memory files
+    fn check_run_with_writers(program: &Program, proof_mode: bool, trace_chunk_size: usize) {
+        let cairo_run_config = CairoRunConfig {
+            layout: LayoutName::all_cairo,
+            proof_mode,
+            trace_enabled: true,
+            relocate_mem: true,
+            ..Default::default()
+        };
+        let runner = cairo_run_program(
+            program,
+            &cairo_run_config,
+            &mut BuiltinHintProcessor::new_empty(),
+        )
+        .unwrap();
+        let mut expected_trace = VecWriter::default();
+        write_encoded_trace(
+            runner.relocated_trace.as_ref().unwrap(),
+            &mut expected_trace,
+        )
+        .unwrap();
+        let mut expected_memory = VecWriter::default();
+        write_encoded_memory(&runner.relocated_memory, &mut expected_memory).unwrap();
+
+        let mut trace = VecWriter::default();
+        let mut memory = VecWriter::default();
+        let streamed_runner = run_program_with_writers(
+            program,
+            &cairo_run_config,
+            &mut BuiltinHintProcessor::new_empty(),
+            Some(&mut trace),
+            Some(&mut memory),
+            trace_chunk_size,
+        )
+        .unwrap();
+        assert_eq!(trace.0, expected_trace.0);
+        assert_eq!(memory.0, expected_memory.0);
+        assert!(streamed_runner.vm.trace.is_none());
+        assert!(streamed_runner.relocated_trace.is_none());
+        assert!(streamed_runner.relocated_memory.is_empty());
+        assert_eq!(
+            streamed_runner.get_execution_resources().unwrap(),
+            runner.get_execution_resources().unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case(include_bytes!("../../cairo_programs/fibonacci.json"), false)]
+    #[case(include_bytes!("../../cairo_programs/integration.json"), false)]
+    #[case(include_bytes!("../../cairo_programs/relocate_segments.json"), false)]
+    #[case(include_bytes!("../../cairo_programs/proof_programs/fibonacci.json"), true)]
+    #[case(include_bytes!("../../cairo_programs/proof_programs/bitwise_output.json"), true)]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_with_writers(
+        #[case] program_content: &[u8],
+        #[case] proof_mode: bool,
+        #[values(1, 7, TRACE_CHUNK_SIZE)] trace_chunk_size: usize,
+    ) {
+        let program = Program::from_bytes(program_content, Some("main")).unwrap();
+        check_run_with_writers(&program, proof_mode, trace_chunk_size);
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_with_writers_output_range_check_program() {
+        let program = output_range_check_program();
+        for trace_chunk_size in [1, 5, 18, TRACE_CHUNK_SIZE] {
+            check_run_with_writers(&program, false, trace_chunk_size);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_with_writers_without_trace_writer() {
+        let program = Program::from_bytes(
+            include_bytes!("../../cairo_programs/fibonacci.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let mut memory = VecWriter::default();
+        let runner = cairo_run_program_with_writers(
+            &program,
+            &CairoRunConfig::default(),
+            &mut BuiltinHintProcessor::new_empty(),
+            None,
+            Some(&mut memory),
+        )
+        .unwrap();
+        assert!(runner.vm.trace.is_none());
+        assert!(!memory.0.is_empty());
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_with_writers_program_segment_grows() {
+        // main calls a function which writes 5 past the end of the program segment, through its return pc:
+        //   call rel 3; ret; [ap] = 5, ap++; [ap - 1] = [[fp - 1] + 10]; ret
+        let program = program!(
+            data = vec_data!(
+                (0x1104800180018000_i64),
+                (3),
+                (0x208b7fff7fff7ffe_i64),
+                (0x480680017fff8000_i64),
+                (5),
+                (0x4002800a7fff7fff_i64),
+                (0x208b7fff7fff7ffe_i64)
+            ),
+            main = Some(0),
+        );
+        let cairo_run_config = CairoRunConfig {
+            layout: LayoutName::all_cairo,
+            ..Default::default()
+        };
+        let mut trace = VecWriter::default();
+        let mut memory = VecWriter::default();
+        let result = run_program_with_writers(
+            &program,
+            &cairo_run_config,
+            &mut BuiltinHintProcessor::new_empty(),
+            Some(&mut trace),
+            Some(&mut memory),
+            1,
+        );
+        assert_matches!(
+            result.err(),
+            Some(CairoRunError::Trace(TraceError::ProgramSegmentSizeChanged))
+        );
+        // Only the entries of the steps run before the program segment grew were written
+        assert_eq!(trace.0.len(), 2 * 3 * 8);
+        assert!(memory.0.is_empty());
+    }
+
     #[test]
     fn cairo_run_pie_n_steps_not_set() {
         // First run program to get Cairo PIE
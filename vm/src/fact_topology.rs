@@ -0,0 +1,719 @@
+//! Fact topologies & facts of Cairo program runs.
+//!
+//! The output of a program is split into pages, as recorded by its output builtin, which are
+//! hashed into a merkle-like tree described by the program's fact topology. The fact registered
+//! on-chain for a run is `keccak(program_hash, output_root)`.
+
+use crate::stdlib::prelude::*;
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use crate::program_hash::{compute_program_hash, ProgramHashError, ProgramHashFunction};
+use crate::types::builtin_name::BuiltinName;
+use crate::types::errors::program_errors::ProgramError;
+use crate::types::relocatable::{MaybeRelocatable, Relocatable};
+use crate::vm::errors::memory_errors::MemoryError;
+use crate::vm::runners::builtin_runner::BuiltinRunner;
+use crate::vm::runners::cairo_pie::{
+    BuiltinAdditionalData, CairoPie, OutputBuiltinAdditionalData, Pages, StrippedProgram,
+};
+use crate::vm::runners::cairo_runner::CairoRunner;
+use crate::Felt252;
+
+/// Name of the output builtin attribute describing the fact topology of a program.
+pub const GPS_FACT_TOPOLOGY: &str = "gps_fact_topology";
+
+/// Version of the bootloader used to compute the program hashes of facts.
+const FACT_BOOTLOADER_VERSION: usize = 0;
+
+#[derive(thiserror_no_std::Error, Debug)]
+pub enum FactTopologyError {
+    #[error("Invalid fact topology: {0}")]
+    InvalidFactTopology(Box<str>),
+    #[error("The program doesn't use the output builtin")]
+    NoOutputBuiltin,
+    #[error(transparent)]
+    Memory(#[from] MemoryError),
+    #[error(transparent)]
+    ProgramHash(#[from] ProgramHashError),
+    #[error(transparent)]
+    Program(#[from] Box<ProgramError>),
+}
+
+fn invalid_fact_topology(msg: String) -> FactTopologyError {
+    FactTopologyError::InvalidFactTopology(msg.into_boxed_str())
+}
+
+/// The structure of the fact of a program output: the sizes of the pages of the output and the
+/// merkle-like tree built over them.
+///
+/// `tree_structure` is a list of pairs `(n_pages, n_nodes)`: each pair pushes the next `n_pages`
+/// pages to a stack of nodes, then replaces its last `n_nodes` nodes with their parent node.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FactTopology {
+    pub tree_structure: Vec<usize>,
+    pub page_sizes: Vec<usize>,
+}
+
+/// Returns the sizes of the pages of an output, starting with page 0, which holds everything
+/// before page 1. Pages are expected to be consecutive, numbered from 1, and to cover the output
+/// up to its end. Page starts are relative to `output_start`.
+/// [(cairo_lang reference)](https://github.com/starkware-libs/cairo-lang/blob/efa9648f57568aad8f8a13fbf027d2de7c63c2c0/src/starkware/cairo/bootloaders/fact_topology.py#L67)
+pub fn get_page_sizes_from_page_dict(
+    output_size: usize,
+    output_start: usize,
+    pages: &Pages,
+) -> Result<Vec<usize>, FactTopologyError> {
+    let mut sorted_pages: Vec<_> = pages.iter().collect();
+    sorted_pages.sort_by_key(|(page_id, _)| **page_id);
+
+    let mut page_sizes = vec![output_size];
+    let mut expected_page_start = None;
+    for (expected_page_id, (page_id, page)) in (1..).zip(sorted_pages) {
+        if *page_id != expected_page_id {
+            return Err(invalid_fact_topology(format!(
+                "Expected page id {expected_page_id}, found {page_id}."
+            )));
+        }
+        let page_start = page
+            .start
+            .checked_sub(output_start)
+            .ok_or_else(|| invalid_fact_topology(format!("Invalid page start {}.", page.start)))?;
+        match expected_page_start {
+            None if page_start <= output_size => page_sizes[0] = page_start,
+            None => {
+                return Err(invalid_fact_topology(format!(
+                    "Invalid page start {page_start}."
+                )))
+            }
+            Some(expected) if expected != page_start => {
+                return Err(invalid_fact_topology(format!(
+                    "Expected page start {expected}, found {page_start}."
+                )))
+            }
+            Some(_) => {}
+        }
+        if page.size > output_size {
+            return Err(invalid_fact_topology(format!(
+                "Invalid page size {}.",
+                page.size
+            )));
+        }
+        page_sizes.push(page.size);
+        expected_page_start = Some(page_start + page.size);
+    }
+
+    if expected_page_start.is_some_and(|end| end != output_size) {
+        return Err(invalid_fact_topology(
+            "Pages must cover the entire program output.".to_string(),
+        ));
+    }
+
+    Ok(page_sizes)
+}
+
+/// Builds the fact topology of an output from the pages and attributes its output builtin
+/// recorded. Without a [`GPS_FACT_TOPOLOGY`] attribute, the output must be a single page.
+pub fn get_fact_topology_from_additional_data(
+    output_size: usize,
+    output_start: usize,
+    additional_data: &OutputBuiltinAdditionalData,
+) -> Result<FactTopology, FactTopologyError> {
+    let tree_structure = match additional_data.attributes.get(GPS_FACT_TOPOLOGY) {
+        Some(tree_structure) => tree_structure.clone(),
+        None if additional_data.pages.is_empty() => vec![1, 0],
+        None => {
+            return Err(invalid_fact_topology(format!(
+                "Additional pages cannot be used since the '{GPS_FACT_TOPOLOGY}' attribute is not specified."
+            )))
+        }
+    };
+
+    Ok(FactTopology {
+        tree_structure,
+        page_sizes: get_page_sizes_from_page_dict(
+            output_size,
+            output_start,
+            &additional_data.pages,
+        )?,
+    })
+}
+
+/// A node of the tree built over the pages of an output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FactNode {
+    pub node_hash: [u8; 32],
+    /// Offset of the end of the last page covered by the node.
+    pub end_offset: usize,
+    /// Number of output cells covered by the node.
+    pub size: usize,
+    pub children: Vec<FactNode>,
+}
+
+// Keccak hash of the values, encoded as 32-byte big-endian words
+fn keccak_words<'a>(words: impl Iterator<Item = &'a [u8; 32]>) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    for word in words {
+        hasher.update(word);
+    }
+    hasher.finalize().into()
+}
+
+// Adds one to a 32-byte big-endian word, wrapping on overflow
+fn increment_word(mut word: [u8; 32]) -> [u8; 32] {
+    for byte in word.iter_mut().rev() {
+        let (sum, overflow) = byte.overflowing_add(1);
+        *byte = sum;
+        if !overflow {
+            break;
+        }
+    }
+    word
+}
+
+fn usize_to_word(value: usize) -> [u8; 32] {
+    Felt252::from(value).to_bytes_be()
+}
+
+/// Builds the tree of an output with the given fact topology, returning its root.
+///
+/// Leaves are the keccak hashes of the pages, and the hash of an inner node is the keccak hash
+/// of the `(node_hash, end_offset)` pairs of its children, plus one.
+/// [(cairo_lang reference)](https://github.com/starkware-libs/cairo-lang/blob/efa9648f57568aad8f8a13fbf027d2de7c63c2c0/src/starkware/cairo/bootloaders/generate_fact.py#L30)
+pub fn compute_output_root(
+    program_output: &[Felt252],
+    fact_topology: &FactTopology,
+) -> Result<FactNode, FactTopologyError> {
+    let page_sizes = &fact_topology.page_sizes;
+    let tree_structure = &fact_topology.tree_structure;
+    if page_sizes.iter().sum::<usize>() != program_output.len() {
+        return Err(invalid_fact_topology(format!(
+            "The pages cover {} cells, but the output has {}.",
+            page_sizes.iter().sum::<usize>(),
+            program_output.len()
+        )));
+    }
+    if tree_structure.len() % 2 != 0 {
+        return Err(invalid_fact_topology(
+            "The tree structure must have an even length.".to_string(),
+        ));
+    }
+
+    let mut node_stack: Vec<FactNode> = Vec::new();
+    let mut next_page_index = 0;
+    let mut offset = 0;
+    for pair in tree_structure.chunks_exact(2) {
+        let (n_pages, n_nodes) = (pair[0], pair[1]);
+        if n_pages > page_sizes.len() - next_page_index {
+            return Err(invalid_fact_topology(format!(
+                "Can't add {n_pages} pages, only {} are left.",
+                page_sizes.len() - next_page_index
+            )));
+        }
+        for page_size in &page_sizes[next_page_index..next_page_index + n_pages] {
+            let page = &program_output[offset..offset + page_size];
+            let page_words: Vec<_> = page.iter().map(Felt252::to_bytes_be).collect();
+            offset += page_size;
+            node_stack.push(FactNode {
+                node_hash: keccak_words(page_words.iter()),
+                end_offset: offset,
+                size: *page_size,
+                children: vec![],
+            });
+        }
+        next_page_index += n_pages;
+
+        if n_nodes > 0 {
+            if n_nodes > node_stack.len() {
+                return Err(invalid_fact_topology(format!(
+                    "Can't create a node from {n_nodes} nodes, only {} are available.",
+                    node_stack.len()
+                )));
+            }
+            let children = node_stack.split_off(node_stack.len() - n_nodes);
+            let node_data: Vec<_> = children
+                .iter()
+                .flat_map(|child| [child.node_hash, usize_to_word(child.end_offset)])
+                .collect();
+            node_stack.push(FactNode {
+                node_hash: increment_word(keccak_words(node_data.iter())),
+                end_offset: children[n_nodes - 1].end_offset,
+                size: children.iter().map(|child| child.size).sum(),
+                children,
+            });
+        }
+    }
+
+    if next_page_index != page_sizes.len() {
+        return Err(invalid_fact_topology(format!(
+            "Only {next_page_index} of the {} pages were used.",
+            page_sizes.len()
+        )));
+    }
+    match (node_stack.pop(), node_stack.is_empty()) {
+        (Some(root), true) => Ok(root),
+        _ => Err(invalid_fact_topology(
+            "The tree structure must result in a single root.".to_string(),
+        )),
+    }
+}
+
+/// Computes the fact of a program run, `keccak(program_hash, output_root)`.
+/// [(cairo_lang reference)](https://github.com/starkware-libs/cairo-lang/blob/efa9648f57568aad8f8a13fbf027d2de7c63c2c0/src/starkware/cairo/bootloaders/generate_fact.py#L67)
+pub fn compute_fact(
+    program_hash: &Felt252,
+    program_output: &[Felt252],
+    fact_topology: &FactTopology,
+) -> Result<[u8; 32], FactTopologyError> {
+    let output_root = compute_output_root(program_output, fact_topology)?;
+    Ok(keccak_words(
+        [program_hash.to_bytes_be(), output_root.node_hash].iter(),
+    ))
+}
+
+/// The output, fact topology & fact of a program run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FactInfo {
+    pub program_output: Vec<Felt252>,
+    pub fact_topology: FactTopology,
+    pub fact: [u8; 32],
+}
+
+impl FactInfo {
+    fn new(
+        program: &StrippedProgram,
+        program_output: Vec<Felt252>,
+        additional_data: &OutputBuiltinAdditionalData,
+        hash_function: ProgramHashFunction,
+    ) -> Result<FactInfo, FactTopologyError> {
+        let fact_topology =
+            get_fact_topology_from_additional_data(program_output.len(), 0, additional_data)?;
+        let program_hash = Felt252::from_bytes_be(
+            &compute_program_hash(program, FACT_BOOTLOADER_VERSION, hash_function)?.to_bytes_be(),
+        );
+        let fact = compute_fact(&program_hash, &program_output, &fact_topology)?;
+        Ok(FactInfo {
+            program_output,
+            fact_topology,
+            fact,
+        })
+    }
+
+    /// Computes the fact info of a finished run. The program hash is computed with the given
+    /// hash function.
+    pub fn from_runner(
+        runner: &CairoRunner,
+        hash_function: ProgramHashFunction,
+    ) -> Result<FactInfo, FactTopologyError> {
+        let output_builtin = runner
+            .vm
+            .builtin_runners
+            .iter()
+            .find_map(|builtin| match builtin {
+                BuiltinRunner::Output(output_builtin) => Some(output_builtin),
+                _ => None,
+            })
+            .ok_or(FactTopologyError::NoOutputBuiltin)?;
+        let output_size = runner
+            .vm
+            .get_segment_used_size(output_builtin.base())
+            .ok_or(MemoryError::MissingSegmentUsedSizes)?;
+        let program_output = runner
+            .vm
+            .get_integer_range(
+                Relocatable::from((output_builtin.base() as isize, 0)),
+                output_size,
+            )?
+            .into_iter()
+            .map(|value| value.into_owned())
+            .collect();
+        let program = runner
+            .get_program()
+            .get_stripped_program()
+            .map_err(Box::new)?;
+        let additional_data = OutputBuiltinAdditionalData {
+            pages: output_builtin.pages.clone(),
+            attributes: output_builtin.attributes.clone(),
+        };
+        FactInfo::new(&program, program_output, &additional_data, hash_function)
+    }
+
+    /// Computes the fact info of the run of a Cairo PIE. The program hash is computed with the
+    /// given hash function.
+    pub fn from_cairo_pie(
+        cairo_pie: &CairoPie,
+        hash_function: ProgramHashFunction,
+    ) -> Result<FactInfo, FactTopologyError> {
+        let output_segment = cairo_pie
+            .metadata
+            .builtin_segments
+            .get(&BuiltinName::output)
+            .ok_or(FactTopologyError::NoOutputBuiltin)?;
+        let additional_data = match cairo_pie.additional_data.0.get(&BuiltinName::output) {
+            Some(BuiltinAdditionalData::Output(additional_data)) => additional_data,
+            _ => return Err(FactTopologyError::NoOutputBuiltin),
+        };
+
+        let mut program_output = vec![None; output_segment.size];
+        for ((segment_index, offset), value) in cairo_pie.memory.0.iter() {
+            if *segment_index as isize != output_segment.index || *offset >= output_segment.size {
+                continue;
+            }
+            let address = Relocatable::from((output_segment.index, *offset));
+            match value {
+                MaybeRelocatable::Int(value) => program_output[*offset] = Some(*value),
+                MaybeRelocatable::RelocatableValue(_) => {
+                    return Err(MemoryError::ExpectedInteger(Box::new(address)).into())
+                }
+            }
+        }
+        let program_output = program_output
+            .into_iter()
+            .enumerate()
+            .map(|(offset, value)| {
+                value.ok_or_else(|| {
+                    MemoryError::UnknownMemoryCell(Box::new(Relocatable::from((
+                        output_segment.index,
+                        offset,
+                    ))))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        FactInfo::new(
+            &cairo_pie.metadata.program,
+            program_output,
+            additional_data,
+            hash_function,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::collections::HashMap;
+    use crate::utils::test_utils::*;
+    use crate::vm::runners::cairo_pie::{
+        CairoPieAdditionalData, CairoPieMemory, CairoPieMetadata, CairoPieVersion,
+        PublicMemoryPage, SegmentInfo,
+    };
+    use crate::vm::runners::cairo_runner::ExecutionResources;
+    use assert_matches::assert_matches;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn pages(pages: &[(usize, usize, usize)]) -> Pages {
+        pages
+            .iter()
+            .map(|(id, start, size)| {
+                (
+                    *id,
+                    PublicMemoryPage {
+                        start: *start,
+                        size: *size,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn felts(values: &[u64]) -> Vec<Felt252> {
+        values.iter().map(|value| Felt252::from(*value)).collect()
+    }
+
+    fn keccak_felts(values: &[Felt252]) -> [u8; 32] {
+        let words: Vec<_> = values.iter().map(Felt252::to_bytes_be).collect();
+        keccak_words(words.iter())
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn page_sizes_without_pages() {
+        assert_eq!(
+            get_page_sizes_from_page_dict(10, 0, &HashMap::new()).unwrap(),
+            vec![10]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn page_sizes_with_consecutive_pages() {
+        let pages = pages(&[(2, 9, 3), (1, 5, 4)]);
+        assert_eq!(
+            get_page_sizes_from_page_dict(10, 2, &pages).unwrap(),
+            vec![3, 4, 3]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn page_sizes_with_gap() {
+        let pages = pages(&[(1, 2, 3), (2, 6, 4)]);
+        assert_matches!(
+            get_page_sizes_from_page_dict(10, 0, &pages),
+            Err(FactTopologyError::InvalidFactTopology(msg)) if msg.as_ref() == "Expected page start 5, found 6."
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn page_sizes_not_covering_output() {
+        let pages = pages(&[(1, 2, 3)]);
+        assert_matches!(
+            get_page_sizes_from_page_dict(10, 0, &pages),
+            Err(FactTopologyError::InvalidFactTopology(msg)) if msg.as_ref() == "Pages must cover the entire program output."
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn fact_topology_pages_without_attribute() {
+        let additional_data = OutputBuiltinAdditionalData {
+            pages: pages(&[(1, 0, 2)]),
+            attributes: HashMap::new(),
+        };
+        assert_matches!(
+            get_fact_topology_from_additional_data(2, 0, &additional_data),
+            Err(FactTopologyError::InvalidFactTopology(_))
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn output_root_single_page() {
+        let output = felts(&[1, 2, 3]);
+        let fact_topology = FactTopology {
+            tree_structure: vec![1, 0],
+            page_sizes: vec![3],
+        };
+        let root = compute_output_root(&output, &fact_topology).unwrap();
+        assert_eq!(root.node_hash, keccak_felts(&output));
+        assert_eq!(root.end_offset, 3);
+        assert!(root.children.is_empty());
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn output_root_multiple_pages() {
+        let output = felts(&[1, 2, 3, 4, 5]);
+        // Pages 1 & 2 are merged into a node, which is merged with page 0 into the root
+        let fact_topology = FactTopology {
+            tree_structure: vec![1, 0, 2, 2, 0, 2],
+            page_sizes: vec![2, 1, 2],
+        };
+        let root = compute_output_root(&output, &fact_topology).unwrap();
+
+        let page_0 = keccak_felts(&output[0..2]);
+        let page_1 = keccak_felts(&output[2..3]);
+        let page_2 = keccak_felts(&output[3..5]);
+        let inner_node = increment_word(keccak_words(
+            [page_1, usize_to_word(3), page_2, usize_to_word(5)].iter(),
+        ));
+        let expected_root = increment_word(keccak_words(
+            [page_0, usize_to_word(2), inner_node, usize_to_word(5)].iter(),
+        ));
+        assert_eq!(root.node_hash, expected_root);
+        assert_eq!(root.size, 5);
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(root.children[1].node_hash, inner_node);
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn output_root_invalid_topologies() {
+        let output = felts(&[1, 2, 3]);
+        let topology = |tree_structure: &[usize], page_sizes: &[usize]| FactTopology {
+            tree_structure: tree_structure.to_vec(),
+            page_sizes: page_sizes.to_vec(),
+        };
+        for fact_topology in [
+            topology(&[1, 0], &[2]),
+            topology(&[1], &[3]),
+            topology(&[2, 0], &[3]),
+            topology(&[1, 2], &[3]),
+            topology(&[1, 0], &[1, 2]),
+            topology(&[2, 0], &[1, 2]),
+        ] {
+            assert_matches!(
+                compute_output_root(&output, &fact_topology),
+                Err(FactTopologyError::InvalidFactTopology(_))
+            );
+        }
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn increment_word_carries() {
+        let mut word = [0xff; 32];
+        word[0] = 0;
+        let mut expected = [0; 32];
+        expected[0] = 1;
+        assert_eq!(increment_word(word), expected);
+        assert_eq!(increment_word([0xff; 32]), [0; 32]);
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn fact_info_from_runner() {
+        let program = program!(
+            builtins = vec![BuiltinName::output],
+            data = vec_data!((5)),
+            main = Some(0),
+        );
+        let mut cairo_runner = cairo_runner!(program);
+        cairo_runner.initialize_builtins(false).unwrap();
+        cairo_runner.initialize_segments(None);
+        let output_base = cairo_runner.vm.builtin_runners[0].base() as isize;
+        for (offset, value) in [1, 2, 3].into_iter().enumerate() {
+            cairo_runner
+                .vm
+                .insert_value(
+                    Relocatable::from((output_base, offset)),
+                    Felt252::from(value),
+                )
+                .unwrap();
+        }
+        let output_builtin = cairo_runner.vm.get_output_builtin_mut().unwrap();
+        output_builtin
+            .add_page(1, Relocatable::from((output_base, 1)), 2)
+            .unwrap();
+        output_builtin.add_attribute(GPS_FACT_TOPOLOGY.to_string(), vec![2, 2]);
+        cairo_runner.vm.segments.compute_effective_sizes();
+
+        let fact_info =
+            FactInfo::from_runner(&cairo_runner, ProgramHashFunction::Poseidon).unwrap();
+        assert_eq!(fact_info.program_output, felts(&[1, 2, 3]));
+        assert_eq!(
+            fact_info.fact_topology,
+            FactTopology {
+                tree_structure: vec![2, 2],
+                page_sizes: vec![1, 2],
+            }
+        );
+        let program_hash = Felt252::from_bytes_be(
+            &compute_program_hash(
+                &program.get_stripped_program().unwrap(),
+                0,
+                ProgramHashFunction::Poseidon,
+            )
+            .unwrap()
+            .to_bytes_be(),
+        );
+        let output_root = compute_output_root(&fact_info.program_output, &fact_info.fact_topology)
+            .unwrap()
+            .node_hash;
+        assert_eq!(
+            fact_info.fact,
+            keccak_words([program_hash.to_bytes_be(), output_root].iter())
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn fact_info_from_runner_without_output_builtin() {
+        let program = program!(data = vec_data!((5)), main = Some(0),);
+        let mut cairo_runner = cairo_runner!(program);
+        cairo_runner.initialize_builtins(false).unwrap();
+        cairo_runner.initialize_segments(None);
+        assert_matches!(
+            FactInfo::from_runner(&cairo_runner, ProgramHashFunction::Pedersen),
+            Err(FactTopologyError::NoOutputBuiltin)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn fact_info_from_cairo_pie() {
+        let program = StrippedProgram {
+            data: vec![MaybeRelocatable::from(5)],
+            builtins: vec![BuiltinName::output],
+            main: 0,
+            prime: (),
+        };
+        let cairo_pie = CairoPie {
+            metadata: CairoPieMetadata {
+                program: program.clone(),
+                program_segment: SegmentInfo::from((0, 1)),
+                execution_segment: SegmentInfo::from((1, 0)),
+                ret_fp_segment: SegmentInfo::from((3, 0)),
+                ret_pc_segment: SegmentInfo::from((4, 0)),
+                builtin_segments: HashMap::from([(BuiltinName::output, SegmentInfo::from((2, 2)))]),
+                extra_segments: vec![],
+            },
+            memory: CairoPieMemory(vec![
+                ((0, 0), MaybeRelocatable::from(5)),
+                ((2, 1), MaybeRelocatable::from(8)),
+                ((2, 0), MaybeRelocatable::from(7)),
+            ]),
+            execution_resources: ExecutionResources::default(),
+            additional_data: CairoPieAdditionalData(HashMap::from([(
+                BuiltinName::output,
+                BuiltinAdditionalData::Output(OutputBuiltinAdditionalData {
+                    pages: HashMap::new(),
+                    attributes: HashMap::new(),
+                }),
+            )])),
+            version: CairoPieVersion { cairo_pie: () },
+        };
+
+        let fact_info =
+            FactInfo::from_cairo_pie(&cairo_pie, ProgramHashFunction::Pedersen).unwrap();
+        assert_eq!(fact_info.program_output, felts(&[7, 8]));
+        assert_eq!(
+            fact_info.fact_topology,
+            FactTopology {
+                tree_structure: vec![1, 0],
+                page_sizes: vec![2],
+            }
+        );
+        let program_hash = Felt252::from_bytes_be(
+            &compute_program_hash(&program, 0, ProgramHashFunction::Pedersen)
+                .unwrap()
+                .to_bytes_be(),
+        );
+        assert_eq!(
+            fact_info.fact,
+            keccak_words([program_hash.to_bytes_be(), keccak_felts(&felts(&[7, 8]))].iter())
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn fact_info_from_cairo_pie_missing_output_cell() {
+        let cairo_pie = CairoPie {
+            metadata: CairoPieMetadata {
+                program: StrippedProgram {
+                    data: vec![],
+                    builtins: vec![BuiltinName::output],
+                    main: 0,
+                    prime: (),
+                },
+                program_segment: SegmentInfo::from((0, 0)),
+                execution_segment: SegmentInfo::from((1, 0)),
+                ret_fp_segment: SegmentInfo::from((3, 0)),
+                ret_pc_segment: SegmentInfo::from((4, 0)),
+                builtin_segments: HashMap::from([(BuiltinName::output, SegmentInfo::from((2, 2)))]),
+                extra_segments: vec![],
+            },
+            memory: CairoPieMemory(vec![((2, 0), MaybeRelocatable::from(7))]),
+            execution_resources: ExecutionResources::default(),
+            additional_data: CairoPieAdditionalData(HashMap::from([(
+                BuiltinName::output,
+                BuiltinAdditionalData::Output(OutputBuiltinAdditionalData {
+                    pages: HashMap::new(),
+                    attributes: HashMap::new(),
+                }),
+            )])),
+            version: CairoPieVersion { cairo_pie: () },
+        };
+
+        assert_matches!(
+            FactInfo::from_cairo_pie(&cairo_pie, ProgramHashFunction::Pedersen),
+            Err(FactTopologyError::Memory(MemoryError::UnknownMemoryCell(address)))
+                if *address == Relocatable::from((2, 1))
+        );
+    }
+}
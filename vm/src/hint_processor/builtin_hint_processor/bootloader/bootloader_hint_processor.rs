@@ -0,0 +1,486 @@
+use crate::stdlib::{any::Any, collections::HashMap, prelude::*, sync::Arc};
+use core::time::Duration;
+
+use super::bootloader_hints::*;
+use super::execute_task_hints::*;
+use super::hint_code;
+use super::select_builtins::{select_builtin, select_builtins_enter_scope};
+use super::simple_bootloader_hints::*;
+use super::types::Task;
+use crate::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::{
+    BuiltinHintProcessor, HintProcessorData,
+};
+#[cfg(feature = "extensive_hints")]
+use crate::hint_processor::hint_processor_definition::HintExtension;
+use crate::hint_processor::hint_processor_definition::{HintProcessorLogic, HintReference};
+use crate::hint_processor::hint_whitelist::HintWhitelist;
+use crate::serde::deserialize_program::ApTracking;
+use crate::types::exec_scope::ExecutionScopes;
+use crate::vm::errors::hint_errors::HintError;
+use crate::vm::errors::vm_errors::VirtualMachineError;
+use crate::vm::runners::cairo_runner::{ResourceTracker, RunResources};
+use crate::vm::vm_core::VirtualMachine;
+use crate::Felt252;
+
+/// Hint processor able to run the simple bootloader and the bootloader.
+///
+/// The input of the run has to be inserted in the execution scopes beforehand, as a
+/// `BootloaderInput` named `bootloader_input` or a `SimpleBootloaderInput` named
+/// `simple_bootloader_input`. Hints that aren't part of the bootloaders are handled by the
+/// inner `BuiltinHintProcessor`.
+///
+/// Tasks given as programs can only contain hints if the `extensive_hints` feature is enabled,
+/// as their hints have to be loaded into the vm when the task is called. These hints receive
+/// the constants of the bootloader program.
+pub struct BootloaderHintProcessor {
+    builtin_hint_processor: BuiltinHintProcessor,
+}
+
+impl BootloaderHintProcessor {
+    pub fn new_empty() -> Self {
+        Self::new(BuiltinHintProcessor::new_empty())
+    }
+
+    pub fn new(builtin_hint_processor: BuiltinHintProcessor) -> Self {
+        BootloaderHintProcessor {
+            builtin_hint_processor,
+        }
+    }
+
+    /// Makes hint compilation fail for hints that are not whitelisted, or that use references not
+    /// allowed by the whitelist, see [`BuiltinHintProcessor::set_hint_whitelist`]. This includes
+    /// the bootloader's own hints, and the hints of the tasks it loads.
+    pub fn set_hint_whitelist(&mut self, hint_whitelist: HintWhitelist) {
+        self.builtin_hint_processor
+            .set_hint_whitelist(hint_whitelist);
+    }
+
+    fn execute_bootloader_hint(
+        &mut self,
+        vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        hint_data: &HintProcessorData,
+    ) -> Result<(), HintError> {
+        let ids_data = &hint_data.ids_data;
+        let ap_tracking = &hint_data.ap_tracking;
+        match &*hint_data.code {
+            hint_code::SIMPLE_BOOTLOADER_LOAD_INPUT => load_simple_bootloader_input(exec_scopes),
+            hint_code::SIMPLE_BOOTLOADER_PREPARE_TASK_RANGE_CHECKS => {
+                prepare_task_range_checks(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::SIMPLE_BOOTLOADER_SET_TASKS_VARIABLE => set_tasks_variable(exec_scopes),
+            hint_code::SIMPLE_BOOTLOADER_DIVIDE_NUM_BY_2 => {
+                divide_num_by_2(vm, ids_data, ap_tracking)
+            }
+            hint_code::SIMPLE_BOOTLOADER_SET_CURRENT_TASK => {
+                set_current_task(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::SIMPLE_BOOTLOADER_ZERO => simple_bootloader_zero(vm),
+            hint_code::SIMPLE_BOOTLOADER_COMPUTE_FACT_TOPOLOGIES => {
+                simple_bootloader_compute_fact_topologies(vm, exec_scopes)
+            }
+            hint_code::EXECUTE_TASK_ALLOCATE_PROGRAM_DATA_SEGMENT => {
+                allocate_program_data_segment(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::EXECUTE_TASK_LOAD_PROGRAM => {
+                load_program_hint(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::EXECUTE_TASK_VALIDATE_HASH => {
+                validate_hash(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::EXECUTE_TASK_VALIDATE_HASH_WITH_HASH_FUNCTION => {
+                validate_hash_with_hash_function(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::EXECUTE_TASK_ASSERT_PROGRAM_ADDRESS => {
+                assert_program_address(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::EXECUTE_TASK_CALL_TASK => {
+                // Without a hint extension, the hints of the task can't be loaded
+                if let Task::Program(program) = exec_scopes.get_ref::<Task>("task")? {
+                    if program
+                        .shared_program_data
+                        .hints_collection
+                        .iter_hints()
+                        .next()
+                        .is_some()
+                    {
+                        return Err(HintError::TaskHintsNotLoadable);
+                    }
+                }
+                call_task(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::EXECUTE_TASK_WRITE_RETURN_BUILTINS => {
+                write_return_builtins_hint(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::EXECUTE_TASK_APPEND_FACT_TOPOLOGIES => {
+                append_fact_topologies(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::SELECT_BUILTINS_ENTER_SCOPE => {
+                select_builtins_enter_scope(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::INNER_SELECT_BUILTINS_SELECT_BUILTIN => {
+                select_builtin(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::BOOTLOADER_PREPARE_SIMPLE_BOOTLOADER_OUTPUT_SEGMENT => {
+                prepare_simple_bootloader_output_segment(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::BOOTLOADER_PREPARE_SIMPLE_BOOTLOADER_INPUT => {
+                prepare_simple_bootloader_input(exec_scopes)
+            }
+            hint_code::BOOTLOADER_RESTORE_BOOTLOADER_OUTPUT => {
+                restore_bootloader_output(vm, exec_scopes)
+            }
+            hint_code::BOOTLOADER_LOAD_BOOTLOADER_CONFIG => {
+                load_bootloader_config(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::BOOTLOADER_SAVE_OUTPUT_POINTER => {
+                save_output_pointer(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::BOOTLOADER_SAVE_PACKED_OUTPUTS => save_packed_outputs(exec_scopes),
+            hint_code::BOOTLOADER_ENTER_PACKED_OUTPUT_SCOPE => {
+                enter_packed_output_scope(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::BOOTLOADER_IMPORT_PACKED_OUTPUT_SCHEMAS => import_packed_output_schemas(),
+            hint_code::BOOTLOADER_IS_PLAIN_PACKED_OUTPUT => is_plain_packed_output(vm, exec_scopes),
+            hint_code::BOOTLOADER_ASSERT_IS_COMPOSITE_PACKED_OUTPUT => {
+                assert_is_composite_packed_output(exec_scopes)
+            }
+            hint_code::BOOTLOADER_GUESS_PRE_IMAGE_OF_SUBTASKS_OUTPUT_HASH => {
+                guess_pre_image_of_subtasks_output_hash(vm, exec_scopes, ids_data, ap_tracking)
+            }
+            hint_code::BOOTLOADER_SET_PACKED_OUTPUT_TO_SUBTASKS => {
+                set_packed_output_to_subtasks(exec_scopes)
+            }
+            hint_code::BOOTLOADER_COMPUTE_FACT_TOPOLOGIES => {
+                bootloader_compute_fact_topologies(vm, exec_scopes)
+            }
+            code => Err(HintError::UnknownHint(code.to_string().into_boxed_str())),
+        }
+    }
+
+    /// Compiles the hints of the current task, if it is a program, so that they can be
+    /// executed at the address the program was loaded at.
+    #[cfg(feature = "extensive_hints")]
+    fn task_hint_extension(
+        &self,
+        exec_scopes: &ExecutionScopes,
+    ) -> Result<HintExtension, HintError> {
+        let Task::Program(program) = exec_scopes.get_ref::<Task>("task")? else {
+            return Ok(HintExtension::default());
+        };
+        let program_address: crate::types::relocatable::Relocatable =
+            exec_scopes.get("program_address")?;
+        let references = &program.shared_program_data.reference_manager;
+
+        let mut hint_extension = HintExtension::default();
+        let hints = crate::stdlib::collections::BTreeMap::from(
+            &program.shared_program_data.hints_collection,
+        );
+        for (pc, hints) in hints {
+            let compiled_hints = hints
+                .iter()
+                .map(|hint| {
+                    self.compile_hint(
+                        &hint.code,
+                        &hint.flow_tracking_data.ap_tracking,
+                        &hint.flow_tracking_data.reference_ids,
+                        references,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            hint_extension.insert((program_address + pc)?, compiled_hints);
+        }
+        Ok(hint_extension)
+    }
+}
+
+impl Default for BootloaderHintProcessor {
+    fn default() -> Self {
+        Self::new_empty()
+    }
+}
+
+impl HintProcessorLogic for BootloaderHintProcessor {
+    fn compile_hint(
+        &self,
+        hint_code: &str,
+        ap_tracking_data: &ApTracking,
+        reference_ids: &HashMap<String, usize>,
+        references: &[HintReference],
+    ) -> Result<Box<dyn Any>, VirtualMachineError> {
+        self.builtin_hint_processor.compile_hint(
+            hint_code,
+            ap_tracking_data,
+            reference_ids,
+            references,
+        )
+    }
+
+    fn compile_precompiled_hint(
+        &self,
+        hint_data: &Arc<HintProcessorData>,
+        reference_ids: &HashMap<String, usize>,
+        references: &[HintReference],
+    ) -> Result<Box<dyn Any>, VirtualMachineError> {
+        self.builtin_hint_processor
+            .compile_precompiled_hint(hint_data, reference_ids, references)
+    }
+
+    fn execute_hint(
+        &mut self,
+        vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        hint_data: &Box<dyn Any>,
+        constants: &HashMap<String, Felt252>,
+    ) -> Result<(), HintError> {
+        match self
+            .builtin_hint_processor
+            .execute_hint(vm, exec_scopes, hint_data, constants)
+        {
+            Err(HintError::UnknownHint(_)) => {}
+            res => return res,
+        }
+        let hint_data =
+            HintProcessorData::downcast(&**hint_data).ok_or(HintError::WrongHintData)?;
+        self.execute_bootloader_hint(vm, exec_scopes, hint_data)
+    }
+
+    #[cfg(feature = "extensive_hints")]
+    fn execute_hint_extensive(
+        &mut self,
+        vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        hint_data: &Box<dyn Any>,
+        constants: &HashMap<String, Felt252>,
+    ) -> Result<HintExtension, HintError> {
+        let data = HintProcessorData::downcast(&**hint_data).ok_or(HintError::WrongHintData)?;
+        if data.code != hint_code::EXECUTE_TASK_CALL_TASK {
+            self.execute_hint(vm, exec_scopes, hint_data, constants)?;
+            return Ok(HintExtension::default());
+        }
+        // The hints of the task are read before call_task enters the scope of the task
+        let hint_extension = self.task_hint_extension(exec_scopes)?;
+        call_task(vm, exec_scopes, &data.ids_data, &data.ap_tracking)?;
+        Ok(hint_extension)
+    }
+}
+
+impl ResourceTracker for BootloaderHintProcessor {
+    fn consumed(&self) -> bool {
+        self.builtin_hint_processor.consumed()
+    }
+
+    fn consume_step(&mut self) {
+        self.builtin_hint_processor.consume_step()
+    }
+
+    fn consume_hint_time(&mut self, hint_time: Duration) {
+        self.builtin_hint_processor.consume_hint_time(hint_time)
+    }
+
+    fn get_n_steps(&self) -> Option<usize> {
+        self.builtin_hint_processor.get_n_steps()
+    }
+
+    fn run_resources(&self) -> &RunResources {
+        self.builtin_hint_processor.run_resources()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::any_box;
+    use crate::cairo_run::{cairo_run_program, CairoRunConfig};
+    use crate::hint_processor::builtin_hint_processor::hint_code::ADD_SEGMENT;
+    use crate::serde::deserialize_program::{
+        ApTracking, FlowTrackingData, HintParams, ReferenceManager,
+    };
+    use crate::types::layout_name::LayoutName;
+    use crate::types::program::Program;
+    use crate::types::relocatable::MaybeRelocatable;
+    use crate::utils::test_utils::*;
+    use crate::vm::errors::cairo_run_errors::CairoRunError;
+    use assert_matches::assert_matches;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn program_with_hint() -> Program {
+        Program::new(
+            vec![],
+            vec![MaybeRelocatable::from(5)],
+            Some(0),
+            HashMap::from([(
+                0,
+                vec![HintParams {
+                    code: ADD_SEGMENT.to_string(),
+                    accessible_scopes: vec![],
+                    flow_tracking_data: FlowTrackingData {
+                        ap_tracking: ApTracking::default(),
+                        reference_ids: HashMap::new(),
+                    },
+                }],
+            )]),
+            ReferenceManager { references: vec![] },
+            HashMap::new(),
+            vec![],
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn execute_builtin_hint() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        let hint_data = any_box!(HintProcessorData::new_default(
+            ADD_SEGMENT.to_string(),
+            HashMap::new()
+        ));
+        let mut hint_processor = BootloaderHintProcessor::new_empty();
+        assert_matches!(
+            hint_processor.execute_hint(&mut vm, exec_scopes_ref!(), &hint_data, &HashMap::new()),
+            Ok(())
+        );
+        check_memory![vm.segments.memory, ((1, 0), (2, 0))];
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn execute_bootloader_hint() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        let hint_data = any_box!(HintProcessorData::new_default(
+            hint_code::SIMPLE_BOOTLOADER_ZERO.to_string(),
+            HashMap::new()
+        ));
+        let mut hint_processor = BootloaderHintProcessor::new_empty();
+        assert_matches!(
+            hint_processor.execute_hint(&mut vm, exec_scopes_ref!(), &hint_data, &HashMap::new()),
+            Ok(())
+        );
+        check_memory![vm.segments.memory, ((1, 0), 0)];
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn execute_unknown_hint() {
+        let mut vm = vm!();
+        let hint_data = any_box!(HintProcessorData::new_default(
+            "print('hello')".to_string(),
+            HashMap::new()
+        ));
+        let mut hint_processor = BootloaderHintProcessor::new_empty();
+        assert_matches!(
+            hint_processor.execute_hint(
+                &mut vm,
+                exec_scopes_ref!(),
+                &hint_data,
+                &HashMap::new()
+            ),
+            Err(HintError::UnknownHint(code)) if code.as_ref() == "print('hello')"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn call_task_with_hints_not_loadable() {
+        let mut vm = vm!();
+        let mut exec_scopes = scope![("task", Task::Program(program_with_hint()))];
+        let hint_data = any_box!(HintProcessorData::new_default(
+            hint_code::EXECUTE_TASK_CALL_TASK.to_string(),
+            HashMap::new()
+        ));
+        let mut hint_processor = BootloaderHintProcessor::new_empty();
+        assert_matches!(
+            hint_processor.execute_hint(&mut vm, &mut exec_scopes, &hint_data, &HashMap::new()),
+            Err(HintError::TaskHintsNotLoadable)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn whitelist_rejects_hints() {
+        let mut hint_processor = BootloaderHintProcessor::new_empty();
+        hint_processor.set_hint_whitelist(
+            serde_json::from_str(
+                r#"{
+                    "allowed_reference_expressions_for_hint": [
+                        {"allowed_expressions": [], "hint_lines": ["memory[ap] = segments.add()"]}
+                    ]
+                }"#,
+            )
+            .unwrap(),
+        );
+        assert!(hint_processor
+            .compile_hint(ADD_SEGMENT, &ApTracking::default(), &HashMap::new(), &[])
+            .is_ok());
+        assert_matches!(
+            hint_processor.compile_hint(
+                hint_code::SIMPLE_BOOTLOADER_ZERO,
+                &ApTracking::default(),
+                &HashMap::new(),
+                &[]
+            ),
+            Err(VirtualMachineError::HintNotWhitelisted(_))
+        );
+
+        // Asserts [ap] = 1 after running the hint "import math"
+        let program = program!(
+            data = vec_data!((0x480680017fff8000_u64), (1), (0x208b7fff7fff7ffe_u64)),
+            main = Some(0),
+            hints = crate::stdlib::collections::BTreeMap::from([(
+                0,
+                vec![HintParams {
+                    code: "import math".to_string(),
+                    accessible_scopes: vec![],
+                    flow_tracking_data: FlowTrackingData {
+                        ap_tracking: ApTracking::default(),
+                        reference_ids: HashMap::new(),
+                    },
+                }]
+            )]),
+        );
+        let result = cairo_run_program(
+            &program,
+            &CairoRunConfig {
+                layout: LayoutName::all_cairo,
+                ..Default::default()
+            },
+            &mut hint_processor,
+        );
+        match result {
+            Err(CairoRunError::VmException(exception)) => assert_matches!(
+                exception.inner_exc,
+                VirtualMachineError::HintNotWhitelisted(code) if code.as_ref() == "import math"
+            ),
+            Err(_) => panic!("Wrong error returned, expected VmException"),
+            Ok(_) => panic!("Expected run to fail"),
+        }
+    }
+
+    #[cfg(feature = "extensive_hints")]
+    #[test]
+    fn task_hint_extension_relocates_hints() {
+        let exec_scopes = scope![
+            ("task", Task::Program(program_with_hint())),
+            (
+                "program_address",
+                crate::types::relocatable::Relocatable::from((3, 7))
+            )
+        ];
+        let hint_processor = BootloaderHintProcessor::new_empty();
+        let hint_extension = hint_processor.task_hint_extension(&exec_scopes).unwrap();
+        assert_eq!(hint_extension.len(), 1);
+        let hints = hint_extension
+            .get(&crate::types::relocatable::Relocatable::from((3, 7)))
+            .unwrap();
+        assert_eq!(hints.len(), 1);
+        assert_eq!(
+            hints[0].downcast_ref::<HintProcessorData>().unwrap().code,
+            ADD_SEGMENT
+        );
+    }
+}
@@ -0,0 +1,576 @@
+use crate::stdlib::{collections::HashMap, prelude::*};
+
+use num_traits::ToPrimitive;
+
+use super::fact_topologies::{compute_fact_topologies, configure_fact_topologies};
+use super::types::{BootloaderInput, FactTopology, PackedOutput};
+use crate::any_box;
+use crate::hint_processor::builtin_hint_processor::hint_utils::{
+    get_integer_from_var_name, get_ptr_from_var_name, insert_value_from_var_name,
+    insert_value_into_ap,
+};
+use crate::hint_processor::hint_processor_definition::HintReference;
+use crate::serde::deserialize_program::ApTracking;
+use crate::types::exec_scope::ExecutionScopes;
+use crate::types::relocatable::{MaybeRelocatable, Relocatable};
+use crate::vm::errors::hint_errors::HintError;
+use crate::vm::runners::builtin_runner::OutputBuiltinState;
+use crate::vm::vm_core::VirtualMachine;
+use crate::Felt252;
+
+/*
+Implements hint:
+%{
+    from starkware.cairo.bootloaders.bootloader.objects import BootloaderInput
+    bootloader_input = BootloaderInput.Schema().load(program_input)
+
+    ids.simple_bootloader_output_start = segments.add()
+
+    # Change output builtin state to a different segment in preparation for calling the
+    # simple bootloader.
+    output_builtin_state = output_builtin.get_state()
+    output_builtin.new_state(base=ids.simple_bootloader_output_start)
+%}
+The input is expected to be provided in the execution scope as `bootloader_input`.
+*/
+pub fn prepare_simple_bootloader_output_segment(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    exec_scopes.get_ref::<BootloaderInput>("bootloader_input")?;
+
+    let simple_bootloader_output_start = vm.add_memory_segment();
+    insert_value_from_var_name(
+        "simple_bootloader_output_start",
+        simple_bootloader_output_start,
+        vm,
+        ids_data,
+        ap_tracking,
+    )?;
+
+    let output_builtin = vm.get_output_builtin_mut()?;
+    let output_builtin_state = output_builtin.get_state();
+    output_builtin.new_state(
+        simple_bootloader_output_start.segment_index as usize,
+        output_builtin.included,
+    );
+    exec_scopes.insert_value("output_builtin_state", output_builtin_state);
+    Ok(())
+}
+
+/*
+Implements hint:
+%{ simple_bootloader_input = bootloader_input %}
+*/
+pub fn prepare_simple_bootloader_input(exec_scopes: &mut ExecutionScopes) -> Result<(), HintError> {
+    let simple_bootloader_input = exec_scopes
+        .get_ref::<BootloaderInput>("bootloader_input")?
+        .simple_bootloader_input
+        .clone();
+    exec_scopes.insert_value("simple_bootloader_input", simple_bootloader_input);
+    Ok(())
+}
+
+/*
+Implements hint:
+%{
+    # Restore the bootloader's output builtin state.
+    output_builtin.set_state(output_builtin_state)
+%}
+*/
+pub fn restore_bootloader_output(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+) -> Result<(), HintError> {
+    let output_builtin_state: OutputBuiltinState = exec_scopes.get("output_builtin_state")?;
+    vm.get_output_builtin_mut()?.set_state(output_builtin_state);
+    Ok(())
+}
+
+/*
+Implements hint:
+%{
+    from starkware.cairo.bootloaders.bootloader.objects import BootloaderConfig
+    bootloader_config: BootloaderConfig = bootloader_input.bootloader_config
+
+    ids.bootloader_config = segments.gen_arg(
+        [
+            bootloader_config.simple_bootloader_program_hash,
+            len(bootloader_config.supported_cairo_verifier_program_hashes),
+            bootloader_config.supported_cairo_verifier_program_hashes,
+        ],
+    )
+%}
+*/
+pub fn load_bootloader_config(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let bootloader_config = &exec_scopes
+        .get_ref::<BootloaderInput>("bootloader_input")?
+        .bootloader_config;
+
+    let verifier_program_hashes: Vec<MaybeRelocatable> = bootloader_config
+        .supported_cairo_verifier_program_hashes
+        .iter()
+        .map(MaybeRelocatable::from)
+        .collect();
+    let verifier_program_hashes_ptr = vm.gen_arg(&verifier_program_hashes)?;
+    let config = vec![
+        MaybeRelocatable::from(bootloader_config.simple_bootloader_program_hash),
+        MaybeRelocatable::from(verifier_program_hashes.len()),
+        verifier_program_hashes_ptr,
+    ];
+    let config_ptr = vm.gen_arg(&config)?;
+    insert_value_from_var_name("bootloader_config", config_ptr, vm, ids_data, ap_tracking)
+}
+
+/*
+Implements hint:
+%{ output_start = ids.output_ptr %}
+*/
+pub fn save_output_pointer(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let output_ptr = get_ptr_from_var_name("output_ptr", vm, ids_data, ap_tracking)?;
+    exec_scopes.insert_value("output_start", output_ptr);
+    Ok(())
+}
+
+/*
+Implements hint:
+%{ packed_outputs = bootloader_input.packed_outputs %}
+*/
+pub fn save_packed_outputs(exec_scopes: &mut ExecutionScopes) -> Result<(), HintError> {
+    let packed_outputs = exec_scopes
+        .get_ref::<BootloaderInput>("bootloader_input")?
+        .packed_outputs
+        .clone();
+    exec_scopes.insert_value("packed_outputs", packed_outputs);
+    Ok(())
+}
+
+/*
+Implements hint:
+%{
+    from starkware.cairo.bootloaders.bootloader.objects import PackedOutput
+
+    task_id = len(packed_outputs) - ids.n_subtasks
+    packed_output: PackedOutput = packed_outputs[task_id]
+
+    vm_enter_scope(new_scope_locals=dict(packed_output=packed_output))
+%}
+*/
+pub fn enter_packed_output_scope(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let n_subtasks = get_integer_from_var_name("n_subtasks", vm, ids_data, ap_tracking)?;
+    let packed_outputs = exec_scopes.get_list_ref::<PackedOutput>("packed_outputs")?;
+    let packed_output = n_subtasks
+        .to_usize()
+        .and_then(|n_subtasks| packed_outputs.len().checked_sub(n_subtasks))
+        .and_then(|task_id| packed_outputs.get(task_id))
+        .ok_or_else(|| {
+            HintError::InvalidValue(Box::new((
+                "n_subtasks",
+                n_subtasks,
+                Felt252::from(packed_outputs.len()),
+            )))
+        })?
+        .clone();
+    exec_scopes.enter_scope(HashMap::from([(
+        String::from("packed_output"),
+        any_box!(packed_output),
+    )]));
+    Ok(())
+}
+
+/*
+Implements hint:
+%{
+    from starkware.cairo.bootloaders.bootloader.objects import (
+        CompositePackedOutput,
+        PlainPackedOutput,
+    )
+%}
+*/
+pub fn import_packed_output_schemas() -> Result<(), HintError> {
+    Ok(())
+}
+
+/*
+Implements hint:
+%{ memory[ap] = to_felt_or_relocatable(isinstance(packed_output, PlainPackedOutput)) %}
+*/
+pub fn is_plain_packed_output(
+    vm: &mut VirtualMachine,
+    exec_scopes: &ExecutionScopes,
+) -> Result<(), HintError> {
+    let packed_output = exec_scopes.get_ref::<PackedOutput>("packed_output")?;
+    let is_plain = matches!(packed_output, PackedOutput::Plain);
+    insert_value_into_ap(vm, Felt252::from(is_plain as u8))
+}
+
+/*
+Implements hint:
+%{ assert isinstance(packed_output, CompositePackedOutput) %}
+*/
+pub fn assert_is_composite_packed_output(exec_scopes: &ExecutionScopes) -> Result<(), HintError> {
+    match exec_scopes.get_ref::<PackedOutput>("packed_output")? {
+        PackedOutput::Composite(_) => Ok(()),
+        PackedOutput::Plain => Err(HintError::NotCompositePackedOutput),
+    }
+}
+
+/*
+Implements hint:
+%{
+    data = packed_output.elements_for_hash()
+    ids.nested_subtasks_output_len = len(data)
+    ids.nested_subtasks_output = segments.gen_arg(data)
+%}
+*/
+pub fn guess_pre_image_of_subtasks_output_hash(
+    vm: &mut VirtualMachine,
+    exec_scopes: &ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let PackedOutput::Composite(packed_output) =
+        exec_scopes.get_ref::<PackedOutput>("packed_output")?
+    else {
+        return Err(HintError::NotCompositePackedOutput);
+    };
+
+    let data: Vec<MaybeRelocatable> = packed_output
+        .elements_for_hash()
+        .iter()
+        .map(MaybeRelocatable::from)
+        .collect();
+    insert_value_from_var_name(
+        "nested_subtasks_output_len",
+        Felt252::from(data.len()),
+        vm,
+        ids_data,
+        ap_tracking,
+    )?;
+    let nested_subtasks_output = vm.gen_arg(&data)?;
+    insert_value_from_var_name(
+        "nested_subtasks_output",
+        nested_subtasks_output,
+        vm,
+        ids_data,
+        ap_tracking,
+    )
+}
+
+/*
+Implements hint:
+%{ packed_outputs = packed_output.subtasks %}
+*/
+pub fn set_packed_output_to_subtasks(exec_scopes: &mut ExecutionScopes) -> Result<(), HintError> {
+    let PackedOutput::Composite(packed_output) =
+        exec_scopes.get_ref::<PackedOutput>("packed_output")?
+    else {
+        return Err(HintError::NotCompositePackedOutput);
+    };
+    let subtasks = packed_output.subtasks.clone();
+    exec_scopes.insert_value("packed_outputs", subtasks);
+    Ok(())
+}
+
+/*
+Implements hint:
+%{
+    from typing import List
+
+    from starkware.cairo.bootloaders.bootloader.utils import compute_fact_topologies
+    from starkware.cairo.bootloaders.fact_topology import FactTopology
+    from starkware.cairo.bootloaders.simple_bootloader.utils import (
+        configure_fact_topologies,
+        write_to_fact_topologies_file,
+    )
+
+    # Compute the fact topologies of the plain packed outputs based on packed_outputs and
+    # fact_topologies of the inner tasks.
+    plain_fact_topologies: List[FactTopology] = compute_fact_topologies(
+        packed_outputs=packed_outputs, fact_topologies=fact_topologies,
+    )
+
+    # Configure the memory pages in the output builtin, based on plain_fact_topologies.
+    configure_fact_topologies(
+        fact_topologies=plain_fact_topologies, output_start=output_start,
+        output_builtin=output_builtin,
+    )
+
+    # Dump fact topologies to a json file.
+    if bootloader_input.fact_topologies_path is not None:
+        write_to_fact_topologies_file(
+            fact_topologies_path=bootloader_input.fact_topologies_path,
+            fact_topologies=plain_fact_topologies,
+        )
+%}
+*/
+pub fn bootloader_compute_fact_topologies(
+    vm: &mut VirtualMachine,
+    exec_scopes: &ExecutionScopes,
+) -> Result<(), HintError> {
+    let plain_fact_topologies = compute_fact_topologies(
+        exec_scopes.get_list_ref::<PackedOutput>("packed_outputs")?,
+        exec_scopes.get_list_ref::<FactTopology>("fact_topologies")?,
+    )?;
+    let output_start: Relocatable = exec_scopes.get("output_start")?;
+    configure_fact_topologies(
+        &plain_fact_topologies,
+        output_start,
+        vm.get_output_builtin_mut()?,
+    )?;
+
+    #[cfg(feature = "std")]
+    if let Some(path) = &exec_scopes
+        .get_ref::<BootloaderInput>("bootloader_input")?
+        .simple_bootloader_input
+        .fact_topologies_path
+    {
+        super::fact_topologies::write_to_fact_topologies_file(
+            std::path::Path::new(path),
+            &plain_fact_topologies,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hint_processor::builtin_hint_processor::bootloader::types::{
+        BootloaderConfig, CompositePackedOutput, SimpleBootloaderInput,
+    };
+    use crate::utils::test_utils::*;
+    use crate::vm::runners::builtin_runner::{BuiltinRunner, OutputBuiltinRunner};
+    use crate::vm::runners::cairo_pie::PublicMemoryPage;
+    use assert_matches::assert_matches;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn bootloader_input(packed_outputs: Vec<PackedOutput>) -> BootloaderInput {
+        BootloaderInput {
+            simple_bootloader_input: SimpleBootloaderInput {
+                fact_topologies_path: None,
+                single_page: false,
+                tasks: vec![],
+            },
+            bootloader_config: BootloaderConfig {
+                simple_bootloader_program_hash: Felt252::from(17),
+                supported_cairo_verifier_program_hashes: vec![Felt252::from(3), Felt252::from(4)],
+            },
+            packed_outputs,
+        }
+    }
+
+    fn composite_packed_output() -> PackedOutput {
+        PackedOutput::Composite(CompositePackedOutput {
+            outputs: vec![Felt252::from(10), Felt252::from(11)],
+            subtasks: vec![PackedOutput::Plain],
+            fact_topologies: vec![FactTopology {
+                tree_structure: vec![1, 0],
+                page_sizes: vec![2],
+            }],
+        })
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_prepare_and_restore_bootloader_output() {
+        let mut vm = vm!();
+        vm.builtin_runners = vec![BuiltinRunner::Output(OutputBuiltinRunner::new(true))];
+        vm.run_context.fp = 1;
+        add_segments!(vm, 2);
+        vm.get_output_builtin_mut()
+            .unwrap()
+            .add_page(1, Relocatable::from((0, 0)), 1)
+            .unwrap();
+        let original_state = vm.get_output_builtin_mut().unwrap().get_state();
+        let ids_data = ids_data!["simple_bootloader_output_start"];
+        let mut exec_scopes = scope![("bootloader_input", bootloader_input(vec![]))];
+
+        assert_matches!(
+            prepare_simple_bootloader_output_segment(
+                &mut vm,
+                &mut exec_scopes,
+                &ids_data,
+                &ApTracking::default()
+            ),
+            Ok(())
+        );
+        check_memory![vm.segments.memory, ((1, 0), (2, 0))];
+        let output_builtin = vm.get_output_builtin_mut().unwrap();
+        assert_eq!(output_builtin.base(), 2);
+        assert!(output_builtin.pages.is_empty());
+
+        assert_matches!(restore_bootloader_output(&mut vm, &mut exec_scopes), Ok(()));
+        assert_eq!(
+            vm.get_output_builtin_mut().unwrap().get_state(),
+            original_state
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_load_bootloader_config() {
+        let mut vm = vm!();
+        vm.run_context.fp = 1;
+        add_segments!(vm, 2);
+        let ids_data = ids_data!["bootloader_config"];
+        let mut exec_scopes = scope![("bootloader_input", bootloader_input(vec![]))];
+
+        assert_matches!(
+            load_bootloader_config(&mut vm, &mut exec_scopes, &ids_data, &ApTracking::default()),
+            Ok(())
+        );
+        check_memory![
+            vm.segments.memory,
+            ((1, 0), (3, 0)),
+            ((3, 0), 17),
+            ((3, 1), 2),
+            ((3, 2), (2, 0)),
+            ((2, 0), 3),
+            ((2, 1), 4)
+        ];
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_enter_packed_output_scope() {
+        let mut vm = vm!();
+        vm.run_context.fp = 1;
+        vm.segments = segments![((1, 0), 1)];
+        let ids_data = ids_data!["n_subtasks"];
+        let mut exec_scopes = scope![(
+            "packed_outputs",
+            vec![PackedOutput::Plain, composite_packed_output()]
+        )];
+
+        assert_matches!(
+            enter_packed_output_scope(&mut vm, &mut exec_scopes, &ids_data, &ApTracking::default()),
+            Ok(())
+        );
+        assert_eq!(exec_scopes.data.len(), 2);
+        assert_eq!(
+            exec_scopes
+                .get_ref::<PackedOutput>("packed_output")
+                .unwrap(),
+            &composite_packed_output()
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_is_plain_packed_output() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        let exec_scopes = scope![("packed_output", PackedOutput::Plain)];
+
+        assert_matches!(is_plain_packed_output(&mut vm, &exec_scopes), Ok(()));
+        check_memory![vm.segments.memory, ((1, 0), 1)];
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_assert_is_composite_packed_output_plain() {
+        let exec_scopes = scope![("packed_output", PackedOutput::Plain)];
+        assert_matches!(
+            assert_is_composite_packed_output(&exec_scopes),
+            Err(HintError::NotCompositePackedOutput)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_guess_pre_image_of_subtasks_output_hash() {
+        let mut vm = vm!();
+        vm.run_context.fp = 2;
+        add_segments!(vm, 2);
+        let ids_data = ids_data!["nested_subtasks_output_len", "nested_subtasks_output"];
+        let exec_scopes = scope![("packed_output", composite_packed_output())];
+
+        assert_matches!(
+            guess_pre_image_of_subtasks_output_hash(
+                &mut vm,
+                &exec_scopes,
+                &ids_data,
+                &ApTracking::default()
+            ),
+            Ok(())
+        );
+        check_memory![
+            vm.segments.memory,
+            ((1, 0), 2),
+            ((1, 1), (2, 0)),
+            ((2, 0), 10),
+            ((2, 1), 11)
+        ];
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_set_packed_output_to_subtasks() {
+        let mut exec_scopes = scope![("packed_output", composite_packed_output())];
+        assert_matches!(set_packed_output_to_subtasks(&mut exec_scopes), Ok(()));
+        assert_eq!(
+            exec_scopes
+                .get_list_ref::<PackedOutput>("packed_outputs")
+                .unwrap(),
+            &vec![PackedOutput::Plain]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_bootloader_compute_fact_topologies() {
+        let mut vm = vm!();
+        vm.builtin_runners = vec![BuiltinRunner::Output(OutputBuiltinRunner::new(true))];
+        add_segments!(vm, 1);
+        let fact_topologies = vec![
+            FactTopology {
+                tree_structure: vec![1, 0],
+                page_sizes: vec![3],
+            },
+            FactTopology {
+                tree_structure: vec![1, 0],
+                page_sizes: vec![4],
+            },
+        ];
+        let packed_outputs = vec![PackedOutput::Plain, composite_packed_output()];
+        let exec_scopes = scope![
+            ("bootloader_input", bootloader_input(packed_outputs.clone())),
+            ("packed_outputs", packed_outputs),
+            ("fact_topologies", fact_topologies),
+            ("output_start", Relocatable::from((0, 1)))
+        ];
+
+        assert_matches!(
+            bootloader_compute_fact_topologies(&mut vm, &exec_scopes),
+            Ok(())
+        );
+        // The composite output is replaced by the fact topology of its subtask
+        assert_eq!(
+            vm.get_output_builtin_mut().unwrap().pages,
+            HashMap::from([
+                (1, PublicMemoryPage { start: 3, size: 3 }),
+                (2, PublicMemoryPage { start: 8, size: 2 })
+            ])
+        );
+    }
+}
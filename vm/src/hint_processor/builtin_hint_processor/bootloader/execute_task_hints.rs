@@ -0,0 +1,771 @@
+use crate::stdlib::{collections::HashMap, prelude::*};
+
+use super::fact_topologies::get_task_fact_topology;
+use super::load_cairo_pie::load_cairo_pie;
+use super::program_loader::{load_program, BOOTLOADER_VERSION};
+use super::types::{FactTopology, Task};
+use crate::any_box;
+use crate::hint_processor::builtin_hint_processor::hint_utils::{
+    get_integer_from_var_name, get_ptr_from_var_name, get_relocatable_from_var_name,
+    insert_value_from_var_name,
+};
+use crate::hint_processor::hint_processor_definition::HintReference;
+use crate::program_hash::{compute_program_hash, ProgramHashFunction};
+use crate::serde::deserialize_program::ApTracking;
+use crate::types::builtin_name::BuiltinName;
+use crate::types::exec_scope::ExecutionScopes;
+use crate::types::relocatable::Relocatable;
+use crate::vm::errors::{hint_errors::HintError, memory_errors::MemoryError};
+use crate::vm::runners::builtin_runner::OutputBuiltinState;
+use crate::vm::vm_core::VirtualMachine;
+use crate::Felt252;
+
+/// The builtins supported by the bootloader, in the order of the fields of its `BuiltinData`
+/// struct.
+pub const ALL_BUILTINS: [BuiltinName; 8] = [
+    BuiltinName::output,
+    BuiltinName::pedersen,
+    BuiltinName::range_check,
+    BuiltinName::ecdsa,
+    BuiltinName::bitwise,
+    BuiltinName::ec_op,
+    BuiltinName::keccak,
+    BuiltinName::poseidon,
+];
+
+/// Size of the bootloader's `BuiltinData` struct.
+pub const BUILTIN_DATA_SIZE: usize = ALL_BUILTINS.len();
+
+/// Offset of `builtin_list` in the bootloader's `ProgramHeader` struct.
+pub const PROGRAM_HEADER_BUILTIN_LIST_OFFSET: usize = 4;
+
+/*
+Implements hint:
+%{ ids.program_data_ptr = program_data_base = segments.add() %}
+*/
+pub fn allocate_program_data_segment(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let program_data_base = vm.add_memory_segment();
+    insert_value_from_var_name(
+        "program_data_ptr",
+        program_data_base,
+        vm,
+        ids_data,
+        ap_tracking,
+    )?;
+    exec_scopes.insert_value("program_data_base", program_data_base);
+    Ok(())
+}
+
+/*
+Implements hint:
+%{
+    from starkware.cairo.bootloaders.simple_bootloader.utils import load_program
+
+    # Call load_program to load the program header and code to memory.
+    program_address, program_data_size = load_program(
+        task=task, memory=memory, program_header=ids.program_header,
+        builtins_offset=ids.ProgramHeader.builtin_list)
+    segments.finalize(program_data_base.segment_index, program_data_size)
+%}
+*/
+pub fn load_program_hint(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let program = exec_scopes.get_ref::<Task>("task")?.get_program()?;
+    let program_data_base: Relocatable = exec_scopes.get("program_data_base")?;
+    let program_header = get_ptr_from_var_name("program_header", vm, ids_data, ap_tracking)?;
+
+    let loaded_program = load_program(
+        &mut vm.segments,
+        &program,
+        program_header,
+        PROGRAM_HEADER_BUILTIN_LIST_OFFSET,
+    )?;
+    vm.segments.finalize(
+        Some(loaded_program.size),
+        program_data_base.segment_index as usize,
+        None,
+    );
+
+    exec_scopes.insert_value("program_address", loaded_program.code_address);
+    Ok(())
+}
+
+/*
+Implements hint:
+%{
+    # Validate hash.
+    from starkware.cairo.bootloaders.hash_program import compute_program_hash_chain
+
+    assert memory[ids.output_ptr + 1] == compute_program_hash_chain(task.get_program()), \
+      'Computed hash does not match input.'
+%}
+*/
+pub fn validate_hash(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    check_program_hash(
+        vm,
+        exec_scopes,
+        ids_data,
+        ap_tracking,
+        ProgramHashFunction::Pedersen,
+    )
+}
+
+/*
+Implements hint:
+%{
+    # Validate hash.
+    from starkware.cairo.bootloaders.hash_program import compute_program_hash_chain
+
+    assert memory[ids.output_ptr + 1] == compute_program_hash_chain(
+        program=task.get_program(),
+        use_poseidon=bool(ids.use_poseidon)), 'Computed hash does not match input.'
+%}
+*/
+pub fn validate_hash_with_hash_function(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let use_poseidon = get_integer_from_var_name("use_poseidon", vm, ids_data, ap_tracking)?;
+    let hash_function = if use_poseidon == Felt252::ZERO {
+        ProgramHashFunction::Pedersen
+    } else {
+        ProgramHashFunction::Poseidon
+    };
+    check_program_hash(vm, exec_scopes, ids_data, ap_tracking, hash_function)
+}
+
+// Checks that the hash of the task's program matches the one written at output_ptr + 1
+fn check_program_hash(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+    hash_function: ProgramHashFunction,
+) -> Result<(), HintError> {
+    let program = exec_scopes.get_ref::<Task>("task")?.get_program()?;
+    let output_ptr = get_ptr_from_var_name("output_ptr", vm, ids_data, ap_tracking)?;
+    let program_hash = vm.get_integer((output_ptr + 1)?)?.into_owned();
+
+    let computed_hash = Felt252::from_bytes_be(
+        &compute_program_hash(&program, BOOTLOADER_VERSION, hash_function)?.to_bytes_be(),
+    );
+    if computed_hash != program_hash {
+        return Err(HintError::ProgramHashMismatch(Box::new((
+            computed_hash,
+            program_hash,
+        ))));
+    }
+    Ok(())
+}
+
+/*
+Implements hint:
+%{
+    # Sanity check.
+    assert ids.program_address == program_address
+%}
+*/
+pub fn assert_program_address(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let ids_program_address = get_ptr_from_var_name("program_address", vm, ids_data, ap_tracking)?;
+    let program_address: Relocatable = exec_scopes.get("program_address")?;
+    if ids_program_address != program_address {
+        return Err(HintError::ProgramAddressMismatch(Box::new((
+            ids_program_address,
+            program_address,
+        ))));
+    }
+    Ok(())
+}
+
+/*
+Implements hint:
+%{
+    from starkware.cairo.bootloaders.simple_bootloader.objects import (
+        CairoPieTask,
+        RunProgramTask,
+        Task,
+    )
+    from starkware.cairo.bootloaders.simple_bootloader.utils import (
+        load_cairo_pie,
+        prepare_output_runner,
+    )
+
+    assert isinstance(task, Task)
+    n_builtins = len(task.get_program().builtins)
+    new_task_locals = {}
+    if isinstance(task, RunProgramTask):
+        new_task_locals['program_input'] = task.program_input
+        new_task_locals['WITH_BOOTLOADER'] = True
+
+        vm_load_program(task.program, program_address)
+    elif isinstance(task, CairoPieTask):
+        ret_pc = ids.ret_pc_label.instruction_offset_ - ids.call_task.instruction_offset_ + pc
+        load_cairo_pie(
+            task=task.cairo_pie, memory=memory, segments=segments,
+            program_address=program_address, execution_segment_address= ap - n_builtins,
+            builtin_runners=builtin_runners, ret_fp=fp, ret_pc=ret_pc)
+    else:
+        raise NotImplementedError(f'Unexpected task type: {type(task).__name__}.')
+
+    output_runner_data = prepare_output_runner(
+        task=task,
+        output_builtin=output_builtin,
+        output_ptr=ids.pre_execution_builtin_ptrs.output)
+    vm_enter_scope(new_task_locals)
+%}
+The hints of program tasks (`vm_load_program`) are loaded by the hint processor, see
+`BootloaderHintProcessor`.
+*/
+pub fn call_task(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let program_address: Relocatable = exec_scopes.get("program_address")?;
+    let pre_execution_builtin_ptrs =
+        get_ptr_from_var_name("pre_execution_builtin_ptrs", vm, ids_data, ap_tracking)?;
+
+    let output_runner_data = match exec_scopes.get_ref::<Task>("task")? {
+        Task::Program(_) => {
+            // Give the task a fresh output builtin state, starting at its output
+            let output_ptr = vm.get_relocatable(pre_execution_builtin_ptrs)?;
+            let output_builtin = vm.get_output_builtin_mut()?;
+            let output_builtin_state = output_builtin.get_state();
+            output_builtin.new_state(output_ptr.segment_index as usize, output_builtin.included);
+            Some(output_builtin_state)
+        }
+        Task::Pie(cairo_pie) => {
+            let n_builtins = cairo_pie.metadata.program.builtins.len();
+            // The task returns right after the call instruction this hint is attached to
+            let ret_pc = (vm.get_pc() + vm.decode_current_instruction()?.size())?;
+            let execution_segment_address = (vm.get_ap() - n_builtins)?;
+            let ret_fp = vm.get_fp();
+            load_cairo_pie(
+                cairo_pie,
+                &mut vm.segments,
+                program_address,
+                execution_segment_address,
+                ret_fp,
+                ret_pc,
+            )?;
+            None
+        }
+    };
+
+    exec_scopes.insert_value("output_runner_data", output_runner_data);
+    exec_scopes.enter_scope(HashMap::new());
+    Ok(())
+}
+
+/// Writes the builtin pointers after the execution of a task at `return_builtins_addr`, in
+/// the order of [`ALL_BUILTINS`]: the ones returned by the task (at `used_builtins_addr`) for
+/// the builtins it uses, and the ones before its execution for the rest.
+/// [(cairo_lang reference)](https://github.com/starkware-libs/cairo-lang/blob/efa9648f57568aad8f8a13fbf027d2de7c63c2c0/src/starkware/cairo/bootloaders/simple_bootloader/utils.py#L73)
+pub fn write_return_builtins(
+    vm: &mut VirtualMachine,
+    return_builtins_addr: Relocatable,
+    used_builtins: &[BuiltinName],
+    used_builtins_addr: Relocatable,
+    pre_execution_builtins_addr: Relocatable,
+    task: &Task,
+) -> Result<(), HintError> {
+    let mut used_builtin_offset = 0;
+    for (index, builtin) in ALL_BUILTINS.iter().enumerate() {
+        let pre_execution_addr = (pre_execution_builtins_addr + index)?;
+        let pre_execution_ptr = vm
+            .get_maybe(&pre_execution_addr)
+            .ok_or_else(|| MemoryError::UnknownMemoryCell(Box::new(pre_execution_addr)))?;
+        if !used_builtins.contains(builtin) {
+            // The builtin is unused, hence its value is the same as before calling the program
+            vm.insert_value((return_builtins_addr + index)?, pre_execution_ptr)?;
+            continue;
+        }
+
+        let return_ptr = vm.get_relocatable((used_builtins_addr + used_builtin_offset)?)?;
+        used_builtin_offset += 1;
+        vm.insert_value((return_builtins_addr + index)?, return_ptr)?;
+
+        if let Task::Pie(cairo_pie) = task {
+            let pre_execution_ptr = vm.get_relocatable(pre_execution_addr)?;
+            let used = (return_ptr - pre_execution_ptr)?;
+            let expected = cairo_pie
+                .metadata
+                .builtin_segments
+                .get(builtin)
+                .map(|segment| segment.size)
+                .unwrap_or_default();
+            if used != expected {
+                return Err(HintError::BuiltinUsageMismatch(Box::new((
+                    *builtin, expected, used,
+                ))));
+            }
+        }
+    }
+    Ok(())
+}
+
+/*
+Implements hint:
+%{
+    from starkware.cairo.bootloaders.simple_bootloader.utils import write_return_builtins
+
+    # Fill the values of all builtin pointers after executing the task.
+    builtins = task.get_program().builtins
+    write_return_builtins(
+        memory=memory, return_builtins_addr=ids.return_builtin_ptrs.address_,
+        used_builtins=builtins, used_builtins_addr=ids.used_builtins_addr,
+        pre_execution_builtins_addr=ids.pre_execution_builtin_ptrs.address_, task=task)
+
+    vm_enter_scope({'n_selected_builtins': n_builtins})
+%}
+*/
+pub fn write_return_builtins_hint(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let return_builtins_addr =
+        get_relocatable_from_var_name("return_builtin_ptrs", vm, ids_data, ap_tracking)?;
+    let used_builtins_addr =
+        get_ptr_from_var_name("used_builtins_addr", vm, ids_data, ap_tracking)?;
+    let pre_execution_builtins_addr =
+        get_ptr_from_var_name("pre_execution_builtin_ptrs", vm, ids_data, ap_tracking)?;
+
+    let task = exec_scopes.get_ref::<Task>("task")?;
+    let builtins = task.get_program()?.builtins;
+    write_return_builtins(
+        vm,
+        return_builtins_addr,
+        &builtins,
+        used_builtins_addr,
+        pre_execution_builtins_addr,
+        task,
+    )?;
+
+    exec_scopes.enter_scope(HashMap::from([(
+        String::from("n_selected_builtins"),
+        any_box!(builtins.len()),
+    )]));
+    Ok(())
+}
+
+/*
+Implements hint:
+%{
+    from starkware.cairo.bootloaders.simple_bootloader.utils import get_task_fact_topology
+
+    # Add the fact topology of the current task to 'fact_topologies'.
+    output_start = ids.pre_execution_builtin_ptrs.output
+    output_end = ids.return_builtin_ptrs.output
+    fact_topologies.append(get_task_fact_topology(
+        output_size=output_end - output_start,
+        task=task,
+        output_builtin=output_builtin,
+        output_runner_data=output_runner_data,
+    ))
+%}
+*/
+pub fn append_fact_topologies(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let pre_execution_builtin_ptrs =
+        get_ptr_from_var_name("pre_execution_builtin_ptrs", vm, ids_data, ap_tracking)?;
+    let return_builtin_ptrs =
+        get_relocatable_from_var_name("return_builtin_ptrs", vm, ids_data, ap_tracking)?;
+    // The output builtin is the first field of BuiltinData
+    let output_start = vm.get_relocatable(pre_execution_builtin_ptrs)?;
+    let output_end = vm.get_relocatable(return_builtin_ptrs)?;
+    let output_size = (output_end - output_start)?;
+
+    let output_runner_data: Option<OutputBuiltinState> = exec_scopes.get("output_runner_data")?;
+    let fact_topology = get_task_fact_topology(
+        output_size,
+        output_start,
+        exec_scopes.get_ref::<Task>("task")?,
+        vm.get_output_builtin_mut()?,
+        output_runner_data,
+    )?;
+    exec_scopes
+        .get_mut_list_ref::<FactTopology>("fact_topologies")?
+        .push(fact_topology);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::program::Program;
+    use crate::types::relocatable::MaybeRelocatable;
+    use crate::utils::test_utils::*;
+    use crate::vm::runners::builtin_runner::{BuiltinRunner, OutputBuiltinRunner};
+    use crate::vm::runners::cairo_pie::{
+        CairoPie, CairoPieAdditionalData, CairoPieMemory, CairoPieMetadata, CairoPieVersion,
+        SegmentInfo, StrippedProgram,
+    };
+    use crate::vm::runners::cairo_runner::ExecutionResources;
+    use assert_matches::assert_matches;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn program_task() -> Task {
+        Task::Program(program!(
+            builtins = vec![BuiltinName::output],
+            data = vec_data!((1), (2), (3)),
+            main = Some(1),
+        ))
+    }
+
+    fn pie_task(builtin_segments: HashMap<BuiltinName, SegmentInfo>) -> Task {
+        Task::Pie(Box::new(CairoPie {
+            metadata: CairoPieMetadata {
+                program: StrippedProgram {
+                    data: vec![],
+                    builtins: builtin_segments.keys().copied().collect(),
+                    main: 0,
+                    prime: (),
+                },
+                program_segment: SegmentInfo::from((0, 0)),
+                execution_segment: SegmentInfo::from((1, 0)),
+                ret_fp_segment: SegmentInfo::from((3, 0)),
+                ret_pc_segment: SegmentInfo::from((4, 0)),
+                builtin_segments,
+                extra_segments: vec![],
+            },
+            memory: CairoPieMemory(vec![]),
+            execution_resources: ExecutionResources::default(),
+            additional_data: CairoPieAdditionalData(HashMap::new()),
+            version: CairoPieVersion { cairo_pie: () },
+        }))
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_allocate_program_data_segment() {
+        let mut vm = vm!();
+        vm.run_context.fp = 1;
+        add_segments!(vm, 2);
+        let ids_data = ids_data!["program_data_ptr"];
+        let mut exec_scopes = ExecutionScopes::new();
+
+        assert_matches!(
+            allocate_program_data_segment(
+                &mut vm,
+                &mut exec_scopes,
+                &ids_data,
+                &ApTracking::default()
+            ),
+            Ok(())
+        );
+        check_memory![vm.segments.memory, ((1, 0), (2, 0))];
+        assert_eq!(
+            exec_scopes.get::<Relocatable>("program_data_base").unwrap(),
+            Relocatable::from((2, 0))
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_load_program_and_validate_hash() {
+        let mut vm = vm!();
+        vm.run_context.fp = 2;
+        // program_header, output_ptr
+        vm.segments = segments![((1, 0), (2, 0)), ((1, 1), (3, 0))];
+        add_segments!(vm, 2);
+        let ids_data = ids_data!["program_header", "output_ptr"];
+        let task = program_task();
+        let program_hash = Felt252::from_bytes_be(
+            &compute_program_hash(
+                &task.get_program().unwrap(),
+                BOOTLOADER_VERSION,
+                ProgramHashFunction::Pedersen,
+            )
+            .unwrap()
+            .to_bytes_be(),
+        );
+        vm.insert_value(Relocatable::from((3, 1)), program_hash)
+            .unwrap();
+        let mut exec_scopes = scope![
+            ("task", task),
+            ("program_data_base", Relocatable::from((2, 0)))
+        ];
+
+        assert_matches!(
+            load_program_hint(&mut vm, &mut exec_scopes, &ids_data, &ApTracking::default()),
+            Ok(())
+        );
+        // Header: data_length, bootloader_version, main, n_builtins, builtin_list
+        check_memory![
+            vm.segments.memory,
+            ((2, 0), 7),
+            ((2, 1), 0),
+            ((2, 2), 1),
+            ((2, 3), 1),
+            ((2, 5), 1),
+            ((2, 7), 3)
+        ];
+        assert_eq!(vm.segments.segment_sizes.get(&2), Some(&8));
+        assert_eq!(
+            exec_scopes.get::<Relocatable>("program_address").unwrap(),
+            Relocatable::from((2, 5))
+        );
+
+        assert_matches!(
+            validate_hash(&mut vm, &mut exec_scopes, &ids_data, &ApTracking::default()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_validate_hash_mismatch() {
+        let mut vm = vm!();
+        vm.run_context.fp = 1;
+        vm.segments = segments![((1, 0), (2, 0)), ((2, 1), 42)];
+        let ids_data = ids_data!["output_ptr"];
+        let mut exec_scopes = scope![("task", program_task())];
+
+        assert_matches!(
+            validate_hash(&mut vm, &mut exec_scopes, &ids_data, &ApTracking::default()),
+            Err(HintError::ProgramHashMismatch(bx)) if bx.1 == Felt252::from(42)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_validate_hash_with_poseidon() {
+        let mut vm = vm!();
+        vm.run_context.fp = 2;
+        let task = program_task();
+        let program_hash = Felt252::from_bytes_be(
+            &compute_program_hash(
+                &task.get_program().unwrap(),
+                BOOTLOADER_VERSION,
+                ProgramHashFunction::Poseidon,
+            )
+            .unwrap()
+            .to_bytes_be(),
+        );
+        // output_ptr, use_poseidon
+        vm.segments = segments![((1, 0), (2, 0)), ((1, 1), 1)];
+        add_segments!(vm, 1);
+        vm.insert_value(Relocatable::from((2, 1)), program_hash)
+            .unwrap();
+        let ids_data = ids_data!["output_ptr", "use_poseidon"];
+        let mut exec_scopes = scope![("task", task)];
+
+        assert_matches!(
+            validate_hash_with_hash_function(
+                &mut vm,
+                &mut exec_scopes,
+                &ids_data,
+                &ApTracking::default()
+            ),
+            Ok(())
+        );
+        // The Pedersen hash of the program doesn't match
+        assert_matches!(
+            validate_hash(&mut vm, &mut exec_scopes, &ids_data, &ApTracking::default()),
+            Err(HintError::ProgramHashMismatch(_))
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_assert_program_address_mismatch() {
+        let mut vm = vm!();
+        vm.run_context.fp = 1;
+        vm.segments = segments![((1, 0), (2, 3))];
+        let ids_data = ids_data!["program_address"];
+        let mut exec_scopes = scope![("program_address", Relocatable::from((2, 5)))];
+
+        assert_matches!(
+            assert_program_address(&mut vm, &mut exec_scopes, &ids_data, &ApTracking::default()),
+            Err(HintError::ProgramAddressMismatch(bx))
+                if *bx == (Relocatable::from((2, 3)), Relocatable::from((2, 5)))
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_call_task_program() {
+        let mut vm = vm!();
+        vm.builtin_runners = vec![BuiltinRunner::Output(OutputBuiltinRunner::new(true))];
+        vm.run_context.fp = 1;
+        // pre_execution_builtin_ptrs points to the builtin pointers, output first
+        vm.segments = segments![((1, 0), (2, 0)), ((2, 0), (0, 3))];
+        vm.get_output_builtin_mut()
+            .unwrap()
+            .add_attribute("gps_fact_topology".to_string(), vec![1, 0]);
+        let ids_data = ids_data!["pre_execution_builtin_ptrs"];
+        let mut exec_scopes = scope![
+            ("task", program_task()),
+            ("program_address", Relocatable::from((3, 0)))
+        ];
+
+        assert_matches!(
+            call_task(&mut vm, &mut exec_scopes, &ids_data, &ApTracking::default()),
+            Ok(())
+        );
+        // The task runs with a clean output builtin state
+        assert!(vm.get_output_builtin_mut().unwrap().attributes.is_empty());
+        assert_eq!(exec_scopes.data.len(), 2);
+        exec_scopes.exit_scope().unwrap();
+        let output_runner_data = exec_scopes
+            .get::<Option<OutputBuiltinState>>("output_runner_data")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            output_runner_data.attributes.get("gps_fact_topology"),
+            Some(&vec![1, 0])
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_write_return_builtins() {
+        let mut vm = vm!();
+        vm.run_context.fp = 3;
+        // return_builtin_ptrs is a struct at fp, the other ids are pointers
+        vm.segments = segments![
+            ((1, 1), (2, 0)),
+            ((1, 2), (3, 0)),
+            ((2, 0), (4, 2)),
+            ((3, 0), (4, 0)),
+            ((3, 1), (5, 0)),
+            ((3, 2), (6, 0)),
+            ((3, 3), (7, 0)),
+            ((3, 4), (8, 0)),
+            ((3, 5), (9, 0)),
+            ((3, 6), (10, 0)),
+            ((3, 7), (11, 0))
+        ];
+        let ids_data = non_continuous_ids_data![
+            ("return_builtin_ptrs", 0),
+            ("used_builtins_addr", -2),
+            ("pre_execution_builtin_ptrs", -1)
+        ];
+        let mut exec_scopes = scope![("task", program_task())];
+
+        assert_matches!(
+            write_return_builtins_hint(
+                &mut vm,
+                &mut exec_scopes,
+                &ids_data,
+                &ApTracking::default()
+            ),
+            Ok(())
+        );
+        let return_builtins: Vec<_> = (0..BUILTIN_DATA_SIZE)
+            .map(|i| vm.get_relocatable(Relocatable::from((1, 3 + i))).unwrap())
+            .collect();
+        assert_eq!(return_builtins[0], Relocatable::from((4, 2)));
+        assert_eq!(return_builtins[1], Relocatable::from((5, 0)));
+        assert_eq!(return_builtins[7], Relocatable::from((11, 0)));
+        assert_eq!(exec_scopes.get::<usize>("n_selected_builtins").unwrap(), 1);
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn write_return_builtins_inconsistent_pie() {
+        let mut vm = vm!();
+        vm.segments = segments![((1, 0), (2, 3)), ((1, 1), (2, 0))];
+        add_segments!(vm, 2);
+        let pre_execution_ptrs: Vec<MaybeRelocatable> = (0..BUILTIN_DATA_SIZE)
+            .map(|i| Relocatable::from((2 + i as isize, 0)).into())
+            .collect();
+        vm.load_data(Relocatable::from((3, 0)), &pre_execution_ptrs)
+            .unwrap();
+        let task = pie_task(HashMap::from([(
+            BuiltinName::output,
+            SegmentInfo::from((2, 2)),
+        )]));
+
+        assert_matches!(
+            write_return_builtins(
+                &mut vm,
+                Relocatable::from((3, 10)),
+                &[BuiltinName::output],
+                Relocatable::from((1, 0)),
+                Relocatable::from((3, 0)),
+                &task,
+            ),
+            Err(HintError::BuiltinUsageMismatch(bx)) if *bx == (BuiltinName::output, 2, 3)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_append_fact_topologies_program() {
+        let mut vm = vm!();
+        vm.builtin_runners = vec![BuiltinRunner::Output(OutputBuiltinRunner::new(true))];
+        vm.run_context.fp = 2;
+        // return_builtin_ptrs is a struct at fp - 2, pre_execution_builtin_ptrs a pointer
+        vm.segments = segments![((1, 0), (0, 5)), ((1, 1), (2, 0)), ((2, 0), (0, 2))];
+        vm.get_output_builtin_mut()
+            .unwrap()
+            .add_page(1, Relocatable::from((0, 3)), 2)
+            .unwrap();
+        vm.get_output_builtin_mut()
+            .unwrap()
+            .add_attribute("gps_fact_topology".to_string(), vec![2, 1, 0, 2]);
+        let ids_data = ids_data!["return_builtin_ptrs", "pre_execution_builtin_ptrs"];
+        let saved_state = OutputBuiltinRunner::new(true).get_state();
+        let mut exec_scopes = scope![
+            ("task", program_task()),
+            ("output_runner_data", Some(saved_state.clone())),
+            ("fact_topologies", Vec::<FactTopology>::new())
+        ];
+
+        assert_matches!(
+            append_fact_topologies(&mut vm, &mut exec_scopes, &ids_data, &ApTracking::default()),
+            Ok(())
+        );
+        assert_eq!(
+            exec_scopes
+                .get_list_ref::<FactTopology>("fact_topologies")
+                .unwrap(),
+            &vec![FactTopology {
+                tree_structure: vec![2, 1, 0, 2],
+                page_sizes: vec![1, 2],
+            }]
+        );
+        assert_eq!(
+            vm.get_output_builtin_mut().unwrap().get_state(),
+            saved_state
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn task_program_without_main() {
+        let task = Task::Program(Program::default());
+        assert_matches!(task.get_program(), Err(HintError::TaskProgramNoMain));
+    }
+}
@@ -0,0 +1,207 @@
+use crate::stdlib::prelude::*;
+
+use super::types::{PackedOutput, Task};
+use crate::fact_topology::{
+    get_fact_topology_from_additional_data, FactTopology, FactTopologyError,
+};
+use crate::types::builtin_name::BuiltinName;
+use crate::types::relocatable::Relocatable;
+use crate::vm::errors::{hint_errors::HintError, vm_errors::VirtualMachineError};
+use crate::vm::runners::builtin_runner::{OutputBuiltinRunner, OutputBuiltinState};
+use crate::vm::runners::cairo_pie::{BuiltinAdditionalData, OutputBuiltinAdditionalData};
+
+fn invalid_fact_topology(msg: String) -> HintError {
+    FactTopologyError::InvalidFactTopology(msg.into_boxed_str()).into()
+}
+
+/// Computes the fact topology of a task that just ran, from the pages its output builtin
+/// recorded (program tasks) or from the additional data of its PIE. For program tasks, the
+/// output builtin state saved before running the task (`output_runner_data`) is restored.
+pub fn get_task_fact_topology(
+    output_size: usize,
+    output_start: Relocatable,
+    task: &Task,
+    output_builtin: &mut OutputBuiltinRunner,
+    output_runner_data: Option<OutputBuiltinState>,
+) -> Result<FactTopology, HintError> {
+    match (task, output_runner_data) {
+        (Task::Program(_), Some(output_runner_data)) => {
+            let additional_data = OutputBuiltinAdditionalData {
+                pages: output_builtin.pages.clone(),
+                attributes: output_builtin.attributes.clone(),
+            };
+            let fact_topology = get_fact_topology_from_additional_data(
+                output_size,
+                output_start.offset,
+                &additional_data,
+            )?;
+            output_builtin.set_state(output_runner_data);
+            Ok(fact_topology)
+        }
+        (Task::Pie(cairo_pie), None) => match cairo_pie.additional_data.0.get(&BuiltinName::output)
+        {
+            Some(BuiltinAdditionalData::Output(additional_data)) => Ok(
+                get_fact_topology_from_additional_data(output_size, 0, additional_data)?,
+            ),
+            _ => Err(invalid_fact_topology(
+                "The Cairo PIE has no output builtin data.".to_string(),
+            )),
+        },
+        _ => Err(invalid_fact_topology(
+            "The output builtin state doesn't match the task type.".to_string(),
+        )),
+    }
+}
+
+/// Adds a page to the output builtin for each page size, starting at `output_start` with page
+/// id `cur_page_id`. Returns the next free page id.
+pub fn add_consecutive_output_pages(
+    page_sizes: &[usize],
+    output_builtin: &mut OutputBuiltinRunner,
+    mut cur_page_id: usize,
+    mut output_start: Relocatable,
+) -> Result<usize, HintError> {
+    for page_size in page_sizes {
+        output_builtin
+            .add_page(cur_page_id, output_start, *page_size)
+            .map_err(VirtualMachineError::RunnerError)?;
+        output_start = (output_start + *page_size)?;
+        cur_page_id += 1;
+    }
+    Ok(cur_page_id)
+}
+
+/// Configures the pages of the output builtin for the outputs of the given tasks, which are
+/// laid out one after the other starting at `output_start`. Each task output is prefixed by
+/// two words written by the bootloader (its size and its program hash), which stay in page 0.
+pub fn configure_fact_topologies(
+    fact_topologies: &[FactTopology],
+    mut output_start: Relocatable,
+    output_builtin: &mut OutputBuiltinRunner,
+) -> Result<(), HintError> {
+    // Page 0 is reserved for the bootloader program and arguments
+    let mut cur_page_id = 1;
+    for fact_topology in fact_topologies {
+        // Skip the bootloader output of the task
+        output_start = (output_start + 2)?;
+        cur_page_id = add_consecutive_output_pages(
+            &fact_topology.page_sizes,
+            output_builtin,
+            cur_page_id,
+            output_start,
+        )?;
+        output_start = (output_start + fact_topology.page_sizes.iter().sum::<usize>())?;
+    }
+    Ok(())
+}
+
+/// Flattens the fact topologies of the tasks of a bootloader run: plain outputs keep their own
+/// topology, while composite outputs are replaced by the topologies of their subtasks.
+pub fn compute_fact_topologies(
+    packed_outputs: &[PackedOutput],
+    fact_topologies: &[FactTopology],
+) -> Result<Vec<FactTopology>, HintError> {
+    if packed_outputs.len() != fact_topologies.len() {
+        return Err(invalid_fact_topology(format!(
+            "Expected {} fact topologies, got {}.",
+            packed_outputs.len(),
+            fact_topologies.len()
+        )));
+    }
+    let mut plain_fact_topologies = Vec::new();
+    for (packed_output, fact_topology) in packed_outputs.iter().zip(fact_topologies) {
+        match packed_output {
+            PackedOutput::Plain => plain_fact_topologies.push(fact_topology.clone()),
+            PackedOutput::Composite(composite) => plain_fact_topologies.extend(
+                compute_fact_topologies(&composite.subtasks, &composite.fact_topologies)?,
+            ),
+        }
+    }
+    Ok(plain_fact_topologies)
+}
+
+/// Writes the fact topologies as json, in the format expected by the prover.
+#[cfg(feature = "std")]
+pub fn write_to_fact_topologies_file(
+    path: &std::path::Path,
+    fact_topologies: &[FactTopology],
+) -> Result<(), HintError> {
+    let file = std::fs::File::create(path)
+        .map_err(|e| HintError::FactTopologiesFile(e.to_string().into_boxed_str()))?;
+    serde_json::to_writer(
+        std::io::BufWriter::new(file),
+        &serde_json::json!({ "fact_topologies": fact_topologies }),
+    )
+    .map_err(|e| HintError::FactTopologiesFile(e.to_string().into_boxed_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::runners::cairo_pie::{Pages, PublicMemoryPage};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn pages(pages: &[(usize, usize, usize)]) -> Pages {
+        pages
+            .iter()
+            .map(|(id, start, size)| {
+                (
+                    *id,
+                    PublicMemoryPage {
+                        start: *start,
+                        size: *size,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn configure_fact_topologies_adds_pages() {
+        let mut output_builtin = OutputBuiltinRunner::new(true);
+        let fact_topologies = vec![
+            FactTopology {
+                tree_structure: vec![1, 0],
+                page_sizes: vec![3],
+            },
+            FactTopology {
+                tree_structure: vec![2, 1, 0, 2],
+                page_sizes: vec![1, 2],
+            },
+        ];
+        configure_fact_topologies(
+            &fact_topologies,
+            Relocatable::from((0, 1)),
+            &mut output_builtin,
+        )
+        .unwrap();
+        assert_eq!(
+            output_builtin.pages,
+            pages(&[(1, 3, 3), (2, 8, 1), (3, 9, 2)])
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn compute_fact_topologies_flattens_composite_outputs() {
+        let topology = |size| FactTopology {
+            tree_structure: vec![1, 0],
+            page_sizes: vec![size],
+        };
+        let packed_outputs = vec![
+            PackedOutput::Plain,
+            PackedOutput::Composite(super::super::types::CompositePackedOutput {
+                outputs: vec![],
+                subtasks: vec![PackedOutput::Plain, PackedOutput::Plain],
+                fact_topologies: vec![topology(2), topology(3)],
+            }),
+        ];
+        assert_eq!(
+            compute_fact_topologies(&packed_outputs, &[topology(1), topology(5)]).unwrap(),
+            vec![topology(1), topology(2), topology(3)]
+        );
+    }
+}
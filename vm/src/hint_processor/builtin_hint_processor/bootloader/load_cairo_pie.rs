@@ -0,0 +1,167 @@
+use crate::stdlib::{collections::HashMap, prelude::*};
+
+use crate::types::relocatable::{MaybeRelocatable, Relocatable};
+use crate::vm::errors::hint_errors::HintError;
+use crate::vm::errors::memory_errors::MemoryError;
+use crate::vm::runners::cairo_pie::CairoPie;
+use crate::vm::vm_memory::memory_segments::MemorySegmentManager;
+
+fn relocate_address(
+    addr: Relocatable,
+    segment_offsets: &HashMap<isize, Relocatable>,
+) -> Result<Relocatable, HintError> {
+    let base = segment_offsets
+        .get(&addr.segment_index)
+        .ok_or_else(|| MemoryError::UnknownMemoryCell(Box::new(addr)))?;
+    Ok((*base + addr.offset)?)
+}
+
+fn relocate_value(
+    value: &MaybeRelocatable,
+    segment_offsets: &HashMap<isize, Relocatable>,
+) -> Result<MaybeRelocatable, HintError> {
+    match value {
+        MaybeRelocatable::Int(_) => Ok(value.clone()),
+        MaybeRelocatable::RelocatableValue(addr) => {
+            Ok(relocate_address(*addr, segment_offsets)?.into())
+        }
+    }
+}
+
+/// Loads the memory of a Cairo PIE so that it can be re-executed as a bootloader task.
+///
+/// Every segment of the PIE is mapped onto a segment of the current run: the program onto
+/// `program_address`, the execution segment onto `execution_segment_address`, the return
+/// pointers onto `ret_fp` and `ret_pc`, the builtins onto the pointers already written at the
+/// start of the execution segment, and each extra segment onto a new segment.
+/// [(cairo_lang reference)](https://github.com/starkware-libs/cairo-lang/blob/efa9648f57568aad8f8a13fbf027d2de7c63c2c0/src/starkware/cairo/bootloaders/simple_bootloader/utils.py#L109)
+pub fn load_cairo_pie(
+    cairo_pie: &CairoPie,
+    segments: &mut MemorySegmentManager,
+    program_address: Relocatable,
+    execution_segment_address: Relocatable,
+    ret_fp: Relocatable,
+    ret_pc: Relocatable,
+) -> Result<(), HintError> {
+    let metadata = &cairo_pie.metadata;
+    let mut segment_offsets = HashMap::from([
+        (metadata.program_segment.index, program_address),
+        (metadata.execution_segment.index, execution_segment_address),
+        (metadata.ret_fp_segment.index, ret_fp),
+        (metadata.ret_pc_segment.index, ret_pc),
+    ]);
+
+    // The builtin segments are mapped onto the pointers of the initial stack
+    let pie_memory: HashMap<(usize, usize), &MaybeRelocatable> = cairo_pie
+        .memory
+        .0
+        .iter()
+        .map(|(addr, value)| (*addr, value))
+        .collect();
+    for index in 0..metadata.program.builtins.len() {
+        let orig_address = (metadata.execution_segment.index as usize, index);
+        let orig_base = pie_memory
+            .get(&orig_address)
+            .and_then(|value| value.get_relocatable())
+            .ok_or_else(|| {
+                MemoryError::UnknownMemoryCell(Box::new(Relocatable::from((
+                    metadata.execution_segment.index,
+                    index,
+                ))))
+            })?;
+        let base = segments
+            .memory
+            .get_relocatable((execution_segment_address + index)?)?;
+        segment_offsets.insert(orig_base.segment_index, base);
+    }
+
+    for segment_info in metadata.extra_segments.iter() {
+        let base = segments.add();
+        segments.finalize(Some(segment_info.size), base.segment_index as usize, None);
+        segment_offsets.insert(segment_info.index, base);
+    }
+
+    for ((segment_index, offset), value) in cairo_pie.memory.0.iter() {
+        let addr = relocate_address(
+            Relocatable::from((*segment_index as isize, *offset)),
+            &segment_offsets,
+        )?;
+        segments
+            .memory
+            .insert(addr, relocate_value(value, &segment_offsets)?)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::builtin_name::BuiltinName;
+    use crate::utils::test_utils::*;
+    use crate::vm::runners::cairo_pie::{
+        CairoPieAdditionalData, CairoPieMemory, CairoPieMetadata, CairoPieVersion, SegmentInfo,
+        StrippedProgram,
+    };
+    use crate::vm::runners::cairo_runner::ExecutionResources;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn load_cairo_pie_relocates_segments() {
+        let cairo_pie = CairoPie {
+            metadata: CairoPieMetadata {
+                program: StrippedProgram {
+                    data: vec![MaybeRelocatable::from(5)],
+                    builtins: vec![BuiltinName::output],
+                    main: 0,
+                    prime: (),
+                },
+                program_segment: SegmentInfo::from((0, 1)),
+                execution_segment: SegmentInfo::from((1, 3)),
+                ret_fp_segment: SegmentInfo::from((3, 0)),
+                ret_pc_segment: SegmentInfo::from((4, 0)),
+                builtin_segments: HashMap::from([(BuiltinName::output, SegmentInfo::from((2, 1)))]),
+                extra_segments: vec![SegmentInfo::from((5, 1))],
+            },
+            memory: CairoPieMemory(vec![
+                ((0, 0), MaybeRelocatable::from(5)),
+                ((1, 0), MaybeRelocatable::from((2, 0))),
+                ((1, 1), MaybeRelocatable::from((3, 0))),
+                ((1, 2), MaybeRelocatable::from((4, 0))),
+                ((2, 0), MaybeRelocatable::from(7)),
+                ((5, 0), MaybeRelocatable::from(9)),
+            ]),
+            execution_resources: ExecutionResources::default(),
+            additional_data: CairoPieAdditionalData(HashMap::new()),
+            version: CairoPieVersion { cairo_pie: () },
+        };
+        let mut vm = vm!();
+        // The output builtin pointer of the task
+        vm.segments = segments![((1, 3), (2, 5))];
+        add_segments!(vm, 2);
+
+        load_cairo_pie(
+            &cairo_pie,
+            &mut vm.segments,
+            Relocatable::from((3, 0)),
+            Relocatable::from((1, 3)),
+            Relocatable::from((1, 10)),
+            Relocatable::from((0, 20)),
+        )
+        .unwrap();
+
+        check_memory![
+            vm.segments.memory,
+            ((3, 0), 5),
+            ((1, 3), (2, 5)),
+            ((1, 4), (1, 10)),
+            ((1, 5), (0, 20)),
+            ((2, 5), 7),
+            ((4, 0), 9)
+        ];
+        assert_eq!(vm.segments.segment_sizes.get(&4), Some(&1));
+    }
+}
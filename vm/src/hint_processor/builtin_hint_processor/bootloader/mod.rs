@@ -0,0 +1,10 @@
+pub mod bootloader_hint_processor;
+pub mod bootloader_hints;
+pub mod execute_task_hints;
+pub mod fact_topologies;
+pub mod hint_code;
+pub mod load_cairo_pie;
+pub mod program_loader;
+pub mod select_builtins;
+pub mod simple_bootloader_hints;
+pub mod types;
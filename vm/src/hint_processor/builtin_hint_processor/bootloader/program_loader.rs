@@ -0,0 +1,141 @@
+use crate::stdlib::prelude::*;
+
+use crate::types::builtin_name::BuiltinName;
+use crate::types::relocatable::Relocatable;
+use crate::vm::errors::memory_errors::MemoryError;
+use crate::vm::runners::cairo_pie::StrippedProgram;
+use crate::vm::vm_memory::memory_segments::MemorySegmentManager;
+use crate::Felt252;
+
+/// Version of the bootloader, written to the program header of every task.
+pub const BOOTLOADER_VERSION: usize = 0;
+
+/// Location of a program loaded by [`load_program`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadedProgram {
+    /// Address of the first instruction of the program.
+    pub code_address: Relocatable,
+    /// Size of the header and the code of the program.
+    pub size: usize,
+}
+
+/// Encodes a builtin as the bootloader does: its name (without suffix) as a big-endian felt.
+pub fn builtin_encoding(builtin: &BuiltinName) -> Felt252 {
+    Felt252::from_bytes_be_slice(builtin.to_str().as_bytes())
+}
+
+/// Writes the program header followed by the program code at `header_address`.
+///
+/// The header has the layout of the bootloader's `ProgramHeader` struct:
+/// `data_length, bootloader_version, program_main, n_builtins, builtin_list`, where
+/// `builtin_list` starts at `builtins_offset` and `data_length` counts every following cell.
+/// Fails with [`MemoryError::EmptyProgramHeader`] if the header has no cells.
+/// [(cairo_lang reference)](https://github.com/starkware-libs/cairo-lang/blob/efa9648f57568aad8f8a13fbf027d2de7c63c2c0/src/starkware/cairo/bootloaders/simple_bootloader/utils.py#L36)
+pub fn load_program(
+    segments: &mut MemorySegmentManager,
+    program: &StrippedProgram,
+    header_address: Relocatable,
+    builtins_offset: usize,
+) -> Result<LoadedProgram, MemoryError> {
+    let n_builtins = program.builtins.len();
+    let header_size = builtins_offset + n_builtins;
+    // data_length doesn't count its own cell
+    let data_length = header_size
+        .checked_sub(1)
+        .ok_or(MemoryError::EmptyProgramHeader)?
+        + program.data.len();
+
+    segments
+        .memory
+        .insert_value(header_address, Felt252::from(data_length))?;
+    segments
+        .memory
+        .insert_value((header_address + 1)?, Felt252::from(BOOTLOADER_VERSION))?;
+    segments
+        .memory
+        .insert_value((header_address + 2)?, Felt252::from(program.main))?;
+    segments
+        .memory
+        .insert_value((header_address + 3)?, Felt252::from(n_builtins))?;
+    let builtin_list: Vec<_> = program
+        .builtins
+        .iter()
+        .map(|builtin| builtin_encoding(builtin).into())
+        .collect();
+    segments.load_data((header_address + builtins_offset)?, &builtin_list)?;
+
+    let code_address = (header_address + header_size)?;
+    segments.load_data(code_address, &program.data)?;
+
+    Ok(LoadedProgram {
+        code_address,
+        size: header_size + program.data.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relocatable;
+    use crate::types::relocatable::MaybeRelocatable;
+    use crate::utils::test_utils::*;
+    use assert_matches::assert_matches;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn load_program_writes_header_and_code() {
+        let mut vm = vm!();
+        let base = vm.add_memory_segment();
+        let program = StrippedProgram {
+            data: vec![MaybeRelocatable::from(10), MaybeRelocatable::from(20)],
+            builtins: vec![BuiltinName::output, BuiltinName::pedersen],
+            main: 1,
+            prime: (),
+        };
+
+        let loaded = load_program(&mut vm.segments, &program, base, 4).unwrap();
+
+        assert_eq!(loaded.code_address, relocatable!(0, 6));
+        assert_eq!(loaded.size, 8);
+        assert_eq!(
+            vm.segments
+                .memory
+                .get_integer_range(base, 8)
+                .unwrap()
+                .into_iter()
+                .map(|value| value.into_owned())
+                .collect::<Vec<_>>(),
+            vec![
+                Felt252::from(7),
+                Felt252::from(BOOTLOADER_VERSION),
+                Felt252::ONE,
+                Felt252::TWO,
+                Felt252::from_bytes_be_slice(b"output"),
+                Felt252::from_bytes_be_slice(b"pedersen"),
+                Felt252::from(10),
+                Felt252::from(20),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn load_program_empty_header() {
+        let mut vm = vm!();
+        let base = vm.add_memory_segment();
+        let program = StrippedProgram {
+            data: vec![MaybeRelocatable::from(10)],
+            builtins: vec![],
+            main: 0,
+            prime: (),
+        };
+
+        assert_matches!(
+            load_program(&mut vm.segments, &program, base, 0),
+            Err(MemoryError::EmptyProgramHeader)
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use crate::stdlib::{collections::HashMap, prelude::*};
+
+use num_traits::ToPrimitive;
+
+use crate::any_box;
+use crate::hint_processor::builtin_hint_processor::hint_utils::{
+    get_integer_from_var_name, get_ptr_from_var_name, insert_value_from_var_name,
+};
+use crate::hint_processor::hint_processor_definition::HintReference;
+use crate::serde::deserialize_program::ApTracking;
+use crate::types::exec_scope::ExecutionScopes;
+use crate::vm::errors::hint_errors::HintError;
+use crate::vm::vm_core::VirtualMachine;
+use crate::Felt252;
+
+/*
+Implements hint:
+%{ vm_enter_scope({'n_selected_builtins': ids.n_selected_builtins}) %}
+*/
+pub fn select_builtins_enter_scope(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let n_selected_builtins =
+        get_integer_from_var_name("n_selected_builtins", vm, ids_data, ap_tracking)?;
+    let n_selected_builtins = n_selected_builtins
+        .to_usize()
+        .ok_or(HintError::BigintToUsizeFail)?;
+    exec_scopes.enter_scope(HashMap::from([(
+        String::from("n_selected_builtins"),
+        any_box!(n_selected_builtins),
+    )]));
+    Ok(())
+}
+
+/*
+Implements hint:
+%{
+    # A builtin should be selected iff its encoding appears in the selected encodings list
+    # and the list wasn't exhausted.
+    # Note that testing inclusion by a single comparison is possible since the lists are sorted.
+    ids.select_builtin = int(
+      n_selected_builtins > 0 and memory[ids.selected_encodings] == memory[ids.all_encodings])
+    if ids.select_builtin:
+      n_selected_builtins = n_selected_builtins - 1
+%}
+*/
+pub fn select_builtin(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let n_selected_builtins = exec_scopes.get_mut_ref::<usize>("n_selected_builtins")?;
+    let selected_encodings =
+        get_ptr_from_var_name("selected_encodings", vm, ids_data, ap_tracking)?;
+    let all_encodings = get_ptr_from_var_name("all_encodings", vm, ids_data, ap_tracking)?;
+
+    let select_builtin = *n_selected_builtins > 0
+        && vm.get_integer(selected_encodings)? == vm.get_integer(all_encodings)?;
+    insert_value_from_var_name(
+        "select_builtin",
+        Felt252::from(select_builtin as u8),
+        vm,
+        ids_data,
+        ap_tracking,
+    )?;
+    if select_builtin {
+        *n_selected_builtins -= 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use assert_matches::assert_matches;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_select_builtins_enter_scope() {
+        let mut vm = vm!();
+        vm.run_context.fp = 1;
+        vm.segments = segments![((1, 0), 3)];
+        let ids_data = ids_data!["n_selected_builtins"];
+        let mut exec_scopes = ExecutionScopes::new();
+
+        assert_matches!(
+            select_builtins_enter_scope(
+                &mut vm,
+                &mut exec_scopes,
+                &ids_data,
+                &ApTracking::default()
+            ),
+            Ok(())
+        );
+        assert_eq!(exec_scopes.data.len(), 2);
+        assert_eq!(exec_scopes.get::<usize>("n_selected_builtins").unwrap(), 3);
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_select_builtin_matching_encodings() {
+        let mut vm = vm!();
+        vm.run_context.fp = 3;
+        vm.segments = segments![((1, 0), (2, 0)), ((1, 1), (2, 1)), ((2, 0), 5), ((2, 1), 5)];
+        let ids_data = ids_data!["selected_encodings", "all_encodings", "select_builtin"];
+        let mut exec_scopes = scope![("n_selected_builtins", 1_usize)];
+
+        assert_matches!(
+            select_builtin(&mut vm, &mut exec_scopes, &ids_data, &ApTracking::default()),
+            Ok(())
+        );
+        check_memory![vm.segments.memory, ((1, 2), 1)];
+        assert_eq!(exec_scopes.get::<usize>("n_selected_builtins").unwrap(), 0);
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_select_builtin_exhausted_list() {
+        let mut vm = vm!();
+        vm.run_context.fp = 3;
+        // The selected encodings list is exhausted, so it isn't read
+        vm.segments = segments![((1, 0), (2, 5)), ((1, 1), (2, 1)), ((2, 1), 5)];
+        let ids_data = ids_data!["selected_encodings", "all_encodings", "select_builtin"];
+        let mut exec_scopes = scope![("n_selected_builtins", 0_usize)];
+
+        assert_matches!(
+            select_builtin(&mut vm, &mut exec_scopes, &ids_data, &ApTracking::default()),
+            Ok(())
+        );
+        check_memory![vm.segments.memory, ((1, 2), 0)];
+        assert_eq!(exec_scopes.get::<usize>("n_selected_builtins").unwrap(), 0);
+    }
+}
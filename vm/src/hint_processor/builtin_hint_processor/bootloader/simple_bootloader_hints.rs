@@ -0,0 +1,326 @@
+use crate::stdlib::{collections::HashMap, prelude::*};
+
+use num_traits::ToPrimitive;
+
+use super::execute_task_hints::BUILTIN_DATA_SIZE;
+use super::fact_topologies::configure_fact_topologies;
+use super::types::{FactTopology, SimpleBootloaderInput, Task};
+use crate::hint_processor::builtin_hint_processor::hint_utils::{
+    get_integer_from_var_name, get_ptr_from_var_name, insert_value_from_var_name,
+    insert_value_into_ap,
+};
+use crate::hint_processor::hint_processor_definition::HintReference;
+use crate::serde::deserialize_program::ApTracking;
+use crate::types::exec_scope::ExecutionScopes;
+use crate::types::relocatable::Relocatable;
+use crate::vm::errors::hint_errors::HintError;
+use crate::vm::vm_core::VirtualMachine;
+use crate::Felt252;
+
+/*
+Implements hint:
+%{
+    from starkware.cairo.bootloaders.simple_bootloader.objects import SimpleBootloaderInput
+    simple_bootloader_input = SimpleBootloaderInput.Schema().load(program_input)
+%}
+The input is expected to be provided in the execution scope as `simple_bootloader_input`.
+*/
+pub fn load_simple_bootloader_input(exec_scopes: &ExecutionScopes) -> Result<(), HintError> {
+    exec_scopes.get_ref::<SimpleBootloaderInput>("simple_bootloader_input")?;
+    Ok(())
+}
+
+/*
+Implements hint:
+%{
+    n_tasks = len(simple_bootloader_input.tasks)
+    memory[ids.output_ptr] = n_tasks
+
+    # Task range checks are located right after simple bootloader validation range checks, and
+    # this is validated later in this function.
+    ids.task_range_check_ptr = ids.range_check_ptr + ids.BuiltinData.SIZE * n_tasks
+
+    # A list of fact_toplogies that instruct how to generate the fact from the program output
+    # for each task.
+    fact_topologies = []
+%}
+*/
+pub fn prepare_task_range_checks(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let n_tasks = exec_scopes
+        .get_ref::<SimpleBootloaderInput>("simple_bootloader_input")?
+        .tasks
+        .len();
+    let output_ptr = get_ptr_from_var_name("output_ptr", vm, ids_data, ap_tracking)?;
+    vm.insert_value(output_ptr, Felt252::from(n_tasks))?;
+
+    let range_check_ptr = get_ptr_from_var_name("range_check_ptr", vm, ids_data, ap_tracking)?;
+    let task_range_check_ptr = (range_check_ptr + BUILTIN_DATA_SIZE * n_tasks)?;
+    insert_value_from_var_name(
+        "task_range_check_ptr",
+        task_range_check_ptr,
+        vm,
+        ids_data,
+        ap_tracking,
+    )?;
+
+    exec_scopes.insert_value("fact_topologies", Vec::<FactTopology>::new());
+    Ok(())
+}
+
+/*
+Implements hint:
+%{ tasks = simple_bootloader_input.tasks %}
+*/
+pub fn set_tasks_variable(exec_scopes: &mut ExecutionScopes) -> Result<(), HintError> {
+    let tasks = exec_scopes
+        .get_ref::<SimpleBootloaderInput>("simple_bootloader_input")?
+        .tasks
+        .clone();
+    exec_scopes.insert_value("tasks", tasks);
+    Ok(())
+}
+
+/*
+Implements hint:
+%{ memory[ap] = to_felt_or_relocatable(ids.num // 2) %}
+*/
+pub fn divide_num_by_2(
+    vm: &mut VirtualMachine,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let num = get_integer_from_var_name("num", vm, ids_data, ap_tracking)?;
+    insert_value_into_ap(vm, Felt252::from(num.to_biguint() >> 1_u32))
+}
+
+/*
+Implements hint:
+%{
+    from starkware.cairo.bootloaders.simple_bootloader.objects import Task
+
+    # Pass current task to execute_task.
+    task_id = len(simple_bootloader_input.tasks) - ids.n_tasks
+    task = simple_bootloader_input.tasks[task_id].load_task()
+%}
+*/
+pub fn set_current_task(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let n_tasks = get_integer_from_var_name("n_tasks", vm, ids_data, ap_tracking)?;
+    let tasks = &exec_scopes
+        .get_ref::<SimpleBootloaderInput>("simple_bootloader_input")?
+        .tasks;
+    let task = n_tasks
+        .to_usize()
+        .and_then(|n_tasks| tasks.len().checked_sub(n_tasks))
+        .and_then(|task_id| tasks.get(task_id))
+        .ok_or_else(|| {
+            HintError::InvalidValue(Box::new(("n_tasks", n_tasks, Felt252::from(tasks.len()))))
+        })?
+        .clone();
+    exec_scopes.insert_value::<Task>("task", task);
+    Ok(())
+}
+
+/*
+Implements hint:
+%{ memory[ap] = to_felt_or_relocatable(0) %}
+*/
+pub fn simple_bootloader_zero(vm: &mut VirtualMachine) -> Result<(), HintError> {
+    insert_value_into_ap(vm, Felt252::ZERO)
+}
+
+/*
+Implements hint:
+%{
+    # Dump fact topologies to a json file.
+    from starkware.cairo.bootloaders.simple_bootloader.utils import (
+        configure_fact_topologies,
+        write_to_fact_topologies_file,
+    )
+
+    # The task-related output is prefixed by a single word that contains the number of tasks.
+    tasks_output_start = output_builtin.base + 1
+
+    if not simple_bootloader_input.single_page:
+        # Configure the memory pages in the output builtin, based on fact_topologies.
+        configure_fact_topologies(
+            fact_topologies=fact_topologies, output_start=tasks_output_start,
+            output_builtin=output_builtin,
+        )
+
+    if simple_bootloader_input.fact_topologies_path is not None:
+        write_to_fact_topologies_file(
+            fact_topologies_path=simple_bootloader_input.fact_topologies_path,
+            fact_topologies=fact_topologies,
+        )
+%}
+*/
+pub fn simple_bootloader_compute_fact_topologies(
+    vm: &mut VirtualMachine,
+    exec_scopes: &ExecutionScopes,
+) -> Result<(), HintError> {
+    let simple_bootloader_input =
+        exec_scopes.get_ref::<SimpleBootloaderInput>("simple_bootloader_input")?;
+    let fact_topologies = exec_scopes.get_list_ref::<FactTopology>("fact_topologies")?;
+    let output_builtin = vm.get_output_builtin_mut()?;
+
+    // The task-related output is prefixed by a single word that contains the number of tasks
+    let tasks_output_start = Relocatable::from((output_builtin.base() as isize, 1));
+    if !simple_bootloader_input.single_page {
+        configure_fact_topologies(fact_topologies, tasks_output_start, output_builtin)?;
+    }
+
+    #[cfg(feature = "std")]
+    if let Some(path) = &simple_bootloader_input.fact_topologies_path {
+        super::fact_topologies::write_to_fact_topologies_file(
+            std::path::Path::new(path),
+            fact_topologies,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::any_box;
+    use crate::types::program::Program;
+    use crate::utils::test_utils::*;
+    use crate::vm::runners::builtin_runner::{BuiltinRunner, OutputBuiltinRunner};
+    use crate::vm::runners::cairo_pie::PublicMemoryPage;
+    use assert_matches::assert_matches;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn simple_bootloader_input(n_tasks: usize) -> SimpleBootloaderInput {
+        SimpleBootloaderInput {
+            fact_topologies_path: None,
+            single_page: false,
+            tasks: (0..n_tasks)
+                .map(|main| Task::Program(program!(main = Some(main),)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn load_simple_bootloader_input_missing() {
+        assert_matches!(
+            load_simple_bootloader_input(&ExecutionScopes::new()),
+            Err(HintError::VariableNotInScopeError(name)) if name.as_ref() == "simple_bootloader_input"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_prepare_task_range_checks() {
+        let mut vm = vm!();
+        vm.run_context.fp = 3;
+        vm.segments = segments![((1, 0), (2, 0)), ((1, 1), (3, 5))];
+        add_segments!(vm, 2);
+        let ids_data = ids_data!["output_ptr", "range_check_ptr", "task_range_check_ptr"];
+        let mut exec_scopes = scope![("simple_bootloader_input", simple_bootloader_input(2))];
+
+        assert_matches!(
+            prepare_task_range_checks(&mut vm, &mut exec_scopes, &ids_data, &ApTracking::default()),
+            Ok(())
+        );
+        check_memory![
+            vm.segments.memory,
+            ((2, 0), 2),
+            ((1, 2), (3, 5 + 2 * BUILTIN_DATA_SIZE))
+        ];
+        assert_eq!(
+            exec_scopes
+                .get_list_ref::<FactTopology>("fact_topologies")
+                .unwrap(),
+            &vec![]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_divide_num_by_2() {
+        let mut vm = vm!();
+        vm.run_context.fp = 1;
+        vm.run_context.ap = 1;
+        vm.segments = segments![((1, 0), 7)];
+        let ids_data = ids_data!["num"];
+
+        assert_matches!(
+            divide_num_by_2(&mut vm, &ids_data, &ApTracking::default()),
+            Ok(())
+        );
+        check_memory![vm.segments.memory, ((1, 1), 3)];
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_set_current_task() {
+        let mut vm = vm!();
+        vm.run_context.fp = 1;
+        vm.segments = segments![((1, 0), 2)];
+        let ids_data = ids_data!["n_tasks"];
+        let input = simple_bootloader_input(3);
+        let mut exec_scopes = scope![("simple_bootloader_input", input.clone())];
+
+        assert_matches!(
+            set_current_task(&mut vm, &mut exec_scopes, &ids_data, &ApTracking::default()),
+            Ok(())
+        );
+        assert_eq!(
+            exec_scopes.get_ref::<Task>("task").unwrap(),
+            &input.tasks[1]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_set_current_task_out_of_range() {
+        let mut vm = vm!();
+        vm.run_context.fp = 1;
+        vm.segments = segments![((1, 0), 4)];
+        let ids_data = ids_data!["n_tasks"];
+        let mut exec_scopes = scope![("simple_bootloader_input", simple_bootloader_input(3))];
+
+        assert_matches!(
+            set_current_task(&mut vm, &mut exec_scopes, &ids_data, &ApTracking::default()),
+            Err(HintError::InvalidValue(_))
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_simple_bootloader_compute_fact_topologies() {
+        let mut vm = vm!();
+        vm.builtin_runners = vec![BuiltinRunner::Output(OutputBuiltinRunner::new(true))];
+        vm.segments.add();
+        let fact_topologies = vec![FactTopology {
+            tree_structure: vec![1, 0],
+            page_sizes: vec![3],
+        }];
+        let exec_scopes = scope![
+            ("simple_bootloader_input", simple_bootloader_input(1)),
+            ("fact_topologies", fact_topologies)
+        ];
+
+        assert_matches!(
+            simple_bootloader_compute_fact_topologies(&mut vm, &exec_scopes),
+            Ok(())
+        );
+        assert_eq!(
+            vm.get_output_builtin_mut().unwrap().pages,
+            HashMap::from([(1, PublicMemoryPage { start: 3, size: 3 })])
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use crate::stdlib::prelude::*;
+
+pub use crate::fact_topology::FactTopology;
+use crate::types::{builtin_name::BuiltinName, layout::CairoLayout, program::Program};
+use crate::vm::errors::{cairo_pie_errors::CairoPieValidationError, hint_errors::HintError};
+use crate::vm::runners::cairo_pie::{CairoPie, StrippedProgram};
+use crate::Felt252;
+
+/// A task run by the simple bootloader: either a full program, whose hints are loaded
+/// into the vm when the task is called, or a previous execution packaged as a Cairo PIE.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Task {
+    Program(Program),
+    Pie(Box<CairoPie>),
+}
+
+impl Task {
+    pub fn get_program(&self) -> Result<StrippedProgram, HintError> {
+        match self {
+            Task::Program(program) => program
+                .get_stripped_program()
+                .map_err(|_| HintError::TaskProgramNoMain),
+            Task::Pie(cairo_pie) => Ok(cairo_pie.metadata.program.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimpleBootloaderInput {
+    /// Where the fact topologies of the tasks are written (as json), if anywhere.
+    pub fact_topologies_path: Option<String>,
+    /// When set, the output of all tasks is left in a single page.
+    pub single_page: bool,
+    pub tasks: Vec<Task>,
+}
+
+impl SimpleBootloaderInput {
+    /// Packages Cairo PIEs, possibly of different programs, as the tasks of a single simple
+    /// bootloader run with the given layout, whose own Cairo PIE can then be proven in their place.
+    /// The resulting tasks are checked with [`SimpleBootloaderInput::validate`].
+    pub fn from_cairo_pies(
+        cairo_pies: Vec<CairoPie>,
+        layout: &CairoLayout,
+    ) -> Result<Self, CairoPieValidationError> {
+        if cairo_pies.is_empty() {
+            return Err(CairoPieValidationError::NoCairoPies);
+        }
+        let input = SimpleBootloaderInput {
+            fact_topologies_path: None,
+            single_page: false,
+            tasks: cairo_pies
+                .into_iter()
+                .map(|cairo_pie| Task::Pie(Box::new(cairo_pie)))
+                .collect(),
+        };
+        input.validate(layout)?;
+        Ok(input)
+    }
+
+    /// Checks that the tasks can be run by the simple bootloader with the given layout: the Cairo
+    /// PIEs have to pass [`CairoPie::run_validity_checks`], and the builtins used by every task,
+    /// along with the builtin segments of the Cairo PIEs, have to be included in the layout.
+    pub fn validate(&self, layout: &CairoLayout) -> Result<(), CairoPieValidationError> {
+        let check_builtin = |builtin: &BuiltinName| {
+            if layout.builtins.includes(*builtin) {
+                Ok(())
+            } else {
+                Err(CairoPieValidationError::BuiltinNotInLayout(Box::new((
+                    *builtin,
+                    layout.name,
+                ))))
+            }
+        };
+        for task in self.tasks.iter() {
+            match task {
+                Task::Program(program) => program.iter_builtins().try_for_each(check_builtin)?,
+                Task::Pie(cairo_pie) => {
+                    cairo_pie.run_validity_checks()?;
+                    cairo_pie
+                        .metadata
+                        .program
+                        .builtins
+                        .iter()
+                        .chain(cairo_pie.metadata.builtin_segments.keys())
+                        .try_for_each(check_builtin)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BootloaderConfig {
+    pub simple_bootloader_program_hash: Felt252,
+    pub supported_cairo_verifier_program_hashes: Vec<Felt252>,
+}
+
+/// Describes how the output of a task was packed by a previous bootloader run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PackedOutput {
+    Plain,
+    Composite(CompositePackedOutput),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositePackedOutput {
+    pub outputs: Vec<Felt252>,
+    pub subtasks: Vec<PackedOutput>,
+    pub fact_topologies: Vec<FactTopology>,
+}
+
+impl CompositePackedOutput {
+    /// The values whose hash is the output of the task.
+    pub fn elements_for_hash(&self) -> &Vec<Felt252> {
+        &self.outputs
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BootloaderInput {
+    pub simple_bootloader_input: SimpleBootloaderInput,
+    pub bootloader_config: BootloaderConfig,
+    pub packed_outputs: Vec<PackedOutput>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serde::deserialize_program::ReferenceManager;
+    use crate::stdlib::collections::HashMap;
+    use crate::types::layout_name::LayoutName;
+    use crate::utils::test_utils::*;
+    use crate::vm::runners::cairo_pie::{test::output_pie, PublicMemoryPage};
+
+    fn valid_pie() -> CairoPie {
+        output_pie(
+            vec![(2, 0).into(), (3, 0).into(), (4, 0).into()],
+            vec![7.into()],
+            vec![(2, 0).into()],
+            HashMap::from([(1, PublicMemoryPage { start: 0, size: 1 })]),
+            HashMap::new(),
+            10,
+        )
+    }
+
+    fn layout(name: LayoutName) -> CairoLayout {
+        CairoLayout::from_name(name, None).unwrap()
+    }
+
+    #[test]
+    fn package_cairo_pies_as_bootloader_tasks() {
+        let pie_a = valid_pie();
+        let mut pie_b = pie_a.clone();
+        pie_b.metadata.program.main = 1;
+
+        let input = SimpleBootloaderInput::from_cairo_pies(
+            vec![pie_a.clone(), pie_b.clone()],
+            &layout(LayoutName::small),
+        )
+        .unwrap();
+        assert_eq!(
+            input.tasks,
+            vec![Task::Pie(Box::new(pie_a)), Task::Pie(Box::new(pie_b))]
+        );
+        assert_eq!(input.fact_topologies_path, None);
+        assert!(!input.single_page);
+    }
+
+    #[test]
+    fn package_invalid_cairo_pie_as_bootloader_task() {
+        let pie_a = output_pie(vec![], vec![], vec![], HashMap::new(), HashMap::new(), 1);
+        let pie_b = output_pie(
+            vec![(2, 0).into(), (6, 0).into()],
+            vec![],
+            vec![],
+            HashMap::new(),
+            HashMap::new(),
+            1,
+        );
+        assert_eq!(
+            SimpleBootloaderInput::from_cairo_pies(vec![pie_a, pie_b], &layout(LayoutName::small)),
+            Err(CairoPieValidationError::InvalidAddress)
+        );
+    }
+
+    #[test]
+    fn package_no_cairo_pies_as_bootloader_tasks() {
+        assert_eq!(
+            SimpleBootloaderInput::from_cairo_pies(vec![], &layout(LayoutName::small)),
+            Err(CairoPieValidationError::NoCairoPies)
+        );
+    }
+
+    #[test]
+    fn package_cairo_pies_incompatible_with_layout_as_bootloader_tasks() {
+        // The plain layout has no output builtin
+        assert_eq!(
+            SimpleBootloaderInput::from_cairo_pies(
+                vec![valid_pie(), valid_pie()],
+                &layout(LayoutName::plain)
+            ),
+            Err(CairoPieValidationError::BuiltinNotInLayout(Box::new((
+                BuiltinName::output,
+                LayoutName::plain
+            ))))
+        );
+    }
+
+    #[test]
+    fn validate_bootloader_tasks() {
+        let mut input =
+            SimpleBootloaderInput::from_cairo_pies(vec![valid_pie()], &layout(LayoutName::small))
+                .unwrap();
+        input
+            .tasks
+            .push(Task::Program(program![BuiltinName::bitwise]));
+        assert_eq!(input.validate(&layout(LayoutName::all_cairo)), Ok(()));
+        assert_eq!(
+            input.validate(&layout(LayoutName::small)),
+            Err(CairoPieValidationError::BuiltinNotInLayout(Box::new((
+                BuiltinName::bitwise,
+                LayoutName::small
+            ))))
+        );
+    }
+}
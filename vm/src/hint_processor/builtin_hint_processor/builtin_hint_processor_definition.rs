@@ -21,15 +21,20 @@ use super::{
         pack::*,
     },
 };
+use crate::any_box;
 use crate::Felt252;
 use crate::{
     hint_processor::{
         builtin_hint_processor::secp::ec_utils::{
             ec_double_assign_new_x, ec_double_assign_new_x_v2,
         },
-        hint_processor_definition::HintProcessorLogic,
+        hint_processor_definition::{get_ids_data, HintProcessorLogic},
+        hint_whitelist::HintWhitelist,
+    },
+    vm::{
+        errors::vm_errors::VirtualMachineError,
+        runners::cairo_runner::{ResourceTracker, RunResources},
     },
-    vm::runners::cairo_runner::{ResourceTracker, RunResources},
 };
 use crate::{
     hint_processor::{
@@ -109,10 +114,11 @@ use crate::{
         hint_processor_definition::HintReference,
     },
     serde::deserialize_program::ApTracking,
-    stdlib::{any::Any, collections::HashMap, prelude::*, rc::Rc},
+    stdlib::{any::Any, collections::HashMap, prelude::*, rc::Rc, sync::Arc},
     types::exec_scope::ExecutionScopes,
     vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
 };
+use core::time::Duration;
 
 #[cfg(feature = "test_utils")]
 use crate::hint_processor::builtin_hint_processor::skip_next_instruction::skip_next_instruction;
@@ -125,6 +131,7 @@ use crate::hint_processor::builtin_hint_processor::secp::secp_utils::{
 
 use super::blake2s_utils::example_blake2s_compress;
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct HintProcessorData {
     pub code: String,
     pub ap_tracking: ApTracking,
@@ -139,6 +146,16 @@ impl HintProcessorData {
             ids_data,
         }
     }
+
+    /// Downcasts hint data compiled by [`HintProcessorLogic::compile_hint`], or shared with a
+    /// compiled program by [`HintProcessorLogic::compile_precompiled_hint`]
+    pub fn downcast(hint_data: &dyn Any) -> Option<&HintProcessorData> {
+        hint_data.downcast_ref::<HintProcessorData>().or_else(|| {
+            hint_data
+                .downcast_ref::<Arc<HintProcessorData>>()
+                .map(AsRef::as_ref)
+        })
+    }
 }
 
 #[allow(clippy::type_complexity)]
@@ -157,12 +174,14 @@ pub struct HintFunc(
 pub struct BuiltinHintProcessor {
     pub extra_hints: HashMap<String, Rc<HintFunc>>,
     run_resources: RunResources,
+    hint_whitelist: Option<HintWhitelist>,
 }
 impl BuiltinHintProcessor {
     pub fn new_empty() -> Self {
         BuiltinHintProcessor {
             extra_hints: HashMap::new(),
             run_resources: RunResources::default(),
+            hint_whitelist: None,
         }
     }
 
@@ -170,15 +189,51 @@ impl BuiltinHintProcessor {
         BuiltinHintProcessor {
             extra_hints,
             run_resources,
+            hint_whitelist: None,
         }
     }
 
     pub fn add_hint(&mut self, hint_code: String, hint_func: Rc<HintFunc>) {
         self.extra_hints.insert(hint_code, hint_func);
     }
+
+    /// Makes hint compilation fail for hints (including extra hints) that are not whitelisted,
+    /// or that use references not allowed by the whitelist, including precompiled ones
+    pub fn set_hint_whitelist(&mut self, hint_whitelist: HintWhitelist) {
+        self.hint_whitelist = Some(hint_whitelist);
+    }
 }
 
 impl HintProcessorLogic for BuiltinHintProcessor {
+    fn compile_hint(
+        &self,
+        hint_code: &str,
+        ap_tracking_data: &ApTracking,
+        reference_ids: &HashMap<String, usize>,
+        references: &[HintReference],
+    ) -> Result<Box<dyn Any>, VirtualMachineError> {
+        if let Some(ref hint_whitelist) = self.hint_whitelist {
+            hint_whitelist.verify_hint(hint_code, reference_ids, references)?;
+        }
+        Ok(any_box!(HintProcessorData {
+            code: hint_code.to_string(),
+            ap_tracking: ap_tracking_data.clone(),
+            ids_data: get_ids_data(reference_ids, references)?,
+        }))
+    }
+
+    fn compile_precompiled_hint(
+        &self,
+        hint_data: &Arc<HintProcessorData>,
+        reference_ids: &HashMap<String, usize>,
+        references: &[HintReference],
+    ) -> Result<Box<dyn Any>, VirtualMachineError> {
+        if let Some(ref hint_whitelist) = self.hint_whitelist {
+            hint_whitelist.verify_hint(&hint_data.code, reference_ids, references)?;
+        }
+        Ok(any_box!(hint_data.clone()))
+    }
+
     fn execute_hint(
         &mut self,
         vm: &mut VirtualMachine,
@@ -186,9 +241,8 @@ impl HintProcessorLogic for BuiltinHintProcessor {
         hint_data: &Box<dyn Any>,
         constants: &HashMap<String, Felt252>,
     ) -> Result<(), HintError> {
-        let hint_data = hint_data
-            .downcast_ref::<HintProcessorData>()
-            .ok_or(HintError::WrongHintData)?;
+        let hint_data =
+            HintProcessorData::downcast(&**hint_data).ok_or(HintError::WrongHintData)?;
 
         if let Some(hint_func) = self.extra_hints.get(&hint_data.code) {
             return hint_func.0(
@@ -876,6 +930,10 @@ impl ResourceTracker for BuiltinHintProcessor {
         self.run_resources.consume_step();
     }
 
+    fn consume_hint_time(&mut self, hint_time: Duration) {
+        self.run_resources.consume_hint_time(hint_time);
+    }
+
     fn consumed(&self) -> bool {
         self.run_resources.consumed()
     }
@@ -1326,4 +1384,42 @@ mod tests {
         );
         assert_eq!(exec_scopes.data.len(), 3);
     }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn compile_precompiled_hint_skips_references() {
+        // Processors which don't override compile_precompiled_hint compile the hint again
+        struct DefaultHintProcessor;
+        impl HintProcessorLogic for DefaultHintProcessor {
+            fn execute_hint(
+                &mut self,
+                _vm: &mut VirtualMachine,
+                _exec_scopes: &mut ExecutionScopes,
+                _hint_data: &Box<dyn Any>,
+                _constants: &HashMap<String, Felt252>,
+            ) -> Result<(), HintError> {
+                Ok(())
+            }
+        }
+
+        let hint_data = Arc::new(HintProcessorData::new_default(
+            String::from("memory[ap] = segments.add()"),
+            HashMap::from([(String::from("a"), HintReference::new_simple(0))]),
+        ));
+        // The reference of `ids.a` is missing, so resolving the hint's references with get_ids_data fails
+        let reference_ids = HashMap::from([(String::from("main.a"), 0)]);
+        assert_matches!(
+            DefaultHintProcessor.compile_precompiled_hint(&hint_data, &reference_ids, &[]),
+            Err(VirtualMachineError::Unexpected)
+        );
+        let compiled_hint = BuiltinHintProcessor::new_empty()
+            .compile_precompiled_hint(&hint_data, &reference_ids, &[])
+            .unwrap();
+        assert!(Arc::ptr_eq(
+            compiled_hint
+                .downcast_ref::<Arc<HintProcessorData>>()
+                .unwrap(),
+            &hint_data
+        ));
+    }
 }
@@ -419,7 +419,7 @@ mod tests {
         let mut exec_scopes = ExecutionScopes::new();
         run_hint!(vm, ids_data, hint_code, &mut exec_scopes).expect("Error while executing hint");
         //third new segment is added for the dictionary
-        assert_eq!(vm.segments.memory.data.len(), 3);
+        assert_eq!(vm.segments.num_segments(), 3);
         //new segment base (2,0) is inserted into ap (0,0)
         check_memory![vm.segments.memory, ((1, 1), (2, 0))];
         //Check the dict manager has a tracker for segment 2,
@@ -1,5 +1,7 @@
 use super::dict_manager::DictManagerExecScope;
 use super::hint_processor_utils::*;
+use super::syscall_handler::{InMemorySyscallHandler, SyscallHandler};
+use super::syscalls::{execute_cheatcode, execute_syscall};
 use crate::any_box;
 use crate::hint_processor::cairo_1_hint_processor::dict_manager::DictSquashExecScope;
 use crate::hint_processor::hint_processor_definition::HintReference;
@@ -17,20 +19,22 @@ use crate::{
 use ark_ff::fields::{Fp256, MontBackend, MontConfig};
 use ark_ff::{Field, PrimeField};
 use ark_std::UniformRand;
-use cairo_lang_casm::hints::{CoreHintBase, DeprecatedHint};
+use cairo_lang_casm::hints::{CoreHintBase, DeprecatedHint, StarknetHint};
 use cairo_lang_casm::{
     hints::{CoreHint, Hint},
     operand::{CellRef, ResOperand},
 };
 use core::any::Any;
 use core::ops::Shl;
+use core::time::Duration;
 
 use num_bigint::{BigInt, BigUint};
 use num_integer::{ExtendedGcd, Integer};
 use num_traits::{Signed, ToPrimitive};
 
 /// Execution scope for constant memory allocation.
-struct MemoryExecScope {
+#[derive(Clone)]
+pub(crate) struct MemoryExecScope {
     /// The first free address in the segment.
     next_address: Relocatable,
 }
@@ -51,9 +55,11 @@ fn get_beta() -> Felt252 {
 }
 
 /// HintProcessor for Cairo 1 compiler hints.
+/// Starknet syscalls and cheatcodes are forwarded to its [SyscallHandler], an [InMemorySyscallHandler] by default.
 pub struct Cairo1HintProcessor {
     hints: HashMap<usize, Vec<Hint>>,
     run_resources: RunResources,
+    syscall_handler: Box<dyn SyscallHandler>,
 }
 
 impl Cairo1HintProcessor {
@@ -61,11 +67,19 @@ impl Cairo1HintProcessor {
         Self {
             hints: hints.iter().cloned().collect(),
             run_resources,
+            syscall_handler: Box::<InMemorySyscallHandler>::default(),
         }
     }
+
+    /// Replaces the handler of the Starknet syscalls and cheatcodes
+    pub fn with_syscall_handler(mut self, syscall_handler: Box<dyn SyscallHandler>) -> Self {
+        self.syscall_handler = syscall_handler;
+        self
+    }
+
     // Runs a single Hint
     pub fn execute(
-        &self,
+        &mut self,
         vm: &mut VirtualMachine,
         exec_scopes: &mut ExecutionScopes,
         hint: &Hint,
@@ -267,6 +281,29 @@ impl Cairo1HintProcessor {
                 t_or_k1,
             ),
 
+            Hint::Starknet(StarknetHint::SystemCall { system }) => execute_syscall(
+                self.syscall_handler.as_mut(),
+                vm,
+                exec_scopes,
+                system,
+                &mut self.run_resources,
+            ),
+            Hint::Starknet(StarknetHint::Cheatcode {
+                selector,
+                input_start,
+                input_end,
+                output_start,
+                output_end,
+            }) => execute_cheatcode(
+                self.syscall_handler.as_mut(),
+                vm,
+                &Felt252::from(&selector.value),
+                input_start,
+                input_end,
+                output_start,
+                output_end,
+            ),
+
             hint => Err(HintError::UnknownHint(
                 format!("{:?}", hint).into_boxed_str(),
             )),
@@ -1224,6 +1261,10 @@ impl ResourceTracker for Cairo1HintProcessor {
         self.run_resources.consume_step()
     }
 
+    fn consume_hint_time(&mut self, hint_time: Duration) {
+        self.run_resources.consume_hint_time(hint_time)
+    }
+
     fn get_n_steps(&self) -> Option<usize> {
         self.run_resources.get_n_steps()
     }
@@ -1,3 +1,27 @@
 pub mod dict_manager;
 pub mod hint_processor;
 pub mod hint_processor_utils;
+pub mod syscall_handler;
+mod syscalls;
+
+use crate::{
+    stdlib::any::TypeId,
+    types::exec_scope::{forkable_by_clone, forkable_type, ForkFn},
+};
+
+forkable_by_clone!(
+    dict_manager::DictManagerExecScope,
+    dict_manager::DictSquashExecScope,
+    hint_processor::MemoryExecScope,
+    syscalls::SecpExecScope,
+);
+
+/// Returns the types stored in the scopes by the Cairo 1 hints, along with the function forking them
+pub(crate) fn forkable_scope_values() -> [(TypeId, ForkFn); 4] {
+    [
+        forkable_type::<dict_manager::DictManagerExecScope>(),
+        forkable_type::<dict_manager::DictSquashExecScope>(),
+        forkable_type::<hint_processor::MemoryExecScope>(),
+        forkable_type::<syscalls::SecpExecScope>(),
+    ]
+}
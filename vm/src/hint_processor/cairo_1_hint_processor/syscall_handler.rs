@@ -0,0 +1,1020 @@
+use super::hint_processor::Cairo1HintProcessor;
+use crate::stdlib::{collections::HashMap, prelude::*, sync::Arc};
+use crate::types::{
+    builtin_name::BuiltinName,
+    errors::{math_errors::MathError, program_errors::ProgramError},
+    layout_name::LayoutName,
+    program::Program,
+    relocatable::MaybeRelocatable,
+};
+use crate::vm::errors::{
+    cairo_run_errors::CairoRunError, hint_errors::HintError, runner_errors::RunnerError,
+    vm_errors::VirtualMachineError,
+};
+use crate::vm::runners::cairo_runner::{
+    CairoArg, CairoRunner, ExecutionResources, ResourceTracker, RunResources,
+};
+use crate::Felt252;
+use cairo_lang_sierra::extensions::gas::CostTokenType;
+use cairo_lang_starknet_classes::casm_contract_class::{CasmContractClass, CasmContractEntryPoint};
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive};
+use sha3::{Digest, Keccak256};
+#[cfg(not(feature = "std"))]
+use spin::{Mutex, MutexGuard};
+use starknet_types_core::hash::{Pedersen, StarkHash};
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Error returned by the methods of a [SyscallHandler]
+#[derive(Debug)]
+pub enum SyscallError {
+    /// The syscall failed, the calling contract receives the revert reason
+    Revert(Vec<Felt252>),
+    /// The run can't continue
+    Hint(HintError),
+}
+
+impl SyscallError {
+    /// Reverts the syscall with a reason encoded as a single short string
+    pub fn revert(reason: &str) -> Self {
+        SyscallError::Revert(vec![Felt252::from_bytes_be_slice(reason.as_bytes())])
+    }
+}
+
+impl From<HintError> for SyscallError {
+    fn from(error: HintError) -> Self {
+        SyscallError::Hint(error)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockInfo {
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub sequencer_address: Felt252,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceBounds {
+    pub resource: Felt252,
+    pub max_amount: u64,
+    pub max_price_per_unit: u128,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TxInfo {
+    pub version: Felt252,
+    pub account_contract_address: Felt252,
+    pub max_fee: u128,
+    pub signature: Vec<Felt252>,
+    pub transaction_hash: Felt252,
+    pub chain_id: Felt252,
+    pub nonce: Felt252,
+    pub resource_bounds: Vec<ResourceBounds>,
+    pub tip: u128,
+    pub paymaster_data: Vec<Felt252>,
+    pub nonce_data_availability_mode: u32,
+    pub fee_data_availability_mode: u32,
+    pub account_deployment_data: Vec<Felt252>,
+}
+
+/// Value returned by the `get_execution_info` syscall
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionInfo {
+    pub block_info: BlockInfo,
+    pub tx_info: TxInfo,
+    pub caller_address: Felt252,
+    pub contract_address: Felt252,
+    pub entry_point_selector: Felt252,
+}
+
+/// Gas charged for each operation of a secp256k1/r1 syscall
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecpGasCosts {
+    pub new: u64,
+    pub add: u64,
+    pub mul: u64,
+    pub get_point_from_x: u64,
+    pub get_xy: u64,
+}
+
+/// Gas charged for each syscall, on top of the gas consumed by nested calls.
+/// The default costs are the ones charged by Starknet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyscallGasCosts {
+    pub call_contract: u64,
+    pub deploy: u64,
+    pub emit_event: u64,
+    pub get_block_hash: u64,
+    pub get_execution_info: u64,
+    /// Charged once per keccak syscall, on top of `keccak_round` for each block of the input
+    pub keccak: u64,
+    pub keccak_round: u64,
+    pub library_call: u64,
+    pub replace_class: u64,
+    pub secp256k1: SecpGasCosts,
+    pub secp256r1: SecpGasCosts,
+    pub send_message_to_l1: u64,
+    pub sha256_process_block: u64,
+    pub storage_read: u64,
+    pub storage_write: u64,
+}
+
+const STEP: u64 = 100;
+const RANGE_CHECK: u64 = 70;
+const BITWISE: u64 = 594;
+const ENTRY_POINT: u64 = 500 * STEP;
+
+// Returned by the default implementation of `SyscallHandler::syscall_gas_costs`
+static DEFAULT_SYSCALL_GAS_COSTS: SyscallGasCosts = SyscallGasCosts {
+    call_contract: 10 * STEP + ENTRY_POINT,
+    deploy: 200 * STEP + ENTRY_POINT,
+    emit_event: 10 * STEP,
+    get_block_hash: 50 * STEP,
+    get_execution_info: 10 * STEP,
+    keccak: 0,
+    keccak_round: 180000,
+    library_call: 10 * STEP + ENTRY_POINT,
+    replace_class: 50 * STEP,
+    secp256k1: SecpGasCosts {
+        new: 475 * STEP + 35 * RANGE_CHECK + 40000,
+        add: 406 * STEP + 29 * RANGE_CHECK,
+        mul: 76501 * STEP + 7045 * RANGE_CHECK,
+        get_point_from_x: 391 * STEP + 30 * RANGE_CHECK + 20000,
+        get_xy: 239 * STEP + 11 * RANGE_CHECK + 40000,
+    },
+    secp256r1: SecpGasCosts {
+        new: 594 * STEP + 49 * RANGE_CHECK + 40000,
+        add: 589 * STEP + 57 * RANGE_CHECK,
+        mul: 125340 * STEP + 13961 * RANGE_CHECK,
+        get_point_from_x: 510 * STEP + 44 * RANGE_CHECK + 20000,
+        get_xy: 241 * STEP + 11 * RANGE_CHECK + 40000,
+    },
+    send_message_to_l1: 50 * STEP,
+    sha256_process_block: 1852 * STEP + 65 * RANGE_CHECK + 1115 * BITWISE,
+    storage_read: 50 * STEP,
+    storage_write: 50 * STEP,
+};
+
+impl Default for SyscallGasCosts {
+    fn default() -> Self {
+        DEFAULT_SYSCALL_GAS_COSTS
+    }
+}
+
+/// Handles the Starknet syscalls and cheatcodes of the contracts run by a [Cairo1HintProcessor].
+///
+/// The processor reads the requests from the syscall segment, charges their gas, and writes back the responses,
+/// so implementors only deal with the state of the chain.
+/// Syscalls which only compute values (keccak, sha256 and the secp256k1/r1 operations) are run natively by the processor.
+pub trait SyscallHandler: Send {
+    /// Gas charged by the processor for each syscall, before handing it over to the handler
+    fn syscall_gas_costs(&self) -> &SyscallGasCosts {
+        &DEFAULT_SYSCALL_GAS_COSTS
+    }
+
+    fn storage_read(
+        &mut self,
+        address_domain: Felt252,
+        key: Felt252,
+    ) -> Result<Felt252, SyscallError>;
+
+    fn storage_write(
+        &mut self,
+        address_domain: Felt252,
+        key: Felt252,
+        value: Felt252,
+    ) -> Result<(), SyscallError>;
+
+    fn emit_event(&mut self, keys: Vec<Felt252>, data: Vec<Felt252>) -> Result<(), SyscallError>;
+
+    fn send_message_to_l1(
+        &mut self,
+        to_address: Felt252,
+        payload: Vec<Felt252>,
+    ) -> Result<(), SyscallError>;
+
+    fn get_block_hash(&mut self, block_number: u64) -> Result<Felt252, SyscallError>;
+
+    fn get_execution_info(&mut self) -> Result<ExecutionInfo, SyscallError>;
+
+    /// Calls a deployed contract, returning its retdata.
+    /// `gas` & `run_resources` hold the gas & resources available to the call (those of the calling run),
+    /// and must be updated to the ones left after it
+    fn call_contract(
+        &mut self,
+        contract_address: Felt252,
+        selector: Felt252,
+        calldata: Vec<Felt252>,
+        gas: &mut u64,
+        run_resources: &mut RunResources,
+    ) -> Result<Vec<Felt252>, SyscallError>;
+
+    /// Calls a declared class in the context of the current contract, returning its retdata.
+    /// `gas` & `run_resources` are handled as in `call_contract`
+    fn library_call(
+        &mut self,
+        class_hash: Felt252,
+        selector: Felt252,
+        calldata: Vec<Felt252>,
+        gas: &mut u64,
+        run_resources: &mut RunResources,
+    ) -> Result<Vec<Felt252>, SyscallError>;
+
+    /// Deploys a declared class, returning the address of the new contract and the retdata of its constructor.
+    /// `gas` & `run_resources` are handled as in `call_contract`, and used by the constructor
+    fn deploy(
+        &mut self,
+        class_hash: Felt252,
+        contract_address_salt: Felt252,
+        calldata: Vec<Felt252>,
+        deploy_from_zero: bool,
+        gas: &mut u64,
+        run_resources: &mut RunResources,
+    ) -> Result<(Felt252, Vec<Felt252>), SyscallError>;
+
+    fn replace_class(&mut self, class_hash: Felt252) -> Result<(), SyscallError>;
+
+    /// Runs a cheatcode of the Cairo test runner (such as `set_block_number` or `pop_log`), returning its output
+    fn cheatcode(
+        &mut self,
+        selector: &str,
+        _input: Vec<Felt252>,
+    ) -> Result<Vec<Felt252>, HintError> {
+        Err(HintError::UnknownHint(
+            format!("Cheatcode {selector}").into_boxed_str(),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub from_address: Felt252,
+    pub keys: Vec<Felt252>,
+    pub data: Vec<Felt252>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct L2ToL1Message {
+    pub from_address: Felt252,
+    pub to_address: Felt252,
+    pub payload: Vec<Felt252>,
+}
+
+/// State of the chain kept by an [InMemorySyscallHandler]
+#[derive(Debug, Clone, Default)]
+pub struct StarknetState {
+    /// Declared classes, by class hash
+    pub classes: HashMap<Felt252, Arc<CasmContractClass>>,
+    /// Class hash of each deployed contract, by contract address
+    pub contracts: HashMap<Felt252, Felt252>,
+    /// Storage of each contract, by contract address and key
+    pub storage: HashMap<Felt252, HashMap<Felt252, Felt252>>,
+    pub block_hashes: HashMap<u64, Felt252>,
+    pub events: Vec<Event>,
+    pub l2_to_l1_messages: Vec<L2ToL1Message>,
+}
+
+impl StarknetState {
+    pub fn declare_class(&mut self, class_hash: Felt252, contract_class: CasmContractClass) {
+        self.classes.insert(class_hash, Arc::new(contract_class));
+    }
+
+    /// Deploys a declared class at the given address, without running its constructor
+    pub fn deploy_contract(&mut self, contract_address: Felt252, class_hash: Felt252) {
+        self.contracts.insert(contract_address, class_hash);
+    }
+
+    pub fn storage_at(&self, contract_address: &Felt252, key: &Felt252) -> Felt252 {
+        self.storage
+            .get(contract_address)
+            .and_then(|storage| storage.get(key))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+// Selector of the `constructor` entry point, sn_keccak("constructor")
+const CONSTRUCTOR_SELECTOR: Felt252 = Felt252::from_hex_unchecked(
+    "0x28ffe4ff0f226a9107253e17a904099aa4f63a02a5621de0576e5aa71bc5194",
+);
+
+/// Maximum depth of nested contract calls.
+/// Each nested call is run by a nested runner on the native stack, so the limit is kept well below Starknet's,
+/// at a depth that fits in the default stack of a spawned thread (2 MiB)
+pub const MAX_CALL_DEPTH: usize = 100;
+
+// Selects which entry points of a class are searched for a selector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EntryPointType {
+    External,
+    Constructor,
+}
+
+// A change made to the state by a call, recorded so that it can be undone if the call fails
+#[derive(Debug, Clone)]
+enum StateChange {
+    Storage {
+        contract_address: Felt252,
+        key: Felt252,
+        previous_value: Option<Felt252>,
+    },
+    Contract {
+        contract_address: Felt252,
+        previous_class_hash: Option<Felt252>,
+    },
+    EventEmitted,
+    EventPopped(usize, Event),
+    MessageSent,
+    MessagePopped(usize, L2ToL1Message),
+}
+
+// A panic while the lock was held can't leave the state half-updated, as it is only changed by single operations
+#[cfg(feature = "std")]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(not(feature = "std"))]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock()
+}
+
+/// Syscall handler backed by a [StarknetState] held in memory, which runs contract calls in nested runners.
+///
+/// Clones of the handler share the same state, so it can be inspected after handing the handler to a [Cairo1HintProcessor].
+/// The state is locked with a `std::sync::Mutex`, or with a `spin::Mutex` without the `std` feature.
+/// A call that fails reverts all the changes it made to the state, and calls nested deeper than [MAX_CALL_DEPTH] fail the run.
+/// The programs of the classes are built once and cached by class hash, so classes must not be redeclared under the same hash.
+#[derive(Debug, Clone, Default)]
+pub struct InMemorySyscallHandler {
+    state: Arc<Mutex<StarknetState>>,
+    execution_info: ExecutionInfo,
+    syscall_gas_costs: SyscallGasCosts,
+    // Amount of calls the current one is nested in
+    call_depth: usize,
+    // Changes made by the calls in progress, undone up to the start of a call when it fails
+    journal: Arc<Mutex<Vec<StateChange>>>,
+    // Program of each class that was called, by class hash
+    programs: Arc<Mutex<HashMap<Felt252, Program>>>,
+}
+
+impl InMemorySyscallHandler {
+    pub fn new(state: Arc<Mutex<StarknetState>>, execution_info: ExecutionInfo) -> Self {
+        Self {
+            state,
+            execution_info,
+            ..Default::default()
+        }
+    }
+
+    /// Replaces the gas charged for each syscall, including the ones made by nested calls
+    pub fn with_syscall_gas_costs(mut self, syscall_gas_costs: SyscallGasCosts) -> Self {
+        self.syscall_gas_costs = syscall_gas_costs;
+        self
+    }
+
+    pub fn state(&self) -> &Arc<Mutex<StarknetState>> {
+        &self.state
+    }
+
+    pub fn execution_info(&self) -> &ExecutionInfo {
+        &self.execution_info
+    }
+
+    pub fn execution_info_mut(&mut self) -> &mut ExecutionInfo {
+        &mut self.execution_info
+    }
+
+    // Runs an entry point of a declared class in a nested runner, with the given caller & contract addresses
+    #[allow(clippy::too_many_arguments)]
+    fn execute_call(
+        &mut self,
+        class_hash: Felt252,
+        entry_point_type: EntryPointType,
+        selector: Felt252,
+        caller_address: Felt252,
+        contract_address: Felt252,
+        calldata: &[Felt252],
+        gas: &mut u64,
+        run_resources: &mut RunResources,
+    ) -> Result<Vec<Felt252>, SyscallError> {
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(HintError::SyscallError(
+                format!("Max call depth of {MAX_CALL_DEPTH} exceeded").into_boxed_str(),
+            )
+            .into());
+        }
+        let contract_class = lock(&self.state)
+            .classes
+            .get(&class_hash)
+            .cloned()
+            .ok_or_else(|| SyscallError::revert("CLASS_HASH_NOT_FOUND"))?;
+        let entry_points = match entry_point_type {
+            EntryPointType::External => &contract_class.entry_points_by_type.external,
+            EntryPointType::Constructor => &contract_class.entry_points_by_type.constructor,
+        };
+        let Some(entry_point) = entry_points
+            .iter()
+            .find(|entry_point| Felt252::from(&entry_point.selector) == selector)
+        else {
+            // Classes without constructor can be deployed with empty calldata
+            if entry_point_type == EntryPointType::Constructor && calldata.is_empty() {
+                return Ok(Vec::new());
+            }
+            return Err(SyscallError::revert("ENTRYPOINT_NOT_FOUND"));
+        };
+
+        let program = self.program(class_hash, &contract_class)?;
+        let callee = InMemorySyscallHandler {
+            state: self.state.clone(),
+            execution_info: ExecutionInfo {
+                caller_address,
+                contract_address,
+                entry_point_selector: selector,
+                ..self.execution_info.clone()
+            },
+            syscall_gas_costs: self.syscall_gas_costs,
+            call_depth: self.call_depth + 1,
+            journal: self.journal.clone(),
+            programs: self.programs.clone(),
+        };
+        let checkpoint = lock(&self.journal).len();
+        let call_info = match run_program_entrypoint(
+            &program,
+            &contract_class,
+            entry_point,
+            calldata,
+            *gas,
+            run_resources,
+            Box::new(callee),
+        ) {
+            Ok(call_info) => call_info,
+            Err(error) => {
+                self.revert_to(checkpoint);
+                return Err(HintError::SyscallError(error.to_string().into_boxed_str()).into());
+            }
+        };
+        *gas = call_info.remaining_gas;
+        if call_info.failed {
+            self.revert_to(checkpoint);
+            return Err(SyscallError::Revert(call_info.retdata));
+        }
+        // Calls made outside of any other call can't be reverted anymore
+        if self.call_depth == 0 {
+            lock(&self.journal).truncate(checkpoint);
+        }
+        Ok(call_info.retdata)
+    }
+
+    // Gets the program of a class, building it on its first call
+    fn program(
+        &self,
+        class_hash: Felt252,
+        contract_class: &CasmContractClass,
+    ) -> Result<Program, SyscallError> {
+        if let Some(program) = lock(&self.programs).get(&class_hash) {
+            return Ok(program.clone());
+        }
+        let program = Program::try_from(contract_class.clone())
+            .map_err(|error| HintError::SyscallError(error.to_string().into_boxed_str()))?;
+        lock(&self.programs).insert(class_hash, program.clone());
+        Ok(program)
+    }
+
+    // Records a change to the state, to be undone if the call making it fails.
+    // Changes made outside of calls are never undone, so they aren't recorded
+    fn record(&self, change: StateChange) {
+        if self.call_depth > 0 {
+            lock(&self.journal).push(change);
+        }
+    }
+
+    // Undoes the changes recorded after the checkpoint, from the latest to the earliest
+    fn revert_to(&self, checkpoint: usize) {
+        let mut journal = lock(&self.journal);
+        let mut state = lock(&self.state);
+        for change in journal.drain(checkpoint..).rev() {
+            match change {
+                StateChange::Storage {
+                    contract_address,
+                    key,
+                    previous_value,
+                } => {
+                    let storage = state.storage.entry(contract_address).or_default();
+                    match previous_value {
+                        Some(value) => storage.insert(key, value),
+                        None => storage.remove(&key),
+                    };
+                }
+                StateChange::Contract {
+                    contract_address,
+                    previous_class_hash,
+                } => {
+                    match previous_class_hash {
+                        Some(class_hash) => state.contracts.insert(contract_address, class_hash),
+                        None => state.contracts.remove(&contract_address),
+                    };
+                }
+                StateChange::EventEmitted => {
+                    state.events.pop();
+                }
+                StateChange::EventPopped(index, event) => state.events.insert(index, event),
+                StateChange::MessageSent => {
+                    state.l2_to_l1_messages.pop();
+                }
+                StateChange::MessagePopped(index, message) => {
+                    state.l2_to_l1_messages.insert(index, message)
+                }
+            }
+        }
+    }
+
+    // Deploys a class at an address, recording the change
+    fn set_contract_class(&self, contract_address: Felt252, class_hash: Felt252) {
+        let previous_class_hash = lock(&self.state)
+            .contracts
+            .insert(contract_address, class_hash);
+        self.record(StateChange::Contract {
+            contract_address,
+            previous_class_hash,
+        });
+    }
+}
+
+impl SyscallHandler for InMemorySyscallHandler {
+    fn syscall_gas_costs(&self) -> &SyscallGasCosts {
+        &self.syscall_gas_costs
+    }
+
+    fn storage_read(
+        &mut self,
+        address_domain: Felt252,
+        key: Felt252,
+    ) -> Result<Felt252, SyscallError> {
+        if address_domain != Felt252::ZERO {
+            return Err(SyscallError::revert("Unsupported address domain"));
+        }
+        Ok(lock(&self.state).storage_at(&self.execution_info.contract_address, &key))
+    }
+
+    fn storage_write(
+        &mut self,
+        address_domain: Felt252,
+        key: Felt252,
+        value: Felt252,
+    ) -> Result<(), SyscallError> {
+        if address_domain != Felt252::ZERO {
+            return Err(SyscallError::revert("Unsupported address domain"));
+        }
+        let contract_address = self.execution_info.contract_address;
+        let previous_value = lock(&self.state)
+            .storage
+            .entry(contract_address)
+            .or_default()
+            .insert(key, value);
+        self.record(StateChange::Storage {
+            contract_address,
+            key,
+            previous_value,
+        });
+        Ok(())
+    }
+
+    fn emit_event(&mut self, keys: Vec<Felt252>, data: Vec<Felt252>) -> Result<(), SyscallError> {
+        lock(&self.state).events.push(Event {
+            from_address: self.execution_info.contract_address,
+            keys,
+            data,
+        });
+        self.record(StateChange::EventEmitted);
+        Ok(())
+    }
+
+    fn send_message_to_l1(
+        &mut self,
+        to_address: Felt252,
+        payload: Vec<Felt252>,
+    ) -> Result<(), SyscallError> {
+        lock(&self.state).l2_to_l1_messages.push(L2ToL1Message {
+            from_address: self.execution_info.contract_address,
+            to_address,
+            payload,
+        });
+        self.record(StateChange::MessageSent);
+        Ok(())
+    }
+
+    fn get_block_hash(&mut self, block_number: u64) -> Result<Felt252, SyscallError> {
+        lock(&self.state)
+            .block_hashes
+            .get(&block_number)
+            .copied()
+            .ok_or_else(|| SyscallError::revert("Block number out of range"))
+    }
+
+    fn get_execution_info(&mut self) -> Result<ExecutionInfo, SyscallError> {
+        Ok(self.execution_info.clone())
+    }
+
+    fn call_contract(
+        &mut self,
+        contract_address: Felt252,
+        selector: Felt252,
+        calldata: Vec<Felt252>,
+        gas: &mut u64,
+        run_resources: &mut RunResources,
+    ) -> Result<Vec<Felt252>, SyscallError> {
+        let class_hash = lock(&self.state)
+            .contracts
+            .get(&contract_address)
+            .copied()
+            .ok_or_else(|| SyscallError::revert("CONTRACT_NOT_DEPLOYED"))?;
+        self.execute_call(
+            class_hash,
+            EntryPointType::External,
+            selector,
+            self.execution_info.contract_address,
+            contract_address,
+            &calldata,
+            gas,
+            run_resources,
+        )
+    }
+
+    fn library_call(
+        &mut self,
+        class_hash: Felt252,
+        selector: Felt252,
+        calldata: Vec<Felt252>,
+        gas: &mut u64,
+        run_resources: &mut RunResources,
+    ) -> Result<Vec<Felt252>, SyscallError> {
+        self.execute_call(
+            class_hash,
+            EntryPointType::External,
+            selector,
+            self.execution_info.caller_address,
+            self.execution_info.contract_address,
+            &calldata,
+            gas,
+            run_resources,
+        )
+    }
+
+    fn deploy(
+        &mut self,
+        class_hash: Felt252,
+        contract_address_salt: Felt252,
+        calldata: Vec<Felt252>,
+        deploy_from_zero: bool,
+        gas: &mut u64,
+        run_resources: &mut RunResources,
+    ) -> Result<(Felt252, Vec<Felt252>), SyscallError> {
+        let deployer_address = if deploy_from_zero {
+            Felt252::ZERO
+        } else {
+            self.execution_info.contract_address
+        };
+        let contract_address = calculate_contract_address(
+            &contract_address_salt,
+            &class_hash,
+            &calldata,
+            &deployer_address,
+        );
+        {
+            let state = lock(&self.state);
+            if !state.classes.contains_key(&class_hash) {
+                return Err(SyscallError::revert("CLASS_HASH_NOT_FOUND"));
+            }
+            if state.contracts.contains_key(&contract_address) {
+                return Err(SyscallError::revert("CONTRACT_ADDRESS_UNAVAILABLE"));
+            }
+        }
+        self.set_contract_class(contract_address, class_hash);
+        // The deployment itself is reverted along with the constructor
+        let retdata = self
+            .execute_call(
+                class_hash,
+                EntryPointType::Constructor,
+                CONSTRUCTOR_SELECTOR,
+                deployer_address,
+                contract_address,
+                &calldata,
+                gas,
+                run_resources,
+            )
+            .map_err(|error| {
+                lock(&self.state).contracts.remove(&contract_address);
+                error
+            })?;
+        Ok((contract_address, retdata))
+    }
+
+    fn replace_class(&mut self, class_hash: Felt252) -> Result<(), SyscallError> {
+        if !lock(&self.state).classes.contains_key(&class_hash) {
+            return Err(SyscallError::revert("CLASS_HASH_NOT_FOUND"));
+        }
+        self.set_contract_class(self.execution_info.contract_address, class_hash);
+        Ok(())
+    }
+
+    fn cheatcode(
+        &mut self,
+        selector: &str,
+        input: Vec<Felt252>,
+    ) -> Result<Vec<Felt252>, HintError> {
+        let info = &mut self.execution_info;
+        let invalid_input =
+            || HintError::SyscallError(format!("Invalid input for cheatcode {selector}").into());
+        let value = || input.first().copied().ok_or_else(invalid_input);
+        let u64_value = || value()?.to_u64().ok_or_else(invalid_input);
+        let u128_value = || value()?.to_u128().ok_or_else(invalid_input);
+        match selector {
+            "set_block_number" => info.block_info.block_number = u64_value()?,
+            "set_block_timestamp" => info.block_info.block_timestamp = u64_value()?,
+            "set_sequencer_address" => info.block_info.sequencer_address = value()?,
+            "set_caller_address" => info.caller_address = value()?,
+            "set_contract_address" => info.contract_address = value()?,
+            "set_version" => info.tx_info.version = value()?,
+            "set_account_contract_address" => info.tx_info.account_contract_address = value()?,
+            "set_max_fee" => info.tx_info.max_fee = u128_value()?,
+            "set_transaction_hash" => info.tx_info.transaction_hash = value()?,
+            "set_chain_id" => info.tx_info.chain_id = value()?,
+            "set_nonce" => info.tx_info.nonce = value()?,
+            "set_signature" => info.tx_info.signature = input,
+            // Pops the oldest event emitted by the given contract, as [n_keys, keys.., n_data, data..]
+            "pop_log" => {
+                let address = value()?;
+                let mut state = lock(&self.state);
+                let Some(index) = state
+                    .events
+                    .iter()
+                    .position(|event| event.from_address == address)
+                else {
+                    return Ok(Vec::new());
+                };
+                let event = state.events.remove(index);
+                drop(state);
+                self.record(StateChange::EventPopped(index, event.clone()));
+                let mut output = vec![event.keys.len().into()];
+                output.extend(event.keys);
+                output.push(event.data.len().into());
+                output.extend(event.data);
+                return Ok(output);
+            }
+            // Pops the oldest message sent by the given contract, as [to_address, n_payload, payload..]
+            "pop_l2_to_l1_message" => {
+                let address = value()?;
+                let mut state = lock(&self.state);
+                let Some(index) = state
+                    .l2_to_l1_messages
+                    .iter()
+                    .position(|message| message.from_address == address)
+                else {
+                    return Ok(Vec::new());
+                };
+                let message = state.l2_to_l1_messages.remove(index);
+                drop(state);
+                self.record(StateChange::MessagePopped(index, message.clone()));
+                let mut output = vec![message.to_address, message.payload.len().into()];
+                output.extend(message.payload);
+                return Ok(output);
+            }
+            _ => {
+                return Err(HintError::UnknownHint(
+                    format!("Cheatcode {selector}").into_boxed_str(),
+                ))
+            }
+        }
+        Ok(Vec::new())
+    }
+}
+
+/// Outcome of running a contract entry point
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallInfo {
+    /// Whether the entry point panicked, in which case the retdata holds the panic reason
+    pub failed: bool,
+    pub retdata: Vec<Felt252>,
+    pub remaining_gas: u64,
+    pub execution_resources: ExecutionResources,
+}
+
+/// Gas cost of a single token of each type, as charged by Starknet & cairo-test.
+/// Contracts read the cost of the builtins from a table holding the costs of `CostTokenType::iter_precost`
+pub fn token_gas_cost(token_type: CostTokenType) -> usize {
+    match token_type {
+        CostTokenType::Const => 1,
+        CostTokenType::Pedersen => 4130,
+        CostTokenType::Bitwise => 583,
+        CostTokenType::EcOp => 4085,
+        CostTokenType::Poseidon => 491,
+        // Steps, holes & range checks are already accounted for by the `Const` token
+        _ => 0,
+    }
+}
+
+/// Runs an entry point of a contract class with the given calldata and gas, handling its syscalls with `syscall_handler`.
+/// The run is limited by `run_resources`, which is updated to the resources left after it
+pub fn run_contract_entrypoint(
+    contract_class: &CasmContractClass,
+    entry_point: &CasmContractEntryPoint,
+    calldata: &[Felt252],
+    initial_gas: u64,
+    run_resources: &mut RunResources,
+    syscall_handler: Box<dyn SyscallHandler>,
+) -> Result<CallInfo, CairoRunError> {
+    let program = Program::try_from(contract_class.clone())?;
+    run_program_entrypoint(
+        &program,
+        contract_class,
+        entry_point,
+        calldata,
+        initial_gas,
+        run_resources,
+        syscall_handler,
+    )
+}
+
+/// Runs an entry point of a contract class as [run_contract_entrypoint] does,
+/// reusing the program built from the class (`Program::try_from(contract_class)`)
+pub fn run_program_entrypoint(
+    program: &Program,
+    contract_class: &CasmContractClass,
+    entry_point: &CasmContractEntryPoint,
+    calldata: &[Felt252],
+    initial_gas: u64,
+    run_resources: &mut RunResources,
+    syscall_handler: Box<dyn SyscallHandler>,
+) -> Result<CallInfo, CairoRunError> {
+    let mut runner = CairoRunner::new(program, LayoutName::all_cairo, None, false, false)?;
+    let mut hint_processor = Cairo1HintProcessor::new(&contract_class.hints, run_resources.clone())
+        .with_syscall_handler(syscall_handler);
+
+    let builtins = entry_point
+        .builtins
+        .iter()
+        .map(|name| {
+            BuiltinName::from_str(name).ok_or_else(|| ProgramError::UnknownBuiltin(name.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    runner.initialize_function_runner_cairo_1(&builtins)?;
+
+    // Implicit args: builtins, gas & syscall segment
+    let mut implicit_args: Vec<MaybeRelocatable> = runner
+        .vm
+        .get_builtin_runners()
+        .iter()
+        .filter(|builtin| builtins.contains(&builtin.name()))
+        .flat_map(|builtin| builtin.initial_stack())
+        .collect();
+    implicit_args.push(Felt252::from(initial_gas).into());
+    implicit_args.push(runner.vm.add_memory_segment().into());
+
+    // The contract expects a pointer to the builtin costs right after the program, preceded by a `ret`
+    let builtin_costs: Vec<MaybeRelocatable> = CostTokenType::iter_precost()
+        .map(|token_type| token_gas_cost(*token_type).into())
+        .collect();
+    let builtin_costs_ptr = runner.vm.add_memory_segment();
+    runner.vm.load_data(builtin_costs_ptr, &builtin_costs)?;
+    let core_program_end_ptr = (runner.program_base.ok_or(RunnerError::NoProgBase)?
+        + runner.program.shared_program_data.data.len())
+    .map_err(VirtualMachineError::Math)?;
+    let program_extra_data: Vec<MaybeRelocatable> =
+        vec![0x208B7FFF7FFF7FFE.into(), builtin_costs_ptr.into()];
+    runner
+        .vm
+        .load_data(core_program_end_ptr, &program_extra_data)?;
+
+    let calldata: Vec<MaybeRelocatable> = calldata.iter().map(MaybeRelocatable::from).collect();
+    let calldata_start = runner.vm.add_memory_segment();
+    let calldata_end = runner.vm.load_data(calldata_start, &calldata)?;
+
+    let mut entrypoint_args: Vec<CairoArg> =
+        implicit_args.into_iter().map(CairoArg::from).collect();
+    entrypoint_args.extend([
+        MaybeRelocatable::from(calldata_start).into(),
+        MaybeRelocatable::from(calldata_end).into(),
+    ]);
+    let entrypoint_args: Vec<&CairoArg> = entrypoint_args.iter().collect();
+
+    let run_result = runner.run_from_entrypoint(
+        entry_point.offset,
+        &entrypoint_args,
+        true,
+        Some(runner.program.shared_program_data.data.len() + program_extra_data.len()),
+        &mut hint_processor,
+    );
+    *run_resources = hint_processor.run_resources().clone();
+    run_result?;
+
+    // Return values: gas, syscall pointer, failure flag & retdata
+    let return_values_ptr = (runner.vm.get_ap() - 5).map_err(VirtualMachineError::Math)?;
+    let remaining_gas = runner.vm.get_integer(return_values_ptr)?.into_owned();
+    let remaining_gas = remaining_gas.to_u64().ok_or_else(|| {
+        VirtualMachineError::Math(MathError::Felt252ToU64Conversion(Box::new(remaining_gas)))
+    })?;
+    let failed = *runner
+        .vm
+        .get_integer((return_values_ptr + 2_usize).map_err(VirtualMachineError::Math)?)?
+        != Felt252::ZERO;
+    let retdata_start = runner
+        .vm
+        .get_relocatable((return_values_ptr + 3_usize).map_err(VirtualMachineError::Math)?)?;
+    let retdata_end = runner
+        .vm
+        .get_relocatable((return_values_ptr + 4_usize).map_err(VirtualMachineError::Math)?)?;
+    let retdata = runner
+        .vm
+        .get_integer_range(
+            retdata_start,
+            (retdata_end - retdata_start).map_err(VirtualMachineError::Math)?,
+        )?
+        .into_iter()
+        .map(|value| value.into_owned())
+        .collect();
+
+    Ok(CallInfo {
+        failed,
+        retdata,
+        remaining_gas,
+        execution_resources: runner.get_execution_resources()?,
+    })
+}
+
+/// Computes the selector of an entry point from its name, as Starknet does (`sn_keccak`)
+pub fn selector_from_name(name: &str) -> Felt252 {
+    let hash = BigUint::from_bytes_be(&Keccak256::digest(name.as_bytes()));
+    // sn_keccak keeps the lowest 250 bits of the keccak hash
+    Felt252::from(&(hash & ((BigUint::one() << 250_u32) - 1_u32)))
+}
+
+/// Computes the address of a contract deployed from `deployer_address`, as Starknet does
+pub fn calculate_contract_address(
+    salt: &Felt252,
+    class_hash: &Felt252,
+    constructor_calldata: &[Felt252],
+    deployer_address: &Felt252,
+) -> Felt252 {
+    let address = Pedersen::hash_array(&[
+        Felt252::from_bytes_be_slice(b"STARKNET_CONTRACT_ADDRESS"),
+        *deployer_address,
+        *salt,
+        *class_hash,
+        Pedersen::hash_array(constructor_calldata),
+    ]);
+    // Addresses are kept below 2**251 - 256
+    let upper_bound = (BigUint::one() << 251_u32) - 256_u32;
+    Felt252::from(&(address.to_biguint() % upper_bound))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn hint_processor_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<InMemorySyscallHandler>();
+        assert_send::<Cairo1HintProcessor>();
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn calls_nested_too_deep_fail() {
+        let mut handler = InMemorySyscallHandler {
+            call_depth: MAX_CALL_DEPTH,
+            ..Default::default()
+        };
+        assert_matches!(
+            handler.library_call(
+                Felt252::ONE,
+                Felt252::ONE,
+                Vec::new(),
+                &mut 100_000,
+                &mut RunResources::default()
+            ),
+            Err(SyscallError::Hint(HintError::SyscallError(message)))
+                if message.as_ref() == format!("Max call depth of {MAX_CALL_DEPTH} exceeded")
+        );
+        // Below the limit, calls to missing classes are reverted
+        handler.call_depth = MAX_CALL_DEPTH - 1;
+        assert_matches!(
+            handler.library_call(
+                Felt252::ONE,
+                Felt252::ONE,
+                Vec::new(),
+                &mut 100_000,
+                &mut RunResources::default()
+            ),
+            Err(SyscallError::Revert(_))
+        );
+    }
+}
@@ -0,0 +1,942 @@
+use super::hint_processor_utils::{cell_ref_to_relocatable, extract_buffer, get_ptr};
+use super::syscall_handler::{
+    ExecutionInfo, SecpGasCosts, SyscallError, SyscallGasCosts, SyscallHandler,
+};
+use crate::hint_processor::hint_processor_utils::felt_to_u32;
+use crate::stdlib::prelude::*;
+use crate::types::{
+    exec_scope::ExecutionScopes,
+    relocatable::{MaybeRelocatable, Relocatable},
+};
+use crate::vm::{
+    errors::hint_errors::HintError, runners::cairo_runner::RunResources, vm_core::VirtualMachine,
+};
+use crate::Felt252;
+use cairo_lang_casm::operand::{CellRef, ResOperand};
+use generic_array::GenericArray;
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{One, ToPrimitive, Zero};
+use sha2::compress256;
+
+/// Result of a syscall, written back to the syscall segment after the request
+enum SyscallResult {
+    Success(Vec<MaybeRelocatable>),
+    Failure(Vec<Felt252>),
+}
+
+/// Cursor over the syscall segment, which reads the request and then writes the response
+struct SyscallBuffer {
+    ptr: Relocatable,
+}
+
+impl SyscallBuffer {
+    fn next_felt(&mut self, vm: &VirtualMachine) -> Result<Felt252, HintError> {
+        let value = vm.get_integer(self.ptr)?.into_owned();
+        self.ptr += 1;
+        Ok(value)
+    }
+
+    fn next_u64(&mut self, vm: &VirtualMachine) -> Result<u64, HintError> {
+        let value = self.next_felt(vm)?;
+        value.to_u64().ok_or_else(|| {
+            HintError::SyscallError(format!("Expected a u64 value, got {value}").into())
+        })
+    }
+
+    fn next_usize(&mut self, vm: &VirtualMachine) -> Result<usize, HintError> {
+        let value = self.next_felt(vm)?;
+        value.to_usize().ok_or_else(|| {
+            HintError::SyscallError(format!("Expected a usize value, got {value}").into())
+        })
+    }
+
+    // A u256 is passed as its low & high 128-bit limbs
+    fn next_u256(&mut self, vm: &VirtualMachine) -> Result<BigUint, HintError> {
+        let low = self.next_felt(vm)?.to_biguint();
+        let high = self.next_felt(vm)?.to_biguint();
+        Ok(low + (high << 128_u32))
+    }
+
+    fn next_addr(&mut self, vm: &VirtualMachine) -> Result<Relocatable, HintError> {
+        let value = vm.get_relocatable(self.ptr)?;
+        self.ptr += 1;
+        Ok(value)
+    }
+
+    // An array is passed as its start & end pointers
+    fn next_arr(&mut self, vm: &VirtualMachine) -> Result<Vec<Felt252>, HintError> {
+        let start = self.next_addr(vm)?;
+        let end = self.next_addr(vm)?;
+        read_felts(vm, start, (end - start)?)
+    }
+
+    fn write<T: Into<MaybeRelocatable>>(
+        &mut self,
+        vm: &mut VirtualMachine,
+        value: T,
+    ) -> Result<(), HintError> {
+        vm.insert_value(self.ptr, value)?;
+        self.ptr += 1;
+        Ok(())
+    }
+}
+
+fn read_felts(
+    vm: &VirtualMachine,
+    start: Relocatable,
+    len: usize,
+) -> Result<Vec<Felt252>, HintError> {
+    Ok(vm
+        .get_integer_range(start, len)?
+        .into_iter()
+        .map(|value| value.into_owned())
+        .collect())
+}
+
+// Writes the values to a new segment, returning its start & end pointers
+fn write_segment(
+    vm: &mut VirtualMachine,
+    values: Vec<MaybeRelocatable>,
+) -> Result<(Relocatable, Relocatable), HintError> {
+    let start = vm.add_memory_segment();
+    let end = vm.load_data(start, &values)?;
+    Ok((start, end))
+}
+
+fn felts(values: &[Felt252]) -> Vec<MaybeRelocatable> {
+    values.iter().map(MaybeRelocatable::from).collect()
+}
+
+// Fails the syscall if there isn't enough gas left to pay for it
+fn deduct_gas(gas_counter: &mut u64, amount: u64) -> Result<(), SyscallError> {
+    *gas_counter = gas_counter
+        .checked_sub(amount)
+        .ok_or_else(|| SyscallError::revert("Syscall out of gas"))?;
+    Ok(())
+}
+
+fn extract_relocatable(vm: &VirtualMachine, buffer: &ResOperand) -> Result<Relocatable, HintError> {
+    let (cell, base_offset) = extract_buffer(buffer)?;
+    Ok(get_ptr(vm, cell, &base_offset)?)
+}
+
+/// Runs the syscall whose request starts at the `system` pointer.
+///
+/// The request starts with the syscall selector (its name as a short string) and the gas counter,
+/// and is followed by the response: the gas counter, a failure flag, and either the syscall's return values
+/// or the revert reason as an array
+pub(crate) fn execute_syscall(
+    syscall_handler: &mut dyn SyscallHandler,
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    system: &ResOperand,
+    run_resources: &mut RunResources,
+) -> Result<(), HintError> {
+    let mut buffer = SyscallBuffer {
+        ptr: extract_relocatable(vm, system)?,
+    };
+    let selector = felt_to_short_string(&buffer.next_felt(vm)?);
+    let mut gas_counter = buffer.next_u64(vm)?;
+    let gas_costs = *syscall_handler.syscall_gas_costs();
+
+    let result = match run_syscall(
+        syscall_handler,
+        vm,
+        exec_scopes,
+        &mut buffer,
+        &selector,
+        &mut gas_counter,
+        &gas_costs,
+        run_resources,
+    ) {
+        Ok(values) => SyscallResult::Success(values),
+        Err(SyscallError::Revert(reason)) => SyscallResult::Failure(reason),
+        Err(SyscallError::Hint(error)) => return Err(error),
+    };
+
+    buffer.write(vm, Felt252::from(gas_counter))?;
+    match result {
+        SyscallResult::Success(values) => {
+            buffer.write(vm, Felt252::ZERO)?;
+            for value in values {
+                buffer.write(vm, value)?;
+            }
+        }
+        SyscallResult::Failure(reason) => {
+            buffer.write(vm, Felt252::ONE)?;
+            let (start, end) = write_segment(vm, felts(&reason))?;
+            buffer.write(vm, start)?;
+            buffer.write(vm, end)?;
+        }
+    }
+    Ok(())
+}
+
+// Reads the request of the syscall, charges its gas and runs it, returning its response values
+#[allow(clippy::too_many_arguments)]
+fn run_syscall(
+    syscall_handler: &mut dyn SyscallHandler,
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    buffer: &mut SyscallBuffer,
+    selector: &str,
+    gas_counter: &mut u64,
+    gas_costs: &SyscallGasCosts,
+    run_resources: &mut RunResources,
+) -> Result<Vec<MaybeRelocatable>, SyscallError> {
+    match selector {
+        "StorageRead" => {
+            let address_domain = buffer.next_felt(vm)?;
+            let key = buffer.next_felt(vm)?;
+            deduct_gas(gas_counter, gas_costs.storage_read)?;
+            let value = syscall_handler.storage_read(address_domain, key)?;
+            Ok(vec![value.into()])
+        }
+        "StorageWrite" => {
+            let address_domain = buffer.next_felt(vm)?;
+            let key = buffer.next_felt(vm)?;
+            let value = buffer.next_felt(vm)?;
+            deduct_gas(gas_counter, gas_costs.storage_write)?;
+            syscall_handler.storage_write(address_domain, key, value)?;
+            Ok(Vec::new())
+        }
+        "EmitEvent" => {
+            let keys = buffer.next_arr(vm)?;
+            let data = buffer.next_arr(vm)?;
+            deduct_gas(gas_counter, gas_costs.emit_event)?;
+            syscall_handler.emit_event(keys, data)?;
+            Ok(Vec::new())
+        }
+        "SendMessageToL1" => {
+            let to_address = buffer.next_felt(vm)?;
+            let payload = buffer.next_arr(vm)?;
+            deduct_gas(gas_counter, gas_costs.send_message_to_l1)?;
+            syscall_handler.send_message_to_l1(to_address, payload)?;
+            Ok(Vec::new())
+        }
+        "GetBlockHash" => {
+            let block_number = buffer.next_u64(vm)?;
+            deduct_gas(gas_counter, gas_costs.get_block_hash)?;
+            let block_hash = syscall_handler.get_block_hash(block_number)?;
+            Ok(vec![block_hash.into()])
+        }
+        "GetExecutionInfo" => {
+            deduct_gas(gas_counter, gas_costs.get_execution_info)?;
+            let execution_info = syscall_handler.get_execution_info()?;
+            Ok(vec![write_execution_info(vm, &execution_info)?.into()])
+        }
+        "CallContract" => {
+            let contract_address = buffer.next_felt(vm)?;
+            let selector = buffer.next_felt(vm)?;
+            let calldata = buffer.next_arr(vm)?;
+            deduct_gas(gas_counter, gas_costs.call_contract)?;
+            let retdata = syscall_handler.call_contract(
+                contract_address,
+                selector,
+                calldata,
+                gas_counter,
+                run_resources,
+            )?;
+            let (start, end) = write_segment(vm, felts(&retdata))?;
+            Ok(vec![start.into(), end.into()])
+        }
+        "LibraryCall" => {
+            let class_hash = buffer.next_felt(vm)?;
+            let selector = buffer.next_felt(vm)?;
+            let calldata = buffer.next_arr(vm)?;
+            deduct_gas(gas_counter, gas_costs.library_call)?;
+            let retdata = syscall_handler.library_call(
+                class_hash,
+                selector,
+                calldata,
+                gas_counter,
+                run_resources,
+            )?;
+            let (start, end) = write_segment(vm, felts(&retdata))?;
+            Ok(vec![start.into(), end.into()])
+        }
+        "Deploy" => {
+            let class_hash = buffer.next_felt(vm)?;
+            let salt = buffer.next_felt(vm)?;
+            let calldata = buffer.next_arr(vm)?;
+            let deploy_from_zero = buffer.next_felt(vm)? != Felt252::ZERO;
+            deduct_gas(gas_counter, gas_costs.deploy)?;
+            let (contract_address, retdata) = syscall_handler.deploy(
+                class_hash,
+                salt,
+                calldata,
+                deploy_from_zero,
+                gas_counter,
+                run_resources,
+            )?;
+            let (start, end) = write_segment(vm, felts(&retdata))?;
+            Ok(vec![contract_address.into(), start.into(), end.into()])
+        }
+        "ReplaceClass" => {
+            let class_hash = buffer.next_felt(vm)?;
+            deduct_gas(gas_counter, gas_costs.replace_class)?;
+            syscall_handler.replace_class(class_hash)?;
+            Ok(Vec::new())
+        }
+        "Keccak" => {
+            let input = buffer.next_arr(vm)?;
+            keccak_syscall(&input, gas_counter, gas_costs)
+        }
+        "Sha256ProcessBlock" => {
+            let state_ptr = buffer.next_addr(vm)?;
+            let block_ptr = buffer.next_addr(vm)?;
+            deduct_gas(gas_counter, gas_costs.sha256_process_block)?;
+            sha256_process_block(vm, state_ptr, block_ptr)
+        }
+        _ => {
+            if let Some((curve, secp_gas_costs, operation)) = selector
+                .strip_prefix("Secp256k1")
+                .map(|operation| (&SECP256K1, &gas_costs.secp256k1, operation))
+                .or_else(|| {
+                    selector
+                        .strip_prefix("Secp256r1")
+                        .map(|operation| (&SECP256R1, &gas_costs.secp256r1, operation))
+                })
+            {
+                return secp_syscall(
+                    curve,
+                    secp_gas_costs,
+                    operation,
+                    vm,
+                    exec_scopes,
+                    buffer,
+                    gas_counter,
+                );
+            }
+            Err(HintError::SyscallError(format!("Unknown syscall {selector}").into()).into())
+        }
+    }
+}
+
+/// Runs the cheatcode with the given selector, writing its output to a new segment
+pub(crate) fn execute_cheatcode(
+    syscall_handler: &mut dyn SyscallHandler,
+    vm: &mut VirtualMachine,
+    selector: &Felt252,
+    input_start: &ResOperand,
+    input_end: &ResOperand,
+    output_start: &CellRef,
+    output_end: &CellRef,
+) -> Result<(), HintError> {
+    let input_start = extract_relocatable(vm, input_start)?;
+    let input_end = extract_relocatable(vm, input_end)?;
+    let input = read_felts(vm, input_start, (input_end - input_start)?)?;
+    let output = syscall_handler.cheatcode(&felt_to_short_string(selector), input)?;
+    let (start, end) = write_segment(vm, felts(&output))?;
+    vm.insert_value(cell_ref_to_relocatable(output_start, vm)?, start)?;
+    vm.insert_value(cell_ref_to_relocatable(output_end, vm)?, end)?;
+    Ok(())
+}
+
+fn felt_to_short_string(value: &Felt252) -> String {
+    let bytes = value.to_bytes_be();
+    String::from_utf8_lossy(&bytes)
+        .trim_start_matches('\0')
+        .to_string()
+}
+
+// Writes the execution info struct and the structs it points to, returning its address
+fn write_execution_info(
+    vm: &mut VirtualMachine,
+    execution_info: &ExecutionInfo,
+) -> Result<Relocatable, HintError> {
+    let block_info = &execution_info.block_info;
+    let (block_info_ptr, _) = write_segment(
+        vm,
+        vec![
+            Felt252::from(block_info.block_number).into(),
+            Felt252::from(block_info.block_timestamp).into(),
+            block_info.sequencer_address.into(),
+        ],
+    )?;
+
+    let tx_info = &execution_info.tx_info;
+    let (signature_start, signature_end) = write_segment(vm, felts(&tx_info.signature))?;
+    let resource_bounds = tx_info
+        .resource_bounds
+        .iter()
+        .flat_map(|bounds| {
+            [
+                bounds.resource.into(),
+                Felt252::from(bounds.max_amount).into(),
+                Felt252::from(bounds.max_price_per_unit).into(),
+            ]
+        })
+        .collect();
+    let (resource_bounds_start, resource_bounds_end) = write_segment(vm, resource_bounds)?;
+    let (paymaster_data_start, paymaster_data_end) =
+        write_segment(vm, felts(&tx_info.paymaster_data))?;
+    let (account_deployment_data_start, account_deployment_data_end) =
+        write_segment(vm, felts(&tx_info.account_deployment_data))?;
+    let (tx_info_ptr, _) = write_segment(
+        vm,
+        vec![
+            tx_info.version.into(),
+            tx_info.account_contract_address.into(),
+            Felt252::from(tx_info.max_fee).into(),
+            signature_start.into(),
+            signature_end.into(),
+            tx_info.transaction_hash.into(),
+            tx_info.chain_id.into(),
+            tx_info.nonce.into(),
+            resource_bounds_start.into(),
+            resource_bounds_end.into(),
+            Felt252::from(tx_info.tip).into(),
+            paymaster_data_start.into(),
+            paymaster_data_end.into(),
+            Felt252::from(tx_info.nonce_data_availability_mode).into(),
+            Felt252::from(tx_info.fee_data_availability_mode).into(),
+            account_deployment_data_start.into(),
+            account_deployment_data_end.into(),
+        ],
+    )?;
+
+    let (execution_info_ptr, _) = write_segment(
+        vm,
+        vec![
+            block_info_ptr.into(),
+            tx_info_ptr.into(),
+            execution_info.caller_address.into(),
+            execution_info.contract_address.into(),
+            execution_info.entry_point_selector.into(),
+        ],
+    )?;
+    Ok(execution_info_ptr)
+}
+
+// Absorbs the input, made of 64-bit words, in blocks of 17 words and returns the hash as a u256
+fn keccak_syscall(
+    input: &[Felt252],
+    gas_counter: &mut u64,
+    gas_costs: &SyscallGasCosts,
+) -> Result<Vec<MaybeRelocatable>, SyscallError> {
+    deduct_gas(gas_counter, gas_costs.keccak)?;
+    if input.len() % 17 != 0 {
+        return Err(SyscallError::revert("Invalid keccak input size"));
+    }
+    let mut state = [0_u64; 25];
+    for block in input.chunks(17) {
+        deduct_gas(gas_counter, gas_costs.keccak_round)?;
+        for (word, value) in state.iter_mut().zip(block) {
+            *word ^= value.to_u64().ok_or_else(|| {
+                HintError::SyscallError(format!("Invalid keccak input word {value}").into())
+            })?;
+        }
+        keccak::f1600(&mut state);
+    }
+    let low = ((state[1] as u128) << 64) | state[0] as u128;
+    let high = ((state[3] as u128) << 64) | state[2] as u128;
+    Ok(vec![Felt252::from(low).into(), Felt252::from(high).into()])
+}
+
+// Compresses a 16-word block into the 8-word state, writing the new state to a new segment
+fn sha256_process_block(
+    vm: &mut VirtualMachine,
+    state_ptr: Relocatable,
+    block_ptr: Relocatable,
+) -> Result<Vec<MaybeRelocatable>, SyscallError> {
+    let mut state = [0_u32; 8];
+    for (word, value) in state.iter_mut().zip(read_felts(vm, state_ptr, 8)?) {
+        *word = felt_to_u32(&value).map_err(HintError::from)?;
+    }
+    let mut block = Vec::with_capacity(64);
+    for value in read_felts(vm, block_ptr, 16)? {
+        block.extend(felt_to_u32(&value).map_err(HintError::from)?.to_be_bytes());
+    }
+    compress256(&mut state, &[GenericArray::clone_from_slice(&block)]);
+    let (new_state_ptr, _) = write_segment(
+        vm,
+        state
+            .iter()
+            .map(|word| Felt252::from(*word).into())
+            .collect(),
+    )?;
+    Ok(vec![new_state_ptr.into()])
+}
+
+/// Short Weierstrass curve y^2 = x^3 + a*x + b over the prime field of size p
+struct Curve {
+    name: &'static str,
+    p: &'static str,
+    a: &'static str,
+    b: &'static str,
+}
+
+const SECP256K1: Curve = Curve {
+    name: "secp256k1",
+    p: "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+    a: "0",
+    b: "7",
+};
+
+const SECP256R1: Curve = Curve {
+    name: "secp256r1",
+    p: "ffffffff00000001000000000000000000000000ffffffffffffffffffffffff",
+    a: "ffffffff00000001000000000000000000000000fffffffffffffffffffffffc",
+    b: "5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b",
+};
+
+/// A point in affine coordinates, None being the point at infinity
+type Point = Option<(BigUint, BigUint)>;
+
+/// Field arithmetic of a [Curve], with its parameters parsed
+struct CurveArithmetic {
+    p: BigUint,
+    a: BigUint,
+    b: BigUint,
+}
+
+impl CurveArithmetic {
+    fn new(curve: &Curve) -> Self {
+        let parse = |value: &[u8]| BigUint::parse_bytes(value, 16).unwrap_or_default();
+        CurveArithmetic {
+            p: parse(curve.p.as_bytes()),
+            a: parse(curve.a.as_bytes()),
+            b: parse(curve.b.as_bytes()),
+        }
+    }
+
+    fn inverse(&self, value: &BigUint) -> BigUint {
+        value.modpow(&(&self.p - 2_u32), &self.p)
+    }
+
+    // Returns x^3 + a*x + b
+    fn y_squared(&self, x: &BigUint) -> BigUint {
+        (x * x * x + &self.a * x + &self.b) % &self.p
+    }
+
+    fn is_on_curve(&self, (x, y): (&BigUint, &BigUint)) -> bool {
+        (y * y) % &self.p == self.y_squared(x)
+    }
+
+    fn add(&self, lhs: &Point, rhs: &Point) -> Point {
+        let ((x0, y0), (x1, y1)) = match (lhs, rhs) {
+            (None, point) | (point, None) => return point.clone(),
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+        };
+        let p = &self.p;
+        let slope = if x0 == x1 {
+            if (y0 + y1) % p == BigUint::zero() {
+                return None;
+            }
+            (BigUint::from(3_u32) * x0 * x0 + &self.a) * self.inverse(&(y0 * 2_u32)) % p
+        } else {
+            ((y1 + p - y0) % p) * self.inverse(&((x1 + p - x0) % p)) % p
+        };
+        let x = (&slope * &slope + p * 2_u32 - x0 - x1) % p;
+        let y = (slope * ((x0 + p - &x) % p) + p - y0) % p;
+        Some((x, y))
+    }
+
+    fn mul(&self, point: &Point, scalar: &BigUint) -> Point {
+        let mut result = None;
+        for bit in (0..scalar.bits()).rev() {
+            result = self.add(&result, &result);
+            if scalar.bit(bit) {
+                result = self.add(&result, point);
+            }
+        }
+        result
+    }
+
+    // p = 3 (mod 4) for both curves, so the square root of a quadratic residue is its (p + 1) / 4 power
+    fn sqrt(&self, value: &BigUint) -> Option<BigUint> {
+        let root = value.modpow(&((&self.p + 1_u32) >> 2), &self.p);
+        ((&root * &root) % &self.p == *value).then_some(root)
+    }
+}
+
+/// Points created by the secp256k1/r1 syscalls of a run, referred to by their index
+#[derive(Clone, Default)]
+pub(crate) struct SecpExecScope {
+    points: Vec<Point>,
+}
+
+impl SecpExecScope {
+    fn add_point(&mut self, point: Point) -> MaybeRelocatable {
+        self.points.push(point);
+        (self.points.len() - 1).into()
+    }
+
+    fn get_point(&self, id: usize) -> Result<&Point, HintError> {
+        self.points
+            .get(id)
+            .ok_or_else(|| HintError::SyscallError(format!("Unknown secp point id {id}").into()))
+    }
+}
+
+// Options are returned as a variant index (Some being 0) followed by the value
+fn some(value: MaybeRelocatable) -> Vec<MaybeRelocatable> {
+    vec![0.into(), value]
+}
+
+fn none() -> Vec<MaybeRelocatable> {
+    vec![1.into(), 0.into()]
+}
+
+fn u256_limbs(value: &BigUint) -> [MaybeRelocatable; 2] {
+    let (high, low) = value.div_rem(&(BigUint::one() << 128_u32));
+    [Felt252::from(&low).into(), Felt252::from(&high).into()]
+}
+
+fn secp_syscall(
+    curve: &Curve,
+    gas_costs: &SecpGasCosts,
+    operation: &str,
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    buffer: &mut SyscallBuffer,
+    gas_counter: &mut u64,
+) -> Result<Vec<MaybeRelocatable>, SyscallError> {
+    let arithmetic = CurveArithmetic::new(curve);
+    let scope_name = format!("{}_exec_scope", curve.name);
+    let scope = match exec_scopes.get_mut_ref::<SecpExecScope>(&scope_name) {
+        Ok(scope) => scope,
+        Err(_) => {
+            exec_scopes.assign_or_update_variable(&scope_name, Box::<SecpExecScope>::default());
+            exec_scopes.get_mut_ref::<SecpExecScope>(&scope_name)?
+        }
+    };
+    match operation {
+        "New" => {
+            let x = buffer.next_u256(vm)?;
+            let y = buffer.next_u256(vm)?;
+            deduct_gas(gas_counter, gas_costs.new)?;
+            if x >= arithmetic.p || y >= arithmetic.p {
+                return Err(SyscallError::revert("Coordinates out of range"));
+            }
+            // (0, 0) stands for the point at infinity
+            if x.is_zero() && y.is_zero() {
+                return Ok(some(scope.add_point(None)));
+            }
+            if !arithmetic.is_on_curve((&x, &y)) {
+                return Ok(none());
+            }
+            Ok(some(scope.add_point(Some((x, y)))))
+        }
+        "Add" => {
+            let lhs = buffer.next_usize(vm)?;
+            let rhs = buffer.next_usize(vm)?;
+            deduct_gas(gas_counter, gas_costs.add)?;
+            let sum = arithmetic.add(scope.get_point(lhs)?, scope.get_point(rhs)?);
+            Ok(vec![scope.add_point(sum)])
+        }
+        "Mul" => {
+            let point = buffer.next_usize(vm)?;
+            let scalar = buffer.next_u256(vm)?;
+            deduct_gas(gas_counter, gas_costs.mul)?;
+            let product = arithmetic.mul(scope.get_point(point)?, &scalar);
+            Ok(vec![scope.add_point(product)])
+        }
+        "GetPointFromX" => {
+            let x = buffer.next_u256(vm)?;
+            let y_parity = buffer.next_felt(vm)? != Felt252::ZERO;
+            deduct_gas(gas_counter, gas_costs.get_point_from_x)?;
+            if x >= arithmetic.p {
+                return Err(SyscallError::revert("Coordinates out of range"));
+            }
+            let Some(y) = arithmetic.sqrt(&arithmetic.y_squared(&x)) else {
+                return Ok(none());
+            };
+            let y = if y.bit(0) == y_parity {
+                y
+            } else {
+                (&arithmetic.p - y) % &arithmetic.p
+            };
+            Ok(some(scope.add_point(Some((x, y)))))
+        }
+        "GetXy" => {
+            let point = buffer.next_usize(vm)?;
+            deduct_gas(gas_counter, gas_costs.get_xy)?;
+            let (x, y) = scope
+                .get_point(point)?
+                .clone()
+                .unwrap_or((BigUint::zero(), BigUint::zero()));
+            Ok(u256_limbs(&x).into_iter().chain(u256_limbs(&y)).collect())
+        }
+        _ => Err(HintError::SyscallError(
+            format!("Unknown syscall {}{operation}", curve.name).into(),
+        )
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::syscall_handler::{InMemorySyscallHandler, StarknetState};
+    use super::*;
+    use crate::stdlib::sync::{Arc, Mutex};
+    use crate::utils::test_utils::*;
+    use cairo_lang_casm::operand::Register;
+    use num_traits::Num;
+    use rstest::rstest;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    // The request is written to segment 2, pointed to by [fp].
+    // Segment 3 is left empty for the arrays of the requests
+    fn run_request(
+        handler: &mut dyn SyscallHandler,
+        exec_scopes: &mut ExecutionScopes,
+        request: Vec<MaybeRelocatable>,
+    ) -> (VirtualMachine, Relocatable) {
+        let mut vm = vm!();
+        vm.add_memory_segment();
+        let execution_segment = vm.add_memory_segment();
+        let syscall_segment = vm.add_memory_segment();
+        vm.add_memory_segment();
+        vm.insert_value(execution_segment, syscall_segment).unwrap();
+        let response = vm.load_data(syscall_segment, &request).unwrap();
+        let system = ResOperand::Deref(CellRef {
+            register: Register::FP,
+            offset: 0,
+        });
+        execute_syscall(
+            handler,
+            &mut vm,
+            exec_scopes,
+            &system,
+            &mut RunResources::default(),
+        )
+        .unwrap();
+        (vm, response)
+    }
+
+    fn short_string(value: &str) -> MaybeRelocatable {
+        Felt252::from_bytes_be_slice(value.as_bytes()).into()
+    }
+
+    fn response(vm: &VirtualMachine, ptr: Relocatable, len: usize) -> Vec<MaybeRelocatable> {
+        vm.get_continuous_range(ptr, len).unwrap()
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn storage_write_and_read() {
+        let state = Arc::new(Mutex::new(StarknetState::default()));
+        let mut handler = InMemorySyscallHandler::new(state.clone(), Default::default());
+        let gas_costs = SyscallGasCosts::default();
+        let mut exec_scopes = ExecutionScopes::new();
+        let (vm, ptr) = run_request(
+            &mut handler,
+            &mut exec_scopes,
+            vec![
+                short_string("StorageWrite"),
+                10000.into(),
+                0.into(),
+                5.into(),
+                7.into(),
+            ],
+        );
+        assert_eq!(
+            response(&vm, ptr, 2),
+            vec![(10000 - gas_costs.storage_write as usize).into(), 0.into()]
+        );
+        assert_eq!(
+            state
+                .lock()
+                .unwrap()
+                .storage_at(&Felt252::ZERO, &Felt252::from(5)),
+            Felt252::from(7)
+        );
+
+        let (vm, ptr) = run_request(
+            &mut handler,
+            &mut exec_scopes,
+            vec![
+                short_string("StorageRead"),
+                10000.into(),
+                0.into(),
+                5.into(),
+            ],
+        );
+        assert_eq!(
+            response(&vm, ptr, 3),
+            vec![
+                (10000 - gas_costs.storage_read as usize).into(),
+                0.into(),
+                7.into()
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn syscall_out_of_gas() {
+        let mut handler = InMemorySyscallHandler::default();
+        let mut exec_scopes = ExecutionScopes::new();
+        let (vm, ptr) = run_request(
+            &mut handler,
+            &mut exec_scopes,
+            vec![short_string("StorageRead"), 10.into(), 0.into(), 5.into()],
+        );
+        let response = response(&vm, ptr, 4);
+        assert_eq!(response[..2], [10.into(), 1.into()]);
+        let reason_start = response[2].get_relocatable().unwrap();
+        assert_eq!(
+            vm.get_integer(reason_start).unwrap().into_owned(),
+            Felt252::from_bytes_be_slice(b"Syscall out of gas")
+        );
+        assert_eq!(response[3], (reason_start + 1_usize).unwrap().into());
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn syscall_charges_configured_gas_costs() {
+        let mut handler =
+            InMemorySyscallHandler::default().with_syscall_gas_costs(SyscallGasCosts {
+                storage_read: 7,
+                ..Default::default()
+            });
+        let mut exec_scopes = ExecutionScopes::new();
+        let (vm, ptr) = run_request(
+            &mut handler,
+            &mut exec_scopes,
+            vec![short_string("StorageRead"), 10.into(), 0.into(), 5.into()],
+        );
+        assert_eq!(response(&vm, ptr, 3), vec![3.into(), 0.into(), 0.into()]);
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn call_undeployed_contract_reverts() {
+        let mut handler = InMemorySyscallHandler::default();
+        let mut exec_scopes = ExecutionScopes::new();
+        let calldata = Relocatable::from((3, 0));
+        let (vm, ptr) = run_request(
+            &mut handler,
+            &mut exec_scopes,
+            vec![
+                short_string("CallContract"),
+                1000000.into(),
+                3.into(),
+                4.into(),
+                calldata.into(),
+                calldata.into(),
+            ],
+        );
+        let response = response(&vm, ptr, 4);
+        assert_eq!(response[1], 1.into());
+        assert_eq!(
+            vm.get_integer(response[2].get_relocatable().unwrap())
+                .unwrap()
+                .into_owned(),
+            Felt252::from_bytes_be_slice(b"CONTRACT_NOT_DEPLOYED")
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn keccak_of_empty_block() {
+        let mut input = vec![0_u64; 17];
+        // Padding of the empty input
+        input[0] = 1;
+        input[16] = 0x8000000000000000;
+        let input: Vec<Felt252> = input.into_iter().map(Felt252::from).collect();
+        let gas_costs = SyscallGasCosts::default();
+        let mut gas = 1000000;
+        let hash = keccak_syscall(&input, &mut gas, &gas_costs).unwrap();
+        // keccak256("") = c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470, as little endian u256
+        assert_eq!(
+            hash,
+            vec![
+                Felt252::from(0xc003c7dcb27d7e923c23f7860146d2c5_u128).into(),
+                Felt252::from(0x70a4855d04d8fa7b3b2782ca53b600e5_u128).into(),
+            ]
+        );
+        assert_eq!(gas, 1000000 - gas_costs.keccak_round);
+        assert_matches::assert_matches!(
+            keccak_syscall(&input[..16], &mut gas, &gas_costs),
+            Err(SyscallError::Revert(_))
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn secp256k1_generator_double() {
+        let arithmetic = CurveArithmetic::new(&SECP256K1);
+        let hex = |value: &str| BigUint::from_str_radix(value, 16).unwrap();
+        let x = hex("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798");
+        let y = hex("483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8");
+        assert!(arithmetic.is_on_curve((&x, &y)));
+        assert_eq!(
+            arithmetic
+                .sqrt(&arithmetic.y_squared(&x))
+                .map(|root| root == y || root == &arithmetic.p - &y),
+            Some(true)
+        );
+        let generator = Some((x, y));
+        let double = arithmetic.add(&generator, &generator);
+        assert_eq!(double, arithmetic.mul(&generator, &BigUint::from(2_u32)));
+        assert_eq!(
+            double,
+            Some((
+                hex("c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5"),
+                hex("1ae168fea63dc339a3c58419466ceaeef7f632653266d0e1236431a950cfe52a"),
+            ))
+        );
+        assert_eq!(arithmetic.add(&generator, &None), generator);
+    }
+    // Multiples of the generator of each curve, from the SEC 2 & NIST test vectors, along with the order of the generator
+    #[rstest]
+    #[case::secp256k1(
+        &SECP256K1,
+        [
+            ("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798", "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"),
+            ("c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5", "1ae168fea63dc339a3c58419466ceaeef7f632653266d0e1236431a950cfe52a"),
+            ("f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9", "388f7b0f632de8140fe337e62a37f3566500a99934c2231b6cb9fd7584b8e672"),
+        ],
+        "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141"
+    )]
+    #[case::secp256r1(
+        &SECP256R1,
+        [
+            ("6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296", "4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5"),
+            ("7cf27b188d034f7e8a52380304b51ac3c08969e277f21b35a60b48fc47669978", "07775510db8ed040293d9ac69f7430dbba7dade63ce982299e04b79d227873d1"),
+            ("5ecbe4d1a6330a44c8f7ef951d4bf165e6c6b721efada985fb41661bc6e7fd6c", "8734640c4998ff7e374b06ce1a64a2ecd82ab036384fb83d9a79b127a27d5032"),
+        ],
+        "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551"
+    )]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn secp_generator_multiples(
+        #[case] curve: &Curve,
+        #[case] multiples: [(&str, &str); 3],
+        #[case] order: &str,
+    ) {
+        let arithmetic = CurveArithmetic::new(curve);
+        let hex = |value: &str| BigUint::from_str_radix(value, 16).unwrap();
+        let multiples: Vec<Point> = multiples
+            .iter()
+            .map(|(x, y)| Some((hex(x), hex(y))))
+            .collect();
+        let generator = &multiples[0];
+        for (i, multiple) in multiples.iter().enumerate() {
+            let (x, y) = multiple.as_ref().unwrap();
+            assert!(arithmetic.is_on_curve((x, y)));
+            assert_eq!(&arithmetic.mul(generator, &BigUint::from(i + 1)), multiple);
+            // Recovering the point from its x coordinate gives either the point or its negation
+            assert_eq!(
+                arithmetic
+                    .sqrt(&arithmetic.y_squared(x))
+                    .map(|root| root == *y || root == &arithmetic.p - y),
+                Some(true)
+            );
+        }
+        assert_eq!(arithmetic.add(&multiples[0], &multiples[1]), multiples[2]);
+        // Multiplying by the order of the generator gives the point at infinity, one less its negation
+        let order = hex(order);
+        assert_eq!(arithmetic.mul(generator, &order), None);
+        let (x, y) = generator.clone().unwrap();
+        assert_eq!(
+            arithmetic.mul(generator, &(&order - 1_u32)),
+            Some((x, &arithmetic.p - y))
+        );
+    }
+}
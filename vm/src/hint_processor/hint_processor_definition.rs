@@ -1,4 +1,4 @@
-use crate::stdlib::{any::Any, boxed::Box, collections::HashMap, prelude::*};
+use crate::stdlib::{any::Any, boxed::Box, collections::HashMap, prelude::*, sync::Arc};
 
 use crate::any_box;
 use crate::serde::deserialize_program::ApTracking;
@@ -51,6 +51,27 @@ pub trait HintProcessorLogic {
         }))
     }
 
+    //Transforms hint data precompiled by a CompiledProgram into the format used by execute_hint
+    //By default the hint is compiled again with compile_hint, resolving its references once more.
+    //Custom processors must override it to benefit from a CompiledProgram: processors whose
+    //execute_hint takes HintProcessorData can return the shared hint data as BuiltinHintProcessor does
+    fn compile_precompiled_hint(
+        &self,
+        //Hint data shared by every run of the compiled program
+        hint_data: &Arc<HintProcessorData>,
+        //Map from variable name to reference id number
+        reference_ids: &HashMap<String, usize>,
+        //List of all references (key corresponds to element of the previous dictionary)
+        references: &[HintReference],
+    ) -> Result<Box<dyn Any>, VirtualMachineError> {
+        self.compile_hint(
+            &hint_data.code,
+            &hint_data.ap_tracking,
+            reference_ids,
+            references,
+        )
+    }
+
     #[cfg(feature = "extensive_hints")]
     // Executes the hint which's data is provided by a dynamic structure previously created by compile_hint
     // Also returns a map of hints to be loaded after the current hint is executed
@@ -77,7 +98,7 @@ pub type HintExtension = HashMap<Relocatable, Vec<Box<dyn Any>>>;
 pub trait HintProcessor: HintProcessorLogic + ResourceTracker {}
 impl<T> HintProcessor for T where T: HintProcessorLogic + ResourceTracker {}
 
-fn get_ids_data(
+pub(crate) fn get_ids_data(
     reference_ids: &HashMap<String, usize>,
     references: &[HintReference],
 ) -> Result<HashMap<String, HintReference>, VirtualMachineError> {
@@ -0,0 +1,382 @@
+use serde::Deserialize;
+
+use crate::{
+    hint_processor::hint_processor_definition::HintReference,
+    serde::{
+        deserialize_program::{HintParams, ValueAddress},
+        deserialize_utils::parse_value,
+    },
+    stdlib::{
+        collections::{BTreeMap, HashMap},
+        prelude::*,
+    },
+    types::program::Program,
+    vm::errors::vm_errors::VirtualMachineError,
+};
+
+/// The hints a program may contain, along with the reference expressions each of them may use,
+/// read from a whitelist in the format of cairo-lang's `allowed_reference_expressions_for_hint`.
+/// Programs are only accepted if every hint is whitelisted, and if every reference accessible by
+/// a hint matches one of the expressions allowed for it under the same name.
+/// Whitelists holding an expression that can't be parsed fail to deserialize.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "WhitelistJson")]
+pub struct HintWhitelist {
+    // Maps each hint code to its allowed (reference name, expression) pairs
+    allowed_hints: HashMap<String, Vec<(String, ValueAddress)>>,
+}
+
+#[derive(Deserialize)]
+struct WhitelistJson {
+    allowed_reference_expressions_for_hint: Vec<WhitelistEntryJson>,
+}
+
+#[derive(Deserialize)]
+struct WhitelistEntryJson {
+    allowed_expressions: Vec<NamedExpressionJson>,
+    hint_lines: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct NamedExpressionJson {
+    expr: String,
+    name: String,
+}
+
+impl TryFrom<WhitelistJson> for HintWhitelist {
+    type Error = String;
+
+    fn try_from(whitelist: WhitelistJson) -> Result<Self, Self::Error> {
+        let mut hint_whitelist = HintWhitelist::default();
+        for entry in whitelist.allowed_reference_expressions_for_hint {
+            let allowed_expressions = entry
+                .allowed_expressions
+                .into_iter()
+                .map(|named_expr| match parse_value(&named_expr.expr) {
+                    Ok((_, expr)) => Ok((named_expr.name, expr)),
+                    Err(_) => Err(format!(
+                        "Invalid reference expression {:?} allowed for {} in the hint whitelist",
+                        named_expr.expr, named_expr.name
+                    )),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            hint_whitelist
+                .allowed_hints
+                .entry(entry.hint_lines.join("\n"))
+                .or_default()
+                .extend(allowed_expressions);
+        }
+        Ok(hint_whitelist)
+    }
+}
+
+impl HintWhitelist {
+    /// Reads a whitelist from a JSON file
+    #[cfg(feature = "std")]
+    pub fn from_file(whitelist_path: &std::path::Path) -> std::io::Result<Self> {
+        let whitelist_file = std::fs::File::open(whitelist_path)?;
+        let whitelist = serde_json::from_reader(std::io::BufReader::new(whitelist_file))?;
+        Ok(whitelist)
+    }
+
+    /// Adds the hints & reference expressions allowed by another whitelist to this one
+    pub fn extend(&mut self, other: HintWhitelist) {
+        for (hint_code, allowed_expressions) in other.allowed_hints {
+            self.allowed_hints
+                .entry(hint_code)
+                .or_default()
+                .extend(allowed_expressions);
+        }
+    }
+
+    pub fn is_whitelisted(&self, hint_code: &str) -> bool {
+        self.allowed_hints.contains_key(hint_code)
+    }
+
+    /// Checks that a hint is whitelisted, and that the references it can access (given by their
+    /// full name & id in `references`) are allowed for it.
+    pub fn verify_hint(
+        &self,
+        hint_code: &str,
+        reference_ids: &HashMap<String, usize>,
+        references: &[HintReference],
+    ) -> Result<(), VirtualMachineError> {
+        let allowed_expressions = self
+            .allowed_hints
+            .get(hint_code)
+            .ok_or_else(|| VirtualMachineError::HintNotWhitelisted(hint_code.into()))?;
+        let mut reference_ids: Vec<_> = reference_ids.iter().collect();
+        reference_ids.sort();
+        for (name, id) in reference_ids {
+            let allowed = references.get(*id).is_some_and(|reference| {
+                allowed_expressions
+                    .iter()
+                    .any(|(allowed_name, expr)| allowed_name == name && is_expr(reference, expr))
+            });
+            if !allowed {
+                return Err(VirtualMachineError::ForbiddenHintReference(Box::new((
+                    hint_code.to_string(),
+                    name.clone(),
+                ))));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks every hint of a program with [`HintWhitelist::verify_hint`]
+    pub fn verify_program(&self, program: &Program) -> Result<(), VirtualMachineError> {
+        let hints: BTreeMap<usize, Vec<HintParams>> =
+            (&program.shared_program_data.hints_collection).into();
+        for hint in hints.values().flatten() {
+            self.verify_hint(
+                &hint.code,
+                &hint.flow_tracking_data.reference_ids,
+                &program.shared_program_data.reference_manager,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Program references are built from the same parsed expressions, see `Program::get_reference_list`
+fn is_expr(reference: &HintReference, expr: &ValueAddress) -> bool {
+    reference.offset1 == expr.offset1
+        && reference.offset2 == expr.offset2
+        && reference.outer_dereference == expr.outer_dereference
+        && reference.inner_dereference == expr.inner_dereference
+        && reference.cairo_type.as_deref() == Some(expr.value_type.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cairo_run::{cairo_run_program, CairoRunConfig};
+    use crate::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor;
+    use crate::hint_processor::hint_processor_definition::HintProcessorLogic;
+    use crate::serde::deserialize_program::{ApTracking, FlowTrackingData};
+    use crate::types::layout_name::LayoutName;
+    use crate::utils::test_utils::*;
+    use crate::vm::errors::cairo_run_errors::CairoRunError;
+    use assert_matches::assert_matches;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    const WHITELIST: &str = r#"{
+        "allowed_reference_expressions_for_hint": [
+            {
+                "allowed_expressions": [
+                    {
+                        "expr": "[cast(fp + (-3), felt*)]",
+                        "name": "__main__.main.x"
+                    }
+                ],
+                "hint_lines": [
+                    "from starkware.cairo.common.math_utils import assert_integer",
+                    "assert_integer(ids.x)"
+                ]
+            },
+            {
+                "allowed_expressions": [],
+                "hint_lines": [
+                    "memory[ap] = segments.add()"
+                ]
+            }
+        ]
+    }"#;
+
+    const ASSERT_INTEGER: &str =
+        "from starkware.cairo.common.math_utils import assert_integer\nassert_integer(ids.x)";
+
+    // The parsed type of `[cast(fp + (-3), felt*)]`
+    fn reference(offset: i32) -> HintReference {
+        HintReference {
+            cairo_type: Some("felt".to_string()),
+            ..HintReference::new_simple(offset)
+        }
+    }
+
+    fn reference_ids(names: &[&str]) -> HashMap<String, usize> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (name.to_string(), id))
+            .collect()
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn verify_whitelisted_hints() {
+        let whitelist: HintWhitelist = serde_json::from_str(WHITELIST).unwrap();
+        assert!(whitelist.is_whitelisted(ASSERT_INTEGER));
+        assert!(whitelist.is_whitelisted("memory[ap] = segments.add()"));
+        assert_matches!(
+            whitelist.verify_hint(
+                ASSERT_INTEGER,
+                &reference_ids(&["__main__.main.x"]),
+                &[reference(-3)]
+            ),
+            Ok(())
+        );
+        assert_matches!(
+            whitelist.verify_hint("memory[ap] = segments.add()", &HashMap::new(), &[]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn verify_hint_not_whitelisted() {
+        let whitelist: HintWhitelist = serde_json::from_str(WHITELIST).unwrap();
+        assert_matches!(
+            whitelist.verify_hint("import os", &HashMap::new(), &[]),
+            Err(VirtualMachineError::HintNotWhitelisted(code)) if code.as_ref() == "import os"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn verify_hint_forbidden_references() {
+        let whitelist: HintWhitelist = serde_json::from_str(WHITELIST).unwrap();
+        let forbidden =
+            |reference_ids: HashMap<String, usize>, references: &[HintReference]| match whitelist
+                .verify_hint(ASSERT_INTEGER, &reference_ids, references)
+            {
+                Err(VirtualMachineError::ForbiddenHintReference(forbidden)) => {
+                    assert_eq!(forbidden.0, ASSERT_INTEGER);
+                    forbidden.1
+                }
+                result => panic!("unexpected result: {result:?}"),
+            };
+        // Another expression
+        assert_eq!(
+            forbidden(reference_ids(&["__main__.main.x"]), &[reference(-4)]),
+            "__main__.main.x"
+        );
+        // Another name
+        assert_eq!(
+            forbidden(reference_ids(&["__main__.main.z"]), &[reference(-3)]),
+            "__main__.main.z"
+        );
+        // A reference that isn't allowed along with an allowed one
+        assert_eq!(
+            forbidden(
+                reference_ids(&["__main__.main.x", "__main__.main.y"]),
+                &[reference(-3), reference(-3)]
+            ),
+            "__main__.main.y"
+        );
+        // An unknown reference id
+        assert_eq!(
+            forbidden(reference_ids(&["__main__.main.x"]), &[]),
+            "__main__.main.x"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn whitelist_with_invalid_expression() {
+        let whitelist = WHITELIST.replace("[cast(fp + (-3), felt*)]", "not a reference");
+        let err = serde_json::from_str::<HintWhitelist>(&whitelist)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("\"not a reference\" allowed for __main__.main.x"));
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn extend_whitelist() {
+        let mut whitelist = HintWhitelist::default();
+        assert!(!whitelist.is_whitelisted(ASSERT_INTEGER));
+        whitelist.extend(serde_json::from_str(WHITELIST).unwrap());
+        assert_eq!(
+            whitelist,
+            serde_json::from_str::<HintWhitelist>(WHITELIST).unwrap()
+        );
+    }
+
+    #[test]
+    fn whitelist_from_file() {
+        let whitelist_path = std::env::temp_dir().join("cairo_vm_hint_whitelist.json");
+        std::fs::write(&whitelist_path, WHITELIST).unwrap();
+        assert_eq!(
+            HintWhitelist::from_file(&whitelist_path).unwrap(),
+            serde_json::from_str(WHITELIST).unwrap()
+        );
+        std::fs::remove_file(whitelist_path).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn builtin_hint_processor_rejects_hints() {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.set_hint_whitelist(serde_json::from_str(WHITELIST).unwrap());
+        assert!(hint_processor
+            .compile_hint(
+                "memory[ap] = segments.add()",
+                &ApTracking::default(),
+                &HashMap::new(),
+                &[]
+            )
+            .is_ok());
+        assert_matches!(
+            hint_processor.compile_hint("import os", &ApTracking::default(), &HashMap::new(), &[]),
+            Err(VirtualMachineError::HintNotWhitelisted(_))
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn verify_program_rejects_program() {
+        // valid_program_a.json has the hints "memory[ap] = segments.add()" & "import math"
+        let program = Program::from_bytes(
+            include_bytes!("../../../cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let whitelist: HintWhitelist = serde_json::from_str(WHITELIST).unwrap();
+        assert_matches!(
+            whitelist.verify_program(&program),
+            Err(VirtualMachineError::HintNotWhitelisted(code)) if code.as_ref() == "import math"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_with_hint_processor_whitelist_rejects_program() {
+        // Asserts [ap] = 1 after running the hint "import math"
+        let program = program!(
+            data = vec_data!((0x480680017fff8000_u64), (1), (0x208b7fff7fff7ffe_u64)),
+            main = Some(0),
+            hints = BTreeMap::from([(
+                0,
+                vec![HintParams {
+                    code: "import math".to_string(),
+                    accessible_scopes: vec![],
+                    flow_tracking_data: FlowTrackingData {
+                        ap_tracking: ApTracking::default(),
+                        reference_ids: HashMap::new(),
+                    },
+                }]
+            )]),
+        );
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.set_hint_whitelist(serde_json::from_str(WHITELIST).unwrap());
+        let result = cairo_run_program(
+            &program,
+            &CairoRunConfig {
+                layout: LayoutName::all_cairo,
+                ..Default::default()
+            },
+            &mut hint_processor,
+        );
+        match result {
+            Err(CairoRunError::VmException(exception)) => assert_matches!(
+                exception.inner_exc,
+                VirtualMachineError::HintNotWhitelisted(code) if code.as_ref() == "import math"
+            ),
+            Err(_) => panic!("Wrong error returned, expected VmException"),
+            Ok(_) => panic!("Expected run to fail"),
+        }
+    }
+}
@@ -3,3 +3,4 @@ pub mod builtin_hint_processor;
 pub mod cairo_1_hint_processor;
 pub mod hint_processor_definition;
 pub mod hint_processor_utils;
+pub mod hint_whitelist;
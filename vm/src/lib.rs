@@ -2,8 +2,9 @@
 //!
 //! ## Feature Flags
 //! - `std`: Enables usage of the [`std`] standard library. Enabled by default.
+//! - `hooks`: Enables [`Hooks`](crate::vm::hooks::Hooks) support for the [VirtualMachine](vm::vm_core::VirtualMachine). Not enabled by default.
 //! - `test_utils`: Enables the following to help with tests (not enabled by default):
-//!    - [`Hooks`](crate::vm::hooks::Hooks) support for the [VirtualMachine](vm::vm_core::VirtualMachine);
+//!    - the `hooks` feature;
 //!    - the `print_*` family of hints;
 //!    - the `skip_next_instruction()` hints;
 //!    - implementations of [`arbitrary::Arbitrary`](https://docs.rs/arbitrary/latest/arbitrary/) for some structs.
@@ -55,8 +56,10 @@ pub mod stdlib {
 pub mod air_private_input;
 pub mod air_public_input;
 pub mod cairo_run;
+pub mod fact_topology;
 pub mod hint_processor;
 pub mod math_utils;
+pub mod program_analysis;
 pub mod program_hash;
 pub mod serde;
 pub mod types;
@@ -0,0 +1,767 @@
+use serde::{Serialize, Serializer};
+
+use crate::{
+    hint_processor::hint_processor_definition::HintReference,
+    math_utils::signed_felt,
+    serde::deserialize_program::{ApTracking, HintParams, OffsetValue},
+    stdlib::{
+        collections::{BTreeMap, HashMap, HashSet},
+        fmt,
+        prelude::*,
+    },
+    types::{
+        instruction::{ApUpdate, Op1Addr, Opcode, PcUpdate, Register, Res},
+        program::Program,
+    },
+    vm::decoding::disassembler::{DisassembledCode, Disassembly},
+};
+use num_traits::ToPrimitive;
+
+/// How control can flow from an instruction to one of its successors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    /// Execution continues with the next instruction (including after a call returns).
+    Next,
+    /// An unconditional jump.
+    Jump,
+    /// The taken branch of a conditional jump.
+    Branch,
+    /// A call to a function.
+    Call,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Edge {
+    pub target: usize,
+    pub kind: EdgeKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CfgNode {
+    pub pc: usize,
+    #[serde(serialize_with = "serialize_code")]
+    pub code: DisassembledCode,
+    /// Successors known statically. Jumps & calls to computed addresses have no edges.
+    pub successors: Vec<Edge>,
+}
+
+fn serialize_code<S: Serializer>(
+    code: &DisassembledCode,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(code)
+}
+
+/// The control flow graph of a program, with one node per decoded instruction (or data word).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ControlFlowGraph {
+    /// Nodes sorted by pc.
+    pub nodes: Vec<CfgNode>,
+}
+
+impl ControlFlowGraph {
+    pub fn from_disassembly(disassembly: &Disassembly) -> ControlFlowGraph {
+        let nodes = disassembly
+            .lines
+            .iter()
+            .map(|line| CfgNode {
+                pc: line.pc,
+                code: line.code.clone(),
+                successors: successors(line.pc, &line.code),
+            })
+            .collect();
+        ControlFlowGraph { nodes }
+    }
+
+    /// Returns the node of the instruction starting at `pc`.
+    pub fn node(&self, pc: usize) -> Option<&CfgNode> {
+        self.nodes
+            .binary_search_by_key(&pc, |node| node.pc)
+            .ok()
+            .map(|index| &self.nodes[index])
+    }
+
+    // Pcs of the nodes reachable from the given roots
+    fn reachable(&self, roots: impl Iterator<Item = usize>) -> HashSet<usize> {
+        let mut reachable = HashSet::new();
+        let mut pending: Vec<usize> = roots.collect();
+        while let Some(pc) = pending.pop() {
+            let Some(node) = self.node(pc) else {
+                continue;
+            };
+            if reachable.insert(pc) {
+                pending.extend(node.successors.iter().map(|edge| edge.target));
+            }
+        }
+        reachable
+    }
+}
+
+fn successors(pc: usize, code: &DisassembledCode) -> Vec<Edge> {
+    let DisassembledCode::Instruction { instruction, .. } = code else {
+        return vec![];
+    };
+    let next = Edge {
+        target: pc + code.size(),
+        kind: EdgeKind::Next,
+    };
+    let target = |kind| {
+        code.jump_target(pc)
+            .map(|target| Edge { target, kind })
+            .into_iter()
+    };
+    match (instruction.opcode, instruction.pc_update) {
+        (Opcode::Ret, _) => vec![],
+        (Opcode::Call, _) => target(EdgeKind::Call).chain([next]).collect(),
+        (_, PcUpdate::Jump | PcUpdate::JumpRel) => target(EdgeKind::Jump).collect(),
+        (_, PcUpdate::Jnz) => [next].into_iter().chain(target(EdgeKind::Branch)).collect(),
+        (_, PcUpdate::Regular) => vec![next],
+    }
+}
+
+/// A function of the program, as listed in its identifiers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Function {
+    pub name: String,
+    pub pc: usize,
+    /// The difference between ap when the function returns and ap when it is called, when it is
+    /// the same on every path through the function.
+    pub ap_change: Option<isize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AnalysisIssue {
+    /// Instructions in `start..end` can't be reached from `main` nor from any function.
+    UnreachableCode { start: usize, end: usize },
+    /// The instruction at `pc` jumps (or calls) into the immediate of the instruction at
+    /// `target - 1`.
+    JumpIntoInstruction { pc: usize, target: usize },
+    /// The instruction at `pc` jumps (or calls) outside of the program data.
+    JumpOutOfProgram { pc: usize, target: usize },
+    /// A hint is attached to a pc that is not the start of an instruction.
+    HintAtNonInstruction { pc: usize },
+    /// A hint at `pc` uses an ap-based reference whose ap tracking doesn't match the one of the
+    /// hint, so its address can't be computed.
+    OutdatedReference {
+        pc: usize,
+        name: String,
+        reference_ap_tracking: Option<ApTracking>,
+        hint_ap_tracking: ApTracking,
+    },
+    /// The ap change of a function is not statically known, because of the instruction at `pc`.
+    UnknownApChange { function: String, pc: usize },
+}
+
+impl fmt::Display for AnalysisIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnalysisIssue::UnreachableCode { start, end } => {
+                write!(f, "pc {start}: unreachable code up to pc {end}")
+            }
+            AnalysisIssue::JumpIntoInstruction { pc, target } => write!(
+                f,
+                "pc {pc}: jump to pc {target}, in the middle of the instruction at pc {}",
+                target - 1
+            ),
+            AnalysisIssue::JumpOutOfProgram { pc, target } => {
+                write!(f, "pc {pc}: jump to pc {target}, outside of the program")
+            }
+            AnalysisIssue::HintAtNonInstruction { pc } => {
+                write!(
+                    f,
+                    "pc {pc}: hint attached to a pc that doesn't start an instruction"
+                )
+            }
+            AnalysisIssue::OutdatedReference {
+                pc,
+                name,
+                reference_ap_tracking,
+                hint_ap_tracking,
+            } => {
+                write!(
+                    f,
+                    "pc {pc}: reference {name} has an out-of-date ap tracking ("
+                )?;
+                match reference_ap_tracking {
+                    Some(ApTracking { group, offset }) => {
+                        write!(f, "group {group}, offset {offset}")?
+                    }
+                    None => write!(f, "none")?,
+                }
+                write!(
+                    f,
+                    " in the reference, group {}, offset {} in the hint)",
+                    hint_ap_tracking.group, hint_ap_tracking.offset
+                )
+            }
+            AnalysisIssue::UnknownApChange { function, pc } => write!(
+                f,
+                "pc {pc}: the ap change of {function} is not statically known"
+            ),
+        }
+    }
+}
+
+/// The result of statically analyzing the data of a program.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ProgramAnalysis {
+    pub cfg: ControlFlowGraph,
+    pub functions: Vec<Function>,
+    pub issues: Vec<AnalysisIssue>,
+}
+
+impl ProgramAnalysis {
+    /// Builds the control flow graph of a program & reports its unreachable code, jumps into
+    /// immediates or outside of the program, misplaced hints, out-of-date references and
+    /// functions with an unknown ap change. As in [`Disassembly::from_program`], programs
+    /// without function identifiers only get their `main` analyzed as a function.
+    pub fn from_program(program: &Program) -> ProgramAnalysis {
+        let cfg = ControlFlowGraph::from_disassembly(&Disassembly::from_program(program));
+        let data_len = program.shared_program_data.data.len();
+
+        let mut entrypoints: BTreeMap<usize, String> = BTreeMap::new();
+        for (name, identifier) in program.iter_identifiers() {
+            if let (Some("function"), Some(pc)) = (identifier.type_.as_deref(), identifier.pc) {
+                entrypoints
+                    .entry(pc)
+                    .and_modify(|entrypoint| {
+                        if name < entrypoint.as_str() {
+                            *entrypoint = name.to_string()
+                        }
+                    })
+                    .or_insert_with(|| name.to_string());
+            }
+        }
+        if let (true, Some(main)) = (entrypoints.is_empty(), program.shared_program_data.main) {
+            entrypoints.insert(main, "main".to_string());
+        }
+
+        let mut issues = unreachable_code(&cfg, program, &entrypoints);
+        issues.extend(invalid_jumps(&cfg, data_len));
+        let hints: BTreeMap<usize, Vec<HintParams>> =
+            (&program.shared_program_data.hints_collection).into();
+        issues.extend(
+            hints
+                .keys()
+                .filter(|pc| {
+                    !matches!(
+                        cfg.node(**pc),
+                        Some(CfgNode {
+                            code: DisassembledCode::Instruction { .. },
+                            ..
+                        })
+                    )
+                })
+                .map(|pc| AnalysisIssue::HintAtNonInstruction { pc: *pc }),
+        );
+        issues.extend(outdated_references(
+            &hints,
+            &program.shared_program_data.reference_manager,
+        ));
+
+        let mut ap_changes = HashMap::new();
+        let functions: Vec<Function> = entrypoints
+            .into_iter()
+            .map(|(pc, name)| {
+                let ap_change = function_ap_change(&cfg, pc, &mut ap_changes);
+                if let Err(unknown_pc) = ap_change {
+                    issues.push(AnalysisIssue::UnknownApChange {
+                        function: name.clone(),
+                        pc: unknown_pc,
+                    });
+                }
+                Function {
+                    name,
+                    pc,
+                    ap_change: ap_change.ok(),
+                }
+            })
+            .collect();
+
+        ProgramAnalysis {
+            cfg,
+            functions,
+            issues,
+        }
+    }
+
+    pub fn serialize_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&self)
+    }
+}
+
+impl fmt::Display for ProgramAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Functions:")?;
+        for function in self.functions.iter() {
+            write!(f, "  {} (pc {}): ", function.name, function.pc)?;
+            match function.ap_change {
+                Some(ap_change) => writeln!(f, "ap change {ap_change}")?,
+                None => writeln!(f, "unknown ap change")?,
+            }
+        }
+        if self.issues.is_empty() {
+            return writeln!(f, "No issues found");
+        }
+        writeln!(f, "Issues:")?;
+        for issue in self.issues.iter() {
+            writeln!(f, "  {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+// Groups the nodes that can't be reached from the functions, main & start of the program into
+// contiguous ranges
+fn unreachable_code(
+    cfg: &ControlFlowGraph,
+    program: &Program,
+    entrypoints: &BTreeMap<usize, String>,
+) -> Vec<AnalysisIssue> {
+    let mut roots: Vec<usize> = entrypoints.keys().copied().collect();
+    roots.extend(program.shared_program_data.main);
+    roots.extend(program.shared_program_data.start);
+    if roots.is_empty() {
+        roots.push(0);
+    }
+    let reachable = cfg.reachable(roots.into_iter());
+
+    let mut issues = Vec::new();
+    let mut unreachable_start = None;
+    for node in cfg.nodes.iter() {
+        match (reachable.contains(&node.pc), unreachable_start) {
+            (false, None) => unreachable_start = Some(node.pc),
+            (true, Some(start)) => {
+                issues.push(AnalysisIssue::UnreachableCode {
+                    start,
+                    end: node.pc,
+                });
+                unreachable_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let (Some(start), Some(last_node)) = (unreachable_start, cfg.nodes.last()) {
+        issues.push(AnalysisIssue::UnreachableCode {
+            start,
+            end: last_node.pc + last_node.code.size(),
+        });
+    }
+    issues
+}
+
+fn invalid_jumps(cfg: &ControlFlowGraph, data_len: usize) -> Vec<AnalysisIssue> {
+    let mut issues = Vec::new();
+    for node in cfg.nodes.iter() {
+        for edge in node.successors.iter() {
+            if edge.kind == EdgeKind::Next || cfg.node(edge.target).is_some() {
+                continue;
+            }
+            issues.push(if edge.target < data_len {
+                AnalysisIssue::JumpIntoInstruction {
+                    pc: node.pc,
+                    target: edge.target,
+                }
+            } else {
+                AnalysisIssue::JumpOutOfProgram {
+                    pc: node.pc,
+                    target: edge.target,
+                }
+            });
+        }
+    }
+    issues
+}
+
+// Ap-based references can only be computed by hints of the same ap tracking group, placed after
+// the reference was defined
+fn outdated_references(
+    hints: &BTreeMap<usize, Vec<HintParams>>,
+    references: &[HintReference],
+) -> Vec<AnalysisIssue> {
+    let uses_ap = |offset: &OffsetValue| matches!(offset, OffsetValue::Reference(Register::AP, ..));
+    let mut issues = Vec::new();
+    for (pc, hint) in hints
+        .iter()
+        .flat_map(|(pc, hints)| hints.iter().map(move |hint| (*pc, hint)))
+    {
+        let hint_ap_tracking = &hint.flow_tracking_data.ap_tracking;
+        let mut reference_ids: Vec<_> = hint.flow_tracking_data.reference_ids.iter().collect();
+        reference_ids.sort();
+        for (name, id) in reference_ids {
+            let Some(reference) = references.get(*id) else {
+                continue;
+            };
+            if !uses_ap(&reference.offset1) && !uses_ap(&reference.offset2) {
+                continue;
+            }
+            let up_to_date = reference
+                .ap_tracking_data
+                .as_ref()
+                .is_some_and(|ap_tracking| {
+                    ap_tracking.group == hint_ap_tracking.group
+                        && ap_tracking.offset <= hint_ap_tracking.offset
+                });
+            if !up_to_date {
+                issues.push(AnalysisIssue::OutdatedReference {
+                    pc,
+                    name: name.clone(),
+                    reference_ap_tracking: reference.ap_tracking_data.clone(),
+                    hint_ap_tracking: hint_ap_tracking.clone(),
+                });
+            }
+        }
+    }
+    issues
+}
+
+enum ApChangeState {
+    InProgress,
+    Done(Result<isize, usize>),
+}
+
+// Returns the ap change of the function at `pc`, or the pc of the instruction making it unknown.
+// Recursive functions have an unknown ap change
+fn function_ap_change(
+    cfg: &ControlFlowGraph,
+    pc: usize,
+    states: &mut HashMap<usize, ApChangeState>,
+) -> Result<isize, usize> {
+    match states.get(&pc) {
+        Some(ApChangeState::InProgress) => return Err(pc),
+        Some(ApChangeState::Done(ap_change)) => return *ap_change,
+        None => {}
+    }
+    states.insert(pc, ApChangeState::InProgress);
+    let ap_change = compute_function_ap_change(cfg, pc, states);
+    states.insert(pc, ApChangeState::Done(ap_change));
+    ap_change
+}
+
+fn compute_function_ap_change(
+    cfg: &ControlFlowGraph,
+    entry: usize,
+    states: &mut HashMap<usize, ApChangeState>,
+) -> Result<isize, usize> {
+    // The ap offset (relative to the entry) before executing each instruction
+    let mut ap_offsets: HashMap<usize, isize> = [(entry, 0)].into_iter().collect();
+    let mut pending = vec![entry];
+    let mut return_ap_offset = None;
+    while let Some(pc) = pending.pop() {
+        let ap_offset = ap_offsets[&pc];
+        let node = cfg.node(pc).ok_or(pc)?;
+        let DisassembledCode::Instruction { instruction, imm } = &node.code else {
+            return Err(pc);
+        };
+        let mut next_ap_offset = match (instruction.ap_update, instruction.res, imm) {
+            (ApUpdate::Regular, ..) => ap_offset,
+            (ApUpdate::Add1, ..) => ap_offset + 1,
+            (ApUpdate::Add2, ..) => ap_offset + 2,
+            (ApUpdate::Add, Res::Op1, Some(imm)) if instruction.op1_addr == Op1Addr::Imm => {
+                ap_offset + signed_felt(*imm).to_isize().ok_or(pc)?
+            }
+            (ApUpdate::Add, ..) => return Err(pc),
+        };
+        if instruction.opcode == Opcode::Ret {
+            if return_ap_offset.is_some_and(|return_ap_offset| return_ap_offset != ap_offset) {
+                return Err(pc);
+            }
+            return_ap_offset = Some(ap_offset);
+            continue;
+        }
+        // Jumps & calls to computed addresses
+        let target = node.code.jump_target(pc);
+        if instruction.pc_update != PcUpdate::Regular && target.is_none() {
+            return Err(pc);
+        }
+        if let (Opcode::Call, Some(callee)) = (instruction.opcode, target) {
+            next_ap_offset += function_ap_change(cfg, callee, states).map_err(|_| pc)?;
+        }
+        for edge in node
+            .successors
+            .iter()
+            .filter(|edge| edge.kind != EdgeKind::Call)
+        {
+            match ap_offsets.get(&edge.target) {
+                Some(ap_offset) if *ap_offset != next_ap_offset => return Err(edge.target),
+                Some(_) => {}
+                None => {
+                    ap_offsets.insert(edge.target, next_ap_offset);
+                    pending.push(edge.target);
+                }
+            }
+        }
+    }
+    return_ap_offset.ok_or(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serde::deserialize_program::{FlowTrackingData, Identifier};
+    use crate::stdlib::sync::Arc;
+    use crate::utils::test_utils::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn function(pc: usize) -> Identifier {
+        Identifier {
+            pc: Some(pc),
+            type_: Some("function".to_string()),
+            value: None,
+            full_name: None,
+            members: None,
+            cairo_type: None,
+        }
+    }
+
+    fn hint(group: usize, reference_ids: &[(&str, usize)]) -> HintParams {
+        HintParams {
+            code: "x = 1".to_string(),
+            accessible_scopes: vec![],
+            flow_tracking_data: FlowTrackingData {
+                ap_tracking: ApTracking { group, offset: 0 },
+                reference_ids: reference_ids
+                    .iter()
+                    .map(|(name, id)| (name.to_string(), *id))
+                    .collect(),
+            },
+        }
+    }
+
+    // The program with issues used by the tests below:
+    //  0: jmp rel 4 if [fp + -3] != 0
+    //  2: jmp abs 1
+    //  4: ap += 2
+    //  6: ret
+    //  7: ret
+    //  8: dw 1:0
+    fn program_with_issues() -> Program {
+        let mut program = program!(
+            data = vec_data!(
+                (0x20780017fff7ffd_u64),
+                (4),
+                (0x8780017fff7fff_u64),
+                (1),
+                (0x40780017fff7fff_u64),
+                (2),
+                (0x208b7fff7fff7ffe_u64),
+                (0x208b7fff7fff7ffe_u64),
+                ((1, 0))
+            ),
+            main = Some(0),
+            hints = BTreeMap::from([
+                (
+                    0,
+                    vec![hint(2, &[("__main__.main.x", 0), ("__main__.main.y", 1)])],
+                ),
+                (1, vec![hint(2, &[])]),
+            ]),
+        );
+        let mut ap_reference = HintReference::new_simple(-1);
+        ap_reference.offset1 = OffsetValue::Reference(Register::AP, -1, false);
+        ap_reference.ap_tracking_data = Some(ApTracking {
+            group: 1,
+            offset: 0,
+        });
+        Arc::get_mut(&mut program.shared_program_data)
+            .unwrap()
+            .reference_manager = vec![ap_reference, HintReference::new_simple(-3)];
+        program
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn analyze_program_without_issues() {
+        //  0: call rel 3
+        //  2: ret
+        //  3: [ap] = 10, ap++
+        //  5: ret
+        let program = program!(
+            data = vec_data!(
+                (0x1104800180018000_u64),
+                (3),
+                (0x208b7fff7fff7ffe_u64),
+                (0x480680017fff8000_u64),
+                (10),
+                (0x208b7fff7fff7ffe_u64)
+            ),
+            main = Some(0),
+            identifiers = HashMap::from([
+                ("__main__.main".to_string(), function(0)),
+                ("__main__.foo".to_string(), function(3)),
+            ]),
+        );
+        let analysis = ProgramAnalysis::from_program(&program);
+        assert_eq!(
+            analysis.cfg.node(0).unwrap().successors,
+            vec![
+                Edge {
+                    target: 3,
+                    kind: EdgeKind::Call
+                },
+                Edge {
+                    target: 2,
+                    kind: EdgeKind::Next
+                }
+            ]
+        );
+        assert_eq!(analysis.cfg.node(2).unwrap().successors, vec![]);
+        assert_eq!(analysis.cfg.node(1), None);
+        assert_eq!(
+            analysis.functions,
+            vec![
+                Function {
+                    name: "__main__.main".to_string(),
+                    pc: 0,
+                    ap_change: Some(3),
+                },
+                Function {
+                    name: "__main__.foo".to_string(),
+                    pc: 3,
+                    ap_change: Some(1),
+                }
+            ]
+        );
+        assert_eq!(analysis.issues, vec![]);
+        assert_eq!(
+            analysis.to_string(),
+            "Functions:\n  __main__.main (pc 0): ap change 3\n  __main__.foo (pc 3): ap change 1\nNo issues found\n"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn analyze_program_with_issues() {
+        let analysis = ProgramAnalysis::from_program(&program_with_issues());
+        assert_eq!(
+            analysis.cfg.node(0).unwrap().successors,
+            vec![
+                Edge {
+                    target: 2,
+                    kind: EdgeKind::Next
+                },
+                Edge {
+                    target: 4,
+                    kind: EdgeKind::Branch
+                }
+            ]
+        );
+        assert_eq!(
+            analysis.issues,
+            vec![
+                AnalysisIssue::UnreachableCode { start: 7, end: 9 },
+                AnalysisIssue::JumpIntoInstruction { pc: 2, target: 1 },
+                AnalysisIssue::HintAtNonInstruction { pc: 1 },
+                AnalysisIssue::OutdatedReference {
+                    pc: 0,
+                    name: "__main__.main.x".to_string(),
+                    reference_ap_tracking: Some(ApTracking {
+                        group: 1,
+                        offset: 0
+                    }),
+                    hint_ap_tracking: ApTracking {
+                        group: 2,
+                        offset: 0
+                    },
+                },
+                AnalysisIssue::UnknownApChange {
+                    function: "main".to_string(),
+                    pc: 1
+                },
+            ]
+        );
+        assert_eq!(
+            analysis.to_string(),
+            "Functions:\n  main (pc 0): unknown ap change\nIssues:\n  pc 7: unreachable code up to pc 9\n  pc 2: jump to pc 1, in the middle of the instruction at pc 0\n  pc 1: hint attached to a pc that doesn't start an instruction\n  pc 0: reference __main__.main.x has an out-of-date ap tracking (group 1, offset 0 in the reference, group 2, offset 0 in the hint)\n  pc 1: the ap change of main is not statically known\n"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn analyze_unknown_ap_changes() {
+        //  0: [ap] = 1, ap++
+        //  2: jmp rel -2 if [ap + -1] != 0
+        //  4: ret
+        //  5: call rel 0
+        //  7: ret
+        //  8: jmp abs 100
+        let program = program!(
+            data = vec_data!(
+                (0x480680017fff8000_u64),
+                (1),
+                (0x20680017fff7fff_u64),
+                ({ -2 }),
+                (0x208b7fff7fff7ffe_u64),
+                (0x1104800180018000_u64),
+                (0),
+                (0x208b7fff7fff7ffe_u64),
+                (0x8780017fff7fff_u64),
+                (100)
+            ),
+            main = Some(0),
+            identifiers = HashMap::from([
+                ("__main__.loop".to_string(), function(0)),
+                ("__main__.recursive".to_string(), function(5)),
+                ("__main__.out_of_program".to_string(), function(8)),
+            ]),
+        );
+        let analysis = ProgramAnalysis::from_program(&program);
+        assert_eq!(
+            analysis
+                .functions
+                .iter()
+                .map(|function| function.ap_change)
+                .collect::<Vec<_>>(),
+            vec![None, None, None]
+        );
+        assert_eq!(
+            analysis.issues,
+            vec![
+                AnalysisIssue::JumpOutOfProgram { pc: 8, target: 100 },
+                AnalysisIssue::UnknownApChange {
+                    function: "__main__.loop".to_string(),
+                    pc: 0
+                },
+                AnalysisIssue::UnknownApChange {
+                    function: "__main__.recursive".to_string(),
+                    pc: 5
+                },
+                AnalysisIssue::UnknownApChange {
+                    function: "__main__.out_of_program".to_string(),
+                    pc: 100
+                },
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn serialize_analysis_json() {
+        let analysis = ProgramAnalysis::from_program(&program_with_issues());
+        let json: serde_json::Value =
+            serde_json::from_str(&analysis.serialize_json().unwrap()).unwrap();
+        assert_eq!(
+            json["cfg"]["nodes"][0],
+            serde_json::json!({
+                "pc": 0,
+                "code": "jmp rel 4 if [fp + -3] != 0",
+                "successors": [
+                    {"target": 2, "kind": "next"},
+                    {"target": 4, "kind": "branch"}
+                ]
+            })
+        );
+        assert_eq!(
+            json["functions"],
+            serde_json::json!([{"name": "main", "pc": 0, "ap_change": null}])
+        );
+        assert_eq!(
+            json["issues"][1],
+            serde_json::json!({"kind": "jump_into_instruction", "pc": 2, "target": 1})
+        );
+    }
+}
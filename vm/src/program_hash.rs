@@ -1,4 +1,4 @@
-use starknet_crypto::{pedersen_hash, FieldElement};
+use starknet_crypto::{pedersen_hash, poseidon_hash_many, FieldElement};
 
 use crate::Felt252;
 
@@ -34,6 +34,17 @@ pub enum ProgramHashError {
     Felt252ToFieldElementConversionFailed,
 }
 
+/// Hash function used to compute the hash of a program.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(all(feature = "clap", feature = "std"), derive(clap::ValueEnum))]
+pub enum ProgramHashFunction {
+    /// Pedersen hash chain, prefixed with the length of the data.
+    #[default]
+    Pedersen,
+    /// Poseidon hash over the data, as used by newer bootloaders.
+    Poseidon,
+}
+
 /// Computes a hash chain over the data, in the following order:
 ///     h(data[0], h(data[1], h(..., h(data[n-2], data[n-1])))).
 /// [cairo_lang reference](https://github.com/starkware-libs/cairo-lang/blob/efa9648f57568aad8f8a13fbf027d2de7c63c2c0/src/starkware/cairo/common/hash_chain.py#L6)
@@ -89,6 +100,20 @@ fn maybe_relocatable_to_field_element(
 pub fn compute_program_hash_chain(
     program: &StrippedProgram,
     bootloader_version: usize,
+) -> Result<FieldElement, ProgramHashError> {
+    compute_program_hash(program, bootloader_version, ProgramHashFunction::Pedersen)
+}
+
+/// Computes the hash of a program with the given hash function.
+///
+/// The hashed data is the program header (bootloader version, main & number of builtins),
+/// followed by the builtin names and the program data. The Pedersen hash chain is prefixed
+/// with the length of this data, while the Poseidon hash is computed over the data alone.
+/// [(cairo_lang reference)](https://github.com/starkware-libs/cairo-lang/blob/v0.13.1/src/starkware/cairo/bootloaders/hash_program.py#L11)
+pub fn compute_program_hash(
+    program: &StrippedProgram,
+    bootloader_version: usize,
+    hash_function: ProgramHashFunction,
 ) -> Result<FieldElement, ProgramHashError> {
     let program_main = program.main;
     let program_main = FieldElement::from(program_main);
@@ -115,17 +140,29 @@ pub fn compute_program_hash_chain(
     let program_data = program_data?;
 
     let data_chain_len = program_header.len() + builtin_list.len() + program_data.len();
-    let data_chain_len_vec = vec![FieldElement::from(data_chain_len)];
-
-    // Prepare a chain of iterators to feed to the hash function
-    let data_chain = [
-        &data_chain_len_vec,
-        &program_header,
-        &builtin_list,
-        &program_data,
-    ];
 
-    let hash = compute_hash_chain(data_chain.iter().flat_map(|&v| v.iter()), pedersen_hash)?;
+    let hash = match hash_function {
+        ProgramHashFunction::Pedersen => {
+            let data_chain_len_vec = vec![FieldElement::from(data_chain_len)];
+
+            // Prepare a chain of iterators to feed to the hash function
+            let data_chain = [
+                &data_chain_len_vec,
+                &program_header,
+                &builtin_list,
+                &program_data,
+            ];
+
+            compute_hash_chain(data_chain.iter().flat_map(|&v| v.iter()), pedersen_hash)?
+        }
+        ProgramHashFunction::Poseidon => {
+            let mut data_chain = Vec::with_capacity(data_chain_len);
+            data_chain.extend(program_header);
+            data_chain.extend(builtin_list);
+            data_chain.extend(program_data);
+            poseidon_hash_many(&data_chain)
+        }
+    };
     Ok(hash)
 }
 
@@ -157,7 +194,8 @@ mod tests {
 
     #[cfg(feature = "std")]
     #[rstest]
-    // Expected hashes generated with `cairo-hash-program`
+    // Expected hashes generated with cairo-lang 0.13.1 (the version pinned in requirements.txt):
+    // `cairo-hash-program --program cairo_programs/<program>.json`
     #[case::fibonacci(
         "../cairo_programs/fibonacci.json",
         "0x43b17e9592f33142246af4c06cd2b574b460dd1f718d76b51341175a62b220f"
@@ -187,4 +225,127 @@ mod tests {
 
         assert_eq!(program_hash_hex, expected_program_hash);
     }
+
+    fn stripped_program() -> StrippedProgram {
+        StrippedProgram {
+            data: vec![MaybeRelocatable::from(5), MaybeRelocatable::from(7)],
+            builtins: vec![BuiltinName::output],
+            main: 1,
+            prime: (),
+        }
+    }
+
+    /*Program used:
+    func main() {
+        [ap] = 7, ap++;
+        [ap] = [ap - 1] * 3, ap++;
+        jmp not_zero if [ap - 1] != 0;
+        [ap] = 1, ap++;
+        ret;
+        not_zero:
+        [ap - 1] = 22;
+        ret;
+    }
+    */
+    fn stripped_program_with_builtins() -> StrippedProgram {
+        StrippedProgram {
+            data: [
+                0x480680017fff8000_i64,
+                7,
+                0x484480017fff8000_i64,
+                3,
+                0x020680017fff7fff_i64,
+                6,
+                0x480680017fff8000_i64,
+                1,
+                0x208b7fff7fff7ffe_i64,
+                0,
+                0x400680017fff7fff_i64,
+                22,
+                0x208b7fff7fff7ffe_i64,
+            ]
+            .into_iter()
+            .map(|value| MaybeRelocatable::from(Felt252::from(value)))
+            .collect(),
+            builtins: vec![
+                BuiltinName::output,
+                BuiltinName::pedersen,
+                BuiltinName::range_check,
+                BuiltinName::bitwise,
+            ],
+            main: 0,
+            prime: (),
+        }
+    }
+
+    // Expected hashes generated with cairo-lang 0.13.1 (the version pinned in requirements.txt).
+    // These programs aren't compiled files, so `cairo-hash-program` can't load them; the hashes come
+    // from the function it calls, e.g. for the Pedersen hash of `stripped_program` (the Poseidon
+    // hashes pass `use_poseidon=True`):
+    // `python3 -c 'from starkware.cairo.bootloaders.hash_program import compute_program_hash_chain;
+    //  from starkware.cairo.lang.cairo_constants import DEFAULT_PRIME;
+    //  from starkware.cairo.lang.compiler.program import StrippedProgram;
+    //  print(hex(compute_program_hash_chain(StrippedProgram(prime=DEFAULT_PRIME, data=[5, 7],
+    //  builtins=["output"], main=1), use_poseidon=False)))'`
+    #[test]
+    fn test_compute_program_hash_pedersen() {
+        let program = stripped_program();
+        let expected_hash = FieldElement::from_hex_be(
+            "0xdb9bdfa4c9f5feb5eefdf4a676e50b2df603fc0a21b60b162648371ba21f2",
+        )
+        .unwrap();
+
+        assert_eq!(
+            compute_program_hash(&program, 0, ProgramHashFunction::Pedersen).unwrap(),
+            expected_hash
+        );
+        assert_eq!(
+            compute_program_hash_chain(&program, 0).unwrap(),
+            expected_hash
+        );
+        assert_eq!(
+            compute_program_hash_chain(&stripped_program_with_builtins(), 0).unwrap(),
+            FieldElement::from_hex_be(
+                "0xe397372614293a7de6ba509996fad7685d460b32971d8722b044078281f592"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_compute_program_hash_poseidon() {
+        assert_eq!(
+            compute_program_hash(&stripped_program(), 0, ProgramHashFunction::Poseidon).unwrap(),
+            FieldElement::from_hex_be(
+                "0x6af2dbd835b6a1c6a7d2d8844be930708fb30ccee21fb18a769baf66782998b"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            compute_program_hash(
+                &stripped_program_with_builtins(),
+                0,
+                ProgramHashFunction::Poseidon
+            )
+            .unwrap(),
+            FieldElement::from_hex_be(
+                "0x4ab181874312f4eccf0186c73cbc08618c51820111f9c284657705abb568c49"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_compute_program_hash_relocatable_data() {
+        let program = StrippedProgram {
+            data: vec![MaybeRelocatable::from((0, 1))],
+            builtins: vec![],
+            main: 0,
+            prime: (),
+        };
+        assert!(matches!(
+            compute_program_hash(&program, 0, ProgramHashFunction::Poseidon),
+            Err(ProgramHashError::InvalidProgramData)
+        ));
+    }
 }
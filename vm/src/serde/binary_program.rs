@@ -0,0 +1,470 @@
+//! Compact binary encoding of a [`Program`] & the data its hints are compiled into.
+//!
+//! Programs are encoded with bincode, after a header made of [`BINARY_PROGRAM_MAGIC`] and the
+//! little endian [`BINARY_PROGRAM_VERSION`]. The hint references are stored already resolved, so
+//! decoding a program doesn't require parsing its json nor compiling its hints again.
+//! Felts are stored as 32-byte big endian words.
+
+use crate::{
+    hint_processor::{
+        builtin_hint_processor::builtin_hint_processor_definition::HintProcessorData,
+        hint_processor_definition::HintReference,
+    },
+    serde::deserialize_program::{
+        ApTracking, Attribute, FlowTrackingData, HintParams, Identifier, InstructionLocation,
+        Member, OffsetValue,
+    },
+    stdlib::{
+        collections::{BTreeMap, HashMap},
+        prelude::*,
+        sync::Arc,
+    },
+    types::{
+        builtin_name::BuiltinName,
+        errors::program_errors::ProgramError,
+        instruction::Register,
+        program::{HintsCollection, Program, SharedProgramData},
+        relocatable::{MaybeRelocatable, Relocatable},
+    },
+    Felt252,
+};
+use serde::{Deserialize, Serialize};
+
+/// Bytes every binary program starts with.
+pub const BINARY_PROGRAM_MAGIC: &[u8; 8] = b"CAIROVMP";
+/// Version of the binary program format, bumped on every incompatible change.
+pub const BINARY_PROGRAM_VERSION: u32 = 1;
+
+const HEADER_LEN: usize = BINARY_PROGRAM_MAGIC.len() + 4;
+
+#[derive(Serialize, Deserialize)]
+struct BinaryFelt([u8; 32]);
+
+impl From<&Felt252> for BinaryFelt {
+    fn from(felt: &Felt252) -> Self {
+        BinaryFelt(felt.to_bytes_be())
+    }
+}
+
+impl From<BinaryFelt> for Felt252 {
+    fn from(felt: BinaryFelt) -> Self {
+        Felt252::from_bytes_be(&felt.0)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum BinaryMaybeRelocatable {
+    RelocatableValue(isize, usize),
+    Int(BinaryFelt),
+}
+
+impl From<&MaybeRelocatable> for BinaryMaybeRelocatable {
+    fn from(value: &MaybeRelocatable) -> Self {
+        match value {
+            MaybeRelocatable::RelocatableValue(relocatable) => {
+                BinaryMaybeRelocatable::RelocatableValue(
+                    relocatable.segment_index,
+                    relocatable.offset,
+                )
+            }
+            MaybeRelocatable::Int(felt) => BinaryMaybeRelocatable::Int(felt.into()),
+        }
+    }
+}
+
+impl From<BinaryMaybeRelocatable> for MaybeRelocatable {
+    fn from(value: BinaryMaybeRelocatable) -> Self {
+        match value {
+            BinaryMaybeRelocatable::RelocatableValue(segment_index, offset) => {
+                MaybeRelocatable::RelocatableValue(Relocatable::from((segment_index, offset)))
+            }
+            BinaryMaybeRelocatable::Int(felt) => MaybeRelocatable::Int(felt.into()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryFlowTrackingData {
+    ap_tracking: ApTracking,
+    reference_ids: Vec<(String, usize)>,
+}
+
+impl From<&FlowTrackingData> for BinaryFlowTrackingData {
+    fn from(flow_tracking_data: &FlowTrackingData) -> Self {
+        BinaryFlowTrackingData {
+            ap_tracking: flow_tracking_data.ap_tracking.clone(),
+            reference_ids: flow_tracking_data
+                .reference_ids
+                .iter()
+                .map(|(name, id)| (name.clone(), *id))
+                .collect(),
+        }
+    }
+}
+
+impl From<BinaryFlowTrackingData> for FlowTrackingData {
+    fn from(flow_tracking_data: BinaryFlowTrackingData) -> Self {
+        FlowTrackingData {
+            ap_tracking: flow_tracking_data.ap_tracking,
+            reference_ids: flow_tracking_data.reference_ids.into_iter().collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryHintParams {
+    code: String,
+    accessible_scopes: Vec<String>,
+    flow_tracking_data: BinaryFlowTrackingData,
+}
+
+impl From<&HintParams> for BinaryHintParams {
+    fn from(hint: &HintParams) -> Self {
+        BinaryHintParams {
+            code: hint.code.clone(),
+            accessible_scopes: hint.accessible_scopes.clone(),
+            flow_tracking_data: (&hint.flow_tracking_data).into(),
+        }
+    }
+}
+
+impl From<BinaryHintParams> for HintParams {
+    fn from(hint: BinaryHintParams) -> Self {
+        HintParams {
+            code: hint.code,
+            accessible_scopes: hint.accessible_scopes,
+            flow_tracking_data: hint.flow_tracking_data.into(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryAttribute {
+    name: String,
+    start_pc: usize,
+    end_pc: usize,
+    value: String,
+    flow_tracking_data: Option<BinaryFlowTrackingData>,
+}
+
+impl From<&Attribute> for BinaryAttribute {
+    fn from(attribute: &Attribute) -> Self {
+        BinaryAttribute {
+            name: attribute.name.clone(),
+            start_pc: attribute.start_pc,
+            end_pc: attribute.end_pc,
+            value: attribute.value.clone(),
+            flow_tracking_data: attribute.flow_tracking_data.as_ref().map(Into::into),
+        }
+    }
+}
+
+impl From<BinaryAttribute> for Attribute {
+    fn from(attribute: BinaryAttribute) -> Self {
+        Attribute {
+            name: attribute.name,
+            start_pc: attribute.start_pc,
+            end_pc: attribute.end_pc,
+            value: attribute.value,
+            flow_tracking_data: attribute.flow_tracking_data.map(Into::into),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryIdentifier {
+    pc: Option<usize>,
+    type_: Option<String>,
+    value: Option<BinaryFelt>,
+    full_name: Option<String>,
+    members: Option<Vec<(String, Member)>>,
+    cairo_type: Option<String>,
+}
+
+impl From<&Identifier> for BinaryIdentifier {
+    fn from(identifier: &Identifier) -> Self {
+        BinaryIdentifier {
+            pc: identifier.pc,
+            type_: identifier.type_.clone(),
+            value: identifier.value.as_ref().map(Into::into),
+            full_name: identifier.full_name.clone(),
+            members: identifier.members.as_ref().map(|members| {
+                members
+                    .iter()
+                    .map(|(name, member)| (name.clone(), member.clone()))
+                    .collect()
+            }),
+            cairo_type: identifier.cairo_type.clone(),
+        }
+    }
+}
+
+impl From<BinaryIdentifier> for Identifier {
+    fn from(identifier: BinaryIdentifier) -> Self {
+        Identifier {
+            pc: identifier.pc,
+            type_: identifier.type_,
+            value: identifier.value.map(Into::into),
+            full_name: identifier.full_name,
+            members: identifier
+                .members
+                .map(|members| members.into_iter().collect()),
+            cairo_type: identifier.cairo_type,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum BinaryOffsetValue {
+    Immediate(BinaryFelt),
+    Value(i32),
+    Reference(Register, i32, bool),
+}
+
+impl From<&OffsetValue> for BinaryOffsetValue {
+    fn from(offset: &OffsetValue) -> Self {
+        match offset {
+            OffsetValue::Immediate(felt) => BinaryOffsetValue::Immediate(felt.into()),
+            OffsetValue::Value(value) => BinaryOffsetValue::Value(*value),
+            OffsetValue::Reference(register, offset, dereference) => {
+                BinaryOffsetValue::Reference(*register, *offset, *dereference)
+            }
+        }
+    }
+}
+
+impl From<BinaryOffsetValue> for OffsetValue {
+    fn from(offset: BinaryOffsetValue) -> Self {
+        match offset {
+            BinaryOffsetValue::Immediate(felt) => OffsetValue::Immediate(felt.into()),
+            BinaryOffsetValue::Value(value) => OffsetValue::Value(value),
+            BinaryOffsetValue::Reference(register, offset, dereference) => {
+                OffsetValue::Reference(register, offset, dereference)
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryHintReference {
+    offset1: BinaryOffsetValue,
+    offset2: BinaryOffsetValue,
+    inner_dereference: bool,
+    outer_dereference: bool,
+    ap_tracking_data: Option<ApTracking>,
+    cairo_type: Option<String>,
+}
+
+impl From<&HintReference> for BinaryHintReference {
+    fn from(reference: &HintReference) -> Self {
+        BinaryHintReference {
+            offset1: (&reference.offset1).into(),
+            offset2: (&reference.offset2).into(),
+            inner_dereference: reference.inner_dereference,
+            outer_dereference: reference.outer_dereference,
+            ap_tracking_data: reference.ap_tracking_data.clone(),
+            cairo_type: reference.cairo_type.clone(),
+        }
+    }
+}
+
+impl From<BinaryHintReference> for HintReference {
+    fn from(reference: BinaryHintReference) -> Self {
+        HintReference {
+            offset1: reference.offset1.into(),
+            offset2: reference.offset2.into(),
+            inner_dereference: reference.inner_dereference,
+            outer_dereference: reference.outer_dereference,
+            ap_tracking_data: reference.ap_tracking_data,
+            cairo_type: reference.cairo_type,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryHintProcessorData {
+    code: String,
+    ap_tracking: ApTracking,
+    ids_data: Vec<(String, BinaryHintReference)>,
+}
+
+impl From<&HintProcessorData> for BinaryHintProcessorData {
+    fn from(hint_data: &HintProcessorData) -> Self {
+        BinaryHintProcessorData {
+            code: hint_data.code.clone(),
+            ap_tracking: hint_data.ap_tracking.clone(),
+            ids_data: hint_data
+                .ids_data
+                .iter()
+                .map(|(name, reference)| (name.clone(), reference.into()))
+                .collect(),
+        }
+    }
+}
+
+impl From<BinaryHintProcessorData> for HintProcessorData {
+    fn from(hint_data: BinaryHintProcessorData) -> Self {
+        HintProcessorData {
+            code: hint_data.code,
+            ap_tracking: hint_data.ap_tracking,
+            ids_data: hint_data
+                .ids_data
+                .into_iter()
+                .map(|(name, reference)| (name, reference.into()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryProgram {
+    source_hash: Option<[u8; 32]>,
+    builtins: Vec<BuiltinName>,
+    constants: Vec<(String, BinaryFelt)>,
+    data: Vec<BinaryMaybeRelocatable>,
+    hints: Vec<(usize, Vec<BinaryHintParams>)>,
+    main: Option<usize>,
+    start: Option<usize>,
+    end: Option<usize>,
+    error_message_attributes: Vec<BinaryAttribute>,
+    instruction_locations: Option<Vec<(usize, InstructionLocation)>>,
+    identifiers: Vec<(String, BinaryIdentifier)>,
+    reference_manager: Vec<BinaryHintReference>,
+    hint_data: Vec<BinaryHintProcessorData>,
+}
+
+/// Contents of a binary program.
+pub(crate) struct DecodedBinaryProgram {
+    pub(crate) source_hash: Option<[u8; 32]>,
+    pub(crate) program: Program,
+    pub(crate) hint_data: Vec<HintProcessorData>,
+}
+
+pub(crate) fn encode_binary_program(
+    program: &Program,
+    hint_data: &[Arc<HintProcessorData>],
+    source_hash: Option<[u8; 32]>,
+) -> Result<Vec<u8>, ProgramError> {
+    let shared_program_data = &program.shared_program_data;
+    let hints: BTreeMap<usize, Vec<HintParams>> = (&shared_program_data.hints_collection).into();
+    let binary_program = BinaryProgram {
+        source_hash,
+        builtins: program.builtins.clone(),
+        constants: program
+            .constants
+            .iter()
+            .map(|(name, value)| (name.clone(), value.into()))
+            .collect(),
+        data: shared_program_data.data.iter().map(Into::into).collect(),
+        hints: hints
+            .iter()
+            .map(|(pc, hints)| (*pc, hints.iter().map(Into::into).collect()))
+            .collect(),
+        main: shared_program_data.main,
+        start: shared_program_data.start,
+        end: shared_program_data.end,
+        error_message_attributes: shared_program_data
+            .error_message_attributes
+            .iter()
+            .map(Into::into)
+            .collect(),
+        instruction_locations: shared_program_data.instruction_locations.as_ref().map(
+            |locations| {
+                locations
+                    .iter()
+                    .map(|(pc, location)| (*pc, location.clone()))
+                    .collect()
+            },
+        ),
+        identifiers: shared_program_data
+            .identifiers
+            .iter()
+            .map(|(name, identifier)| (name.clone(), identifier.into()))
+            .collect(),
+        reference_manager: shared_program_data
+            .reference_manager
+            .iter()
+            .map(Into::into)
+            .collect(),
+        hint_data: hint_data
+            .iter()
+            .map(|hint_data| hint_data.as_ref().into())
+            .collect(),
+    };
+
+    let mut bytes = BINARY_PROGRAM_MAGIC.to_vec();
+    bytes.extend_from_slice(&BINARY_PROGRAM_VERSION.to_le_bytes());
+    let body = bincode::serde::encode_to_vec(binary_program, bincode::config::standard())
+        .map_err(|e| ProgramError::InvalidBinaryProgram(e.to_string()))?;
+    bytes.extend(body);
+    Ok(bytes)
+}
+
+pub(crate) fn decode_binary_program(bytes: &[u8]) -> Result<DecodedBinaryProgram, ProgramError> {
+    if bytes.len() < HEADER_LEN || !bytes.starts_with(BINARY_PROGRAM_MAGIC) {
+        return Err(ProgramError::InvalidBinaryProgram(
+            "missing binary program header".to_string(),
+        ));
+    }
+    let mut version = [0; 4];
+    version.copy_from_slice(&bytes[BINARY_PROGRAM_MAGIC.len()..HEADER_LEN]);
+    let version = u32::from_le_bytes(version);
+    if version != BINARY_PROGRAM_VERSION {
+        return Err(ProgramError::BinaryProgramVersion(version));
+    }
+    let (binary_program, _): (BinaryProgram, usize) =
+        bincode::serde::decode_from_slice(&bytes[HEADER_LEN..], bincode::config::standard())
+            .map_err(|e| ProgramError::InvalidBinaryProgram(e.to_string()))?;
+
+    let data: Vec<MaybeRelocatable> = binary_program.data.into_iter().map(Into::into).collect();
+    let hints: BTreeMap<usize, Vec<HintParams>> = binary_program
+        .hints
+        .into_iter()
+        .map(|(pc, hints)| (pc, hints.into_iter().map(Into::into).collect()))
+        .collect();
+    let hints_collection = HintsCollection::new(&hints, data.len())?;
+    let shared_program_data = SharedProgramData {
+        data,
+        hints_collection,
+        main: binary_program.main,
+        start: binary_program.start,
+        end: binary_program.end,
+        error_message_attributes: binary_program
+            .error_message_attributes
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+        instruction_locations: binary_program
+            .instruction_locations
+            .map(|locations| locations.into_iter().collect()),
+        identifiers: binary_program
+            .identifiers
+            .into_iter()
+            .map(|(name, identifier)| (name, identifier.into()))
+            .collect(),
+        reference_manager: binary_program
+            .reference_manager
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+    };
+    let constants: HashMap<String, Felt252> = binary_program
+        .constants
+        .into_iter()
+        .map(|(name, value)| (name, value.into()))
+        .collect();
+
+    Ok(DecodedBinaryProgram {
+        source_hash: binary_program.source_hash,
+        program: Program {
+            shared_program_data: Arc::new(shared_program_data),
+            constants,
+            builtins: binary_program.builtins,
+        },
+        hint_data: binary_program
+            .hint_data
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+    })
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "std")]
+pub mod binary_program;
 pub mod deserialize_program;
-mod deserialize_utils;
+pub(crate) mod deserialize_utils;
 pub(crate) mod serialize_program;
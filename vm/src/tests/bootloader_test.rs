@@ -0,0 +1,171 @@
+use crate::{
+    cairo_run::{cairo_run_program, CairoRunConfig},
+    fact_topology::{compute_fact, FactInfo, FactTopology},
+    hint_processor::builtin_hint_processor::{
+        bootloader::{
+            bootloader_hint_processor::BootloaderHintProcessor,
+            types::{BootloaderConfig, BootloaderInput, PackedOutput, SimpleBootloaderInput, Task},
+        },
+        builtin_hint_processor_definition::BuiltinHintProcessor,
+    },
+    program_hash::{compute_program_hash, ProgramHashFunction},
+    types::{layout_name::LayoutName, program::Program, relocatable::Relocatable},
+    vm::runners::cairo_runner::CairoRunner,
+    Felt252,
+};
+use num_traits::ToPrimitive;
+
+// Task programs, which only write to the output builtin
+const PROGRAM_TASK: &[u8] = include_bytes!("../../../cairo_programs/simple_print.json");
+const PIE_TASK: &[u8] = include_bytes!("../../../cairo_programs/jmp.json");
+
+// Runs a task on its own, returning the fact info of the run & its Cairo PIE
+fn run_task(program_content: &[u8]) -> (Program, FactInfo, Task) {
+    let program = Program::from_bytes(program_content, Some("main")).unwrap();
+    let runner = cairo_run_program(
+        &program,
+        &CairoRunConfig {
+            layout: LayoutName::all_cairo,
+            ..Default::default()
+        },
+        &mut BuiltinHintProcessor::new_empty(),
+    )
+    .unwrap();
+    let fact_info = FactInfo::from_runner(&runner, ProgramHashFunction::Pedersen).unwrap();
+    let cairo_pie = runner.get_cairo_pie().unwrap();
+    (program, fact_info, Task::Pie(Box::new(cairo_pie)))
+}
+
+// Runs a bootloader program with its input inserted in the execution scopes under `input_name`
+fn run_bootloader<T: 'static>(program_content: &[u8], input_name: &str, input: T) -> Vec<Felt252> {
+    let program = Program::from_bytes(program_content, Some("main")).unwrap();
+    let mut runner = CairoRunner::new(
+        &program,
+        LayoutName::starknet_with_keccak,
+        None,
+        false,
+        false,
+    )
+    .unwrap();
+    runner.exec_scopes.insert_value(input_name, input);
+    let mut hint_processor = BootloaderHintProcessor::new_empty();
+    let end = runner.initialize(false).unwrap();
+    runner.run_until_pc(end, &mut hint_processor).unwrap();
+    runner.end_run(false, false, &mut hint_processor).unwrap();
+    runner.read_return_values(false).unwrap();
+
+    let output_base = runner.vm.get_output_builtin_mut().unwrap().base();
+    let output_size = runner.vm.get_segment_used_size(output_base).unwrap();
+    runner
+        .vm
+        .get_integer_range(Relocatable::from((output_base as isize, 0)), output_size)
+        .unwrap()
+        .into_iter()
+        .map(|value| value.into_owned())
+        .collect()
+}
+
+// Expected output of the simple bootloader: the number of tasks, then the size (including this
+// header), program hash & output of each task
+fn simple_bootloader_output(tasks: &[(&Program, &FactInfo)]) -> Vec<Felt252> {
+    let mut output = vec![Felt252::from(tasks.len())];
+    for (program, fact_info) in tasks {
+        output.push(Felt252::from(fact_info.program_output.len() + 2));
+        output.push(program_hash(program));
+        output.extend(&fact_info.program_output);
+    }
+    output
+}
+
+fn program_hash(program: &Program) -> Felt252 {
+    let program_hash = compute_program_hash(
+        &program.get_stripped_program().unwrap(),
+        0,
+        ProgramHashFunction::Pedersen,
+    )
+    .unwrap();
+    Felt252::from_bytes_be(&program_hash.to_bytes_be())
+}
+
+#[test]
+fn simple_bootloader_runs_program_and_cairo_pie_tasks() {
+    let (program, program_fact_info, _) = run_task(PROGRAM_TASK);
+    let (pie_program, pie_fact_info, pie_task) = run_task(PIE_TASK);
+    let fact_topologies_dir = tempfile::tempdir().unwrap();
+    let fact_topologies_path = fact_topologies_dir.path().join("fact_topologies.json");
+
+    let output = run_bootloader(
+        include_bytes!("../../../cairo_programs/bootloader_programs/simple_bootloader.json"),
+        "simple_bootloader_input",
+        SimpleBootloaderInput {
+            fact_topologies_path: Some(fact_topologies_path.to_string_lossy().into_owned()),
+            single_page: false,
+            tasks: vec![Task::Program(program.clone()), pie_task],
+        },
+    );
+    assert_eq!(
+        output,
+        simple_bootloader_output(&[
+            (&program, &program_fact_info),
+            (&pie_program, &pie_fact_info)
+        ])
+    );
+
+    // The facts of the tasks, computed from the bootloader output & fact topologies, match the
+    // ones of the tasks run on their own
+    let fact_topologies: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&fact_topologies_path).unwrap()).unwrap();
+    let fact_topologies: Vec<FactTopology> =
+        serde_json::from_value(fact_topologies["fact_topologies"].clone()).unwrap();
+    assert_eq!(fact_topologies.len(), 2);
+    let mut task_output = &output[1..];
+    for (fact_topology, fact_info) in fact_topologies
+        .iter()
+        .zip([&program_fact_info, &pie_fact_info])
+    {
+        assert_eq!(fact_topology, &fact_info.fact_topology);
+        let task_size = task_output[0].to_usize().unwrap();
+        let fact =
+            compute_fact(&task_output[1], &task_output[2..task_size], fact_topology).unwrap();
+        assert_eq!(fact, fact_info.fact);
+        task_output = &task_output[task_size..];
+    }
+    assert!(task_output.is_empty());
+}
+
+#[test]
+fn bootloader_runs_program_and_cairo_pie_tasks() {
+    let simple_bootloader_content =
+        include_bytes!("../../../cairo_programs/bootloader_programs/simple_bootloader.json");
+    let simple_bootloader_program_hash =
+        program_hash(&Program::from_bytes(simple_bootloader_content, Some("main")).unwrap());
+    let (program, program_fact_info, _) = run_task(PROGRAM_TASK);
+    let (pie_program, pie_fact_info, pie_task) = run_task(PIE_TASK);
+
+    let output = run_bootloader(
+        include_bytes!("../../../cairo_programs/bootloader_programs/bootloader.json"),
+        "bootloader_input",
+        BootloaderInput {
+            simple_bootloader_input: SimpleBootloaderInput {
+                fact_topologies_path: None,
+                single_page: false,
+                tasks: vec![Task::Program(program.clone()), pie_task],
+            },
+            bootloader_config: BootloaderConfig {
+                simple_bootloader_program_hash,
+                supported_cairo_verifier_program_hashes: vec![],
+            },
+            packed_outputs: vec![PackedOutput::Plain, PackedOutput::Plain],
+        },
+    );
+    // The bootloader config is followed by the outputs of the plain tasks, as written by the
+    // simple bootloader
+    assert_eq!(output[0], simple_bootloader_program_hash);
+    assert_eq!(
+        output[2..],
+        simple_bootloader_output(&[
+            (&program, &program_fact_info),
+            (&pie_program, &pie_fact_info)
+        ])
+    );
+}
@@ -1,4 +1,12 @@
-use crate::{tests::*, vm::runners::cairo_runner::ResourceTracker};
+use crate::{
+    hint_processor::cairo_1_hint_processor::syscall_handler::{
+        selector_from_name, ExecutionInfo, InMemorySyscallHandler, StarknetState, SyscallError,
+        SyscallHandler, MAX_CALL_DEPTH,
+    },
+    stdlib::sync::{Arc, Mutex},
+    tests::*,
+    vm::{errors::hint_errors::HintError, runners::cairo_runner::ResourceTracker},
+};
 use assert_matches::assert_matches;
 
 #[test]
@@ -658,3 +666,171 @@ fn fibonacci_with_run_resources_error() {
     .is_err());
     assert_eq!(hint_processor.run_resources(), &RunResources::new(0));
 }
+
+#[test]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+fn syscalls_with_in_memory_syscall_handler() {
+    let program_data = include_bytes!("../../../cairo_programs/cairo-2-contracts/syscalls.casm");
+    let contract_class: CasmContractClass = serde_json::from_slice(program_data).unwrap();
+    let state = Arc::new(Mutex::new(StarknetState::default()));
+    let class_hash = Felt252::from(1);
+    let contract_address = Felt252::from(2);
+    state
+        .lock()
+        .unwrap()
+        .declare_class(class_hash, contract_class);
+    state
+        .lock()
+        .unwrap()
+        .deploy_contract(contract_address, class_hash);
+    let mut syscall_handler = InMemorySyscallHandler::new(state.clone(), ExecutionInfo::default());
+    syscall_handler.execution_info_mut().block_info.block_number = 10;
+    let mut gas = 100_000_000;
+    let mut call = |name: &str, calldata: &[Felt252]| {
+        syscall_handler.call_contract(
+            contract_address,
+            selector_from_name(name),
+            calldata.to_vec(),
+            &mut gas,
+            &mut RunResources::default(),
+        )
+    };
+
+    assert_matches!(call("set_value", &[5.into(), 7.into()]), Ok(retdata) if retdata.is_empty());
+    assert_matches!(call("get_value", &[5.into()]), Ok(retdata) if retdata == [7.into()]);
+    assert_matches!(
+        call("get_value_from", &[contract_address, 5.into()]),
+        Ok(retdata) if retdata == [7.into()]
+    );
+    assert_matches!(call("get_block_number", &[]), Ok(retdata) if retdata == [10.into()]);
+    assert_matches!(
+        call("keccak_u256", &[1.into(), 0.into()]),
+        Ok(retdata) if retdata == [
+            Felt252::from(0x587f7cc3722e9654ea3963d5fe8c0748_u128),
+            Felt252::from(0xa5963aa610cb75ba273817bce5f8c48f_u128)
+        ]
+    );
+
+    // The failed call reverts its storage writes
+    assert_matches!(
+        call("set_value_and_fail", &[6.into(), 8.into()]),
+        Err(SyscallError::Revert(reason)) if reason == [Felt252::from_bytes_be_slice(b"failure")]
+    );
+    assert_matches!(call("get_value", &[6.into()]), Ok(retdata) if retdata == [0.into()]);
+
+    let events = &state.lock().unwrap().events;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].from_address, contract_address);
+    assert_eq!(events[0].keys[1], 5.into());
+    assert_eq!(events[0].data, [7.into()]);
+    assert!(gas < 100_000_000);
+}
+
+#[test]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+fn in_memory_syscall_handler_calls_use_caller_run_resources() {
+    let program_data = include_bytes!("../../../cairo_programs/cairo-2-contracts/syscalls.casm");
+    let contract_class: CasmContractClass = serde_json::from_slice(program_data).unwrap();
+    let state = Arc::new(Mutex::new(StarknetState::default()));
+    let contract_address = Felt252::from(2);
+    state
+        .lock()
+        .unwrap()
+        .declare_class(1.into(), contract_class);
+    state
+        .lock()
+        .unwrap()
+        .deploy_contract(contract_address, 1.into());
+    let mut syscall_handler = InMemorySyscallHandler::new(state.clone(), ExecutionInfo::default());
+    let mut call = |calldata: &[Felt252], run_resources: &mut RunResources| {
+        syscall_handler.call_contract(
+            contract_address,
+            selector_from_name("set_value"),
+            calldata.to_vec(),
+            &mut 100_000_000,
+            run_resources,
+        )
+    };
+
+    let mut run_resources = RunResources::new(1_000_000);
+    assert_matches!(call(&[5.into(), 7.into()], &mut run_resources), Ok(_));
+    let n_steps = 1_000_000 - run_resources.get_n_steps().unwrap();
+    assert!(n_steps > 0);
+
+    // The call runs out of steps after writing to the storage, which is reverted
+    let mut run_resources = RunResources::new(n_steps - 1);
+    assert_matches!(
+        call(&[6.into(), 8.into()], &mut run_resources),
+        Err(SyscallError::Hint(HintError::SyscallError(_)))
+    );
+    assert_eq!(run_resources, RunResources::new(0));
+    assert_eq!(
+        state
+            .lock()
+            .unwrap()
+            .storage_at(&contract_address, &6.into()),
+        0.into()
+    );
+    assert_eq!(
+        state
+            .lock()
+            .unwrap()
+            .storage_at(&contract_address, &5.into()),
+        7.into()
+    );
+}
+
+#[test]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+fn in_memory_syscall_handler_deep_calls() {
+    let program_data = include_bytes!("../../../cairo_programs/cairo-2-contracts/syscalls.casm");
+    let contract_class: CasmContractClass = serde_json::from_slice(program_data).unwrap();
+    let state = Arc::new(Mutex::new(StarknetState::default()));
+    let class_hash = Felt252::from(1);
+    let contract_address = Felt252::from(2);
+    state
+        .lock()
+        .unwrap()
+        .declare_class(class_hash, contract_class);
+    state
+        .lock()
+        .unwrap()
+        .deploy_contract(contract_address, class_hash);
+    let mut syscall_handler = InMemorySyscallHandler::new(state, ExecutionInfo::default());
+    let mut call_self = |depth: usize| {
+        syscall_handler.call_contract(
+            contract_address,
+            selector_from_name("call_self"),
+            vec![class_hash, depth.into()],
+            &mut 1_000_000_000,
+            &mut RunResources::default(),
+        )
+    };
+
+    // The call to the contract & the calls it nests reach the maximum depth
+    assert_matches!(
+        call_self(MAX_CALL_DEPTH - 1),
+        Ok(retdata) if retdata == [(MAX_CALL_DEPTH - 1).into()]
+    );
+    assert_matches!(
+        call_self(MAX_CALL_DEPTH),
+        Err(SyscallError::Hint(HintError::SyscallError(_)))
+    );
+}
+
+#[test]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+fn selector_from_name_is_sn_keccak() {
+    assert_eq!(
+        selector_from_name("constructor"),
+        Felt252::from_hex_unchecked(
+            "0x28ffe4ff0f226a9107253e17a904099aa4f63a02a5621de0576e5aa71bc5194"
+        )
+    );
+    assert_eq!(
+        selector_from_name("transfer"),
+        Felt252::from_hex_unchecked(
+            "0x83afd3f4caedc6eebf44246fe54e38c95e3179a5ec9ea81740eca5b482d12e"
+        )
+    );
+}
@@ -29,6 +29,9 @@ use wasm_bindgen_test::*;
 use alloc::{string::String, vec::Vec};
 
 mod bitwise_test;
+// The bootloaders write their fact topologies to the file system
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+mod bootloader_test;
 #[cfg(test)]
 mod run_deprecated_contract_class_simplified;
 
@@ -113,6 +116,7 @@ fn run_cairo_1_entrypoint(
     let mut runner = CairoRunner::new(
         &(contract_class.clone().try_into().unwrap()),
         LayoutName::all_cairo,
+        None,
         false,
         false,
     )
@@ -217,6 +221,7 @@ fn run_cairo_1_entrypoint_with_run_resources(
     let mut runner = CairoRunner::new(
         &(contract_class.clone().try_into().unwrap()),
         LayoutName::all_cairo,
+        None,
         false,
         false,
     )
@@ -110,9 +110,8 @@ impl HintProcessorLogic for SimplifiedOsHintProcessor {
             res => return res,
         }
         // Execute os-specific hints
-        let hint_data = hint_data
-            .downcast_ref::<HintProcessorData>()
-            .ok_or(HintError::WrongHintData)?;
+        let hint_data =
+            HintProcessorData::downcast(&**hint_data).ok_or(HintError::WrongHintData)?;
         match &*hint_data.code {
             ALLOC_FACTS => alloc_facts(vm, &hint_data.ids_data, &hint_data.ap_tracking),
             COMPILE_CLASS => compile_class(vm, &hint_data.ids_data, &hint_data.ap_tracking),
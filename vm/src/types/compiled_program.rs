@@ -0,0 +1,423 @@
+use crate::{
+    hint_processor::{
+        builtin_hint_processor::builtin_hint_processor_definition::HintProcessorData,
+        hint_processor_definition::get_ids_data,
+    },
+    stdlib::{prelude::*, sync::Arc},
+    types::program::Program,
+    vm::errors::vm_errors::VirtualMachineError,
+};
+#[cfg(feature = "std")]
+use crate::{
+    serde::binary_program::{decode_binary_program, encode_binary_program},
+    types::errors::program_errors::ProgramError,
+    vm::errors::cairo_run_errors::CairoRunError,
+};
+#[cfg(feature = "std")]
+use sha3::{Digest, Keccak256};
+#[cfg(feature = "std")]
+use std::path::Path;
+
+/// A program along with the data its hints are compiled into, as executed by the
+/// [`BuiltinHintProcessor`](crate::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor).
+///
+/// Runners of a compiled program (see [`CairoRunner::set_compiled_hint_data`](crate::vm::runners::cairo_runner::CairoRunner::set_compiled_hint_data))
+/// reuse its hint data instead of compiling the hints on each run, and compiled programs can be
+/// stored in a compact binary format, which is much faster to load than the program's json.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompiledProgram {
+    program: Program,
+    hint_data: Arc<[Arc<HintProcessorData>]>,
+    // Hash of the json & entrypoint the program was compiled from, used to validate caches
+    source_hash: Option<[u8; 32]>,
+}
+
+impl CompiledProgram {
+    /// Compiles the hints of the program, resolving the references they use.
+    pub fn new(program: Program) -> Result<CompiledProgram, VirtualMachineError> {
+        let references = &program.shared_program_data.reference_manager;
+        let hint_data = program
+            .shared_program_data
+            .hints_collection
+            .iter_hints()
+            .map(|hint| {
+                Ok(Arc::new(HintProcessorData {
+                    code: hint.code.clone(),
+                    ap_tracking: hint.flow_tracking_data.ap_tracking.clone(),
+                    ids_data: get_ids_data(&hint.flow_tracking_data.reference_ids, references)
+                        .map_err(|_| {
+                            VirtualMachineError::CompileHintFail(hint.code.clone().into())
+                        })?,
+                }))
+            })
+            .collect::<Result<Vec<_>, VirtualMachineError>>()?;
+        Ok(CompiledProgram {
+            program,
+            hint_data: hint_data.into(),
+            source_hash: None,
+        })
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// The data of each hint of the program, in the order of
+    /// [`CairoRunner::get_hint_data`](crate::vm::runners::cairo_runner::CairoRunner::get_hint_data).
+    pub fn hint_data(&self) -> &Arc<[Arc<HintProcessorData>]> {
+        &self.hint_data
+    }
+
+    /// Encodes the compiled program in the binary format of [`crate::serde::binary_program`].
+    #[cfg(feature = "std")]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ProgramError> {
+        encode_binary_program(&self.program, &self.hint_data, self.source_hash)
+    }
+
+    /// Decodes a compiled program encoded by [`CompiledProgram::to_bytes`].
+    #[cfg(feature = "std")]
+    pub fn from_bytes(bytes: &[u8]) -> Result<CompiledProgram, ProgramError> {
+        let decoded = decode_binary_program(bytes)?;
+        let n_hints = decoded
+            .program
+            .shared_program_data
+            .hints_collection
+            .iter_hints()
+            .count();
+        if decoded.hint_data.len() != n_hints {
+            return Err(ProgramError::InvalidBinaryProgram(format!(
+                "expected the data of {n_hints} hints, got {}",
+                decoded.hint_data.len()
+            )));
+        }
+        Ok(CompiledProgram {
+            program: decoded.program,
+            hint_data: decoded.hint_data.into_iter().map(Arc::new).collect(),
+            source_hash: decoded.source_hash,
+        })
+    }
+
+    /// Loads the compiled program stored at `cache_path`, if it was compiled from the given
+    /// program file & entrypoint. Otherwise, the program file is parsed & compiled, and the
+    /// compiled program is written to `cache_path` for the next calls.
+    #[cfg(feature = "std")]
+    pub fn from_file_cached(
+        program_path: &Path,
+        entrypoint: Option<&str>,
+        cache_path: &Path,
+    ) -> Result<CompiledProgram, CairoRunError> {
+        let program_content = std::fs::read(program_path).map_err(ProgramError::IO)?;
+        let mut hasher = Keccak256::new();
+        hasher.update(&program_content);
+        hasher.update(entrypoint.unwrap_or_default().as_bytes());
+        let source_hash: [u8; 32] = hasher.finalize().into();
+
+        // An unreadable or outdated cache is replaced
+        if let Some(compiled_program) = std::fs::read(cache_path)
+            .ok()
+            .and_then(|bytes| CompiledProgram::from_bytes(&bytes).ok())
+            .filter(|compiled_program| compiled_program.source_hash == Some(source_hash))
+        {
+            return Ok(compiled_program);
+        }
+
+        let program = Program::from_bytes(&program_content, entrypoint)?;
+        let mut compiled_program = CompiledProgram::new(program)?;
+        compiled_program.source_hash = Some(source_hash);
+        std::fs::write(cache_path, compiled_program.to_bytes()?).map_err(ProgramError::IO)?;
+        Ok(compiled_program)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cairo_run::{cairo_run_compiled_program, CairoRunConfig};
+    use crate::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor;
+    use crate::hint_processor::builtin_hint_processor::hint_code;
+    use crate::hint_processor::hint_processor_definition::HintReference;
+    use crate::serde::binary_program::{BINARY_PROGRAM_MAGIC, BINARY_PROGRAM_VERSION};
+    use crate::serde::deserialize_program::{
+        ApTracking, Attribute, FlowTrackingData, HintParams, Identifier, Member, OffsetValue,
+    };
+    use crate::stdlib::collections::{BTreeMap, HashMap};
+    use crate::types::{layout_name::LayoutName, relocatable::MaybeRelocatable};
+    use crate::utils::test_utils::*;
+    use crate::vm::errors::cairo_run_errors::CairoRunError;
+    use crate::vm::errors::runner_errors::RunnerError;
+    use crate::Felt252;
+    use assert_matches::assert_matches;
+
+    fn hint(code: &str, reference_ids: HashMap<String, usize>) -> HintParams {
+        HintParams {
+            code: code.to_string(),
+            accessible_scopes: vec![],
+            flow_tracking_data: FlowTrackingData {
+                ap_tracking: ApTracking::default(),
+                reference_ids,
+            },
+        }
+    }
+
+    // Asserts [ap] = 1, entering & exiting a scope through hints
+    fn scoped_program() -> Program {
+        program!(
+            data = vec_data!((0x480680017fff8000_u64), (1), (0x208b7fff7fff7ffe_u64)),
+            main = Some(0),
+            hints = BTreeMap::from([
+                (0, vec![hint(hint_code::VM_ENTER_SCOPE, HashMap::new())]),
+                (2, vec![hint(hint_code::VM_EXIT_SCOPE, HashMap::new())]),
+            ]),
+        )
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn compile_program_hints() {
+        let program = Program::from_bytes(
+            include_bytes!("../../../cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let compiled_program = CompiledProgram::new(program.clone()).unwrap();
+        assert_eq!(compiled_program.program(), &program);
+        assert_eq!(
+            compiled_program
+                .hint_data()
+                .iter()
+                .map(|hint_data| hint_data.code.as_str())
+                .collect::<Vec<_>>(),
+            vec!["memory[ap] = segments.add()", "import math"]
+        );
+    }
+
+    #[test]
+    fn compiled_program_bytes_round_trip() {
+        let program = Program::from_bytes(
+            include_bytes!("../../../cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let compiled_program = CompiledProgram::new(program).unwrap();
+        let bytes = compiled_program.to_bytes().unwrap();
+        assert!(bytes.starts_with(BINARY_PROGRAM_MAGIC));
+        assert_eq!(
+            CompiledProgram::from_bytes(&bytes).unwrap(),
+            compiled_program
+        );
+    }
+
+    #[test]
+    fn compiled_program_from_bytes_invalid() {
+        assert_matches!(
+            CompiledProgram::from_bytes(b"{}"),
+            Err(ProgramError::InvalidBinaryProgram(_))
+        );
+
+        let mut bytes = BINARY_PROGRAM_MAGIC.to_vec();
+        bytes.extend_from_slice(&(BINARY_PROGRAM_VERSION + 1).to_le_bytes());
+        assert_matches!(
+            CompiledProgram::from_bytes(&bytes),
+            Err(ProgramError::BinaryProgramVersion(version)) if version == BINARY_PROGRAM_VERSION + 1
+        );
+
+        bytes.truncate(BINARY_PROGRAM_MAGIC.len());
+        bytes.extend_from_slice(&BINARY_PROGRAM_VERSION.to_le_bytes());
+        bytes.push(1);
+        assert_matches!(
+            CompiledProgram::from_bytes(&bytes),
+            Err(ProgramError::InvalidBinaryProgram(_))
+        );
+    }
+
+    #[test]
+    fn compiled_program_from_file_cached() {
+        let program_path = Path::new("../cairo_programs/manually_compiled/valid_program_a.json");
+        let cache_path = std::env::temp_dir().join("cairo_vm_compiled_program_cache.bin");
+        let _ = std::fs::remove_file(&cache_path);
+
+        let compiled_program =
+            CompiledProgram::from_file_cached(program_path, Some("main"), &cache_path).unwrap();
+        let cached_bytes = std::fs::read(&cache_path).unwrap();
+        assert_eq!(
+            CompiledProgram::from_bytes(&cached_bytes).unwrap(),
+            compiled_program
+        );
+        assert_eq!(
+            CompiledProgram::from_file_cached(program_path, Some("main"), &cache_path).unwrap(),
+            compiled_program
+        );
+
+        // A cache of another program or entrypoint is replaced
+        let other_compiled_program =
+            CompiledProgram::from_file_cached(program_path, None, &cache_path).unwrap();
+        assert_ne!(other_compiled_program, compiled_program);
+        assert_ne!(std::fs::read(&cache_path).unwrap(), cached_bytes);
+        std::fs::remove_file(cache_path).unwrap();
+    }
+
+    #[test]
+    fn compiled_program_bytes_round_trip_program_fields() {
+        let mut program = program!(
+            data = vec_data!((0x208b7fff7fff7ffe_u64), ((1, 2)), (5)),
+            main = Some(0),
+            hints = BTreeMap::from([(
+                0,
+                vec![hint(
+                    hint_code::VM_ENTER_SCOPE,
+                    HashMap::from([("__main__.main.x".to_string(), 0)]),
+                )],
+            )]),
+            identifiers = HashMap::from([(
+                "__main__.Point".to_string(),
+                Identifier {
+                    pc: None,
+                    type_: Some("struct".to_string()),
+                    value: Some(Felt252::from(-3)),
+                    full_name: Some("__main__.Point".to_string()),
+                    members: Some(HashMap::from([(
+                        "x".to_string(),
+                        Member {
+                            cairo_type: "felt".to_string(),
+                            offset: 0,
+                        },
+                    )])),
+                    cairo_type: None,
+                },
+            )]),
+            error_message_attributes = vec![Attribute {
+                name: "error_message".to_string(),
+                start_pc: 0,
+                end_pc: 1,
+                value: "Error".to_string(),
+                flow_tracking_data: Some(FlowTrackingData {
+                    ap_tracking: ApTracking {
+                        group: 1,
+                        offset: 2
+                    },
+                    reference_ids: HashMap::from([("__main__.main.x".to_string(), 0)]),
+                }),
+            }],
+            constants = HashMap::from([("__main__.N".to_string(), Felt252::from(7))]),
+            builtins = vec![crate::types::builtin_name::BuiltinName::output],
+        );
+        let mut reference = HintReference::new_simple(-3);
+        reference.offset2 = OffsetValue::Immediate(Felt252::from(-1));
+        reference.ap_tracking_data = Some(ApTracking {
+            group: 1,
+            offset: 0,
+        });
+        reference.cairo_type = Some("felt*".to_string());
+        Arc::get_mut(&mut program.shared_program_data)
+            .unwrap()
+            .reference_manager = vec![reference.clone()];
+
+        let compiled_program = CompiledProgram::new(program).unwrap();
+        assert_eq!(
+            compiled_program.hint_data()[0].ids_data,
+            HashMap::from([("x".to_string(), reference)])
+        );
+        let decoded = CompiledProgram::from_bytes(&compiled_program.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded, compiled_program);
+        assert_eq!(
+            decoded.program().iter_data().nth(1),
+            Some(&MaybeRelocatable::from((1, 2)))
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn compile_program_hints_missing_reference() {
+        let program = program!(
+            data = vec_data!((0x208b7fff7fff7ffe_u64)),
+            hints = BTreeMap::from([(
+                0,
+                vec![hint(
+                    hint_code::VM_ENTER_SCOPE,
+                    HashMap::from([("__main__.main.x".to_string(), 0)]),
+                )],
+            )]),
+        );
+        assert_matches!(
+            CompiledProgram::new(program),
+            Err(VirtualMachineError::CompileHintFail(code)) if code.as_ref() == hint_code::VM_ENTER_SCOPE
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_compiled_program() {
+        let compiled_program = CompiledProgram::new(scoped_program()).unwrap();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let runner = cairo_run_compiled_program(
+            &compiled_program,
+            &CairoRunConfig {
+                layout: LayoutName::all_cairo,
+                ..Default::default()
+            },
+            &mut hint_processor,
+        )
+        .unwrap();
+        assert_eq!(runner.exec_scopes.data.len(), 1);
+        assert_eq!(
+            runner
+                .get_hint_data(&[], &mut hint_processor)
+                .unwrap()
+                .iter()
+                .map(|hint_data| HintProcessorData::downcast(&**hint_data).unwrap())
+                .collect::<Vec<_>>(),
+            compiled_program
+                .hint_data()
+                .iter()
+                .map(AsRef::as_ref)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn run_compiled_program_checks_hint_whitelist() {
+        let compiled_program = CompiledProgram::new(scoped_program()).unwrap();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        // Only the first hint of the program is whitelisted
+        hint_processor.set_hint_whitelist(
+            serde_json::from_value(serde_json::json!({
+                "allowed_reference_expressions_for_hint": [{
+                    "allowed_expressions": [],
+                    "hint_lines": [hint_code::VM_ENTER_SCOPE]
+                }]
+            }))
+            .unwrap(),
+        );
+        let result = cairo_run_compiled_program(
+            &compiled_program,
+            &CairoRunConfig {
+                layout: LayoutName::all_cairo,
+                ..Default::default()
+            },
+            &mut hint_processor,
+        );
+        match result {
+            Err(CairoRunError::VmException(exception)) => assert_matches!(
+                exception.inner_exc,
+                VirtualMachineError::HintNotWhitelisted(code) if code.as_ref() == hint_code::VM_EXIT_SCOPE
+            ),
+            Err(_) => panic!("Wrong error returned, expected VmException"),
+            Ok(_) => panic!("Expected run to fail"),
+        }
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn set_compiled_hint_data_program_mismatch() {
+        let compiled_program = CompiledProgram::new(scoped_program()).unwrap();
+        let mut runner = cairo_runner!(scoped_program());
+        assert_matches!(
+            runner.set_compiled_hint_data(&compiled_program),
+            Err(RunnerError::CompiledProgramMismatch)
+        );
+    }
+}
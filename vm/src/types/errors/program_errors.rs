@@ -19,6 +19,12 @@ pub enum ProgramError {
     StrippedProgramNoMain,
     #[error("Hint PC ({0}) is greater or equal to program length ({1})")]
     InvalidHintPc(usize, usize),
+    #[error("Unknown builtin {0}")]
+    UnknownBuiltin(String),
+    #[error("Invalid binary program: {0}")]
+    InvalidBinaryProgram(String),
+    #[error("Unsupported binary program version {0}")]
+    BinaryProgramVersion(u32),
 }
 
 #[cfg(test)]
@@ -1,22 +1,211 @@
-use crate::stdlib::{any::Any, cell::RefCell, collections::HashMap, prelude::*, rc::Rc};
+use crate::stdlib::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    prelude::*,
+    rc::Rc,
+};
 use crate::{
     any_box,
     hint_processor::builtin_hint_processor::dict_manager::DictManager,
+    types::relocatable::{MaybeRelocatable, Relocatable},
     vm::errors::{exec_scope_errors::ExecScopeError, hint_errors::HintError},
+    Felt252,
 };
+use num_bigint::{BigInt, BigUint};
+
+/// A scope variable which can be copied when forking a run (see [`ExecutionScopes::fork`]).
+/// Unlike a plain clone, the copy must not share any mutable state with the original value.
+pub trait ForkableScopeValue: Any + Sized {
+    fn fork_value(&self) -> Self;
+
+    /// Copies the value while forking the scopes holding it. Values whose state can be shared by
+    /// several variables, such as an `Rc`, should fork it via [`ForkedValues::get_or_fork`] so
+    /// that their copies keep sharing it
+    fn fork_value_in(&self, _forked: &mut ForkedValues) -> Self {
+        self.fork_value()
+    }
+}
+
+/// The copies of the shared states forked so far by [`ExecutionScopes::fork`], by their address
+#[derive(Default)]
+pub struct ForkedValues(HashMap<usize, Box<dyn Any>>);
+
+impl ForkedValues {
+    /// Returns the copy of the state at `ptr`, calling `fork` to make it the first time
+    pub fn get_or_fork<T: Clone + 'static>(
+        &mut self,
+        ptr: *const (),
+        fork: impl FnOnce() -> T,
+    ) -> T {
+        if let Some(copy) = self
+            .0
+            .get(&(ptr as usize))
+            .and_then(|copy| copy.downcast_ref::<T>())
+        {
+            return copy.clone();
+        }
+        let copy = fork();
+        self.0.insert(ptr as usize, any_box!(copy.clone()));
+        copy
+    }
+}
+
+// Implements ForkableScopeValue via Clone
+macro_rules! forkable_by_clone {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl $crate::types::exec_scope::ForkableScopeValue for $ty {
+                fn fork_value(&self) -> Self {
+                    self.clone()
+                }
+            }
+        )*
+    };
+}
+pub(crate) use forkable_by_clone;
+
+forkable_by_clone!(
+    Felt252,
+    BigInt,
+    BigUint,
+    usize,
+    u64,
+    u32,
+    i32,
+    bool,
+    String,
+    Relocatable,
+    MaybeRelocatable,
+    Vec<Felt252>,
+    Vec<BigInt>,
+    Vec<usize>,
+    Vec<u64>,
+    Vec<Relocatable>,
+    Vec<MaybeRelocatable>,
+    HashMap<Felt252, Vec<Felt252>>,
+    HashMap<Felt252, Vec<u64>>,
+);
+
+impl ForkableScopeValue for Rc<RefCell<DictManager>> {
+    fn fork_value(&self) -> Self {
+        Rc::new(RefCell::new(self.borrow().clone()))
+    }
+
+    fn fork_value_in(&self, forked: &mut ForkedValues) -> Self {
+        forked.get_or_fork(Rc::as_ptr(self).cast(), || self.fork_value())
+    }
+}
+
+pub(crate) type ForkFn = fn(&dyn Any, &mut ForkedValues) -> Option<Box<dyn Any>>;
+
+fn fork_any<T: ForkableScopeValue>(
+    value: &dyn Any,
+    forked: &mut ForkedValues,
+) -> Option<Box<dyn Any>> {
+    value
+        .downcast_ref::<T>()
+        .map(|value| any_box!(value.fork_value_in(forked)))
+}
+
+pub(crate) fn forkable_type<T: ForkableScopeValue>() -> (TypeId, ForkFn) {
+    (TypeId::of::<T>(), fork_any::<T>)
+}
+
+// Returns the function forking the scope variables of type `type_id` stored by the builtin &
+// Cairo 1 hints, which every ExecutionScopes can fork
+fn default_fork_fn(type_id: TypeId) -> Option<ForkFn> {
+    let builtin_types = [
+        forkable_type::<Felt252>(),
+        forkable_type::<BigInt>(),
+        forkable_type::<BigUint>(),
+        forkable_type::<usize>(),
+        forkable_type::<u64>(),
+        forkable_type::<u32>(),
+        forkable_type::<i32>(),
+        forkable_type::<bool>(),
+        forkable_type::<String>(),
+        forkable_type::<Relocatable>(),
+        forkable_type::<MaybeRelocatable>(),
+        forkable_type::<Vec<Felt252>>(),
+        forkable_type::<Vec<BigInt>>(),
+        forkable_type::<Vec<usize>>(),
+        forkable_type::<Vec<u64>>(),
+        forkable_type::<Vec<Relocatable>>(),
+        forkable_type::<Vec<MaybeRelocatable>>(),
+        forkable_type::<HashMap<Felt252, Vec<Felt252>>>(),
+        forkable_type::<HashMap<Felt252, Vec<u64>>>(),
+        forkable_type::<Rc<RefCell<DictManager>>>(),
+    ];
+    #[cfg(feature = "cairo-1-hints")]
+    let cairo_1_types = crate::hint_processor::cairo_1_hint_processor::forkable_scope_values();
+    #[cfg(not(feature = "cairo-1-hints"))]
+    let cairo_1_types = [];
+    builtin_types
+        .into_iter()
+        .chain(cairo_1_types)
+        .find_map(|(forkable_type_id, fork_fn)| (forkable_type_id == type_id).then_some(fork_fn))
+}
 
 #[derive(Debug)]
 pub struct ExecutionScopes {
     pub data: Vec<HashMap<String, Box<dyn Any>>>,
+    // Maps the types registered on top of the default ones to the function forking them
+    forkable_types: HashMap<TypeId, ForkFn>,
 }
 
 impl ExecutionScopes {
     pub fn new() -> ExecutionScopes {
+        ExecutionScopes::from_data(vec![HashMap::new()])
+    }
+
+    /// Creates scopes holding the given variables, the last scope being the current one
+    pub fn from_data(data: Vec<HashMap<String, Box<dyn Any>>>) -> ExecutionScopes {
         ExecutionScopes {
-            data: vec![HashMap::new()],
+            data,
+            forkable_types: HashMap::new(),
         }
     }
 
+    /// Allows scope variables of type `T` to be copied by [`ExecutionScopes::fork`].
+    /// Hint processors storing their own types in the scopes should register them to support forking
+    pub fn register_forkable_type<T: ForkableScopeValue>(&mut self) {
+        let (type_id, fork_fn) = forkable_type::<T>();
+        self.forkable_types.insert(type_id, fork_fn);
+    }
+
+    /// Returns an independent copy of the scopes. Variables sharing their state, such as the
+    /// `DictManager`'s `Rc`, keep sharing it in the copy.
+    /// Fails if a variable's type wasn't registered via [`ExecutionScopes::register_forkable_type`]
+    pub fn fork(&self) -> Result<ExecutionScopes, ExecScopeError> {
+        let mut forked = ForkedValues::default();
+        let data = self
+            .data
+            .iter()
+            .map(|scope| {
+                scope
+                    .iter()
+                    .map(|(name, value)| {
+                        let type_id = value.as_ref().type_id();
+                        self.forkable_types
+                            .get(&type_id)
+                            .copied()
+                            .or_else(|| default_fork_fn(type_id))
+                            .and_then(|fork| fork(value.as_ref(), &mut forked))
+                            .map(|value| (name.clone(), value))
+                            .ok_or_else(|| {
+                                ExecScopeError::UnforkableVariable(name.clone().into_boxed_str())
+                            })
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(ExecutionScopes {
+            data,
+            forkable_types: self.forkable_types.clone(),
+        })
+    }
+
     pub fn enter_scope(&mut self, new_scope_locals: HashMap<String, Box<dyn Any>>) {
         self.data.push(new_scope_locals);
     }
@@ -214,7 +403,7 @@ mod tests {
 
         let scope = HashMap::from([(var_name, var_value)]);
 
-        let scopes = ExecutionScopes { data: vec![scope] };
+        let scopes = ExecutionScopes::from_data(vec![scope]);
         assert_eq!(scopes.get_local_variables().unwrap().len(), 1);
         assert_eq!(
             scopes
@@ -235,12 +424,10 @@ mod tests {
 
         let new_scope = HashMap::from([(var_name, var_value)]);
 
-        let mut scopes = ExecutionScopes {
-            data: vec![HashMap::from([(
-                String::from("b"),
-                (Box::new(Felt252::ONE) as Box<dyn Any>),
-            )])],
-        };
+        let mut scopes = ExecutionScopes::from_data(vec![HashMap::from([(
+            String::from("b"),
+            (Box::new(Felt252::ONE) as Box<dyn Any>),
+        )])]);
 
         assert_eq!(scopes.get_local_variables().unwrap().len(), 1);
         assert_eq!(
@@ -336,7 +523,7 @@ mod tests {
 
         let scope = HashMap::from([(var_name, var_value)]);
 
-        let mut scopes = ExecutionScopes { data: vec![scope] };
+        let mut scopes = ExecutionScopes::from_data(vec![scope]);
 
         let var_value_new: Box<dyn Any> = Box::new(Felt252::from(3));
 
@@ -362,7 +549,7 @@ mod tests {
 
         let scope = HashMap::from([(var_name, var_value)]);
 
-        let mut scopes = ExecutionScopes { data: vec![scope] };
+        let mut scopes = ExecutionScopes::from_data(vec![scope]);
 
         assert!(scopes
             .get_local_variables()
@@ -475,4 +662,88 @@ mod tests {
         assert!(scopes.get_any_boxed_mut("no_variable").is_err());
         assert!(scopes.get_any_boxed_ref("no_variable").is_err());
     }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn fork_copies_scopes() {
+        let mut scopes = ExecutionScopes::new();
+        scopes.insert_value("n", 3_usize);
+        scopes.insert_value("dict_manager", Rc::new(RefCell::new(DictManager::new())));
+        scopes.enter_scope(HashMap::from([(
+            String::from("keys"),
+            any_box!(vec![Felt252::ONE]),
+        )]));
+
+        let mut fork = scopes.fork().unwrap();
+        assert_eq!(fork.data.len(), 2);
+        fork.get_mut_list_ref::<Felt252>("keys")
+            .unwrap()
+            .push(Felt252::TWO);
+        assert_eq!(
+            scopes.get_list_ref::<Felt252>("keys").unwrap(),
+            &vec![Felt252::ONE]
+        );
+
+        fork.exit_scope().unwrap();
+        scopes.exit_scope().unwrap();
+        assert_eq!(fork.get::<usize>("n").unwrap(), 3);
+        assert!(!Rc::ptr_eq(
+            &scopes.get_dict_manager().unwrap(),
+            &fork.get_dict_manager().unwrap()
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn fork_keeps_shared_dict_manager() {
+        let dict_manager = Rc::new(RefCell::new(DictManager::new()));
+        let mut scopes = ExecutionScopes::new();
+        scopes.insert_value("dict_manager", dict_manager.clone());
+        scopes.enter_scope(HashMap::from([(
+            String::from("dict_manager"),
+            any_box!(dict_manager.clone()),
+        )]));
+        scopes.insert_value(
+            "other_dict_manager",
+            Rc::new(RefCell::new(DictManager::new())),
+        );
+
+        let mut fork = scopes.fork().unwrap();
+        let forked_dict_manager = fork.get_dict_manager().unwrap();
+        assert!(!Rc::ptr_eq(&forked_dict_manager, &dict_manager));
+        assert!(!Rc::ptr_eq(
+            &forked_dict_manager,
+            &fork
+                .get::<Rc<RefCell<DictManager>>>("other_dict_manager")
+                .unwrap()
+        ));
+        fork.exit_scope().unwrap();
+        assert!(Rc::ptr_eq(
+            &forked_dict_manager,
+            &fork.get_dict_manager().unwrap()
+        ));
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn fork_unregistered_type() {
+        #[derive(Clone)]
+        struct Custom;
+
+        impl ForkableScopeValue for Custom {
+            fn fork_value(&self) -> Self {
+                self.clone()
+            }
+        }
+
+        let mut scopes = ExecutionScopes::new();
+        scopes.insert_value("custom", Custom);
+        assert_matches!(
+            scopes.fork(),
+            Err(ExecScopeError::UnforkableVariable(bx)) if bx.as_ref() == "custom"
+        );
+
+        scopes.register_forkable_type::<Custom>();
+        assert!(scopes.fork().unwrap().get_ref::<Custom>("custom").is_ok());
+    }
 }
@@ -5,12 +5,13 @@ use super::{
     pedersen_instance_def::PedersenInstanceDef, poseidon_instance_def::PoseidonInstanceDef,
     range_check_instance_def::RangeCheckInstanceDef,
 };
+use crate::types::{builtin_name::BuiltinName, layout::CairoLayoutParams};
 
 pub(crate) const BUILTIN_INSTANCES_PER_COMPONENT: u32 = 1;
 
 use serde::Serialize;
 
-#[derive(Serialize, Debug, PartialEq)]
+#[derive(Serialize, Clone, Debug, PartialEq)]
 pub(crate) struct BuiltinsInstanceDef {
     pub(crate) output: bool,
     pub(crate) pedersen: Option<PedersenInstanceDef>,
@@ -26,6 +27,24 @@ pub(crate) struct BuiltinsInstanceDef {
 }
 
 impl BuiltinsInstanceDef {
+    /// Returns true if the layout has instances of the given builtin
+    pub(crate) fn includes(&self, builtin: BuiltinName) -> bool {
+        match builtin {
+            BuiltinName::output => self.output,
+            BuiltinName::pedersen => self.pedersen.is_some(),
+            BuiltinName::range_check => self.range_check.is_some(),
+            BuiltinName::ecdsa => self.ecdsa.is_some(),
+            BuiltinName::bitwise => self.bitwise.is_some(),
+            BuiltinName::ec_op => self.ec_op.is_some(),
+            BuiltinName::keccak => self.keccak.is_some(),
+            BuiltinName::poseidon => self.poseidon.is_some(),
+            BuiltinName::range_check96 => self.range_check96.is_some(),
+            BuiltinName::add_mod => self.add_mod.is_some(),
+            BuiltinName::mul_mod => self.mul_mod.is_some(),
+            BuiltinName::segment_arena => false,
+        }
+    }
+
     pub(crate) fn plain() -> BuiltinsInstanceDef {
         BuiltinsInstanceDef {
             output: false,
@@ -192,21 +211,41 @@ impl BuiltinsInstanceDef {
         }
     }
 
-    pub(crate) fn dynamic() -> BuiltinsInstanceDef {
+    pub(crate) fn dynamic(params: &CairoLayoutParams) -> BuiltinsInstanceDef {
         BuiltinsInstanceDef {
             output: true,
-            pedersen: Some(PedersenInstanceDef::new(None)),
-            range_check: Some(RangeCheckInstanceDef::new(None)),
-            ecdsa: Some(EcdsaInstanceDef::new(None)),
-            bitwise: Some(BitwiseInstanceDef::new(None)),
-            ec_op: Some(EcOpInstanceDef::new(None)),
-            keccak: None,
-            poseidon: None,
-            range_check96: None,
+            pedersen: params
+                .uses_pedersen_builtin
+                .then(|| PedersenInstanceDef::new(Some(params.pedersen_ratio))),
+            range_check: params
+                .uses_range_check_builtin
+                .then(|| RangeCheckInstanceDef::new(Some(params.range_check_ratio))),
+            ecdsa: params
+                .uses_ecdsa_builtin
+                .then(|| EcdsaInstanceDef::new(Some(params.ecdsa_ratio))),
+            bitwise: params
+                .uses_bitwise_builtin
+                .then(|| BitwiseInstanceDef::new(Some(params.bitwise_ratio))),
+            ec_op: params
+                .uses_ec_op_builtin
+                .then(|| EcOpInstanceDef::new(Some(params.ec_op_ratio))),
+            keccak: params
+                .uses_keccak_builtin
+                .then(|| KeccakInstanceDef::new(Some(params.keccak_ratio))),
+            poseidon: params
+                .uses_poseidon_builtin
+                .then(|| PoseidonInstanceDef::new(Some(params.poseidon_ratio))),
+            range_check96: params
+                .uses_range_check96_builtin
+                .then(|| RangeCheckInstanceDef::new(Some(params.range_check96_ratio))),
             #[cfg(feature = "mod_builtin")]
-            add_mod: Some(ModInstanceDef::new(None, 1, 96)),
+            add_mod: params
+                .uses_add_mod_builtin
+                .then(|| ModInstanceDef::new(Some(params.add_mod_ratio), 1, 96)),
             #[cfg(feature = "mod_builtin")]
-            mul_mod: Some(ModInstanceDef::new(None, 1, 96)),
+            mul_mod: params
+                .uses_mul_mod_builtin
+                .then(|| ModInstanceDef::new(Some(params.mul_mod_ratio), 1, 96)),
             #[cfg(not(feature = "mod_builtin"))]
             add_mod: None,
             #[cfg(not(feature = "mod_builtin"))]
@@ -345,14 +384,31 @@ mod tests {
 
     #[test]
     fn get_builtins_dynamic() {
-        let builtins = BuiltinsInstanceDef::dynamic();
+        let mut params: CairoLayoutParams =
+            serde_json::from_str(include_str!("../../tests/cairo_layout_params_file.json"))
+                .unwrap();
+        params.uses_keccak_builtin = false;
+        params.uses_mul_mod_builtin = false;
+        let builtins = BuiltinsInstanceDef::dynamic(&params);
         assert!(builtins.output);
-        assert!(builtins.pedersen.is_some());
-        assert!(builtins.range_check.is_some());
+        assert_eq!(builtins.pedersen, Some(PedersenInstanceDef::new(Some(256))));
+        assert_eq!(
+            builtins.range_check,
+            Some(RangeCheckInstanceDef::new(Some(8)))
+        );
         assert!(builtins.ecdsa.is_some());
         assert!(builtins.bitwise.is_some());
         assert!(builtins.ec_op.is_some());
         assert!(builtins.keccak.is_none());
-        assert!(builtins.poseidon.is_none());
+        assert_eq!(builtins.poseidon, Some(PoseidonInstanceDef::new(Some(256))));
+        assert!(builtins.range_check96.is_some());
+        #[cfg(feature = "mod_builtin")]
+        assert_eq!(
+            builtins.add_mod,
+            Some(ModInstanceDef::new(Some(128), 1, 96))
+        );
+        #[cfg(not(feature = "mod_builtin"))]
+        assert!(builtins.add_mod.is_none());
+        assert!(builtins.mul_mod.is_none());
     }
 }
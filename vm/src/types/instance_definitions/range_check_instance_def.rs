@@ -1,7 +1,7 @@
 use serde::Serialize;
 pub(crate) const CELLS_PER_RANGE_CHECK: u32 = 1;
 
-#[derive(Serialize, Debug, PartialEq)]
+#[derive(Serialize, Clone, Debug, PartialEq)]
 pub(crate) struct RangeCheckInstanceDef {
     pub(crate) ratio: Option<u32>,
 }
@@ -1,20 +1,151 @@
-use crate::types::layout_name::LayoutName;
+use crate::{
+    stdlib::prelude::*, types::layout_name::LayoutName, vm::errors::runner_errors::RunnerError,
+};
 
 use super::instance_definitions::{
     builtins_instance_def::BuiltinsInstanceDef, diluted_pool_instance_def::DilutedPoolInstanceDef,
 };
 
 pub(crate) const MEMORY_UNITS_PER_STEP: u32 = 8;
+pub(crate) const DEFAULT_PUBLIC_MEMORY_FRACTION: u32 = 8;
 
-use serde::Serialize;
+#[cfg(feature = "test_utils")]
+use arbitrary::{self, Arbitrary};
+use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone)]
 pub struct CairoLayout {
     pub(crate) name: LayoutName,
     pub(crate) rc_units: u32,
+    pub(crate) memory_units_per_step: u32,
     pub(crate) builtins: BuiltinsInstanceDef,
     pub(crate) public_memory_fraction: u32,
     pub(crate) diluted_pool_instance_def: Option<DilutedPoolInstanceDef>,
+    pub(crate) dynamic_layout_params: Option<CairoLayoutParams>,
+}
+
+/// Parameters of a dynamic layout.
+///
+/// Follows the format of the dynamic layout params JSON file used by the Python toolchain
+/// (`--cairo_layout_params_file`), unknown fields are ignored.
+#[cfg_attr(feature = "test_utils", derive(Arbitrary))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CairoLayoutParams {
+    pub rc_units: u32,
+    pub log_diluted_units_per_step: i32,
+    #[serde(default = "default_cpu_component_step")]
+    pub cpu_component_step: u32,
+    #[serde(default = "default_memory_units_per_step")]
+    pub memory_units_per_step: u32,
+    #[serde(default = "default_public_memory_fraction")]
+    pub public_memory_fraction: u32,
+    pub uses_pedersen_builtin: bool,
+    pub pedersen_ratio: u32,
+    pub uses_range_check_builtin: bool,
+    pub range_check_ratio: u32,
+    pub uses_ecdsa_builtin: bool,
+    pub ecdsa_ratio: u32,
+    pub uses_bitwise_builtin: bool,
+    pub bitwise_ratio: u32,
+    pub uses_ec_op_builtin: bool,
+    pub ec_op_ratio: u32,
+    pub uses_keccak_builtin: bool,
+    pub keccak_ratio: u32,
+    pub uses_poseidon_builtin: bool,
+    pub poseidon_ratio: u32,
+    pub uses_range_check96_builtin: bool,
+    pub range_check96_ratio: u32,
+    #[serde(default = "default_ratio_den")]
+    pub range_check96_ratio_den: u32,
+    pub uses_add_mod_builtin: bool,
+    pub add_mod_ratio: u32,
+    #[serde(default = "default_ratio_den")]
+    pub add_mod_ratio_den: u32,
+    pub uses_mul_mod_builtin: bool,
+    pub mul_mod_ratio: u32,
+    #[serde(default = "default_ratio_den")]
+    pub mul_mod_ratio_den: u32,
+}
+
+fn default_cpu_component_step() -> u32 {
+    1
+}
+
+fn default_memory_units_per_step() -> u32 {
+    MEMORY_UNITS_PER_STEP
+}
+
+fn default_public_memory_fraction() -> u32 {
+    DEFAULT_PUBLIC_MEMORY_FRACTION
+}
+
+fn default_ratio_den() -> u32 {
+    1
+}
+
+impl CairoLayoutParams {
+    /// Reads the dynamic layout params from a JSON file
+    #[cfg(feature = "std")]
+    pub fn from_file(params_path: &std::path::Path) -> std::io::Result<Self> {
+        let params_file = std::fs::File::open(params_path)?;
+        let params = serde_json::from_reader(std::io::BufReader::new(params_file))?;
+        Ok(params)
+    }
+
+    /// Checks that the params describe a layout that can be run by this VM
+    fn validate(&self) -> Result<(), RunnerError> {
+        let invalid = |msg: &str| Err(RunnerError::InvalidDynamicLayoutParams(msg.into()));
+        // Three range check units per step are used by the instruction offsets
+        if self.rc_units < 3 {
+            return invalid("rc_units must be at least 3");
+        }
+        // 2 ^ |log_diluted_units_per_step| diluted units are used per step, or steps per diluted unit if negative
+        if self.log_diluted_units_per_step.unsigned_abs() >= u32::BITS {
+            return invalid("log_diluted_units_per_step must be between -31 and 31");
+        }
+        if self.cpu_component_step != 1 {
+            return invalid("cpu_component_step must be 1");
+        }
+        if self.memory_units_per_step == 0 || self.public_memory_fraction == 0 {
+            return invalid("memory_units_per_step and public_memory_fraction must be non-zero");
+        }
+        let ratios = [
+            (self.uses_pedersen_builtin, self.pedersen_ratio, 1),
+            (self.uses_range_check_builtin, self.range_check_ratio, 1),
+            (self.uses_ecdsa_builtin, self.ecdsa_ratio, 1),
+            (self.uses_bitwise_builtin, self.bitwise_ratio, 1),
+            (self.uses_ec_op_builtin, self.ec_op_ratio, 1),
+            (self.uses_keccak_builtin, self.keccak_ratio, 1),
+            (self.uses_poseidon_builtin, self.poseidon_ratio, 1),
+            (
+                self.uses_range_check96_builtin,
+                self.range_check96_ratio,
+                self.range_check96_ratio_den,
+            ),
+            (
+                self.uses_add_mod_builtin,
+                self.add_mod_ratio,
+                self.add_mod_ratio_den,
+            ),
+            (
+                self.uses_mul_mod_builtin,
+                self.mul_mod_ratio,
+                self.mul_mod_ratio_den,
+            ),
+        ];
+        for (used, ratio, ratio_den) in ratios {
+            if !used {
+                continue;
+            }
+            if ratio == 0 {
+                return invalid("builtin ratios must be non-zero");
+            }
+            if ratio_den != 1 {
+                return invalid("builtin ratio denominators other than 1 are not supported");
+            }
+        }
+        Ok(())
+    }
 }
 
 impl CairoLayout {
@@ -22,9 +153,11 @@ impl CairoLayout {
         CairoLayout {
             name: LayoutName::plain,
             rc_units: 16,
+            memory_units_per_step: MEMORY_UNITS_PER_STEP,
             builtins: BuiltinsInstanceDef::plain(),
             public_memory_fraction: 4,
             diluted_pool_instance_def: None,
+            dynamic_layout_params: None,
         }
     }
 
@@ -32,9 +165,11 @@ impl CairoLayout {
         CairoLayout {
             name: LayoutName::small,
             rc_units: 16,
+            memory_units_per_step: MEMORY_UNITS_PER_STEP,
             builtins: BuiltinsInstanceDef::small(),
             public_memory_fraction: 4,
             diluted_pool_instance_def: None,
+            dynamic_layout_params: None,
         }
     }
 
@@ -42,9 +177,11 @@ impl CairoLayout {
         CairoLayout {
             name: LayoutName::dex,
             rc_units: 4,
+            memory_units_per_step: MEMORY_UNITS_PER_STEP,
             builtins: BuiltinsInstanceDef::dex(),
             public_memory_fraction: 4,
             diluted_pool_instance_def: None,
+            dynamic_layout_params: None,
         }
     }
 
@@ -52,9 +189,11 @@ impl CairoLayout {
         CairoLayout {
             name: LayoutName::recursive,
             rc_units: 4,
+            memory_units_per_step: MEMORY_UNITS_PER_STEP,
             builtins: BuiltinsInstanceDef::recursive(),
             public_memory_fraction: 8,
             diluted_pool_instance_def: Some(DilutedPoolInstanceDef::default()),
+            dynamic_layout_params: None,
         }
     }
 
@@ -62,9 +201,11 @@ impl CairoLayout {
         CairoLayout {
             name: LayoutName::starknet,
             rc_units: 4,
+            memory_units_per_step: MEMORY_UNITS_PER_STEP,
             builtins: BuiltinsInstanceDef::starknet(),
             public_memory_fraction: 8,
             diluted_pool_instance_def: Some(DilutedPoolInstanceDef::new(2, 4, 16)),
+            dynamic_layout_params: None,
         }
     }
 
@@ -72,9 +213,11 @@ impl CairoLayout {
         CairoLayout {
             name: LayoutName::starknet_with_keccak,
             rc_units: 4,
+            memory_units_per_step: MEMORY_UNITS_PER_STEP,
             builtins: BuiltinsInstanceDef::starknet_with_keccak(),
             public_memory_fraction: 8,
             diluted_pool_instance_def: Some(DilutedPoolInstanceDef::default()),
+            dynamic_layout_params: None,
         }
     }
 
@@ -82,18 +225,22 @@ impl CairoLayout {
         CairoLayout {
             name: LayoutName::recursive_large_output,
             rc_units: 4,
+            memory_units_per_step: MEMORY_UNITS_PER_STEP,
             builtins: BuiltinsInstanceDef::recursive_large_output(),
             public_memory_fraction: 8,
             diluted_pool_instance_def: Some(DilutedPoolInstanceDef::default()),
+            dynamic_layout_params: None,
         }
     }
     pub(crate) fn recursive_with_poseidon() -> CairoLayout {
         CairoLayout {
             name: LayoutName::recursive_with_poseidon,
             rc_units: 4,
+            memory_units_per_step: MEMORY_UNITS_PER_STEP,
             builtins: BuiltinsInstanceDef::recursive_with_poseidon(),
             public_memory_fraction: 8,
             diluted_pool_instance_def: Some(DilutedPoolInstanceDef::new(8, 4, 16)),
+            dynamic_layout_params: None,
         }
     }
 
@@ -101,9 +248,11 @@ impl CairoLayout {
         CairoLayout {
             name: LayoutName::all_cairo,
             rc_units: 4,
+            memory_units_per_step: MEMORY_UNITS_PER_STEP,
             builtins: BuiltinsInstanceDef::all_cairo(),
             public_memory_fraction: 8,
             diluted_pool_instance_def: Some(DilutedPoolInstanceDef::default()),
+            dynamic_layout_params: None,
         }
     }
 
@@ -111,26 +260,63 @@ impl CairoLayout {
         CairoLayout {
             name: LayoutName::all_solidity,
             rc_units: 8,
+            memory_units_per_step: MEMORY_UNITS_PER_STEP,
             builtins: BuiltinsInstanceDef::all_solidity(),
             public_memory_fraction: 8,
             diluted_pool_instance_def: Some(DilutedPoolInstanceDef::default()),
+            dynamic_layout_params: None,
         }
     }
 
-    pub(crate) fn dynamic_instance() -> CairoLayout {
-        CairoLayout {
+    /// Returns the layout with the given name. The dynamic layout is built from
+    /// `dynamic_layout_params`, which are required for it and ignored by the other layouts
+    pub fn from_name(
+        name: LayoutName,
+        dynamic_layout_params: Option<CairoLayoutParams>,
+    ) -> Result<CairoLayout, RunnerError> {
+        Ok(match name {
+            LayoutName::plain => CairoLayout::plain_instance(),
+            LayoutName::small => CairoLayout::small_instance(),
+            LayoutName::dex => CairoLayout::dex_instance(),
+            LayoutName::recursive => CairoLayout::recursive_instance(),
+            LayoutName::starknet => CairoLayout::starknet_instance(),
+            LayoutName::starknet_with_keccak => CairoLayout::starknet_with_keccak_instance(),
+            LayoutName::recursive_large_output => CairoLayout::recursive_large_output_instance(),
+            LayoutName::recursive_with_poseidon => CairoLayout::recursive_with_poseidon(),
+            LayoutName::all_cairo => CairoLayout::all_cairo_instance(),
+            LayoutName::all_solidity => CairoLayout::all_solidity_instance(),
+            LayoutName::dynamic => CairoLayout::from_params(
+                dynamic_layout_params.ok_or(RunnerError::MissingDynamicLayoutParams)?,
+            )?,
+        })
+    }
+
+    /// Builds the dynamic layout described by `params`
+    pub fn from_params(params: CairoLayoutParams) -> Result<CairoLayout, RunnerError> {
+        params.validate()?;
+        Ok(CairoLayout {
             name: LayoutName::dynamic,
-            rc_units: 16,
-            builtins: BuiltinsInstanceDef::dynamic(),
-            public_memory_fraction: 8,
-            diluted_pool_instance_def: Some(DilutedPoolInstanceDef::default()),
-        }
+            rc_units: params.rc_units,
+            memory_units_per_step: params.memory_units_per_step,
+            builtins: BuiltinsInstanceDef::dynamic(&params),
+            public_memory_fraction: params.public_memory_fraction,
+            diluted_pool_instance_def: Some(
+                DilutedPoolInstanceDef::from_log_units_per_step(params.log_diluted_units_per_step)
+                    .ok_or_else(|| {
+                        RunnerError::InvalidDynamicLayoutParams(
+                            "log_diluted_units_per_step out of range".into(),
+                        )
+                    })?,
+            ),
+            dynamic_layout_params: Some(params),
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use assert_matches::assert_matches;
 
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::*;
@@ -255,17 +441,121 @@ mod tests {
         );
     }
 
+    fn layout_params() -> CairoLayoutParams {
+        serde_json::from_str(include_str!("../tests/cairo_layout_params_file.json")).unwrap()
+    }
+
     #[test]
     fn get_dynamic_instance() {
-        let layout = CairoLayout::dynamic_instance();
-        let builtins = BuiltinsInstanceDef::dynamic();
+        let params = layout_params();
+        let layout = CairoLayout::from_params(params.clone()).unwrap();
+        let builtins = BuiltinsInstanceDef::dynamic(&params);
         assert_eq!(layout.name, LayoutName::dynamic);
-        assert_eq!(layout.rc_units, 16);
+        assert_eq!(layout.rc_units, 4);
+        assert_eq!(layout.memory_units_per_step, 8);
         assert_eq!(layout.builtins, builtins);
         assert_eq!(layout.public_memory_fraction, 8);
         assert_eq!(
             layout.diluted_pool_instance_def,
             Some(DilutedPoolInstanceDef::default())
         );
+        assert_eq!(layout.dynamic_layout_params, Some(params));
+    }
+
+    #[test]
+    fn get_dynamic_instance_custom_params() {
+        let params = CairoLayoutParams {
+            rc_units: 16,
+            log_diluted_units_per_step: 3,
+            public_memory_fraction: 4,
+            uses_poseidon_builtin: false,
+            ..layout_params()
+        };
+        let layout = CairoLayout::from_params(params).unwrap();
+        assert_eq!(layout.rc_units, 16);
+        assert_eq!(layout.public_memory_fraction, 4);
+        assert_eq!(
+            layout.diluted_pool_instance_def,
+            Some(DilutedPoolInstanceDef::new(8, 4, 16))
+        );
+        assert_eq!(layout.builtins.poseidon, None);
+    }
+
+    #[test]
+    fn get_dynamic_instance_fractional_diluted_units() {
+        let params = CairoLayoutParams {
+            log_diluted_units_per_step: -2,
+            ..layout_params()
+        };
+        let diluted_pool_instance_def = CairoLayout::from_params(params)
+            .unwrap()
+            .diluted_pool_instance_def
+            .unwrap();
+        assert_eq!(diluted_pool_instance_def.units_per_step, 4);
+        assert!(diluted_pool_instance_def.fractional_units_per_step);
+    }
+
+    #[test]
+    fn deserialize_layout_params_defaults() {
+        let mut value: serde_json::Value =
+            serde_json::from_str(include_str!("../tests/cairo_layout_params_file.json")).unwrap();
+        let object = value.as_object_mut().unwrap();
+        object.remove("public_memory_fraction");
+        object.remove("mul_mod_ratio_den");
+        object.insert("num_columns_first".to_string(), 8.into());
+        let params: CairoLayoutParams = serde_json::from_value(value).unwrap();
+        assert_eq!(params.public_memory_fraction, 8);
+        assert_eq!(params.mul_mod_ratio_den, 1);
+    }
+
+    #[test]
+    fn get_dynamic_instance_invalid_params() {
+        let invalid_params = [
+            CairoLayoutParams {
+                rc_units: 2,
+                ..layout_params()
+            },
+            CairoLayoutParams {
+                log_diluted_units_per_step: 32,
+                ..layout_params()
+            },
+            CairoLayoutParams {
+                log_diluted_units_per_step: -32,
+                ..layout_params()
+            },
+            CairoLayoutParams {
+                log_diluted_units_per_step: i32::MIN,
+                ..layout_params()
+            },
+            CairoLayoutParams {
+                public_memory_fraction: 0,
+                ..layout_params()
+            },
+            CairoLayoutParams {
+                pedersen_ratio: 0,
+                ..layout_params()
+            },
+            CairoLayoutParams {
+                add_mod_ratio_den: 2,
+                ..layout_params()
+            },
+        ];
+        for params in invalid_params {
+            assert_matches!(
+                CairoLayout::from_params(params),
+                Err(RunnerError::InvalidDynamicLayoutParams(_))
+            );
+        }
+    }
+
+    #[test]
+    fn get_dynamic_instance_unused_builtin_ratio_is_ignored() {
+        let params = CairoLayoutParams {
+            uses_add_mod_builtin: false,
+            add_mod_ratio: 0,
+            add_mod_ratio_den: 0,
+            ..layout_params()
+        };
+        assert!(CairoLayout::from_params(params).is_ok());
     }
 }
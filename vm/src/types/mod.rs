@@ -1,4 +1,5 @@
 pub mod builtin_name;
+pub mod compiled_program;
 pub mod errors;
 pub mod exec_scope;
 pub mod instance_definitions;
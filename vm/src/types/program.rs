@@ -325,6 +325,18 @@ impl Program {
         Some(relocated_instructions)
     }
 
+    /// Iterates over the source locations of the program's instructions, keyed by their pc.
+    /// Yields nothing if the program was compiled without debug info
+    pub fn iter_instruction_locations(
+        &self,
+    ) -> impl Iterator<Item = (usize, &InstructionLocation)> {
+        self.shared_program_data
+            .instruction_locations
+            .iter()
+            .flatten()
+            .map(|(pc, location)| (*pc, location))
+    }
+
     pub fn iter_identifiers(&self) -> impl Iterator<Item = (&str, &Identifier)> {
         self.shared_program_data
             .identifiers
@@ -1030,6 +1042,37 @@ mod tests {
         assert_eq!(relocated_instructions.get(&14), Some(&il_3));
     }
 
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn iter_instruction_locations() {
+        let program = Program::from_bytes(
+            include_bytes!("../../../cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let mut locations: Vec<_> = program
+            .iter_instruction_locations()
+            .map(|(pc, location)| (pc, location.inst.start_line))
+            .collect();
+        locations.sort();
+        assert_eq!(locations, vec![(0, 2), (2, 3), (4, 4), (5, 5)]);
+
+        let program = Program::new(
+            Vec::new(),
+            Vec::new(),
+            None,
+            HashMap::new(),
+            ReferenceManager {
+                references: Vec::new(),
+            },
+            HashMap::new(),
+            Vec::new(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(program.iter_instruction_locations().count(), 0);
+    }
+
     #[test]
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     fn iter_identifiers() {
@@ -52,6 +52,9 @@ pub fn from_relocatable_to_indexes(relocatable: Relocatable) -> (usize, usize) {
 #[cfg(test)]
 #[macro_use]
 pub mod test_utils {
+    use crate::serde::deserialize_program::Identifier;
+    use crate::stdlib::{collections::HashMap, prelude::*};
+    use crate::types::builtin_name::BuiltinName;
     use crate::types::exec_scope::ExecutionScopes;
     use crate::types::relocatable::MaybeRelocatable;
     use crate::vm::trace::trace_entry::TraceEntry;
@@ -161,9 +164,13 @@ pub mod test_utils {
                 Err($crate::vm::errors::memory_errors::MemoryError::UnallocatedSegment(_))
             ) {
                 if $si < 0 {
-                    $mem.temp_data.push($crate::stdlib::vec::Vec::new())
+                    $crate::vm::vm_memory::memory_backend::MemoryBackend::add_segment(
+                        &mut $mem.temp_data,
+                    )
                 } else {
-                    $mem.data.push($crate::stdlib::vec::Vec::new());
+                    $crate::vm::vm_memory::memory_backend::MemoryBackend::add_segment(
+                        &mut $mem.data,
+                    );
                 }
                 res = $mem.insert(k, v);
             }
@@ -176,9 +183,13 @@ pub mod test_utils {
                 Err($crate::vm::errors::memory_errors::MemoryError::UnallocatedSegment(_))
             ) {
                 if $si < 0 {
-                    $mem.temp_data.push($crate::stdlib::vec::Vec::new())
+                    $crate::vm::vm_memory::memory_backend::MemoryBackend::add_segment(
+                        &mut $mem.temp_data,
+                    )
                 } else {
-                    $mem.data.push($crate::stdlib::vec::Vec::new());
+                    $crate::vm::vm_memory::memory_backend::MemoryBackend::add_segment(
+                        &mut $mem.data,
+                    );
                 }
                 res = $mem.insert(k, v);
             }
@@ -252,19 +263,23 @@ pub mod test_utils {
             crate::vm::runners::cairo_runner::CairoRunner::new(
                 &$program,
                 crate::types::layout_name::LayoutName::all_cairo,
+                None,
                 false,
                 false,
             )
             .unwrap()
         };
         ($program:expr, $layout:expr) => {
-            crate::vm::runners::cairo_runner::CairoRunner::new(&$program, $layout, false, false)
-                .unwrap()
+            crate::vm::runners::cairo_runner::CairoRunner::new(
+                &$program, $layout, None, false, false,
+            )
+            .unwrap()
         };
         ($program:expr, $layout:expr, $proof_mode:expr) => {
             crate::vm::runners::cairo_runner::CairoRunner::new(
                 &$program,
                 $layout,
+                None,
                 $proof_mode,
                 false,
             )
@@ -274,6 +289,7 @@ pub mod test_utils {
             crate::vm::runners::cairo_runner::CairoRunner::new(
                 &$program,
                 $layout,
+                None,
                 $proof_mode,
                 $trace_enabled,
             )
@@ -457,6 +473,61 @@ pub mod test_utils {
         }
     }
 
+    /// The program run by cairo_runner's `initialize_and_run_output_range_check_builtin` test,
+    /// which uses the output & range_check builtins.
+    /// `serialize_word` starts at pc 0, `check_range` at pc 4 and `main` at pc 13
+    pub(crate) fn output_range_check_program() -> Program {
+        let function = |pc| Identifier {
+            pc: Some(pc),
+            type_: Some(String::from("function")),
+            value: None,
+            full_name: None,
+            members: None,
+            cairo_type: None,
+        };
+        program!(
+            builtins = vec![BuiltinName::output, BuiltinName::range_check],
+            data = vec_data!(
+                (4612671182993129469_i64),
+                (5198983563776393216_i64),
+                (1),
+                (2345108766317314046_i64),
+                (4612671182993129469_i64),
+                (5189976364521848832_i64),
+                (18446744073709551615_i128),
+                (5199546496550207487_i64),
+                (4612389712311386111_i64),
+                (5198983563776393216_i64),
+                (2),
+                (5191102247248822272_i64),
+                (2345108766317314046_i64),
+                (5191102247248822272_i64),
+                (5189976364521848832_i64),
+                (7),
+                (1226245742482522112_i64),
+                ((
+                    "3618502788666131213697322783095070105623107215331596699973092056135872020469",
+                    10
+                )),
+                (5191102242953854976_i64),
+                (5193354051357474816_i64),
+                (1226245742482522112_i64),
+                ((
+                    "3618502788666131213697322783095070105623107215331596699973092056135872020461",
+                    10
+                )),
+                (5193354029882638336_i64),
+                (2345108766317314046_i64)
+            ),
+            identifiers = HashMap::from([
+                (String::from("__main__.serialize_word"), function(0)),
+                (String::from("__main__.check_range"), function(4)),
+                (String::from("__main__.main"), function(13)),
+            ]),
+            main = Some(13),
+        )
+    }
+
     macro_rules! exec_scopes_ref {
         () => {
             &mut crate::types::exec_scope::ExecutionScopes::new()
@@ -660,13 +731,14 @@ mod test {
     use wasm_bindgen_test::*;
 
     use super::*;
+    use crate::vm::vm_memory::memory_backend::MemoryBackend;
 
     #[test]
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     fn memory_macro_test() {
         let mut memory = Memory::new();
         for _ in 0..2 {
-            memory.data.push(Vec::new());
+            memory.data.add_segment();
         }
         memory
             .insert(
@@ -678,7 +750,10 @@ mod test {
             .insert(Relocatable::from((1, 1)), &MaybeRelocatable::from((1, 0)))
             .unwrap();
         let mem = memory![((1, 2), 1), ((1, 1), (1, 0))];
-        assert_eq!(memory.data, mem.data);
+        assert_eq!(
+            memory.data.iter_values().collect::<Vec<_>>(),
+            mem.data.iter_values().collect::<Vec<_>>()
+        );
     }
 
     #[test]
@@ -686,7 +761,7 @@ mod test {
     fn check_memory_macro_test() {
         let mut memory = Memory::new();
         for _ in 0..2 {
-            memory.data.push(Vec::new());
+            memory.data.add_segment();
         }
         memory
             .insert(Relocatable::from((1, 1)), &MaybeRelocatable::from((1, 0)))
@@ -707,7 +782,7 @@ mod test {
     fn check_memory_address_macro_test() {
         let mut memory = Memory::new();
         for _ in 0..2 {
-            memory.data.push(Vec::new());
+            memory.data.add_segment();
         }
         memory
             .insert(Relocatable::from((1, 1)), &MaybeRelocatable::from((1, 0)))
@@ -784,7 +859,7 @@ mod test {
         add_segments!(vm, 1);
         assert_matches::assert_matches!(run_hint!(vm, HashMap::new(), hint_code), Ok(()));
         //A segment is added
-        assert_eq!(vm.segments.memory.data.len(), 2);
+        assert_eq!(vm.segments.num_segments(), 2);
     }
 
     #[test]
@@ -0,0 +1,449 @@
+use crate::{
+    math_utils::signed_felt,
+    serde::deserialize_program::HintParams,
+    stdlib::{
+        collections::{BTreeMap, HashMap},
+        fmt,
+        prelude::*,
+    },
+    types::{
+        instruction::{ApUpdate, Instruction, Op1Addr, Opcode, PcUpdate, Register, Res},
+        program::Program,
+        relocatable::MaybeRelocatable,
+    },
+    vm::decoding::decoder::decode_instruction,
+    Felt252,
+};
+use num_traits::ToPrimitive;
+
+/// The contents of a program word (or pair of words, for instructions with an immediate).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DisassembledCode {
+    Instruction {
+        instruction: Instruction,
+        imm: Option<Felt252>,
+    },
+    /// A word that can't be decoded as an instruction, displayed as a `dw` directive.
+    Data(MaybeRelocatable),
+}
+
+impl DisassembledCode {
+    pub fn size(&self) -> usize {
+        match self {
+            DisassembledCode::Instruction { instruction, .. } => instruction.size(),
+            DisassembledCode::Data(_) => 1,
+        }
+    }
+
+    /// The pc an instruction jumps to (or calls), when it can be known statically.
+    pub fn jump_target(&self, pc: usize) -> Option<usize> {
+        let DisassembledCode::Instruction {
+            instruction,
+            imm: Some(imm),
+        } = self
+        else {
+            return None;
+        };
+        if !matches!(instruction.opcode, Opcode::NOp | Opcode::Call)
+            || !matches!(instruction.res, Res::Op1 | Res::Unconstrained)
+        {
+            return None;
+        }
+        match instruction.pc_update {
+            PcUpdate::Jump => imm.to_usize(),
+            PcUpdate::JumpRel | PcUpdate::Jnz => (signed_felt(*imm) + pc).to_usize(),
+            PcUpdate::Regular => None,
+        }
+    }
+}
+
+// Formats a memory access such as [ap], [fp + -3] or [[ap + 1] + 2]
+struct Access<'a>(&'a str, isize);
+
+impl fmt::Display for Access<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.1 {
+            0 => write!(f, "[{}]", self.0),
+            offset => write!(f, "[{} + {offset}]", self.0),
+        }
+    }
+}
+
+fn register_name(register: Register) -> &'static str {
+    match register {
+        Register::AP => "ap",
+        Register::FP => "fp",
+    }
+}
+
+impl fmt::Display for DisassembledCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (instruction, imm) = match self {
+            DisassembledCode::Instruction { instruction, imm } => (instruction, imm),
+            DisassembledCode::Data(value) => return write!(f, "dw {value}"),
+        };
+        let dst = Access(register_name(instruction.dst_register), instruction.off0).to_string();
+        let op0 = Access(register_name(instruction.op0_register), instruction.off1).to_string();
+        let op1 = match (instruction.op1_addr, imm) {
+            (Op1Addr::Imm, Some(imm)) => signed_felt(*imm).to_string(),
+            (Op1Addr::Imm, None) => "<missing immediate>".to_string(),
+            (Op1Addr::AP, _) => Access("ap", instruction.off2).to_string(),
+            (Op1Addr::FP, _) => Access("fp", instruction.off2).to_string(),
+            (Op1Addr::Op0, _) => Access(&op0, instruction.off2).to_string(),
+        };
+        let res = match instruction.res {
+            Res::Op1 | Res::Unconstrained => op1.clone(),
+            Res::Add => format!("{op0} + {op1}"),
+            Res::Mul => format!("{op0} * {op1}"),
+        };
+
+        match (instruction.opcode, instruction.pc_update) {
+            (Opcode::AssertEq, _) => write!(f, "{dst} = {res}")?,
+            (Opcode::Call, PcUpdate::JumpRel) => write!(f, "call rel {res}")?,
+            (Opcode::Call, _) => write!(f, "call abs {res}")?,
+            (Opcode::Ret, _) => write!(f, "ret")?,
+            (Opcode::NOp, PcUpdate::Jump) => write!(f, "jmp abs {res}")?,
+            (Opcode::NOp, PcUpdate::JumpRel) => write!(f, "jmp rel {res}")?,
+            (Opcode::NOp, PcUpdate::Jnz) => write!(f, "jmp rel {op1} if {dst} != 0")?,
+            (Opcode::NOp, PcUpdate::Regular) if instruction.ap_update == ApUpdate::Add => {
+                return write!(f, "ap += {res}")
+            }
+            (Opcode::NOp, PcUpdate::Regular) => write!(f, "nop")?,
+        }
+        match instruction.ap_update {
+            ApUpdate::Add1 => write!(f, ", ap++"),
+            ApUpdate::Add => write!(f, ", ap += {res}"),
+            ApUpdate::Regular | ApUpdate::Add2 => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisassembledLine {
+    pub pc: usize,
+    /// Functions & labels starting at this pc.
+    pub labels: Vec<String>,
+    /// Code of the hints run before this pc.
+    pub hints: Vec<String>,
+    pub code: DisassembledCode,
+    /// Functions & labels at the pc of the immediate of this line's instruction, which doesn't
+    /// start an instruction, along with that pc.
+    pub inner_labels: Vec<(usize, String)>,
+    /// Code of the hints attached to the pc of the immediate of this line's instruction, along
+    /// with that pc.
+    pub inner_hints: Vec<(usize, String)>,
+}
+
+/// A program rendered as Cairo assembly, one line per instruction.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Disassembly {
+    pub lines: Vec<DisassembledLine>,
+}
+
+impl Disassembly {
+    /// Disassembles the data of a program, annotating it with the functions & labels in its
+    /// identifiers and with its hints. Programs without identifiers, such as the ones obtained
+    /// from Cairo PIEs via [`Program::from_stripped_program`], only get their `main` labeled.
+    pub fn from_program(program: &Program) -> Disassembly {
+        let mut labels: HashMap<usize, Vec<String>> = HashMap::new();
+        for (name, identifier) in program.iter_identifiers() {
+            if let (Some("function" | "label"), Some(pc)) =
+                (identifier.type_.as_deref(), identifier.pc)
+            {
+                labels.entry(pc).or_default().push(name.to_string());
+            }
+        }
+        if let (true, Some(main)) = (labels.is_empty(), program.shared_program_data.main) {
+            labels.insert(main, vec!["main".to_string()]);
+        }
+        let mut hints: BTreeMap<usize, Vec<HintParams>> =
+            (&program.shared_program_data.hints_collection).into();
+
+        let data = &program.shared_program_data.data;
+        let mut lines = Vec::new();
+        let mut pc = 0;
+        while pc < data.len() {
+            let code = decode_code(&data[pc..]);
+            let mut take_labels = |pc| {
+                let mut pc_labels = labels.remove(&pc).unwrap_or_default();
+                pc_labels.sort();
+                pc_labels
+            };
+            let mut take_hints = |pc| {
+                hints
+                    .remove(&pc)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|hint| hint.code)
+            };
+            let inner_pcs = pc + 1..pc + code.size();
+            lines.push(DisassembledLine {
+                pc,
+                labels: take_labels(pc),
+                hints: take_hints(pc).collect(),
+                inner_labels: inner_pcs
+                    .clone()
+                    .flat_map(|inner_pc| {
+                        take_labels(inner_pc)
+                            .into_iter()
+                            .map(move |label| (inner_pc, label))
+                    })
+                    .collect(),
+                inner_hints: inner_pcs
+                    .flat_map(|inner_pc| take_hints(inner_pc).map(move |hint| (inner_pc, hint)))
+                    .collect(),
+                code,
+            });
+            pc += lines[lines.len() - 1].code.size();
+        }
+        Disassembly { lines }
+    }
+}
+
+// Decodes the instruction at the start of the words given, or returns the first word as data
+fn decode_code(words: &[MaybeRelocatable]) -> DisassembledCode {
+    let instruction = match words[0]
+        .get_int_ref()
+        .and_then(|word| word.to_u64())
+        .and_then(|word| decode_instruction(word).ok())
+    {
+        Some(instruction) => instruction,
+        None => return DisassembledCode::Data(words[0].clone()),
+    };
+    match (instruction.op1_addr, words.get(1)) {
+        (Op1Addr::Imm, Some(MaybeRelocatable::Int(imm))) => DisassembledCode::Instruction {
+            instruction,
+            imm: Some(*imm),
+        },
+        (Op1Addr::Imm, _) => DisassembledCode::Data(words[0].clone()),
+        _ => DisassembledCode::Instruction {
+            instruction,
+            imm: None,
+        },
+    }
+}
+
+impl fmt::Display for Disassembly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let labels: HashMap<usize, &Vec<String>> = self
+            .lines
+            .iter()
+            .filter(|line| !line.labels.is_empty())
+            .map(|line| (line.pc, &line.labels))
+            .collect();
+        for line in self.lines.iter() {
+            for label in line.labels.iter() {
+                writeln!(f, "{label}:")?;
+            }
+            for hint in line.hints.iter() {
+                writeln!(f, "    %{{")?;
+                for hint_line in hint.lines() {
+                    writeln!(f, "        {hint_line}")?;
+                }
+                writeln!(f, "    %}}")?;
+            }
+            write!(f, "{:>6}: {}", line.pc, line.code)?;
+            match line
+                .code
+                .jump_target(line.pc)
+                .map(|target| (target, labels.get(&target)))
+            {
+                Some((_, Some(target_labels))) => write!(f, "  // {}", target_labels.join(", "))?,
+                Some((target, None)) => write!(f, "  // pc {target}")?,
+                None => {}
+            }
+            writeln!(f)?;
+            // Jumping to these pcs, or running these hints, would happen in the middle of the instruction
+            for (pc, label) in line.inner_labels.iter() {
+                writeln!(f, "        // {label}: at pc {pc}, inside the instruction")?;
+            }
+            for (pc, hint) in line.inner_hints.iter() {
+                writeln!(f, "        // %{{ at pc {pc}, inside the instruction")?;
+                for hint_line in hint.lines() {
+                    writeln!(f, "        //     {hint_line}")?;
+                }
+                writeln!(f, "        // %}}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serde::deserialize_program::{ApTracking, FlowTrackingData, Identifier};
+    use crate::types::program::Program;
+    use crate::utils::test_utils::*;
+    use crate::vm::runners::cairo_pie::StrippedProgram;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn code(encoded_instruction: u64, imm: Option<i64>) -> String {
+        let instruction = decode_instruction(encoded_instruction).unwrap();
+        DisassembledCode::Instruction {
+            instruction,
+            imm: imm.map(Felt252::from),
+        }
+        .to_string()
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn display_instructions() {
+        assert_eq!(code(0x480a7ffd7fff8000, None), "[ap] = [fp + -3], ap++");
+        assert_eq!(
+            code(0x482680017ffd8000, Some(5)),
+            "[ap] = [fp + -3] + 5, ap++"
+        );
+        assert_eq!(code(0x400380007ffc7ffd, None), "[fp + -3] = [[fp + -4]]");
+        assert_eq!(code(0x1104800180018000, Some(-4)), "call rel -4");
+        assert_eq!(code(0x10780017fff7fff, Some(7)), "jmp rel 7");
+        assert_eq!(
+            code(0x20780017fff7ffd, Some(5)),
+            "jmp rel 5 if [fp + -3] != 0"
+        );
+        assert_eq!(code(0x208b7fff7fff7ffe, None), "ret");
+        assert_eq!(code(0x40780017fff7fff, Some(2)), "ap += 2");
+        assert_eq!(code(0x8780017fff7fff, Some(3)), "jmp abs 3");
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn disassemble_program() {
+        let program = program!(
+            data = vec_data!(
+                (0x480680017fff8000_u64),
+                (10),
+                (0x1104800180018000_u64),
+                (2),
+                (0x208b7fff7fff7ffe_u64),
+                ((1, 0))
+            ),
+            main = Some(0),
+            identifiers = HashMap::from([
+                (
+                    "__main__.main".to_string(),
+                    Identifier {
+                        pc: Some(0),
+                        type_: Some("function".to_string()),
+                        value: None,
+                        full_name: None,
+                        members: None,
+                        cairo_type: None,
+                    },
+                ),
+                (
+                    "__main__.end".to_string(),
+                    Identifier {
+                        pc: Some(4),
+                        type_: Some("label".to_string()),
+                        value: None,
+                        full_name: None,
+                        members: None,
+                        cairo_type: None,
+                    },
+                ),
+            ]),
+            hints = BTreeMap::from([(
+                0,
+                vec![HintParams {
+                    code: "memory[ap] = 1\nx = 2".to_string(),
+                    accessible_scopes: vec![],
+                    flow_tracking_data: FlowTrackingData {
+                        ap_tracking: ApTracking::default(),
+                        reference_ids: HashMap::new(),
+                    },
+                }],
+            )]),
+        );
+        let disassembly = Disassembly::from_program(&program);
+        assert_eq!(
+            disassembly
+                .lines
+                .iter()
+                .map(|line| line.pc)
+                .collect::<Vec<_>>(),
+            vec![0, 2, 4, 5]
+        );
+        assert_eq!(
+            disassembly.to_string(),
+            "__main__.main:\n    %{\n        memory[ap] = 1\n        x = 2\n    %}\n     0: [ap] = 10, ap++\n     2: call rel 2  // __main__.end\n__main__.end:\n     4: ret\n     5: dw 1:0\n"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn disassemble_labels_and_hints_inside_instructions() {
+        let program = program!(
+            data = vec_data!((0x480680017fff8000_u64), (10), (0x208b7fff7fff7ffe_u64)),
+            main = Some(0),
+            identifiers = HashMap::from([(
+                "__main__.inner".to_string(),
+                Identifier {
+                    pc: Some(1),
+                    type_: Some("label".to_string()),
+                    value: None,
+                    full_name: None,
+                    members: None,
+                    cairo_type: None,
+                },
+            )]),
+            hints = BTreeMap::from([(
+                1,
+                vec![HintParams {
+                    code: "memory[ap] = 1\nx = 2".to_string(),
+                    accessible_scopes: vec![],
+                    flow_tracking_data: FlowTrackingData {
+                        ap_tracking: ApTracking::default(),
+                        reference_ids: HashMap::new(),
+                    },
+                }],
+            )]),
+        );
+        let disassembly = Disassembly::from_program(&program);
+        assert_eq!(
+            disassembly.lines[0].inner_labels,
+            vec![(1, "__main__.inner".to_string())]
+        );
+        assert_eq!(
+            disassembly.lines[0].inner_hints,
+            vec![(1, "memory[ap] = 1\nx = 2".to_string())]
+        );
+        assert!(disassembly.lines[1].inner_labels.is_empty());
+        assert_eq!(
+            disassembly.to_string(),
+            "     0: [ap] = 10, ap++\n        // __main__.inner: at pc 1, inside the instruction\n        // %{ at pc 1, inside the instruction\n        //     memory[ap] = 1\n        //     x = 2\n        // %}\n     2: ret\n"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn disassemble_stripped_program() {
+        let stripped_program = StrippedProgram {
+            data: vec_data!((0x10780017fff7fff_u64), (5), (0x208b7fff7fff7ffe_u64)),
+            builtins: vec![],
+            main: 2,
+            prime: (),
+        };
+        let disassembly =
+            Disassembly::from_program(&Program::from_stripped_program(&stripped_program));
+        assert_eq!(
+            disassembly.to_string(),
+            "     0: jmp rel 5  // pc 5\nmain:\n     2: ret\n"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn disassemble_missing_immediate() {
+        let program = program!(data = vec_data!((0x480680017fff8000_u64)),);
+        assert_eq!(
+            Disassembly::from_program(&program).lines[0].code,
+            DisassembledCode::Data(MaybeRelocatable::from(Felt252::from(
+                0x480680017fff8000_u64
+            )))
+        );
+    }
+}
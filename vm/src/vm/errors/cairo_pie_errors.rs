@@ -1,6 +1,9 @@
+// The `(*.0).0` syntax of thiserror falsely triggers this clippy warning
+#![allow(clippy::explicit_auto_deref)]
+
 use thiserror_no_std::Error;
 
-use crate::types::builtin_name::BuiltinName;
+use crate::types::{builtin_name::BuiltinName, layout_name::LayoutName};
 
 #[derive(Eq, Hash, PartialEq, Debug, Error)]
 pub enum CairoPieValidationError {
@@ -28,6 +31,10 @@ pub enum CairoPieValidationError {
     InvalidExtraSegmentIndex,
     #[error("Invalid address")]
     InvalidAddress,
+    #[error("No Cairo PIEs were given")]
+    NoCairoPies,
+    #[error("The {} builtin isn't included in the {} layout", (*.0).0, (*.0).1)]
+    BuiltinNotInLayout(Box<(BuiltinName, LayoutName)>),
     #[error("Cairo PIE diff: metadata mismatch")]
     DiffMetadata,
     #[error("Cairo PIE diff: memory mismatch")]
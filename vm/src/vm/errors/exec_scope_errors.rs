@@ -1,3 +1,4 @@
+use crate::stdlib::prelude::*;
 use thiserror_no_std::Error;
 
 #[derive(Eq, Hash, PartialEq, Debug, Error)]
@@ -6,4 +7,6 @@ pub enum ExecScopeError {
     ExitMainScopeError,
     #[error("Every enter_scope() requires a corresponding exit_scope().")]
     NoScopeError,
+    #[error("Variable {0} can't be forked, its type should implement ForkableScopeValue and be registered via ExecutionScopes::register_forkable_type")]
+    UnforkableVariable(Box<str>),
 }
@@ -8,7 +8,10 @@ use thiserror_no_std::Error;
 use crate::Felt252;
 use num_bigint::{BigInt, BigUint};
 
+use crate::fact_topology::FactTopologyError;
+use crate::program_hash::ProgramHashError;
 use crate::types::{
+    builtin_name::BuiltinName,
     errors::math_errors::MathError,
     relocatable::{MaybeRelocatable, Relocatable},
 };
@@ -184,6 +187,30 @@ pub enum HintError {
     NPairBitsTooLowM,
     #[error("{0}")]
     SyscallError(Box<str>),
+    #[error(transparent)]
+    ProgramHash(#[from] ProgramHashError),
+    #[error("Task program has no main entrypoint")]
+    TaskProgramNoMain,
+    #[error("Computed program hash {} does not match input {}", (*.0).0, (*.0).1)]
+    ProgramHashMismatch(Box<(Felt252, Felt252)>),
+    #[error("Program address {} does not match the address the program was loaded at: {}", (*.0).0, (*.0).1)]
+    ProgramAddressMismatch(Box<(Relocatable, Relocatable)>),
+    #[error("Usage of builtin {} is inconsistent with the CairoPie: expected {}, got {}", (*.0).0, (*.0).1, (*.0).2)]
+    BuiltinUsageMismatch(Box<(BuiltinName, usize, usize)>),
+    #[error(transparent)]
+    FactTopology(Box<FactTopologyError>),
+    #[error("Expected packed output to be composite")]
+    NotCompositePackedOutput,
+    #[error("Loading the hints of a task program requires the extensive_hints feature")]
+    TaskHintsNotLoadable,
+    #[error("Failed to write fact topologies file: {0}")]
+    FactTopologiesFile(Box<str>),
+}
+
+impl From<FactTopologyError> for HintError {
+    fn from(error: FactTopologyError) -> Self {
+        HintError::FactTopology(Box::new(error))
+    }
 }
 
 #[cfg(test)]
@@ -97,10 +97,20 @@ pub enum MemoryError {
     InvalidUsedSizeSegmentArena,
     #[error("Vector capacity exceeded")]
     VecCapacityExceeded,
+    #[error("Can't allocate more than {0} memory cells")]
+    MemoryCellsLimit(usize),
+    #[error("Memory backend can't be forked")]
+    UnforkableMemoryBackend,
     #[error("Memory wasn't relocated")]
     UnrelocatedMemory,
     #[error("Malformed public memory")]
     MalformedPublicMemory,
+    #[error("Can't load a program with an empty header")]
+    EmptyProgramHeader,
+    #[error("The memory backend failed to access its storage: {0}")]
+    MemoryBackendStorage(Box<str>),
+    #[error("Can't restore the snapshot's cell at {0}, which is beyond the length of its segment")]
+    SnapshotCellOutOfBounds(Box<Relocatable>),
 }
 
 #[derive(Debug, PartialEq, Eq, Error)]
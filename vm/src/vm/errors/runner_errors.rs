@@ -6,7 +6,9 @@ use crate::types::builtin_name::BuiltinName;
 use crate::types::layout_name::LayoutName;
 use thiserror_no_std::Error;
 
-use super::{memory_errors::MemoryError, trace_errors::TraceError};
+use super::{
+    exec_scope_errors::ExecScopeError, memory_errors::MemoryError, trace_errors::TraceError,
+};
 use crate::types::{errors::math_errors::MathError, relocatable::Relocatable};
 use crate::Felt252;
 
@@ -132,6 +134,20 @@ pub enum RunnerError {
     CairoPieProofMode,
     #[error("{0}: Invalid additional data")]
     InvalidAdditionalData(BuiltinName),
+    #[error("The dynamic layout requires layout params")]
+    MissingDynamicLayoutParams,
+    #[error("Invalid dynamic layout params: {0}")]
+    InvalidDynamicLayoutParams(Box<str>),
+    #[error("The runner snapshot doesn't match the given program")]
+    SnapshotProgramMismatch,
+    #[error("Execution scope variable {0} can't be stored in a runner snapshot")]
+    UnsupportedSnapshotScopeValue(Box<str>),
+    #[error("The runner snapshot of builtin {0} is missing its instance definition")]
+    SnapshotMissingModParams(BuiltinName),
+    #[error("The compiled program doesn't match the program of the runner")]
+    CompiledProgramMismatch,
+    #[error(transparent)]
+    ExecScope(#[from] ExecScopeError),
 }
 
 #[cfg(test)]
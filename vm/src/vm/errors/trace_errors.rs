@@ -14,6 +14,8 @@ pub enum TraceError {
     MemoryError(#[from] MemoryError),
     #[error("Trace not relocated")]
     TraceNotRelocated,
+    #[error("The program segment's size changed after the trace started being written")]
+    ProgramSegmentSizeChanged,
 }
 
 #[cfg(test)]
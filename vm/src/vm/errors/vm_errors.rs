@@ -104,6 +104,10 @@ pub enum VirtualMachineError {
     SliceToArrayError,
     #[error("Failed to compile hint: {0}")]
     CompileHintFail(Box<str>),
+    #[error("Hint is not whitelisted: {0}")]
+    HintNotWhitelisted(Box<str>),
+    #[error("Reference {} is not whitelisted for hint: {}", (*.0).1, (*.0).0)]
+    ForbiddenHintReference(Box<(String, String)>),
     #[error("op1_addr is Op1Addr.IMM, but no immediate was given")]
     NoImm,
     #[error("Execution reached the end of the program. Requested remaining steps: {0}.")]
@@ -112,6 +116,12 @@ pub enum VirtualMachineError {
     StepsLimit(u64),
     #[error("Could not reach the end of the program. RunResources has no remaining steps.")]
     UnfinishedExecution,
+    #[error("Could not reach the end of the program. The run allocated more than {0} segments.")]
+    SegmentsLimit(usize),
+    #[error("Could not reach the end of the program. The run used more than {} instances of the {} builtin.", (*.0).1, (*.0).0)]
+    BuiltinInstancesLimit(Box<(BuiltinName, usize)>),
+    #[error("Could not reach the end of the program. RunResources has no remaining hint time.")]
+    HintTimeLimit,
     #[error("Current run is not finished")]
     RunNotFinished,
     #[error("Invalid argument count, expected {} but got {}", (*.0).0, (*.0).1)]
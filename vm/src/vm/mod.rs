@@ -3,10 +3,11 @@ pub mod decoding;
 pub mod errors;
 pub mod runners;
 pub mod security;
+pub mod symbolic;
 pub mod trace;
 pub mod vm_core;
 pub mod vm_memory;
 
-#[cfg(feature = "test_utils")]
-#[cfg_attr(docsrs, doc(cfg(feature = "test_utils")))]
+#[cfg(feature = "hooks")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hooks")))]
 pub mod hooks;
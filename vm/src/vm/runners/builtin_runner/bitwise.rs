@@ -8,7 +8,9 @@ use crate::{
     },
     vm::{
         errors::{memory_errors::MemoryError, runner_errors::RunnerError},
-        vm_memory::{memory::Memory, memory_segments::MemorySegmentManager},
+        vm_memory::{
+            memory::Memory, memory_backend::MemoryBackend, memory_segments::MemorySegmentManager,
+        },
     },
 };
 use num_integer::div_ceil;
@@ -141,8 +143,7 @@ impl BitwiseBuiltinRunner {
 
     pub fn air_private_input(&self, memory: &Memory) -> Vec<PrivateInput> {
         let mut private_inputs = vec![];
-        if let Some(segment) = memory.data.get(self.base) {
-            let segment_len = segment.len();
+        if let Some(segment_len) = memory.data.segment_len(self.base) {
             for (index, off) in (0..segment_len)
                 .step_by(CELLS_PER_BITWISE as usize)
                 .enumerate()
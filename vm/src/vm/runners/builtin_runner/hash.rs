@@ -7,6 +7,7 @@ use crate::vm::errors::memory_errors::MemoryError;
 use crate::vm::errors::runner_errors::RunnerError;
 use crate::vm::runners::cairo_pie::BuiltinAdditionalData;
 use crate::vm::vm_memory::memory::Memory;
+use crate::vm::vm_memory::memory_backend::MemoryBackend;
 use crate::vm::vm_memory::memory_segments::MemorySegmentManager;
 use num_integer::{div_ceil, Integer};
 use starknet_types_core::hash::StarkHash;
@@ -143,8 +144,7 @@ impl HashBuiltinRunner {
 
     pub fn air_private_input(&self, memory: &Memory) -> Vec<PrivateInput> {
         let mut private_inputs = vec![];
-        if let Some(segment) = memory.data.get(self.base) {
-            let segment_len = segment.len();
+        if let Some(segment_len) = memory.data.segment_len(self.base) {
             for (index, off) in (0..segment_len)
                 .step_by(CELLS_PER_HASH as usize)
                 .enumerate()
@@ -9,6 +9,7 @@ use crate::types::relocatable::{MaybeRelocatable, Relocatable};
 use crate::vm::errors::memory_errors::MemoryError;
 use crate::vm::errors::runner_errors::RunnerError;
 use crate::vm::vm_memory::memory::Memory;
+use crate::vm::vm_memory::memory_backend::MemoryBackend;
 use crate::vm::vm_memory::memory_segments::MemorySegmentManager;
 use crate::Felt252;
 use lazy_static::lazy_static;
@@ -164,8 +165,7 @@ impl KeccakBuiltinRunner {
 
     pub fn air_private_input(&self, memory: &Memory) -> Vec<PrivateInput> {
         let mut private_inputs = vec![];
-        if let Some(segment) = memory.data.get(self.base) {
-            let segment_len = segment.len();
+        if let Some(segment_len) = memory.data.segment_len(self.base) {
             for (index, off) in (0..segment_len)
                 .step_by(CELLS_PER_KECCAK as usize)
                 .enumerate()
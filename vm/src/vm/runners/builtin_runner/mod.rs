@@ -27,6 +27,7 @@ use crate::vm::errors::runner_errors::RunnerError;
 use crate::vm::errors::vm_errors::VirtualMachineError;
 use crate::vm::vm_core::VirtualMachine;
 use crate::vm::vm_memory::memory::Memory;
+use crate::vm::vm_memory::memory_backend::MemoryBackend;
 use crate::vm::vm_memory::memory_segments::MemorySegmentManager;
 
 mod bitwise;
@@ -48,7 +49,7 @@ pub use hash::HashBuiltinRunner;
 pub use keccak::KeccakBuiltinRunner;
 pub use modulo::ModBuiltinRunner;
 use num_integer::div_floor;
-pub use output::OutputBuiltinRunner;
+pub use output::{OutputBuiltinRunner, OutputBuiltinState};
 pub use poseidon::PoseidonBuiltinRunner;
 pub use range_check::RangeCheckBuiltinRunner;
 pub use segment_arena::SegmentArenaBuiltinRunner;
@@ -203,7 +204,7 @@ impl BuiltinRunner {
     }
 
     /// Returns if the builtin is included in the program builtins
-    fn included(&self) -> bool {
+    pub(crate) fn included(&self) -> bool {
         match *self {
             BuiltinRunner::Bitwise(ref bitwise) => bitwise.included,
             BuiltinRunner::EcOp(ref ec) => ec.included,
@@ -252,6 +253,14 @@ impl BuiltinRunner {
         }
     }
 
+    /// Returns an independent copy of the runner, used to fork the vm
+    pub(crate) fn fork(&self) -> BuiltinRunner {
+        match self {
+            BuiltinRunner::Signature(signature) => BuiltinRunner::Signature(signature.fork()),
+            _ => self.clone(),
+        }
+    }
+
     pub fn add_validation_rule(&self, memory: &mut Memory) {
         match *self {
             BuiltinRunner::RangeCheck(ref range_check) => range_check.add_validation_rule(memory),
@@ -362,7 +371,7 @@ impl BuiltinRunner {
         }
     }
 
-    fn cells_per_instance(&self) -> u32 {
+    pub(crate) fn cells_per_instance(&self) -> u32 {
         match self {
             BuiltinRunner::Bitwise(_) => CELLS_PER_BITWISE,
             BuiltinRunner::EcOp(_) => CELLS_PER_EC_OP,
@@ -426,17 +435,21 @@ impl BuiltinRunner {
         let n_input_cells = self.n_input_cells() as usize;
         let builtin_segment_index = self.base();
         // If the builtin's segment is empty, there are no security checks to run
-        let builtin_segment = match vm.segments.memory.data.get(builtin_segment_index) {
-            Some(segment) if !segment.is_empty() => segment,
+        let memory_data = &vm.segments.memory.data;
+        let segment_len = match memory_data.segment_len(builtin_segment_index) {
+            Some(segment_len) if segment_len != 0 => segment_len,
             _ => return Ok(()),
         };
         // The builtin segment's size - 1 is the maximum offset within the segment's addresses
         // Assumption: The last element is not a None value
         // It is safe to asume this for normal program execution
         // If there are trailing None values at the end, the following security checks will fail
-        let offset_max = builtin_segment.len().saturating_sub(1);
+        let offset_max = segment_len.saturating_sub(1);
         // offset_len is the amount of non-None values in the segment
-        let offset_len = builtin_segment.iter().filter(|x| x.is_some()).count();
+        let offset_len = memory_data
+            .cells(builtin_segment_index)
+            .filter(|(_, x)| x.is_some())
+            .count();
         let n = match offset_len {
             0 => 0,
             _ => div_floor(offset_max, cells_per_instance) + 1,
@@ -452,8 +465,8 @@ impl BuiltinRunner {
         for i in 0..n {
             for j in 0..n_input_cells {
                 let offset = cells_per_instance * i + j;
-                if builtin_segment
-                    .get(offset)
+                if memory_data
+                    .get(builtin_segment_index, offset)
                     .filter(|x| x.is_some())
                     .is_none()
                 {
@@ -473,8 +486,8 @@ impl BuiltinRunner {
         for i in 0..n {
             for j in n_input_cells..cells_per_instance {
                 let offset = cells_per_instance * i + j;
-                if builtin_segment
-                    .get(offset)
+                if memory_data
+                    .get(builtin_segment_index, offset)
                     .filter(|x| x.is_some())
                     .is_none()
                 {
@@ -573,6 +586,31 @@ impl BuiltinRunner {
         }
     }
 
+    /// Sets the builtin's base to an already existing segment.
+    /// Used when restoring a runner from a snapshot, where the builtin segments are loaded
+    /// alongside the rest of the memory instead of being created by `initialize_segments`
+    pub(crate) fn set_base(&mut self, base: usize, segments: &MemorySegmentManager) {
+        match self {
+            BuiltinRunner::Bitwise(ref mut bitwise) => bitwise.base = base,
+            BuiltinRunner::EcOp(ref mut ec) => ec.base = base,
+            BuiltinRunner::Hash(ref mut hash) => hash.base = base,
+            BuiltinRunner::Output(ref mut output) => output.base = base,
+            BuiltinRunner::RangeCheck(ref mut range_check) => range_check.base = base,
+            BuiltinRunner::RangeCheck96(ref mut range_check) => range_check.base = base,
+            BuiltinRunner::Keccak(ref mut keccak) => keccak.base = base,
+            BuiltinRunner::Signature(ref mut signature) => signature.base = base,
+            BuiltinRunner::Poseidon(ref mut poseidon) => poseidon.base = base,
+            BuiltinRunner::SegmentArena(ref mut segment_arena) => {
+                segment_arena.base =
+                    Relocatable::from((base as isize, segment_arena::INITIAL_SEGMENT_SIZE))
+            }
+            BuiltinRunner::Mod(ref mut modulo) => {
+                modulo.base = base;
+                modulo.zero_segment_index = segments.zero_segment_index;
+            }
+        }
+    }
+
     pub(crate) fn stop_ptr(&self) -> Option<usize> {
         match self {
             BuiltinRunner::Bitwise(ref bitwise) => bitwise.stop_ptr,
@@ -1190,7 +1228,7 @@ mod tests {
         let builtin = BuiltinRunner::Bitwise(BitwiseBuiltinRunner::new(Some(256), true));
         let mut vm = vm!();
 
-        vm.segments.memory.data = vec![vec![]];
+        vm.segments.memory.data = vec![vec![]].into();
 
         assert_matches!(builtin.run_security_checks(&vm), Ok(()));
     }
@@ -1327,7 +1365,8 @@ mod tests {
 
         let mut vm = vm!();
 
-        vm.segments.memory.data = vec![vec![MemoryCell::NONE, MemoryCell::NONE, MemoryCell::NONE]];
+        vm.segments.memory.data =
+            vec![vec![MemoryCell::NONE, MemoryCell::NONE, MemoryCell::NONE]].into();
 
         assert_matches!(builtin.run_security_checks(&vm), Ok(()));
     }
@@ -1363,7 +1402,7 @@ mod tests {
 
         let mut vm = vm!();
         // The values stored in memory are not relevant for this test
-        vm.segments.memory.data = vec![vec![]];
+        vm.segments.memory.data = vec![vec![]].into();
 
         assert_matches!(builtin.run_security_checks(&vm), Ok(()));
     }
@@ -38,11 +38,11 @@ const N_OFFSET: u32 = 6;
 #[derive(Debug, Clone)]
 pub struct ModBuiltinRunner {
     builtin_type: ModBuiltinType,
-    base: usize,
+    pub(crate) base: usize,
     pub(crate) stop_ptr: Option<usize>,
     instance_def: ModInstanceDef,
     pub(crate) included: bool,
-    zero_segment_index: usize,
+    pub(crate) zero_segment_index: usize,
     zero_segment_size: usize,
     // Precomputed powers used for reading and writing values that are represented as n_words words of word_bit_len bits each.
     shift: BigUint,
@@ -141,6 +141,10 @@ impl ModBuiltinRunner {
         self.instance_def.batch_size
     }
 
+    pub fn word_bit_len(&self) -> u32 {
+        self.instance_def.word_bit_len
+    }
+
     pub fn get_used_cells(&self, segments: &MemorySegmentManager) -> Result<usize, MemoryError> {
         segments
             .get_segment_used_size(self.base)
@@ -697,7 +701,8 @@ mod tests {
 
         let mut hint_processor = BuiltinHintProcessor::new_empty();
         let program = Program::from_bytes(program_data, Some("main")).unwrap();
-        let mut runner = CairoRunner::new(&program, LayoutName::all_cairo, true, false).unwrap();
+        let mut runner =
+            CairoRunner::new(&program, LayoutName::all_cairo, None, true, false).unwrap();
 
         let end = runner.initialize(false).unwrap();
         // Modify add_mod & mul_mod params
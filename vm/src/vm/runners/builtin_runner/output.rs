@@ -18,7 +18,7 @@ pub struct OutputBuiltinState {
 
 #[derive(Debug, Clone)]
 pub struct OutputBuiltinRunner {
-    base: usize,
+    pub(crate) base: usize,
     pub(crate) pages: Pages,
     pub(crate) attributes: Attributes,
     pub(crate) stop_ptr: Option<usize>,
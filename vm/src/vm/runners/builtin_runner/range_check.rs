@@ -14,6 +14,7 @@ use crate::{
         errors::memory_errors::MemoryError,
         vm_memory::{
             memory::{Memory, ValidationRule},
+            memory_backend::MemoryBackend,
             memory_segments::MemorySegmentManager,
         },
     },
@@ -36,7 +37,7 @@ lazy_static! {
 #[derive(Debug, Clone)]
 pub struct RangeCheckBuiltinRunner<const N_PARTS: u64> {
     ratio: Option<u32>,
-    base: usize,
+    pub(crate) base: usize,
     pub(crate) stop_ptr: Option<usize>,
     pub(crate) included: bool,
 }
@@ -115,13 +116,14 @@ impl<const N_PARTS: u64> RangeCheckBuiltinRunner<N_PARTS> {
     }
 
     pub fn get_range_check_usage(&self, memory: &Memory) -> Option<(usize, usize)> {
-        let range_check_segment = memory.data.get(self.base)?;
-        let mut rc_bounds =
-            (!range_check_segment.is_empty()).then_some((usize::MAX, usize::MIN))?;
+        let segment_len = memory.data.segment_len(self.base)?;
+        let mut rc_bounds = (segment_len != 0).then_some((usize::MAX, usize::MIN))?;
 
         // Split value into n_parts parts of less than _INNER_RC_BOUND size.
-        for value in range_check_segment {
-            rc_bounds = value
+        for offset in 0..segment_len {
+            rc_bounds = memory
+                .data
+                .get(self.base, offset)?
                 .get_value()?
                 .get_int_ref()?
                 .to_le_digits()
@@ -149,11 +151,9 @@ impl<const N_PARTS: u64> RangeCheckBuiltinRunner<N_PARTS> {
 
     pub fn air_private_input(&self, memory: &Memory) -> Vec<PrivateInput> {
         let mut private_inputs = vec![];
-        if let Some(segment) = memory.data.get(self.base) {
-            for (index, cell) in segment.iter().enumerate() {
-                if let Some(value) = cell.get_value().and_then(|value| value.get_int()) {
-                    private_inputs.push(PrivateInput::Value(PrivateInputValue { index, value }))
-                }
+        for (index, cell) in memory.data.cells(self.base) {
+            if let Some(value) = cell.get_value().and_then(|value| value.get_int()) {
+                private_inputs.push(PrivateInput::Value(PrivateInputValue { index, value }))
             }
         }
         private_inputs
@@ -10,11 +10,11 @@ use num_integer::div_ceil;
 
 pub(crate) const ARENA_BUILTIN_SIZE: u32 = 3;
 // The size of the builtin segment at the time of its creation.
-const INITIAL_SEGMENT_SIZE: usize = ARENA_BUILTIN_SIZE as usize;
+pub(crate) const INITIAL_SEGMENT_SIZE: usize = ARENA_BUILTIN_SIZE as usize;
 
 #[derive(Debug, Clone)]
 pub struct SegmentArenaBuiltinRunner {
-    base: Relocatable,
+    pub(crate) base: Relocatable,
     pub(crate) included: bool,
     pub(crate) stop_ptr: Option<usize>,
 }
@@ -36,7 +36,7 @@ lazy_static! {
 pub struct SignatureBuiltinRunner {
     pub(crate) included: bool,
     ratio: Option<u32>,
-    base: usize,
+    pub(crate) base: usize,
     pub(crate) stop_ptr: Option<usize>,
     signatures: Rc<RefCell<HashMap<Relocatable, Signature>>>,
 }
@@ -52,6 +52,28 @@ impl SignatureBuiltinRunner {
         }
     }
 
+    /// Returns a copy of the runner which doesn't share its signatures with the original one
+    pub(crate) fn fork(&self) -> Self {
+        SignatureBuiltinRunner {
+            signatures: Rc::new(RefCell::new(
+                self.signatures
+                    .borrow()
+                    .iter()
+                    .map(|(addr, signature)| {
+                        (
+                            *addr,
+                            Signature {
+                                r: signature.r,
+                                s: signature.s,
+                            },
+                        )
+                    })
+                    .collect(),
+            )),
+            ..self.clone()
+        }
+    }
+
     pub fn add_signature(
         &mut self,
         relocatable: Relocatable,
@@ -3,7 +3,11 @@ use crate::stdlib::prelude::{String, Vec};
 use crate::types::builtin_name::BuiltinName;
 use crate::vm::errors::cairo_pie_errors::CairoPieValidationError;
 use crate::{
-    stdlib::{collections::HashMap, prelude::*},
+    stdlib::{
+        collections::{BTreeMap, HashMap},
+        fmt,
+        prelude::*,
+    },
     types::relocatable::{MaybeRelocatable, Relocatable},
     Felt252,
 };
@@ -115,6 +119,176 @@ pub struct CairoPieVersion {
     pub cairo_pie: (),
 }
 
+/// A difference between two Cairo PIEs, as found by [`CairoPie::diff`].
+/// Values missing from one of the PIEs are represented by `None`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CairoPieDiff {
+    /// A field of the program (`main`, `builtins` or `data`) differs.
+    Program(&'static str),
+    /// A segment differs. Segments are named after their role: `program`, `execution`,
+    /// `ret_fp`, `ret_pc`, the name of a builtin, or `extra[i]`.
+    Segment {
+        name: String,
+        left: Option<SegmentInfo>,
+        right: Option<SegmentInfo>,
+    },
+    MemoryCell {
+        address: (usize, usize),
+        left: Option<MaybeRelocatable>,
+        right: Option<MaybeRelocatable>,
+    },
+    /// An execution resource (`n_steps`, `n_memory_holes` or the instance counter of a builtin)
+    /// differs.
+    ExecutionResources {
+        name: String,
+        left: Option<usize>,
+        right: Option<usize>,
+    },
+    /// An entry of the additional data of a builtin differs. The entry is empty when the whole
+    /// additional data differs, in which case its kind is reported.
+    AdditionalData {
+        builtin: BuiltinName,
+        entry: String,
+        left: Option<String>,
+        right: Option<String>,
+    },
+}
+
+impl fmt::Display for SegmentInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "index {}, size {}", self.index, self.size)
+    }
+}
+
+impl fmt::Display for CairoPieDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn side<T: fmt::Display>(value: &Option<T>) -> String {
+            value
+                .as_ref()
+                .map_or_else(|| "<missing>".to_string(), |value| value.to_string())
+        }
+        match self {
+            CairoPieDiff::Program(field) => write!(f, "program.{field} differs"),
+            CairoPieDiff::Segment { name, left, right } => {
+                write!(f, "segment {name}: {} != {}", side(left), side(right))
+            }
+            CairoPieDiff::MemoryCell {
+                address: (segment_index, offset),
+                left,
+                right,
+            } => write!(
+                f,
+                "memory[{segment_index}:{offset}]: {} != {}",
+                side(left),
+                side(right)
+            ),
+            CairoPieDiff::ExecutionResources { name, left, right } => write!(
+                f,
+                "execution_resources.{name}: {} != {}",
+                side(left),
+                side(right)
+            ),
+            CairoPieDiff::AdditionalData {
+                builtin,
+                entry,
+                left,
+                right,
+            } if entry.is_empty() => write!(
+                f,
+                "additional_data[{}]: {} != {}",
+                builtin.to_str(),
+                side(left),
+                side(right)
+            ),
+            CairoPieDiff::AdditionalData {
+                builtin,
+                entry,
+                left,
+                right,
+            } => write!(
+                f,
+                "additional_data[{}].{entry}: {} != {}",
+                builtin.to_str(),
+                side(left),
+                side(right)
+            ),
+        }
+    }
+}
+
+// Compares the values of two maps key by key, in key order
+fn diff_maps<K: Ord + Clone, V: PartialEq + Clone>(
+    left: &BTreeMap<K, V>,
+    right: &BTreeMap<K, V>,
+) -> Vec<(K, Option<V>, Option<V>)> {
+    let keys: BTreeMap<&K, ()> = left.keys().chain(right.keys()).map(|k| (k, ())).collect();
+    keys.into_keys()
+        .filter_map(|key| {
+            let (left, right) = (left.get(key), right.get(key));
+            (left != right).then(|| (key.clone(), left.cloned(), right.cloned()))
+        })
+        .collect()
+}
+
+impl CairoPieMetadata {
+    // Segments of the PIE, named after their role
+    fn named_segments(&self) -> BTreeMap<String, SegmentInfo> {
+        let mut segments = BTreeMap::from([
+            ("program".to_string(), self.program_segment.clone()),
+            ("execution".to_string(), self.execution_segment.clone()),
+            ("ret_fp".to_string(), self.ret_fp_segment.clone()),
+            ("ret_pc".to_string(), self.ret_pc_segment.clone()),
+        ]);
+        for (name, segment) in self.builtin_segments.iter() {
+            segments.insert(name.to_str().to_string(), segment.clone());
+        }
+        for (i, segment) in self.extra_segments.iter().enumerate() {
+            segments.insert(format!("extra[{i}]"), segment.clone());
+        }
+        segments
+    }
+}
+
+impl BuiltinAdditionalData {
+    // Entries of the additional data, as displayed by CairoPieDiff
+    fn entries(&self) -> BTreeMap<String, String> {
+        match self {
+            BuiltinAdditionalData::Hash(addresses) => addresses
+                .iter()
+                .map(|address| (address.to_string(), "verified".to_string()))
+                .collect(),
+            BuiltinAdditionalData::Output(data) => {
+                data.pages
+                    .iter()
+                    .map(|(page_id, page)| {
+                        (
+                            format!("pages[{page_id}]"),
+                            format!("start {}, size {}", page.start, page.size),
+                        )
+                    })
+                    .chain(data.attributes.iter().map(|(name, values)| {
+                        (format!("attributes[{name}]"), format!("{values:?}"))
+                    }))
+                    .collect()
+            }
+            BuiltinAdditionalData::Signature(signatures) => signatures
+                .iter()
+                .map(|(address, (r, s))| (address.to_string(), format!("({r}, {s})")))
+                .collect(),
+            BuiltinAdditionalData::None => BTreeMap::new(),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            BuiltinAdditionalData::Hash(_) => "hash",
+            BuiltinAdditionalData::Output(_) => "output",
+            BuiltinAdditionalData::Signature(_) => "signature",
+            BuiltinAdditionalData::None => "none",
+        }
+    }
+}
+
 impl CairoPieMetadata {
     pub(crate) fn run_validity_checks(&self) -> Result<(), CairoPieValidationError> {
         if self.program.main > self.program.data.len() {
@@ -247,6 +421,97 @@ impl CairoPie {
         Ok(())
     }
 
+    /// Lists the differences between self and the pie received: program fields, segments,
+    /// memory cells, execution resources and additional data entries.
+    pub fn diff(&self, pie: &CairoPie) -> Vec<CairoPieDiff> {
+        let mut diffs = vec![];
+
+        let (left_program, right_program) = (&self.metadata.program, &pie.metadata.program);
+        if left_program.main != right_program.main {
+            diffs.push(CairoPieDiff::Program("main"));
+        }
+        if left_program.builtins != right_program.builtins {
+            diffs.push(CairoPieDiff::Program("builtins"));
+        }
+        if left_program.data != right_program.data {
+            diffs.push(CairoPieDiff::Program("data"));
+        }
+
+        diffs.extend(
+            diff_maps(
+                &self.metadata.named_segments(),
+                &pie.metadata.named_segments(),
+            )
+            .into_iter()
+            .map(|(name, left, right)| CairoPieDiff::Segment { name, left, right }),
+        );
+
+        let left_memory: BTreeMap<_, _> = self.memory.0.iter().cloned().collect();
+        let right_memory: BTreeMap<_, _> = pie.memory.0.iter().cloned().collect();
+        diffs.extend(diff_maps(&left_memory, &right_memory).into_iter().map(
+            |(address, left, right)| CairoPieDiff::MemoryCell {
+                address,
+                left,
+                right,
+            },
+        ));
+
+        let execution_resources = |resources: &ExecutionResources| {
+            let mut named_resources = BTreeMap::from([
+                ("n_steps".to_string(), resources.n_steps),
+                ("n_memory_holes".to_string(), resources.n_memory_holes),
+            ]);
+            for (name, counter) in resources.builtin_instance_counter.iter() {
+                named_resources.insert(
+                    format!("builtin_instance_counter[{}]", name.to_str()),
+                    *counter,
+                );
+            }
+            named_resources
+        };
+        diffs.extend(
+            diff_maps(
+                &execution_resources(&self.execution_resources),
+                &execution_resources(&pie.execution_resources),
+            )
+            .into_iter()
+            .map(|(name, left, right)| CairoPieDiff::ExecutionResources { name, left, right }),
+        );
+
+        let builtin_names: BTreeMap<&str, BuiltinName> = self
+            .additional_data
+            .0
+            .keys()
+            .chain(pie.additional_data.0.keys())
+            .map(|name| (name.to_str(), *name))
+            .collect();
+        for builtin in builtin_names.into_values() {
+            match (
+                self.additional_data.0.get(&builtin),
+                pie.additional_data.0.get(&builtin),
+            ) {
+                (Some(left), Some(right)) if left.kind() == right.kind() => diffs.extend(
+                    diff_maps(&left.entries(), &right.entries())
+                        .into_iter()
+                        .map(|(entry, left, right)| CairoPieDiff::AdditionalData {
+                            builtin,
+                            entry,
+                            left,
+                            right,
+                        }),
+                ),
+                (left, right) => diffs.push(CairoPieDiff::AdditionalData {
+                    builtin,
+                    entry: String::new(),
+                    left: left.map(|data| data.kind().to_string()),
+                    right: right.map(|data| data.kind().to_string()),
+                }),
+            }
+        }
+
+        diffs
+    }
+
     #[cfg(feature = "std")]
     pub fn write_zip_file(&self, file_path: &Path) -> Result<(), std::io::Error> {
         let file = File::create(file_path)?;
@@ -662,7 +927,7 @@ pub(super) mod serde_impl {
 }
 
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
     #[cfg(feature = "std")]
     use rstest::rstest;
 
@@ -771,4 +1036,183 @@ mod test {
         // Remove zip file created by the test
         std::fs::remove_file(file_path).unwrap();
     }
+
+    pub(crate) fn output_pie(
+        execution_memory: Vec<MaybeRelocatable>,
+        output: Vec<MaybeRelocatable>,
+        extra_memory: Vec<MaybeRelocatable>,
+        pages: Pages,
+        attributes: Attributes,
+        n_steps: usize,
+    ) -> CairoPie {
+        let mut memory = vec![((0, 0), MaybeRelocatable::from(5))];
+        for (segment_index, values) in [(1, &execution_memory), (2, &output), (5, &extra_memory)] {
+            memory.extend(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(offset, value)| ((segment_index, offset), value.clone())),
+            );
+        }
+        let extra_segments = if extra_memory.is_empty() {
+            vec![]
+        } else {
+            vec![SegmentInfo::from((5, extra_memory.len()))]
+        };
+        CairoPie {
+            metadata: CairoPieMetadata {
+                program: StrippedProgram {
+                    data: vec![MaybeRelocatable::from(5)],
+                    builtins: vec![BuiltinName::output],
+                    main: 0,
+                    prime: (),
+                },
+                program_segment: SegmentInfo::from((0, 1)),
+                execution_segment: SegmentInfo::from((1, execution_memory.len())),
+                ret_fp_segment: SegmentInfo::from((3, 0)),
+                ret_pc_segment: SegmentInfo::from((4, 0)),
+                builtin_segments: HashMap::from([(
+                    BuiltinName::output,
+                    SegmentInfo::from((2, output.len())),
+                )]),
+                extra_segments,
+            },
+            memory: CairoPieMemory(memory),
+            execution_resources: ExecutionResources {
+                n_steps,
+                n_memory_holes: 0,
+                builtin_instance_counter: HashMap::from([(BuiltinName::output, output.len())]),
+            },
+            additional_data: CairoPieAdditionalData(HashMap::from([(
+                BuiltinName::output,
+                BuiltinAdditionalData::Output(OutputBuiltinAdditionalData { pages, attributes }),
+            )])),
+            version: CairoPieVersion { cairo_pie: () },
+        }
+    }
+
+    #[test]
+    fn diff_equal_cairo_pies() {
+        let pie = output_pie(
+            vec![(2, 0).into(), (3, 0).into()],
+            vec![7.into()],
+            vec![],
+            HashMap::from([(1, PublicMemoryPage { start: 0, size: 1 })]),
+            HashMap::new(),
+            10,
+        );
+        assert_eq!(pie.diff(&pie.clone()), vec![]);
+    }
+
+    #[test]
+    fn diff_cairo_pies() {
+        let pie_a = output_pie(
+            vec![(2, 0).into(), (3, 0).into()],
+            vec![7.into()],
+            vec![],
+            HashMap::from([(1, PublicMemoryPage { start: 0, size: 1 })]),
+            HashMap::from([("gps_fact_topology".to_string(), vec![1, 0])]),
+            10,
+        );
+        let pie_b = output_pie(
+            vec![(2, 0).into(), (3, 0).into()],
+            vec![8.into(), 9.into()],
+            vec![(2, 0).into()],
+            HashMap::new(),
+            HashMap::from([("gps_fact_topology".to_string(), vec![1, 0])]),
+            12,
+        );
+        let diffs = pie_a.diff(&pie_b);
+        assert_eq!(
+            diffs,
+            vec![
+                CairoPieDiff::Segment {
+                    name: "extra[0]".to_string(),
+                    left: None,
+                    right: Some(SegmentInfo::from((5, 1))),
+                },
+                CairoPieDiff::Segment {
+                    name: "output".to_string(),
+                    left: Some(SegmentInfo::from((2, 1))),
+                    right: Some(SegmentInfo::from((2, 2))),
+                },
+                CairoPieDiff::MemoryCell {
+                    address: (2, 0),
+                    left: Some(7.into()),
+                    right: Some(8.into()),
+                },
+                CairoPieDiff::MemoryCell {
+                    address: (2, 1),
+                    left: None,
+                    right: Some(9.into()),
+                },
+                CairoPieDiff::MemoryCell {
+                    address: (5, 0),
+                    left: None,
+                    right: Some((2, 0).into()),
+                },
+                CairoPieDiff::ExecutionResources {
+                    name: "builtin_instance_counter[output]".to_string(),
+                    left: Some(1),
+                    right: Some(2),
+                },
+                CairoPieDiff::ExecutionResources {
+                    name: "n_steps".to_string(),
+                    left: Some(10),
+                    right: Some(12),
+                },
+                CairoPieDiff::AdditionalData {
+                    builtin: BuiltinName::output,
+                    entry: "pages[1]".to_string(),
+                    left: Some("start 0, size 1".to_string()),
+                    right: None,
+                },
+            ]
+        );
+        assert_eq!(
+            diffs
+                .iter()
+                .map(|diff| diff.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "segment extra[0]: <missing> != index 5, size 1",
+                "segment output: index 2, size 1 != index 2, size 2",
+                "memory[2:0]: 7 != 8",
+                "memory[2:1]: <missing> != 9",
+                "memory[5:0]: <missing> != 2:0",
+                "execution_resources.builtin_instance_counter[output]: 1 != 2",
+                "execution_resources.n_steps: 10 != 12",
+                "additional_data[output].pages[1]: start 0, size 1 != <missing>",
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_cairo_pies_program_and_additional_data_kind() {
+        let pie_a = output_pie(vec![], vec![], vec![], HashMap::new(), HashMap::new(), 1);
+        let mut pie_b = pie_a.clone();
+        pie_b.metadata.program.main = 1;
+        pie_b
+            .additional_data
+            .0
+            .insert(BuiltinName::output, BuiltinAdditionalData::None);
+        let diffs = pie_a.diff(&pie_b);
+        assert_eq!(
+            diffs,
+            vec![
+                CairoPieDiff::Program("main"),
+                CairoPieDiff::AdditionalData {
+                    builtin: BuiltinName::output,
+                    entry: String::new(),
+                    left: Some("output".to_string()),
+                    right: Some("none".to_string()),
+                },
+            ]
+        );
+        assert_eq!(diffs[0].to_string(), "program.main differs");
+        assert_eq!(
+            diffs[1].to_string(),
+            "additional_data[output]: output != none"
+        );
+    }
 }
@@ -1,3 +1,5 @@
+use core::time::Duration;
+
 use crate::{
     air_private_input::AirPrivateInput,
     air_public_input::{PublicInput, PublicInputError},
@@ -6,17 +8,25 @@ use crate::{
         collections::{HashMap, HashSet},
         ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign},
         prelude::*,
+        sync::Arc,
+    },
+    types::{
+        builtin_name::BuiltinName, compiled_program::CompiledProgram, layout::CairoLayoutParams,
+        layout_name::LayoutName,
     },
-    types::{builtin_name::BuiltinName, layout::MEMORY_UNITS_PER_STEP, layout_name::LayoutName},
     vm::{
         runners::builtin_runner::SegmentArenaBuiltinRunner,
-        trace::trace_entry::{relocate_trace_register, RelocatedTraceEntry},
+        trace::trace_entry::{relocate_trace_register, RelocatedTraceEntry, TraceEntry},
+        vm_memory::{memory_backend::MemoryBackend, memory_segments::MemorySegmentManager},
     },
     Felt252,
 };
 
 use crate::{
-    hint_processor::hint_processor_definition::{HintProcessor, HintReference},
+    hint_processor::{
+        builtin_hint_processor::builtin_hint_processor_definition::HintProcessorData,
+        hint_processor_definition::{HintProcessor, HintReference},
+    },
     math_utils::safe_div_usize,
     types::{
         errors::{math_errors::MathError, program_errors::ProgramError},
@@ -36,6 +46,7 @@ use crate::{
             vm_exception::VmException,
         },
         security::verify_secure_runner,
+        symbolic::{SymbolicPath, SymbolicState},
         {
             runners::builtin_runner::{
                 BitwiseBuiltinRunner, BuiltinRunner, EcOpBuiltinRunner, HashBuiltinRunner,
@@ -45,10 +56,13 @@ use crate::{
         },
     },
 };
-use num_integer::div_rem;
+use num_integer::{div_ceil, div_rem};
 use num_traits::{ToPrimitive, Zero};
 use serde::{Deserialize, Serialize};
 
+use super::snapshot::{
+    self, BuiltinRunnerSnapshot, CairoRunnerSnapshot, MemorySnapshot, RegistersSnapshot,
+};
 use super::{builtin_runner::ModBuiltinRunner, cairo_pie::CairoPieAdditionalData};
 use super::{
     builtin_runner::{
@@ -82,11 +96,26 @@ impl From<Vec<MaybeRelocatable>> for CairoArg {
 // ================
 
 /// Maintains the resources of a cairo run. Can be used across multiple runners.
+/// Besides the step count, a run can be capped on the memory cells & segments it allocates, on the
+/// instances of each builtin it uses, and on the time spent running hints (only measured with the `std` feature).
 #[derive(Clone, Default, Debug, PartialEq)]
 pub struct RunResources {
     n_steps: Option<usize>,
+    memory_cells: Option<usize>,
+    segments: Option<usize>,
+    builtin_instances: Vec<(BuiltinName, usize)>,
+    hint_time: Option<Duration>,
 }
 
+// Returned by the default implementation of `ResourceTracker::run_resources`
+static UNLIMITED_RUN_RESOURCES: RunResources = RunResources {
+    n_steps: None,
+    memory_cells: None,
+    segments: None,
+    builtin_instances: Vec::new(),
+    hint_time: None,
+};
+
 /// This trait is in charge of overseeing the VM's step usage in contexts where a limited amount of steps are available
 /// for a single execution (which may or not involve other executions taking place in the duration of it ).
 /// This is mostly used in the context of starknet, where contracts can call other contracts while sharing the same step limit.
@@ -99,13 +128,15 @@ pub trait ResourceTracker {
     }
     /// Subtracts 1 step from the available steps
     fn consume_step(&mut self) {}
+    /// Subtracts the time spent running the hints of a step from the available hint time
+    fn consume_hint_time(&mut self, _hint_time: Duration) {}
     /// Returns the available steps for the run
     fn get_n_steps(&self) -> Option<usize> {
         None
     }
     /// Returns a reference to the available resources
     fn run_resources(&self) -> &RunResources {
-        &RunResources { n_steps: None }
+        &UNLIMITED_RUN_RESOURCES
     }
 }
 
@@ -113,8 +144,60 @@ impl RunResources {
     pub fn new(n_steps: usize) -> Self {
         Self {
             n_steps: Some(n_steps),
+            ..Default::default()
         }
     }
+
+    /// Caps the memory cells allocated in the runner's segments, see [`Memory::allocated_cells`](crate::vm::vm_memory::memory::Memory::allocated_cells).
+    /// Writes which would allocate more cells fail before the memory is allocated
+    pub fn with_memory_cells_limit(mut self, memory_cells: usize) -> Self {
+        self.memory_cells = Some(memory_cells);
+        self
+    }
+
+    /// Caps the real & temporary segments of the runner
+    pub fn with_segments_limit(mut self, segments: usize) -> Self {
+        self.segments = Some(segments);
+        self
+    }
+
+    /// Caps the instances of a builtin used by the runner, replacing any previous cap on it
+    pub fn with_builtin_instances_limit(mut self, builtin: BuiltinName, instances: usize) -> Self {
+        self.builtin_instances.retain(|(name, _)| *name != builtin);
+        self.builtin_instances.push((builtin, instances));
+        self
+    }
+
+    /// Caps the wall-clock time spent running hints. Hints are only timed with the `std` feature
+    pub fn with_hint_time_limit(mut self, hint_time: Duration) -> Self {
+        self.hint_time = Some(hint_time);
+        self
+    }
+
+    pub fn get_memory_cells_limit(&self) -> Option<usize> {
+        self.memory_cells
+    }
+
+    pub fn get_segments_limit(&self) -> Option<usize> {
+        self.segments
+    }
+
+    pub fn get_builtin_instances_limit(&self, builtin: BuiltinName) -> Option<usize> {
+        self.builtin_instances
+            .iter()
+            .find(|(name, _)| *name == builtin)
+            .map(|(_, instances)| *instances)
+    }
+
+    /// Returns the hint time left for the run
+    pub fn get_hint_time(&self) -> Option<Duration> {
+        self.hint_time
+    }
+
+    /// Returns true if there is no hint time left
+    pub fn hint_time_consumed(&self) -> bool {
+        self.hint_time == Some(Duration::ZERO)
+    }
 }
 
 impl ResourceTracker for RunResources {
@@ -131,6 +214,12 @@ impl ResourceTracker for RunResources {
         }
     }
 
+    fn consume_hint_time(&mut self, hint_time: Duration) {
+        if let Some(available_time) = self.hint_time {
+            self.hint_time = Some(available_time.saturating_sub(hint_time));
+        }
+    }
+
     fn get_n_steps(&self) -> Option<usize> {
         self.n_steps
     }
@@ -158,9 +247,10 @@ pub struct CairoRunner {
     pub relocated_memory: Vec<Option<Felt252>>,
     pub exec_scopes: ExecutionScopes,
     pub relocated_trace: Option<Vec<RelocatedTraceEntry>>,
+    compiled_hint_data: Option<Arc<[Arc<HintProcessorData>]>>,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum RunnerMode {
     ExecutionMode,
     ProofModeCanonical,
@@ -171,22 +261,11 @@ impl CairoRunner {
     pub fn new_v2(
         program: &Program,
         layout: LayoutName,
+        dynamic_layout_params: Option<CairoLayoutParams>,
         mode: RunnerMode,
         trace_enabled: bool,
     ) -> Result<CairoRunner, RunnerError> {
-        let cairo_layout = match layout {
-            LayoutName::plain => CairoLayout::plain_instance(),
-            LayoutName::small => CairoLayout::small_instance(),
-            LayoutName::dex => CairoLayout::dex_instance(),
-            LayoutName::recursive => CairoLayout::recursive_instance(),
-            LayoutName::starknet => CairoLayout::starknet_instance(),
-            LayoutName::starknet_with_keccak => CairoLayout::starknet_with_keccak_instance(),
-            LayoutName::recursive_large_output => CairoLayout::recursive_large_output_instance(),
-            LayoutName::recursive_with_poseidon => CairoLayout::recursive_with_poseidon(),
-            LayoutName::all_cairo => CairoLayout::all_cairo_instance(),
-            LayoutName::all_solidity => CairoLayout::all_solidity_instance(),
-            LayoutName::dynamic => CairoLayout::dynamic_instance(),
-        };
+        let cairo_layout = CairoLayout::from_name(layout, dynamic_layout_params)?;
         Ok(CairoRunner {
             program: program.clone(),
             vm: VirtualMachine::new(trace_enabled),
@@ -209,12 +288,14 @@ impl CairoRunner {
                 None
             },
             relocated_trace: None,
+            compiled_hint_data: None,
         })
     }
 
     pub fn new(
         program: &Program,
         layout: LayoutName,
+        dynamic_layout_params: Option<CairoLayoutParams>,
         proof_mode: bool,
         trace_enabled: bool,
     ) -> Result<CairoRunner, RunnerError> {
@@ -222,11 +303,18 @@ impl CairoRunner {
             Self::new_v2(
                 program,
                 layout,
+                dynamic_layout_params,
                 RunnerMode::ProofModeCanonical,
                 trace_enabled,
             )
         } else {
-            Self::new_v2(program, layout, RunnerMode::ExecutionMode, trace_enabled)
+            Self::new_v2(
+                program,
+                layout,
+                dynamic_layout_params,
+                RunnerMode::ExecutionMode,
+                trace_enabled,
+            )
         }
     }
 
@@ -627,12 +715,59 @@ impl CairoRunner {
         self.initial_fp
     }
 
+    pub fn get_final_pc(&self) -> Option<Relocatable> {
+        self.final_pc
+    }
+
+    /// Makes the runner share the hint data of the compiled program instead of compiling the hints
+    /// on each run. The runner must have been created from the program of the compiled program.
+    /// The shared data is still passed through
+    /// [`HintProcessorLogic::compile_precompiled_hint`](crate::hint_processor::hint_processor_definition::HintProcessorLogic::compile_precompiled_hint)
+    /// on each run, so that each hint processor can check it or compile it into its own format.
+    pub fn set_compiled_hint_data(
+        &mut self,
+        compiled_program: &CompiledProgram,
+    ) -> Result<(), RunnerError> {
+        if !Arc::ptr_eq(
+            &self.program.shared_program_data,
+            &compiled_program.program().shared_program_data,
+        ) {
+            return Err(RunnerError::CompiledProgramMismatch);
+        }
+        self.compiled_hint_data = Some(compiled_program.hint_data().clone());
+        Ok(())
+    }
+
     /// Gets the data used by the HintProcessor to execute each hint
     pub fn get_hint_data(
         &self,
         references: &[HintReference],
         hint_executor: &mut dyn HintProcessor,
     ) -> Result<Vec<Box<dyn Any>>, VirtualMachineError> {
+        // Hints rejected by a whitelist keep their error, other errors only identify the hint
+        let compile_hint_error = |err, hint_code: &str| match err {
+            VirtualMachineError::HintNotWhitelisted(_)
+            | VirtualMachineError::ForbiddenHintReference(_) => err,
+            _ => VirtualMachineError::CompileHintFail(hint_code.into()),
+        };
+        if let Some(ref compiled_hint_data) = self.compiled_hint_data {
+            return self
+                .program
+                .shared_program_data
+                .hints_collection
+                .iter_hints()
+                .zip(compiled_hint_data.iter())
+                .map(|(hint, hint_data)| {
+                    hint_executor
+                        .compile_precompiled_hint(
+                            hint_data,
+                            &hint.flow_tracking_data.reference_ids,
+                            references,
+                        )
+                        .map_err(|err| compile_hint_error(err, &hint.code))
+                })
+                .collect();
+        }
         self.program
             .shared_program_data
             .hints_collection
@@ -645,7 +780,7 @@ impl CairoRunner {
                         &hint.flow_tracking_data.reference_ids,
                         references,
                     )
-                    .map_err(|_| VirtualMachineError::CompileHintFail(hint.code.clone().into()))
+                    .map_err(|err| compile_hint_error(err, &hint.code))
             })
             .collect()
     }
@@ -663,6 +798,21 @@ impl CairoRunner {
         address: Relocatable,
         hint_processor: &mut dyn HintProcessor,
     ) -> Result<(), VirtualMachineError> {
+        self.run_until_pc_with_trace_chunks(address, hint_processor, usize::MAX, |_, _| {
+            Ok::<(), VirtualMachineError>(())
+        })
+    }
+
+    /// Same as `run_until_pc`, but whenever the trace reaches `chunk_size` entries they are handed over to
+    /// `trace_chunk_handler`, along with the vm's current segments, and removed from the vm's trace, so that the trace
+    /// doesn't need to be kept in memory. The entries recorded since the last chunk are left in the vm's trace
+    pub fn run_until_pc_with_trace_chunks<E: From<VirtualMachineError>>(
+        &mut self,
+        address: Relocatable,
+        hint_processor: &mut dyn HintProcessor,
+        chunk_size: usize,
+        mut trace_chunk_handler: impl FnMut(&[TraceEntry], &MemorySegmentManager) -> Result<(), E>,
+    ) -> Result<(), E> {
         let references = &self.program.shared_program_data.reference_manager;
         #[cfg(not(feature = "extensive_hints"))]
         let hint_data = self.get_hint_data(references, hint_processor)?;
@@ -675,8 +825,12 @@ impl CairoRunner {
             .hints_collection
             .hints_ranges
             .clone();
-        #[cfg(feature = "test_utils")]
+        #[cfg(feature = "hooks")]
         self.vm.execute_before_first_step(&hint_data)?;
+        self.vm
+            .segments
+            .memory
+            .set_memory_cells_limit(hint_processor.run_resources().get_memory_cells_limit());
         while self.vm.get_pc() != address && !hint_processor.consumed() {
             self.vm.step(
                 hint_processor,
@@ -698,10 +852,16 @@ impl CairoRunner {
             )?;
 
             hint_processor.consume_step();
+            self.check_run_resources(hint_processor.run_resources())?;
+
+            if let Some(trace) = self.vm.trace.as_mut().filter(|t| t.len() >= chunk_size) {
+                trace_chunk_handler(trace, &self.vm.segments)?;
+                trace.clear();
+            }
         }
 
         if self.vm.get_pc() != address {
-            return Err(VirtualMachineError::UnfinishedExecution);
+            return Err(VirtualMachineError::UnfinishedExecution.into());
         }
 
         Ok(())
@@ -735,6 +895,10 @@ impl CairoRunner {
                 range.and_then(|(start, length)| hint_data.get(start..start + length.get()))
             })
             .unwrap_or(&[]);
+        self.vm
+            .segments
+            .memory
+            .set_memory_cells_limit(hint_processor.run_resources().get_memory_cells_limit());
 
         for remaining_steps in (1..=steps).rev() {
             if self.final_pc.as_ref() == Some(&self.vm.get_pc()) {
@@ -752,11 +916,124 @@ impl CairoRunner {
                 &mut hint_ranges,
                 &self.program.constants,
             )?;
+            self.check_run_resources(hint_processor.run_resources())?;
         }
 
         Ok(())
     }
 
+    /// Checks the segments & builtin instances used by the run against the limits set in
+    /// `run_resources`. The step & hint time limits are enforced as the run goes, and the memory
+    /// cells limit as the memory is allocated
+    pub fn check_run_resources(
+        &self,
+        run_resources: &RunResources,
+    ) -> Result<(), VirtualMachineError> {
+        if let Some(segments) = run_resources.segments {
+            let segment_manager = &self.vm.segments;
+            if segment_manager.num_segments() + segment_manager.num_temp_segments() > segments {
+                return Err(VirtualMachineError::SegmentsLimit(segments));
+            }
+        }
+        for (name, instances) in &run_resources.builtin_instances {
+            let Some(builtin) = self.vm.builtin_runners.iter().find(|b| b.name() == *name) else {
+                continue;
+            };
+            let used_cells = self
+                .vm
+                .segments
+                .memory
+                .segment_len(builtin.base() as isize)
+                .unwrap_or_default();
+            let used_instances = match builtin.cells_per_instance() as usize {
+                0 => used_cells,
+                cells_per_instance => div_ceil(used_cells, cells_per_instance),
+            };
+            if used_instances > *instances {
+                return Err(VirtualMachineError::BuiltinInstancesLimit(Box::new((
+                    *name, *instances,
+                ))));
+            }
+        }
+        Ok(())
+    }
+
+    /// Compiles the hints of the program, for them to be executed by `step_with_hint_data`
+    pub fn compile_hints(
+        &self,
+        hint_processor: &mut dyn HintProcessor,
+    ) -> Result<Vec<Box<dyn Any>>, VirtualMachineError> {
+        self.get_hint_data(
+            &self.program.shared_program_data.reference_manager,
+            hint_processor,
+        )
+    }
+
+    /// Executes a single step with the hint data returned by `compile_hints`, so that a program
+    /// executed one step at a time only has its hints compiled once
+    pub fn step_with_hint_data(
+        &mut self,
+        hint_processor: &mut dyn HintProcessor,
+        #[cfg(feature = "extensive_hints")] hint_data: &mut Vec<Box<dyn Any>>,
+        #[cfg(not(feature = "extensive_hints"))] hint_data: &[Box<dyn Any>],
+    ) -> Result<(), VirtualMachineError> {
+        if self.final_pc.as_ref() == Some(&self.vm.get_pc()) {
+            return Err(VirtualMachineError::EndOfProgram(1));
+        }
+        #[cfg(feature = "extensive_hints")]
+        let mut hint_ranges = self
+            .program
+            .shared_program_data
+            .hints_collection
+            .hints_ranges
+            .clone();
+        self.vm
+            .segments
+            .memory
+            .set_memory_cells_limit(hint_processor.run_resources().get_memory_cells_limit());
+        self.vm.step(
+            hint_processor,
+            &mut self.exec_scopes,
+            #[cfg(feature = "extensive_hints")]
+            hint_data,
+            #[cfg(not(feature = "extensive_hints"))]
+            self.program
+                .shared_program_data
+                .hints_collection
+                .get_hint_range_for_pc(self.vm.get_pc().offset)
+                .and_then(|range| {
+                    range.and_then(|(start, length)| hint_data.get(start..start + length.get()))
+                })
+                .unwrap_or(&[]),
+            #[cfg(feature = "extensive_hints")]
+            &mut hint_ranges,
+            &self.program.constants,
+        )?;
+        self.check_run_resources(hint_processor.run_resources())
+    }
+
+    // Same as `run_for_steps`, but whenever the trace reaches `chunk_size` entries they are handed over to
+    // `trace_chunk_handler` and removed from the vm's trace
+    fn run_for_steps_with_trace_chunks<E: From<VirtualMachineError>>(
+        &mut self,
+        mut steps: usize,
+        hint_processor: &mut dyn HintProcessor,
+        chunk_size: usize,
+        trace_chunk_handler: &mut impl FnMut(&[TraceEntry], &MemorySegmentManager) -> Result<(), E>,
+    ) -> Result<(), E> {
+        while steps > 0 {
+            let trace_len = self.vm.trace.as_ref().map_or(0, Vec::len);
+            let chunk_steps = steps.min(chunk_size.saturating_sub(trace_len).max(1));
+            self.run_for_steps(chunk_steps, hint_processor)?;
+            steps -= chunk_steps;
+            if let Some(trace) = self.vm.trace.as_mut().filter(|t| t.len() >= chunk_size) {
+                trace_chunk_handler(trace, &self.vm.segments)?;
+                trace.clear();
+            }
+        }
+        Ok(())
+    }
+
     /// Execute steps until a number of steps since the start of the program is reached.
     pub fn run_until_steps(
         &mut self,
@@ -844,7 +1121,11 @@ impl CairoRunner {
             used_units_by_builtins += used_units * multiplier;
         }
 
-        let diluted_units = diluted_pool_instance.units_per_step as usize * self.vm.current_step;
+        let diluted_units = if diluted_pool_instance.fractional_units_per_step {
+            self.vm.current_step / diluted_pool_instance.units_per_step as usize
+        } else {
+            diluted_pool_instance.units_per_step as usize * self.vm.current_step
+        };
         let unused_diluted_units = diluted_units.saturating_sub(used_units_by_builtins);
 
         let diluted_usage_upper_bound = 1usize << diluted_pool_instance.n_bits;
@@ -867,11 +1148,34 @@ impl CairoRunner {
         disable_finalize_all: bool,
         hint_processor: &mut dyn HintProcessor,
     ) -> Result<(), VirtualMachineError> {
+        self.end_run_with_trace_chunks(
+            disable_trace_padding,
+            disable_finalize_all,
+            hint_processor,
+            usize::MAX,
+            |_, _| Ok::<(), VirtualMachineError>(()),
+        )
+    }
+
+    /// Same as `end_run`, but the trace entries of the steps run to pad the trace are handed over to
+    /// `trace_chunk_handler` in chunks, like `run_until_pc_with_trace_chunks` does
+    pub fn end_run_with_trace_chunks<E: From<VirtualMachineError>>(
+        &mut self,
+        disable_trace_padding: bool,
+        disable_finalize_all: bool,
+        hint_processor: &mut dyn HintProcessor,
+        chunk_size: usize,
+        mut trace_chunk_handler: impl FnMut(&[TraceEntry], &MemorySegmentManager) -> Result<(), E>,
+    ) -> Result<(), E> {
         if self.run_ended {
-            return Err(RunnerError::EndRunCalledTwice.into());
+            return Err(VirtualMachineError::from(RunnerError::EndRunCalledTwice).into());
         }
 
-        self.vm.segments.memory.relocate_memory()?;
+        self.vm
+            .segments
+            .memory
+            .relocate_memory()
+            .map_err(VirtualMachineError::from)?;
         self.vm.end_run(&self.exec_scopes)?;
 
         if disable_finalize_all {
@@ -880,19 +1184,31 @@ impl CairoRunner {
 
         self.vm.segments.compute_effective_sizes();
         if self.is_proof_mode() && !disable_trace_padding {
-            self.run_until_next_power_of_2(hint_processor)?;
+            let steps = self.vm.current_step.next_power_of_two() - self.vm.current_step;
+            self.run_for_steps_with_trace_chunks(
+                steps,
+                hint_processor,
+                chunk_size,
+                &mut trace_chunk_handler,
+            )?;
             loop {
                 match self.check_used_cells() {
                     Ok(_) => break,
                     Err(e) => match e {
                         VirtualMachineError::Memory(MemoryError::InsufficientAllocatedCells(_)) => {
                         }
-                        e => return Err(e),
+                        e => return Err(e.into()),
                     },
                 }
 
-                self.run_for_steps(1, hint_processor)?;
-                self.run_until_next_power_of_2(hint_processor)?;
+                // Doubles the amount of steps
+                let steps = (self.vm.current_step + 1).next_power_of_two() - self.vm.current_step;
+                self.run_for_steps_with_trace_chunks(
+                    steps,
+                    hint_processor,
+                    chunk_size,
+                    &mut trace_chunk_handler,
+                )?;
             }
         }
 
@@ -937,8 +1253,9 @@ impl CairoRunner {
         }
         //Relocated addresses start at 1
         self.relocated_memory.push(None);
-        for (index, segment) in self.vm.segments.memory.data.iter().enumerate() {
-            for (seg_offset, cell) in segment.iter().enumerate() {
+        let data = &self.vm.segments.memory.data;
+        for index in 0..data.num_segments() {
+            for (seg_offset, cell) in data.cells(index) {
                 match cell.get_value() {
                     Some(cell) => {
                         let relocated_addr = relocate_address(
@@ -1047,6 +1364,44 @@ impl CairoRunner {
         })
     }
 
+    /// Returns the resources used so far by a run that couldn't finish, for example because it
+    /// exhausted its [`RunResources`], so that they can be charged for.
+    /// The segments' used sizes are taken from the current memory, without storing them, so the
+    /// run can still be resumed afterwards
+    pub fn get_partial_execution_resources(&self) -> Result<ExecutionResources, RunnerError> {
+        let n_steps = self
+            .vm
+            .trace
+            .as_ref()
+            .map(|x| x.len())
+            .unwrap_or(self.vm.current_step);
+        let segment_used_sizes = self.vm.segments.effective_sizes();
+        let n_memory_holes = self.vm.segments.get_memory_holes_for_used_sizes(
+            self.vm.builtin_runners.len(),
+            self.program.builtins.contains(&BuiltinName::output),
+            &segment_used_sizes,
+        )?;
+
+        // Builtins only read the used sizes of their segments
+        let segments = MemorySegmentManager {
+            segment_used_sizes: Some(segment_used_sizes),
+            ..MemorySegmentManager::new()
+        };
+        let mut builtin_instance_counter = HashMap::new();
+        for builtin_runner in &self.vm.builtin_runners {
+            builtin_instance_counter.insert(
+                builtin_runner.name(),
+                builtin_runner.get_used_instances(&segments)?,
+            );
+        }
+
+        Ok(ExecutionResources {
+            n_steps,
+            n_memory_holes,
+            builtin_instance_counter,
+        })
+    }
+
     // Finalizes the segments.
     //     Note:
     //     1.  end_run() must precede a call to this method.
@@ -1172,7 +1527,7 @@ impl CairoRunner {
 
         // Out of the memory units available per step, a fraction is used for public memory, and
         // four are used for the instruction.
-        let total_memory_units = MEMORY_UNITS_PER_STEP * vm_current_step_u32;
+        let total_memory_units = instance.memory_units_per_step * vm_current_step_u32;
         let (public_memory_units, rem) =
             div_rem(total_memory_units, instance.public_memory_fraction);
         if rem != 0 {
@@ -1416,6 +1771,7 @@ impl CairoRunner {
         PublicInput::new(
             &self.relocated_memory,
             self.layout.name.to_str(),
+            self.layout.dynamic_layout_params.clone(),
             &self.vm.get_public_memory_addresses()?,
             self.get_memory_segment_addresses()?,
             self.relocated_trace
@@ -1426,6 +1782,204 @@ impl CairoRunner {
         )
     }
 
+    /// Returns an independent copy of the runner, which can carry on with the run without affecting this one.
+    /// This is meant for speculative execution, where several continuations are tried from the same state.
+    ///
+    /// Memory segments are shared between the copies and only copied a page at a time when written to.
+    /// The first fork turns the default memory backend into such a copy-on-write one, custom
+    /// backends are copied via `MemoryBackend::fork`.
+    ///
+    /// Note that this is why the runner is borrowed mutably: its memory stays copy-on-write after the
+    /// fork, so its accesses keep going through the pages, until `unshare_memory` is called once the
+    /// forks have been dropped.
+    ///
+    /// Execution scope variables are copied too, so their types must be registered via
+    /// `ExecutionScopes::register_forkable_type`. Hint processors are not part of the runner and
+    /// have to be copied separately.
+    pub fn fork(&mut self) -> Result<CairoRunner, RunnerError> {
+        Ok(CairoRunner {
+            vm: self.vm.fork()?,
+            program: self.program.clone(),
+            layout: self.layout.clone(),
+            final_pc: self.final_pc,
+            program_base: self.program_base,
+            execution_base: self.execution_base,
+            entrypoint: self.entrypoint,
+            initial_ap: self.initial_ap,
+            initial_fp: self.initial_fp,
+            initial_pc: self.initial_pc,
+            run_ended: self.run_ended,
+            segments_finalized: self.segments_finalized,
+            execution_public_memory: self.execution_public_memory.clone(),
+            runner_mode: self.runner_mode.clone(),
+            relocated_memory: self.relocated_memory.clone(),
+            exec_scopes: self.exec_scopes.fork()?,
+            relocated_trace: self.relocated_trace.clone(),
+            compiled_hint_data: self.compiled_hint_data.clone(),
+        })
+    }
+
+    /// Turns the memory back into the default backend after it was forked (see `fork`), once its forks have
+    /// been dropped. Returns false, leaving the memory copy-on-write, while some of its pages are still
+    /// shared with a fork.
+    pub fn unshare_memory(&mut self) -> bool {
+        self.vm.segments.memory.unshare()
+    }
+
+    /// Explores the paths through the symbolic branches of the program (see [`crate::vm::symbolic`]),
+    /// running each of them on a fork of the runner until `address` is reached or the run fails.
+    /// The runner itself isn't run, its memory cells should have been made symbolic before being used.
+    ///
+    /// Paths are explored depth-first by flipping the decisions taken on the branches of the ones already run
+    /// (see [`SymbolicState::flip_branch`]), until `max_paths` of them have been run. `solve` is given the
+    /// constraints of each flipped path, and returns the values of the variables satisfying them, or None if
+    /// there are none. The path is then run with the symbolic cells holding these values, the other variables
+    /// keeping their current ones.
+    /// Assertions over symbolic values are part of these constraints, so `solve` can rule out the flipped paths
+    /// contradicting them. A path whose values don't satisfy one of its assertions is still run to its end, but
+    /// isn't known to be feasible (see [`SymbolicPath::is_known_feasible`]).
+    /// Note that the hint processor's run resources are shared between the paths.
+    pub fn explore_symbolic_paths(
+        &mut self,
+        address: Relocatable,
+        hint_processor: &mut dyn HintProcessor,
+        max_paths: usize,
+        solve: &mut dyn FnMut(&SymbolicState) -> Option<HashMap<usize, Felt252>>,
+    ) -> Result<Vec<SymbolicPath>, VirtualMachineError> {
+        let mut paths = Vec::new();
+        // The flipped paths left to run, along with the number of branches they share with the path they come from
+        let mut pending = vec![(None, 0)];
+        while let Some((flipped, n_shared)) = pending.pop().filter(|_| paths.len() < max_paths) {
+            let values = match flipped {
+                Some(flipped) => match solve(&flipped) {
+                    Some(values) => values,
+                    None => continue,
+                },
+                None => HashMap::new(),
+            };
+            let mut runner = self.fork()?;
+            for (var, value) in values {
+                runner.vm.set_symbolic_value(var, value)?;
+            }
+            let result = runner.run_until_pc(address, hint_processor);
+            let state = runner
+                .vm
+                .symbolic
+                .take()
+                .map(|state| *state)
+                .unwrap_or_default();
+            pending.extend(
+                (n_shared..state.branches().len())
+                    .filter_map(|index| Some((Some(state.flip_branch(index)?), index + 1))),
+            );
+            paths.push(SymbolicPath { state, result });
+        }
+        Ok(paths)
+    }
+
+    /// Takes a serializable snapshot of the runner's current state, which can be used to resume the run
+    /// later on (possibly on another machine) via `CairoRunner::from_snapshot`.
+    /// Fails if an execution scope variable's type can't be serialized (such as the `DictManager`).
+    pub fn get_snapshot(&self) -> Result<CairoRunnerSnapshot, RunnerError> {
+        Ok(CairoRunnerSnapshot {
+            layout: self.layout.name,
+            dynamic_layout_params: self.layout.dynamic_layout_params.clone(),
+            runner_mode: self.runner_mode.clone(),
+            program_base: self.program_base,
+            execution_base: self.execution_base,
+            entrypoint: self.entrypoint,
+            initial_pc: self.initial_pc,
+            initial_ap: self.initial_ap,
+            initial_fp: self.initial_fp,
+            final_pc: self.final_pc,
+            execution_public_memory: self.execution_public_memory.clone(),
+            registers: RegistersSnapshot {
+                pc: self.vm.run_context.pc,
+                ap: self.vm.run_context.ap,
+                fp: self.vm.run_context.fp,
+            },
+            current_step: self.vm.current_step,
+            rc_limits: self.vm.rc_limits,
+            run_ended: self.run_ended,
+            run_finished: self.vm.run_finished,
+            skip_instruction_execution: self.vm.skip_instruction_execution,
+            trace: self.vm.trace.clone(),
+            memory: MemorySnapshot::new(&self.vm.segments),
+            builtins: self
+                .vm
+                .builtin_runners
+                .iter()
+                .map(BuiltinRunnerSnapshot::new)
+                .collect(),
+            exec_scopes: snapshot::exec_scopes_snapshot(&self.exec_scopes)?,
+        })
+    }
+
+    /// Rebuilds a runner from a snapshot taken with `CairoRunner::get_snapshot`.
+    /// The program must be the same one the snapshot was taken from.
+    /// The run can then be resumed with `run_until_pc`, using the address returned by `get_final_pc`.
+    pub fn from_snapshot(
+        program: &Program,
+        snapshot: CairoRunnerSnapshot,
+    ) -> Result<CairoRunner, RunnerError> {
+        let mut runner = CairoRunner::new_v2(
+            program,
+            snapshot.layout,
+            snapshot.dynamic_layout_params,
+            snapshot.runner_mode,
+            snapshot.trace.is_some(),
+        )?;
+        runner.program_base = snapshot.program_base;
+        runner.execution_base = snapshot.execution_base;
+        runner.entrypoint = snapshot.entrypoint;
+        runner.initial_pc = snapshot.initial_pc;
+        runner.initial_ap = snapshot.initial_ap;
+        runner.initial_fp = snapshot.initial_fp;
+        runner.final_pc = snapshot.final_pc;
+        runner.execution_public_memory = snapshot.execution_public_memory;
+
+        snapshot.memory.restore(&mut runner.vm.segments)?;
+        if let Some(program_base) = runner.program_base {
+            let program_data = &program.shared_program_data.data;
+            if runner
+                .vm
+                .segments
+                .memory
+                .get_continuous_range(program_base, program_data.len())
+                .ok()
+                .as_ref()
+                != Some(program_data)
+            {
+                return Err(RunnerError::SnapshotProgramMismatch);
+            }
+        }
+        for builtin in snapshot.builtins.iter() {
+            let builtin = builtin.restore(&runner.vm.segments)?;
+            runner.vm.builtin_runners.push(builtin);
+        }
+        for builtin in runner.vm.builtin_runners.iter() {
+            builtin.add_validation_rule(&mut runner.vm.segments.memory);
+        }
+        runner
+            .vm
+            .segments
+            .memory
+            .validate_existing_memory()
+            .map_err(RunnerError::MemoryValidationError)?;
+
+        runner.vm.run_context.pc = snapshot.registers.pc;
+        runner.vm.run_context.ap = snapshot.registers.ap;
+        runner.vm.run_context.fp = snapshot.registers.fp;
+        runner.vm.current_step = snapshot.current_step;
+        runner.vm.rc_limits = snapshot.rc_limits;
+        runner.run_ended = snapshot.run_ended;
+        runner.vm.run_finished = snapshot.run_finished;
+        runner.vm.skip_instruction_execution = snapshot.skip_instruction_execution;
+        runner.vm.trace = snapshot.trace;
+        runner.exec_scopes = snapshot::restore_exec_scopes(snapshot.exec_scopes);
+        Ok(runner)
+    }
+
     pub fn get_air_private_input(&self) -> AirPrivateInput {
         let mut private_inputs = HashMap::new();
         for builtin in self.vm.builtin_runners.iter() {
@@ -1572,7 +2126,12 @@ mod tests {
     use crate::air_private_input::{PrivateInput, PrivateInputSignature, SignatureInput};
     use crate::cairo_run::{cairo_run, CairoRunConfig};
     use crate::stdlib::collections::{HashMap, HashSet};
-    use crate::vm::vm_memory::memory::MemoryCell;
+    use crate::types::instance_definitions::diluted_pool_instance_def::DilutedPoolInstanceDef;
+    use crate::vm::vm_memory::{
+        memory::MemoryCell,
+        memory_backend::{MemoryData, SparseMemoryBackend},
+        memory_segments::MemorySegmentManager,
+    };
 
     use crate::felt_hex;
     use crate::{
@@ -2388,6 +2947,131 @@ mod tests {
             .is_none());
     }
 
+    // The program of `initialize_and_run_range_check_builtin`, which checks that 7 fits in 64 bits
+    fn range_check_program() -> Program {
+        program!(
+            builtins = vec![BuiltinName::range_check],
+            data = vec_data!(
+                (4612671182993129469_i64),
+                (5189976364521848832_i64),
+                (18446744073709551615_i128),
+                (5199546496550207487_i64),
+                (4612389712311386111_i64),
+                (5198983563776393216_i64),
+                (2),
+                (2345108766317314046_i64),
+                (5191102247248822272_i64),
+                (5189976364521848832_i64),
+                (7),
+                (1226245742482522112_i64),
+                ((
+                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
+                    10
+                )),
+                (2345108766317314046_i64)
+            ),
+            main = Some(8),
+        )
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn fork_runner_mid_run() {
+        let program = range_check_program();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let finish = |mut cairo_runner: CairoRunner, end: Relocatable| {
+            let mut hint_processor = BuiltinHintProcessor::new_empty();
+            cairo_runner.run_until_pc(end, &mut hint_processor).unwrap();
+            cairo_runner
+                .end_run(false, false, &mut hint_processor)
+                .unwrap();
+            cairo_runner.relocate(true).unwrap();
+            cairo_runner
+        };
+
+        let mut reference_runner = cairo_runner!(program, LayoutName::all_cairo, false, true);
+        let end = reference_runner.initialize(false).unwrap();
+        let reference_runner = finish(reference_runner, end);
+
+        let mut cairo_runner = cairo_runner!(program, LayoutName::all_cairo, false, true);
+        let end = cairo_runner.initialize(false).unwrap();
+        cairo_runner.run_for_steps(2, &mut hint_processor).unwrap();
+        let mut fork = cairo_runner.fork().unwrap();
+        assert_eq!(fork.vm.get_pc(), cairo_runner.vm.get_pc());
+        assert_eq!(fork.vm.current_step, 2);
+
+        // Speculative writes to the fork aren't seen by the original runner
+        let segment = fork.vm.add_memory_segment();
+        fork.vm.insert_value(segment, Felt252::ONE).unwrap();
+        assert_eq!(
+            cairo_runner.vm.segments.num_segments() + 1,
+            fork.vm.segments.num_segments()
+        );
+        assert_eq!(
+            fork.vm.get_integer(segment).unwrap().as_ref(),
+            &Felt252::ONE
+        );
+
+        // The memory is copy-on-write until the forks are dropped
+        assert!(matches!(
+            cairo_runner.vm.segments.memory.data,
+            MemoryData::Cow(_)
+        ));
+        assert!(!cairo_runner.unshare_memory());
+        drop(fork);
+        assert!(cairo_runner.unshare_memory());
+        assert!(matches!(
+            cairo_runner.vm.segments.memory.data,
+            MemoryData::Vec(_)
+        ));
+
+        // Both runners carry on from the same state
+        let fork = cairo_runner.fork().unwrap();
+        let (cairo_runner, fork) = (finish(cairo_runner, end), finish(fork, end));
+        assert_eq!(
+            cairo_runner.relocated_memory,
+            reference_runner.relocated_memory
+        );
+        assert_eq!(fork.relocated_memory, reference_runner.relocated_memory);
+        assert_eq!(fork.relocated_trace, reference_runner.relocated_trace);
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn initialize_and_run_range_check_builtin_with_sparse_memory_backend() {
+        let program = range_check_program();
+        let run = |sparse: bool| {
+            let mut hint_processor = BuiltinHintProcessor::new_empty();
+            let mut cairo_runner = cairo_runner!(program, LayoutName::all_cairo, false, true);
+            if sparse {
+                cairo_runner.vm.segments = MemorySegmentManager::with_memory_backends(
+                    Box::new(SparseMemoryBackend::new()),
+                    Box::new(SparseMemoryBackend::new()),
+                );
+            }
+            let end = cairo_runner.initialize(false).unwrap();
+            cairo_runner.run_until_pc(end, &mut hint_processor).unwrap();
+            cairo_runner
+                .end_run(false, false, &mut hint_processor)
+                .unwrap();
+            cairo_runner.relocate(true).unwrap();
+            cairo_runner
+        };
+        let (default_runner, sparse_runner) = (run(false), run(true));
+        assert_eq!(
+            sparse_runner.relocated_memory,
+            default_runner.relocated_memory
+        );
+        assert_eq!(
+            sparse_runner.get_perm_range_check_limits(),
+            default_runner.get_perm_range_check_limits()
+        );
+        assert_eq!(
+            sparse_runner.get_memory_holes().unwrap(),
+            default_runner.get_memory_holes().unwrap()
+        );
+    }
+
     #[test]
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     /*Program used:
@@ -3385,6 +4069,48 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn step_with_hint_data() {
+        let program = range_check_program();
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program, LayoutName::all_cairo, false, true);
+        cairo_runner.initialize_builtins(false).unwrap();
+        cairo_runner.initialize_segments(None);
+
+        cairo_runner.initialize_main_entrypoint().unwrap();
+        cairo_runner.initialize_vm().unwrap();
+
+        // Full takes 10 steps.
+        #[cfg(feature = "extensive_hints")]
+        let mut hint_data = cairo_runner.compile_hints(&mut hint_processor).unwrap();
+        #[cfg(not(feature = "extensive_hints"))]
+        let hint_data = cairo_runner.compile_hints(&mut hint_processor).unwrap();
+        for _ in 0..10 {
+            assert_matches!(
+                cairo_runner.step_with_hint_data(
+                    &mut hint_processor,
+                    #[cfg(feature = "extensive_hints")]
+                    &mut hint_data,
+                    #[cfg(not(feature = "extensive_hints"))]
+                    &hint_data,
+                ),
+                Ok(())
+            );
+        }
+        assert_matches!(
+            cairo_runner.step_with_hint_data(
+                &mut hint_processor,
+                #[cfg(feature = "extensive_hints")]
+                &mut hint_data,
+                #[cfg(not(feature = "extensive_hints"))]
+                &hint_data,
+            ),
+            Err(VirtualMachineError::EndOfProgram(1))
+        );
+    }
+
     #[test]
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     fn run_empty() {
@@ -3725,6 +4451,30 @@ mod tests {
         assert_matches!(cairo_runner.check_diluted_check_usage(), Ok(()));
     }
 
+    /// Test that check_diluted_check_usage() allocates a diluted unit every
+    /// `units_per_step` steps when units per step are fractional.
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn check_diluted_check_usage_fractional_units_per_step() {
+        let program = program!();
+
+        let mut cairo_runner = cairo_runner!(program);
+
+        cairo_runner.vm.current_step = 1 << 17;
+        cairo_runner.vm.builtin_runners = vec![];
+        cairo_runner.layout.diluted_pool_instance_def =
+            DilutedPoolInstanceDef::from_log_units_per_step(2);
+        assert_matches!(cairo_runner.check_diluted_check_usage(), Ok(()));
+        cairo_runner.layout.diluted_pool_instance_def =
+            DilutedPoolInstanceDef::from_log_units_per_step(-2);
+        assert_matches!(
+            cairo_runner.check_diluted_check_usage(),
+            Err(VirtualMachineError::Memory(
+                MemoryError::InsufficientAllocatedCells(_)
+            ))
+        );
+    }
+
     #[test]
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     fn end_run_run_already_finished() {
@@ -3786,6 +4536,55 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn end_run_with_trace_chunks_proof_mode() {
+        let program = Program::from_bytes(
+            include_bytes!("../../../../cairo_programs/proof_programs/fibonacci.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let run_until_end = |cairo_runner: &mut CairoRunner| {
+            let end = cairo_runner.initialize(false).unwrap();
+            cairo_runner
+                .run_until_pc(end, &mut BuiltinHintProcessor::new_empty())
+                .unwrap();
+        };
+
+        let mut cairo_runner = cairo_runner!(program, LayoutName::all_cairo, true, true);
+        run_until_end(&mut cairo_runner);
+        cairo_runner
+            .end_run(false, false, &mut BuiltinHintProcessor::new_empty())
+            .unwrap();
+        let expected_trace = cairo_runner.vm.trace.unwrap();
+
+        let mut cairo_runner = cairo_runner!(program, LayoutName::all_cairo, true, true);
+        run_until_end(&mut cairo_runner);
+        let mut trace = cairo_runner.vm.trace.take().unwrap();
+        cairo_runner.vm.trace = Some(Vec::new());
+        let mut chunk_sizes = Vec::new();
+        cairo_runner
+            .end_run_with_trace_chunks(
+                false,
+                false,
+                &mut BuiltinHintProcessor::new_empty(),
+                7,
+                |chunk, _| {
+                    chunk_sizes.push(chunk.len());
+                    trace.extend_from_slice(chunk);
+                    Ok::<(), VirtualMachineError>(())
+                },
+            )
+            .unwrap();
+        // The padding steps are handed over as they are run, only the ones since the last chunk are left in the trace
+        let remaining_trace = cairo_runner.vm.trace.unwrap();
+        assert!(!chunk_sizes.is_empty());
+        assert!(chunk_sizes.iter().all(|size| *size == 7));
+        assert!(remaining_trace.len() < 7);
+        trace.extend(remaining_trace);
+        assert_eq!(trace, expected_trace);
+    }
+
     #[test]
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     fn get_builtin_segments_info_empty() {
@@ -4072,7 +4871,8 @@ mod tests {
                 MemoryCell::new(Felt252::from(0x8FFF_8000_0750u64).into()),
             ],
             vec![MemoryCell::new((0isize, 0usize).into()); 128 * 1024],
-        ];
+        ]
+        .into();
 
         cairo_runner.run_for_steps(1, &mut hint_processor).unwrap();
 
@@ -4093,7 +4893,8 @@ mod tests {
 
         cairo_runner.vm.segments.memory.data = vec![vec![MemoryCell::new(mayberelocatable!(
             0x80FF_8000_0530u64
-        ))]];
+        ))]]
+        .into();
         cairo_runner.vm.builtin_runners =
             vec![RangeCheckBuiltinRunner::<RC_N_PARTS_STANDARD>::new(Some(12), true).into()];
 
@@ -4125,7 +4926,8 @@ mod tests {
         cairo_runner.vm.current_step = 10000;
         cairo_runner.vm.segments.memory.data = vec![vec![MemoryCell::new(mayberelocatable!(
             0x80FF_8000_0530u64
-        ))]];
+        ))]]
+        .into();
         cairo_runner.vm.trace = Some(vec![TraceEntry {
             pc: (0, 0).into(),
             ap: 0,
@@ -4147,7 +4949,8 @@ mod tests {
             vec![RangeCheckBuiltinRunner::<RC_N_PARTS_STANDARD>::new(Some(8), true).into()];
         cairo_runner.vm.segments.memory.data = vec![vec![MemoryCell::new(mayberelocatable!(
             0x80FF_8000_0530u64
-        ))]];
+        ))]]
+        .into();
         cairo_runner.vm.trace = Some(vec![TraceEntry {
             pc: (0, 0).into(),
             ap: 0,
@@ -4212,7 +5015,8 @@ mod tests {
             vec![RangeCheckBuiltinRunner::<RC_N_PARTS_STANDARD>::new(Some(8), true).into()];
         cairo_runner.vm.segments.memory.data = vec![vec![MemoryCell::new(mayberelocatable!(
             0x80FF_8000_0530u64
-        ))]];
+        ))]]
+        .into();
         cairo_runner.vm.trace = Some(vec![TraceEntry {
             pc: (0, 0).into(),
             ap: 0,
@@ -4701,7 +5505,8 @@ mod tests {
             vec![],
             vec![MemoryCell::new(MaybeRelocatable::from((0, 0)))],
             vec![],
-        ];
+        ]
+        .into();
         cairo_runner.vm.set_ap(1);
         cairo_runner.vm.segments.segment_used_sizes = Some(vec![0, 1, 0]);
         //Check values written by first call to segments.finalize()
@@ -4731,7 +5536,8 @@ mod tests {
             vec![MemoryCell::new(MaybeRelocatable::from((0, 0)))],
             vec![MemoryCell::new(MaybeRelocatable::from((0, 1)))],
             vec![],
-        ];
+        ]
+        .into();
         cairo_runner.vm.set_ap(1);
         cairo_runner.vm.segments.segment_used_sizes = Some(vec![1, 1, 0]);
         //Check values written by first call to segments.finalize()
@@ -4767,7 +5573,8 @@ mod tests {
                 MemoryCell::new(MaybeRelocatable::from((3, 5))),
             ],
             vec![],
-        ];
+        ]
+        .into();
         cairo_runner.vm.set_ap(2);
         // We use 5 as bitwise builtin's segment size as a bitwise instance is 5 cells
         cairo_runner.vm.segments.segment_used_sizes = Some(vec![0, 2, 0, 5]);
@@ -5229,6 +6036,141 @@ mod tests {
         assert_eq!(hint_processor.run_resources(), &RunResources::new(0));
     }
 
+    // Writes 1000 & 2000 to [ap] & [ap + 1], then their sum to [ap + 2]
+    fn sum_program(builtin_names: Vec<BuiltinName>) -> Program {
+        program!(
+            builtins = builtin_names,
+            data = vec_data!(
+                (0x480680017fff8000),
+                (1000),
+                (0x480680017fff8000),
+                (2000),
+                (0x48307fff7ffe8000),
+                (0x208b7fff7fff7ffe)
+            ),
+            main = Some(0),
+        )
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn test_run_resources_memory_cells_limit() {
+        let program = sum_program(vec![]);
+        let mut runner = cairo_runner!(program);
+        let end = runner.initialize(false).unwrap();
+        let cells = runner.vm.segments.memory.allocated_cells();
+        // Each step writes a new cell
+        let mut hint_processor = BuiltinHintProcessor::new(
+            HashMap::new(),
+            RunResources::default().with_memory_cells_limit(cells + 1),
+        );
+        // The second step fails before allocating the cell it writes to
+        assert_matches!(
+            runner.run_until_pc(end, &mut hint_processor),
+            Err(VirtualMachineError::Memory(MemoryError::MemoryCellsLimit(limit))) if limit == cells + 1
+        );
+        assert_eq!(runner.vm.segments.memory.allocated_cells(), cells + 1);
+        let execution_resources = runner.get_partial_execution_resources().unwrap();
+        assert_eq!(execution_resources.n_steps, 1);
+        // The used sizes are only computed once the run ends
+        assert_eq!(runner.vm.segments.segment_used_sizes, None);
+
+        let mut runner = cairo_runner!(program);
+        let end = runner.initialize(false).unwrap();
+        let mut hint_processor = BuiltinHintProcessor::new(
+            HashMap::new(),
+            RunResources::default().with_memory_cells_limit(cells + 3),
+        );
+        assert_matches!(runner.run_until_pc(end, &mut hint_processor), Ok(()));
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn test_run_resources_segments_limit() {
+        let program = sum_program(vec![]);
+        let mut runner = cairo_runner!(program);
+        let end = runner.initialize(false).unwrap();
+        let segments = runner.vm.segments.num_segments();
+        let mut hint_processor = BuiltinHintProcessor::new(
+            HashMap::new(),
+            RunResources::default().with_segments_limit(segments - 1),
+        );
+        assert_matches!(
+            runner.run_until_pc(end, &mut hint_processor),
+            Err(VirtualMachineError::SegmentsLimit(limit)) if limit == segments - 1
+        );
+        assert_eq!(runner.get_partial_execution_resources().unwrap().n_steps, 1);
+
+        let mut runner = cairo_runner!(program);
+        let end = runner.initialize(false).unwrap();
+        let mut hint_processor = BuiltinHintProcessor::new(
+            HashMap::new(),
+            RunResources::default().with_segments_limit(segments),
+        );
+        assert_matches!(runner.run_until_pc(end, &mut hint_processor), Ok(()));
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn test_run_resources_builtin_instances_limit() {
+        let program = sum_program(vec![BuiltinName::range_check]);
+        let mut runner = cairo_runner!(program);
+        runner.initialize(false).unwrap();
+        let base = runner.vm.builtin_runners[0].base() as isize;
+        runner
+            .vm
+            .segments
+            .memory
+            .insert((base, 0).into(), 1)
+            .unwrap();
+        runner
+            .vm
+            .segments
+            .memory
+            .insert((base, 1).into(), 2)
+            .unwrap();
+        let run_resources = RunResources::default()
+            .with_builtin_instances_limit(BuiltinName::range_check, 3)
+            .with_builtin_instances_limit(BuiltinName::range_check, 1);
+        assert_eq!(
+            run_resources.get_builtin_instances_limit(BuiltinName::range_check),
+            Some(1)
+        );
+        assert_matches!(
+            runner.check_run_resources(&run_resources),
+            Err(VirtualMachineError::BuiltinInstancesLimit(limit))
+                if *limit == (BuiltinName::range_check, 1)
+        );
+        assert_matches!(
+            runner.check_run_resources(
+                &run_resources.with_builtin_instances_limit(BuiltinName::range_check, 2)
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_run_resources_hint_time_limit() {
+        // valid_program_a.json has a hint at its first instruction
+        let program = Program::from_bytes(
+            include_bytes!("../../../../cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let mut runner = cairo_runner!(program);
+        let end = runner.initialize(false).unwrap();
+        let mut hint_processor = BuiltinHintProcessor::new(
+            HashMap::new(),
+            RunResources::default().with_hint_time_limit(Duration::from_nanos(1)),
+        );
+        assert_matches!(
+            runner.run_until_pc(end, &mut hint_processor),
+            Err(VirtualMachineError::HintTimeLimit)
+        );
+        assert!(hint_processor.run_resources().hint_time_consumed());
+    }
+
     #[test]
     fn get_cairo_pie_no_program_base() {
         let runner = cairo_runner!(Default::default());
@@ -1,3 +1,5 @@
 pub mod builtin_runner;
 pub mod cairo_pie;
 pub mod cairo_runner;
+pub mod profiler;
+pub mod snapshot;
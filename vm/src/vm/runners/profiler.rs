@@ -0,0 +1,558 @@
+use crate::stdlib::{
+    collections::{HashMap, HashSet},
+    prelude::*,
+};
+
+use num_traits::ToPrimitive;
+
+use crate::{
+    types::{builtin_name::BuiltinName, relocatable::Relocatable},
+    vm::{
+        context::run_context::RunContext, decoding::decoder::decode_instruction,
+        errors::trace_errors::TraceError, trace::trace_entry::TraceEntry,
+    },
+};
+
+use super::cairo_runner::CairoRunner;
+
+/// Name given to the pcs that don't belong to any known function
+pub const UNKNOWN_FUNCTION: &str = "<unknown>";
+
+// Index of the execution segment, where ap & fp point to
+const EXECUTION_SEGMENT: isize = 1;
+
+/// Execution profile of a run, attributing the steps and builtin instances used to the call stacks they were used from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    /// Builtins used by the run, in the same order as the builtin counters of each sample
+    pub builtins: Vec<BuiltinName>,
+    /// Samples sorted by call stack
+    pub samples: Vec<ProfileSample>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileSample {
+    /// Functions of the call stack, starting from the outermost caller
+    pub stack: Vec<String>,
+    pub steps: u64,
+    /// Amount of instances of each builtin first accessed from this call stack
+    pub builtins: Vec<u64>,
+}
+
+impl Profile {
+    /// Builds the profile of a Cairo 0 run, using the functions declared in the program's identifiers.
+    /// The runner must have been run with the trace enabled
+    pub fn from_runner(runner: &CairoRunner) -> Result<Profile, TraceError> {
+        let functions: Vec<_> = runner
+            .get_program()
+            .iter_identifiers()
+            .filter(|(_, identifier)| identifier.type_.as_deref() == Some("function"))
+            .filter_map(|(name, identifier)| Some((identifier.pc?, name.to_string())))
+            .collect();
+        Self::from_runner_with_functions(runner, &functions)
+    }
+
+    /// Builds the profile of a run given the (pc, name) of each function of the program.
+    /// A function spans from its pc up to the pc of the next one.
+    /// The runner must have been run with the trace enabled
+    pub fn from_runner_with_functions(
+        runner: &CairoRunner,
+        functions: &[(usize, String)],
+    ) -> Result<Profile, TraceError> {
+        let trace = runner
+            .vm
+            .trace
+            .as_ref()
+            .ok_or(TraceError::TraceNotEnabled)?;
+        let mut builder = ProfileBuilder::new(runner, functions);
+        for entry in trace {
+            builder.add_step(entry);
+        }
+        Ok(builder.build())
+    }
+
+    /// Returns the profile in the folded stacks format used by flamegraph tools, weighted by steps.
+    /// Each line holds a call stack, with its functions separated by `;`, followed by its amount of steps
+    pub fn to_folded(&self) -> String {
+        let mut folded = String::new();
+        for sample in self.samples.iter().filter(|sample| sample.steps > 0) {
+            folded.push_str(&sample.stack.join(";"));
+            folded.push_str(&format!(" {}\n", sample.steps));
+        }
+        folded
+    }
+
+    /// Returns the profile encoded as an (uncompressed) pprof protobuf message.
+    /// Each sample holds the amount of steps, followed by the amount of instances of each builtin
+    pub fn to_pprof(&self) -> Vec<u8> {
+        let mut strings = StringTable::default();
+        let mut profile = Vec::new();
+
+        // sample_type
+        let steps = strings.index("steps");
+        let mut value_types = vec![(steps, strings.index("count"))];
+        let instances = strings.index("instances");
+        for builtin in self.builtins.iter() {
+            value_types.push((strings.index(builtin.to_str()), instances));
+        }
+        for (type_, unit) in value_types {
+            let mut value_type = Vec::new();
+            write_uint64_field(&mut value_type, 1, type_);
+            write_uint64_field(&mut value_type, 2, unit);
+            write_bytes_field(&mut profile, 1, &value_type);
+        }
+
+        // Each function has a single location, sharing its id
+        let mut function_ids = HashMap::<&str, u64>::new();
+        let mut function_names = Vec::new();
+        for sample in self.samples.iter() {
+            let mut location_ids = Vec::with_capacity(sample.stack.len());
+            // pprof stacks start from the innermost function
+            for function in sample.stack.iter().rev() {
+                let id = *function_ids.entry(function).or_insert_with(|| {
+                    function_names.push(function.as_str());
+                    function_names.len() as u64
+                });
+                location_ids.push(id);
+            }
+            let mut values = vec![sample.steps];
+            values.extend(sample.builtins.iter());
+            let mut sample = Vec::new();
+            write_packed_field(&mut sample, 1, &location_ids);
+            write_packed_field(&mut sample, 2, &values);
+            write_bytes_field(&mut profile, 2, &sample);
+        }
+
+        // location
+        for id in 1..=function_names.len() as u64 {
+            let mut line = Vec::new();
+            write_uint64_field(&mut line, 1, id);
+            let mut location = Vec::new();
+            write_uint64_field(&mut location, 1, id);
+            write_bytes_field(&mut location, 4, &line);
+            write_bytes_field(&mut profile, 4, &location);
+        }
+
+        // function
+        for (id, name) in (1..).zip(function_names) {
+            let name = strings.index(name);
+            let mut function = Vec::new();
+            write_uint64_field(&mut function, 1, id);
+            write_uint64_field(&mut function, 2, name);
+            write_uint64_field(&mut function, 3, name);
+            write_bytes_field(&mut profile, 5, &function);
+        }
+
+        // string_table
+        for string in strings.strings {
+            write_bytes_field(&mut profile, 6, string.as_bytes());
+        }
+        profile
+    }
+}
+
+struct ProfileBuilder<'a> {
+    runner: &'a CairoRunner,
+    program_segment: isize,
+    // Functions sorted by pc, followed by the unknown function
+    functions: Vec<(usize, &'a str)>,
+    // Segment index -> (builtin index, cells per instance)
+    builtin_segments: HashMap<isize, (usize, usize)>,
+    // Call stacks are stored as a tree, each node holding its parent & the index of its function
+    nodes: Vec<(Option<usize>, usize)>,
+    node_indexes: HashMap<(Option<usize>, usize), usize>,
+    // fp -> node of the caller of the frame
+    frames: HashMap<usize, Option<usize>>,
+    // Node -> (steps, builtin instances)
+    samples: HashMap<usize, (u64, Vec<u64>)>,
+    // (builtin index, instance) of the builtin instances already accessed
+    accessed_instances: HashSet<(usize, usize)>,
+}
+
+impl<'a> ProfileBuilder<'a> {
+    fn new(runner: &'a CairoRunner, functions: &'a [(usize, String)]) -> Self {
+        let mut functions: Vec<_> = functions
+            .iter()
+            .map(|(pc, name)| (*pc, name.as_str()))
+            .collect();
+        functions.sort();
+        functions.push((usize::MAX, UNKNOWN_FUNCTION));
+        let builtin_segments = runner
+            .vm
+            .builtin_runners
+            .iter()
+            .enumerate()
+            .map(|(index, builtin)| {
+                // The output builtin has no instances, each of its cells is counted instead
+                let cells_per_instance = (builtin.cells_per_instance() as usize).max(1);
+                (builtin.base() as isize, (index, cells_per_instance))
+            })
+            .collect();
+        ProfileBuilder {
+            runner,
+            program_segment: runner
+                .program_base
+                .map(|base| base.segment_index)
+                .unwrap_or_default(),
+            functions,
+            builtin_segments,
+            nodes: Vec::new(),
+            node_indexes: HashMap::new(),
+            frames: HashMap::new(),
+            samples: HashMap::new(),
+            accessed_instances: HashSet::new(),
+        }
+    }
+
+    fn add_step(&mut self, entry: &TraceEntry) {
+        let caller = self.caller_node(entry.fp);
+        let function = self.function_index(entry.pc);
+        let node = self.node(caller, function);
+        let n_builtins = self.runner.vm.builtin_runners.len();
+        let accessed_builtins: Vec<_> = self
+            .accessed_addresses(entry)
+            .into_iter()
+            .filter_map(|addr| {
+                let (builtin, cells_per_instance) =
+                    *self.builtin_segments.get(&addr.segment_index)?;
+                self.accessed_instances
+                    .insert((builtin, addr.offset / cells_per_instance))
+                    .then_some(builtin)
+            })
+            .collect();
+        let (steps, builtins) = self
+            .samples
+            .entry(node)
+            .or_insert_with(|| (0, vec![0; n_builtins]));
+        *steps += 1;
+        for builtin in accessed_builtins {
+            builtins[builtin] += 1;
+        }
+    }
+
+    fn build(self) -> Profile {
+        let mut samples: Vec<_> = self
+            .samples
+            .iter()
+            .map(|(node, (steps, builtins))| {
+                let mut stack = Vec::new();
+                let mut current = Some(*node);
+                while let Some(node) = current {
+                    let (parent, function) = self.nodes[node];
+                    stack.push(self.functions[function].1.to_string());
+                    current = parent;
+                }
+                stack.reverse();
+                ProfileSample {
+                    stack,
+                    steps: *steps,
+                    builtins: builtins.clone(),
+                }
+            })
+            .collect();
+        samples.sort_by(|a, b| a.stack.cmp(&b.stack));
+        Profile {
+            builtins: self
+                .runner
+                .vm
+                .builtin_runners
+                .iter()
+                .map(|builtin| builtin.name())
+                .collect(),
+            samples,
+        }
+    }
+
+    fn function_index(&self, pc: Relocatable) -> usize {
+        if pc.segment_index != self.program_segment {
+            return self.functions.len() - 1;
+        }
+        match self
+            .functions
+            .partition_point(|(function_pc, _)| *function_pc <= pc.offset)
+        {
+            0 => self.functions.len() - 1,
+            index => index - 1,
+        }
+    }
+
+    fn node(&mut self, parent: Option<usize>, function: usize) -> usize {
+        *self
+            .node_indexes
+            .entry((parent, function))
+            .or_insert_with(|| {
+                self.nodes.push((parent, function));
+                self.nodes.len() - 1
+            })
+    }
+
+    // Walks the fp chain, like `VirtualMachine::get_traceback_entries`, memoizing the caller stack of each frame
+    fn caller_node(&mut self, fp: usize) -> Option<usize> {
+        let mut pending_frames = Vec::new();
+        let mut current_fp = fp;
+        let mut node = loop {
+            if let Some(node) = self.frames.get(&current_fp) {
+                break *node;
+            }
+            match self.caller_frame(current_fp) {
+                Some((previous_fp, function)) => {
+                    pending_frames.push((current_fp, function));
+                    current_fp = previous_fp;
+                }
+                None => {
+                    self.frames.insert(current_fp, None);
+                    break None;
+                }
+            }
+        };
+        for (fp, function) in pending_frames.into_iter().rev() {
+            node = Some(self.node(node, function));
+            self.frames.insert(fp, node);
+        }
+        node
+    }
+
+    // Returns the fp of the caller of the frame & the function the call was made from
+    fn caller_frame(&self, fp: usize) -> Option<(usize, usize)> {
+        let memory = &self.runner.vm.segments.memory;
+        let fp = Relocatable::from((EXECUTION_SEGMENT, fp));
+        let return_pc = memory.get_relocatable((fp - 1).ok()?).ok()?;
+        let previous_fp = memory.get_relocatable((fp - 2).ok()?).ok()?;
+        // The initial frame returns to the end of the run, outside of the program segment
+        if return_pc.segment_index != self.program_segment
+            || previous_fp.segment_index != EXECUTION_SEGMENT
+            || previous_fp.offset >= fp.offset
+        {
+            return None;
+        }
+        let call_pc = (return_pc - 1).ok()?;
+        Some((previous_fp.offset, self.function_index(call_pc)))
+    }
+
+    // Returns the addresses of the dst, op0 & op1 operands of the instruction executed at the given step
+    fn accessed_addresses(&self, entry: &TraceEntry) -> Vec<Relocatable> {
+        let memory = &self.runner.vm.segments.memory;
+        let Some(instruction) = memory
+            .get_integer(entry.pc)
+            .ok()
+            .and_then(|encoded| encoded.to_u64())
+            .and_then(|encoded| decode_instruction(encoded).ok())
+        else {
+            return Vec::new();
+        };
+        let run_context = RunContext::new(entry.pc, entry.ap, entry.fp);
+        let op0_addr = run_context.compute_op0_addr(&instruction).ok();
+        let op0 = op0_addr.and_then(|addr| memory.get(&addr));
+        [
+            run_context.compute_dst_addr(&instruction).ok(),
+            op0_addr,
+            run_context
+                .compute_op1_addr(&instruction, op0.as_deref())
+                .ok(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+#[derive(Default)]
+struct StringTable {
+    // The first string of a pprof string table must be empty
+    strings: Vec<String>,
+    indexes: HashMap<String, u64>,
+}
+
+impl StringTable {
+    fn index(&mut self, string: &str) -> u64 {
+        if self.strings.is_empty() {
+            self.strings.push(String::new());
+            self.indexes.insert(String::new(), 0);
+        }
+        if let Some(index) = self.indexes.get(string) {
+            return *index;
+        }
+        self.strings.push(string.to_string());
+        self.indexes
+            .insert(string.to_string(), self.strings.len() as u64 - 1);
+        self.strings.len() as u64 - 1
+    }
+}
+
+// Minimal protobuf encoding, only the varint & length-delimited wire types are needed
+const WIRE_TYPE_VARINT: u64 = 0;
+const WIRE_TYPE_LEN: u64 = 2;
+
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+fn write_uint64_field(buffer: &mut Vec<u8>, field: u64, value: u64) {
+    write_varint(buffer, field << 3 | WIRE_TYPE_VARINT);
+    write_varint(buffer, value);
+}
+
+fn write_bytes_field(buffer: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    write_varint(buffer, field << 3 | WIRE_TYPE_LEN);
+    write_varint(buffer, bytes.len() as u64);
+    buffer.extend_from_slice(bytes);
+}
+
+fn write_packed_field(buffer: &mut Vec<u8>, field: u64, values: &[u64]) {
+    let mut packed = Vec::new();
+    for value in values {
+        write_varint(&mut packed, *value);
+    }
+    write_bytes_field(buffer, field, &packed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+        types::{layout_name::LayoutName, program::Program},
+        utils::test_utils::*,
+    };
+    use assert_matches::assert_matches;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn run(program: &Program, trace_enabled: bool) -> CairoRunner {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program, LayoutName::all_cairo, false, trace_enabled);
+        let end = cairo_runner.initialize(false).unwrap();
+        cairo_runner.run_until_pc(end, &mut hint_processor).unwrap();
+        cairo_runner
+    }
+
+    fn stack(functions: &[&str]) -> Vec<String> {
+        functions
+            .iter()
+            .map(|function| function.to_string())
+            .collect()
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn profile_from_program_identifiers() {
+        let runner = run(&output_range_check_program(), true);
+        let profile = Profile::from_runner(&runner).unwrap();
+        assert_eq!(
+            profile,
+            Profile {
+                builtins: vec![BuiltinName::output, BuiltinName::range_check],
+                samples: vec![
+                    ProfileSample {
+                        stack: stack(&["__main__.main"]),
+                        steps: 8,
+                        builtins: vec![0, 0],
+                    },
+                    ProfileSample {
+                        stack: stack(&["__main__.main", "__main__.check_range"]),
+                        steps: 7,
+                        builtins: vec![0, 2],
+                    },
+                    ProfileSample {
+                        stack: stack(&["__main__.main", "__main__.serialize_word"]),
+                        steps: 3,
+                        builtins: vec![1, 0],
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn profile_with_unknown_functions() {
+        let runner = run(&output_range_check_program(), true);
+        let functions = [(4, String::from("check_range")), (13, String::from("main"))];
+        let profile = Profile::from_runner_with_functions(&runner, &functions).unwrap();
+        assert_eq!(
+            profile
+                .samples
+                .iter()
+                .map(|sample| (sample.stack.clone(), sample.steps))
+                .collect::<Vec<_>>(),
+            vec![
+                (stack(&["main"]), 8),
+                (stack(&["main", UNKNOWN_FUNCTION]), 3),
+                (stack(&["main", "check_range"]), 7),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn profile_without_trace() {
+        let runner = run(&output_range_check_program(), false);
+        assert_matches!(
+            Profile::from_runner(&runner),
+            Err(TraceError::TraceNotEnabled)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn folded_stacks() {
+        let runner = run(&output_range_check_program(), true);
+        let profile = Profile::from_runner(&runner).unwrap();
+        assert_eq!(
+            profile.to_folded(),
+            "__main__.main 8\n\
+            __main__.main;__main__.check_range 7\n\
+            __main__.main;__main__.serialize_word 3\n"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn pprof_encoding() {
+        let profile = Profile {
+            builtins: vec![BuiltinName::range_check],
+            samples: vec![
+                ProfileSample {
+                    stack: stack(&["main"]),
+                    steps: 3,
+                    builtins: vec![0],
+                },
+                ProfileSample {
+                    stack: stack(&["main", "foo"]),
+                    steps: 200,
+                    builtins: vec![1],
+                },
+            ],
+        };
+        #[rustfmt::skip]
+        let expected: Vec<u8> = vec![
+            // sample_type: steps (count), range_check (instances)
+            0x0a, 0x04, 0x08, 0x01, 0x10, 0x02,
+            0x0a, 0x04, 0x08, 0x04, 0x10, 0x03,
+            // sample: [main], [3, 0]
+            0x12, 0x07, 0x0a, 0x01, 0x01, 0x12, 0x02, 0x03, 0x00,
+            // sample: [foo, main], [200, 1]
+            0x12, 0x09, 0x0a, 0x02, 0x02, 0x01, 0x12, 0x03, 0xc8, 0x01, 0x01,
+            // location: 1 -> function 1, 2 -> function 2
+            0x22, 0x06, 0x08, 0x01, 0x22, 0x02, 0x08, 0x01,
+            0x22, 0x06, 0x08, 0x02, 0x22, 0x02, 0x08, 0x02,
+            // function: 1 -> main, 2 -> foo
+            0x2a, 0x06, 0x08, 0x01, 0x10, 0x05, 0x18, 0x05,
+            0x2a, 0x06, 0x08, 0x02, 0x10, 0x06, 0x18, 0x06,
+            // string_table
+            0x32, 0x00,
+            0x32, 0x05, b's', b't', b'e', b'p', b's',
+            0x32, 0x05, b'c', b'o', b'u', b'n', b't',
+            0x32, 0x09, b'i', b'n', b's', b't', b'a', b'n', b'c', b'e', b's',
+            0x32, 0x0b, b'r', b'a', b'n', b'g', b'e', b'_', b'c', b'h', b'e', b'c', b'k',
+            0x32, 0x04, b'm', b'a', b'i', b'n',
+            0x32, 0x03, b'f', b'o', b'o',
+        ];
+        assert_eq!(profile.to_pprof(), expected);
+    }
+}
@@ -0,0 +1,667 @@
+use crate::stdlib::{any::Any, collections::HashMap, prelude::*};
+
+use num_bigint::{BigInt, BigUint};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    types::{
+        builtin_name::BuiltinName,
+        exec_scope::ExecutionScopes,
+        instance_definitions::mod_instance_def::ModInstanceDef,
+        layout::CairoLayoutParams,
+        layout_name::LayoutName,
+        relocatable::{MaybeRelocatable, Relocatable},
+    },
+    vm::{
+        errors::{memory_errors::MemoryError, runner_errors::RunnerError},
+        trace::trace_entry::TraceEntry,
+        vm_memory::{
+            memory::MemoryCell,
+            memory_backend::{MemoryBackend, MemoryData},
+            memory_segments::MemorySegmentManager,
+        },
+    },
+    Felt252,
+};
+
+use super::{
+    builtin_runner::{
+        BitwiseBuiltinRunner, BuiltinRunner, EcOpBuiltinRunner, HashBuiltinRunner,
+        KeccakBuiltinRunner, ModBuiltinRunner, OutputBuiltinRunner, PoseidonBuiltinRunner,
+        RangeCheckBuiltinRunner, SegmentArenaBuiltinRunner, SignatureBuiltinRunner, RC_N_PARTS_96,
+        RC_N_PARTS_STANDARD,
+    },
+    cairo_pie::BuiltinAdditionalData,
+    cairo_runner::RunnerMode,
+};
+
+/// Serializable state of a [`CairoRunner`](super::cairo_runner::CairoRunner) taken in the middle of a run
+/// Can be obtained via `CairoRunner::get_snapshot` and turned back into a runner via `CairoRunner::from_snapshot`
+/// The program itself is not part of the snapshot, it has to be provided when restoring it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CairoRunnerSnapshot {
+    pub layout: LayoutName,
+    pub dynamic_layout_params: Option<CairoLayoutParams>,
+    pub runner_mode: RunnerMode,
+    pub program_base: Option<Relocatable>,
+    pub execution_base: Option<Relocatable>,
+    pub entrypoint: Option<usize>,
+    pub initial_pc: Option<Relocatable>,
+    pub initial_ap: Option<Relocatable>,
+    pub initial_fp: Option<Relocatable>,
+    pub final_pc: Option<Relocatable>,
+    pub execution_public_memory: Option<Vec<usize>>,
+    pub registers: RegistersSnapshot,
+    pub current_step: usize,
+    pub rc_limits: Option<(isize, isize)>,
+    pub run_ended: bool,
+    pub run_finished: bool,
+    pub skip_instruction_execution: bool,
+    pub trace: Option<Vec<TraceEntry>>,
+    pub memory: MemorySnapshot,
+    pub builtins: Vec<BuiltinRunnerSnapshot>,
+    pub exec_scopes: Vec<HashMap<String, ScopeValue>>,
+}
+
+/// Values of the `RunContext` registers, ap & fp are represented as their offsets within the execution segment
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RegistersSnapshot {
+    pub pc: Relocatable,
+    pub ap: usize,
+    pub fp: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MemoryCellSnapshot {
+    pub value: MaybeRelocatable,
+    pub accessed: bool,
+}
+
+/// Cells of a memory segment, memory holes are left out
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SegmentSnapshot {
+    pub len: usize,
+    // The filled cells along with their offset, in ascending order
+    pub cells: Vec<(usize, MemoryCellSnapshot)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MemorySnapshot {
+    pub data: Vec<SegmentSnapshot>,
+    pub temp_data: Vec<SegmentSnapshot>,
+    pub relocation_rules: HashMap<usize, Relocatable>,
+    pub segment_sizes: HashMap<usize, usize>,
+    pub segment_used_sizes: Option<Vec<usize>>,
+    pub public_memory_offsets: HashMap<usize, Vec<(usize, usize)>>,
+    pub zero_segment_index: usize,
+    pub zero_segment_size: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BuiltinRunnerSnapshot {
+    pub name: BuiltinName,
+    pub ratio: Option<u32>,
+    pub included: bool,
+    pub base: usize,
+    pub stop_ptr: Option<usize>,
+    pub additional_data: BuiltinAdditionalData,
+    // Instance definition of the add_mod & mul_mod builtins, None for other builtins
+    pub mod_params: Option<ModParamsSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ModParamsSnapshot {
+    pub batch_size: usize,
+    pub word_bit_len: u32,
+}
+
+/// Execution scope variables that can be stored in a snapshot
+/// Snapshots can't be taken while a variable of any other type (such as the `DictManager`) is in scope
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ScopeValue {
+    Felt(Felt252),
+    FeltVec(Vec<Felt252>),
+    BigInt(BigInt),
+    BigUint(BigUint),
+    Usize(usize),
+    UsizeVec(Vec<usize>),
+    U64(u64),
+    Bool(bool),
+    String(String),
+    Relocatable(Relocatable),
+    MaybeRelocatable(MaybeRelocatable),
+    MaybeRelocatableVec(Vec<MaybeRelocatable>),
+}
+
+impl MemorySnapshot {
+    pub(crate) fn new(segments: &MemorySegmentManager) -> Self {
+        fn data_snapshot(data: &MemoryData) -> Vec<SegmentSnapshot> {
+            (0..data.num_segments())
+                .map(|segment| SegmentSnapshot {
+                    len: data.segment_len(segment).unwrap_or_default(),
+                    cells: data
+                        .cells(segment)
+                        .filter_map(|(offset, cell)| {
+                            let value = cell.get_value()?;
+                            Some((
+                                offset,
+                                MemoryCellSnapshot {
+                                    value,
+                                    accessed: cell.is_accessed(),
+                                },
+                            ))
+                        })
+                        .collect(),
+                })
+                .collect()
+        }
+        MemorySnapshot {
+            data: data_snapshot(&segments.memory.data),
+            temp_data: data_snapshot(&segments.memory.temp_data),
+            relocation_rules: segments.memory.relocation_rules.clone(),
+            segment_sizes: segments.segment_sizes.clone(),
+            segment_used_sizes: segments.segment_used_sizes.clone(),
+            public_memory_offsets: segments.public_memory_offsets.clone(),
+            zero_segment_index: segments.zero_segment_index,
+            zero_segment_size: segments.zero_segment_size,
+        }
+    }
+
+    /// Loads the snapshot's memory into an empty segment manager
+    /// Validated addresses are not part of the snapshot, they are recomputed once the builtin's validation rules are added
+    pub(crate) fn restore(self, segments: &mut MemorySegmentManager) -> Result<(), MemoryError> {
+        // The cells are written into the memory's backends, so that a custom backend is kept
+        fn restore_data(
+            data: &mut MemoryData,
+            snapshot: Vec<SegmentSnapshot>,
+            is_temp_data: bool,
+        ) -> Result<(), MemoryError> {
+            for (segment, segment_snapshot) in snapshot.into_iter().enumerate() {
+                data.add_segment();
+                data.grow_segment(segment, segment_snapshot.len)?;
+                for (offset, cell) in segment_snapshot.cells {
+                    let Some(memory_cell) = data.get_mut(segment, offset) else {
+                        // Temporary segments have negative indexes, starting from -1
+                        let segment_index = if is_temp_data {
+                            -(segment as isize) - 1
+                        } else {
+                            segment as isize
+                        };
+                        return Err(MemoryError::SnapshotCellOutOfBounds(Box::new(
+                            (segment_index, offset).into(),
+                        )));
+                    };
+                    *memory_cell = MemoryCell::new(cell.value);
+                    if cell.accessed {
+                        memory_cell.mark_accessed();
+                    }
+                }
+            }
+            Ok(())
+        }
+        restore_data(&mut segments.memory.data, self.data, false)?;
+        restore_data(&mut segments.memory.temp_data, self.temp_data, true)?;
+        segments.memory.allocated_cells = segments.memory.count_allocated_cells();
+        segments.memory.relocation_rules = self.relocation_rules;
+        segments.segment_sizes = self.segment_sizes;
+        segments.segment_used_sizes = self.segment_used_sizes;
+        segments.public_memory_offsets = self.public_memory_offsets;
+        segments.zero_segment_index = self.zero_segment_index;
+        segments.zero_segment_size = self.zero_segment_size;
+        Ok(())
+    }
+}
+
+impl BuiltinRunnerSnapshot {
+    pub(crate) fn new(builtin: &BuiltinRunner) -> Self {
+        BuiltinRunnerSnapshot {
+            name: builtin.name(),
+            ratio: builtin.ratio(),
+            included: builtin.included(),
+            base: builtin.base(),
+            stop_ptr: builtin.stop_ptr(),
+            additional_data: builtin.get_additional_data(),
+            mod_params: match builtin {
+                BuiltinRunner::Mod(modulo) => Some(ModParamsSnapshot {
+                    batch_size: modulo.batch_size(),
+                    word_bit_len: modulo.word_bit_len(),
+                }),
+                _ => None,
+            },
+        }
+    }
+
+    /// Rebuilds the builtin runner, its segments must have already been loaded into `segments`
+    pub(crate) fn restore(
+        &self,
+        segments: &MemorySegmentManager,
+    ) -> Result<BuiltinRunner, RunnerError> {
+        let mod_instance_def = || {
+            self.mod_params
+                .as_ref()
+                .map(|params| {
+                    ModInstanceDef::new(self.ratio, params.batch_size, params.word_bit_len)
+                })
+                .ok_or(RunnerError::SnapshotMissingModParams(self.name))
+        };
+        let mut builtin: BuiltinRunner = match self.name {
+            BuiltinName::output => OutputBuiltinRunner::new(self.included).into(),
+            BuiltinName::pedersen => HashBuiltinRunner::new(self.ratio, self.included).into(),
+            BuiltinName::range_check => {
+                RangeCheckBuiltinRunner::<RC_N_PARTS_STANDARD>::new(self.ratio, self.included)
+                    .into()
+            }
+            BuiltinName::ecdsa => SignatureBuiltinRunner::new(self.ratio, self.included).into(),
+            BuiltinName::bitwise => BitwiseBuiltinRunner::new(self.ratio, self.included).into(),
+            BuiltinName::ec_op => EcOpBuiltinRunner::new(self.ratio, self.included).into(),
+            BuiltinName::keccak => KeccakBuiltinRunner::new(self.ratio, self.included).into(),
+            BuiltinName::poseidon => PoseidonBuiltinRunner::new(self.ratio, self.included).into(),
+            BuiltinName::range_check96 => {
+                RangeCheckBuiltinRunner::<RC_N_PARTS_96>::new(self.ratio, self.included).into()
+            }
+            BuiltinName::segment_arena => SegmentArenaBuiltinRunner::new(self.included).into(),
+            BuiltinName::add_mod => {
+                ModBuiltinRunner::new_add_mod(&mod_instance_def()?, self.included).into()
+            }
+            BuiltinName::mul_mod => {
+                ModBuiltinRunner::new_mul_mod(&mod_instance_def()?, self.included).into()
+            }
+        };
+        builtin.set_base(self.base, segments);
+        if let Some(stop_ptr) = self.stop_ptr {
+            builtin.set_stop_ptr(stop_ptr);
+        }
+        // Once serialized, empty signature data can't be told apart from empty hash data
+        let has_additional_data = match &self.additional_data {
+            BuiltinAdditionalData::None => false,
+            BuiltinAdditionalData::Hash(data) => !data.is_empty(),
+            _ => true,
+        };
+        if has_additional_data {
+            builtin.extend_additional_data(&self.additional_data)?;
+        }
+        Ok(builtin)
+    }
+}
+
+impl ScopeValue {
+    /// Returns None if the value's type can't be stored in a snapshot
+    pub fn from_any(value: &dyn Any) -> Option<Self> {
+        macro_rules! downcast {
+            ($($ty:ty => $variant:ident),* $(,)?) => {
+                $(
+                    if let Some(v) = value.downcast_ref::<$ty>() {
+                        return Some(ScopeValue::$variant(v.clone()));
+                    }
+                )*
+            };
+        }
+        downcast!(
+            Felt252 => Felt,
+            Vec<Felt252> => FeltVec,
+            BigInt => BigInt,
+            BigUint => BigUint,
+            usize => Usize,
+            Vec<usize> => UsizeVec,
+            u64 => U64,
+            bool => Bool,
+            String => String,
+            Relocatable => Relocatable,
+            MaybeRelocatable => MaybeRelocatable,
+            Vec<MaybeRelocatable> => MaybeRelocatableVec,
+        );
+        None
+    }
+
+    pub fn into_any(self) -> Box<dyn Any> {
+        match self {
+            ScopeValue::Felt(v) => Box::new(v),
+            ScopeValue::FeltVec(v) => Box::new(v),
+            ScopeValue::BigInt(v) => Box::new(v),
+            ScopeValue::BigUint(v) => Box::new(v),
+            ScopeValue::Usize(v) => Box::new(v),
+            ScopeValue::UsizeVec(v) => Box::new(v),
+            ScopeValue::U64(v) => Box::new(v),
+            ScopeValue::Bool(v) => Box::new(v),
+            ScopeValue::String(v) => Box::new(v),
+            ScopeValue::Relocatable(v) => Box::new(v),
+            ScopeValue::MaybeRelocatable(v) => Box::new(v),
+            ScopeValue::MaybeRelocatableVec(v) => Box::new(v),
+        }
+    }
+}
+
+// Fails on the first variable whose type can't be stored in a snapshot, as the resumed run would be missing it
+pub(crate) fn exec_scopes_snapshot(
+    exec_scopes: &ExecutionScopes,
+) -> Result<Vec<HashMap<String, ScopeValue>>, RunnerError> {
+    exec_scopes
+        .data
+        .iter()
+        .map(|scope| {
+            scope
+                .iter()
+                .map(|(name, value)| {
+                    ScopeValue::from_any(value.as_ref())
+                        .map(|value| (name.clone(), value))
+                        .ok_or_else(|| {
+                            RunnerError::UnsupportedSnapshotScopeValue(name.as_str().into())
+                        })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub(crate) fn restore_exec_scopes(scopes: Vec<HashMap<String, ScopeValue>>) -> ExecutionScopes {
+    let mut exec_scopes = ExecutionScopes::new();
+    exec_scopes.data = scopes
+        .into_iter()
+        .map(|scope| {
+            scope
+                .into_iter()
+                .map(|(name, value)| (name, value.into_any()))
+                .collect()
+        })
+        .collect();
+    // The main scope can never be exited, so there should always be at least one
+    if exec_scopes.data.is_empty() {
+        exec_scopes.data.push(HashMap::new());
+    }
+    exec_scopes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::{cell::RefCell, rc::Rc};
+    use crate::{
+        any_box,
+        hint_processor::builtin_hint_processor::{
+            builtin_hint_processor_definition::BuiltinHintProcessor, dict_manager::DictManager,
+        },
+        types::program::Program,
+        utils::test_utils::*,
+        vm::{errors::vm_errors::VirtualMachineError, runners::cairo_runner::CairoRunner},
+    };
+    use assert_matches::assert_matches;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn initialized_runner(program: &Program) -> CairoRunner {
+        let mut cairo_runner = cairo_runner!(program, LayoutName::all_cairo, false, true);
+        cairo_runner.initialize(false).unwrap();
+        cairo_runner
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn snapshot_and_resume_matches_uninterrupted_run() {
+        let program = output_range_check_program();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+
+        let mut uninterrupted_runner = initialized_runner(&program);
+        let end = uninterrupted_runner.get_final_pc().unwrap();
+        uninterrupted_runner
+            .run_until_pc(end, &mut hint_processor)
+            .unwrap();
+
+        let mut interrupted_runner = initialized_runner(&program);
+        interrupted_runner
+            .run_for_steps(9, &mut hint_processor)
+            .unwrap();
+        let snapshot = interrupted_runner.get_snapshot().unwrap();
+        assert_eq!(snapshot.current_step, 9);
+        let serialized = serde_json::to_string(&snapshot).unwrap();
+        let deserialized: CairoRunnerSnapshot = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, snapshot);
+
+        let mut resumed_runner = CairoRunner::from_snapshot(&program, deserialized).unwrap();
+        assert_eq!(resumed_runner.get_final_pc(), Some(end));
+        resumed_runner
+            .run_until_pc(end, &mut hint_processor)
+            .unwrap();
+
+        assert_eq!(
+            resumed_runner.get_snapshot().unwrap(),
+            uninterrupted_runner.get_snapshot().unwrap()
+        );
+        assert_eq!(resumed_runner.vm.get_pc(), uninterrupted_runner.vm.get_pc());
+        assert_eq!(resumed_runner.vm.get_ap(), uninterrupted_runner.vm.get_ap());
+        assert_eq!(resumed_runner.vm.trace.unwrap().len(), 18);
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn restore_snapshot_keeps_builtin_state() {
+        let program = output_range_check_program();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = initialized_runner(&program);
+        cairo_runner.run_for_steps(12, &mut hint_processor).unwrap();
+        let snapshot = cairo_runner.get_snapshot().unwrap();
+
+        let restored_runner = CairoRunner::from_snapshot(&program, snapshot.clone()).unwrap();
+        assert_eq!(
+            restored_runner
+                .vm
+                .builtin_runners
+                .iter()
+                .map(BuiltinRunnerSnapshot::new)
+                .collect::<Vec<_>>(),
+            snapshot.builtins
+        );
+        // Validation rules are added back and existing range check cells are revalidated
+        assert_eq!(
+            restored_runner.vm.segments.memory.validated_addresses.len(),
+            cairo_runner.vm.segments.memory.validated_addresses.len()
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn restore_snapshot_keeps_run_state() {
+        let program = output_range_check_program();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = initialized_runner(&program);
+        let end = cairo_runner.get_final_pc().unwrap();
+        cairo_runner.run_until_pc(end, &mut hint_processor).unwrap();
+        cairo_runner
+            .end_run(false, false, &mut hint_processor)
+            .unwrap();
+        let snapshot = cairo_runner.get_snapshot().unwrap();
+        assert!(snapshot.run_ended);
+        assert!(snapshot.run_finished);
+
+        let mut restored_runner = CairoRunner::from_snapshot(&program, snapshot).unwrap();
+        assert_matches!(
+            restored_runner.end_run(false, false, &mut hint_processor),
+            Err(VirtualMachineError::RunnerError(
+                RunnerError::EndRunCalledTwice
+            ))
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn restore_mod_builtin_keeps_instance_def() {
+        let mut segments = MemorySegmentManager::new();
+        let mut builtin: BuiltinRunner =
+            ModBuiltinRunner::new_add_mod(&ModInstanceDef::new(Some(8), 2, 64), true).into();
+        builtin.initialize_segments(&mut segments);
+        let snapshot = BuiltinRunnerSnapshot::new(&builtin);
+        assert_eq!(
+            snapshot.mod_params,
+            Some(ModParamsSnapshot {
+                batch_size: 2,
+                word_bit_len: 64
+            })
+        );
+        assert_matches!(
+            snapshot.restore(&segments),
+            Ok(BuiltinRunner::Mod(modulo)) if modulo.batch_size() == 2 && modulo.word_bit_len() == 64
+        );
+        assert_matches!(
+            BuiltinRunnerSnapshot {
+                mod_params: None,
+                ..snapshot
+            }
+            .restore(&segments),
+            Err(RunnerError::SnapshotMissingModParams(BuiltinName::add_mod))
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn restore_snapshot_with_different_program() {
+        let program = output_range_check_program();
+        let mut cairo_runner = initialized_runner(&program);
+        cairo_runner
+            .run_for_steps(3, &mut BuiltinHintProcessor::new_empty())
+            .unwrap();
+        let snapshot = cairo_runner.get_snapshot().unwrap();
+
+        let other_program = program!(
+            builtins = vec![BuiltinName::output, BuiltinName::range_check],
+            data = vec_data!((1), (2), (3)),
+            main = Some(0),
+        );
+        assert_matches!(
+            CairoRunner::from_snapshot(&other_program, snapshot).err(),
+            Some(RunnerError::SnapshotProgramMismatch)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn snapshot_leaves_out_memory_holes() {
+        let program = output_range_check_program();
+        let mut cairo_runner = initialized_runner(&program);
+        // The execution segment follows the program segment
+        let far_addr = Relocatable::from((1, 1 << 20));
+        cairo_runner
+            .vm
+            .segments
+            .memory
+            .insert(far_addr, Felt252::from(7))
+            .unwrap();
+        let snapshot = cairo_runner.get_snapshot().unwrap();
+        let execution_segment = &snapshot.memory.data[far_addr.segment_index as usize];
+        assert_eq!(execution_segment.len, far_addr.offset + 1);
+        assert_eq!(
+            execution_segment.cells.last(),
+            Some(&(
+                far_addr.offset,
+                MemoryCellSnapshot {
+                    value: Felt252::from(7).into(),
+                    accessed: false,
+                }
+            ))
+        );
+        assert!(execution_segment.cells.len() < 10);
+
+        let restored_runner = CairoRunner::from_snapshot(&program, snapshot).unwrap();
+        assert_eq!(
+            restored_runner
+                .vm
+                .segments
+                .memory
+                .get_integer(far_addr)
+                .unwrap()
+                .as_ref(),
+            &Felt252::from(7)
+        );
+        assert_eq!(
+            restored_runner.vm.segments.memory.allocated_cells(),
+            cairo_runner.vm.segments.memory.allocated_cells()
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn restore_snapshot_with_cell_beyond_segment_length() {
+        let program = output_range_check_program();
+        let cairo_runner = initialized_runner(&program);
+        let out_of_bounds_cell = |len| SegmentSnapshot {
+            len,
+            cells: vec![(
+                len,
+                MemoryCellSnapshot {
+                    value: Felt252::from(7).into(),
+                    accessed: false,
+                },
+            )],
+        };
+
+        let mut snapshot = cairo_runner.get_snapshot().unwrap();
+        snapshot.memory.data[1] = out_of_bounds_cell(2);
+        assert_matches!(
+            CairoRunner::from_snapshot(&program, snapshot).err(),
+            Some(RunnerError::Memory(MemoryError::SnapshotCellOutOfBounds(addr))) if *addr == Relocatable::from((1, 2))
+        );
+
+        let mut snapshot = cairo_runner.get_snapshot().unwrap();
+        snapshot.memory.temp_data = vec![out_of_bounds_cell(0)];
+        assert_matches!(
+            CairoRunner::from_snapshot(&program, snapshot).err(),
+            Some(RunnerError::Memory(MemoryError::SnapshotCellOutOfBounds(addr))) if *addr == Relocatable::from((-1, 0))
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn exec_scopes_snapshot_and_restore() {
+        let mut exec_scopes = ExecutionScopes::new();
+        exec_scopes.insert_value("n", 5_usize);
+        exec_scopes.insert_value("value", Felt252::from(17));
+        exec_scopes.enter_scope(HashMap::from([(
+            String::from("keys"),
+            any_box!(vec![Felt252::ONE, Felt252::TWO]),
+        )]));
+
+        let scopes = exec_scopes_snapshot(&exec_scopes).unwrap();
+        assert_eq!(
+            scopes,
+            vec![
+                HashMap::from([
+                    (String::from("n"), ScopeValue::Usize(5)),
+                    (String::from("value"), ScopeValue::Felt(Felt252::from(17))),
+                ]),
+                HashMap::from([(
+                    String::from("keys"),
+                    ScopeValue::FeltVec(vec![Felt252::ONE, Felt252::TWO])
+                )]),
+            ]
+        );
+
+        let restored = restore_exec_scopes(scopes);
+        assert_eq!(restored.data.len(), 2);
+        assert_eq!(
+            restored.get_ref::<Vec<Felt252>>("keys").unwrap(),
+            &vec![Felt252::ONE, Felt252::TWO]
+        );
+        assert_eq!(
+            restored.data[0]["value"].downcast_ref::<Felt252>(),
+            Some(&Felt252::from(17))
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn exec_scopes_snapshot_unsupported_value() {
+        let mut exec_scopes = ExecutionScopes::new();
+        exec_scopes.insert_value("n", 5_usize);
+        exec_scopes.enter_scope(HashMap::new());
+        exec_scopes.insert_value("dict_manager", Rc::new(RefCell::new(DictManager::new())));
+        assert_matches!(
+            exec_scopes_snapshot(&exec_scopes),
+            Err(RunnerError::UnsupportedSnapshotScopeValue(name)) if name.as_ref() == "dict_manager"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn restore_empty_exec_scopes() {
+        assert_eq!(restore_exec_scopes(Vec::new()).data.len(), 1);
+    }
+}
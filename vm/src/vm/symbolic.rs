@@ -0,0 +1,942 @@
+//! Symbolic (concolic) execution of Cairo 0 programs.
+//!
+//! Memory cells marked via [`VirtualMachine::make_symbolic`](crate::vm::vm_core::VirtualMachine::make_symbolic)
+//! keep their concrete value, which drives the run, while the vm tracks how the values computed from them
+//! relate to each other. Assertions over symbolic values are recorded as constraints instead of failing, and
+//! conditional jumps over symbolic values are recorded as branches. Assertions over concrete values are still checked.
+//! When the concrete values of a symbolic assertion differ, the run carries on but the path is reported as unknown
+//! (see [`SymbolicState::unsatisfied_assertion`]), as its concrete run doesn't satisfy its constraints.
+//! The collected constraints can be exported in SMT-LIB via [`SymbolicState::to_smtlib`], and solved to obtain
+//! the inputs leading the run through other paths, which are explored via
+//! [`CairoRunner::explore_symbolic_paths`](crate::vm::runners::cairo_runner::CairoRunner::explore_symbolic_paths).
+//!
+//! Values handed over to builtins, used as pointers or copied by hints are treated as concrete. When a symbolic
+//! value has to be concretized, a constraint pinning it to its concrete value is recorded.
+
+use crate::stdlib::{collections::HashMap, fmt, prelude::*};
+
+use crate::{
+    types::{
+        instruction::{ApUpdate, FpUpdate, Instruction, Opcode, PcUpdate, Res},
+        relocatable::{MaybeRelocatable, Relocatable},
+    },
+    vm::{
+        errors::vm_errors::VirtualMachineError,
+        runners::builtin_runner::BuiltinRunner,
+        vm_core::{DeducedOperands, Operands, OperandsAddresses},
+    },
+    Felt252,
+};
+
+/// A field element which is either known or represented by a variable
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SymbolicValue {
+    Const(Felt252),
+    Var(usize),
+}
+
+/// An operation over symbolic values
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SymbolicExpr {
+    Value(SymbolicValue),
+    Add(SymbolicValue, SymbolicValue),
+    Sub(SymbolicValue, SymbolicValue),
+    Mul(SymbolicValue, SymbolicValue),
+}
+
+/// A constraint over the field, collected along the executed path
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Constraint {
+    Eq(SymbolicExpr, SymbolicExpr),
+    IsZero(SymbolicValue),
+    NonZero(SymbolicValue),
+}
+
+/// A conditional jump whose condition depended on a symbolic value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SymbolicBranch {
+    pub pc: Relocatable,
+    /// Whether the jump was taken, i.e. its condition was non-zero
+    pub taken: bool,
+    /// Index of the constraint recording the decision
+    pub constraint: usize,
+}
+
+/// A path run by [`CairoRunner::explore_symbolic_paths`](crate::vm::runners::cairo_runner::CairoRunner::explore_symbolic_paths)
+#[derive(Debug)]
+pub struct SymbolicPath {
+    /// The constraints & branches collected along the path
+    pub state: SymbolicState,
+    /// The outcome of the run, which may fail on infeasible paths
+    pub result: Result<(), VirtualMachineError>,
+}
+
+impl SymbolicPath {
+    /// Whether the path is known to be feasible, i.e. its concrete run satisfied all of its constraints.
+    /// Otherwise its feasibility is unknown: the run went past an assertion over symbolic values whose concrete
+    /// values differed, and the solver may not be able to satisfy the path's constraints
+    pub fn is_known_feasible(&self) -> bool {
+        self.state.unsatisfied_assertion.is_none()
+    }
+}
+
+/// The symbolic values of the operands of the current instruction, None for concrete ones
+#[derive(Clone, Debug, Default)]
+struct SymbolicOperands {
+    dst: Option<SymbolicValue>,
+    op0: Option<SymbolicValue>,
+    op1: Option<SymbolicValue>,
+    res: Option<SymbolicValue>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SymbolicState {
+    // Names of the variables, the user-defined ones followed by the ones defined during the run
+    variables: Vec<String>,
+    // Maps the symbolic memory cells to their variables
+    cells: HashMap<Relocatable, usize>,
+    constraints: Vec<Constraint>,
+    branches: Vec<SymbolicBranch>,
+    // Symbolic dst of the instruction being run, used to record jnz branches
+    current_dst: Option<SymbolicValue>,
+    // Index of the constraint recording the first assertion whose concrete values differed
+    unsatisfied_assertion: Option<usize>,
+}
+
+impl SymbolicState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names of the variables, indexed by their id
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    pub fn branches(&self) -> &[SymbolicBranch] {
+        &self.branches
+    }
+
+    /// Index of the constraint recording the first assertion over symbolic values whose concrete values
+    /// differed, if any. The run carried on past it, so the concrete values of the path don't satisfy its constraints
+    pub fn unsatisfied_assertion(&self) -> Option<usize> {
+        self.unsatisfied_assertion
+    }
+
+    /// Returns the variable held by a memory cell, if it is symbolic
+    pub fn get_cell(&self, address: Relocatable) -> Option<usize> {
+        self.cells.get(&address).copied()
+    }
+
+    /// Returns the memory cells holding a variable
+    pub(crate) fn cells_of(&self, var: usize) -> impl Iterator<Item = Relocatable> + '_ {
+        self.cells
+            .iter()
+            .filter(move |(_, cell_var)| **cell_var == var)
+            .map(|(address, _)| *address)
+    }
+
+    /// Makes a memory cell symbolic, returning the id of its variable.
+    /// The name is suffixed with the id if it is already taken
+    pub(crate) fn add_cell(&mut self, address: Relocatable, name: &str) -> usize {
+        let id = self.variables.len();
+        let name = if self.variables.iter().any(|var| var == name) {
+            format!("{name}_{id}")
+        } else {
+            name.to_string()
+        };
+        self.variables.push(name);
+        self.cells.insert(address, id);
+        id
+    }
+
+    fn new_var(&mut self) -> usize {
+        let id = self.variables.len();
+        self.variables.push(format!("_v{id}"));
+        id
+    }
+
+    /// Returns a value equal to the expression, defining a new variable if needed
+    fn define(&mut self, expr: SymbolicExpr) -> SymbolicValue {
+        match expr {
+            SymbolicExpr::Value(value) => value,
+            expr => {
+                let var = self.new_var();
+                self.constraints.push(Constraint::Eq(
+                    SymbolicExpr::Value(SymbolicValue::Var(var)),
+                    expr,
+                ));
+                SymbolicValue::Var(var)
+            }
+        }
+    }
+
+    /// Pins a symbolic value to its concrete one
+    fn concretize(&mut self, symbolic: &Option<SymbolicValue>, concrete: &MaybeRelocatable) {
+        if let (Some(value @ SymbolicValue::Var(_)), MaybeRelocatable::Int(num)) =
+            (symbolic, concrete)
+        {
+            self.constraints.push(Constraint::Eq(
+                SymbolicExpr::Value(value.clone()),
+                SymbolicExpr::Value(SymbolicValue::Const(*num)),
+            ));
+        }
+    }
+
+    /// Applies a binary operation, returning None if both operands are concrete.
+    /// Operations mixing symbolic values with relocatables concretize them
+    fn binary_op(
+        &mut self,
+        op: fn(SymbolicValue, SymbolicValue) -> SymbolicExpr,
+        lhs: (&Option<SymbolicValue>, &MaybeRelocatable),
+        rhs: (&Option<SymbolicValue>, &MaybeRelocatable),
+    ) -> Option<SymbolicValue> {
+        if lhs.0.is_none() && rhs.0.is_none() {
+            return None;
+        }
+        match (lhs.1, rhs.1) {
+            (MaybeRelocatable::Int(lhs_num), MaybeRelocatable::Int(rhs_num)) => {
+                let lhs = lhs.0.clone().unwrap_or(SymbolicValue::Const(*lhs_num));
+                let rhs = rhs.0.clone().unwrap_or(SymbolicValue::Const(*rhs_num));
+                Some(self.define(op(lhs, rhs)))
+            }
+            _ => {
+                self.concretize(lhs.0, lhs.1);
+                self.concretize(rhs.0, rhs.1);
+                None
+            }
+        }
+    }
+
+    /// Defines a new variable `quotient` such that `quotient * divisor = dividend`,
+    /// mirroring the deduction of a multiplication's operand
+    fn divide(
+        &mut self,
+        dividend: (&Option<SymbolicValue>, &MaybeRelocatable),
+        divisor: (&Option<SymbolicValue>, &MaybeRelocatable),
+    ) -> Option<SymbolicValue> {
+        if dividend.0.is_none() && divisor.0.is_none() {
+            return None;
+        }
+        let (MaybeRelocatable::Int(dividend_num), MaybeRelocatable::Int(divisor_num)) =
+            (dividend.1, divisor.1)
+        else {
+            return None;
+        };
+        let dividend = dividend
+            .0
+            .clone()
+            .unwrap_or(SymbolicValue::Const(*dividend_num));
+        let divisor = divisor
+            .0
+            .clone()
+            .unwrap_or(SymbolicValue::Const(*divisor_num));
+        let quotient = SymbolicValue::Var(self.new_var());
+        self.constraints.push(Constraint::NonZero(divisor.clone()));
+        self.constraints.push(Constraint::Eq(
+            SymbolicExpr::Mul(quotient.clone(), divisor),
+            SymbolicExpr::Value(dividend),
+        ));
+        Some(quotient)
+    }
+
+    /// Tracks the symbolic values of an instruction's operands, recording the constraints they are subject to.
+    /// Returns true if the instruction's assertion is symbolic, in which case it was recorded as a
+    /// constraint and its concrete values don't have to be equal
+    pub(crate) fn record_operands(
+        &mut self,
+        instruction: &Instruction,
+        operands: &Operands,
+        addresses: &OperandsAddresses,
+        deduced: &DeducedOperands,
+        builtin_runners: &[BuiltinRunner],
+    ) -> bool {
+        let is_builtin_cell = |address: Relocatable| {
+            builtin_runners
+                .iter()
+                .any(|builtin| builtin.base() as isize == address.segment_index)
+        };
+        let cell = |address| self.get_cell(address).map(SymbolicValue::Var);
+        let mut sym = SymbolicOperands {
+            dst: cell(addresses.dst_addr).filter(|_| !deduced.was_dest_deducted()),
+            op0: cell(addresses.op0_addr).filter(|_| !deduced.was_op0_deducted()),
+            op1: cell(addresses.op1_addr).filter(|_| !deduced.was_op1_deducted()),
+            res: None,
+        };
+        let is_assert_eq = instruction.opcode == Opcode::AssertEq;
+
+        // Mirror the deductions made by the vm, builtin cells are deduced concretely
+        let mut res_deduced = false;
+        if is_assert_eq && deduced.was_op0_deducted() && !is_builtin_cell(addresses.op0_addr) {
+            let dst = (&sym.dst, &operands.dst);
+            match instruction.res {
+                Res::Add => {
+                    sym.op0 = self.binary_op(SymbolicExpr::Sub, dst, (&sym.op1, &operands.op1));
+                    (sym.res, res_deduced) = (sym.dst.clone(), true);
+                }
+                Res::Mul => {
+                    sym.op0 = self.divide(dst, (&sym.op1, &operands.op1));
+                    (sym.res, res_deduced) = (sym.dst.clone(), true);
+                }
+                _ => (),
+            }
+        }
+        if is_assert_eq && deduced.was_op1_deducted() && !is_builtin_cell(addresses.op1_addr) {
+            let dst = (&sym.dst, &operands.dst);
+            match instruction.res {
+                Res::Op1 => sym.op1 = sym.dst.clone(),
+                Res::Add => {
+                    sym.op1 = self.binary_op(SymbolicExpr::Sub, dst, (&sym.op0, &operands.op0))
+                }
+                Res::Mul => sym.op1 = self.divide(dst, (&sym.op0, &operands.op0)),
+                Res::Unconstrained => (),
+            }
+            if instruction.res != Res::Unconstrained {
+                (sym.res, res_deduced) = (sym.dst.clone(), true);
+            }
+        }
+        if !res_deduced {
+            if let Some(res) = &operands.res {
+                let op0 = (&sym.op0, &operands.op0);
+                let op1 = (&sym.op1, &operands.op1);
+                sym.res = match instruction.res {
+                    Res::Op1 => sym.op1.clone(),
+                    Res::Add => self.binary_op(SymbolicExpr::Add, op0, op1),
+                    Res::Mul => self.binary_op(SymbolicExpr::Mul, op0, op1),
+                    Res::Unconstrained => None,
+                };
+                if res.get_relocatable().is_some() {
+                    self.concretize(&sym.res, res);
+                    sym.res = None;
+                }
+            }
+        }
+        if is_assert_eq && deduced.was_dest_deducted() {
+            sym.dst = sym.res.clone();
+        }
+
+        for (value, address, was_deduced) in [
+            (&sym.dst, addresses.dst_addr, deduced.was_dest_deducted()),
+            (&sym.op0, addresses.op0_addr, deduced.was_op0_deducted()),
+            (&sym.op1, addresses.op1_addr, deduced.was_op1_deducted()),
+        ] {
+            if let (true, Some(SymbolicValue::Var(var))) = (was_deduced, value) {
+                self.cells.insert(address, *var);
+            }
+        }
+
+        // Registers can't hold symbolic values
+        if instruction.ap_update == ApUpdate::Add {
+            if let Some(res) = &operands.res {
+                self.concretize(&sym.res, res);
+            }
+        }
+        if instruction.fp_update == FpUpdate::Dst {
+            self.concretize(&sym.dst, &operands.dst);
+        }
+        match instruction.pc_update {
+            PcUpdate::Jump | PcUpdate::JumpRel => {
+                if let Some(res) = &operands.res {
+                    self.concretize(&sym.res, res);
+                }
+            }
+            PcUpdate::Jnz => self.concretize(&sym.op1, &operands.op1),
+            PcUpdate::Regular => (),
+        }
+
+        self.current_dst = sym.dst.clone();
+
+        let Some(res) = operands.res.as_ref().filter(|_| {
+            is_assert_eq && !deduced.was_dest_deducted() && (sym.dst.is_some() || sym.res.is_some())
+        }) else {
+            return false;
+        };
+        match (
+            value_or_const(&sym.dst, &operands.dst),
+            value_or_const(&sym.res, res),
+        ) {
+            (Some(dst), Some(res_value)) => {
+                if dst != res_value {
+                    if operands.dst != *res && self.unsatisfied_assertion.is_none() {
+                        self.unsatisfied_assertion = Some(self.constraints.len());
+                    }
+                    self.constraints.push(Constraint::Eq(
+                        SymbolicExpr::Value(dst),
+                        SymbolicExpr::Value(res_value),
+                    ));
+                }
+                true
+            }
+            _ => {
+                // Symbolic values compared to relocatables are pinned to their concrete values
+                self.concretize(&sym.dst, &operands.dst);
+                self.concretize(&sym.res, res);
+                false
+            }
+        }
+    }
+
+    /// Records a jnz over the current instruction's dst, if it is symbolic.
+    /// The decision is taken on the concrete condition
+    pub(crate) fn record_jnz(&mut self, pc: Relocatable, is_zero: bool) {
+        if let Some(dst) = self.current_dst.take() {
+            self.push_branch(pc, !is_zero, dst);
+        }
+    }
+
+    fn push_branch(&mut self, pc: Relocatable, taken: bool, condition: SymbolicValue) {
+        self.branches.push(SymbolicBranch {
+            pc,
+            taken,
+            constraint: self.constraints.len(),
+        });
+        self.constraints.push(match taken {
+            true => Constraint::NonZero(condition),
+            false => Constraint::IsZero(condition),
+        });
+    }
+
+    /// Returns the state leading to the `index`-th branch, with the decision taken on it flipped.
+    /// Solving its constraints yields the inputs leading the run through the other side of the branch
+    pub fn flip_branch(&self, index: usize) -> Option<SymbolicState> {
+        let branch = self.branches.get(index)?;
+        let (Constraint::NonZero(condition) | Constraint::IsZero(condition)) =
+            self.constraints.get(branch.constraint)?.clone()
+        else {
+            return None;
+        };
+        let mut state = self.clone();
+        state.constraints.truncate(branch.constraint);
+        state.branches.truncate(index);
+        state.unsatisfied_assertion = state
+            .unsatisfied_assertion
+            .filter(|constraint| *constraint < branch.constraint);
+        state.push_branch(branch.pc, !branch.taken, condition);
+        Some(state)
+    }
+
+    /// Writes the collected constraints as an SMT-LIB script over the integers, which can be solved to obtain
+    /// values for the variables leading the run through the same path
+    pub fn to_smtlib(&self) -> String {
+        let mut script = String::from("(set-logic QF_NIA)\n");
+        script += &format!(
+            "(define-fun P () Int {})\n",
+            Felt252::MAX.to_biguint() + 1_u32
+        );
+        for var in self.variables.iter() {
+            let var = smtlib_symbol(var);
+            script += &format!("(declare-const {var} Int)\n");
+            script += &format!("(assert (and (<= 0 {var}) (< {var} P)))\n");
+        }
+        for constraint in self.constraints.iter() {
+            script += &format!("(assert {})\n", Smtlib(self, constraint));
+        }
+        script += "(check-sat)\n(get-model)\n";
+        script
+    }
+}
+
+fn value_or_const(
+    symbolic: &Option<SymbolicValue>,
+    concrete: &MaybeRelocatable,
+) -> Option<SymbolicValue> {
+    symbolic
+        .clone()
+        .or_else(|| concrete.get_int().map(SymbolicValue::Const))
+}
+
+/// Quotes the symbol unless it is a simple SMT-LIB symbol
+fn smtlib_symbol(name: &str) -> String {
+    let simple = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "~!@$%^&*_-+=<>.?/".contains(c))
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && !name.is_empty();
+    if simple {
+        name.to_string()
+    } else {
+        format!("|{}|", name.replace(['|', '\\'], "_"))
+    }
+}
+
+/// Displays a value, expression or constraint in SMT-LIB
+struct Smtlib<'a, T>(&'a SymbolicState, &'a T);
+
+impl fmt::Display for Smtlib<'_, SymbolicValue> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.1 {
+            SymbolicValue::Const(num) => write!(f, "{}", num.to_biguint()),
+            SymbolicValue::Var(var) => write!(f, "{}", smtlib_symbol(&self.0.variables[*var])),
+        }
+    }
+}
+
+impl fmt::Display for Smtlib<'_, SymbolicExpr> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (op, lhs, rhs) = match self.1 {
+            SymbolicExpr::Value(value) => return write!(f, "{}", Smtlib(self.0, value)),
+            SymbolicExpr::Add(lhs, rhs) => ("+", lhs, rhs),
+            SymbolicExpr::Sub(lhs, rhs) => ("-", lhs, rhs),
+            SymbolicExpr::Mul(lhs, rhs) => ("*", lhs, rhs),
+        };
+        write!(f, "({op} {} {})", Smtlib(self.0, lhs), Smtlib(self.0, rhs))
+    }
+}
+
+impl fmt::Display for Smtlib<'_, Constraint> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.1 {
+            Constraint::Eq(lhs, rhs) => write!(
+                f,
+                "(= (mod {} P) (mod {} P))",
+                Smtlib(self.0, lhs),
+                Smtlib(self.0, rhs)
+            ),
+            Constraint::IsZero(value) => write!(f, "(= (mod {} P) 0)", Smtlib(self.0, value)),
+            Constraint::NonZero(value) => {
+                write!(f, "(not (= (mod {} P) 0))", Smtlib(self.0, value))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+        relocatable, stdlib::collections::HashMap, utils::test_utils::*,
+        vm::runners::cairo_runner::CairoRunner,
+    };
+    use assert_matches::assert_matches;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    /*Program used:
+    func main() {
+        [ap] = 7, ap++;
+        [ap] = [ap - 1] * 3, ap++;
+        jmp not_zero if [ap - 1] != 0;
+        [ap] = 1, ap++;
+        ret;
+        not_zero:
+        [ap - 1] = 22;
+        ret;
+    }
+    */
+    fn branching_program() -> crate::types::program::Program {
+        program!(
+            data = vec_data!(
+                (0x480680017fff8000_i64),
+                (7),
+                (0x484480017fff8000_i64),
+                (3),
+                (0x020680017fff7fff_i64),
+                (6),
+                (0x480680017fff8000_i64),
+                (1),
+                (0x208b7fff7fff7ffe_i64),
+                (0),
+                (0x400680017fff7fff_i64),
+                (22),
+                (0x208b7fff7fff7ffe_i64)
+            ),
+            main = Some(0),
+        )
+    }
+
+    fn initialized_runner(program: &crate::types::program::Program) -> (CairoRunner, Relocatable) {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let end = cairo_runner.initialize(false).unwrap();
+        cairo_runner.run_for_steps(1, &mut hint_processor).unwrap();
+        (cairo_runner, end)
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn symbolic_assertion_is_recorded_instead_of_failing() {
+        let program = branching_program();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let (mut cairo_runner, end) = initialized_runner(&program);
+        assert_matches!(
+            cairo_runner
+                .fork()
+                .unwrap()
+                .run_until_pc(end, &mut hint_processor),
+            Err(VirtualMachineError::DiffAssertValues(_))
+        );
+
+        assert_eq!(
+            cairo_runner.vm.make_symbolic(relocatable!(1, 2), "x"),
+            Ok(0)
+        );
+        cairo_runner.run_until_pc(end, &mut hint_processor).unwrap();
+        let state = cairo_runner.vm.get_symbolic_state().unwrap();
+        assert_eq!(state.variables(), ["x", "_v1"]);
+        assert_eq!(state.get_cell(relocatable!(1, 3)), Some(1));
+        let (x, v1) = (SymbolicValue::Var(0), SymbolicValue::Var(1));
+        assert_eq!(
+            state.constraints(),
+            [
+                Constraint::Eq(
+                    SymbolicExpr::Value(v1.clone()),
+                    SymbolicExpr::Mul(x, SymbolicValue::Const(Felt252::from(3)))
+                ),
+                Constraint::NonZero(v1.clone()),
+                Constraint::Eq(
+                    SymbolicExpr::Value(v1),
+                    SymbolicExpr::Value(SymbolicValue::Const(Felt252::from(22)))
+                ),
+            ]
+        );
+        assert_eq!(
+            state.branches(),
+            [SymbolicBranch {
+                pc: relocatable!(0, 4),
+                taken: true,
+                constraint: 1
+            }]
+        );
+        // x * 3 = 21, not 22
+        assert_eq!(state.unsatisfied_assertion(), Some(2));
+    }
+
+    /*Program used:
+    func main() {
+        [ap] = 7, ap++;
+        [ap] = [ap - 1] + 5, ap++;
+        [ap - 1] = 12;
+        [ap] = 2, ap++;
+        [ap - 1] = 3;
+        ret;
+    }
+    */
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn symbolic_assertion_is_exported_and_concrete_assertion_is_checked() {
+        let program = program!(
+            data = vec_data!(
+                (0x480680017fff8000_i64),
+                (7),
+                (0x482480017fff8000_i64),
+                (5),
+                (0x400680017fff7fff_i64),
+                (12),
+                (0x480680017fff8000_i64),
+                (2),
+                (0x400680017fff7fff_i64),
+                (3),
+                (0x208b7fff7fff7ffe_i64)
+            ),
+            main = Some(0),
+        );
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let (mut cairo_runner, end) = initialized_runner(&program);
+        cairo_runner
+            .vm
+            .make_symbolic(relocatable!(1, 2), "x")
+            .unwrap();
+
+        assert_matches!(
+            cairo_runner.run_until_pc(end, &mut hint_processor),
+            Err(VirtualMachineError::DiffAssertValues(bx)) if *bx == (
+                MaybeRelocatable::from(2),
+                MaybeRelocatable::from(3)
+            )
+        );
+        assert_eq!(
+            cairo_runner.vm.get_symbolic_state().unwrap().to_smtlib(),
+            "(set-logic QF_NIA)\n\
+            (define-fun P () Int 3618502788666131213697322783095070105623107215331596699973092056135872020481)\n\
+            (declare-const x Int)\n\
+            (assert (and (<= 0 x) (< x P)))\n\
+            (declare-const _v1 Int)\n\
+            (assert (and (<= 0 _v1) (< _v1 P)))\n\
+            (assert (= (mod _v1 P) (mod (+ x 5) P)))\n\
+            (assert (= (mod _v1 P) (mod 12 P)))\n\
+            (check-sat)\n\
+            (get-model)\n"
+        );
+    }
+
+    // Solves the constraints of the paths of programs branching directly on their inputs
+    fn solve_branches(state: &SymbolicState) -> Option<HashMap<usize, Felt252>> {
+        state
+            .branches()
+            .iter()
+            .map(|branch| match &state.constraints()[branch.constraint] {
+                Constraint::IsZero(SymbolicValue::Var(var)) => Some((*var, Felt252::ZERO)),
+                Constraint::NonZero(SymbolicValue::Var(var)) => Some((*var, Felt252::ONE)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn explore_symbolic_paths_flips_branches() {
+        let program = branching_program();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let (mut cairo_runner, end) = initialized_runner(&program);
+        cairo_runner
+            .vm
+            .make_symbolic(relocatable!(1, 2), "x")
+            .unwrap();
+
+        let mut solved = Vec::new();
+        let paths = cairo_runner
+            .explore_symbolic_paths(end, &mut hint_processor, 10, &mut |state| {
+                solved.push(state.constraints().to_vec());
+                // x * 3 is zero when x is
+                Some(HashMap::from([(0, Felt252::ZERO)]))
+            })
+            .unwrap();
+        let (x, v1) = (SymbolicValue::Var(0), SymbolicValue::Var(1));
+        assert_eq!(
+            solved,
+            [vec![
+                Constraint::Eq(
+                    SymbolicExpr::Value(v1.clone()),
+                    SymbolicExpr::Mul(x, SymbolicValue::Const(Felt252::from(3)))
+                ),
+                Constraint::IsZero(v1.clone()),
+            ]]
+        );
+        assert_eq!(paths.len(), 2);
+        assert!(paths.iter().all(|path| path.result.is_ok()));
+        assert_eq!(paths[0].state.constraints().len(), 3);
+        assert!(!paths[0].is_known_feasible());
+        assert!(paths[1].is_known_feasible());
+        assert_eq!(paths[1].state.constraints(), solved[0]);
+        assert_eq!(
+            paths[1].state.branches(),
+            [SymbolicBranch {
+                pc: relocatable!(0, 4),
+                taken: false,
+                constraint: 1
+            }]
+        );
+        // The runner itself isn't run, nor are its cells changed
+        assert_eq!(cairo_runner.vm.get_pc(), relocatable!(0, 2));
+        assert_eq!(
+            cairo_runner
+                .vm
+                .get_integer(relocatable!(1, 2))
+                .unwrap()
+                .as_ref(),
+            &Felt252::from(7)
+        );
+
+        assert_eq!(
+            cairo_runner
+                .explore_symbolic_paths(end, &mut hint_processor, 1, &mut |_| unreachable!())
+                .unwrap()
+                .len(),
+            1
+        );
+        // Infeasible paths aren't run
+        assert_eq!(
+            cairo_runner
+                .explore_symbolic_paths(end, &mut hint_processor, 10, &mut |_| None)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    /*Program used:
+    func main() {
+        [ap] = 7, ap++;
+        [ap - 1] = 22;
+        jmp not_zero if [ap - 1] != 0;
+        [ap] = 1, ap++;
+        not_zero:
+        ret;
+    }
+    */
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn explore_symbolic_paths_with_unsatisfied_assertion() {
+        let program = program!(
+            data = vec_data!(
+                (0x480680017fff8000_i64),
+                (7),
+                (0x400680017fff7fff_i64),
+                (22),
+                (0x020680017fff7fff_i64),
+                (4),
+                (0x480680017fff8000_i64),
+                (1),
+                (0x208b7fff7fff7ffe_i64)
+            ),
+            main = Some(0),
+        );
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let (mut cairo_runner, end) = initialized_runner(&program);
+        cairo_runner
+            .vm
+            .make_symbolic(relocatable!(1, 2), "x")
+            .unwrap();
+
+        let x = SymbolicValue::Var(0);
+        let x_is_22 = Constraint::Eq(
+            SymbolicExpr::Value(x.clone()),
+            SymbolicExpr::Value(SymbolicValue::Const(Felt252::from(22))),
+        );
+        let mut solved = Vec::new();
+        let paths = cairo_runner
+            .explore_symbolic_paths(end, &mut hint_processor, 10, &mut |state| {
+                solved.push(state.constraints().to_vec());
+                // The flipped branch contradicts the assertion
+                None
+            })
+            .unwrap();
+        assert_eq!(
+            solved,
+            [vec![x_is_22.clone(), Constraint::IsZero(x.clone())]]
+        );
+        assert_eq!(paths.len(), 1);
+        assert!(paths[0].result.is_ok());
+        assert_eq!(
+            paths[0].state.constraints(),
+            [x_is_22, Constraint::NonZero(x)]
+        );
+        assert_eq!(paths[0].state.unsatisfied_assertion(), Some(0));
+        assert!(!paths[0].is_known_feasible());
+
+        // The paths run with values which don't satisfy the assertion are unknown too
+        let paths = cairo_runner
+            .explore_symbolic_paths(end, &mut hint_processor, 10, &mut solve_branches)
+            .unwrap();
+        assert_eq!(paths.len(), 2);
+        assert!(paths.iter().all(|path| !path.is_known_feasible()));
+        assert_eq!(
+            paths[1]
+                .state
+                .flip_branch(0)
+                .unwrap()
+                .unsatisfied_assertion(),
+            Some(0)
+        );
+    }
+
+    /*Program used:
+    func main() {
+        [ap] = 7, ap++;
+        [ap] = 5, ap++;
+        jmp x_not_zero if [fp] != 0;
+        [ap] = 1, ap++;
+        x_not_zero:
+        jmp y_not_zero if [fp + 1] != 0;
+        [ap] = 2, ap++;
+        y_not_zero:
+        ret;
+    }
+    */
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn explore_symbolic_paths_of_multiple_branches() {
+        let program = program!(
+            data = vec_data!(
+                (0x480680017fff8000_i64),
+                (7),
+                (0x480680017fff8000_i64),
+                (5),
+                (0x020780017fff8000_i64),
+                (4),
+                (0x480680017fff8000_i64),
+                (1),
+                (0x020780017fff8001_i64),
+                (4),
+                (0x480680017fff8000_i64),
+                (2),
+                (0x208b7fff7fff7ffe_i64)
+            ),
+            main = Some(0),
+        );
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let end = cairo_runner.initialize(false).unwrap();
+        cairo_runner.run_for_steps(2, &mut hint_processor).unwrap();
+        cairo_runner
+            .vm
+            .make_symbolic(relocatable!(1, 2), "x")
+            .unwrap();
+        cairo_runner
+            .vm
+            .make_symbolic(relocatable!(1, 3), "y")
+            .unwrap();
+
+        let paths = cairo_runner
+            .explore_symbolic_paths(end, &mut hint_processor, 10, &mut solve_branches)
+            .unwrap();
+        assert!(paths.iter().all(|path| path.result.is_ok()));
+        let decisions: Vec<Vec<bool>> = paths
+            .iter()
+            .map(|path| {
+                path.state
+                    .branches()
+                    .iter()
+                    .map(|branch| branch.taken)
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            decisions,
+            [[true, true], [true, false], [false, true], [false, false]]
+        );
+        assert_eq!(
+            paths[3].state.to_smtlib(),
+            "(set-logic QF_NIA)\n\
+            (define-fun P () Int 3618502788666131213697322783095070105623107215331596699973092056135872020481)\n\
+            (declare-const x Int)\n\
+            (assert (and (<= 0 x) (< x P)))\n\
+            (declare-const y Int)\n\
+            (assert (and (<= 0 y) (< y P)))\n\
+            (assert (= (mod x P) 0))\n\
+            (assert (= (mod y P) 0))\n\
+            (check-sat)\n\
+            (get-model)\n"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn to_smtlib() {
+        let mut state = SymbolicState::new();
+        state.add_cell(relocatable!(1, 0), "x");
+        state.add_cell(relocatable!(1, 1), "x");
+        state.add_cell(relocatable!(1, 2), "ids.value");
+        state.add_cell(relocatable!(1, 3), "1st arg");
+        let value = state.define(SymbolicExpr::Sub(
+            SymbolicValue::Var(0),
+            SymbolicValue::Const(Felt252::from(-1)),
+        ));
+        state.constraints.push(Constraint::IsZero(value));
+        assert_eq!(
+            state.to_smtlib(),
+            "(set-logic QF_NIA)\n\
+            (define-fun P () Int 3618502788666131213697322783095070105623107215331596699973092056135872020481)\n\
+            (declare-const x Int)\n\
+            (assert (and (<= 0 x) (< x P)))\n\
+            (declare-const x_1 Int)\n\
+            (assert (and (<= 0 x_1) (< x_1 P)))\n\
+            (declare-const ids.value Int)\n\
+            (assert (and (<= 0 ids.value) (< ids.value P)))\n\
+            (declare-const |1st arg| Int)\n\
+            (assert (and (<= 0 |1st arg|) (< |1st arg| P)))\n\
+            (declare-const _v4 Int)\n\
+            (assert (and (<= 0 _v4) (< _v4 P)))\n\
+            (assert (= (mod _v4 P) (mod (- x 3618502788666131213697322783095070105623107215331596699973092056135872020480) P)))\n\
+            (assert (= (mod _v4 P) 0))\n\
+            (check-sat)\n\
+            (get-model)\n"
+        );
+    }
+}
@@ -23,8 +23,9 @@ use crate::{
         runners::builtin_runner::{
             BuiltinRunner, OutputBuiltinRunner, RangeCheckBuiltinRunner, SignatureBuiltinRunner,
         },
+        symbolic::SymbolicState,
         trace::trace_entry::TraceEntry,
-        vm_memory::memory_segments::MemorySegmentManager,
+        vm_memory::{memory_backend::MemoryBackend, memory_segments::MemorySegmentManager},
     },
 };
 
@@ -42,17 +43,17 @@ const MAX_TRACEBACK_ENTRIES: u32 = 20;
 
 #[derive(PartialEq, Eq, Debug)]
 pub struct Operands {
-    dst: MaybeRelocatable,
-    res: Option<MaybeRelocatable>,
-    op0: MaybeRelocatable,
-    op1: MaybeRelocatable,
+    pub(crate) dst: MaybeRelocatable,
+    pub(crate) res: Option<MaybeRelocatable>,
+    pub(crate) op0: MaybeRelocatable,
+    pub(crate) op1: MaybeRelocatable,
 }
 
 #[derive(PartialEq, Eq, Debug)]
 pub struct OperandsAddresses {
-    dst_addr: Relocatable,
-    op0_addr: Relocatable,
-    op1_addr: Relocatable,
+    pub(crate) dst_addr: Relocatable,
+    pub(crate) op0_addr: Relocatable,
+    pub(crate) op1_addr: Relocatable,
 }
 
 #[derive(Default, Debug, Clone, Copy)]
@@ -69,13 +70,13 @@ impl DeducedOperands {
         self.0 |= (value as u8) << 2;
     }
 
-    fn was_dest_deducted(&self) -> bool {
+    pub(crate) fn was_dest_deducted(&self) -> bool {
         self.0 & 1 != 0
     }
-    fn was_op0_deducted(&self) -> bool {
+    pub(crate) fn was_op0_deducted(&self) -> bool {
         self.0 & 1 << 1 != 0
     }
-    fn was_op1_deducted(&self) -> bool {
+    pub(crate) fn was_op1_deducted(&self) -> bool {
         self.0 & 1 << 2 != 0
     }
 }
@@ -87,12 +88,13 @@ pub struct VirtualMachine {
     pub(crate) trace: Option<Vec<TraceEntry>>,
     pub(crate) current_step: usize,
     pub(crate) rc_limits: Option<(isize, isize)>,
-    skip_instruction_execution: bool,
-    run_finished: bool,
+    pub(crate) skip_instruction_execution: bool,
+    pub(crate) run_finished: bool,
     instruction_cache: Vec<Option<Instruction>>,
-    #[cfg(feature = "test_utils")]
+    #[cfg(feature = "hooks")]
     pub(crate) hooks: crate::vm::hooks::Hooks,
     pub(crate) relocation_table: Option<Vec<usize>>,
+    pub(crate) symbolic: Option<Box<SymbolicState>>,
 }
 
 impl VirtualMachine {
@@ -119,10 +121,43 @@ impl VirtualMachine {
             rc_limits: None,
             run_finished: false,
             instruction_cache: Vec::new(),
-            #[cfg(feature = "test_utils")]
+            #[cfg(feature = "hooks")]
             hooks: Default::default(),
             relocation_table: None,
+            symbolic: None,
+        }
+    }
+
+    /// Returns an independent copy of the vm, see [`CairoRunner::fork`](crate::vm::runners::cairo_runner::CairoRunner::fork)
+    pub(crate) fn fork(&mut self) -> Result<VirtualMachine, MemoryError> {
+        let mut segments = self.segments.fork()?;
+        let builtin_runners: Vec<_> = self
+            .builtin_runners
+            .iter()
+            .map(BuiltinRunner::fork)
+            .collect();
+        for builtin in builtin_runners.iter() {
+            builtin.add_validation_rule(&mut segments.memory);
         }
+        Ok(VirtualMachine {
+            run_context: RunContext {
+                pc: self.run_context.pc,
+                ap: self.run_context.ap,
+                fp: self.run_context.fp,
+            },
+            builtin_runners,
+            segments,
+            trace: self.trace.clone(),
+            current_step: self.current_step,
+            rc_limits: self.rc_limits,
+            skip_instruction_execution: self.skip_instruction_execution,
+            run_finished: self.run_finished,
+            instruction_cache: self.instruction_cache.clone(),
+            #[cfg(feature = "hooks")]
+            hooks: self.hooks.clone(),
+            relocation_table: self.relocation_table.clone(),
+            symbolic: self.symbolic.clone(),
+        })
     }
 
     pub fn compute_segments_effective_sizes(&mut self) {
@@ -184,10 +219,16 @@ impl VirtualMachine {
                 },
                 None => return Err(VirtualMachineError::UnconstrainedResJumpRel),
             },
-            PcUpdate::Jnz => match VirtualMachine::is_zero(&operands.dst) {
-                true => (self.run_context.pc + instruction.size())?,
-                false => (self.run_context.pc + &operands.op1)?,
-            },
+            PcUpdate::Jnz => {
+                let is_zero = VirtualMachine::is_zero(&operands.dst);
+                if let Some(symbolic) = self.symbolic.as_deref_mut() {
+                    symbolic.record_jnz(self.run_context.pc, is_zero);
+                }
+                match is_zero {
+                    true => (self.run_context.pc + instruction.size())?,
+                    false => (self.run_context.pc + &operands.op1)?,
+                }
+            }
         };
         self.run_context.pc = new_pc;
         Ok(())
@@ -404,8 +445,23 @@ impl VirtualMachine {
     fn run_instruction(&mut self, instruction: &Instruction) -> Result<(), VirtualMachineError> {
         let (operands, operands_addresses, deduced_operands) =
             self.compute_operands(instruction)?;
+        // Assertions over symbolic values are recorded as constraints, their concrete values may differ,
+        // in which case the path is reported as unknown (see `SymbolicState::unsatisfied_assertion`)
+        let symbolic_assertion = match self.symbolic.as_deref_mut() {
+            Some(symbolic) => symbolic.record_operands(
+                instruction,
+                &operands,
+                &operands_addresses,
+                &deduced_operands,
+                &self.builtin_runners,
+            ),
+            None => false,
+        };
         self.insert_deduced_operands(deduced_operands, &operands, &operands_addresses)?;
-        self.opcode_assertions(instruction, &operands)?;
+        match self.opcode_assertions(instruction, &operands) {
+            Err(VirtualMachineError::DiffAssertValues(_)) if symbolic_assertion => (),
+            result => result?,
+        }
 
         if let Some(ref mut trace) = &mut self.trace {
             trace.push(TraceEntry {
@@ -444,7 +500,7 @@ impl VirtualMachine {
         Ok(())
     }
 
-    fn decode_current_instruction(&self) -> Result<Instruction, VirtualMachineError> {
+    pub(crate) fn decode_current_instruction(&self) -> Result<Instruction, VirtualMachineError> {
         let instruction = self
             .segments
             .memory
@@ -510,7 +566,7 @@ impl VirtualMachine {
             // Run instructions from program segment, using instruction cache
             let pc = self.run_context.pc.offset;
 
-            if self.segments.memory.data[0].len() <= pc {
+            if self.segments.memory.segment_len(0).unwrap_or_default() <= pc {
                 return Err(MemoryError::UnknownMemoryCell(Box::new((0, pc).into())))?;
             }
 
@@ -553,6 +609,35 @@ impl VirtualMachine {
         #[cfg(feature = "extensive_hints")] hint_ranges: &mut HashMap<Relocatable, HintRange>,
         constants: &HashMap<String, Felt252>,
     ) -> Result<(), VirtualMachineError> {
+        let result = self.run_step(
+            hint_processor,
+            exec_scopes,
+            hint_datas,
+            #[cfg(feature = "extensive_hints")]
+            hint_ranges,
+            constants,
+        );
+        // Reading a cell can't fail, so the errors of backends storing their cells outside of
+        // memory are reported here, taking precedence over the ones caused by the missing cells
+        self.segments.memory.check_storage()?;
+        result
+    }
+
+    fn run_step(
+        &mut self,
+        hint_processor: &mut dyn HintProcessor,
+        exec_scopes: &mut ExecutionScopes,
+        #[cfg(feature = "extensive_hints")] hint_datas: &mut Vec<Box<dyn Any>>,
+        #[cfg(not(feature = "extensive_hints"))] hint_datas: &[Box<dyn Any>],
+        #[cfg(feature = "extensive_hints")] hint_ranges: &mut HashMap<Relocatable, HintRange>,
+        constants: &HashMap<String, Felt252>,
+    ) -> Result<(), VirtualMachineError> {
+        // Hints are only timed if there is a hint time limit
+        #[cfg(feature = "std")]
+        let hints_start = hint_processor
+            .run_resources()
+            .get_hint_time()
+            .map(|_| std::time::Instant::now());
         self.step_hint(
             hint_processor,
             exec_scopes,
@@ -561,11 +646,18 @@ impl VirtualMachine {
             hint_ranges,
             constants,
         )?;
+        #[cfg(feature = "std")]
+        if let Some(hints_start) = hints_start {
+            hint_processor.consume_hint_time(hints_start.elapsed());
+            if hint_processor.run_resources().hint_time_consumed() {
+                return Err(VirtualMachineError::HintTimeLimit);
+            }
+        }
 
-        #[cfg(feature = "test_utils")]
+        #[cfg(feature = "hooks")]
         self.execute_pre_step_instruction(hint_processor, exec_scopes, hint_datas, constants)?;
         self.step_instruction()?;
-        #[cfg(feature = "test_utils")]
+        #[cfg(feature = "hooks")]
         self.execute_post_step_instruction(hint_processor, exec_scopes, hint_datas, constants)?;
 
         Ok(())
@@ -687,7 +779,7 @@ impl VirtualMachine {
     pub fn verify_auto_deductions(&self) -> Result<(), VirtualMachineError> {
         for builtin in self.builtin_runners.iter() {
             let index: usize = builtin.base();
-            for (offset, value) in self.segments.memory.data[index].iter().enumerate() {
+            for (offset, value) in self.segments.memory.data.cells(index) {
                 if let Some(deduced_memory_cell) = builtin
                     .deduce_memory_cell(
                         Relocatable::from((index as isize, offset)),
@@ -813,6 +905,41 @@ impl VirtualMachine {
         entries
     }
 
+    /// Makes an integer memory cell symbolic, enabling symbolic execution (see [`crate::vm::symbolic`]).
+    /// The cell keeps its value, which is used as the concrete value of the variable `name`.
+    /// Can be called on entrypoint arguments before the run, or on hint outputs by hint processors
+    pub fn make_symbolic(
+        &mut self,
+        address: Relocatable,
+        name: &str,
+    ) -> Result<usize, MemoryError> {
+        self.segments.memory.get_integer(address)?;
+        Ok(self
+            .symbolic
+            .get_or_insert_with(Default::default)
+            .add_cell(address, name))
+    }
+
+    /// Returns the constraints & branches collected by symbolic execution, if enabled
+    pub fn get_symbolic_state(&self) -> Option<&SymbolicState> {
+        self.symbolic.as_deref()
+    }
+
+    // Changes the concrete value of a variable's memory cells
+    pub(crate) fn set_symbolic_value(
+        &mut self,
+        var: usize,
+        value: Felt252,
+    ) -> Result<(), MemoryError> {
+        let Some(symbolic) = self.symbolic.as_deref() else {
+            return Ok(());
+        };
+        for address in symbolic.cells_of(var) {
+            self.segments.memory.replace(address, value.into())?;
+        }
+        Ok(())
+    }
+
     ///Adds a new segment and to the memory and returns its starting location as a Relocatable value.
     pub fn add_memory_segment(&mut self) -> Relocatable {
         self.segments.add()
@@ -1165,7 +1292,7 @@ pub struct VirtualMachineBuilder {
     pub(crate) current_step: usize,
     skip_instruction_execution: bool,
     run_finished: bool,
-    #[cfg(feature = "test_utils")]
+    #[cfg(feature = "hooks")]
     pub(crate) hooks: crate::vm::hooks::Hooks,
 }
 
@@ -1185,7 +1312,7 @@ impl Default for VirtualMachineBuilder {
             skip_instruction_execution: false,
             segments: MemorySegmentManager::new(),
             run_finished: false,
-            #[cfg(feature = "test_utils")]
+            #[cfg(feature = "hooks")]
             hooks: Default::default(),
         }
     }
@@ -1230,7 +1357,7 @@ impl VirtualMachineBuilder {
         self
     }
 
-    #[cfg(feature = "test_utils")]
+    #[cfg(feature = "hooks")]
     pub fn hooks(mut self, hooks: crate::vm::hooks::Hooks) -> VirtualMachineBuilder {
         self.hooks = hooks;
         self
@@ -1247,9 +1374,10 @@ impl VirtualMachineBuilder {
             rc_limits: None,
             run_finished: self.run_finished,
             instruction_cache: Vec::new(),
-            #[cfg(feature = "test_utils")]
+            #[cfg(feature = "hooks")]
             hooks: self.hooks,
             relocation_table: None,
+            symbolic: None,
         }
     }
 }
@@ -2646,7 +2774,7 @@ mod tests {
             vm.segments.add();
         }
 
-        vm.segments.memory.data.push(Vec::new());
+        vm.segments.memory.data.add_segment();
         let dst_addr = Relocatable::from((1, 0));
         let dst_addr_value = MaybeRelocatable::Int(Felt252::from(5));
         let op0_addr = Relocatable::from((1, 1));
@@ -2705,7 +2833,7 @@ mod tests {
         for _ in 0..2 {
             vm.segments.add();
         }
-        vm.segments.memory.data.push(Vec::new());
+        vm.segments.memory.data.add_segment();
         let dst_addr = relocatable!(1, 0);
         let dst_addr_value = mayberelocatable!(6);
         let op0_addr = relocatable!(1, 1);
@@ -3035,9 +3163,9 @@ mod tests {
 
         //Check that the following addresses have been accessed:
         // Addresses have been copied from python execution:
-        let mem = vm.segments.memory.data;
-        assert!(mem[1][0].is_accessed());
-        assert!(mem[1][1].is_accessed());
+        let mem = &vm.segments.memory.data;
+        assert!(mem.get(1, 0).unwrap().is_accessed());
+        assert!(mem.get(1, 1).unwrap().is_accessed());
     }
 
     #[test]
@@ -3134,15 +3262,15 @@ mod tests {
         //Check that the following addresses have been accessed:
         // Addresses have been copied from python execution:
         let mem = &vm.segments.memory.data;
-        assert!(mem[0][1].is_accessed());
-        assert!(mem[0][4].is_accessed());
-        assert!(mem[0][6].is_accessed());
-        assert!(mem[1][0].is_accessed());
-        assert!(mem[1][1].is_accessed());
-        assert!(mem[1][2].is_accessed());
-        assert!(mem[1][3].is_accessed());
-        assert!(mem[1][4].is_accessed());
-        assert!(mem[1][5].is_accessed());
+        assert!(mem.get(0, 1).unwrap().is_accessed());
+        assert!(mem.get(0, 4).unwrap().is_accessed());
+        assert!(mem.get(0, 6).unwrap().is_accessed());
+        assert!(mem.get(1, 0).unwrap().is_accessed());
+        assert!(mem.get(1, 1).unwrap().is_accessed());
+        assert!(mem.get(1, 2).unwrap().is_accessed());
+        assert!(mem.get(1, 3).unwrap().is_accessed());
+        assert!(mem.get(1, 4).unwrap().is_accessed());
+        assert!(mem.get(1, 5).unwrap().is_accessed());
         assert_eq!(
             vm.segments
                 .memory
@@ -4270,11 +4398,11 @@ mod tests {
         //Check that the following addresses have been accessed:
         // Addresses have been copied from python execution:
         let mem = &vm.segments.memory.data;
-        assert!(mem[0][0].is_accessed());
-        assert!(mem[0][1].is_accessed());
-        assert!(mem[0][2].is_accessed());
-        assert!(mem[0][10].is_accessed());
-        assert!(mem[1][1].is_accessed());
+        assert!(mem.get(0, 0).unwrap().is_accessed());
+        assert!(mem.get(0, 1).unwrap().is_accessed());
+        assert!(mem.get(0, 2).unwrap().is_accessed());
+        assert!(mem.get(0, 10).unwrap().is_accessed());
+        assert!(mem.get(1, 1).unwrap().is_accessed());
         assert_eq!(
             vm.segments
                 .memory
@@ -4375,14 +4503,14 @@ mod tests {
                 fp: 1,
             }]));
 
-        #[cfg(feature = "test_utils")]
+        #[cfg(feature = "hooks")]
         fn before_first_step_hook(
             _vm: &mut VirtualMachine,
             _hint_data: &[Box<dyn Any>],
         ) -> Result<(), VirtualMachineError> {
             Err(VirtualMachineError::Unexpected)
         }
-        #[cfg(feature = "test_utils")]
+        #[cfg(feature = "hooks")]
         let virtual_machine_builder = virtual_machine_builder.hooks(crate::vm::hooks::Hooks::new(
             Some(std::sync::Arc::new(before_first_step_hook)),
             None,
@@ -4416,7 +4544,7 @@ mod tests {
                 fp: 1,
             }])
         );
-        #[cfg(feature = "test_utils")]
+        #[cfg(feature = "hooks")]
         {
             let program = crate::types::program::Program::from_bytes(
                 include_bytes!("../../../cairo_programs/sqrt.json"),
@@ -4485,9 +4613,9 @@ mod tests {
 
         //Check that the following addresses have been accessed:
         // Addresses have been copied from python execution:
-        let mem = vm.segments.memory.data;
-        assert!(mem[1][0].is_accessed());
-        assert!(mem[1][1].is_accessed());
+        let mem = &vm.segments.memory.data;
+        assert!(mem.get(1, 0).unwrap().is_accessed());
+        assert!(mem.get(1, 1).unwrap().is_accessed());
     }
 
     #[test]
@@ -4587,15 +4715,15 @@ mod tests {
         //Check that the following addresses have been accessed:
         // Addresses have been copied from python execution:
         let mem = &vm.segments.memory.data;
-        assert!(mem[4][1].is_accessed());
-        assert!(mem[4][4].is_accessed());
-        assert!(mem[4][6].is_accessed());
-        assert!(mem[1][0].is_accessed());
-        assert!(mem[1][1].is_accessed());
-        assert!(mem[1][2].is_accessed());
-        assert!(mem[1][3].is_accessed());
-        assert!(mem[1][4].is_accessed());
-        assert!(mem[1][5].is_accessed());
+        assert!(mem.get(4, 1).unwrap().is_accessed());
+        assert!(mem.get(4, 4).unwrap().is_accessed());
+        assert!(mem.get(4, 6).unwrap().is_accessed());
+        assert!(mem.get(1, 0).unwrap().is_accessed());
+        assert!(mem.get(1, 1).unwrap().is_accessed());
+        assert!(mem.get(1, 2).unwrap().is_accessed());
+        assert!(mem.get(1, 3).unwrap().is_accessed());
+        assert!(mem.get(1, 4).unwrap().is_accessed());
+        assert!(mem.get(1, 5).unwrap().is_accessed());
         assert_eq!(
             vm.segments
                 .memory
@@ -9,6 +9,8 @@ use crate::{
     vm::errors::memory_errors::MemoryError,
 };
 use bitvec::prelude as bv;
+
+use super::memory_backend::{MemoryBackend, MemoryData};
 use core::cmp::Ordering;
 use num_traits::ToPrimitive;
 
@@ -36,17 +38,20 @@ pub struct ValidationRule(
 /// `Felt252` values are stored in big-endian order to keep the flag bits free.
 /// `Relocatable` values are stored as native endian, with the 3rd word storing the segment index
 /// and the 4th word storing the offset.
+///
+/// It is public as the cell type stored by [`MemoryBackend`]s, which only need to fill their
+/// segments with [`MemoryCell::NONE`] and tell empty cells apart
 #[derive(Copy, Clone, Eq, Ord, PartialEq, PartialOrd, Debug)]
 #[repr(align(32))]
-pub(crate) struct MemoryCell([u64; 4]);
+pub struct MemoryCell([u64; 4]);
 
 impl MemoryCell {
-    pub const NONE_MASK: u64 = 1 << 63;
-    pub const ACCESS_MASK: u64 = 1 << 62;
-    pub const RELOCATABLE_MASK: u64 = 1 << 61;
+    pub(crate) const NONE_MASK: u64 = 1 << 63;
+    pub(crate) const ACCESS_MASK: u64 = 1 << 62;
+    pub(crate) const RELOCATABLE_MASK: u64 = 1 << 61;
     pub const NONE: Self = Self([Self::NONE_MASK, 0, 0, 0]);
 
-    pub fn new(value: MaybeRelocatable) -> Self {
+    pub(crate) fn new(value: MaybeRelocatable) -> Self {
         value.into()
     }
 
@@ -58,17 +63,41 @@ impl MemoryCell {
         !self.is_none()
     }
 
-    pub fn mark_accessed(&mut self) {
+    pub(crate) fn mark_accessed(&mut self) {
         self.0[0] |= Self::ACCESS_MASK;
     }
 
-    pub fn is_accessed(&self) -> bool {
+    pub(crate) fn is_accessed(&self) -> bool {
         self.0[0] & Self::ACCESS_MASK == Self::ACCESS_MASK
     }
 
-    pub fn get_value(&self) -> Option<MaybeRelocatable> {
+    pub(crate) fn get_value(&self) -> Option<MaybeRelocatable> {
         self.is_some().then(|| (*self).into())
     }
+
+    /// Size of the cell once serialized with [`MemoryCell::to_le_bytes`]
+    #[cfg(feature = "std")]
+    pub(crate) const SIZE: usize = 32;
+
+    /// Serializes the raw cell, flags included
+    #[cfg(feature = "std")]
+    pub(crate) fn to_le_bytes(self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        for (chunk, word) in bytes.chunks_exact_mut(8).zip(self.0) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Deserializes a cell serialized with [`MemoryCell::to_le_bytes`]
+    #[cfg(feature = "std")]
+    pub(crate) fn from_le_bytes(bytes: &[u8]) -> Self {
+        let mut words = [0; 4];
+        for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(8)) {
+            *word = u64::from_le_bytes(chunk.try_into().unwrap_or_default());
+        }
+        Self(words)
+    }
 }
 
 impl From<MaybeRelocatable> for MemoryCell {
@@ -106,6 +135,7 @@ impl From<MemoryCell> for MaybeRelocatable {
     }
 }
 
+#[derive(Clone)]
 pub struct AddressSet(Vec<bv::BitVec>);
 
 impl AddressSet {
@@ -157,26 +187,96 @@ impl AddressSet {
 }
 
 pub struct Memory {
-    pub(crate) data: Vec<Vec<MemoryCell>>,
-    pub(crate) temp_data: Vec<Vec<MemoryCell>>,
+    pub(crate) data: MemoryData,
+    pub(crate) temp_data: MemoryData,
     // relocation_rules's keys map to temp_data's indices and therefore begin at
     // zero; that is, segment_index = -1 maps to key 0, -2 to key 1...
     pub(crate) relocation_rules: HashMap<usize, Relocatable>,
     pub validated_addresses: AddressSet,
     validation_rules: Vec<Option<ValidationRule>>,
+    // Sum of the lengths of the real & temporary segments
+    pub(crate) allocated_cells: usize,
+    // Cap on allocated_cells, checked before growing a segment
+    pub(crate) memory_cells_limit: Option<usize>,
+}
+
+// Adds the cells about to be allocated to `allocated_cells`, failing if they exceed `limit`
+fn allocate_cells(
+    allocated_cells: &mut usize,
+    limit: Option<usize>,
+    cells: usize,
+) -> Result<(), MemoryError> {
+    let new_allocated_cells = allocated_cells.saturating_add(cells);
+    match limit {
+        Some(limit) if new_allocated_cells > limit => Err(MemoryError::MemoryCellsLimit(limit)),
+        _ => {
+            *allocated_cells = new_allocated_cells;
+            Ok(())
+        }
+    }
 }
 
 impl Memory {
     pub fn new() -> Memory {
         Memory {
-            data: Vec::new(),
-            temp_data: Vec::new(),
+            data: MemoryData::default(),
+            temp_data: MemoryData::default(),
             relocation_rules: HashMap::new(),
             validated_addresses: AddressSet::new(),
             validation_rules: Vec::with_capacity(7),
+            allocated_cells: 0,
+            memory_cells_limit: None,
         }
     }
 
+    /// Creates an empty memory which stores the real & temporary segments in the given backends
+    /// instead of the default [`VecMemoryBackend`](super::memory_backend::VecMemoryBackend)s.
+    /// The backends should be empty
+    pub fn with_backends(
+        data: Box<dyn MemoryBackend>,
+        temp_data: Box<dyn MemoryBackend>,
+    ) -> Memory {
+        let mut memory = Memory {
+            data: data.into(),
+            temp_data: temp_data.into(),
+            ..Memory::new()
+        };
+        memory.allocated_cells = memory.count_allocated_cells();
+        memory
+    }
+
+    // Sums the lengths of the real & temporary segments
+    pub(crate) fn count_allocated_cells(&self) -> usize {
+        self.data
+            .segment_lens()
+            .into_iter()
+            .chain(self.temp_data.segment_lens())
+            .fold(0, usize::saturating_add)
+    }
+
+    /// Returns a copy of the memory, with the default backends turned into copy-on-write ones
+    /// so that both copies share their unmodified cells. The backends of this memory stay copy-on-write
+    /// until `unshare` is called.
+    /// Validation rules aren't copied, they have to be added again to the returned memory
+    pub(crate) fn fork(&mut self) -> Result<Memory, MemoryError> {
+        Ok(Memory {
+            data: self.data.cow_fork()?,
+            temp_data: self.temp_data.cow_fork()?,
+            relocation_rules: self.relocation_rules.clone(),
+            validated_addresses: self.validated_addresses.clone(),
+            validation_rules: Vec::with_capacity(self.validation_rules.len()),
+            allocated_cells: self.allocated_cells,
+            memory_cells_limit: self.memory_cells_limit,
+        })
+    }
+
+    /// Turns the copy-on-write backends back into the default ones once no copy shares their cells.
+    /// Returns whether both backends could be turned back
+    pub(crate) fn unshare(&mut self) -> bool {
+        // Both backends are turned back independently
+        self.data.unshare() & self.temp_data.unshare()
+    }
+
     /// Inserts a value into a memory address
     /// Will return an Error if the segment index given by the address corresponds to a non-allocated segment,
     /// or if the inserted value is inconsistent with the current value at the memory cell
@@ -194,27 +294,63 @@ impl Memory {
             &mut self.data
         };
 
-        let data_len = data.len();
-        let segment = data
-            .get_mut(value_index)
-            .ok_or_else(|| MemoryError::UnallocatedSegment(Box::new((value_index, data_len))))?;
-
-        //Check if the element is inserted next to the last one on the segment
-        //Forgoing this check would allow data to be inserted in a different index
-        let (len, capacity) = (segment.len(), segment.capacity());
-        if len <= value_offset {
-            let new_len = value_offset
-                .checked_add(1)
-                .ok_or(MemoryError::VecCapacityExceeded)?;
-            segment
-                .try_reserve(new_len.saturating_sub(capacity))
-                .map_err(|_| MemoryError::VecCapacityExceeded)?;
-            segment.resize(new_len, MemoryCell::NONE);
-        }
-        // At this point there's *something* in there
+        let cell = match data {
+            // Fast path for the default backend, which looks the segment up only once
+            MemoryData::Vec(backend) => {
+                let data_len = backend.segments.len();
+                let segment = backend.segments.get_mut(value_index).ok_or_else(|| {
+                    MemoryError::UnallocatedSegment(Box::new((value_index, data_len)))
+                })?;
+
+                //Check if the element is inserted next to the last one on the segment
+                //Forgoing this check would allow data to be inserted in a different index
+                let (len, capacity) = (segment.len(), segment.capacity());
+                if len <= value_offset {
+                    let new_len = value_offset
+                        .checked_add(1)
+                        .ok_or(MemoryError::VecCapacityExceeded)?;
+                    allocate_cells(
+                        &mut self.allocated_cells,
+                        self.memory_cells_limit,
+                        new_len - len,
+                    )?;
+                    segment
+                        .try_reserve(new_len.saturating_sub(capacity))
+                        .map_err(|_| MemoryError::VecCapacityExceeded)?;
+                    segment.resize(new_len, MemoryCell::NONE);
+                }
+                // At this point there's *something* in there
+                &mut segment[value_offset]
+            }
+            data => {
+                // Reads may have lost cells since the last write
+                data.check_storage()?;
+                let len = data.segment_len(value_index).ok_or_else(|| {
+                    MemoryError::UnallocatedSegment(Box::new((value_index, data.num_segments())))
+                })?;
+                if len <= value_offset {
+                    let new_len = value_offset
+                        .checked_add(1)
+                        .ok_or(MemoryError::VecCapacityExceeded)?;
+                    allocate_cells(
+                        &mut self.allocated_cells,
+                        self.memory_cells_limit,
+                        new_len - len,
+                    )?;
+                    data.grow_segment(value_index, new_len)?;
+                }
+                match data.get_mut(value_index, value_offset) {
+                    Some(cell) => cell,
+                    None => {
+                        data.check_storage()?;
+                        return Err(MemoryError::VecCapacityExceeded);
+                    }
+                }
+            }
+        };
 
-        match segment[value_offset].get_value() {
-            None => segment[value_offset] = MemoryCell::new(val),
+        match cell.get_value() {
+            None => *cell = MemoryCell::new(val),
             Some(current_cell) => {
                 if current_cell != val {
                     //Existing memory cannot be changed
@@ -229,6 +365,31 @@ impl Memory {
         self.validate_memory_cell(key)
     }
 
+    /// Replaces the value of a memory cell which already holds one, bypassing the write-once rule.
+    /// Values computed from the previous one aren't updated
+    pub(crate) fn replace(
+        &mut self,
+        key: Relocatable,
+        val: MaybeRelocatable,
+    ) -> Result<(), MemoryError> {
+        let (value_index, value_offset) = from_relocatable_to_indexes(key);
+        let data = if key.segment_index.is_negative() {
+            &mut self.temp_data
+        } else {
+            &mut self.data
+        };
+        let cell = data
+            .get_mut(value_index, value_offset)
+            .filter(|cell| cell.is_some())
+            .ok_or_else(|| MemoryError::UnknownMemoryCell(Box::new(key)))?;
+        let accessed = cell.is_accessed();
+        *cell = MemoryCell::new(val);
+        if accessed {
+            cell.mark_accessed();
+        }
+        Ok(())
+    }
+
     /// Retrieve a value from memory (either normal or temporary) and apply relocation rules
     pub(crate) fn get<'a, 'b: 'a, K: 'a>(&'b self, key: &'a K) -> Option<Cow<MaybeRelocatable>>
     where
@@ -242,7 +403,7 @@ impl Memory {
             &self.data
         };
         let (i, j) = from_relocatable_to_indexes(relocatable);
-        let value = data.get(i)?.get(j)?.get_value()?;
+        let value = data.get(i, j)?.get_value()?;
         Some(Cow::Owned(self.relocate_value(&value).ok()?.into_owned()))
     }
 
@@ -267,35 +428,45 @@ impl Memory {
             return Ok(());
         }
         // Relocate temporary addresses in memory
-        for segment in self.data.iter_mut().chain(self.temp_data.iter_mut()) {
-            for cell in segment.iter_mut() {
-                let value = cell.get_value();
-                match value {
-                    Some(MaybeRelocatable::RelocatableValue(addr)) if addr.segment_index < 0 => {
-                        let mut new_cell = MemoryCell::new(Memory::relocate_address(
-                            addr,
-                            &self.relocation_rules,
-                        )?);
-                        if cell.is_accessed() {
-                            new_cell.mark_accessed();
+        for data in [&mut self.data, &mut self.temp_data] {
+            // Collect the relocated cells first, as backends may share or page out their segments
+            let mut relocated_cells = Vec::new();
+            for segment in 0..data.num_segments() {
+                for (offset, cell) in data.cells(segment) {
+                    match cell.get_value() {
+                        Some(MaybeRelocatable::RelocatableValue(addr))
+                            if addr.segment_index < 0 =>
+                        {
+                            let mut new_cell = MemoryCell::new(Memory::relocate_address(
+                                addr,
+                                &self.relocation_rules,
+                            )?);
+                            if cell.is_accessed() {
+                                new_cell.mark_accessed();
+                            }
+                            relocated_cells.push((segment, offset, new_cell));
                         }
-                        *cell = new_cell;
+                        _ => {}
                     }
-                    _ => {}
+                }
+            }
+            for (segment, offset, new_cell) in relocated_cells {
+                if let Some(cell) = data.get_mut(segment, offset) {
+                    *cell = new_cell;
                 }
             }
         }
         // Move relocated temporary memory into the real memory
-        for index in (0..self.temp_data.len()).rev() {
+        for index in (0..self.temp_data.num_segments()).rev() {
             if let Some(base_addr) = self.relocation_rules.get(&index) {
-                let data_segment = self.temp_data.remove(index);
+                let base_addr = *base_addr;
+                self.allocated_cells = self
+                    .allocated_cells
+                    .saturating_sub(self.temp_data.segment_len(index).unwrap_or_default());
                 // Insert the to-be relocated segment into the real memory
-                let mut addr = *base_addr;
-                if let Some(s) = self.data.get_mut(addr.segment_index as usize) {
-                    s.reserve_exact(data_segment.len())
-                }
-                for cell in data_segment {
+                for (offset, cell) in self.temp_data.remove_segment(index) {
                     if let Some(v) = cell.get_value() {
+                        let addr = (base_addr + offset)?;
                         // Rely on Memory::insert to catch memory inconsistencies
                         self.insert(addr, v)?;
                         // If the cell is accessed, mark the relocated one as accessed too
@@ -303,12 +474,12 @@ impl Memory {
                             self.mark_as_accessed(addr)
                         }
                     }
-                    addr = (addr + 1)?;
                 }
             }
         }
+        self.allocated_cells = self.count_allocated_cells();
         self.relocation_rules.clear();
-        Ok(())
+        self.check_storage()
     }
 
     /// Add a new relocation rule.
@@ -411,19 +582,35 @@ impl Memory {
 
     ///Applies validation_rules to the current memory
     pub fn validate_existing_memory(&mut self) -> Result<(), MemoryError> {
+        // The validated addresses are set aside while the cells are iterated, as the rules don't read them
+        let mut validated_addresses =
+            core::mem::replace(&mut self.validated_addresses, AddressSet::new());
+        let result = self.validate_cells(&mut validated_addresses);
+        self.validated_addresses = validated_addresses;
+        result
+    }
+
+    fn validate_cells(&self, validated_addresses: &mut AddressSet) -> Result<(), MemoryError> {
         for (index, rule) in self.validation_rules.iter().enumerate() {
-            if index >= self.data.len() {
-                continue;
-            }
             let Some(rule) = rule else {
                 continue;
             };
-            for offset in 0..self.data[index].len() {
+            let mut validate = |offset: usize| -> Result<(), MemoryError> {
                 let addr = Relocatable::from((index as isize, offset));
-                if !self.validated_addresses.contains(&addr) {
-                    self.validated_addresses
-                        .extend(rule.0(self, addr)?.as_slice());
+                if !validated_addresses.contains(&addr) {
+                    validated_addresses.extend(rule.0(self, addr)?.as_slice());
+                }
+                Ok(())
+            };
+            match &self.data {
+                // Fast path for the default backend, whose cells are all stored
+                MemoryData::Vec(backend) => {
+                    let len = backend.segments.get(index).map_or(0, Vec::len);
+                    (0..len).try_for_each(validate)?
                 }
+                data => data
+                    .cells(index)
+                    .try_for_each(|(offset, _)| validate(offset))?,
             }
         }
         Ok(())
@@ -443,16 +630,9 @@ impl Memory {
         rhs: Relocatable,
         len: usize,
     ) -> (Ordering, usize) {
-        let get_segment = |idx: isize| {
-            if idx.is_negative() {
-                self.temp_data.get(-(idx + 1) as usize)
-            } else {
-                self.data.get(idx as usize)
-            }
-        };
         match (
-            get_segment(lhs.segment_index),
-            get_segment(rhs.segment_index),
+            self.segment_len(lhs.segment_index),
+            self.segment_len(rhs.segment_index),
         ) {
             (None, None) => {
                 return (Ordering::Equal, 0);
@@ -463,22 +643,24 @@ impl Memory {
             (None, Some(_)) => {
                 return (Ordering::Less, 0);
             }
-            (Some(lhs_segment), Some(rhs_segment)) => {
-                let (lhs_start, rhs_start) = (lhs.offset, rhs.offset);
-                for i in 0..len {
-                    let (lhs, rhs) = (
-                        lhs_segment.get(lhs_start + i),
-                        rhs_segment.get(rhs_start + i),
-                    );
-                    let ord = lhs.cmp(&rhs);
-                    if ord == Ordering::Equal {
-                        continue;
-                    }
-                    return (ord, i);
-                }
-            }
+            (Some(_), Some(_)) => {}
         };
-        (Ordering::Equal, len)
+        match (
+            self.vec_segment(lhs.segment_index),
+            self.vec_segment(rhs.segment_index),
+        ) {
+            // Fast path for the default backend, which indexes the segments' cells directly
+            (Some(lhs_segment), Some(rhs_segment)) => cmp_cells(
+                len,
+                |i| lhs_segment.get(lhs.offset + i),
+                |i| rhs_segment.get(rhs.offset + i),
+            ),
+            _ => cmp_cells(
+                len,
+                |i| self.get_cell(lhs.segment_index, lhs.offset + i),
+                |i| self.get_cell(rhs.segment_index, rhs.offset + i),
+            ),
+        }
     }
 
     /// Compares two ranges of values in memory of length `len`
@@ -493,23 +675,30 @@ impl Memory {
         if lhs == rhs {
             return true;
         }
-        let get_segment = |idx: isize| {
-            if idx.is_negative() {
-                self.temp_data.get(-(idx + 1) as usize)
-            } else {
-                self.data.get(idx as usize)
-            }
+        // Length of the segment after the given address, if the address is within its bounds
+        let remaining_len = |addr: Relocatable| {
+            self.segment_len(addr.segment_index)
+                .and_then(|segment_len| segment_len.checked_sub(addr.offset))
         };
-        match (
-            get_segment(lhs.segment_index).and_then(|s| s.get(lhs.offset..)),
-            get_segment(rhs.segment_index).and_then(|s| s.get(rhs.offset..)),
-        ) {
-            (Some(lhs), Some(rhs)) => {
-                let (lhs_len, rhs_len) = (lhs.len().min(len), rhs.len().min(len));
+        match (remaining_len(lhs), remaining_len(rhs)) {
+            (Some(lhs_len), Some(rhs_len)) => {
+                let (lhs_len, rhs_len) = (lhs_len.min(len), rhs_len.min(len));
                 if lhs_len != rhs_len {
                     return false;
                 }
-                lhs[..lhs_len] == rhs[..rhs_len]
+                match (
+                    self.vec_segment(lhs.segment_index),
+                    self.vec_segment(rhs.segment_index),
+                ) {
+                    // Fast path for the default backend, which compares the segments' slices
+                    (Some(lhs_segment), Some(rhs_segment)) => {
+                        lhs_segment[lhs.offset..][..lhs_len] == rhs_segment[rhs.offset..][..rhs_len]
+                    }
+                    _ => (0..lhs_len).all(|i| {
+                        self.get_cell(lhs.segment_index, lhs.offset + i)
+                            == self.get_cell(rhs.segment_index, rhs.offset + i)
+                    }),
+                }
             }
             (None, None) => true,
             _ => false,
@@ -571,8 +760,7 @@ impl Memory {
         } else {
             &mut self.data
         };
-        let cell = data.get_mut(i).and_then(|x| x.get_mut(j));
-        if let Some(cell) = cell {
+        if let Some(cell) = data.get_mut(i, j) {
             cell.mark_accessed()
         }
     }
@@ -581,15 +769,69 @@ impl Memory {
         &self,
         segment_index: usize,
     ) -> Option<usize> {
-        let segment = self.data.get(segment_index)?;
+        self.data.segment_len(segment_index)?;
         Some(
-            segment
-                .iter()
-                .filter(|x| x.is_some() && x.is_accessed())
+            self.data
+                .cells(segment_index)
+                .filter(|(_, x)| x.is_some() && x.is_accessed())
                 .count(),
         )
     }
 
+    /// Returns the number of cells allocated in the real & temporary segments, memory holes included
+    pub fn allocated_cells(&self) -> usize {
+        self.allocated_cells
+    }
+
+    /// Caps the number of cells allocated in the real & temporary segments. Writes which would
+    /// allocate more cells fail with [`MemoryError::MemoryCellsLimit`] before growing their segment
+    pub fn set_memory_cells_limit(&mut self, memory_cells_limit: Option<usize>) {
+        self.memory_cells_limit = memory_cells_limit;
+    }
+
+    /// Fails if the backend of the real or temporary segments couldn't access its storage, in which
+    /// case the cells read since then may be missing
+    pub(crate) fn check_storage(&self) -> Result<(), MemoryError> {
+        self.data.check_storage()?;
+        self.temp_data.check_storage()
+    }
+
+    /// Returns the length of a real or temporary segment, or None if it doesn't exist
+    pub(crate) fn segment_len(&self, segment_index: isize) -> Option<usize> {
+        if segment_index.is_negative() {
+            self.temp_data.segment_len(-(segment_index + 1) as usize)
+        } else {
+            self.data.segment_len(segment_index as usize)
+        }
+    }
+
+    /// Returns the cell at the given position of a real or temporary segment, without applying
+    /// relocation rules. Offsets within the segment's bounds that the backend doesn't store are
+    /// returned as empty cells
+    fn get_cell(&self, segment_index: isize, offset: usize) -> Option<MemoryCell> {
+        let (data, index) = if segment_index.is_negative() {
+            (&self.temp_data, -(segment_index + 1) as usize)
+        } else {
+            (&self.data, segment_index as usize)
+        };
+        (offset < data.segment_len(index)?)
+            .then(|| data.get(index, offset).copied().unwrap_or(MemoryCell::NONE))
+    }
+
+    /// Returns the cells of a real or temporary segment if it is held by the default backend, which
+    /// stores them contiguously. Returns None for the other backends or if the segment doesn't exist
+    fn vec_segment(&self, segment_index: isize) -> Option<&[MemoryCell]> {
+        let (data, index) = if segment_index.is_negative() {
+            (&self.temp_data, -(segment_index + 1) as usize)
+        } else {
+            (&self.data, segment_index as usize)
+        };
+        match data {
+            MemoryData::Vec(backend) => backend.segments.get(index).map(Vec::as_slice),
+            _ => None,
+        }
+    }
+
     // Inserts a value into memory & inmediately marks it as accessed if insertion was succesful
     // Used by ModBuiltinRunner, as it accesses memory outside of it's segment when operating
     pub(crate) fn insert_as_accessed<V>(
@@ -606,14 +848,28 @@ impl Memory {
     }
 }
 
+// Compares the cells returned by `lhs_cell` & `rhs_cell` for the relative positions `0..len`,
+// returning the ordering and the first position at which they differ, see `Memory::memcmp`
+fn cmp_cells<T: Ord>(
+    len: usize,
+    lhs_cell: impl Fn(usize) -> T,
+    rhs_cell: impl Fn(usize) -> T,
+) -> (Ordering, usize) {
+    for i in 0..len {
+        let ord = lhs_cell(i).cmp(&rhs_cell(i));
+        if ord != Ordering::Equal {
+            return (ord, i);
+        }
+    }
+    (Ordering::Equal, len)
+}
+
 impl From<&Memory> for CairoPieMemory {
     fn from(mem: &Memory) -> CairoPieMemory {
         let mut pie_memory = Vec::default();
-        for (i, segment) in mem.data.iter().enumerate() {
-            for (j, cell) in segment.iter().enumerate() {
-                if let Some(value) = cell.get_value() {
-                    pie_memory.push(((i, j), value))
-                }
+        for (i, j, cell) in mem.data.iter_values() {
+            if let Some(value) = cell.get_value() {
+                pie_memory.push(((i, j), value))
             }
         }
         CairoPieMemory(pie_memory)
@@ -622,19 +878,15 @@ impl From<&Memory> for CairoPieMemory {
 
 impl fmt::Display for Memory {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for (i, segment) in self.temp_data.iter().enumerate() {
-            for (j, cell) in segment.iter().enumerate() {
-                if let Some(elem) = cell.get_value() {
-                    let temp_segment = i + 1;
-                    writeln!(f, "(-{temp_segment},{j}) : {elem}")?;
-                }
+        for (i, j, cell) in self.temp_data.iter_values() {
+            if let Some(elem) = cell.get_value() {
+                let temp_segment = i + 1;
+                writeln!(f, "(-{temp_segment},{j}) : {elem}")?;
             }
         }
-        for (i, segment) in self.data.iter().enumerate() {
-            for (j, cell) in segment.iter().enumerate() {
-                if let Some(elem) = cell.get_value() {
-                    writeln!(f, "({i},{j}) : {elem}")?;
-                }
+        for (i, j, cell) in self.data.iter_values() {
+            if let Some(elem) = cell.get_value() {
+                writeln!(f, "({i},{j}) : {elem}")?;
             }
         }
         Ok(())
@@ -718,7 +970,7 @@ mod memory_tests {
         let key = Relocatable::from((0, 0));
         let val = MaybeRelocatable::from(Felt252::from(5_u64));
         let mut memory = Memory::new();
-        memory.data.push(Vec::new());
+        memory.data.add_segment();
         memory.insert(key, &val).unwrap();
         assert_eq!(
             memory.get(&key).unwrap().as_ref(),
@@ -734,7 +986,8 @@ mod memory_tests {
             MemoryCell::NONE,
             MemoryCell::NONE,
             MemoryCell::new(mayberelocatable!(8)),
-        ]];
+        ]]
+        .into();
         assert_eq!(
             memory.get(&mayberelocatable!(-1, 2)).unwrap().as_ref(),
             &mayberelocatable!(8),
@@ -747,10 +1000,10 @@ mod memory_tests {
         let key = Relocatable::from((-1, 3));
         let val = MaybeRelocatable::from(Felt252::from(8_u64));
         let mut memory = Memory::new();
-        memory.temp_data.push(Vec::new());
+        memory.temp_data.add_segment();
         memory.insert(key, &val).unwrap();
         assert_eq!(
-            memory.temp_data[0][3],
+            *memory.temp_data.get(0, 3).unwrap(),
             MemoryCell::new(MaybeRelocatable::from(Felt252::from(8_u64)))
         );
     }
@@ -761,7 +1014,7 @@ mod memory_tests {
         let key = Relocatable::from((-1, 0));
         let val = MaybeRelocatable::from(Felt252::from(5_u64));
         let mut memory = Memory::new();
-        memory.temp_data.push(Vec::new());
+        memory.temp_data.add_segment();
         memory.insert(key, &val).unwrap();
         assert_eq!(
             memory.get(&key).unwrap().as_ref(),
@@ -777,7 +1030,8 @@ mod memory_tests {
         memory.temp_data = vec![vec![
             MemoryCell::NONE,
             MemoryCell::new(mayberelocatable!(8)),
-        ]];
+        ]]
+        .into();
         assert_eq!(
             memory.insert(key, &mayberelocatable!(5)),
             Err(MemoryError::InconsistentMemory(Box::new((
@@ -824,7 +1078,7 @@ mod memory_tests {
         let val_a = MaybeRelocatable::from(Felt252::from(5_u64));
         let val_b = MaybeRelocatable::from(Felt252::from(6_u64));
         let mut memory = Memory::new();
-        memory.data.push(Vec::new());
+        memory.data.add_segment();
         memory
             .insert(key, &val_a)
             .expect("Unexpected memory insert fail");
@@ -844,7 +1098,7 @@ mod memory_tests {
         let key_b = Relocatable::from((0, 2));
         let val = MaybeRelocatable::from(Felt252::from(5_u64));
         let mut memory = Memory::new();
-        memory.data.push(Vec::new());
+        memory.data.add_segment();
         memory.insert(key_a, &val).unwrap();
         memory.insert(key_b, &val).unwrap();
         assert_eq!(memory.get(&key_b).unwrap().as_ref(), &val);
@@ -857,7 +1111,7 @@ mod memory_tests {
         let key_b = Relocatable::from((0, 5));
         let val = MaybeRelocatable::from(Felt252::from(5_u64));
         let mut memory = Memory::new();
-        memory.data.push(Vec::new());
+        memory.data.add_segment();
         memory.insert(key_a, &val).unwrap();
         memory.insert(key_b, &val).unwrap();
         assert_eq!(memory.get(&key_b).unwrap().as_ref(), &val);
@@ -1053,14 +1307,14 @@ mod memory_tests {
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     fn default_memory() {
         let mem: Memory = Default::default();
-        assert_eq!(mem.data.len(), 0);
+        assert_eq!(mem.data.num_segments(), 0);
     }
 
     #[test]
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     fn insert_and_get_temporary_succesful() {
         let mut memory = Memory::new();
-        memory.temp_data.push(Vec::new());
+        memory.temp_data.add_segment();
 
         let key = Relocatable::from((-1, 0));
         let val = MaybeRelocatable::from(Felt252::from(5));
@@ -1291,7 +1545,7 @@ mod memory_tests {
         memory
             .add_relocation_rule((-1, 0).into(), (2, 1).into())
             .unwrap();
-        memory.data.push(vec![]);
+        memory.data.add_segment();
 
         assert_eq!(memory.relocate_memory(), Ok(()));
         check_memory!(
@@ -1326,7 +1580,8 @@ mod memory_tests {
         memory
             .add_relocation_rule((-1, 0).into(), (2, 0).into())
             .unwrap();
-        memory.data.push(vec![]);
+        memory.data.add_segment();
+        assert_eq!(memory.allocated_cells(), 9);
 
         assert_eq!(memory.relocate_memory(), Ok(()));
 
@@ -1343,6 +1598,32 @@ mod memory_tests {
             ((2, 2), 9)
         );
         assert!(memory.temp_data.is_empty());
+        // The relocated cells are only counted once
+        assert_eq!(memory.allocated_cells(), 9);
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn insert_over_memory_cells_limit() {
+        let mut memory = memory![((0, 0), 1)];
+        memory.set_memory_cells_limit(Some(3));
+        assert_matches!(
+            memory.insert(Relocatable::from((0, 1 << 40)), Felt252::from(2)),
+            Err(MemoryError::MemoryCellsLimit(3))
+        );
+        // The segment isn't grown
+        assert_eq!(memory.segment_len(0), Some(1));
+        assert_eq!(memory.allocated_cells(), 1);
+
+        assert_eq!(
+            memory.insert(Relocatable::from((0, 2)), Felt252::from(2)),
+            Ok(())
+        );
+        assert_eq!(memory.allocated_cells(), 3);
+        assert_matches!(
+            memory.insert(Relocatable::from((0, 3)), Felt252::from(2)),
+            Err(MemoryError::MemoryCellsLimit(3))
+        );
     }
 
     #[test]
@@ -1451,7 +1732,7 @@ mod memory_tests {
         memory
             .add_relocation_rule((-1, 0).into(), (2, 0).into())
             .unwrap();
-        memory.data.push(vec![]);
+        memory.data.add_segment();
 
         assert_eq!(memory.relocate_memory(), Ok(()));
         check_memory!(
@@ -1486,11 +1767,11 @@ mod memory_tests {
             ((-2, 0), 10),
             ((-2, 1), 11)
         ];
-        memory.data.push(vec![]);
+        memory.data.add_segment();
         memory
             .add_relocation_rule((-1, 0).into(), (2, 0).into())
             .unwrap();
-        memory.data.push(vec![]);
+        memory.data.add_segment();
         memory
             .add_relocation_rule((-2, 0).into(), (3, 0).into())
             .unwrap();
@@ -1620,9 +1901,9 @@ mod memory_tests {
     #[test]
     fn mark_address_as_accessed() {
         let mut memory = memory![((0, 0), 0)];
-        assert!(!memory.data[0][0].is_accessed());
+        assert!(!memory.data.get(0, 0).unwrap().is_accessed());
         memory.mark_as_accessed(relocatable!(0, 0));
-        assert!(memory.data[0][0].is_accessed());
+        assert!(memory.data.get(0, 0).unwrap().is_accessed());
     }
 
     #[test]
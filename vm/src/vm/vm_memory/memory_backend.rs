@@ -0,0 +1,1148 @@
+use crate::stdlib::{collections::BTreeMap, prelude::*, rc::Rc};
+#[cfg(feature = "std")]
+use core::cell::{Cell, OnceCell};
+
+use crate::vm::{errors::memory_errors::MemoryError, vm_memory::memory::MemoryCell};
+
+/// Storage for the cells of a set of memory segments.
+///
+/// [`Memory`](crate::vm::vm_memory::memory::Memory) uses one backend for the real segments and
+/// another one for the temporary segments, both of them indexed from zero (temporary segment -1
+/// is stored at index 0 of its backend, -2 at index 1 and so on). All the memory rules, such as
+/// write-once cells, validation and relocation, are applied by `Memory` on top of the backend, so
+/// implementors only need to store cells.
+///
+/// Every offset below the length of a segment is considered allocated, even if the backend doesn't
+/// store anything for it, in which case it behaves as a [`MemoryCell::NONE`].
+pub trait MemoryBackend {
+    /// Returns the number of segments
+    fn num_segments(&self) -> usize;
+
+    /// Appends a new empty segment
+    fn add_segment(&mut self);
+
+    /// Removes a segment, shifting the index of the following ones, and returns its non-empty
+    /// cells along with their offsets, in ascending order
+    fn remove_segment(&mut self, segment: usize) -> Vec<(usize, MemoryCell)>;
+
+    /// Returns the length of a segment, or None if it doesn't exist
+    fn segment_len(&self, segment: usize) -> Option<usize>;
+
+    /// Grows a segment to `len` cells, filling the new offsets with empty cells.
+    /// Does nothing if the segment is already as big.
+    fn grow_segment(&mut self, segment: usize, len: usize) -> Result<(), MemoryError>;
+
+    /// Returns the cell at the given offset, or None if it is out of the segment's bounds or it
+    /// isn't stored
+    fn get(&self, segment: usize, offset: usize) -> Option<&MemoryCell>;
+
+    /// Returns a mutable reference to the cell at the given offset.
+    /// Must return Some for every offset below the segment's length
+    fn get_mut(&mut self, segment: usize, offset: usize) -> Option<&mut MemoryCell>;
+
+    /// Iterates over the stored cells of a segment, along with their offsets, in ascending order.
+    /// The iterator is empty if the segment doesn't exist
+    fn cells(&self, segment: usize) -> Box<dyn Iterator<Item = (usize, &MemoryCell)> + '_>;
+
+    /// Returns an independent copy of the backend, used to fork runners.
+    /// Returns None if the backend can't be copied, which is the default
+    fn fork(&self) -> Option<Box<dyn MemoryBackend>> {
+        None
+    }
+
+    /// Returns an error if the backend failed to read or write the storage holding its cells, in
+    /// which case the cells it returned since then may be missing.
+    /// `Memory` checks it on every write and the VM after every step, as reading a cell can't fail.
+    /// Backends which can't fail, such as the in-memory ones, keep the default, which returns Ok
+    fn check_storage(&self) -> Result<(), MemoryError> {
+        Ok(())
+    }
+}
+
+/// The default backend, which stores each segment as a contiguous vector of cells
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VecMemoryBackend {
+    pub(crate) segments: Vec<Vec<MemoryCell>>,
+}
+
+impl VecMemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl MemoryBackend for VecMemoryBackend {
+    #[inline]
+    fn num_segments(&self) -> usize {
+        self.segments.len()
+    }
+
+    #[inline]
+    fn add_segment(&mut self) {
+        self.segments.push(Vec::new())
+    }
+
+    fn remove_segment(&mut self, segment: usize) -> Vec<(usize, MemoryCell)> {
+        self.segments
+            .remove(segment)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.is_some())
+            .collect()
+    }
+
+    #[inline]
+    fn segment_len(&self, segment: usize) -> Option<usize> {
+        self.segments.get(segment).map(Vec::len)
+    }
+
+    #[inline]
+    fn grow_segment(&mut self, segment: usize, len: usize) -> Result<(), MemoryError> {
+        let Some(segment) = self.segments.get_mut(segment) else {
+            return Ok(());
+        };
+        if segment.len() < len {
+            segment
+                .try_reserve(len.saturating_sub(segment.capacity()))
+                .map_err(|_| MemoryError::VecCapacityExceeded)?;
+            segment.resize(len, MemoryCell::NONE);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn get(&self, segment: usize, offset: usize) -> Option<&MemoryCell> {
+        self.segments.get(segment)?.get(offset)
+    }
+
+    #[inline]
+    fn get_mut(&mut self, segment: usize, offset: usize) -> Option<&mut MemoryCell> {
+        self.segments.get_mut(segment)?.get_mut(offset)
+    }
+
+    fn cells(&self, segment: usize) -> Box<dyn Iterator<Item = (usize, &MemoryCell)> + '_> {
+        match self.segments.get(segment) {
+            Some(segment) => Box::new(segment.iter().enumerate()),
+            None => Box::new(core::iter::empty()),
+        }
+    }
+
+    fn fork(&self) -> Option<Box<dyn MemoryBackend>> {
+        Some(Box::new(self.clone()))
+    }
+}
+
+/// A backend which only stores the written cells, for programs writing at large offsets
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SparseMemoryBackend {
+    // (length, cells) of each segment
+    segments: Vec<(usize, BTreeMap<usize, MemoryCell>)>,
+}
+
+impl SparseMemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl MemoryBackend for SparseMemoryBackend {
+    fn num_segments(&self) -> usize {
+        self.segments.len()
+    }
+
+    fn add_segment(&mut self) {
+        self.segments.push((0, BTreeMap::new()))
+    }
+
+    fn remove_segment(&mut self, segment: usize) -> Vec<(usize, MemoryCell)> {
+        self.segments
+            .remove(segment)
+            .1
+            .into_iter()
+            .filter(|(_, cell)| cell.is_some())
+            .collect()
+    }
+
+    fn segment_len(&self, segment: usize) -> Option<usize> {
+        self.segments.get(segment).map(|(len, _)| *len)
+    }
+
+    fn grow_segment(&mut self, segment: usize, len: usize) -> Result<(), MemoryError> {
+        if let Some((segment_len, _)) = self.segments.get_mut(segment) {
+            *segment_len = len.max(*segment_len);
+        }
+        Ok(())
+    }
+
+    fn get(&self, segment: usize, offset: usize) -> Option<&MemoryCell> {
+        self.segments.get(segment)?.1.get(&offset)
+    }
+
+    fn get_mut(&mut self, segment: usize, offset: usize) -> Option<&mut MemoryCell> {
+        let (len, cells) = self.segments.get_mut(segment)?;
+        (offset < *len).then(|| cells.entry(offset).or_insert(MemoryCell::NONE))
+    }
+
+    fn cells(&self, segment: usize) -> Box<dyn Iterator<Item = (usize, &MemoryCell)> + '_> {
+        match self.segments.get(segment) {
+            Some((_, cells)) => Box::new(cells.iter().map(|(offset, cell)| (*offset, cell))),
+            None => Box::new(core::iter::empty()),
+        }
+    }
+
+    fn fork(&self) -> Option<Box<dyn MemoryBackend>> {
+        Some(Box::new(self.clone()))
+    }
+}
+
+/// A copy-on-write backend, which splits segments into pages shared between its copies.
+/// Cloning it is cheap, as each copy only copies the pages it writes to
+#[derive(Debug, Clone, Default)]
+pub struct CowMemoryBackend {
+    segments: Vec<CowSegment>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct CowSegment {
+    len: usize,
+    // Every page but the last one holds PAGE_SIZE cells
+    pages: Vec<Rc<Vec<MemoryCell>>>,
+}
+
+impl CowMemoryBackend {
+    /// Number of cells in a page
+    pub const PAGE_SIZE: usize = 1 << 10;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl From<VecMemoryBackend> for CowMemoryBackend {
+    fn from(backend: VecMemoryBackend) -> Self {
+        let segments = backend
+            .segments
+            .into_iter()
+            .map(|segment| CowSegment {
+                len: segment.len(),
+                pages: segment
+                    .chunks(Self::PAGE_SIZE)
+                    .map(|page| Rc::new(page.to_vec()))
+                    .collect(),
+            })
+            .collect();
+        Self { segments }
+    }
+}
+
+impl From<CowMemoryBackend> for VecMemoryBackend {
+    fn from(backend: CowMemoryBackend) -> Self {
+        let segments = backend
+            .segments
+            .into_iter()
+            .map(|segment| {
+                let mut cells: Vec<MemoryCell> = segment
+                    .pages
+                    .into_iter()
+                    .flat_map(|page| Rc::try_unwrap(page).unwrap_or_else(|page| page.to_vec()))
+                    .collect();
+                cells.resize(segment.len, MemoryCell::NONE);
+                cells
+            })
+            .collect();
+        Self { segments }
+    }
+}
+
+impl CowMemoryBackend {
+    // Whether some of its pages are still shared with one of its copies
+    fn is_shared(&self) -> bool {
+        self.segments
+            .iter()
+            .flat_map(|segment| &segment.pages)
+            .any(|page| Rc::strong_count(page) > 1)
+    }
+}
+
+impl MemoryBackend for CowMemoryBackend {
+    #[inline]
+    fn num_segments(&self) -> usize {
+        self.segments.len()
+    }
+
+    fn add_segment(&mut self) {
+        self.segments.push(CowSegment::default())
+    }
+
+    fn remove_segment(&mut self, segment: usize) -> Vec<(usize, MemoryCell)> {
+        self.segments
+            .remove(segment)
+            .pages
+            .iter()
+            .flat_map(|page| page.iter())
+            .copied()
+            .enumerate()
+            .filter(|(_, cell)| cell.is_some())
+            .collect()
+    }
+
+    #[inline]
+    fn segment_len(&self, segment: usize) -> Option<usize> {
+        self.segments.get(segment).map(|segment| segment.len)
+    }
+
+    fn grow_segment(&mut self, segment: usize, len: usize) -> Result<(), MemoryError> {
+        let Some(segment) = self.segments.get_mut(segment) else {
+            return Ok(());
+        };
+        while segment.len < len {
+            let page_index = segment.len / Self::PAGE_SIZE;
+            if page_index == segment.pages.len() {
+                segment.pages.push(Rc::new(Vec::new()));
+            }
+            let page_start = page_index * Self::PAGE_SIZE;
+            let page_len = (len - page_start).min(Self::PAGE_SIZE);
+            let page = Rc::make_mut(&mut segment.pages[page_index]);
+            page.try_reserve(page_len.saturating_sub(page.len()))
+                .map_err(|_| MemoryError::VecCapacityExceeded)?;
+            page.resize(page_len, MemoryCell::NONE);
+            segment.len = page_start + page_len;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn get(&self, segment: usize, offset: usize) -> Option<&MemoryCell> {
+        self.segments
+            .get(segment)?
+            .pages
+            .get(offset / Self::PAGE_SIZE)?
+            .get(offset % Self::PAGE_SIZE)
+    }
+
+    #[inline]
+    fn get_mut(&mut self, segment: usize, offset: usize) -> Option<&mut MemoryCell> {
+        let page = self
+            .segments
+            .get_mut(segment)?
+            .pages
+            .get_mut(offset / Self::PAGE_SIZE)?;
+        Rc::make_mut(page).get_mut(offset % Self::PAGE_SIZE)
+    }
+
+    fn cells(&self, segment: usize) -> Box<dyn Iterator<Item = (usize, &MemoryCell)> + '_> {
+        match self.segments.get(segment) {
+            Some(segment) => Box::new(
+                segment
+                    .pages
+                    .iter()
+                    .flat_map(|page| page.iter())
+                    .enumerate(),
+            ),
+            None => Box::new(core::iter::empty()),
+        }
+    }
+
+    fn fork(&self) -> Option<Box<dyn MemoryBackend>> {
+        Some(Box::new(self.clone()))
+    }
+}
+
+/// A backend which keeps its cells in a file, split into pages of which only the most recently
+/// used ones stay in memory, for runs whose memory doesn't fit in RAM.
+///
+/// Pages read through a shared reference stay in memory until the next mutable access to the
+/// backend, which writes the least recently used ones back to the file once there are more than
+/// `max_resident_pages` of them.
+///
+/// # Errors
+/// If its file can't be read or written, the backend keeps the first I/O error and returns it from
+/// [`check_storage`](MemoryBackend::check_storage). The cells of the pages which couldn't be read
+/// are treated as missing, and the pages which couldn't be written stay in memory
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct PagedMemoryBackend {
+    file: std::fs::File,
+    segments: Vec<PagedSegment>,
+    max_resident_pages: usize,
+    resident_pages: Cell<usize>,
+    // Incremented on every page access, to find the least recently used pages
+    clock: Cell<u64>,
+    // Slots of the file which held the pages of removed segments, reused by the new pages
+    free_slots: Vec<u64>,
+    next_slot: u64,
+    // The first error returned by the file
+    io_error: OnceCell<std::io::Error>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+struct PagedSegment {
+    len: usize,
+    pages: Vec<Page>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+struct Page {
+    // Slot of the file holding the page, None if it was never written back to it
+    slot: Option<u64>,
+    // The cells of the page, if it is in memory. Always holds PAGE_SIZE cells
+    cells: OnceCell<Vec<MemoryCell>>,
+    // Whether the cells in memory differ from the ones in the file
+    dirty: bool,
+    last_used: Cell<u64>,
+}
+
+#[cfg(feature = "std")]
+impl PagedMemoryBackend {
+    /// Number of cells in a page
+    pub const PAGE_SIZE: usize = 1 << 12;
+
+    const PAGE_BYTES: u64 = (Self::PAGE_SIZE * MemoryCell::SIZE) as u64;
+
+    /// Creates an empty backend which stores its pages in `file`, overwriting its contents, and
+    /// keeps at most `max_resident_pages` of them in memory between accesses
+    pub fn new(file: std::fs::File, max_resident_pages: usize) -> Self {
+        Self {
+            file,
+            segments: Vec::new(),
+            max_resident_pages: max_resident_pages.max(1),
+            resident_pages: Cell::new(0),
+            clock: Cell::new(0),
+            free_slots: Vec::new(),
+            next_slot: 0,
+            io_error: OnceCell::new(),
+        }
+    }
+
+    /// Returns the number of pages currently held in memory
+    pub fn resident_pages(&self) -> usize {
+        self.resident_pages.get()
+    }
+
+    fn touch(&self, page: &Page) {
+        self.clock.set(self.clock.get() + 1);
+        page.last_used.set(self.clock.get());
+    }
+
+    // Keeps the error, unless the file already returned one
+    fn record_error<T>(&self, result: std::io::Result<T>) -> Option<T> {
+        result
+            .map_err(|error| self.io_error.get_or_init(|| error))
+            .ok()
+    }
+
+    // Returns the cells of a page, reading them from the file if needed.
+    // Returns None if the page was never written to or couldn't be read
+    fn load<'a>(&self, page: &'a Page) -> Option<&'a Vec<MemoryCell>> {
+        self.touch(page);
+        if let Some(cells) = page.cells.get() {
+            return Some(cells);
+        }
+        let cells = self.record_error(read_page(&self.file, page.slot?))?;
+        self.resident_pages.set(self.resident_pages.get() + 1);
+        Some(page.cells.get_or_init(|| cells))
+    }
+
+    // Writes the least recently used pages back to the file, so that only half of
+    // `max_resident_pages` remain in memory, if there are more than `max_resident_pages` of them
+    fn evict(&mut self) {
+        if self.resident_pages.get() <= self.max_resident_pages {
+            return;
+        }
+        let mut resident: Vec<(u64, usize, usize)> = self
+            .segments
+            .iter()
+            .enumerate()
+            .flat_map(|(segment, PagedSegment { pages, .. })| {
+                pages
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, page)| page.cells.get().is_some())
+                    .map(move |(index, page)| (page.last_used.get(), segment, index))
+            })
+            .collect();
+        resident.sort_unstable();
+        let evicted = resident.len().saturating_sub(self.max_resident_pages / 2);
+        let Self {
+            file,
+            segments,
+            resident_pages,
+            free_slots,
+            next_slot,
+            io_error,
+            ..
+        } = self;
+        for (_, segment, index) in resident.into_iter().take(evicted) {
+            let page = &mut segments[segment].pages[index];
+            let Some(cells) = page.cells.get() else {
+                continue;
+            };
+            if page.dirty {
+                let slot = *page.slot.get_or_insert_with(|| {
+                    free_slots.pop().unwrap_or_else(|| {
+                        *next_slot += 1;
+                        *next_slot - 1
+                    })
+                });
+                // Pages which can't be written back stay in memory, so that no cell is lost
+                if let Err(error) = write_page(file, slot, cells) {
+                    io_error.get_or_init(|| error);
+                    continue;
+                }
+                page.dirty = false;
+            }
+            page.cells.take();
+            resident_pages.set(resident_pages.get() - 1);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn read_page(mut file: &std::fs::File, slot: u64) -> std::io::Result<Vec<MemoryCell>> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut bytes = vec![0; PagedMemoryBackend::PAGE_BYTES as usize];
+    file.seek(SeekFrom::Start(slot * PagedMemoryBackend::PAGE_BYTES))?;
+    file.read_exact(&mut bytes)?;
+    Ok(bytes
+        .chunks_exact(MemoryCell::SIZE)
+        .map(MemoryCell::from_le_bytes)
+        .collect())
+}
+
+#[cfg(feature = "std")]
+fn write_page(mut file: &std::fs::File, slot: u64, cells: &[MemoryCell]) -> std::io::Result<()> {
+    use std::io::{Seek, SeekFrom, Write};
+
+    let bytes: Vec<u8> = cells.iter().flat_map(|cell| cell.to_le_bytes()).collect();
+    file.seek(SeekFrom::Start(slot * PagedMemoryBackend::PAGE_BYTES))?;
+    file.write_all(&bytes)
+}
+
+#[cfg(feature = "std")]
+impl MemoryBackend for PagedMemoryBackend {
+    fn num_segments(&self) -> usize {
+        self.segments.len()
+    }
+
+    fn add_segment(&mut self) {
+        self.segments.push(PagedSegment::default())
+    }
+
+    fn remove_segment(&mut self, segment: usize) -> Vec<(usize, MemoryCell)> {
+        let PagedSegment { len, pages } = self.segments.remove(segment);
+        let mut cells = Vec::new();
+        for (index, page) in pages.into_iter().enumerate() {
+            let page_cells = match (page.cells.into_inner(), page.slot) {
+                (Some(page_cells), _) => {
+                    self.resident_pages.set(self.resident_pages.get() - 1);
+                    page_cells
+                }
+                (None, Some(slot)) => match self.record_error(read_page(&self.file, slot)) {
+                    Some(page_cells) => page_cells,
+                    None => continue,
+                },
+                (None, None) => continue,
+            };
+            self.free_slots.extend(page.slot);
+            cells.extend(
+                page_cells
+                    .into_iter()
+                    .enumerate()
+                    .map(|(offset, cell)| (index * Self::PAGE_SIZE + offset, cell))
+                    .filter(|(offset, cell)| *offset < len && cell.is_some()),
+            );
+        }
+        cells
+    }
+
+    fn segment_len(&self, segment: usize) -> Option<usize> {
+        self.segments.get(segment).map(|segment| segment.len)
+    }
+
+    fn grow_segment(&mut self, segment: usize, len: usize) -> Result<(), MemoryError> {
+        let Some(segment) = self.segments.get_mut(segment) else {
+            return Ok(());
+        };
+        if segment.len < len {
+            let pages = len.div_ceil(Self::PAGE_SIZE);
+            segment
+                .pages
+                .try_reserve(pages.saturating_sub(segment.pages.len()))
+                .map_err(|_| MemoryError::VecCapacityExceeded)?;
+            segment.pages.resize_with(pages, Page::default);
+            segment.len = len;
+        }
+        Ok(())
+    }
+
+    fn get(&self, segment: usize, offset: usize) -> Option<&MemoryCell> {
+        let segment = self.segments.get(segment)?;
+        if offset >= segment.len {
+            return None;
+        }
+        self.load(segment.pages.get(offset / Self::PAGE_SIZE)?)?
+            .get(offset % Self::PAGE_SIZE)
+    }
+
+    fn get_mut(&mut self, segment: usize, offset: usize) -> Option<&mut MemoryCell> {
+        if offset >= self.segments.get(segment)?.len {
+            return None;
+        }
+        self.evict();
+        let page = self.segments[segment]
+            .pages
+            .get_mut(offset / Self::PAGE_SIZE)?;
+        if page.cells.get().is_none() {
+            let cells = match page.slot {
+                Some(slot) => match read_page(&self.file, slot) {
+                    Ok(cells) => cells,
+                    Err(error) => {
+                        self.io_error.get_or_init(|| error);
+                        return None;
+                    }
+                },
+                None => vec![MemoryCell::NONE; Self::PAGE_SIZE],
+            };
+            page.cells.get_or_init(|| cells);
+            self.resident_pages.set(self.resident_pages.get() + 1);
+        }
+        self.clock.set(self.clock.get() + 1);
+        page.last_used.set(self.clock.get());
+        page.dirty = true;
+        page.cells.get_mut()?.get_mut(offset % Self::PAGE_SIZE)
+    }
+
+    fn cells(&self, segment: usize) -> Box<dyn Iterator<Item = (usize, &MemoryCell)> + '_> {
+        let Some(segment) = self.segments.get(segment) else {
+            return Box::new(core::iter::empty());
+        };
+        Box::new(
+            segment
+                .pages
+                .iter()
+                .enumerate()
+                .filter_map(move |(index, page)| Some((index, self.load(page)?)))
+                .flat_map(|(index, cells)| {
+                    cells
+                        .iter()
+                        .enumerate()
+                        .map(move |(offset, cell)| (index * Self::PAGE_SIZE + offset, cell))
+                })
+                .take_while(move |(offset, _)| *offset < segment.len),
+        )
+    }
+
+    fn check_storage(&self) -> Result<(), MemoryError> {
+        match self.io_error.get() {
+            Some(error) => Err(MemoryError::MemoryBackendStorage(
+                error.to_string().into_boxed_str(),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The backend held by `Memory`, which dispatches statically to the default backend so that it
+/// keeps its performance
+pub(crate) enum MemoryData {
+    Vec(VecMemoryBackend),
+    // The default backend is turned into a copy-on-write one once the memory is forked
+    Cow(CowMemoryBackend),
+    Custom(Box<dyn MemoryBackend>),
+}
+
+macro_rules! dispatch {
+    ($self:ident, $backend:ident => $expr:expr) => {
+        match $self {
+            MemoryData::Vec($backend) => $expr,
+            MemoryData::Cow($backend) => $expr,
+            MemoryData::Custom($backend) => $expr,
+        }
+    };
+}
+
+impl MemoryBackend for MemoryData {
+    #[inline]
+    fn num_segments(&self) -> usize {
+        dispatch!(self, backend => backend.num_segments())
+    }
+
+    #[inline]
+    fn add_segment(&mut self) {
+        dispatch!(self, backend => backend.add_segment())
+    }
+
+    fn remove_segment(&mut self, segment: usize) -> Vec<(usize, MemoryCell)> {
+        dispatch!(self, backend => backend.remove_segment(segment))
+    }
+
+    #[inline]
+    fn segment_len(&self, segment: usize) -> Option<usize> {
+        dispatch!(self, backend => backend.segment_len(segment))
+    }
+
+    #[inline]
+    fn grow_segment(&mut self, segment: usize, len: usize) -> Result<(), MemoryError> {
+        dispatch!(self, backend => backend.grow_segment(segment, len))
+    }
+
+    #[inline]
+    fn get(&self, segment: usize, offset: usize) -> Option<&MemoryCell> {
+        dispatch!(self, backend => backend.get(segment, offset))
+    }
+
+    #[inline]
+    fn get_mut(&mut self, segment: usize, offset: usize) -> Option<&mut MemoryCell> {
+        dispatch!(self, backend => backend.get_mut(segment, offset))
+    }
+
+    fn cells(&self, segment: usize) -> Box<dyn Iterator<Item = (usize, &MemoryCell)> + '_> {
+        dispatch!(self, backend => backend.cells(segment))
+    }
+
+    #[inline]
+    fn check_storage(&self) -> Result<(), MemoryError> {
+        match self {
+            MemoryData::Custom(backend) => backend.check_storage(),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for MemoryData {
+    fn default() -> Self {
+        Self::Vec(VecMemoryBackend::default())
+    }
+}
+
+impl From<Box<dyn MemoryBackend>> for MemoryData {
+    fn from(backend: Box<dyn MemoryBackend>) -> Self {
+        Self::Custom(backend)
+    }
+}
+
+impl From<Vec<Vec<MemoryCell>>> for MemoryData {
+    fn from(segments: Vec<Vec<MemoryCell>>) -> Self {
+        Self::Vec(VecMemoryBackend { segments })
+    }
+}
+
+impl MemoryData {
+    /// Returns a copy of the backend. The default backend is turned into a copy-on-write one
+    /// beforehand, so that both copies share their pages
+    pub(crate) fn cow_fork(&mut self) -> Result<MemoryData, MemoryError> {
+        if let MemoryData::Vec(backend) = self {
+            *self = MemoryData::Cow(core::mem::take(backend).into());
+        }
+        match self {
+            MemoryData::Cow(backend) => Ok(MemoryData::Cow(backend.clone())),
+            _ => dispatch!(self, backend => backend.fork())
+                .map(MemoryData::Custom)
+                .ok_or(MemoryError::UnforkableMemoryBackend),
+        }
+    }
+
+    /// Turns a copy-on-write backend back into the default one, once none of its pages are shared
+    /// with a copy anymore. Returns false, leaving the backend as it is, if some of them still are
+    pub(crate) fn unshare(&mut self) -> bool {
+        match self {
+            MemoryData::Cow(backend) if backend.is_shared() => false,
+            MemoryData::Cow(backend) => {
+                *self = MemoryData::Vec(core::mem::take(backend).into());
+                true
+            }
+            _ => true,
+        }
+    }
+
+    /// Returns the length of every segment
+    pub(crate) fn segment_lens(&self) -> Vec<usize> {
+        (0..self.num_segments())
+            .map(|segment| self.segment_len(segment).unwrap_or_default())
+            .collect()
+    }
+
+    /// Returns true if there are no segments
+    pub(crate) fn is_empty(&self) -> bool {
+        self.num_segments() == 0
+    }
+
+    /// Iterates over the values stored in every segment, along with their segment index & offset
+    pub(crate) fn iter_values(&self) -> impl Iterator<Item = (usize, usize, &MemoryCell)> + '_ {
+        (0..self.num_segments()).flat_map(move |segment| {
+            self.cells(segment)
+                .filter(|(_, cell)| cell.is_some())
+                .map(move |(offset, cell)| (segment, offset, cell))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        relocatable, types::relocatable::Relocatable, utils::test_utils::*,
+        vm::vm_memory::memory::Memory,
+    };
+    use assert_matches::assert_matches;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::*;
+
+    fn sparse_memory() -> Memory {
+        Memory::with_backends(
+            Box::new(SparseMemoryBackend::new()),
+            Box::new(SparseMemoryBackend::new()),
+        )
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn sparse_backend_cells() {
+        let mut backend = SparseMemoryBackend::new();
+        backend.add_segment();
+        assert_eq!(backend.segment_len(0), Some(0));
+        assert_eq!(backend.segment_len(1), None);
+        backend.grow_segment(0, 1 << 40).unwrap();
+        assert_eq!(backend.segment_len(0), Some(1 << 40));
+        assert_eq!(backend.get(0, 5), None);
+        *backend.get_mut(0, 5).unwrap() = MemoryCell::new(mayberelocatable!(3));
+        assert!(backend.get_mut(0, 1 << 40).is_none());
+        assert_eq!(
+            backend.cells(0).collect::<Vec<_>>(),
+            vec![(5, &MemoryCell::new(mayberelocatable!(3)))]
+        );
+        assert_eq!(
+            backend.remove_segment(0),
+            vec![(5, MemoryCell::new(mayberelocatable!(3)))]
+        );
+        assert_eq!(backend.num_segments(), 0);
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+    fn paged_backend_writes_pages_back_to_file() {
+        const PAGE_SIZE: usize = PagedMemoryBackend::PAGE_SIZE;
+        let mut backend = PagedMemoryBackend::new(tempfile::tempfile().unwrap(), 2);
+        backend.add_segment();
+        backend.grow_segment(0, PAGE_SIZE * 4 - 1).unwrap();
+        assert_eq!(backend.segment_len(0), Some(PAGE_SIZE * 4 - 1));
+        assert_eq!(backend.get(0, 1), None);
+        assert_eq!(backend.resident_pages(), 0);
+        for page in 0..4 {
+            *backend.get_mut(0, page * PAGE_SIZE + 1).unwrap() =
+                MemoryCell::new(mayberelocatable!(page));
+        }
+        // The first two pages were written back to the file before loading the last one
+        assert_eq!(backend.resident_pages(), 2);
+        assert!(backend.get_mut(0, PAGE_SIZE * 4 - 1).is_none());
+        for page in 0..4 {
+            assert_eq!(
+                backend.get(0, page * PAGE_SIZE + 1),
+                Some(&MemoryCell::new(mayberelocatable!(page)))
+            );
+        }
+        assert_eq!(backend.get(0, 0), Some(&MemoryCell::NONE));
+        assert_eq!(backend.resident_pages(), 4);
+        assert_eq!(
+            backend
+                .cells(0)
+                .filter(|(_, cell)| cell.is_some())
+                .map(|(offset, _)| offset)
+                .collect::<Vec<_>>(),
+            vec![1, PAGE_SIZE + 1, PAGE_SIZE * 2 + 1, PAGE_SIZE * 3 + 1]
+        );
+        assert_eq!(backend.cells(0).count(), PAGE_SIZE * 4 - 1);
+
+        backend.add_segment();
+        backend.grow_segment(1, 1).unwrap();
+        *backend.get_mut(1, 0).unwrap() = MemoryCell::new(mayberelocatable!(1, 0));
+        assert_eq!(backend.resident_pages(), 2);
+        assert_eq!(
+            backend.remove_segment(0),
+            (0..4)
+                .map(|page| (
+                    page * PAGE_SIZE + 1,
+                    MemoryCell::new(mayberelocatable!(page))
+                ))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(backend.num_segments(), 1);
+        assert_eq!(
+            backend.get(0, 0),
+            Some(&MemoryCell::new(mayberelocatable!(1, 0)))
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+    fn paged_memory_run_matches_default_memory() {
+        let mut memory = Memory::with_backends(
+            Box::new(PagedMemoryBackend::new(tempfile::tempfile().unwrap(), 1)),
+            Box::new(PagedMemoryBackend::new(tempfile::tempfile().unwrap(), 1)),
+        );
+        memory.data.add_segment();
+        memory.temp_data.add_segment();
+        let far = PagedMemoryBackend::PAGE_SIZE * 3;
+        memory
+            .insert(relocatable!(0, far), mayberelocatable!(-1, 0))
+            .unwrap();
+        memory
+            .insert(relocatable!(-1, 0), mayberelocatable!(5))
+            .unwrap();
+        memory
+            .insert(relocatable!(0, 0), mayberelocatable!(6))
+            .unwrap();
+        memory
+            .add_relocation_rule(relocatable!(-1, 0), relocatable!(0, far + 1))
+            .unwrap();
+        memory.relocate_memory().unwrap();
+        check_memory![
+            memory,
+            ((0, 0), 6),
+            ((0, far), (0, far + 1)),
+            ((0, far + 1), 5)
+        ];
+        assert_eq!(memory.allocated_cells(), far + 2);
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+    fn paged_memory_reports_storage_errors() {
+        const PAGE_SIZE: usize = PagedMemoryBackend::PAGE_SIZE;
+        // Pages can't be written back to a file opened as read-only
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let mut memory = Memory::with_backends(
+            Box::new(PagedMemoryBackend::new(
+                std::fs::File::open(file.path()).unwrap(),
+                1,
+            )),
+            Box::new(VecMemoryBackend::new()),
+        );
+        memory.data.add_segment();
+        for page in 0..3 {
+            memory
+                .insert(relocatable!(0, page * PAGE_SIZE), mayberelocatable!(page))
+                .unwrap();
+        }
+        assert_matches!(
+            memory.insert(relocatable!(0, 1), mayberelocatable!(3)),
+            Err(MemoryError::MemoryBackendStorage(_))
+        );
+        assert_matches!(
+            memory.check_storage(),
+            Err(MemoryError::MemoryBackendStorage(_))
+        );
+        // The pages which couldn't be written back stayed in memory
+        check_memory![
+            memory,
+            ((0, 0), 0),
+            ((0, PAGE_SIZE), 1),
+            ((0, PAGE_SIZE * 2), 2)
+        ];
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn vec_backend_remove_segment_skips_gaps() {
+        let mut backend = VecMemoryBackend::new();
+        backend.add_segment();
+        backend.grow_segment(0, 3).unwrap();
+        *backend.get_mut(0, 2).unwrap() = MemoryCell::new(mayberelocatable!(1, 0));
+        assert_eq!(backend.get(0, 0), Some(&MemoryCell::NONE));
+        assert_eq!(
+            backend.remove_segment(0),
+            vec![(2, MemoryCell::new(mayberelocatable!(1, 0)))]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn cow_backend_copies_pages_on_write() {
+        let mut backend = VecMemoryBackend::new();
+        backend.add_segment();
+        backend.grow_segment(0, 2).unwrap();
+        *backend.get_mut(0, 1).unwrap() = MemoryCell::new(mayberelocatable!(1));
+        let mut backend = CowMemoryBackend::from(backend);
+        backend
+            .grow_segment(0, CowMemoryBackend::PAGE_SIZE + 1)
+            .unwrap();
+        assert_eq!(
+            backend.segment_len(0),
+            Some(CowMemoryBackend::PAGE_SIZE + 1)
+        );
+        assert_eq!(backend.segments[0].pages.len(), 2);
+
+        let mut fork = backend.clone();
+        *fork.get_mut(0, CowMemoryBackend::PAGE_SIZE).unwrap() =
+            MemoryCell::new(mayberelocatable!(2));
+        assert!(Rc::ptr_eq(
+            &backend.segments[0].pages[0],
+            &fork.segments[0].pages[0]
+        ));
+        assert!(!Rc::ptr_eq(
+            &backend.segments[0].pages[1],
+            &fork.segments[0].pages[1]
+        ));
+        assert_eq!(
+            backend.get(0, CowMemoryBackend::PAGE_SIZE),
+            Some(&MemoryCell::NONE)
+        );
+        assert_eq!(fork.get(0, 1), Some(&MemoryCell::new(mayberelocatable!(1))));
+        assert_eq!(
+            fork.remove_segment(0),
+            vec![
+                (1, MemoryCell::new(mayberelocatable!(1))),
+                (
+                    CowMemoryBackend::PAGE_SIZE,
+                    MemoryCell::new(mayberelocatable!(2))
+                )
+            ]
+        );
+        assert_eq!(backend.num_segments(), 1);
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn memory_fork_is_independent() {
+        for mut memory in [Memory::new(), sparse_memory()] {
+            memory.data.add_segment();
+            memory.temp_data.add_segment();
+            memory
+                .insert(relocatable!(0, 0), mayberelocatable!(1))
+                .unwrap();
+            let mut fork = memory.fork().unwrap();
+            memory
+                .insert(relocatable!(0, 1), mayberelocatable!(2))
+                .unwrap();
+            fork.insert(relocatable!(0, 1), mayberelocatable!(3))
+                .unwrap();
+            fork.insert(relocatable!(-1, 0), mayberelocatable!(4))
+                .unwrap();
+            assert_eq!(
+                memory.get(&relocatable!(0, 0)),
+                fork.get(&relocatable!(0, 0))
+            );
+            assert_eq!(
+                memory.get_integer(relocatable!(0, 1)).unwrap().as_ref(),
+                &2.into()
+            );
+            assert_eq!(
+                fork.get_integer(relocatable!(0, 1)).unwrap().as_ref(),
+                &3.into()
+            );
+            assert_eq!(memory.get(&relocatable!(-1, 0)), None);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn with_backends_counts_allocated_cells() {
+        let mut data = SparseMemoryBackend::new();
+        data.add_segment();
+        data.grow_segment(0, 4).unwrap();
+        let mut temp_data = SparseMemoryBackend::new();
+        temp_data.add_segment();
+        temp_data.grow_segment(0, 3).unwrap();
+        *temp_data.get_mut(0, 2).unwrap() = MemoryCell::new(mayberelocatable!(1));
+        let mut memory = Memory::with_backends(Box::new(data), Box::new(temp_data));
+        assert_eq!(memory.allocated_cells(), 7);
+        memory
+            .add_relocation_rule(relocatable!(-1, 0), relocatable!(0, 4))
+            .unwrap();
+        memory.relocate_memory().unwrap();
+        check_memory![memory, ((0, 6), 1)];
+        assert_eq!(memory.allocated_cells(), 7);
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn sparse_memory_insert_at_large_offset() {
+        let mut memory = sparse_memory();
+        memory.data.add_segment();
+        let key = relocatable!(0, 1 << 50);
+        memory.insert(key, mayberelocatable!(7)).unwrap();
+        assert_eq!(memory.get_integer(key).unwrap().as_ref(), &7.into());
+        assert_eq!(memory.get(&relocatable!(0, 3)), None);
+        assert_matches!(
+            memory.insert(key, mayberelocatable!(8)),
+            Err(MemoryError::InconsistentMemory(_))
+        );
+        memory.mark_as_accessed(key);
+        assert_eq!(
+            memory.get_amount_of_accessed_addresses_for_segment(0),
+            Some(1)
+        );
+        assert_matches!(
+            memory.insert(relocatable!(1, 0), mayberelocatable!(7)),
+            Err(MemoryError::UnallocatedSegment(bx)) if *bx == (1, 1)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn sparse_memory_relocate_temporary_segment() {
+        let mut memory = sparse_memory();
+        memory.data.add_segment();
+        memory.temp_data.add_segment();
+        memory
+            .insert(relocatable!(0, 0), mayberelocatable!(-1, 1))
+            .unwrap();
+        memory
+            .insert(relocatable!(-1, 1), mayberelocatable!(5))
+            .unwrap();
+        memory
+            .add_relocation_rule(relocatable!(-1, 0), relocatable!(0, 10))
+            .unwrap();
+        memory.relocate_memory().unwrap();
+        assert!(memory.temp_data.is_empty());
+        check_memory![memory, ((0, 0), (0, 11)), ((0, 11), 5)];
+        assert_eq!(memory.get(&relocatable!(0, 10)), None);
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn sparse_memory_mem_eq_and_memcmp() {
+        let mut memory = sparse_memory();
+        memory.data.add_segment();
+        memory.data.add_segment();
+        for (segment, values) in [(0, [1, 2, 3]), (1, [1, 2, 4])] {
+            for (offset, value) in values.into_iter().enumerate() {
+                memory
+                    .insert(
+                        Relocatable::from((segment, offset)),
+                        mayberelocatable!(value),
+                    )
+                    .unwrap();
+            }
+        }
+        assert!(memory.mem_eq(relocatable!(0, 0), relocatable!(1, 0), 2));
+        assert!(!memory.mem_eq(relocatable!(0, 0), relocatable!(1, 0), 3));
+        assert_eq!(
+            memory.memcmp(relocatable!(0, 0), relocatable!(1, 0), 2),
+            (core::cmp::Ordering::Equal, 2)
+        );
+        assert_matches!(
+            memory.memcmp(relocatable!(0, 0), relocatable!(1, 0), 3),
+            (ord, 2) if ord != core::cmp::Ordering::Equal
+        );
+    }
+
+    #[test]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    fn sparse_memory_display() {
+        let mut memory = sparse_memory();
+        memory.data.add_segment();
+        memory.temp_data.add_segment();
+        memory
+            .insert(relocatable!(0, 4), mayberelocatable!(2))
+            .unwrap();
+        memory
+            .insert(relocatable!(-1, 1), mayberelocatable!(0, 4))
+            .unwrap();
+        assert_eq!(memory.to_string(), "(-1,1) : 0:4\n(0,4) : 2\n");
+    }
+}
@@ -18,6 +18,7 @@ use crate::{
 };
 
 use super::memory::MemoryCell;
+use super::memory_backend::MemoryBackend;
 
 pub struct MemorySegmentManager {
     pub segment_sizes: HashMap<usize, usize>,
@@ -28,37 +29,37 @@ pub struct MemorySegmentManager {
     pub public_memory_offsets: HashMap<usize, Vec<(usize, usize)>>,
     // Segment index of the zero segment index, a memory segment filled with zeroes, used exclusively by builtin runners
     // This segment will never have index 0 so we use 0 to represent uninitialized value
-    zero_segment_index: usize,
+    pub(crate) zero_segment_index: usize,
     // Segment size of the zero segment index
-    zero_segment_size: usize,
+    pub(crate) zero_segment_size: usize,
 }
 
 impl MemorySegmentManager {
     /// Number of segments in the real memory
     pub fn num_segments(&self) -> usize {
-        self.memory.data.len()
+        self.memory.data.num_segments()
     }
 
     /// Number of segments in the temporary memory
     pub fn num_temp_segments(&self) -> usize {
-        self.memory.temp_data.len()
+        self.memory.temp_data.num_segments()
     }
 
     ///Adds a new segment and returns its starting location as a Relocatable value. Its segment index will always be positive.
     pub fn add(&mut self) -> Relocatable {
-        self.memory.data.push(Vec::new());
+        self.memory.data.add_segment();
         Relocatable {
-            segment_index: (self.memory.data.len() - 1) as isize,
+            segment_index: (self.memory.data.num_segments() - 1) as isize,
             offset: 0,
         }
     }
 
     /// Adds a new temporary segment and returns its starting location as a Relocatable value. Its segment index will always be negative.
     pub fn add_temporary_segment(&mut self) -> Relocatable {
-        self.memory.temp_data.push(Vec::new());
+        self.memory.temp_data.add_segment();
         Relocatable {
             // We dont substract 1 as we need to take into account the index shift (temporary memory begins from -1 instead of 0)
-            segment_index: -((self.memory.temp_data.len()) as isize),
+            segment_index: -((self.memory.temp_data.num_segments()) as isize),
             offset: 0,
         }
     }
@@ -88,10 +89,40 @@ impl MemorySegmentManager {
         }
     }
 
+    /// Creates a segment manager whose memory stores the real & temporary segments in the given
+    /// backends. See [`Memory::with_backends`]
+    pub fn with_memory_backends(
+        data: Box<dyn MemoryBackend>,
+        temp_data: Box<dyn MemoryBackend>,
+    ) -> MemorySegmentManager {
+        MemorySegmentManager {
+            memory: Memory::with_backends(data, temp_data),
+            ..MemorySegmentManager::new()
+        }
+    }
+
+    /// Returns a copy of the segments, see [`Memory::fork`]
+    pub(crate) fn fork(&mut self) -> Result<MemorySegmentManager, MemoryError> {
+        Ok(MemorySegmentManager {
+            segment_sizes: self.segment_sizes.clone(),
+            segment_used_sizes: self.segment_used_sizes.clone(),
+            memory: self.memory.fork()?,
+            public_memory_offsets: self.public_memory_offsets.clone(),
+            zero_segment_index: self.zero_segment_index,
+            zero_segment_size: self.zero_segment_size,
+        })
+    }
+
     /// Calculates the size of each memory segment.
     pub fn compute_effective_sizes(&mut self) -> &Vec<usize> {
+        let data = &self.memory.data;
         self.segment_used_sizes
-            .get_or_insert_with(|| self.memory.data.iter().map(Vec::len).collect())
+            .get_or_insert_with(|| data.segment_lens())
+    }
+
+    /// Returns the current sizes of the real segments, without storing them as their used sizes
+    pub(crate) fn effective_sizes(&self) -> Vec<usize> {
+        self.memory.data.segment_lens()
     }
 
     ///Returns the number of used segments if they have been computed.
@@ -204,7 +235,32 @@ impl MemorySegmentManager {
         builtin_count: usize,
         has_output_builtin: bool,
     ) -> Result<usize, MemoryError> {
-        let data = &self.memory.data;
+        self.memory_holes(builtin_count, has_output_builtin, |i| {
+            self.get_segment_size(i)
+        })
+    }
+
+    /// Same as `get_memory_holes`, but with the given used sizes instead of the computed ones
+    pub(crate) fn get_memory_holes_for_used_sizes(
+        &self,
+        builtin_count: usize,
+        has_output_builtin: bool,
+        segment_used_sizes: &[usize],
+    ) -> Result<usize, MemoryError> {
+        self.memory_holes(builtin_count, has_output_builtin, |i| {
+            self.segment_sizes
+                .get(&i)
+                .or_else(|| segment_used_sizes.get(i))
+                .copied()
+        })
+    }
+
+    fn memory_holes(
+        &self,
+        builtin_count: usize,
+        has_output_builtin: bool,
+        segment_size: impl Fn(usize) -> Option<usize>,
+    ) -> Result<usize, MemoryError> {
         let mut memory_holes = 0;
         let builtin_segments_start = if has_output_builtin {
             2 // program segment + execution segment + output segment
@@ -214,7 +270,7 @@ impl MemorySegmentManager {
         let builtin_segments_end = builtin_segments_start + builtin_count;
         // Count the memory holes for each segment by substracting the amount of accessed_addresses from the segment's size
         // Segments without accesses addresses are not accounted for when counting memory holes
-        for i in 0..data.len() {
+        for i in 0..self.memory.data.num_segments() {
             // Instead of marking all of the builtin segment's address as accessed, we just skip them when counting memory holes
             // Output builtin is extempt from this behaviour
             if i > builtin_segments_start && i <= builtin_segments_end {
@@ -230,9 +286,7 @@ impl MemorySegmentManager {
                         _ => continue,
                     }
                 };
-            let segment_size = self
-                .get_segment_size(i)
-                .ok_or(MemoryError::MissingSegmentUsedSizes)?;
+            let segment_size = segment_size(i).ok_or(MemoryError::MissingSegmentUsedSizes)?;
             if accessed_amount > segment_size {
                 return Err(MemoryError::SegmentHasMoreAccessedAddressesThanSize(
                     Box::new((i, accessed_amount, segment_size)),
@@ -294,7 +348,19 @@ impl MemorySegmentManager {
         for _ in 0..self.zero_segment_size.saturating_sub(size) {
             // As zero_segment_index is only accessible to the segment manager
             // we can asume that it is always valid and index direcly into it
-            self.memory.data[self.zero_segment_index].push(MemoryCell::new(Felt252::ZERO.into()))
+            let data = &mut self.memory.data;
+            let offset = data
+                .segment_len(self.zero_segment_index)
+                .unwrap_or_default();
+            if data
+                .grow_segment(self.zero_segment_index, offset + 1)
+                .is_ok()
+            {
+                self.memory.allocated_cells += 1;
+                if let Some(cell) = data.get_mut(self.zero_segment_index, offset) {
+                    *cell = MemoryCell::new(Felt252::ZERO.into())
+                }
+            }
         }
         self.zero_segment_size = max(self.zero_segment_size, size);
         self.zero_segment_index
@@ -607,7 +673,12 @@ mod tests {
 
         assert_eq!(exec, Ok(MaybeRelocatable::from((1, 3))));
         assert_eq!(
-            segments.memory.data[1],
+            segments
+                .memory
+                .data
+                .cells(1)
+                .map(|(_, cell)| *cell)
+                .collect::<Vec<_>>(),
             vec![
                 MemoryCell::new(MaybeRelocatable::from((0, 1))),
                 MemoryCell::new(MaybeRelocatable::from((0, 2))),
@@ -1,2 +1,3 @@
 pub mod memory;
+pub mod memory_backend;
 pub mod memory_segments;